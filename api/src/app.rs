@@ -39,7 +39,7 @@ pub async fn serve(state: &AppState) {
 			async {
 				axum::serve(
 					api_listener,
-					crate::routes::api_patr_cloud::setup_routes(&state)
+					crate::routes::api_patr_cloud::setup_routes(state)
 						.await
 						.into_make_service_with_connect_info::<SocketAddr>(),
 				)
@@ -54,7 +54,7 @@ pub async fn serve(state: &AppState) {
 			async {
 				axum::serve(
 					app_listener,
-					crate::routes::app_patr_cloud::setup_routes(&state)
+					crate::routes::app_patr_cloud::setup_routes(state)
 						.await
 						.into_make_service_with_connect_info::<SocketAddr>(),
 				)
@@ -78,7 +78,7 @@ pub async fn serve(state: &AppState) {
 
 		axum::serve(
 			tcp_listener,
-			setup_routes(&state)
+			setup_routes(state)
 				.await
 				.into_make_service_with_connect_info::<SocketAddr>(),
 		)
@@ -27,14 +27,21 @@ pub async fn connect(config: &DatabaseConfig) -> Pool<DatabaseType> {
 	info!("Connecting to database `{}:{}`", config.host, config.port);
 	PoolOptions::<DatabaseType>::new()
 		.max_connections(config.connection_limit)
-		.connect_with(
-			<DatabaseConnection as sqlx::Connection>::Options::new()
-				.username(config.user.as_str())
-				.password(config.password.as_str())
-				.host(config.host.as_str())
-				.port(config.port)
-				.database(config.database.as_str()),
-		)
+		.connect_with(connect_options(config))
 		.await
 		.expect("Failed to connect to database")
 }
+
+/// The options to connect to the database with, based on a config. This is
+/// used by long-lived tasks that need a connection of their own, outside of
+/// the main pool.
+pub fn connect_options(
+	config: &DatabaseConfig,
+) -> <DatabaseConnection as sqlx::Connection>::Options {
+	<DatabaseConnection as sqlx::Connection>::Options::new()
+		.username(config.user.as_str())
+		.password(config.password.as_str())
+		.host(config.host.as_str())
+		.port(config.port)
+		.database(config.database.as_str())
+}
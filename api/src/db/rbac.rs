@@ -43,7 +43,8 @@ pub async fn initialize_rbac_tables(
 			id UUID NOT NULL,
 			name VARCHAR(100) NOT NULL,
 			description VARCHAR(500) NOT NULL,
-			owner_id UUID NOT NULL
+			owner_id UUID NOT NULL,
+			version BIGINT NOT NULL DEFAULT 1
 		);
 		"#
 	)
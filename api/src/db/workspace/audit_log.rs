@@ -12,7 +12,8 @@ pub async fn initialize_workspace_tables(
 		CREATE TYPE AUDIT_LOG_TYPE AS ENUM (
 			'create',
 			'update',
-			'delete'
+			'delete',
+			'exec'
 		);
 		"#
 	)
@@ -18,10 +18,30 @@ pub async fn initialize_container_registry_tables(
 	.execute(&mut *connection)
 	.await?;
 
+	// Blobs are stored once for the entire registry, but a repository can
+	// only use the blobs that were uploaded (or mounted) to it
+	query!(
+		r#"
+		CREATE TABLE container_registry_repository_blob_link(
+			repository_id UUID NOT NULL,
+			blob_digest TEXT NOT NULL,
+			created TIMESTAMPTZ NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	query!(
 		r#"
 		CREATE TABLE container_registry_manifest(
-			manifest_digest TEXT NOT NULL
+			manifest_digest TEXT NOT NULL,
+			media_type TEXT NOT NULL,
+			size BIGINT NOT NULL,
+			created TIMESTAMPTZ NOT NULL,
+			subject_digest TEXT,
+			artifact_type TEXT,
+			annotations TEXT
 		);
 		"#
 	)
@@ -40,6 +60,20 @@ pub async fn initialize_container_registry_tables(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		CREATE TABLE container_registry_index_manifest(
+			index_digest TEXT NOT NULL,
+			manifest_digest TEXT NOT NULL,
+			architecture TEXT NOT NULL,
+			os TEXT NOT NULL,
+			variant TEXT NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	query!(
 		r#"
 		CREATE TABLE container_registry_repository(
@@ -81,6 +115,79 @@ pub async fn initialize_container_registry_tables(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		CREATE TABLE container_registry_cache_manifest(
+			workspace_id UUID NOT NULL,
+			upstream TEXT NOT NULL,
+			image TEXT NOT NULL,
+			reference TEXT NOT NULL,
+			manifest_digest TEXT NOT NULL,
+			last_fetched TIMESTAMPTZ NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE container_registry_cache_blob(
+			workspace_id UUID NOT NULL,
+			upstream TEXT NOT NULL,
+			image TEXT NOT NULL,
+			blob_digest TEXT NOT NULL,
+			created TIMESTAMPTZ NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE container_registry_signing_key(
+			id UUID NOT NULL,
+			workspace_id UUID NOT NULL,
+			name CITEXT NOT NULL,
+			public_key TEXT NOT NULL,
+			created TIMESTAMPTZ NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE container_registry_image_signature(
+			repository_id UUID NOT NULL,
+			manifest_digest TEXT NOT NULL,
+			signature_manifest_digest TEXT NOT NULL,
+			payload_digest TEXT NOT NULL,
+			payload TEXT NOT NULL,
+			signature TEXT NOT NULL,
+			created TIMESTAMPTZ NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE container_registry_repository_policy(
+			repository_id UUID NOT NULL,
+			immutable_tag_patterns TEXT[] NOT NULL,
+			keep_last_tags INTEGER,
+			untagged_manifest_retention_days INTEGER,
+			storage_limit BIGINT
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
 
@@ -100,6 +207,27 @@ pub async fn initialize_container_registry_indices(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE container_registry_repository_blob_link
+		ADD CONSTRAINT container_registry_repository_blob_link_pk
+		PRIMARY KEY(repository_id, blob_digest);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE INDEX
+			container_registry_repository_blob_link_idx_blob_digest
+		ON
+			container_registry_repository_blob_link(blob_digest);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	query!(
 		r#"
 		ALTER TABLE container_registry_manifest
@@ -120,6 +248,16 @@ pub async fn initialize_container_registry_indices(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE container_registry_index_manifest
+		ADD CONSTRAINT container_registry_index_manifest_pk
+		PRIMARY KEY(index_digest, manifest_digest);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	query!(
 		r#"
 		ALTER TABLE container_registry_repository
@@ -165,6 +303,69 @@ pub async fn initialize_container_registry_indices(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE container_registry_cache_manifest
+		ADD CONSTRAINT container_registry_cache_manifest_pk
+		PRIMARY KEY(workspace_id, upstream, image, reference);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE container_registry_cache_blob
+		ADD CONSTRAINT container_registry_cache_blob_pk
+		PRIMARY KEY(workspace_id, upstream, image, blob_digest);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE container_registry_signing_key
+			ADD CONSTRAINT container_registry_signing_key_pk PRIMARY KEY(id),
+			ADD CONSTRAINT container_registry_signing_key_uq_workspace_id_name UNIQUE(
+				workspace_id, name
+			);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE container_registry_image_signature
+		ADD CONSTRAINT container_registry_image_signature_pk
+		PRIMARY KEY(repository_id, signature_manifest_digest, payload_digest);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE INDEX
+			container_registry_image_signature_idx_repository_id_manifest_digest
+		ON
+			container_registry_image_signature(repository_id, manifest_digest);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE container_registry_repository_policy
+		ADD CONSTRAINT container_registry_repository_policy_pk
+		PRIMARY KEY(repository_id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
 
@@ -177,14 +378,27 @@ pub async fn initialize_container_registry_constraints(
 	query!(
 		r#"
 		ALTER TABLE container_registry_repository_blob
-		ADD CONSTRAINT container_registry_repository_blob_chk_size_positive CHECK(
-			size > 0
+		ADD CONSTRAINT container_registry_repository_blob_chk_size_unsigned CHECK(
+			size >= 0
 		);
 		"#
 	)
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE container_registry_repository_blob_link
+			ADD CONSTRAINT container_registry_repository_blob_link_fk_repository_id
+				FOREIGN KEY(repository_id) REFERENCES container_registry_repository(id),
+			ADD CONSTRAINT container_registry_repository_blob_link_fk_blob_digest
+				FOREIGN KEY(blob_digest)
+					REFERENCES container_registry_repository_blob(blob_digest);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	query!(
 		r#"
 		ALTER TABLE container_registry_manifest_blob
@@ -201,9 +415,26 @@ pub async fn initialize_container_registry_constraints(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE container_registry_index_manifest
+			ADD CONSTRAINT container_registry_index_manifest_fk_index_digest
+				FOREIGN KEY(index_digest)
+					REFERENCES container_registry_manifest(manifest_digest),
+			ADD CONSTRAINT container_registry_index_manifest_fk_manifest_digest
+				FOREIGN KEY(manifest_digest)
+					REFERENCES container_registry_manifest(manifest_digest);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	query!(
 		r#"
 		ALTER TABLE container_registry_repository
+			ADD CONSTRAINT container_registry_repository_chk_name_not_reserved CHECK(
+				name NOT LIKE '\_%'
+			),
 			ADD CONSTRAINT container_registry_repository_fk_workspace_id
 				FOREIGN KEY(workspace_id) REFERENCES workspace(id),
 			ADD CONSTRAINT container_registry_repository_fk_id_workspace_id_deleted
@@ -241,5 +472,139 @@ pub async fn initialize_container_registry_constraints(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE container_registry_cache_manifest
+			ADD CONSTRAINT container_registry_cache_manifest_fk_workspace_id
+				FOREIGN KEY(workspace_id) REFERENCES workspace(id),
+			ADD CONSTRAINT container_registry_cache_manifest_fk_manifest_digest
+				FOREIGN KEY(manifest_digest)
+					REFERENCES container_registry_manifest(manifest_digest);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE container_registry_cache_blob
+			ADD CONSTRAINT container_registry_cache_blob_fk_workspace_id
+				FOREIGN KEY(workspace_id) REFERENCES workspace(id),
+			ADD CONSTRAINT container_registry_cache_blob_fk_blob_digest
+				FOREIGN KEY(blob_digest)
+					REFERENCES container_registry_repository_blob(blob_digest);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE container_registry_signing_key
+			ADD CONSTRAINT container_registry_signing_key_fk_workspace_id
+				FOREIGN KEY(workspace_id) REFERENCES workspace(id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE container_registry_image_signature
+			ADD CONSTRAINT container_registry_image_signature_fk_repository_id
+				FOREIGN KEY(repository_id) REFERENCES container_registry_repository(id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE container_registry_repository_policy
+			ADD CONSTRAINT container_registry_repository_policy_chk_keep_last_tags CHECK(
+				keep_last_tags > 0
+			),
+			ADD CONSTRAINT container_registry_repository_policy_chk_retention_days CHECK(
+				untagged_manifest_retention_days > 0
+			),
+			ADD CONSTRAINT container_registry_repository_policy_chk_storage_limit CHECK(
+				storage_limit > 0
+			),
+			ADD CONSTRAINT container_registry_repository_policy_fk_repository_id
+				FOREIGN KEY(repository_id) REFERENCES container_registry_repository(id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	// Blobs are stored once for the entire registry, no matter how many
+	// manifests (or repositories) they are a part of. So a blob shared by
+	// multiple images (like a base layer) is only counted once.
+	query!(
+		r#"
+		CREATE FUNCTION CONTAINER_REGISTRY_REPOSITORY_SIZE(
+			repository_id UUID
+		) RETURNS BIGINT AS $$
+			SELECT
+				COALESCE(SUM(container_registry_repository_blob.size), 0)::BIGINT
+			FROM
+				container_registry_repository_blob
+			WHERE
+				container_registry_repository_blob.blob_digest IN (
+					SELECT
+						container_registry_manifest_blob.blob_digest
+					FROM
+						container_registry_repository_manifest
+					INNER JOIN
+						container_registry_manifest_blob
+					ON
+						container_registry_manifest_blob.manifest_digest =
+							container_registry_repository_manifest.manifest_digest
+					WHERE
+						container_registry_repository_manifest.repository_id =
+							CONTAINER_REGISTRY_REPOSITORY_SIZE.repository_id
+				);
+		$$ LANGUAGE SQL STABLE;
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE FUNCTION CONTAINER_REGISTRY_WORKSPACE_SIZE(
+			workspace_id UUID
+		) RETURNS BIGINT AS $$
+			SELECT
+				COALESCE(SUM(container_registry_repository_blob.size), 0)::BIGINT
+			FROM
+				container_registry_repository_blob
+			WHERE
+				container_registry_repository_blob.blob_digest IN (
+					SELECT
+						container_registry_manifest_blob.blob_digest
+					FROM
+						container_registry_repository
+					INNER JOIN
+						container_registry_repository_manifest
+					ON
+						container_registry_repository_manifest.repository_id =
+							container_registry_repository.id
+					INNER JOIN
+						container_registry_manifest_blob
+					ON
+						container_registry_manifest_blob.manifest_digest =
+							container_registry_repository_manifest.manifest_digest
+					WHERE
+						container_registry_repository.workspace_id =
+							CONTAINER_REGISTRY_WORKSPACE_SIZE.workspace_id AND
+						container_registry_repository.deleted IS NULL
+				);
+		$$ LANGUAGE SQL STABLE;
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
@@ -15,7 +15,8 @@ pub async fn initialize_deployment_tables(
 			'running', /* Deployment is running successfully */
 			'stopped', /* Deployment is stopped by the user */
 			'errored', /* Deployment is stopped because of too many errors */
-			'deleted' /* Deployment is deleted by the user */
+			'deleted', /* Deployment is deleted by the user */
+			'unreachable' /* The runner of the deployment is not connected */
 		);
 		"#
 	)
@@ -44,6 +45,19 @@ pub async fn initialize_deployment_tables(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		CREATE TYPE DEPLOYMENT_ROLLOUT_STRATEGY AS ENUM(
+			'recreate', /* Stop the old instances before starting the new ones */
+			'rolling', /* Replace the instances a few at a time */
+			'blue_green', /* Start all the new instances before removing the old ones */
+			'canary' /* Run a new image on some of the instances until it is promoted */
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	query!(
 		r#"
 		CREATE TABLE deployment(
@@ -67,6 +81,11 @@ pub async fn initialize_deployment_tables(
 			liveness_probe_path VARCHAR(255),
 			liveness_probe_port_type EXPOSED_PORT_TYPE,
 			current_live_digest TEXT,
+			rollout_strategy DEPLOYMENT_ROLLOUT_STRATEGY NOT NULL DEFAULT 'rolling',
+			rollout_max_surge SMALLINT DEFAULT 1,
+			rollout_max_unavailable SMALLINT DEFAULT 0,
+			rollout_canary_percentage SMALLINT,
+			version BIGINT NOT NULL DEFAULT 1,
 			deleted TIMESTAMPTZ
 		);
 		"#
@@ -124,6 +143,102 @@ pub async fn initialize_deployment_tables(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		CREATE TABLE deployment_revision(
+			deployment_id UUID NOT NULL,
+			revision BIGINT NOT NULL,
+			deployment TEXT NOT NULL,
+			running_details TEXT NOT NULL,
+			created TIMESTAMPTZ NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TYPE DEPLOYMENT_ROLLOUT_STATUS AS ENUM(
+			'progressing', /* The revision is being rolled out to all the instances */
+			'awaiting_promotion', /* A canary of the new image is waiting to be promoted */
+			'completed', /* The revision is running on all the instances */
+			'aborted', /* The canary was aborted and the stable image restored */
+			'superseded' /* A newer revision was rolled out before this one finished */
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_rollout(
+			deployment_id UUID NOT NULL,
+			revision BIGINT NOT NULL,
+			status DEPLOYMENT_ROLLOUT_STATUS NOT NULL,
+			image_tag VARCHAR(255) NOT NULL,
+			image_digest TEXT,
+			stable_image_tag VARCHAR(255),
+			stable_image_digest TEXT,
+			created TIMESTAMPTZ NOT NULL,
+			updated TIMESTAMPTZ NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_runtime(
+			deployment_id UUID NOT NULL,
+			desired_replicas INTEGER NOT NULL,
+			ready_replicas INTEGER NOT NULL,
+			updated_replicas INTEGER NOT NULL,
+			last_error TEXT,
+			last_error_occurred TIMESTAMPTZ,
+			reported TIMESTAMPTZ NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TYPE DEPLOYMENT_EVENT_KIND AS ENUM(
+			'image_pulled', /* The image of the deployment was pulled */
+			'image_pull_failed', /* The image of the deployment could not be pulled */
+			'container_started', /* A container was started */
+			'container_stopped', /* A container was stopped */
+			'container_crashed', /* A container exited with an error */
+			'out_of_memory', /* A container was killed for using too much memory */
+			'probe_failed', /* A health check of a container failed */
+			'scaled', /* The number of instances changed */
+			'rollout_completed', /* A revision finished rolling out */
+			'reconcile_failed' /* The runner failed to reconcile the deployment */
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE TABLE deployment_event(
+			id UUID NOT NULL,
+			deployment_id UUID NOT NULL,
+			kind DEPLOYMENT_EVENT_KIND NOT NULL,
+			container TEXT,
+			message TEXT,
+			timestamp TIMESTAMPTZ NOT NULL
+		);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
 
@@ -196,6 +311,58 @@ pub async fn initialize_deployment_indices(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE deployment_revision
+		ADD CONSTRAINT deployment_revision_pk
+		PRIMARY KEY(deployment_id, revision);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_rollout
+		ADD CONSTRAINT deployment_rollout_pk
+		PRIMARY KEY(deployment_id, revision);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_runtime
+		ADD CONSTRAINT deployment_runtime_pk
+		PRIMARY KEY(deployment_id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_event
+		ADD CONSTRAINT deployment_event_pk
+		PRIMARY KEY(id);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		CREATE INDEX
+			deployment_event_idx_deployment_id_timestamp_id
+		ON
+			deployment_event
+		(deployment_id, timestamp DESC, id DESC);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	query!(
 		r#"
 		CREATE INDEX
@@ -322,6 +489,33 @@ pub async fn initialize_deployment_constraints(
 			ADD CONSTRAINT deployment_chk_liveness_probe_port_type_is_http CHECK(
 				liveness_probe_port_type = 'http'
 			),
+			ADD CONSTRAINT deployment_chk_rollout_strategy_is_valid CHECK(
+				(
+					rollout_strategy = 'rolling' AND
+					rollout_max_surge IS NOT NULL AND
+					rollout_max_unavailable IS NOT NULL AND
+					rollout_canary_percentage IS NULL
+				) OR (
+					rollout_strategy = 'canary' AND
+					rollout_max_surge IS NULL AND
+					rollout_max_unavailable IS NULL AND
+					rollout_canary_percentage IS NOT NULL
+				) OR (
+					rollout_strategy IN ('recreate', 'blue_green') AND
+					rollout_max_surge IS NULL AND
+					rollout_max_unavailable IS NULL AND
+					rollout_canary_percentage IS NULL
+				)
+			),
+			ADD CONSTRAINT deployment_chk_rollout_max_surge_max_unavailable_is_valid CHECK(
+				rollout_max_surge >= 0 AND
+				rollout_max_unavailable >= 0 AND
+				rollout_max_surge + rollout_max_unavailable > 0
+			),
+			ADD CONSTRAINT deployment_chk_rollout_canary_percentage_is_valid CHECK(
+				rollout_canary_percentage > 0 AND
+				rollout_canary_percentage < 100
+			),
 			ADD CONSTRAINT deployment_fk_deployment_id_startup_port_startup_port_type
 				FOREIGN KEY(id, startup_probe_port, startup_probe_port_type)
 					REFERENCES deployment_exposed_port(deployment_id, port, port_type)
@@ -400,5 +594,69 @@ pub async fn initialize_deployment_constraints(
 	.execute(&mut *connection)
 	.await?;
 
+	query!(
+		r#"
+		ALTER TABLE deployment_revision
+			ADD CONSTRAINT deployment_revision_fk_deployment_id
+				FOREIGN KEY(deployment_id) REFERENCES deployment(id),
+			ADD CONSTRAINT deployment_revision_chk_revision_positive CHECK(
+				revision > 0
+			);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_rollout
+			ADD CONSTRAINT deployment_rollout_fk_deployment_id_revision
+				FOREIGN KEY(deployment_id, revision)
+					REFERENCES deployment_revision(deployment_id, revision),
+			ADD CONSTRAINT deployment_rollout_chk_stable_image_is_valid CHECK(
+				stable_image_tag IS NOT NULL OR
+				stable_image_digest IS NULL
+			),
+			ADD CONSTRAINT deployment_rollout_chk_awaiting_promotion_has_stable_image CHECK(
+				status != 'awaiting_promotion' OR
+				stable_image_tag IS NOT NULL
+			);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_runtime
+			ADD CONSTRAINT deployment_runtime_fk_deployment_id
+				FOREIGN KEY(deployment_id) REFERENCES deployment(id),
+			ADD CONSTRAINT deployment_runtime_chk_replicas_are_valid CHECK(
+				desired_replicas >= 0 AND
+				ready_replicas >= 0 AND
+				updated_replicas >= 0
+			),
+			ADD CONSTRAINT deployment_runtime_chk_last_error_is_valid CHECK(
+				(last_error IS NULL) = (last_error_occurred IS NULL)
+			);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		ALTER TABLE deployment_event
+			ADD CONSTRAINT deployment_event_fk_deployment_id
+				FOREIGN KEY(deployment_id) REFERENCES deployment(id),
+			ADD CONSTRAINT deployment_event_chk_container_is_valid CHECK(
+				container IS NULL OR
+				LENGTH(TRIM(container)) > 0
+			);
+		"#
+	)
+	.execute(&mut *connection)
+	.await?;
+
 	Ok(())
 }
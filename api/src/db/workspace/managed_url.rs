@@ -33,6 +33,7 @@ pub async fn initialize_managed_url_tables(
 			url TEXT,
 			workspace_id UUID NOT NULL,
 			is_configured BOOLEAN NOT NULL,
+			version BIGINT NOT NULL DEFAULT 1,
 			deleted TIMESTAMPTZ,
 			permanent_redirect BOOLEAN,
 			http_only BOOLEAN,
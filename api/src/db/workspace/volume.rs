@@ -12,6 +12,7 @@ pub async fn initialize_volume_tables(
 			id UUID NOT NULL,
 			name TEXT NOT NULL,
 			volume_size INT NOT NULL,
+			version BIGINT NOT NULL DEFAULT 1,
 			deleted TIMESTAMPTZ
 		);
 		"#
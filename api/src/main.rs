@@ -45,7 +45,7 @@ pub mod prelude {
 			SecretPermission,
 			StaticSitePermission,
 		},
-		utils::{OneOrMore, Paginated, Uuid},
+		utils::{Cursor, CursorPaginated, OneOrMore, Paginated, ResourceVersion, Uuid},
 		ApiEndpoint,
 		AppResponse,
 		ErrorType,
@@ -61,7 +61,7 @@ pub mod prelude {
 			UnprocessedAppRequest,
 		},
 		redis,
-		utils::{constants, RouterExt, TimeoutExt},
+		utils::{constants, ListQueryExt, RouterExt, TimeoutExt},
 	};
 
 	/// The type of the database connection. A mutable reference to this should
@@ -168,5 +168,11 @@ async fn main() {
 		.await
 		.expect("error initializing database");
 
-	futures::future::join(app::serve(&state), redis_publisher::run(&state)).await;
+	futures::future::join4(
+		app::serve(&state),
+		redis_publisher::run(&state),
+		routes::registry_patr_cloud::garbage_collector::run(&state),
+		routes::registry_patr_cloud::retention::run(&state),
+	)
+	.await;
 }
@@ -2,3 +2,6 @@
 pub mod access_token_data;
 /// Contains all the structs that will be stored in Redis
 pub mod redis;
+/// Contains the struct that will be encoded in the JWT issued to clients of the
+/// container registry.
+pub mod registry_token;
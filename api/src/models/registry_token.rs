@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime};
+
+use super::access_token_data::datetime_as_seconds;
+use crate::prelude::*;
+
+/// The data that is stored inside the token issued to clients of the container
+/// registry (like the Docker CLI), which will be encoded as a JWT. The token
+/// follows the [Docker token authentication specification][1], and contains
+/// the list of repositories the client can access, along with the actions it
+/// can perform on each of them.
+///
+/// [1]: https://distribution.github.io/distribution/spec/auth/token/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryToken {
+	/// The issuer of the token. This is always [`constants::JWT_ISSUER`].
+	pub iss: String,
+	/// The ID of the user that the token was issued to.
+	pub sub: Uuid,
+	/// The ID of the API token that the token was issued for, if the user
+	/// authenticated with an API token. The permissions of the API token are
+	/// used instead of the permissions of the user in this case.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub login_id: Option<Uuid>,
+	/// The service that the token is intended for. This is always
+	/// [`constants::CONTAINER_REGISTRY_SERVICE`].
+	pub aud: String,
+	/// The timestamp after which the token must not be accepted.
+	#[serde(with = "datetime_as_seconds")]
+	pub exp: OffsetDateTime,
+	/// The timestamp before which the token must not be accepted.
+	#[serde(with = "datetime_as_seconds")]
+	pub nbf: OffsetDateTime,
+	/// The timestamp at which the token was issued.
+	#[serde(with = "datetime_as_seconds")]
+	pub iat: OffsetDateTime,
+	/// A unique identifier for the token.
+	pub jti: Uuid,
+	/// The list of resources that the token grants access to.
+	pub access: Vec<RegistryTokenAccess>,
+}
+
+impl RegistryToken {
+	/// How long a registry token is valid for. Clients request a new token
+	/// whenever the token expires, so this is kept short to make sure that
+	/// revoked permissions take effect quickly.
+	pub const VALIDITY: Duration = Duration::minutes(5);
+
+	/// Returns true if the token allows the given action on the resource of the
+	/// given type and name. For repositories, the name is in the format
+	/// `<workspace ID>/<repository name>`.
+	pub fn has_access(&self, r#type: &str, name: &str, action: RegistryAction) -> bool {
+		self.access.iter().any(|access| {
+			access.r#type == r#type && access.name == name && access.actions.contains(&action)
+		})
+	}
+}
+
+/// A resource that a [`RegistryToken`] grants access to, along with the
+/// actions that can be performed on it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryTokenAccess {
+	/// The type of the resource. This is either `repository`, or `registry`
+	/// for the catalog of the registry.
+	pub r#type: String,
+	/// The name of the resource. For repositories, this is in the format
+	/// `<workspace ID>/<repository name>`, and for the registry this is always
+	/// `catalog`.
+	pub name: String,
+	/// The actions that can be performed on the resource.
+	pub actions: Vec<RegistryAction>,
+}
+
+/// An action that can be performed on a repository of the container registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegistryAction {
+	/// Download the blobs and manifests of the repository.
+	Pull,
+	/// Upload blobs and manifests to the repository.
+	Push,
+	/// Delete manifests and tags from the repository.
+	Delete,
+}
+
+impl RegistryAction {
+	/// Parses an action from the scope of a token request. The wildcard action
+	/// (`*`) is parsed as all the actions.
+	pub fn parse_scope_action(action: &str) -> Option<&'static [Self]> {
+		match action {
+			"pull" => Some(&[Self::Pull]),
+			"push" => Some(&[Self::Push]),
+			"delete" => Some(&[Self::Delete]),
+			"*" => Some(&[Self::Pull, Self::Push, Self::Delete]),
+			_ => None,
+		}
+	}
+
+	/// The name of the action, as used in the scope of a token request.
+	pub fn as_str(self) -> &'static str {
+		match self {
+			Self::Pull => "pull",
+			Self::Push => "push",
+			Self::Delete => "delete",
+		}
+	}
+
+	/// The permission that a user needs on a repository to perform this
+	/// action.
+	pub fn permission(self) -> Permission {
+		Permission::ContainerRegistryRepository(match self {
+			Self::Pull => ContainerRegistryRepositoryPermission::Pull,
+			Self::Push => ContainerRegistryRepositoryPermission::Push,
+			Self::Delete => ContainerRegistryRepositoryPermission::DeleteImage,
+		})
+	}
+}
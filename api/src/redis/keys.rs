@@ -1,3 +1,5 @@
+use std::net::IpAddr;
+
 use crate::prelude::*;
 
 /// The key used to store the permissions for a login ID
@@ -49,3 +51,32 @@ pub fn runner_connection_lock(runner_id: &Uuid) -> String {
 pub fn runner_connection_lock_prefix() -> String {
 	String::from("runnerConnectionLock:")
 }
+
+/// The key used to store an ongoing blob upload session of the container
+/// registry. The session expires if the upload is not completed in time.
+pub fn registry_upload_session(upload_id: &Uuid) -> String {
+	format!("registryUploadSession:{}", upload_id)
+}
+
+/// The key used to lock an upload session of the container registry while a
+/// chunk is being uploaded to it, so that only one request can append to the
+/// session at a time.
+pub fn registry_upload_session_lock(upload_id: &Uuid) -> String {
+	format!("registryUploadSessionLock:{}", upload_id)
+}
+
+/// The key used to store the port forwarding sessions that are open for a
+/// deployment, as a sorted set scored by when each session expires. Sessions
+/// that were never removed (say, if the API was restarted) are ignored once
+/// they expire.
+pub fn port_forward_sessions(deployment_id: &Uuid) -> String {
+	format!("portForwardSessions:{}", deployment_id)
+}
+
+/// The key used to count the failed attempts to get a container registry token
+/// with the password of a user from an IP address. Password logins for the
+/// username from that IP address are denied once there are too many failed
+/// attempts, until the key expires.
+pub fn registry_password_attempts(username: &str, client_ip: &IpAddr) -> String {
+	format!("registryPasswordAttempts:{}:{}", username, client_ip)
+}
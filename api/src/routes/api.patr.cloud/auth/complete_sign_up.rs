@@ -16,6 +16,8 @@ use time::OffsetDateTime;
 
 use crate::{models::access_token_data::AccessTokenData, prelude::*};
 
+/// The handler to complete the sign up of a user, verifying the OTP sent to
+/// them.
 pub async fn complete_sign_up(
 	AppRequest {
 		request:
@@ -8,6 +8,8 @@ use time::OffsetDateTime;
 
 use crate::prelude::*;
 
+/// The handler to create a new account, sending an OTP to verify the recovery
+/// option of the user.
 pub async fn create_account(
 	AppRequest {
 		request:
@@ -8,6 +8,8 @@ use time::OffsetDateTime;
 
 use crate::prelude::*;
 
+/// The handler to send an OTP to the recovery option of a user to reset their
+/// password.
 pub async fn forgot_password(
 	AppRequest {
 		request:
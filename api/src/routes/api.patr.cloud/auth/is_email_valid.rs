@@ -3,6 +3,7 @@ use models::api::auth::*;
 
 use crate::prelude::*;
 
+/// The handler to check if an email address is available to sign up with.
 pub async fn is_email_valid(
 	AppRequest {
 		request:
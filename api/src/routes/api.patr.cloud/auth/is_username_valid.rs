@@ -3,6 +3,7 @@ use models::api::auth::*;
 
 use crate::prelude::*;
 
+/// The handler to check if a username is available to sign up with.
 pub async fn is_username_valid(
 	AppRequest {
 		request:
@@ -3,6 +3,7 @@ use models::api::auth::*;
 
 use crate::prelude::*;
 
+/// The handler to list the recovery options of a user, with the details masked.
 pub async fn list_recovery_options(
 	AppRequest {
 		request:
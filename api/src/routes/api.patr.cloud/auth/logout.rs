@@ -6,6 +6,7 @@ use time::OffsetDateTime;
 
 use crate::{prelude::*, redis::keys as redis};
 
+/// The handler to log a user out, invalidating the refresh token of the login.
 pub async fn logout(
 	AuthenticatedAppRequest {
 		request:
@@ -118,7 +119,9 @@ pub async fn logout(
 	redis
 		.setex(
 			redis::login_id_revocation_timestamp(&login_id),
-			constants::CACHED_PERMISSIONS_VALIDITY.whole_seconds() as u64 + 100,
+			constants::CACHED_PERMISSIONS_VALIDITY
+				.unsigned_abs()
+				.as_secs() + 100,
 			OffsetDateTime::now_utc().unix_timestamp(),
 		)
 		.await
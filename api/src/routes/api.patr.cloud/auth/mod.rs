@@ -2,18 +2,31 @@ use axum::Router;
 
 use crate::prelude::*;
 
+/// Complete the sign up of a user, verifying the OTP sent to them.
 mod complete_sign_up;
+/// Create a new account, sending an OTP to verify the recovery option of the
+/// user.
 mod create_account;
+/// Send an OTP to the recovery option of a user to reset their password.
 mod forgot_password;
+/// Check if an email address is available to sign up with.
 mod is_email_valid;
+/// Check if a username is available to sign up with.
 mod is_username_valid;
+/// List the recovery options of a user, with the details masked.
 mod list_recovery_options;
+/// Log a user in, returning the access token and the refresh token.
 mod login;
+/// Log a user out, invalidating the refresh token of the login.
 mod logout;
+/// The OAuth endpoints, for third party applications to access Patr.
 #[expect(unused_variables)]
 mod oauth;
+/// Get a new access token for a login, using its refresh token.
 mod renew_access_token;
+/// Resend the OTP to verify the sign up of a user.
 mod resend_otp;
+/// Reset the password of a user, using the OTP sent to their recovery option.
 mod reset_password;
 
 use self::{
@@ -2,6 +2,8 @@ use models::api::auth::oauth::*;
 
 use crate::prelude::*;
 
+/// The handler to authorize a third party application to access the account of
+/// a user.
 pub async fn authorize(
 	AppRequest {
 		request:
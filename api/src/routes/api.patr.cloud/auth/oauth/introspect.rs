@@ -2,6 +2,7 @@ use models::api::auth::oauth::*;
 
 use crate::prelude::*;
 
+/// The handler to get the details of an OAuth token.
 pub async fn introspect(
 	AppRequest {
 		request:
@@ -2,6 +2,7 @@ use models::api::auth::oauth::*;
 
 use crate::prelude::*;
 
+/// The handler to revoke an OAuth token.
 pub async fn revoke(
 	AppRequest {
 		request:
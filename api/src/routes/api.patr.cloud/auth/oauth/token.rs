@@ -2,6 +2,8 @@ use models::api::auth::oauth::*;
 
 use crate::prelude::*;
 
+/// The handler to exchange an OAuth authorization code or refresh token for an
+/// access token.
 pub async fn token(
 	AppRequest {
 		request:
@@ -8,6 +8,7 @@ use time::OffsetDateTime;
 
 use crate::{models::access_token_data::AccessTokenData, prelude::*};
 
+/// The handler to get a new access token for a login, using its refresh token.
 pub async fn renew_access_token(
 	AppRequest {
 		request:
@@ -12,6 +12,7 @@ use rand::Rng;
 
 use crate::prelude::*;
 
+/// The handler to resend the OTP to verify the sign up of a user.
 pub async fn resend_otp(
 	AppRequest {
 		request:
@@ -12,6 +12,8 @@ use time::OffsetDateTime;
 
 use crate::prelude::*;
 
+/// The handler to reset the password of a user, using the OTP sent to their
+/// recovery option.
 pub async fn reset_password(
 	AppRequest {
 		request:
@@ -1,6 +1,11 @@
+/// All the endpoints to sign up, log in and recover an account.
 mod auth;
+/// Serves the OpenAPI document of the API, along with a page to browse it.
+mod openapi;
+/// All the endpoints to manage the account of the logged in user.
 mod user;
-mod workspace;
+/// All the endpoints to manage a workspace and its resources.
+pub(crate) mod workspace;
 
 use axum::Router;
 
@@ -14,4 +19,5 @@ pub async fn setup_routes(state: &AppState) -> Router {
 		.merge(auth::setup_routes(state).await)
 		.merge(user::setup_routes(state).await)
 		.merge(workspace::setup_routes(state).await)
+		.merge(openapi::setup_routes(state).await)
 }
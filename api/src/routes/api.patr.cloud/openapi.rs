@@ -0,0 +1,59 @@
+use std::sync::OnceLock;
+
+use axum::{http::header::CONTENT_TYPE, response::Html, routing::get, Router};
+use models::utils::generate_openapi_document;
+
+use crate::{prelude::*, utils::API_ENDPOINT_DOCS};
+
+/// The HTML page that renders the OpenAPI document using Redoc. The version of
+/// Redoc is pinned, so that the page doesn't change underneath us whenever a
+/// new version of Redoc is released.
+const REDOC_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+	<head>
+		<title>Patr API Reference</title>
+		<meta charset="utf-8" />
+		<meta name="viewport" content="width=device-width, initial-scale=1" />
+	</head>
+	<body>
+		<redoc spec-url="/openapi.json"></redoc>
+		<script src="https://cdn.redoc.ly/redoc/v2.1.5/bundles/redoc.standalone.js"></script>
+	</body>
+</html>
+"#;
+
+/// Sets up the routes serving the OpenAPI document of the API at
+/// `/openapi.json`, along with a page to browse it at `/docs`. The document is
+/// generated from the endpoints that have been mounted when it is first
+/// requested, since requests are only served once all the routers have been
+/// set up.
+#[instrument(skip(state))]
+pub async fn setup_routes(state: &AppState) -> Router {
+	Router::new()
+		.route(
+			"/openapi.json",
+			get(|| async { ([(CONTENT_TYPE, "application/json")], openapi_document()) }),
+		)
+		.route("/docs", get(|| async { Html(REDOC_PAGE) }))
+		.with_state(state.clone())
+}
+
+/// Returns the OpenAPI document of the API, generating it the first time it is
+/// needed.
+fn openapi_document() -> &'static str {
+	static DOCUMENT: OnceLock<String> = OnceLock::new();
+
+	DOCUMENT.get_or_init(|| {
+		let endpoints = API_ENDPOINT_DOCS
+			.read()
+			.expect("API endpoint docs poisoned");
+		info!("Generating the OpenAPI document for the API");
+		generate_openapi_document(
+			"Patr API",
+			env!("CARGO_PKG_VERSION"),
+			"https://api.patr.cloud",
+			endpoints.iter(),
+		)
+		.to_string()
+	})
+}
@@ -8,6 +8,8 @@ use time::OffsetDateTime;
 
 use crate::prelude::*;
 
+/// The handler to create a new API token for the user, with the given
+/// permissions.
 pub async fn create_api_token(
 	AuthenticatedAppRequest {
 		request:
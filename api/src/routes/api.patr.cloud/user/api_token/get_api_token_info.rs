@@ -8,6 +8,7 @@ use reqwest::StatusCode;
 
 use crate::prelude::*;
 
+/// The handler to get the information of an API token of the user.
 pub async fn get_api_token_info(
 	AuthenticatedAppRequest {
 		request:
@@ -5,6 +5,7 @@ use reqwest::StatusCode;
 
 use crate::prelude::*;
 
+/// The handler to list the API tokens of the user.
 pub async fn list_api_tokens(
 	AuthenticatedAppRequest {
 		request:
@@ -53,8 +54,8 @@ pub async fn list_api_tokens(
 		OFFSET $3;
 		"#,
 		user_data.id as _,
-		count as i32,
-		(count * page) as i32,
+		i64::try_from(count)?,
+		i64::try_from(count * page)?,
 	)
 	.fetch_all(&mut **database)
 	.await?
@@ -78,7 +79,7 @@ pub async fn list_api_tokens(
 	AppResponse::builder()
 		.body(ListApiTokensResponse { tokens })
 		.headers(ListApiTokensResponseHeaders {
-			total_count: TotalCountHeader(total_count as _),
+			total_count: TotalCountHeader(total_count.try_into()?),
 		})
 		.status_code(StatusCode::OK)
 		.build()
@@ -1,8 +1,14 @@
+/// Create a new API token for the user, with the given permissions.
 mod create_api_token;
+/// Get the information of an API token of the user.
 mod get_api_token_info;
+/// List the API tokens of the user.
 mod list_api_tokens;
+/// Regenerate the token of an API token, invalidating the old one.
 mod regenerate_api_token;
+/// Revoke an API token of the user.
 mod revoke_api_token;
+/// Update the name, permissions and restrictions of an API token.
 mod update_api_token;
 
 use axum::Router;
@@ -4,6 +4,8 @@ use reqwest::StatusCode;
 
 use crate::prelude::*;
 
+/// The handler to regenerate the token of an API token, invalidating the old
+/// one.
 pub async fn regenerate_api_token(
 	AuthenticatedAppRequest {
 		request:
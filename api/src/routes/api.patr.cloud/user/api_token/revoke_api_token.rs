@@ -4,6 +4,7 @@ use rustis::commands::GenericCommands;
 
 use crate::prelude::*;
 
+/// The handler to revoke an API token of the user.
 pub async fn revoke_api_token(
 	AuthenticatedAppRequest {
 		request:
@@ -7,6 +7,8 @@ use rustis::commands::GenericCommands;
 
 use crate::prelude::*;
 
+/// The handler to update the name, permissions and restrictions of an API
+/// token.
 pub async fn update_api_token(
 	AuthenticatedAppRequest {
 		request:
@@ -5,6 +5,7 @@ use totp_rs::{Algorithm as TotpAlgorithm, Secret, TOTP};
 
 use crate::prelude::*;
 
+/// The handler to change the password of the user.
 pub async fn change_password(
 	AuthenticatedAppRequest {
 		request:
@@ -3,6 +3,7 @@ use models::api::{user::*, WithId};
 
 use crate::prelude::*;
 
+/// The handler to get the public details of a user, given their ID.
 pub async fn get_user_details(
 	AuthenticatedAppRequest {
 		request:
@@ -3,6 +3,7 @@ use models::api::{user::*, WithId};
 
 use crate::prelude::*;
 
+/// The handler to get the information of the user that is logged in.
 pub async fn get_user_info(
 	AuthenticatedAppRequest {
 		request:
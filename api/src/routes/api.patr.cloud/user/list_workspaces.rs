@@ -3,6 +3,7 @@ use models::api::{user::*, workspace::Workspace, WithId};
 
 use crate::prelude::*;
 
+/// The handler to list the workspaces that the user is a part of.
 pub async fn list_workspaces(
 	AuthenticatedAppRequest {
 		request:
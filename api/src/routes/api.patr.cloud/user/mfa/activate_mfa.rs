@@ -5,6 +5,7 @@ use totp_rs::{Algorithm as TotpAlgorithm, Secret, TOTP};
 
 use crate::{prelude::*, redis::keys as redis};
 
+/// The handler to activate multi-factor authentication for the user.
 pub async fn activate_mfa(
 	AuthenticatedAppRequest {
 		request:
@@ -4,6 +4,7 @@ use totp_rs::{Algorithm as TotpAlgorithm, Secret, TOTP};
 
 use crate::prelude::*;
 
+/// The handler to deactivate multi-factor authentication for the user.
 pub async fn deactivate_mfa(
 	AuthenticatedAppRequest {
 		request:
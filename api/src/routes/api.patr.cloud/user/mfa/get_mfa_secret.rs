@@ -6,6 +6,7 @@ use totp_rs::Secret;
 
 use crate::{prelude::*, redis::keys as redis};
 
+/// The handler to get a new secret to set up multi-factor authentication with.
 pub async fn get_mfa_secret(
 	AuthenticatedAppRequest {
 		request:
@@ -47,7 +48,7 @@ pub async fn get_mfa_secret(
 	redis
 		.setex(
 			redis::user_mfa_secret(&user_data.id),
-			Duration::minutes(5).whole_seconds() as u64,
+			Duration::minutes(5).unsigned_abs().as_secs(),
 			secret.clone(),
 		)
 		.await
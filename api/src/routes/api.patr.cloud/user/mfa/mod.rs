@@ -1,5 +1,8 @@
+/// Activate multi-factor authentication for the user.
 mod activate_mfa;
+/// Deactivate multi-factor authentication for the user.
 mod deactivate_mfa;
+/// Get a new secret to set up multi-factor authentication with.
 mod get_mfa_secret;
 
 use axum::Router;
@@ -2,15 +2,24 @@ use axum::Router;
 
 use crate::prelude::*;
 
+/// All the endpoints to manage the API tokens of the user.
 mod api_token;
+/// Change the password of the user.
 mod change_password;
+/// Get the public details of a user, given their ID.
 mod get_user_details;
+/// Get the information of the user that is logged in.
 mod get_user_info;
+/// List the workspaces that the user is a part of.
 mod list_workspaces;
+/// All the endpoints to manage the multi-factor authentication of the user.
 mod mfa;
+/// All the endpoints to manage the recovery options of the user.
 #[allow(unreachable_code, unused_variables)]
 mod recovery_options;
+/// Update the information of the user.
 mod update_user_info;
+/// All the endpoints to manage the web logins of the user.
 #[allow(unreachable_code, unused_variables)]
 mod web_logins;
 
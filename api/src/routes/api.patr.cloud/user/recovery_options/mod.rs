@@ -1,6 +1,10 @@
+/// Update the recovery email of the user, sending an OTP to verify it.
 mod update_user_email;
+/// Update the recovery phone number of the user, sending an OTP to verify it.
 mod update_user_phone_number;
+/// Verify the recovery email of the user with the OTP sent to it.
 mod verify_user_email;
+/// Verify the recovery phone number of the user with the OTP sent to it.
 mod verify_user_phone_number;
 
 use axum::Router;
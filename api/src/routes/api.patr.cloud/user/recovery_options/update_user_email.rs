@@ -2,6 +2,8 @@ use models::api::user::*;
 
 use crate::prelude::*;
 
+/// The handler to update the recovery email of the user, sending an OTP to
+/// verify it.
 pub async fn update_user_email(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -2,6 +2,8 @@ use models::api::user::*;
 
 use crate::prelude::*;
 
+/// The handler to verify the recovery email of the user with the OTP sent to
+/// it.
 pub async fn verify_user_email(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -2,6 +2,8 @@ use models::api::user::*;
 
 use crate::prelude::*;
 
+/// The handler to verify the recovery phone number of the user with the OTP
+/// sent to it.
 pub async fn verify_user_phone_number(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -3,6 +3,7 @@ use models::api::user::*;
 
 use crate::prelude::*;
 
+/// The handler to update the information of the user.
 pub async fn update_user_info(
 	AuthenticatedAppRequest {
 		request:
@@ -2,6 +2,7 @@ use models::api::user::*;
 
 use crate::prelude::*;
 
+/// The handler to delete a web login of the user, logging it out.
 pub async fn delete_web_login(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
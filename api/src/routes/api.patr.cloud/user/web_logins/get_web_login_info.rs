@@ -2,6 +2,7 @@ use models::api::user::*;
 
 use crate::prelude::*;
 
+/// The handler to get the information of a web login of the user.
 pub async fn get_web_login_info(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -2,6 +2,7 @@ use models::api::user::*;
 
 use crate::prelude::*;
 
+/// The handler to list the web logins of the user.
 pub async fn list_web_logins(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -1,5 +1,8 @@
+/// Delete a web login of the user, logging it out.
 mod delete_web_login;
+/// Get the information of a web login of the user.
 mod get_web_login_info;
+/// List the web logins of the user.
 mod list_web_logins;
 
 use axum::Router;
@@ -0,0 +1,69 @@
+use axum::http::StatusCode;
+use models::{api::workspace::container_registry::*, prelude::*};
+
+use crate::{prelude::*, routes::registry_patr_cloud::image_signature};
+
+pub async fn add_signing_key(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: AddContainerRegistrySigningKeyPath { workspace_id },
+				query: (),
+				headers:
+					AddContainerRegistrySigningKeyRequestHeaders {
+						user_agent: _,
+						authorization: _,
+					},
+				body: AddContainerRegistrySigningKeyRequestProcessed { name, public_key },
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+	}: AuthenticatedAppRequest<'_, AddContainerRegistrySigningKeyRequest>,
+) -> Result<AppResponse<AddContainerRegistrySigningKeyRequest>, ErrorType> {
+	info!(
+		"Adding signing key: `{}` to workspaceId: `{}`",
+		name, workspace_id
+	);
+
+	if !image_signature::is_valid_public_key(&public_key) {
+		return Err(ErrorType::WrongParameters);
+	}
+
+	let key_id = Uuid::new_v4();
+	query!(
+		r#"
+		INSERT INTO
+			container_registry_signing_key(
+				id,
+				workspace_id,
+				name,
+				public_key,
+				created
+			)
+		VALUES
+			($1, $2, $3, $4, NOW());
+		"#,
+		key_id as _,
+		workspace_id as _,
+		name as _,
+		public_key,
+	)
+	.execute(&mut **database)
+	.await
+	.map_err(|e| match e {
+		sqlx::Error::Database(dbe) if dbe.is_unique_violation() => ErrorType::ResourceAlreadyExists,
+		other => other.into(),
+	})?;
+
+	AppResponse::builder()
+		.body(AddContainerRegistrySigningKeyResponse {
+			id: WithId::from(key_id),
+		})
+		.headers(())
+		.status_code(StatusCode::CREATED)
+		.build()
+		.into_result()
+}
@@ -113,6 +113,20 @@ pub async fn delete_repository(
 	.execute(&mut **database)
 	.await?;
 
+	// The blobs themselves are removed by the garbage collector, once no
+	// other repository uses them
+	query!(
+		r#"
+		DELETE FROM
+			container_registry_repository_blob_link
+		WHERE
+			repository_id = $1;
+		"#,
+		repository_id as _
+	)
+	.execute(&mut **database)
+	.await?;
+
 	// Updating the name of docker repository to deleted
 	query!(
 		r#"
@@ -0,0 +1,59 @@
+use axum::http::StatusCode;
+use models::{api::workspace::container_registry::*, prelude::*};
+
+use crate::prelude::*;
+
+pub async fn delete_signing_key(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: DeleteContainerRegistrySigningKeyPath {
+					workspace_id,
+					key_id,
+				},
+				query: (),
+				headers:
+					DeleteContainerRegistrySigningKeyRequestHeaders {
+						user_agent: _,
+						authorization: _,
+					},
+				body: DeleteContainerRegistrySigningKeyRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+	}: AuthenticatedAppRequest<'_, DeleteContainerRegistrySigningKeyRequest>,
+) -> Result<AppResponse<DeleteContainerRegistrySigningKeyRequest>, ErrorType> {
+	info!(
+		"Deleting signing key: `{}` from workspaceId: `{}`",
+		key_id, workspace_id
+	);
+
+	let deleted = query!(
+		r#"
+		DELETE FROM
+			container_registry_signing_key
+		WHERE
+			id = $1 AND
+			workspace_id = $2;
+		"#,
+		key_id as _,
+		workspace_id as _,
+	)
+	.execute(&mut **database)
+	.await?
+	.rows_affected();
+
+	if deleted == 0 {
+		return Err(ErrorType::ResourceDoesNotExist);
+	}
+
+	AppResponse::builder()
+		.body(DeleteContainerRegistrySigningKeyResponse)
+		.headers(())
+		.status_code(StatusCode::RESET_CONTENT)
+		.build()
+		.into_result()
+}
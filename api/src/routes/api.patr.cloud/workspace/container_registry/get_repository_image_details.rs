@@ -1,7 +1,7 @@
 use axum::http::StatusCode;
 use models::{api::workspace::container_registry::*, prelude::*};
 
-use crate::prelude::*;
+use crate::{prelude::*, routes::registry_patr_cloud::image_signature};
 
 pub async fn get_repository_image_details(
 	AuthenticatedAppRequest {
@@ -30,10 +30,13 @@ pub async fn get_repository_image_details(
 ) -> Result<AppResponse<GetContainerRepositoryImageDetailsRequest>, ErrorType> {
 	info!("Starting: Get image details");
 
-	let (image_digest, image_created) = query!(
+	let image = query!(
 		r#"
 		SELECT
 			manifest_digest,
+			architecture,
+			os,
+			variant,
 			created
 		FROM
 			container_registry_repository_manifest
@@ -46,8 +49,8 @@ pub async fn get_repository_image_details(
 	)
 	.fetch_optional(&mut **database)
 	.await?
-	.map(|image| (image.manifest_digest, image.created))
 	.ok_or(ErrorType::ResourceDoesNotExist)?;
+	let (image_digest, image_created) = (image.manifest_digest.clone(), image.created);
 
 	let image_tags = query!(
 		r#"
@@ -69,43 +72,94 @@ pub async fn get_repository_image_details(
 	.map(|row| row.tag)
 	.collect();
 
-	let image_size = query!(
+	// An image index doesn't have any blobs of its own, so the platforms (and
+	// the size) of the image come from the manifests in the index
+	let platform_digests = query!(
 		r#"
 		SELECT
-			COALESCE(SUM(container_registry_repository_blob.size), 0)::BIGINT AS "image_size"
+			manifest_digest,
+			architecture,
+			os,
+			variant
 		FROM
-			container_registry_manifest_blob
-		INNER JOIN
-			container_registry_repository_blob
-		ON
-			container_registry_manifest_blob.blob_digest
-			= container_registry_repository_blob.blob_digest
-		INNER JOIN
-			container_registry_repository_manifest
-		ON
-			container_registry_manifest_blob.manifest_digest
-			= container_registry_repository_manifest.manifest_digest
+			container_registry_index_manifest
 		WHERE
-			container_registry_repository_manifest.repository_id = $1 AND
-			container_registry_repository_manifest.manifest_digest = $2;
+			index_digest = $1;
 		"#,
-		repository_id as _,
-		digest_or_tag
+		&image_digest,
 	)
-	.fetch_one(&mut **database)
-	.await
-	.map(|repo| repo.image_size)?
-	.ok_or(ErrorType::ResourceDoesNotExist)?;
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| (row.manifest_digest, row.architecture, row.os, row.variant))
+	.collect::<Vec<_>>();
+	let platform_digests = if platform_digests.is_empty() {
+		vec![(
+			image.manifest_digest,
+			image.architecture,
+			image.os,
+			image.variant,
+		)]
+	} else {
+		platform_digests
+	};
+
+	let mut platforms = Vec::with_capacity(platform_digests.len());
+	for (digest, architecture, os, variant) in platform_digests {
+		let size = get_manifest_size(&mut **database, &digest).await?;
+		platforms.push(ContainerRepositoryImagePlatform {
+			digest,
+			os,
+			architecture,
+			variant: Some(variant).filter(|variant| !variant.is_empty()),
+			size,
+		});
+	}
+	let image_size = platforms.iter().map(|platform| platform.size).sum();
+
+	let signature =
+		image_signature::verify_image_signature(&mut **database, repository_id, &image_digest)
+			.await?;
 
 	AppResponse::builder()
 		.body(GetContainerRepositoryImageDetailsResponse {
 			digest: image_digest,
-			size: image_size as u64,
+			size: image_size,
 			created: image_created,
 			tags: image_tags,
+			platforms,
+			signature,
 		})
 		.headers(())
 		.status_code(StatusCode::OK)
 		.build()
 		.into_result()
 }
+
+/// Get the total size of the blobs referenced by a manifest
+async fn get_manifest_size(
+	connection: &mut DatabaseConnection,
+	manifest_digest: &str,
+) -> Result<u64, ErrorType> {
+	let size = query!(
+		r#"
+		SELECT
+			COALESCE(SUM(container_registry_repository_blob.size), 0)::BIGINT AS "size!"
+		FROM
+			container_registry_manifest_blob
+		INNER JOIN
+			container_registry_repository_blob
+		ON
+			container_registry_manifest_blob.blob_digest =
+				container_registry_repository_blob.blob_digest
+		WHERE
+			container_registry_manifest_blob.manifest_digest = $1;
+		"#,
+		manifest_digest,
+	)
+	.fetch_one(&mut *connection)
+	.await?
+	.size;
+
+	Ok(size as u64)
+}
@@ -22,7 +22,7 @@ pub async fn get_repository_info(
 		database,
 		redis: _,
 		client_ip: _,
-		config: _,
+		config,
 		user_data: _,
 	}: AuthenticatedAppRequest<'_, GetContainerRepositoryInfoRequest>,
 ) -> Result<AppResponse<GetContainerRepositoryInfoRequest>, ErrorType> {
@@ -46,35 +46,35 @@ pub async fn get_repository_info(
 	.map(|repo| repo.name)
 	.ok_or(ErrorType::ResourceDoesNotExist)?;
 
-	let size = query!(
+	let usage = query!(
 		r#"
 		SELECT
-			COALESCE(SUM(size), 0)::BIGINT as "size!"
-		FROM
-			container_registry_repository
-		INNER JOIN
-			container_registry_repository_manifest
-		ON
-			container_registry_repository.id 
-			= container_registry_repository_manifest.repository_id
-		INNER JOIN
-			container_registry_manifest_blob
-		ON
-			container_registry_repository_manifest.manifest_digest 
-			= container_registry_manifest_blob.manifest_digest
-		INNER JOIN
-			container_registry_repository_blob
-		ON
-			container_registry_manifest_blob.blob_digest 
-			= container_registry_repository_blob.blob_digest
-		WHERE
-			container_registry_repository.workspace_id = $1;
+			CONTAINER_REGISTRY_REPOSITORY_SIZE($1) AS "repository_size!",
+			CONTAINER_REGISTRY_WORKSPACE_SIZE($2) AS "workspace_size!",
+			(
+				SELECT
+					storage_limit
+				FROM
+					container_registry_repository_policy
+				WHERE
+					repository_id = $1
+			) AS "storage_limit";
 		"#,
+		repository_id as _,
 		workspace_id as _,
 	)
 	.fetch_one(&mut **database)
-	.await
-	.map(|repo| repo.size as u64)?;
+	.await?;
+
+	let size = usage.repository_size as u64;
+	let repository_usage = ContainerRegistryStorageUsage {
+		used: size,
+		limit: usage.storage_limit.map(|limit| limit as u64),
+	};
+	let workspace_usage = ContainerRegistryStorageUsage {
+		used: usage.workspace_size as u64,
+		limit: config.container_registry.workspace_storage_limit,
+	};
 
 	let last_updated = query!(
 		r#"
@@ -139,6 +139,8 @@ pub async fn get_repository_info(
 				last_updated,
 				created,
 			},
+			repository_usage,
+			workspace_usage,
 		})
 		.headers(())
 		.status_code(StatusCode::OK)
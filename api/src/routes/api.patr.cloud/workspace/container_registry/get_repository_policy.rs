@@ -0,0 +1,84 @@
+use axum::http::StatusCode;
+use models::{api::workspace::container_registry::*, prelude::*};
+
+use crate::prelude::*;
+
+pub async fn get_repository_policy(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path:
+					GetContainerRepositoryPolicyPath {
+						workspace_id,
+						repository_id,
+					},
+				query: (),
+				headers:
+					GetContainerRepositoryPolicyRequestHeaders {
+						user_agent: _,
+						authorization: _,
+					},
+				body: GetContainerRepositoryPolicyRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+	}: AuthenticatedAppRequest<'_, GetContainerRepositoryPolicyRequest>,
+) -> Result<AppResponse<GetContainerRepositoryPolicyRequest>, ErrorType> {
+	info!("Getting policy of repository: `{}`", repository_id);
+
+	query!(
+		r#"
+		SELECT
+			id
+		FROM
+			container_registry_repository
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL;
+		"#,
+		repository_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	// Repositories without a policy don't have any immutable tags, and keep
+	// everything that is pushed to them
+	let policy = query!(
+		r#"
+		SELECT
+			immutable_tag_patterns,
+			keep_last_tags,
+			untagged_manifest_retention_days,
+			storage_limit
+		FROM
+			container_registry_repository_policy
+		WHERE
+			repository_id = $1;
+		"#,
+		repository_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.map(|row| ContainerRepositoryPolicy {
+		immutable_tag_patterns: row.immutable_tag_patterns,
+		keep_last_tags: row.keep_last_tags.map(|tags| tags as u32),
+		untagged_manifest_retention_days: row
+			.untagged_manifest_retention_days
+			.map(|days| days as u32),
+		storage_limit: row.storage_limit.map(|limit| limit as u64),
+	})
+	.unwrap_or_default();
+
+	AppResponse::builder()
+		.body(GetContainerRepositoryPolicyResponse { policy })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -1,5 +1,6 @@
 use axum::http::StatusCode;
 use models::{api::workspace::container_registry::*, prelude::*};
+use sqlx::{QueryBuilder, Row};
 
 use crate::prelude::*;
 
@@ -8,11 +9,12 @@ pub async fn list_repositories(
 		request:
 			ProcessedApiRequest {
 				path: ListContainerRepositoriesPath { workspace_id },
-				query: Paginated {
-					data: (),
-					count,
-					page,
-				},
+				query:
+					Paginated {
+						data: ListContainerRepositoriesQuery { filter, sort },
+						count,
+						page,
+					},
 				headers:
 					ListContainerRepositoriesRequestHeaders {
 						authorization: _,
@@ -23,102 +25,119 @@ pub async fn list_repositories(
 		database,
 		redis: _,
 		client_ip: _,
-		config: _,
+		config,
 		user_data,
 	}: AuthenticatedAppRequest<'_, ListContainerRepositoriesRequest>,
 ) -> Result<AppResponse<ListContainerRepositoriesRequest>, ErrorType> {
 	info!("Listing container registry repositories");
 
-	let mut total_count = 0;
-
-	let repositories = query!(
-		r#" 
+	let rows = QueryBuilder::<DatabaseType>::new(
+		r#"
 		SELECT
-			container_registry_repository.id,
-			container_registry_repository.name,
-			COALESCE(
-				(
-					SELECT
-						SUM(container_registry_repository_blob.size)
-					FROM
-						container_registry_repository_manifest
-					LEFT JOIN
-						container_registry_manifest_blob
-					ON
-						container_registry_repository_manifest.manifest_digest = container_registry_manifest_blob.manifest_digest
-					LEFT JOIN
-						container_registry_repository_blob
-					ON
-						container_registry_manifest_blob.blob_digest = container_registry_repository_blob.blob_digest
-					WHERE
-						container_registry_repository_manifest.repository_id = container_registry_repository.id
-				),
-				0
-			)::BIGINT AS "size!",
-			GREATEST(
-				(
-					SELECT
-						MAX(container_registry_repository_tag.last_updated)
-					FROM
-						container_registry_repository_tag
-					WHERE
-						repository_id = container_registry_repository.id
-				),
-				(
-					SELECT
-						MAX(container_registry_repository_manifest.created)
-					FROM
-						container_registry_repository_manifest
-					WHERE
-						repository_id = container_registry_repository.id
-				),
+			*,
+			COUNT(*) OVER() AS total_count
+		FROM (
+			SELECT
+				container_registry_repository.id,
+				container_registry_repository.name,
+				CONTAINER_REGISTRY_REPOSITORY_SIZE(container_registry_repository.id) AS size,
+				GREATEST(
+					(
+						SELECT
+							MAX(container_registry_repository_tag.last_updated)
+						FROM
+							container_registry_repository_tag
+						WHERE
+							repository_id = container_registry_repository.id
+					),
+					(
+						SELECT
+							MAX(container_registry_repository_manifest.created)
+						FROM
+							container_registry_repository_manifest
+						WHERE
+							repository_id = container_registry_repository.id
+					),
+					resource.created
+				) AS last_updated,
 				resource.created
-			) AS "last_updated!",
-			resource.created,
-			COUNT(*) OVER () AS "count!"
-		FROM
-			container_registry_repository
-		INNER JOIN
-			resource
-		ON
-			resource.id = container_registry_repository.id
-		INNER JOIN
-			RESOURCES_WITH_PERMISSION_FOR_LOGIN_ID($2, $3) AS permission_resource
-		ON
-			container_registry_repository.id = permission_resource.id
+			FROM
+				container_registry_repository
+			INNER JOIN
+				resource
+			ON
+				resource.id = container_registry_repository.id
+			INNER JOIN
+				RESOURCES_WITH_PERMISSION_FOR_LOGIN_ID("#,
+	)
+	.push_bind(user_data.login_id)
+	.push(", ")
+	.push_bind("TODO permission_name")
+	.push(
+		r#") AS permission_resource
+			ON
+				container_registry_repository.id = permission_resource.id
+			WHERE
+				container_registry_repository.workspace_id = "#,
+	)
+	.push_bind(workspace_id)
+	.push(
+		r#" AND
+				container_registry_repository.deleted IS NULL
+		) AS repository
 		WHERE
-			container_registry_repository.workspace_id = $1 AND
-			container_registry_repository.deleted IS NULL
-		ORDER BY
-			resource.created
-		LIMIT $4
-		OFFSET $5;
-		"#,
-		workspace_id as _,
-		user_data.login_id as _,
-		"TODO permission_name",
-		count as i32,
-		(page * count) as i32
+			TRUE"#,
 	)
+	.push_list_filter(filter.as_ref(), column)
+	.push_list_sort(sort.as_ref(), "created, id", column)
+	.push(" LIMIT ")
+	.push_bind(count as i32)
+	.push(" OFFSET ")
+	.push_bind(i32::try_from(page * count)?)
+	.build()
 	.fetch_all(&mut **database)
-	.await?
-	.into_iter()
-	.map(|repo| {
-		total_count = repo.count;
-		WithId::new(
-			repo.id,
-			ContainerRepository {
-				name: repo.name,
-				size: repo.size as u64,
-				last_updated: repo.last_updated,
-				created: repo.created,
-			},
+	.await?;
+
+	let total_count = rows
+		.first()
+		.map(|row| row.try_get::<i64, _>("total_count"))
+		.transpose()?
+		.unwrap_or(0);
+
+	let repositories = rows
+		.into_iter()
+		.map(|repo| {
+			Ok(WithId::new(
+				repo.try_get::<Uuid, _>("id")?,
+				ContainerRepository {
+					name: repo.try_get("name")?,
+					size: repo.try_get::<i64, _>("size")? as u64,
+					last_updated: repo.try_get("last_updated")?,
+					created: repo.try_get("created")?,
+				},
+			))
+		})
+		.collect::<Result<_, ErrorType>>()?;
+
+	let workspace_usage = ContainerRegistryStorageUsage {
+		used: query!(
+			r#"
+			SELECT
+				CONTAINER_REGISTRY_WORKSPACE_SIZE($1) AS "size!";
+			"#,
+			workspace_id as _,
 		)
-	})
-	.collect();
+		.fetch_one(&mut **database)
+		.await?
+		.size as u64,
+		limit: config.container_registry.workspace_storage_limit,
+	};
 
 	AppResponse::builder()
-		.body(ListContainerRepositoriesResponse { repositories })
+		.body(ListContainerRepositoriesResponse {
+			repositories,
+			workspace_usage,
+		})
 		.headers(ListContainerRepositoriesResponseHeaders {
 			total_count: TotalCountHeader(total_count as _),
 		})
@@ -126,3 +145,13 @@ pub async fn list_repositories(
 		.build()
 		.into_result()
 }
+
+/// The SQL expression of each field that the repositories can be filtered and
+/// sorted by.
+fn column(field: ContainerRepositoryListField) -> &'static str {
+	match field {
+		ContainerRepositoryListField::Name => "name",
+		ContainerRepositoryListField::Created => "created",
+		ContainerRepositoryListField::LastUpdated => "last_updated",
+	}
+}
@@ -1,5 +1,6 @@
 use axum::http::StatusCode;
 use models::{api::workspace::container_registry::*, prelude::*};
+use time::OffsetDateTime;
 
 use crate::prelude::*;
 
@@ -7,16 +8,16 @@ pub async fn list_repository_tags(
 	AuthenticatedAppRequest {
 		request:
 			ProcessedApiRequest {
-				path:
-					ListContainerRepositoryTagsPath {
-						workspace_id: _,
-						repository_id,
-					},
-				query: Paginated {
-					page,
-					count,
-					data: (),
+				path: ListContainerRepositoryTagsPath {
+					workspace_id,
+					repository_id,
 				},
+				query:
+					Paginated {
+						data: ListContainerRepositoryTagsQuery { after, before },
+						count,
+						page,
+					},
 				headers:
 					ListContainerRepositoryTagsRequestHeaders {
 						user_agent: _,
@@ -33,42 +34,116 @@ pub async fn list_repository_tags(
 ) -> Result<AppResponse<ListContainerRepositoryTagsRequest>, ErrorType> {
 	info!("Listing tags for repository: {}", repository_id);
 
-	let mut total_count = 0;
-	let tags = query!(
+	let total_count = query!(
 		r#"
 		SELECT
-			*,
-			COUNT(*) OVER() AS "count!"
+			COUNT(*) AS "count!"
 		FROM
 			container_registry_repository_tag
 		WHERE
-			repository_id = $1
+			repository_id = $1;
+		"#,
+		repository_id as _,
+	)
+	.fetch_one(&mut **database)
+	.await?
+	.count;
+
+	// Cursor pagination is opt-in. The first page links to the next one, so
+	// that clients can follow the links from there instead of counting pages
+	let cursor_query =
+		(page == 0 || after.is_some() || before.is_some()).then_some(CursorPaginated {
+			data: (),
+			count,
+			after,
+			before,
+		});
+	let (cursor, backwards) = cursor_query
+		.as_ref()
+		.map(CursorPaginated::decode_cursor::<(OffsetDateTime, String)>)
+		.transpose()?
+		.unwrap_or_default();
+	let (cursor_last_updated, cursor_tag) = cursor.unzip();
+	let offset = if cursor_query.is_some() {
+		0
+	} else {
+		page.saturating_mul(count)
+	};
+
+	// When going backwards, the items right before the cursor are fetched in
+	// the reverse order, and then sorted back into the order of the list
+	let mut tags = query!(
+		r#"
+		SELECT
+			tag,
+			manifest_digest,
+			last_updated
+		FROM (
+			SELECT
+				tag,
+				manifest_digest,
+				last_updated
+			FROM
+				container_registry_repository_tag
+			WHERE
+				repository_id = $1 AND
+				(
+					$2::TIMESTAMPTZ IS NULL OR
+					($4 AND (last_updated, tag) > ($2, $3::TEXT)) OR
+					(NOT $4 AND (last_updated, tag) < ($2, $3::TEXT))
+				)
+			ORDER BY
+				CASE WHEN $4 THEN last_updated END ASC,
+				CASE WHEN $4 THEN tag END ASC,
+				last_updated DESC,
+				tag DESC
+			LIMIT $5
+			OFFSET $6
+		) AS page
 		ORDER BY
-			last_updated DESC
-		LIMIT $2
-		OFFSET $3;
+			last_updated DESC,
+			tag DESC;
 		"#,
 		repository_id as _,
-		count as i32,
-		(page * count) as i32,
+		cursor_last_updated,
+		cursor_tag,
+		backwards,
+		count.saturating_add(1) as i64,
+		offset as i64,
 	)
 	.fetch_all(&mut **database)
 	.await?
 	.into_iter()
-	.map(|row| {
-		total_count = row.count;
-		ContainerRepositoryTagAndDigestInfo {
-			tag: row.tag,
-			last_updated: row.last_updated,
-			digest: row.manifest_digest,
-		}
+	.map(|row| ContainerRepositoryTagAndDigestInfo {
+		tag: row.tag,
+		last_updated: row.last_updated,
+		digest: row.manifest_digest,
 	})
-	.collect();
+	.collect::<Vec<_>>();
+
+	let (tags, link) = match cursor_query {
+		Some(cursor_query) => {
+			let (tags, link) = cursor_query.into_page(
+				ListContainerRepositoryTagsPath {
+					workspace_id,
+					repository_id,
+				},
+				tags,
+				|tag| Cursor::new(&(tag.last_updated, &tag.tag)),
+			);
+			(tags, Some(link))
+		}
+		None => {
+			tags.truncate(count);
+			(tags, None)
+		}
+	};
 
 	AppResponse::builder()
 		.body(ListContainerRepositoryTagsResponse { tags })
 		.headers(ListContainerRepositoryTagsResponseHeaders {
 			total_count: TotalCountHeader(total_count as _),
+			link,
 		})
 		.status_code(StatusCode::OK)
 		.build()
@@ -0,0 +1,65 @@
+use axum::http::StatusCode;
+use models::{api::workspace::container_registry::*, prelude::*};
+
+use crate::prelude::*;
+
+pub async fn list_signing_keys(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ListContainerRegistrySigningKeysPath { workspace_id },
+				query: (),
+				headers:
+					ListContainerRegistrySigningKeysRequestHeaders {
+						user_agent: _,
+						authorization: _,
+					},
+				body: ListContainerRegistrySigningKeysRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+	}: AuthenticatedAppRequest<'_, ListContainerRegistrySigningKeysRequest>,
+) -> Result<AppResponse<ListContainerRegistrySigningKeysRequest>, ErrorType> {
+	info!("Listing signing keys of workspaceId: `{}`", workspace_id);
+
+	let keys = query!(
+		r#"
+		SELECT
+			id,
+			name::TEXT AS "name!",
+			public_key,
+			created
+		FROM
+			container_registry_signing_key
+		WHERE
+			workspace_id = $1
+		ORDER BY
+			created;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| {
+		WithId::new(
+			row.id,
+			ContainerRegistrySigningKey {
+				name: row.name,
+				public_key: row.public_key,
+				created: row.created,
+			},
+		)
+	})
+	.collect();
+
+	AppResponse::builder()
+		.body(ListContainerRegistrySigningKeysResponse { keys })
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
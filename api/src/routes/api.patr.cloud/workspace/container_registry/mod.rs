@@ -7,37 +7,52 @@ use time::{Duration, OffsetDateTime};
 
 use crate::{prelude::*, utils::config::AppConfig};
 
+mod add_signing_key;
 mod create_repository;
 mod delete_repository;
 mod delete_repository_image;
+mod delete_signing_key;
 mod get_repository_image_details;
 mod get_repository_image_exposed_ports;
 mod get_repository_info;
+mod get_repository_policy;
 mod list_repositories;
 mod list_repository_tags;
+mod list_signing_keys;
+mod update_repository_policy;
 
 use self::{
+	add_signing_key::*,
 	create_repository::*,
 	delete_repository::*,
 	delete_repository_image::*,
+	delete_signing_key::*,
 	get_repository_image_details::*,
 	get_repository_image_exposed_ports::*,
 	get_repository_info::*,
+	get_repository_policy::*,
 	list_repositories::*,
 	list_repository_tags::*,
+	list_signing_keys::*,
+	update_repository_policy::*,
 };
 
 #[instrument(skip(state))]
 pub async fn setup_routes(state: &AppState) -> Router {
 	Router::new()
+		.mount_auth_endpoint(add_signing_key, state)
 		.mount_auth_endpoint(create_repository, state)
 		.mount_auth_endpoint(delete_repository, state)
 		.mount_auth_endpoint(delete_repository_image, state)
+		.mount_auth_endpoint(delete_signing_key, state)
 		.mount_auth_endpoint(get_repository_image_details, state)
 		.mount_auth_endpoint(get_repository_image_exposed_ports, state)
 		.mount_auth_endpoint(get_repository_info, state)
+		.mount_auth_endpoint(get_repository_policy, state)
 		.mount_auth_endpoint(list_repositories, state)
 		.mount_auth_endpoint(list_repository_tags, state)
+		.mount_auth_endpoint(list_signing_keys, state)
+		.mount_auth_endpoint(update_repository_policy, state)
 		.with_state(state.clone())
 }
 
@@ -0,0 +1,109 @@
+use axum::http::StatusCode;
+use models::{api::workspace::container_registry::*, prelude::*};
+
+use crate::prelude::*;
+
+/// The maximum number of immutable tag patterns a repository can have
+const MAX_IMMUTABLE_TAG_PATTERNS: usize = 32;
+
+pub async fn update_repository_policy(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path:
+					UpdateContainerRepositoryPolicyPath {
+						workspace_id,
+						repository_id,
+					},
+				query: (),
+				headers:
+					UpdateContainerRepositoryPolicyRequestHeaders {
+						user_agent: _,
+						authorization: _,
+					},
+				body:
+					UpdateContainerRepositoryPolicyRequestProcessed {
+						immutable_tag_patterns,
+						keep_last_tags,
+						untagged_manifest_retention_days,
+						storage_limit,
+					},
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+	}: AuthenticatedAppRequest<'_, UpdateContainerRepositoryPolicyRequest>,
+) -> Result<AppResponse<UpdateContainerRepositoryPolicyRequest>, ErrorType> {
+	info!("Updating policy of repository: `{}`", repository_id);
+
+	// Patterns can only contain the characters allowed in a tag, along with
+	// the `*` and `?` wildcards
+	let is_valid_pattern = |pattern: &String| {
+		!pattern.is_empty() &&
+			pattern.len() <= 128 &&
+			pattern.chars().all(|character| {
+				character.is_ascii_alphanumeric() ||
+					matches!(character, '_' | '.' | '-' | '*' | '?')
+			})
+	};
+	if immutable_tag_patterns.len() > MAX_IMMUTABLE_TAG_PATTERNS ||
+		!immutable_tag_patterns.iter().all(is_valid_pattern)
+	{
+		return Err(ErrorType::WrongParameters);
+	}
+
+	query!(
+		r#"
+		SELECT
+			id
+		FROM
+			container_registry_repository
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL
+		FOR UPDATE;
+		"#,
+		repository_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	query!(
+		r#"
+		INSERT INTO
+			container_registry_repository_policy(
+				repository_id,
+				immutable_tag_patterns,
+				keep_last_tags,
+				untagged_manifest_retention_days,
+				storage_limit
+			)
+		VALUES
+			($1, $2, $3, $4, $5)
+		ON CONFLICT(repository_id) DO UPDATE SET
+			immutable_tag_patterns = EXCLUDED.immutable_tag_patterns,
+			keep_last_tags = EXCLUDED.keep_last_tags,
+			untagged_manifest_retention_days = EXCLUDED.untagged_manifest_retention_days,
+			storage_limit = EXCLUDED.storage_limit;
+		"#,
+		repository_id as _,
+		&immutable_tag_patterns,
+		keep_last_tags.map(|tags| tags as i32),
+		untagged_manifest_retention_days.map(|days| days as i32),
+		storage_limit.map(|limit| limit as i64),
+	)
+	.execute(&mut **database)
+	.await?;
+
+	AppResponse::builder()
+		.body(UpdateContainerRepositoryPolicyResponse)
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
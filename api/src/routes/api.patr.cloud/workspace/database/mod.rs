@@ -13,6 +13,7 @@ pub async fn setup_routes(state: &AppState) -> Router {
 		.mount_auth_endpoint(list_database, state)
 }
 
+/// The handler to list the machine types that a database can run on.
 async fn all_database_plan(
 	AppRequest {
 		request:
@@ -50,6 +51,7 @@ async fn all_database_plan(
 		.into_result()
 }
 
+/// The handler to create a database in the workspace.
 async fn create_database(
 	AuthenticatedAppRequest {
 		request:
@@ -89,6 +91,7 @@ async fn create_database(
 		.into_result()
 }
 
+/// The handler to delete a database in the workspace.
 async fn delete_database(
 	AuthenticatedAppRequest {
 		request:
@@ -120,6 +123,7 @@ async fn delete_database(
 		.into_result()
 }
 
+/// The handler to get the information of a database in the workspace.
 async fn get_database(
 	AuthenticatedAppRequest {
 		request:
@@ -173,6 +177,7 @@ async fn get_database(
 		.into_result()
 }
 
+/// The handler to list the databases in the workspace.
 async fn list_database(
 	AuthenticatedAppRequest {
 		request:
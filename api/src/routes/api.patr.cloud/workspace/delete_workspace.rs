@@ -92,7 +92,9 @@ pub async fn delete_workspace(
 	redis
 		.setex(
 			redis::keys::workspace_id_revocation_timestamp(&workspace.id.into()),
-			constants::CACHED_PERMISSIONS_VALIDITY.whole_seconds() as u64 + 300,
+			constants::CACHED_PERMISSIONS_VALIDITY
+				.unsigned_abs()
+				.as_secs() + 300,
 			OffsetDateTime::now_utc().unix_timestamp(),
 		)
 		.await
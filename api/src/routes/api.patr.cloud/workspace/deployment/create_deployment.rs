@@ -3,6 +3,7 @@ use models::api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspace
 use rustis::commands::PubSubCommands;
 use time::OffsetDateTime;
 
+use super::{image_policy, revision, rollout};
 use crate::prelude::*;
 
 /// The handler to create a deployment in the workspace. This will create a new
@@ -36,6 +37,7 @@ pub async fn create_deployment(
 								liveness_probe,
 								config_mounts,
 								volumes,
+								rollout_strategy,
 							},
 						deploy_on_create,
 					},
@@ -52,6 +54,25 @@ pub async fn create_deployment(
 		name, workspace_id
 	);
 
+	if !rollout_strategy.is_valid() {
+		debug!("Invalid rollout strategy: {:?}", rollout_strategy);
+		return Err(ErrorType::WrongParameters);
+	}
+
+	// The tag is resolved before the deployment is created, so that a workspace
+	// that requires signed images can't deploy an unsigned image
+	let digest = if let DeploymentRegistry::PatrRegistry { repository_id, .. } = &registry {
+		image_policy::get_trusted_tag_digest(
+			database,
+			workspace_id,
+			*repository_id,
+			image_tag.as_ref(),
+		)
+		.await?
+	} else {
+		None
+	};
+
 	let now = OffsetDateTime::now_utc();
 
 	let deployment_id = query!(
@@ -116,7 +137,11 @@ pub async fn create_deployment(
 				startup_probe_port_type,
 				liveness_probe_port,
 				liveness_probe_path,
-				liveness_probe_port_type
+				liveness_probe_port_type,
+				rollout_strategy,
+				rollout_max_surge,
+				rollout_max_unavailable,
+				rollout_canary_percentage
 			)
 		VALUES
 			(
@@ -138,7 +163,11 @@ pub async fn create_deployment(
 				$16,
 				$17,
 				$18,
-				$19
+				$19,
+				$20,
+				$21,
+				$22,
+				$23
 			);
 		"#,
 		deployment_id as _,
@@ -164,6 +193,18 @@ pub async fn create_deployment(
 		liveness_probe.as_ref().map(|probe| probe.port as i32),
 		liveness_probe.as_ref().map(|probe| probe.path.as_str()),
 		liveness_probe.as_ref().map(|_| ExposedPortType::Http) as _,
+		rollout_strategy.strategy_type() as _,
+		rollout_strategy
+			.max_surge()
+			.map(i16::try_from)
+			.transpose()?,
+		rollout_strategy
+			.max_unavailable()
+			.map(i16::try_from)
+			.transpose()?,
+		rollout_strategy
+			.canary_percentage()
+			.map(|percentage| percentage as i16),
 	)
 	.execute(&mut **database)
 	.await
@@ -317,51 +358,36 @@ pub async fn create_deployment(
 		err => ErrorType::server_error(err),
 	})?;
 
-	if let DeploymentRegistry::PatrRegistry { repository_id, .. } = &registry {
-		let digest = query!(
+	if let (DeploymentRegistry::PatrRegistry { repository_id, .. }, Some(digest)) =
+		(&registry, &digest)
+	{
+		query!(
 			r#"
-			SELECT
-				manifest_digest
-			FROM
-				container_registry_repository_manifest
-			WHERE
-				repository_id = $1
-			ORDER BY
-				created DESC
-			LIMIT 1;
+			INSERT INTO
+				deployment_deploy_history(
+					deployment_id,
+					image_digest,
+					repository_id,
+					created
+				)
+			VALUES
+				($1, $2, $3, $4)
+			ON CONFLICT
+				(deployment_id, image_digest)
+			DO NOTHING;
 			"#,
-			repository_id as _
+			deployment_id as _,
+			digest as _,
+			repository_id as _,
+			now as _,
 		)
-		.fetch_optional(&mut **database)
-		.await?
-		.map(|row| row.manifest_digest);
-
-		if let Some(digest) = digest {
-			query!(
-				r#"
-				INSERT INTO
-					deployment_deploy_history(
-						deployment_id,
-						image_digest,
-						repository_id,
-						created
-					)
-				VALUES
-					($1, $2, $3, $4)
-				ON CONFLICT
-					(deployment_id, image_digest)
-				DO NOTHING;
-				"#,
-				deployment_id as _,
-				digest as _,
-				repository_id as _,
-				now as _,
-			)
-			.execute(&mut **database)
-			.await?;
-		}
+		.execute(&mut **database)
+		.await?;
 	}
 
+	let revision = revision::record_revision(database, deployment_id.into()).await?;
+	rollout::start_rollout(database, deployment_id.into(), revision).await?;
+
 	// TODO Temporary workaround until audit logs and triggers are implemented
 	redis
 		.publish(
@@ -389,6 +415,7 @@ pub async fn create_deployment(
 					liveness_probe,
 					config_mounts,
 					volumes,
+					rollout_strategy,
 				},
 			})
 			.unwrap(),
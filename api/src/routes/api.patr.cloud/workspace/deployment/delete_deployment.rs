@@ -20,6 +20,7 @@ pub async fn delete_deployment(
 					DeleteDeploymentRequestHeaders {
 						authorization: _,
 						user_agent: _,
+						if_match,
 					},
 				body: DeleteDeploymentRequestProcessed,
 			},
@@ -32,20 +33,29 @@ pub async fn delete_deployment(
 ) -> Result<AppResponse<DeleteDeploymentRequest>, ErrorType> {
 	info!("Deleting deployment: {deployment_id}");
 
-	let runner = query!(
+	let deployment = query!(
 		r#"
 		SELECT
-			runner
+			runner,
+			version
 		FROM
 			deployment
 		WHERE
-			id = $1;
+			id = $1
+		FOR UPDATE;
 		"#,
 		deployment_id as _
 	)
-	.fetch_one(&mut **database)
+	.fetch_optional(&mut **database)
 	.await?
-	.runner;
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	if !ResourceVersion(deployment.version).satisfies(if_match.as_ref()) {
+		debug!("Deployment `{deployment_id}` has been modified since it was last fetched");
+		return Err(ErrorType::PreconditionFailed);
+	}
+
+	let runner = deployment.runner;
 
 	query!(
 		r#"
@@ -83,6 +93,54 @@ pub async fn delete_deployment(
 	.execute(&mut **database)
 	.await?;
 
+	query!(
+		r#"
+		DELETE FROM
+			deployment_runtime
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _
+	)
+	.execute(&mut **database)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_event
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _
+	)
+	.execute(&mut **database)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_rollout
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _
+	)
+	.execute(&mut **database)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_revision
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _
+	)
+	.execute(&mut **database)
+	.await?;
+
 	query!(
 		r#"
 		SET CONSTRAINTS ALL DEFERRED;
@@ -1,5 +1,6 @@
 use axum::http::StatusCode;
 use models::{api::workspace::deployment::deploy_history::*, utils::TotalCountHeader};
+use time::OffsetDateTime;
 
 use crate::prelude::*;
 
@@ -13,11 +14,12 @@ pub async fn list_deploy_history(
 					workspace_id,
 					deployment_id,
 				},
-				query: Paginated {
-					data: (),
-					count,
-					page,
-				},
+				query:
+					Paginated {
+						data: ListDeploymentDeployHistoryQuery { after, before },
+						count,
+						page,
+					},
 				headers:
 					ListDeploymentDeployHistoryRequestHeaders {
 						authorization: _,
@@ -53,42 +55,113 @@ pub async fn list_deploy_history(
 	.await?
 	.ok_or(ErrorType::ResourceDoesNotExist)?;
 
-	let mut total_count = 0;
-	let deploys = query!(
+	let total_count = query!(
 		r#"
-		SELECT 
-			image_digest,
-			created,
-			COUNT(*) OVER() AS "total_count!"
+		SELECT
+			COUNT(*) AS "count!"
 		FROM
 			deployment_deploy_history
 		WHERE
-			deployment_id = $1
+			deployment_id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_one(&mut **database)
+	.await?
+	.count;
+
+	// Cursor pagination is opt-in. The first page links to the next one, so
+	// that clients can follow the links from there instead of counting pages
+	let cursor_query =
+		(page == 0 || after.is_some() || before.is_some()).then_some(CursorPaginated {
+			data: (),
+			count,
+			after,
+			before,
+		});
+	let (cursor, backwards) = cursor_query
+		.as_ref()
+		.map(CursorPaginated::decode_cursor::<(OffsetDateTime, String)>)
+		.transpose()?
+		.unwrap_or_default();
+	let (cursor_created, cursor_image_digest) = cursor.unzip();
+	let offset = if cursor_query.is_some() {
+		0
+	} else {
+		page.saturating_mul(count)
+	};
+
+	// When going backwards, the items right before the cursor are fetched in
+	// the reverse order, and then sorted back into the order of the list
+	let mut deploys = query!(
+		r#"
+		SELECT
+			image_digest,
+			created
+		FROM (
+			SELECT
+				image_digest,
+				created
+			FROM
+				deployment_deploy_history
+			WHERE
+				deployment_id = $1 AND
+				(
+					$2::TIMESTAMPTZ IS NULL OR
+					($4 AND (created, image_digest) > ($2, $3::TEXT)) OR
+					(NOT $4 AND (created, image_digest) < ($2, $3::TEXT))
+				)
+			ORDER BY
+				CASE WHEN $4 THEN created END ASC,
+				CASE WHEN $4 THEN image_digest END ASC,
+				created DESC,
+				image_digest DESC
+			LIMIT $5
+			OFFSET $6
+		) AS page
 		ORDER BY
-			created DESC
-		LIMIT $2
-		OFFSET $3;
+			created DESC,
+			image_digest DESC;
 		"#,
 		deployment_id as _,
-		count as i32,
-		(page & count) as i32
+		cursor_created,
+		cursor_image_digest,
+		backwards,
+		i64::try_from(count.saturating_add(1))?,
+		i64::try_from(offset)?,
 	)
 	.fetch_all(&mut **database)
 	.await?
 	.into_iter()
-	.map(|row| {
-		total_count = row.total_count;
-		DeploymentDeployHistory {
-			image_digest: row.image_digest,
-			created: row.created,
-		}
+	.map(|row| DeploymentDeployHistory {
+		image_digest: row.image_digest,
+		created: row.created,
 	})
-	.collect();
+	.collect::<Vec<_>>();
+
+	let (deploys, link) = match cursor_query {
+		Some(cursor_query) => {
+			let (deploys, link) = cursor_query.into_page(
+				ListDeploymentDeployHistoryPath {
+					workspace_id,
+					deployment_id,
+				},
+				deploys,
+				|deploy| Cursor::new(&(deploy.created, &deploy.image_digest)),
+			);
+			(deploys, Some(link))
+		}
+		None => {
+			deploys.truncate(count);
+			(deploys, None)
+		}
+	};
 
 	AppResponse::builder()
 		.body(ListDeploymentDeployHistoryResponse { deploys })
 		.headers(ListDeploymentDeployHistoryResponseHeaders {
-			total_count: TotalCountHeader(total_count as _),
+			total_count: TotalCountHeader(total_count.try_into()?),
+			link,
 		})
 		.status_code(StatusCode::OK)
 		.build()
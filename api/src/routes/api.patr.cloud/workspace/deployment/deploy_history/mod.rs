@@ -2,14 +2,19 @@ use axum::Router;
 
 use crate::prelude::*;
 
+/// Delete an image from the deploy history of a deployment.
 mod delete_deploy_history;
+/// List the images that a deployment has been deployed with.
 mod list_deploy_history;
+/// Roll back a deployment to an image from its deploy history.
+mod rollback_deployment;
 
-use self::{delete_deploy_history::*, list_deploy_history::*};
+use self::{delete_deploy_history::*, list_deploy_history::*, rollback_deployment::*};
 
 #[instrument(skip(state))]
 pub async fn setup_routes(state: &AppState) -> Router {
 	Router::new()
 		.mount_auth_endpoint(list_deploy_history, state)
 		.mount_auth_endpoint(delete_deploy_history, state)
+		.mount_auth_endpoint(rollback_deployment, state)
 }
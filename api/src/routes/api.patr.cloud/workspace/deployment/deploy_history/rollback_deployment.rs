@@ -0,0 +1,368 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::{deploy_history::*, *};
+
+use super::super::{
+	image_policy,
+	revision,
+	rollout,
+	runner_update::{self, RunnerUpdate},
+};
+use crate::prelude::*;
+
+/// Roll a deployment back to an image digest from its deploy history. The
+/// deployment keeps running the digest until it is rolled back again, or a new
+/// image is pushed to its tag (if `deploy_on_push` is enabled). The ports,
+/// environment variables, probes and config mounts that the deployment had
+/// with the image can optionally be restored along with it.
+pub async fn rollback_deployment(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path:
+					RollbackDeploymentPath {
+						workspace_id,
+						deployment_id,
+						image_digest,
+					},
+				query: (),
+				headers:
+					RollbackDeploymentRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: RollbackDeploymentRequestProcessed {
+					restore_running_details,
+				},
+			},
+		database,
+		redis,
+		client_ip: _,
+		config: _,
+		user_data: _,
+	}: AuthenticatedAppRequest<'_, RollbackDeploymentRequest>,
+) -> Result<AppResponse<RollbackDeploymentRequest>, ErrorType> {
+	info!(
+		"Rolling back deployment `{}` to digest: {}",
+		deployment_id, image_digest
+	);
+
+	let update = rollback(
+		database,
+		workspace_id,
+		deployment_id,
+		&image_digest,
+		restore_running_details,
+	)
+	.await?;
+
+	// TODO Temporary workaround until audit logs and triggers are implemented
+	runner_update::notify_runners(redis, workspace_id, update.into_iter().collect()).await?;
+
+	AppResponse::builder()
+		.body(RollbackDeploymentResponse)
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
+
+/// Rolls a deployment back to an image digest from its deploy history, and
+/// returns the update that needs to be sent to its runner, if anything changed.
+async fn rollback(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+	image_digest: &str,
+	restore_running_details: bool,
+) -> Result<Option<RunnerUpdate>, ErrorType> {
+	let deployment = query!(
+		r#"
+		SELECT
+			registry,
+			repository_id,
+			current_live_digest
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL
+		FOR UPDATE;
+		"#,
+		deployment_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	// Only images from the Patr registry have a deploy history
+	let Some(repository_id) = deployment
+		.repository_id
+		.filter(|_| deployment.registry == PatrRegistry.to_string())
+		.map(Uuid::from)
+	else {
+		return Err(ErrorType::WrongParameters);
+	};
+
+	// The digest has to be in the deploy history of the deployment, and the
+	// image must still be in the repository
+	query!(
+		r#"
+		SELECT
+			deployment_deploy_history.image_digest
+		FROM
+			deployment_deploy_history
+		INNER JOIN
+			container_registry_repository_manifest
+		ON
+			container_registry_repository_manifest.repository_id =
+				deployment_deploy_history.repository_id AND
+			container_registry_repository_manifest.manifest_digest =
+				deployment_deploy_history.image_digest
+		WHERE
+			deployment_deploy_history.deployment_id = $1 AND
+			deployment_deploy_history.image_digest = $2 AND
+			deployment_deploy_history.repository_id = $3;
+		"#,
+		deployment_id as _,
+		image_digest,
+		repository_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	// The image may have been deployed before the workspace started requiring
+	// signed images, so the signature is checked again
+	image_policy::ensure_image_is_trusted(
+		&mut *connection,
+		workspace_id,
+		repository_id,
+		Some(image_digest),
+	)
+	.await?;
+
+	let running_details = if restore_running_details {
+		let running_details =
+			revision::get_running_details_for_digest(&mut *connection, deployment_id, image_digest)
+				.await?
+				.ok_or(ErrorType::ResourceDoesNotExist)?;
+		Some(running_details)
+	} else {
+		None
+	};
+
+	if deployment.current_live_digest.as_deref() == Some(image_digest) && running_details.is_none()
+	{
+		return Ok(None);
+	}
+
+	if let Some(running_details) = &running_details {
+		revision::restore_running_details(&mut *connection, deployment_id, running_details)
+			.await
+			.map_err(|err| match err {
+				// A secret used by the environment variables has been deleted
+				sqlx::Error::Database(err) if err.is_foreign_key_violation() => {
+					ErrorType::ResourceDoesNotExist
+				}
+				err => ErrorType::server_error(err),
+			})?;
+	}
+
+	query!(
+		r#"
+		UPDATE
+			deployment
+		SET
+			current_live_digest = $2,
+			status = (
+				CASE
+					WHEN status = 'stopped' THEN
+						status
+					ELSE
+						'deploying'::DEPLOYMENT_STATUS
+				END
+			),
+			version = version + 1
+		WHERE
+			id = $1;
+		"#,
+		deployment_id as _,
+		image_digest,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	let revision = revision::record_revision(&mut *connection, deployment_id).await?;
+	rollout::start_rollout(&mut *connection, deployment_id, revision).await?;
+
+	Ok(runner_update::get_runner_update(&mut *connection, deployment_id).await?)
+}
+
+#[cfg(test)]
+mod tests {
+	use models::api::workspace::deployment::DeploymentStatus;
+
+	use super::rollback;
+	use crate::{
+		prelude::*,
+		routes::api_patr_cloud::workspace::deployment::deploy_on_push,
+		utils::test_utils,
+	};
+
+	/// The digest that the deployment is rolled back to
+	const OLD_DIGEST: &str =
+		"sha256:0000000000000000000000000000000000000000000000000000000000000001";
+	/// The digest that the deployment runs before it is rolled back
+	const NEW_DIGEST: &str =
+		"sha256:0000000000000000000000000000000000000000000000000000000000000002";
+
+	/// Gets the live digest and the names of the environment variables of a
+	/// deployment
+	async fn get_deployment(
+		state: &AppState,
+		deployment_id: Uuid,
+	) -> (Option<String>, Vec<String>) {
+		let digest = query!(
+			r#"
+			SELECT
+				current_live_digest
+			FROM
+				deployment
+			WHERE
+				id = $1;
+			"#,
+			deployment_id as _,
+		)
+		.fetch_one(&state.database)
+		.await
+		.unwrap()
+		.current_live_digest;
+
+		let environment_variables = query!(
+			r#"
+			SELECT
+				name
+			FROM
+				deployment_environment_variable
+			WHERE
+				deployment_id = $1;
+			"#,
+			deployment_id as _,
+		)
+		.fetch_all(&state.database)
+		.await
+		.unwrap()
+		.into_iter()
+		.map(|row| row.name)
+		.collect();
+
+		(digest, environment_variables)
+	}
+
+	/// Pushes a digest to the `latest` tag of a repository, and redeploys the
+	/// deployments that use it
+	async fn push(state: &AppState, workspace_id: Uuid, repository_id: Uuid, digest: &str) {
+		test_utils::create_tag(state, repository_id, "latest", digest).await;
+
+		let mut database = state.database.begin().await.unwrap();
+		deploy_on_push::redeploy_deployments(
+			&mut database,
+			workspace_id,
+			repository_id,
+			"latest",
+			digest,
+		)
+		.await
+		.unwrap();
+		database.commit().await.unwrap();
+	}
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_running_details_are_restored() {
+		let state = test_utils::setup_state().await;
+		let workspace = test_utils::create_workspace(&state).await;
+		let workspace_id = workspace.workspace_id;
+		let repository_id = test_utils::create_repository(&state, workspace_id, "app").await;
+		let runner_id = test_utils::create_runner(&state, workspace_id).await;
+		let deployment_id = test_utils::create_deployment(
+			&state,
+			workspace_id,
+			runner_id,
+			repository_id,
+			"latest",
+			DeploymentStatus::Running,
+			true,
+		)
+		.await;
+
+		push(&state, workspace_id, repository_id, OLD_DIGEST).await;
+
+		// The environment variable is added before the new digest is pushed, so
+		// it is only a part of the revisions that run the new digest
+		query!(
+			r#"
+			INSERT INTO
+				deployment_environment_variable(
+					deployment_id,
+					name,
+					value,
+					secret_id
+				)
+			VALUES
+				($1, 'FOO', 'bar', NULL);
+			"#,
+			deployment_id as _,
+		)
+		.execute(&state.database)
+		.await
+		.unwrap();
+		push(&state, workspace_id, repository_id, NEW_DIGEST).await;
+
+		// Restoring the running details removes the environment variable
+		let mut database = state.database.begin().await.unwrap();
+		let update = rollback(&mut database, workspace_id, deployment_id, OLD_DIGEST, true)
+			.await
+			.unwrap();
+		database.commit().await.unwrap();
+		assert!(update.is_some());
+		assert_eq!(
+			get_deployment(&state, deployment_id).await,
+			(Some(OLD_DIGEST.to_string()), vec![])
+		);
+
+		// Rolling back only the image doesn't add it back
+		let mut database = state.database.begin().await.unwrap();
+		let update = rollback(
+			&mut database,
+			workspace_id,
+			deployment_id,
+			NEW_DIGEST,
+			false,
+		)
+		.await
+		.unwrap();
+		database.commit().await.unwrap();
+		assert!(update.is_some());
+		assert_eq!(
+			get_deployment(&state, deployment_id).await,
+			(Some(NEW_DIGEST.to_string()), vec![])
+		);
+
+		// Rolling back to the digest that is already live does nothing
+		let mut database = state.database.begin().await.unwrap();
+		let update = rollback(
+			&mut database,
+			workspace_id,
+			deployment_id,
+			NEW_DIGEST,
+			false,
+		)
+		.await
+		.unwrap();
+		database.commit().await.unwrap();
+		assert!(update.is_none());
+	}
+}
@@ -0,0 +1,256 @@
+use models::api::workspace::{
+	container_registry::ContainerRepositoryImageSignatureStatus,
+	deployment::*,
+};
+
+use super::{
+	revision,
+	rollout,
+	runner_update::{self, RunnerUpdate},
+};
+use crate::{prelude::*, routes::registry_patr_cloud::image_signature};
+
+/// Points every deployment that uses the given tag of a repository (and has
+/// `deploy_on_push` enabled) to the newly pushed digest, and records the digest
+/// in the deploy history of the deployment. Stopped deployments are left alone,
+/// so that pushing an image doesn't start them again. If the workspace requires
+/// signed images, nothing is redeployed unless the digest is signed by a key
+/// that the workspace trusts.
+///
+/// The returned updates must be sent to the runners using
+/// [`runner_update::notify_runners`] after the transaction is committed, so
+/// that the runners don't see the new digest before it is saved.
+pub async fn redeploy_deployments(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+	repository_id: Uuid,
+	tag: &str,
+	digest: &str,
+) -> Result<Vec<RunnerUpdate>, sqlx::Error> {
+	if image_signature::is_signature_required(connection, workspace_id).await? {
+		let status =
+			image_signature::verify_image_signature(connection, repository_id, digest).await?;
+		if !matches!(
+			status,
+			ContainerRepositoryImageSignatureStatus::Verified { .. }
+		) {
+			warn!(
+				"Not redeploying `{}` with the digest `{}`, since it isn't signed by a trusted key: {:?}",
+				tag, digest, status
+			);
+			return Ok(Vec::new());
+		}
+	}
+
+	let deployments = query!(
+		r#"
+		SELECT
+			id
+		FROM
+			deployment
+		WHERE
+			registry = $4 AND
+			repository_id = $1 AND
+			image_tag = $2 AND
+			deploy_on_push = TRUE AND
+			status != 'stopped' AND
+			current_live_digest IS DISTINCT FROM $3 AND
+			deleted IS NULL
+		FOR UPDATE;
+		"#,
+		repository_id as _,
+		tag,
+		digest,
+		PatrRegistry.to_string(),
+	)
+	.fetch_all(&mut *connection)
+	.await?;
+
+	let mut updates = Vec::with_capacity(deployments.len());
+	for deployment in deployments {
+		let deployment_id: Uuid = deployment.id.into();
+		info!(
+			"Redeploying deployment `{}` with the pushed digest `{}`",
+			deployment_id, digest
+		);
+
+		// The live digest of a deployment must be in its deploy history
+		query!(
+			r#"
+			INSERT INTO
+				deployment_deploy_history(
+					deployment_id,
+					image_digest,
+					repository_id,
+					created
+				)
+			VALUES
+				($1, $2, $3, NOW())
+			ON CONFLICT
+				(deployment_id, image_digest)
+			DO NOTHING;
+			"#,
+			deployment_id as _,
+			digest,
+			repository_id as _,
+		)
+		.execute(&mut *connection)
+		.await?;
+
+		query!(
+			r#"
+			UPDATE
+				deployment
+			SET
+				current_live_digest = $2,
+				status = 'deploying',
+				version = version + 1
+			WHERE
+				id = $1;
+			"#,
+			deployment_id as _,
+			digest,
+		)
+		.execute(&mut *connection)
+		.await?;
+
+		let revision = revision::record_revision(connection, deployment_id).await?;
+		rollout::start_rollout(connection, deployment_id, revision).await?;
+
+		if let Some(update) = runner_update::get_runner_update(connection, deployment_id).await? {
+			updates.push(update);
+		}
+	}
+
+	Ok(updates)
+}
+
+#[cfg(test)]
+mod tests {
+	use models::api::workspace::deployment::DeploymentStatus;
+
+	use super::redeploy_deployments;
+	use crate::{prelude::*, utils::test_utils};
+
+	/// The digest that is pushed by the tests
+	const DIGEST: &str = "sha256:ca2b0f26964cf2e80ba3e084d5983dab293fdb87485dc6445f3f7bbfc89d7459";
+
+	/// Gets the digest that a deployment is running
+	async fn get_live_digest(state: &AppState, deployment_id: Uuid) -> Option<String> {
+		query!(
+			r#"
+			SELECT
+				current_live_digest
+			FROM
+				deployment
+			WHERE
+				id = $1;
+			"#,
+			deployment_id as _,
+		)
+		.fetch_one(&state.database)
+		.await
+		.unwrap()
+		.current_live_digest
+	}
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_deployments_of_the_tag_are_redeployed() {
+		let state = test_utils::setup_state().await;
+		let workspace = test_utils::create_workspace(&state).await;
+		let workspace_id = workspace.workspace_id;
+		let repository_id = test_utils::create_repository(&state, workspace_id, "app").await;
+		let runner_id = test_utils::create_runner(&state, workspace_id).await;
+
+		let create = |tag: &'static str, status: DeploymentStatus, deploy_on_push: bool| {
+			test_utils::create_deployment(
+				&state,
+				workspace_id,
+				runner_id,
+				repository_id,
+				tag,
+				status,
+				deploy_on_push,
+			)
+		};
+		let redeployed = create("latest", DeploymentStatus::Running, true).await;
+		let other_tag = create("stable", DeploymentStatus::Running, true).await;
+		let stopped = create("latest", DeploymentStatus::Stopped, true).await;
+		let disabled = create("latest", DeploymentStatus::Running, false).await;
+
+		let mut database = state.database.begin().await.unwrap();
+		let updates =
+			redeploy_deployments(&mut database, workspace_id, repository_id, "latest", DIGEST)
+				.await
+				.unwrap();
+		database.commit().await.unwrap();
+
+		assert_eq!(updates.len(), 1);
+		assert_eq!(updates[0].runner, runner_id);
+		assert_eq!(
+			get_live_digest(&state, redeployed).await.as_deref(),
+			Some(DIGEST)
+		);
+		for deployment_id in [other_tag, stopped, disabled] {
+			assert_eq!(get_live_digest(&state, deployment_id).await, None);
+		}
+
+		let history = query!(
+			r#"
+			SELECT
+				image_digest
+			FROM
+				deployment_deploy_history
+			WHERE
+				deployment_id = $1;
+			"#,
+			redeployed as _,
+		)
+		.fetch_all(&state.database)
+		.await
+		.unwrap();
+		assert_eq!(history.len(), 1);
+		assert_eq!(history[0].image_digest, DIGEST);
+
+		// Pushing the same digest again doesn't redeploy anything
+		let mut database = state.database.begin().await.unwrap();
+		let updates =
+			redeploy_deployments(&mut database, workspace_id, repository_id, "latest", DIGEST)
+				.await
+				.unwrap();
+		database.commit().await.unwrap();
+		assert!(updates.is_empty());
+	}
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_unsigned_images_are_not_redeployed() {
+		let state = test_utils::setup_state().await;
+		let workspace = test_utils::create_workspace(&state).await;
+		let workspace_id = workspace.workspace_id;
+		let repository_id = test_utils::create_repository(&state, workspace_id, "app").await;
+		let runner_id = test_utils::create_runner(&state, workspace_id).await;
+		let deployment_id = test_utils::create_deployment(
+			&state,
+			workspace_id,
+			runner_id,
+			repository_id,
+			"latest",
+			DeploymentStatus::Running,
+			true,
+		)
+		.await;
+		test_utils::require_signed_images(&state, workspace_id).await;
+
+		let mut database = state.database.begin().await.unwrap();
+		let updates =
+			redeploy_deployments(&mut database, workspace_id, repository_id, "latest", DIGEST)
+				.await
+				.unwrap();
+		database.commit().await.unwrap();
+
+		assert!(updates.is_empty());
+		assert_eq!(get_live_digest(&state, deployment_id).await, None);
+	}
+}
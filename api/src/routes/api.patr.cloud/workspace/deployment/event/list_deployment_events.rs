@@ -0,0 +1,126 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::event::*;
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+/// List the events of a deployment, newest first. Events are only kept for a
+/// limited time, so older events aren't listed.
+pub async fn list_deployment_events(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ListDeploymentEventsPath {
+					workspace_id,
+					deployment_id,
+				},
+				query,
+				headers:
+					ListDeploymentEventsRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ListDeploymentEventsRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+	}: AuthenticatedAppRequest<'_, ListDeploymentEventsRequest>,
+) -> Result<AppResponse<ListDeploymentEventsRequest>, ErrorType> {
+	info!("Listing deployment events");
+
+	// Check if deployment exists
+	query!(
+		r#"
+		SELECT
+			id
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL;
+		"#,
+		deployment_id as _,
+		workspace_id as _
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	let (cursor, backwards) = query.decode_cursor::<(OffsetDateTime, Uuid)>()?;
+	let (cursor_timestamp, cursor_id) = cursor.unzip();
+
+	// When going backwards, the items right before the cursor are fetched in
+	// the reverse order, and then sorted back into the order of the list
+	let events = query!(
+		r#"
+		SELECT
+			id,
+			kind as "kind: DeploymentEventKind",
+			container,
+			message,
+			timestamp
+		FROM (
+			SELECT
+				id,
+				kind,
+				container,
+				message,
+				timestamp
+			FROM
+				deployment_event
+			WHERE
+				deployment_id = $1 AND
+				(
+					$2::TIMESTAMPTZ IS NULL OR
+					($4 AND (timestamp, id) > ($2, $3::UUID)) OR
+					(NOT $4 AND (timestamp, id) < ($2, $3::UUID))
+				)
+			ORDER BY
+				CASE WHEN $4 THEN timestamp END ASC,
+				CASE WHEN $4 THEN id END ASC,
+				timestamp DESC,
+				id DESC
+			LIMIT $5
+		) AS page
+		ORDER BY
+			timestamp DESC,
+			id DESC;
+		"#,
+		deployment_id as _,
+		cursor_timestamp,
+		cursor_id as _,
+		backwards,
+		i64::try_from(query.fetch_limit())?,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| DeploymentEvent {
+		id: row.id.into(),
+		kind: row.kind,
+		container: row.container,
+		message: row.message,
+		timestamp: row.timestamp,
+	})
+	.collect();
+
+	let (events, link) = query.into_page(
+		ListDeploymentEventsPath {
+			workspace_id,
+			deployment_id,
+		},
+		events,
+		|event| Cursor::new(&(event.timestamp, event.id)),
+	);
+
+	AppResponse::builder()
+		.body(ListDeploymentEventsResponse { events })
+		.headers(ListDeploymentEventsResponseHeaders { link })
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,87 @@
+use axum::Router;
+use models::api::workspace::deployment::event::*;
+use time::{Duration, OffsetDateTime};
+
+use crate::prelude::*;
+
+/// List the events of a deployment, most recent first.
+mod list_deployment_events;
+/// Stream the events of a deployment as they are recorded.
+mod stream_deployment_events;
+
+use self::{list_deployment_events::*, stream_deployment_events::*};
+
+/// How long the events of a deployment are kept for
+const EVENT_RETENTION: Duration = Duration::days(30);
+
+#[instrument(skip(state))]
+pub async fn setup_routes(state: &AppState) -> Router {
+	Router::new()
+		.mount_auth_endpoint(list_deployment_events, state)
+		.mount_auth_endpoint(stream_deployment_events, state)
+}
+
+/// The Redis channel that the events of a deployment are published to as they
+/// are recorded
+pub fn events_channel(workspace_id: &Uuid, deployment_id: &Uuid) -> String {
+	format!("{}/deployment/{}/event/stream", workspace_id, deployment_id)
+}
+
+/// Adds an event to the timeline of a deployment. Events that are older than
+/// the retention period of the timeline are removed along with it.
+pub async fn add_event(
+	connection: &mut DatabaseConnection,
+	deployment_id: Uuid,
+	kind: DeploymentEventKind,
+	container: Option<String>,
+	message: Option<String>,
+	timestamp: OffsetDateTime,
+) -> Result<DeploymentEvent, sqlx::Error> {
+	let id = Uuid::new_v4();
+
+	query!(
+		r#"
+		INSERT INTO
+			deployment_event(
+				id,
+				deployment_id,
+				kind,
+				container,
+				message,
+				timestamp
+			)
+		VALUES
+			($1, $2, $3, $4, $5, $6);
+		"#,
+		id as _,
+		deployment_id as _,
+		kind as _,
+		container.as_deref(),
+		message.as_deref(),
+		timestamp,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_event
+		WHERE
+			deployment_id = $1 AND
+			timestamp < $2;
+		"#,
+		deployment_id as _,
+		OffsetDateTime::now_utc() - EVENT_RETENTION,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(DeploymentEvent {
+		id,
+		kind,
+		container,
+		message,
+		timestamp,
+	})
+}
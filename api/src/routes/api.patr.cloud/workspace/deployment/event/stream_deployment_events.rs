@@ -0,0 +1,108 @@
+use axum::{http::StatusCode, response::IntoResponse};
+use axum_typed_websockets::Message;
+use futures::StreamExt;
+use models::{
+	api::workspace::deployment::event::*,
+	utils::{GenericResponse, WebSocketUpgrade},
+};
+
+use crate::prelude::*;
+
+/// Route to stream the events of a deployment as they are recorded. Only the
+/// events that happen after the stream is opened are sent, and the earlier
+/// events can be listed using the list endpoint.
+pub async fn stream_deployment_events(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: StreamDeploymentEventsPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					StreamDeploymentEventsRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: WebSocketUpgrade(upgrade),
+			},
+		database,
+		redis,
+		client_ip: _,
+		config: _,
+		user_data: _,
+	}: AuthenticatedAppRequest<'_, StreamDeploymentEventsRequest>,
+) -> Result<AppResponse<StreamDeploymentEventsRequest>, ErrorType> {
+	info!("Streaming events for deployment: {}", deployment_id);
+
+	query!(
+		r#"
+		SELECT
+			id
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL;
+		"#,
+		deployment_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	let mut pub_sub = redis.create_pub_sub();
+	pub_sub
+		.subscribe(super::events_channel(&workspace_id, &deployment_id))
+		.await?;
+
+	AppResponse::builder()
+		.body(GenericResponse(
+			upgrade
+				.on_upgrade(move |mut websocket| async move {
+					loop {
+						tokio::select! {
+							data = pub_sub.next() => {
+								let Some(Ok(data)) = data else {
+									break;
+								};
+								let Ok(message) = serde_json::from_slice(&data.payload)
+									.inspect_err(|err| {
+										error!("Error parsing deployment event: {:?}", err)
+									})
+								else {
+									continue;
+								};
+								let Ok(()) = websocket
+									.send(Message::Item(message))
+									.await
+									.inspect_err(|err| {
+										debug!("Failed to send event to client: {}", err);
+									})
+								else {
+									break;
+								};
+							}
+							message = websocket.recv() => {
+								// The client isn't expected to send anything,
+								// so the stream only ends once it's closed
+								match message {
+									Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+									Some(Ok(_)) => (),
+								}
+							}
+						}
+					}
+					_ = websocket.send(Message::Close(None)).await;
+					_ = websocket.close().await;
+				})
+				.into_response(),
+		))
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
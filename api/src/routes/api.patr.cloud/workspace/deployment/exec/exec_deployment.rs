@@ -0,0 +1,205 @@
+use axum::{http::StatusCode, response::IntoResponse};
+use axum_typed_websockets::Message;
+use futures::StreamExt;
+use models::{
+	api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceServerMsg},
+	utils::{GenericResponse, WebSocketUpgrade},
+};
+use rustis::commands::PubSubCommands;
+use time::OffsetDateTime;
+
+use crate::{prelude::*, routes::api_patr_cloud::workspace::deployment::runner_update};
+
+/// The shell that commands are run with, and that is started when no command
+/// is given
+const SHELL: &str = "/bin/sh";
+
+/// Route to open an interactive terminal session in a running container of a
+/// deployment. The session is relayed to the runner that the deployment runs
+/// on, over Redis, and the runner runs the command in one of the deployment's
+/// containers. Every session that is opened is recorded in the audit log of
+/// the workspace.
+pub async fn exec_deployment(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ExecDeploymentPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: ExecDeploymentQuery { command },
+				headers:
+					ExecDeploymentRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: WebSocketUpgrade(upgrade),
+			},
+		database,
+		redis,
+		client_ip: _,
+		config: _,
+		user_data,
+	}: AuthenticatedAppRequest<'_, ExecDeploymentRequest>,
+) -> Result<AppResponse<ExecDeploymentRequest>, ErrorType> {
+	info!(
+		"Opening a terminal session in deployment: {}",
+		deployment_id
+	);
+
+	let runner_id =
+		runner_update::get_connected_runner(database, redis, workspace_id, deployment_id).await?;
+
+	query!(
+		r#"
+		INSERT INTO
+			audit_log(
+				id,
+				workspace_id,
+				resource_id,
+				timestamp,
+				action,
+				login_id
+			)
+		VALUES
+			($1, $2, $3, $4, 'exec', $5);
+		"#,
+		Uuid::new_v4() as _,
+		workspace_id as _,
+		deployment_id as _,
+		OffsetDateTime::now_utc(),
+		user_data.login_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	let session_id = Uuid::new_v4();
+	let session_channel = super::session_channel(&workspace_id, &deployment_id, &session_id);
+	let runner_channel = format!("{}/runner/{}/stream", workspace_id, runner_id);
+
+	// Subscribe before the runner is asked to start the session, so that none
+	// of its output is missed
+	let mut pub_sub = redis.create_pub_sub();
+	pub_sub.subscribe(&session_channel).await?;
+
+	let command = match command {
+		Some(command) => vec![SHELL.to_string(), "-c".to_string(), command],
+		None => vec![SHELL.to_string()],
+	};
+	let redis = redis.clone();
+
+	AppResponse::builder()
+		.body(GenericResponse(
+			upgrade
+				.on_upgrade(move |mut websocket| async move {
+					let publish = |message: StreamRunnerDataForWorkspaceServerMsg| {
+						let redis = redis.clone();
+						let runner_channel = runner_channel.clone();
+						async move {
+							redis
+								.publish(runner_channel, serde_json::to_string(&message).unwrap())
+								.await
+								.inspect_err(|err| {
+									error!("Error relaying terminal session to runner: {:?}", err)
+								})
+								.is_ok()
+						}
+					};
+
+					if !publish(
+						StreamRunnerDataForWorkspaceServerMsg::DeploymentExecStarted {
+							id: deployment_id,
+							session_id,
+							command,
+						},
+					)
+					.await
+					{
+						_ = websocket
+							.send(Message::Item(ExecDeploymentServerMsg::Failed {
+								error: "Failed to reach the runner".to_string(),
+							}))
+							.await;
+					} else {
+						loop {
+							tokio::select! {
+								data = pub_sub.next() => {
+									let Some(Ok(data)) = data else {
+										break;
+									};
+									let Ok(message) = serde_json::from_slice::<
+										ExecDeploymentServerMsg,
+									>(&data.payload)
+									.inspect_err(|err| {
+										error!("Error parsing terminal session message: {:?}", err)
+									}) else {
+										continue;
+									};
+									let is_over = matches!(
+										message,
+										ExecDeploymentServerMsg::Exited { .. } |
+											ExecDeploymentServerMsg::Failed { .. }
+									);
+									let Ok(()) = websocket
+										.send(Message::Item(message))
+										.await
+										.inspect_err(|err| {
+											debug!("Failed to send terminal output to client: {}", err);
+										})
+									else {
+										break;
+									};
+									if is_over {
+										break;
+									}
+								}
+								message = websocket.recv() => {
+									let message = match message {
+										Some(Ok(Message::Item(
+											ExecDeploymentClientMsg::Input { data },
+										))) => StreamRunnerDataForWorkspaceServerMsg::DeploymentExecInputReceived {
+											id: deployment_id,
+											session_id,
+											data,
+										},
+										Some(Ok(Message::Item(
+											ExecDeploymentClientMsg::Resized { rows, cols },
+										))) => StreamRunnerDataForWorkspaceServerMsg::DeploymentExecResized {
+											id: deployment_id,
+											session_id,
+											rows,
+											cols,
+										},
+										Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+										Some(Ok(_)) => continue,
+									};
+									if !publish(message).await {
+										break;
+									}
+								}
+							}
+						}
+					}
+
+					// The runner stops the command if it's still running
+					publish(
+						StreamRunnerDataForWorkspaceServerMsg::DeploymentExecClosed {
+							id: deployment_id,
+							session_id,
+						},
+					)
+					.await;
+					_ = pub_sub
+						.unsubscribe(&session_channel)
+						.await
+						.inspect_err(|err| error!("Error closing terminal session: {:?}", err));
+					_ = websocket.send(Message::Close(None)).await;
+					_ = websocket.close().await;
+				})
+				.into_response(),
+		))
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
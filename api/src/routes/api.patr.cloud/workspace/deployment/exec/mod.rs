@@ -0,0 +1,121 @@
+use axum::Router;
+use models::api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceClientMsg};
+
+use crate::prelude::*;
+
+/// Open an interactive terminal session in a running deployment.
+mod exec_deployment;
+
+use self::exec_deployment::*;
+
+#[instrument(skip(state))]
+pub async fn setup_routes(state: &AppState) -> Router {
+	Router::new().mount_auth_endpoint(exec_deployment, state)
+}
+
+/// The Redis channel that the messages of a terminal session are published to,
+/// for the user that opened it
+pub fn session_channel(workspace_id: &Uuid, deployment_id: &Uuid, session_id: &Uuid) -> String {
+	format!(
+		"{}/deployment/{}/exec/{}",
+		workspace_id, deployment_id, session_id
+	)
+}
+
+/// Converts a message from a runner about a terminal session into the message
+/// that is relayed to the user that opened the session, along with the ID of
+/// the session. Messages that aren't about a terminal session are given back
+/// as they are.
+pub fn into_session_message(
+	message: StreamRunnerDataForWorkspaceClientMsg,
+) -> Result<(Uuid, ExecDeploymentServerMsg), StreamRunnerDataForWorkspaceClientMsg> {
+	match message {
+		StreamRunnerDataForWorkspaceClientMsg::DeploymentExecOutputSent {
+			id: _,
+			session_id,
+			data,
+		} => Ok((session_id, ExecDeploymentServerMsg::Output { data })),
+		StreamRunnerDataForWorkspaceClientMsg::DeploymentExecExited {
+			id: _,
+			session_id,
+			exit_code,
+		} => Ok((session_id, ExecDeploymentServerMsg::Exited { exit_code })),
+		StreamRunnerDataForWorkspaceClientMsg::DeploymentExecFailed {
+			id: _,
+			session_id,
+			error,
+		} => Ok((session_id, ExecDeploymentServerMsg::Failed { error })),
+		message => Err(message),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use models::api::workspace::{
+		deployment::ExecDeploymentServerMsg,
+		runner::StreamRunnerDataForWorkspaceClientMsg,
+	};
+
+	use super::into_session_message;
+	use crate::prelude::*;
+
+	#[test]
+	fn assert_session_messages_are_relayed() {
+		let id = Uuid::new_v4();
+		let session_id = Uuid::new_v4();
+
+		assert_eq!(
+			into_session_message(
+				StreamRunnerDataForWorkspaceClientMsg::DeploymentExecOutputSent {
+					id,
+					session_id,
+					data: b"hello".as_slice().into(),
+				}
+			),
+			Ok((
+				session_id,
+				ExecDeploymentServerMsg::Output {
+					data: b"hello".as_slice().into(),
+				}
+			))
+		);
+		assert_eq!(
+			into_session_message(
+				StreamRunnerDataForWorkspaceClientMsg::DeploymentExecExited {
+					id,
+					session_id,
+					exit_code: Some(1),
+				}
+			),
+			Ok((
+				session_id,
+				ExecDeploymentServerMsg::Exited { exit_code: Some(1) }
+			))
+		);
+		assert_eq!(
+			into_session_message(
+				StreamRunnerDataForWorkspaceClientMsg::DeploymentExecFailed {
+					id,
+					session_id,
+					error: "no such container".to_string(),
+				}
+			),
+			Ok((
+				session_id,
+				ExecDeploymentServerMsg::Failed {
+					error: "no such container".to_string(),
+				}
+			))
+		);
+	}
+
+	#[test]
+	fn assert_other_messages_are_given_back() {
+		let message = StreamRunnerDataForWorkspaceClientMsg::DeploymentReconcileFailed {
+			id: Uuid::new_v4(),
+			error: "image not found".to_string(),
+		};
+
+		assert_eq!(into_session_message(message.clone()), Err(message));
+	}
+}
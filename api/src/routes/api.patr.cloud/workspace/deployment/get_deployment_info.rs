@@ -1,6 +1,7 @@
 use axum::http::StatusCode;
 use models::{api::workspace::deployment::*, utils::StringifiedU16};
 
+use super::{rollout, runtime};
 use crate::prelude::*;
 
 /// The handler to get the deployment info in the workspace. This will return
@@ -45,7 +46,12 @@ pub async fn get_deployment_info(
 	.fetch_all(&mut **database)
 	.await?
 	.into_iter()
-	.map(|row| (StringifiedU16::new(row.port as u16), row.port_type))
+	.map(|row| {
+		(
+			StringifiedU16::new(row.port.try_into().unwrap_or_default()),
+			row.port_type,
+		)
+	})
 	.collect();
 
 	let environment_variables = query!(
@@ -112,7 +118,10 @@ pub async fn get_deployment_info(
 	.map(|row| (row.volume_id.into(), row.volume_mount_path))
 	.collect();
 
-	let deployment = query!(
+	let rollout = rollout::get_latest_rollout(database, deployment_id).await?;
+	let runtime = runtime::get_runtime(database, deployment_id).await?;
+
+	let (version, deployment) = query!(
 		r#"
 		SELECT
 			id,
@@ -132,7 +141,12 @@ pub async fn get_deployment_info(
 			startup_probe_path,
 			liveness_probe_port,
 			liveness_probe_path,
-			current_live_digest
+			current_live_digest,
+			rollout_strategy as "rollout_strategy: DeploymentRolloutStrategyType",
+			rollout_max_surge,
+			rollout_max_unavailable,
+			rollout_canary_percentage,
+			version
 		FROM
 			deployment
 		WHERE
@@ -143,56 +157,76 @@ pub async fn get_deployment_info(
 	)
 	.fetch_optional(&mut **database)
 	.await?
-	.map(|row| GetDeploymentInfoResponse {
-		deployment: WithId::new(
-			row.id,
-			Deployment {
-				name: row.name,
-				registry: if row.registry == PatrRegistry.to_string() {
-					DeploymentRegistry::PatrRegistry {
-						registry: PatrRegistry,
-						repository_id: row.repository_id.unwrap().into(),
-					}
-				} else {
-					DeploymentRegistry::ExternalRegistry {
-						registry: row.registry,
-						image_name: row.image_name.unwrap(),
-					}
+	.map(|row| {
+		let rollout_strategy = DeploymentRolloutStrategy::from_parts(
+			row.rollout_strategy,
+			row.rollout_max_surge.map(u16::try_from).transpose()?,
+			row.rollout_max_unavailable.map(u16::try_from).transpose()?,
+			row.rollout_canary_percentage
+				.map(u8::try_from)
+				.transpose()?,
+		)
+		.ok_or_else(|| ErrorType::server_error("corrupted deployment, invalid rollout strategy"))?;
+
+		Ok::<_, ErrorType>((
+			ResourceVersion(row.version),
+			GetDeploymentInfoResponse {
+				deployment: WithId::new(
+					row.id,
+					Deployment {
+						name: row.name,
+						registry: if row.registry == PatrRegistry.to_string() {
+							DeploymentRegistry::PatrRegistry {
+								registry: PatrRegistry,
+								repository_id: row.repository_id.unwrap().into(),
+							}
+						} else {
+							DeploymentRegistry::ExternalRegistry {
+								registry: row.registry,
+								image_name: row.image_name.unwrap(),
+							}
+						},
+						image_tag: row.image_tag,
+						status: row.status,
+						runner: row.runner.into(),
+						machine_type: row.machine_type.into(),
+						current_live_digest: row.current_live_digest,
+					},
+				),
+				running_details: DeploymentRunningDetails {
+					deploy_on_push: row.deploy_on_push,
+					min_horizontal_scale: u16::try_from(row.min_horizontal_scale)?,
+					max_horizontal_scale: u16::try_from(row.max_horizontal_scale)?,
+					ports,
+					environment_variables,
+					startup_probe: row.startup_probe_port.zip(row.startup_probe_path).map(
+						|(port, path)| DeploymentProbe {
+							port: port.try_into().unwrap_or_default(),
+							path,
+						},
+					),
+					liveness_probe: row.liveness_probe_port.zip(row.liveness_probe_path).map(
+						|(port, path)| DeploymentProbe {
+							port: port.try_into().unwrap_or_default(),
+							path,
+						},
+					),
+					config_mounts,
+					volumes,
+					rollout_strategy,
 				},
-				image_tag: row.image_tag,
-				status: row.status,
-				runner: row.runner.into(),
-				machine_type: row.machine_type.into(),
-				current_live_digest: row.current_live_digest,
+				rollout,
+				runtime,
 			},
-		),
-		running_details: DeploymentRunningDetails {
-			deploy_on_push: row.deploy_on_push,
-			min_horizontal_scale: row.min_horizontal_scale as u16,
-			max_horizontal_scale: row.max_horizontal_scale as u16,
-			ports,
-			environment_variables,
-			startup_probe: row.startup_probe_port.zip(row.startup_probe_path).map(
-				|(port, path)| DeploymentProbe {
-					port: port as u16,
-					path,
-				},
-			),
-			liveness_probe: row.liveness_probe_port.zip(row.liveness_probe_path).map(
-				|(port, path)| DeploymentProbe {
-					port: port as u16,
-					path,
-				},
-			),
-			config_mounts,
-			volumes,
-		},
+		))
 	})
-	.ok_or(ErrorType::ResourceDoesNotExist)?;
+	.ok_or(ErrorType::ResourceDoesNotExist)??;
 
 	AppResponse::builder()
 		.body(deployment)
-		.headers(())
+		.headers(GetDeploymentInfoResponseHeaders {
+			etag: version.to_etag(),
+		})
 		.status_code(StatusCode::OK)
 		.build()
 		.into_result()
@@ -5,21 +5,27 @@ use time::OffsetDateTime;
 
 use crate::prelude::*;
 
+/// The response of a range query to Loki
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct LokiResponse {
+	/// The data returned for the query
 	data: LokiData,
 }
 
+/// The data returned by Loki for a range query
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct LokiData {
+	/// The streams of logs that matched the query
 	result: Vec<LokiMatrixResult>,
 }
 
+/// A stream of logs returned by Loki
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct LokiMatrixResult {
+	/// The timestamp (in nanoseconds) and the line of each log in the stream
 	values: Vec<(i128, String)>,
 }
 
@@ -111,9 +117,7 @@ pub async fn get_deployment_logs(
 	}) = serde_json::from_str::<LokiResponse>(&loki_response)
 	else {
 		error!("Cannot parse Loki response: {}", loki_response);
-		return Err(ErrorType::server_error(format!(
-			"Failed to parse Loki response"
-		)));
+		return Err(ErrorType::server_error("Failed to parse Loki response"));
 	};
 
 	let logs = result
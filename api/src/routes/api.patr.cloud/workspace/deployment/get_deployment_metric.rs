@@ -5,21 +5,27 @@ use time::{Duration, OffsetDateTime};
 
 use crate::prelude::*;
 
+/// The response of a query to Mimir
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct MimirResponse {
+	/// The data returned for the query
 	data: MimirData,
 }
 
+/// The data returned by Mimir for a query
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct MimirData {
+	/// The series that matched the query, if any
 	result: Option<[MimirMatrixResult; 1]>,
 }
 
+/// A series of metrics returned by Mimir
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct MimirMatrixResult {
+	/// The timestamp and the value of each sample in the series
 	#[serde(rename = "value")]
 	values: Vec<(i128, String)>,
 }
@@ -102,16 +108,14 @@ pub async fn get_deployment_metric(
 	}) = serde_json::from_str::<MimirResponse>(&mimir_response)
 	else {
 		error!("Cannot parse Mimir response: {}", mimir_response);
-		return Err(ErrorType::server_error(format!(
-			"Failed to parse Mimir response"
-		)));
+		return Err(ErrorType::server_error("Failed to parse Mimir response"));
 	};
 
 	let metrics = result
 		.map(|[MimirMatrixResult { values }]| {
 			values
 				.into_iter()
-				.map(|(timestamp, metric)| DeploymentMetric {
+				.map(|(timestamp, _metric)| DeploymentMetric {
 					timestamp: OffsetDateTime::from_unix_timestamp_nanos(timestamp)
 						.unwrap_or(OffsetDateTime::UNIX_EPOCH),
 					cpu_usage: String::new(),
@@ -0,0 +1,120 @@
+use models::api::workspace::container_registry::ContainerRepositoryImageSignatureStatus;
+
+use crate::{prelude::*, routes::registry_patr_cloud::image_signature};
+
+/// Gets the digest of the manifest that a tag of a repository points to, if the
+/// tag exists.
+pub async fn get_tag_digest(
+	connection: &mut DatabaseConnection,
+	repository_id: Uuid,
+	tag: &str,
+) -> Result<Option<String>, sqlx::Error> {
+	query!(
+		r#"
+		SELECT
+			manifest_digest
+		FROM
+			container_registry_repository_tag
+		WHERE
+			repository_id = $1 AND
+			tag = $2;
+		"#,
+		repository_id as _,
+		tag,
+	)
+	.fetch_optional(&mut *connection)
+	.await
+	.map(|row| row.map(|row| row.manifest_digest))
+}
+
+/// Makes sure that an image of a repository can be deployed in a workspace. If
+/// the workspace requires signed images, the digest must be signed by a key
+/// that the workspace trusts. An image whose digest isn't known (for example,
+/// a tag that hasn't been pushed yet) can't be verified, so it is rejected.
+pub async fn ensure_image_is_trusted(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+	repository_id: Uuid,
+	digest: Option<&str>,
+) -> Result<(), ErrorType> {
+	if !image_signature::is_signature_required(connection, workspace_id).await? {
+		return Ok(());
+	}
+
+	let Some(digest) = digest else {
+		debug!(
+			"Not deploying an unknown digest of repository `{}`, since it can't be verified",
+			repository_id
+		);
+		return Err(ErrorType::ImageNotSigned);
+	};
+
+	let status = image_signature::verify_image_signature(connection, repository_id, digest).await?;
+	if !matches!(
+		status,
+		ContainerRepositoryImageSignatureStatus::Verified { .. }
+	) {
+		debug!(
+			"Not deploying `{}`, since it isn't signed by a trusted key: {:?}",
+			digest, status
+		);
+		return Err(ErrorType::ImageNotSigned);
+	}
+
+	Ok(())
+}
+
+/// Resolves a tag of a repository to the digest that it points to, and makes
+/// sure that the digest can be deployed in the workspace (see
+/// [`ensure_image_is_trusted`]). Returns [`None`] if the tag hasn't been pushed
+/// yet and the workspace doesn't require signed images.
+pub async fn get_trusted_tag_digest(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+	repository_id: Uuid,
+	tag: &str,
+) -> Result<Option<String>, ErrorType> {
+	let digest = get_tag_digest(connection, repository_id, tag).await?;
+	ensure_image_is_trusted(connection, workspace_id, repository_id, digest.as_deref()).await?;
+
+	Ok(digest)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::get_trusted_tag_digest;
+	use crate::{prelude::*, utils::test_utils};
+
+	/// The digest that the tag used by the tests points to
+	const DIGEST: &str = "sha256:ca2b0f26964cf2e80ba3e084d5983dab293fdb87485dc6445f3f7bbfc89d7459";
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_unsigned_images_are_rejected() {
+		let state = test_utils::setup_state().await;
+		let workspace = test_utils::create_workspace(&state).await;
+		let workspace_id = workspace.workspace_id;
+		let repository_id = test_utils::create_repository(&state, workspace_id, "app").await;
+		test_utils::create_tag(&state, repository_id, "latest", DIGEST).await;
+
+		let mut connection = state.database.acquire().await.unwrap();
+		let digest = get_trusted_tag_digest(&mut connection, workspace_id, repository_id, "latest")
+			.await
+			.unwrap();
+		assert_eq!(digest.as_deref(), Some(DIGEST));
+		let digest = get_trusted_tag_digest(&mut connection, workspace_id, repository_id, "stable")
+			.await
+			.unwrap();
+		assert_eq!(digest, None);
+
+		// Neither the unsigned tag nor the tag that hasn't been pushed can be
+		// verified, once the workspace requires signed images
+		test_utils::require_signed_images(&state, workspace_id).await;
+		for tag in ["latest", "stable"] {
+			assert!(matches!(
+				get_trusted_tag_digest(&mut connection, workspace_id, repository_id, tag).await,
+				Err(ErrorType::ImageNotSigned)
+			));
+		}
+	}
+}
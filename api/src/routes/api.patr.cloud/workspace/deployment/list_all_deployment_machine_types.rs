@@ -40,8 +40,8 @@ pub async fn machine_type(
 		WithId::new(
 			machine.id,
 			DeploymentMachineType {
-				cpu_count: machine.cpu_count as u16,
-				memory_count: machine.memory_count as u32,
+				cpu_count: machine.cpu_count.try_into().unwrap_or_default(),
+				memory_count: machine.memory_count.try_into().unwrap_or_default(),
 			},
 		)
 	})
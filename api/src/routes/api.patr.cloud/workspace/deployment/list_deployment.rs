@@ -1,5 +1,6 @@
 use axum::http::StatusCode;
 use models::{api::workspace::deployment::*, utils::TotalCountHeader};
+use sqlx::{QueryBuilder, Row};
 
 use crate::prelude::*;
 
@@ -10,11 +11,12 @@ pub async fn list_deployment(
 		request:
 			ProcessedApiRequest {
 				path: ListDeploymentPath { workspace_id },
-				query: Paginated {
-					data: (),
-					count,
-					page,
-				},
+				query:
+					Paginated {
+						data: ListDeploymentQuery { filter, sort },
+						count,
+						page,
+					},
 				headers:
 					ListDeploymentRequestHeaders {
 						authorization: _,
@@ -31,77 +33,112 @@ pub async fn list_deployment(
 ) -> Result<AppResponse<ListDeploymentRequest>, ErrorType> {
 	info!("Listing all deployments in workspace: {}", workspace_id);
 
-	let mut total_count = 0;
-	let deployments = query!(
+	let rows = QueryBuilder::<DatabaseType>::new(
 		r#"
 		SELECT
-			deployment.id,
-			name,
-			registry,
-			repository_id,
-			image_name,
-			image_tag,
-			status AS "status: DeploymentStatus",
-			runner,
-			machine_type,
-			current_live_digest,
-			COUNT(*) OVER() AS "total_count!"
-		FROM
-			deployment
-		INNER JOIN
-			RESOURCES_WITH_PERMISSION_FOR_LOGIN_ID($2, $3) AS resource
-		ON
-			deployment.id = resource.id
+			*,
+			COUNT(*) OVER() AS total_count
+		FROM (
+			SELECT
+				deployment.id,
+				name,
+				registry,
+				repository_id,
+				image_name,
+				image_tag,
+				status,
+				runner,
+				machine_type,
+				current_live_digest,
+				resource.created
+			FROM
+				deployment
+			INNER JOIN
+				RESOURCES_WITH_PERMISSION_FOR_LOGIN_ID("#,
+	)
+	.push_bind(user_data.login_id)
+	.push(", ")
+	.push_bind(Permission::Deployment(DeploymentPermission::View))
+	.push(
+		r#") AS resource
+			ON
+				deployment.id = resource.id
+			WHERE
+				workspace_id = "#,
+	)
+	.push_bind(workspace_id)
+	.push(
+		r#" AND
+				deployment.deleted IS NULL
+		) AS deployment
 		WHERE
-			workspace_id = $1 AND
-			deployment.deleted IS NULL
-		ORDER BY
-			resource.created DESC
-		LIMIT $4
-		OFFSET $5;
-		"#,
-		workspace_id as _,
-		user_data.login_id as _,
-		Permission::Deployment(DeploymentPermission::View) as _,
-		count as i32,
-		(count * page) as i32,
+			TRUE"#,
 	)
+	.push_list_filter(filter.as_ref(), column)
+	.push_list_sort(sort.as_ref(), "created DESC, id", column)
+	.push(" LIMIT ")
+	.push_bind(i32::try_from(count)?)
+	.push(" OFFSET ")
+	.push_bind(i32::try_from(count * page)?)
+	.build()
 	.fetch_all(&mut **database)
-	.await?
-	.into_iter()
-	.map(|row| {
-		total_count = row.total_count;
-		WithId::new(
-			row.id,
-			Deployment {
-				name: row.name,
-				registry: if row.registry == PatrRegistry.to_string() {
-					DeploymentRegistry::PatrRegistry {
-						registry: PatrRegistry,
-						repository_id: row.repository_id.unwrap().into(),
-					}
-				} else {
-					DeploymentRegistry::ExternalRegistry {
-						registry: row.registry,
-						image_name: row.image_name.unwrap(),
-					}
+	.await?;
+
+	let total_count = rows
+		.first()
+		.map(|row| row.try_get::<i64, _>("total_count"))
+		.transpose()?
+		.unwrap_or(0);
+
+	let deployments = rows
+		.into_iter()
+		.map(|row| {
+			let registry = row.try_get::<String, _>("registry")?;
+			Ok(WithId::new(
+				row.try_get::<Uuid, _>("id")?,
+				Deployment {
+					name: row.try_get("name")?,
+					registry: if registry == PatrRegistry.to_string() {
+						DeploymentRegistry::PatrRegistry {
+							registry: PatrRegistry,
+							repository_id: row
+								.try_get::<Option<Uuid>, _>("repository_id")?
+								.ok_or(ErrorType::server_error("repository_id in db is NULL"))?,
+						}
+					} else {
+						DeploymentRegistry::ExternalRegistry {
+							registry,
+							image_name: row
+								.try_get::<Option<String>, _>("image_name")?
+								.ok_or(ErrorType::server_error("image_name in db is NULL"))?,
+						}
+					},
+					image_tag: row.try_get("image_tag")?,
+					status: row.try_get("status")?,
+					runner: row.try_get("runner")?,
+					machine_type: row.try_get("machine_type")?,
+					current_live_digest: row.try_get("current_live_digest")?,
 				},
-				image_tag: row.image_tag,
-				status: row.status,
-				runner: row.runner.into(),
-				machine_type: row.machine_type.into(),
-				current_live_digest: row.current_live_digest,
-			},
-		)
-	})
-	.collect();
+			))
+		})
+		.collect::<Result<_, ErrorType>>()?;
 
 	AppResponse::builder()
 		.body(ListDeploymentResponse { deployments })
 		.headers(ListDeploymentResponseHeaders {
-			total_count: TotalCountHeader(total_count as _),
+			total_count: TotalCountHeader(total_count.try_into()?),
 		})
 		.status_code(StatusCode::OK)
 		.build()
 		.into_result()
 }
+
+/// The SQL expression of each field that the deployments can be filtered and
+/// sorted by.
+fn column(field: DeploymentListField) -> &'static str {
+	match field {
+		DeploymentListField::Name => "name",
+		DeploymentListField::Status => "status::TEXT",
+		DeploymentListField::Created => "created",
+	}
+}
@@ -3,17 +3,57 @@ use axum::Router;
 /// The history of deploys for a deployment. This includes the status of the
 /// deploy, and the time it was deployed.
 pub mod deploy_history;
+/// Redeploys the deployments that use a tag of a repository when a new image is
+/// pushed to it.
+pub mod deploy_on_push;
+/// The events of deployments, along with the helpers to record them and
+/// publish them to the event streams.
+pub mod event;
+/// Interactive terminal sessions in deployments, along with the helpers to
+/// relay them between the user and the runner.
+pub mod exec;
+/// The checks that an image must pass before it can be deployed, like the
+/// signature policy of the workspace.
+pub mod image_policy;
+/// Ports of deployments forwarded to users, along with the helpers to relay
+/// the connections between the user and the runner.
+pub mod port_forward;
+/// The revisions of a deployment's configuration, along with the helpers to
+/// record them.
+pub mod revision;
+/// The rollouts of a deployment's revisions, along with the helpers to start
+/// them.
+pub mod rollout;
+/// The updates of deployments that are sent to the runners they run on, along
+/// with the helpers to send them.
+pub mod runner_update;
+/// The state of deployments as reported by their runners, along with the
+/// helpers to record the reports.
+pub mod runtime;
 
+/// Create a deployment in the workspace.
 mod create_deployment;
+/// Delete a deployment in the workspace.
 mod delete_deployment;
+/// Get the information of a deployment, along with its running details.
 mod get_deployment_info;
+/// Get the logs of a deployment in a range of time.
 mod get_deployment_logs;
+/// Get the metrics of a deployment in a range of time.
 mod get_deployment_metric;
+/// List the machine types that a deployment can run on.
 mod list_all_deployment_machine_types;
+/// List the deployments in the workspace.
 mod list_deployment;
+/// Restart every instance of a deployment, one after the other.
+mod restart_deployment;
+/// Start a deployment that is stopped.
 mod start_deployment;
+/// Stop a running deployment.
 mod stop_deployment;
+/// Stream the logs of a deployment as they are generated.
 mod stream_deployment_logs;
+/// Update the configuration of a deployment.
 mod update_deployment;
 
 use self::{
@@ -24,6 +64,7 @@ use self::{
 	get_deployment_metric::*,
 	list_all_deployment_machine_types::*,
 	list_deployment::*,
+	restart_deployment::*,
 	start_deployment::*,
 	stop_deployment::*,
 	stream_deployment_logs::*,
@@ -43,12 +84,18 @@ Figure out how to structure:
 pub async fn setup_routes(state: &AppState) -> Router {
 	Router::new()
 		.merge(deploy_history::setup_routes(state).await)
+		.merge(event::setup_routes(state).await)
+		.merge(exec::setup_routes(state).await)
+		.merge(port_forward::setup_routes(state).await)
+		.merge(revision::setup_routes(state).await)
+		.merge(rollout::setup_routes(state).await)
 		.mount_endpoint(machine_type, state)
 		.mount_auth_endpoint(list_deployment, state)
 		.mount_auth_endpoint(create_deployment, state)
 		.mount_auth_endpoint(get_deployment_info, state)
 		.mount_auth_endpoint(start_deployment, state)
 		.mount_auth_endpoint(stop_deployment, state)
+		.mount_auth_endpoint(restart_deployment, state)
 		.mount_auth_endpoint(get_deployment_logs, state)
 		.mount_auth_endpoint(delete_deployment, state)
 		.mount_auth_endpoint(update_deployment, state)
@@ -0,0 +1,182 @@
+use axum::{http::StatusCode, response::IntoResponse};
+use axum_typed_websockets::Message;
+use futures::StreamExt;
+use models::{
+	api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceServerMsg},
+	utils::{GenericResponse, WebSocketUpgrade},
+};
+use rustis::commands::PubSubCommands;
+
+use super::SessionSlot;
+use crate::{prelude::*, routes::api_patr_cloud::workspace::deployment::runner_update};
+
+/// Route to forward a TCP connection to a port of a running container of a
+/// deployment. The connection is relayed to the runner that the deployment
+/// runs on, over Redis, and the runner connects to the port of one of the
+/// deployment's containers. Only a limited number of connections can be open
+/// for a deployment at a time, and each of them is closed once it has been
+/// open for too long.
+pub async fn forward_deployment_port(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ForwardDeploymentPortPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: ForwardDeploymentPortQuery { port },
+				headers:
+					ForwardDeploymentPortRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: WebSocketUpgrade(upgrade),
+			},
+		database,
+		redis,
+		client_ip: _,
+		config: _,
+		user_data: _,
+	}: AuthenticatedAppRequest<'_, ForwardDeploymentPortRequest>,
+) -> Result<AppResponse<ForwardDeploymentPortRequest>, ErrorType> {
+	info!("Forwarding port {} of deployment: {}", port, deployment_id);
+
+	if port == 0 {
+		return Err(ErrorType::WrongParameters);
+	}
+
+	let runner_id =
+		runner_update::get_connected_runner(database, redis, workspace_id, deployment_id).await?;
+	let slot = SessionSlot::reserve(redis, &deployment_id).await?;
+
+	let session_id = Uuid::new_v4();
+	let session_channel = super::session_channel(&workspace_id, &deployment_id, &session_id);
+	let runner_channel = format!("{}/runner/{}/stream", workspace_id, runner_id);
+
+	// Subscribe before the runner is asked to connect, so that none of the
+	// data from the container is missed
+	let mut pub_sub = redis.create_pub_sub();
+	pub_sub.subscribe(&session_channel).await?;
+	let redis = redis.clone();
+
+	AppResponse::builder()
+		.body(GenericResponse(
+			upgrade
+				.on_upgrade(move |mut websocket| async move {
+					let publish = |message: StreamRunnerDataForWorkspaceServerMsg| {
+						let redis = redis.clone();
+						let runner_channel = runner_channel.clone();
+						async move {
+							redis
+								.publish(runner_channel, serde_json::to_string(&message).unwrap())
+								.await
+								.inspect_err(|err| {
+									error!("Error relaying forwarded port to runner: {:?}", err)
+								})
+								.is_ok()
+						}
+					};
+
+					if !publish(
+						StreamRunnerDataForWorkspaceServerMsg::DeploymentPortForwardStarted {
+							id: deployment_id,
+							session_id,
+							port,
+						},
+					)
+					.await
+					{
+						_ = websocket
+							.send(Message::Item(ForwardDeploymentPortServerMsg::Closed {
+								error: Some("Failed to reach the runner".to_string()),
+							}))
+							.await;
+					} else {
+						let timeout = tokio::time::sleep(super::MAX_SESSION_DURATION);
+						tokio::pin!(timeout);
+
+						loop {
+							tokio::select! {
+								data = pub_sub.next() => {
+									let Some(Ok(data)) = data else {
+										break;
+									};
+									let Ok(message) = serde_json::from_slice::<
+										ForwardDeploymentPortServerMsg,
+									>(&data.payload)
+									.inspect_err(|err| {
+										error!("Error parsing forwarded port message: {:?}", err)
+									}) else {
+										continue;
+									};
+									let is_over =
+										matches!(message, ForwardDeploymentPortServerMsg::Closed { .. });
+									let Ok(()) = websocket
+										.send(Message::Item(message))
+										.await
+										.inspect_err(|err| {
+											debug!("Failed to send forwarded data to client: {}", err);
+										})
+									else {
+										break;
+									};
+									if is_over {
+										break;
+									}
+								}
+								message = websocket.recv() => {
+									let message = match message {
+										Some(Ok(Message::Item(
+											ForwardDeploymentPortClientMsg::Data { data },
+										))) => StreamRunnerDataForWorkspaceServerMsg::DeploymentPortForwardDataReceived {
+											id: deployment_id,
+											session_id,
+											data,
+										},
+										Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+										Some(Ok(_)) => continue,
+									};
+									if !publish(message).await {
+										break;
+									}
+								}
+								_ = &mut timeout => {
+									_ = websocket
+										.send(Message::Item(ForwardDeploymentPortServerMsg::Closed {
+											error: Some(
+												"The connection was open for too long and has been closed"
+													.to_string(),
+											),
+										}))
+										.await;
+									break;
+								}
+							}
+						}
+					}
+
+					// The runner closes its connection to the container if it's
+					// still open
+					publish(
+						StreamRunnerDataForWorkspaceServerMsg::DeploymentPortForwardClosed {
+							id: deployment_id,
+							session_id,
+						},
+					)
+					.await;
+					_ = pub_sub
+						.unsubscribe(&session_channel)
+						.await
+						.inspect_err(|err| error!("Error closing forwarded port: {:?}", err));
+					_ = websocket.send(Message::Close(None)).await;
+					_ = websocket.close().await;
+					// The slot is held for as long as the connection is open
+					drop(slot);
+				})
+				.into_response(),
+		))
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -0,0 +1,261 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::Router;
+use models::api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceClientMsg};
+use rustis::{
+	client::Client as RedisClient,
+	commands::{ExpireOption, GenericCommands, SortedSetCommands, ZAddOptions},
+};
+
+use crate::prelude::*;
+
+/// Forward a port of a running deployment to the user.
+mod forward_deployment_port;
+
+use self::forward_deployment_port::*;
+
+/// The most number of ports that can be forwarded from a deployment at the
+/// same time, across all users
+pub const MAX_SESSIONS_PER_DEPLOYMENT: usize = 10;
+
+/// The longest a forwarded connection can stay open for, after which it is
+/// closed
+pub const MAX_SESSION_DURATION: Duration = Duration::from_secs(60 * 60);
+
+#[instrument(skip(state))]
+pub async fn setup_routes(state: &AppState) -> Router {
+	Router::new().mount_auth_endpoint(forward_deployment_port, state)
+}
+
+/// The Redis channel that the messages of a forwarded connection are published
+/// to, for the user that opened it
+pub fn session_channel(workspace_id: &Uuid, deployment_id: &Uuid, session_id: &Uuid) -> String {
+	format!(
+		"{}/deployment/{}/port-forward/{}",
+		workspace_id, deployment_id, session_id
+	)
+}
+
+/// Converts a message from a runner about a forwarded connection into the
+/// message that is relayed to the user that opened the connection, along with
+/// the ID of the session. Messages that aren't about a forwarded connection are
+/// given back as they are.
+pub fn into_session_message(
+	message: StreamRunnerDataForWorkspaceClientMsg,
+) -> Result<(Uuid, ForwardDeploymentPortServerMsg), StreamRunnerDataForWorkspaceClientMsg> {
+	match message {
+		StreamRunnerDataForWorkspaceClientMsg::DeploymentPortForwardDataSent {
+			id: _,
+			session_id,
+			data,
+		} => Ok((session_id, ForwardDeploymentPortServerMsg::Data { data })),
+		StreamRunnerDataForWorkspaceClientMsg::DeploymentPortForwardEnded {
+			id: _,
+			session_id,
+			error,
+		} => Ok((session_id, ForwardDeploymentPortServerMsg::Closed { error })),
+		message => Err(message),
+	}
+}
+
+/// A forwarded connection of a deployment, counted towards the
+/// [`MAX_SESSIONS_PER_DEPLOYMENT`] that can be open at a time. The slot is
+/// freed when it is dropped, so that connections that are never upgraded to a
+/// websocket don't use up the limit.
+pub struct SessionSlot {
+	/// The Redis client used to free the slot
+	redis: RedisClient,
+	/// The key that the forwarded connections of the deployment are stored in
+	key: String,
+	/// The ID of the slot in the forwarded connections of the deployment
+	id: Uuid,
+}
+
+impl SessionSlot {
+	/// Reserves a slot for a forwarded connection of a deployment, unless all
+	/// of them are in use. Slots that have outlived the longest a connection
+	/// can stay open for are no longer counted, in case they were never freed.
+	pub async fn reserve(redis: &RedisClient, deployment_id: &Uuid) -> Result<Self, ErrorType> {
+		let now = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.unwrap_or_default();
+		let slot = Self {
+			redis: redis.clone(),
+			key: redis::keys::port_forward_sessions(deployment_id),
+			id: Uuid::new_v4(),
+		};
+
+		redis.zremrangebyscore(&slot.key, 0, now.as_secs()).await?;
+		redis
+			.zadd(
+				&slot.key,
+				(
+					(now + MAX_SESSION_DURATION).as_secs() as f64,
+					slot.id.to_string(),
+				),
+				ZAddOptions::default(),
+			)
+			.await?;
+		redis
+			.expire(
+				&slot.key,
+				MAX_SESSION_DURATION.as_secs(),
+				ExpireOption::None,
+			)
+			.await?;
+
+		// The slot is counted before checking the limit, so that two
+		// connections opened at the same time can't both take the last slot
+		if redis.zcard(&slot.key).await? > MAX_SESSIONS_PER_DEPLOYMENT {
+			return Err(ErrorType::TooManyPortForwardSessions);
+		}
+
+		Ok(slot)
+	}
+}
+
+impl Drop for SessionSlot {
+	fn drop(&mut self) {
+		let redis = self.redis.clone();
+		let key = std::mem::take(&mut self.key);
+		let id = self.id.to_string();
+		tokio::spawn(async move {
+			_ = redis
+				.zrem(&key, id)
+				.await
+				.inspect_err(|err| error!("Error freeing forwarded port slot: {:?}", err));
+		});
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use models::api::workspace::{
+		deployment::ForwardDeploymentPortServerMsg,
+		runner::StreamRunnerDataForWorkspaceClientMsg,
+	};
+	use rustis::commands::SortedSetCommands;
+
+	use super::{into_session_message, SessionSlot, MAX_SESSIONS_PER_DEPLOYMENT};
+	use crate::{prelude::*, utils::test_utils};
+
+	/// Waits for the number of forwarded connections of a deployment to reach
+	/// the given count, since slots are freed in the background
+	async fn wait_for_sessions(state: &AppState, deployment_id: &Uuid, count: usize) {
+		let key = redis::keys::port_forward_sessions(deployment_id);
+		for _ in 0..100 {
+			let sessions = state.redis.zcard(&key).await.unwrap();
+			if sessions == count {
+				return;
+			}
+			tokio::time::sleep(Duration::from_millis(10)).await;
+		}
+		panic!("the deployment never had {} forwarded connections", count);
+	}
+
+	#[test]
+	fn assert_session_messages_are_relayed() {
+		let id = Uuid::new_v4();
+		let session_id = Uuid::new_v4();
+
+		assert_eq!(
+			into_session_message(
+				StreamRunnerDataForWorkspaceClientMsg::DeploymentPortForwardDataSent {
+					id,
+					session_id,
+					data: b"hello".as_slice().into(),
+				}
+			),
+			Ok((
+				session_id,
+				ForwardDeploymentPortServerMsg::Data {
+					data: b"hello".as_slice().into(),
+				}
+			))
+		);
+		assert_eq!(
+			into_session_message(
+				StreamRunnerDataForWorkspaceClientMsg::DeploymentPortForwardEnded {
+					id,
+					session_id,
+					error: Some("connection refused".to_string()),
+				}
+			),
+			Ok((
+				session_id,
+				ForwardDeploymentPortServerMsg::Closed {
+					error: Some("connection refused".to_string()),
+				}
+			))
+		);
+	}
+
+	#[test]
+	fn assert_other_messages_are_given_back() {
+		let message = StreamRunnerDataForWorkspaceClientMsg::DeploymentExecExited {
+			id: Uuid::new_v4(),
+			session_id: Uuid::new_v4(),
+			exit_code: Some(0),
+		};
+
+		assert_eq!(into_session_message(message.clone()), Err(message));
+	}
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_sessions_are_limited_per_deployment() {
+		let state = test_utils::setup_state().await;
+		let deployment_id = Uuid::new_v4();
+
+		let mut slots = Vec::new();
+		for _ in 0..MAX_SESSIONS_PER_DEPLOYMENT {
+			slots.push(
+				SessionSlot::reserve(&state.redis, &deployment_id)
+					.await
+					.unwrap(),
+			);
+		}
+		assert_eq!(
+			SessionSlot::reserve(&state.redis, &deployment_id)
+				.await
+				.err(),
+			Some(ErrorType::TooManyPortForwardSessions)
+		);
+
+		// Other deployments have limits of their own
+		assert!(SessionSlot::reserve(&state.redis, &Uuid::new_v4())
+			.await
+			.is_ok());
+
+		// The rejected slot isn't counted
+		wait_for_sessions(&state, &deployment_id, MAX_SESSIONS_PER_DEPLOYMENT).await;
+	}
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_dropped_slots_are_freed() {
+		let state = test_utils::setup_state().await;
+		let deployment_id = Uuid::new_v4();
+
+		let mut slots = Vec::new();
+		for _ in 0..MAX_SESSIONS_PER_DEPLOYMENT {
+			slots.push(
+				SessionSlot::reserve(&state.redis, &deployment_id)
+					.await
+					.unwrap(),
+			);
+		}
+
+		// A connection that is closed, or never upgraded, frees its slot
+		slots.pop();
+		wait_for_sessions(&state, &deployment_id, MAX_SESSIONS_PER_DEPLOYMENT - 1).await;
+		assert!(SessionSlot::reserve(&state.redis, &deployment_id)
+			.await
+			.is_ok());
+
+		drop(slots);
+		wait_for_sessions(&state, &deployment_id, 0).await;
+	}
+}
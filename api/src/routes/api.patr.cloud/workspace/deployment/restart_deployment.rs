@@ -0,0 +1,160 @@
+use axum::http::StatusCode;
+use models::api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceServerMsg};
+
+use super::runner_update::{self, RunnerUpdate};
+use crate::prelude::*;
+
+/// The handler to do a rolling restart of a deployment. The runner replaces the
+/// instances of the deployment one at a time, so that the deployment keeps
+/// serving traffic while it restarts. The configuration of the deployment is
+/// not changed, so no new revision is recorded.
+pub async fn restart_deployment(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: RestartDeploymentPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					RestartDeploymentRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: RestartDeploymentRequestProcessed,
+			},
+		database,
+		redis,
+		client_ip: _,
+		config: _,
+		user_data: _,
+	}: AuthenticatedAppRequest<'_, RestartDeploymentRequest>,
+) -> Result<AppResponse<RestartDeploymentRequest>, ErrorType> {
+	info!("Restarting deployment: {}", deployment_id);
+
+	let update = get_restart_update(database, workspace_id, deployment_id).await?;
+
+	// TODO Temporary workaround until audit logs and triggers are implemented
+	runner_update::notify_runners(redis, workspace_id, vec![update]).await?;
+
+	AppResponse::builder()
+		.body(RestartDeploymentResponse)
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
+
+/// Gets the message that asks the runner of a deployment to restart it. Only
+/// deployments that are supposed to be running can be restarted.
+async fn get_restart_update(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+) -> Result<RunnerUpdate, ErrorType> {
+	let deployment = query!(
+		r#"
+		SELECT
+			runner,
+			status as "status: DeploymentStatus"
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL;
+		"#,
+		deployment_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	if deployment.status == DeploymentStatus::Stopped {
+		return Err(ErrorType::DeploymentNotRunning);
+	}
+
+	Ok(RunnerUpdate {
+		runner: deployment.runner.into(),
+		message: StreamRunnerDataForWorkspaceServerMsg::DeploymentRestarted { id: deployment_id },
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use models::api::workspace::{
+		deployment::DeploymentStatus,
+		runner::StreamRunnerDataForWorkspaceServerMsg,
+	};
+
+	use super::get_restart_update;
+	use crate::{prelude::*, utils::test_utils};
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_running_deployment_is_restarted_on_its_runner() {
+		let state = test_utils::setup_state().await;
+		let workspace = test_utils::create_workspace(&state).await;
+		let workspace_id = workspace.workspace_id;
+		let repository_id = test_utils::create_repository(&state, workspace_id, "app").await;
+		let runner_id = test_utils::create_runner(&state, workspace_id).await;
+		let deployment_id = test_utils::create_deployment(
+			&state,
+			workspace_id,
+			runner_id,
+			repository_id,
+			"latest",
+			DeploymentStatus::Running,
+			false,
+		)
+		.await;
+
+		let mut connection = state.database.acquire().await.unwrap();
+		let update = get_restart_update(&mut connection, workspace_id, deployment_id)
+			.await
+			.unwrap();
+		assert_eq!(update.runner, runner_id);
+		assert!(matches!(
+			update.message,
+			StreamRunnerDataForWorkspaceServerMsg::DeploymentRestarted { id } if id == deployment_id
+		));
+
+		// The deployment can only be restarted from its own workspace
+		assert_eq!(
+			get_restart_update(&mut connection, Uuid::new_v4(), deployment_id)
+				.await
+				.err(),
+			Some(ErrorType::ResourceDoesNotExist)
+		);
+	}
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_stopped_deployment_is_not_restarted() {
+		let state = test_utils::setup_state().await;
+		let workspace = test_utils::create_workspace(&state).await;
+		let workspace_id = workspace.workspace_id;
+		let repository_id = test_utils::create_repository(&state, workspace_id, "app").await;
+		let runner_id = test_utils::create_runner(&state, workspace_id).await;
+		let deployment_id = test_utils::create_deployment(
+			&state,
+			workspace_id,
+			runner_id,
+			repository_id,
+			"latest",
+			DeploymentStatus::Stopped,
+			false,
+		)
+		.await;
+
+		let mut connection = state.database.acquire().await.unwrap();
+		assert_eq!(
+			get_restart_update(&mut connection, workspace_id, deployment_id)
+				.await
+				.err(),
+			Some(ErrorType::DeploymentNotRunning)
+		);
+	}
+}
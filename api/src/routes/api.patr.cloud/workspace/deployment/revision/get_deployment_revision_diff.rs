@@ -0,0 +1,224 @@
+use std::collections::BTreeMap;
+
+use axum::http::StatusCode;
+use models::api::workspace::deployment::revision::*;
+use serde_json::Value;
+
+use crate::prelude::*;
+
+/// Fields of a revision that describe the state the deployment was in, rather
+/// than its configuration. These aren't included in the diff
+const IGNORED_FIELDS: [&str; 1] = ["status"];
+
+/// How deep the objects in a revision are flattened when comparing them
+const MAX_FLATTEN_DEPTH: usize = 2;
+
+/// Get the fields that changed between two revisions of a deployment. The
+/// entries of maps (like environment variables and ports) are compared
+/// individually, so that changing one environment variable doesn't show all of
+/// them as changed.
+pub async fn get_deployment_revision_diff(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: GetDeploymentRevisionDiffPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: GetDeploymentRevisionDiffQuery { from, to },
+				headers:
+					GetDeploymentRevisionDiffRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: GetDeploymentRevisionDiffRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+	}: AuthenticatedAppRequest<'_, GetDeploymentRevisionDiffRequest>,
+) -> Result<AppResponse<GetDeploymentRevisionDiffRequest>, ErrorType> {
+	info!(
+		"Getting the diff between revisions `{}` and `{}` of deployment `{}`",
+		from, to, deployment_id
+	);
+
+	// Check if deployment exists
+	query!(
+		r#"
+		SELECT
+			id
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL;
+		"#,
+		deployment_id as _,
+		workspace_id as _
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	let mut revisions = query!(
+		r#"
+		SELECT
+			revision,
+			deployment,
+			running_details
+		FROM
+			deployment_revision
+		WHERE
+			deployment_id = $1 AND
+			revision IN ($2, $3);
+		"#,
+		deployment_id as _,
+		i64::try_from(from)?,
+		i64::try_from(to)?,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| {
+		let mut fields = BTreeMap::new();
+		for json in [row.deployment, row.running_details] {
+			flatten_fields("", serde_json::from_str(&json)?, 0, &mut fields);
+		}
+		Ok((u64::try_from(row.revision).unwrap_or_default(), fields))
+	})
+	.collect::<Result<BTreeMap<_, _>, serde_json::Error>>()
+	.map_err(ErrorType::server_error)?;
+
+	let (Some(from), Some(to)) = (revisions.get(&from).cloned(), revisions.remove(&to)) else {
+		return Err(ErrorType::ResourceDoesNotExist);
+	};
+
+	AppResponse::builder()
+		.body(GetDeploymentRevisionDiffResponse {
+			changes: diff_fields(from, to),
+		})
+		.headers(())
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
+
+/// Flattens a JSON value into a map of the paths of its fields (separated by a
+/// `.`) to their values. Only the fields of the revision and the entries of the
+/// maps in it (like environment variables and ports) are flattened, so that an
+/// environment variable changing from a value to a secret is a single change.
+fn flatten_fields(prefix: &str, value: Value, depth: usize, fields: &mut BTreeMap<String, Value>) {
+	match value {
+		Value::Object(object) if depth < MAX_FLATTEN_DEPTH => {
+			for (key, value) in object {
+				let field = if prefix.is_empty() {
+					key
+				} else {
+					format!("{}.{}", prefix, key)
+				};
+				flatten_fields(&field, value, depth + 1, fields);
+			}
+		}
+		value => {
+			fields.insert(prefix.to_string(), value);
+		}
+	}
+}
+
+/// Gets the fields that were added, removed or changed between two flattened
+/// revisions.
+fn diff_fields(
+	mut from: BTreeMap<String, Value>,
+	to: BTreeMap<String, Value>,
+) -> Vec<DeploymentRevisionChange> {
+	let mut changes = Vec::new();
+	for (field, to_value) in to {
+		match from.remove(&field) {
+			Some(from_value) if from_value == to_value => (),
+			from_value => changes.push(DeploymentRevisionChange {
+				field,
+				from: from_value,
+				to: Some(to_value),
+			}),
+		}
+	}
+	changes.extend(
+		from.into_iter()
+			.map(|(field, from_value)| DeploymentRevisionChange {
+				field,
+				from: Some(from_value),
+				to: None,
+			}),
+	);
+	changes.retain(|change| !IGNORED_FIELDS.contains(&change.field.as_str()));
+	changes.sort_by(|a, b| a.field.cmp(&b.field));
+
+	changes
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+
+	use serde_json::json;
+
+	use super::{diff_fields, flatten_fields};
+
+	fn flatten(value: serde_json::Value) -> BTreeMap<String, serde_json::Value> {
+		let mut fields = BTreeMap::new();
+		flatten_fields("", value, 0, &mut fields);
+		fields
+	}
+
+	#[test]
+	fn assert_revision_diff() {
+		let from = flatten(json!({
+			"name": "api",
+			"status": "running",
+			"minHorizontalScale": 1,
+			"environmentVariables": {
+				"PORT": "8080",
+				"DEBUG": "true",
+			},
+		}));
+		let to = flatten(json!({
+			"name": "api",
+			"status": "deploying",
+			"minHorizontalScale": 2,
+			"environmentVariables": {
+				"PORT": "8080",
+				"DATABASE_URL": { "fromSecret": "secret" },
+			},
+		}));
+
+		let changes = diff_fields(from, to)
+			.into_iter()
+			.map(|change| (change.field, change.from, change.to))
+			.collect::<Vec<_>>();
+
+		assert_eq!(
+			changes,
+			vec![
+				(
+					"environmentVariables.DATABASE_URL".to_string(),
+					None,
+					Some(json!({ "fromSecret": "secret" }))
+				),
+				(
+					"environmentVariables.DEBUG".to_string(),
+					Some(json!("true")),
+					None
+				),
+				(
+					"minHorizontalScale".to_string(),
+					Some(json!(1)),
+					Some(json!(2))
+				),
+			]
+		);
+	}
+}
@@ -0,0 +1,121 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::{revision::*, *};
+
+use crate::prelude::*;
+
+/// List the revisions of a deployment's configuration, newest first.
+pub async fn list_deployment_revisions(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: ListDeploymentRevisionsPath {
+					workspace_id,
+					deployment_id,
+				},
+				query,
+				headers:
+					ListDeploymentRevisionsRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: ListDeploymentRevisionsRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+	}: AuthenticatedAppRequest<'_, ListDeploymentRevisionsRequest>,
+) -> Result<AppResponse<ListDeploymentRevisionsRequest>, ErrorType> {
+	info!("Listing deployment revisions");
+
+	// Check if deployment exists
+	query!(
+		r#"
+		SELECT
+			id
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL;
+		"#,
+		deployment_id as _,
+		workspace_id as _
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	let (cursor, backwards) = query.decode_cursor::<u64>()?;
+
+	// When going backwards, the revisions right before the cursor are fetched
+	// in the reverse order, and then sorted back into the order of the list
+	let revisions = query!(
+		r#"
+		SELECT
+			revision,
+			deployment,
+			running_details,
+			created
+		FROM (
+			SELECT
+				revision,
+				deployment,
+				running_details,
+				created
+			FROM
+				deployment_revision
+			WHERE
+				deployment_id = $1 AND
+				(
+					$2::BIGINT IS NULL OR
+					($3 AND revision > $2) OR
+					(NOT $3 AND revision < $2)
+				)
+			ORDER BY
+				CASE WHEN $3 THEN revision END ASC,
+				revision DESC
+			LIMIT $4
+		) AS page
+		ORDER BY
+			revision DESC;
+		"#,
+		deployment_id as _,
+		cursor.map(i64::try_from).transpose()?,
+		backwards,
+		i64::try_from(query.fetch_limit())?,
+	)
+	.fetch_all(&mut **database)
+	.await?
+	.into_iter()
+	.map(|row| {
+		Ok(DeploymentRevision {
+			revision: row.revision.try_into().unwrap_or_default(),
+			created: row.created,
+			deployment: serde_json::from_str::<Deployment>(&row.deployment)?,
+			running_details: serde_json::from_str::<DeploymentRunningDetails>(
+				&row.running_details,
+			)?,
+		})
+	})
+	.collect::<Result<Vec<_>, serde_json::Error>>()
+	.map_err(ErrorType::server_error)?;
+
+	let (revisions, link) = query.into_page(
+		ListDeploymentRevisionsPath {
+			workspace_id,
+			deployment_id,
+		},
+		revisions,
+		|revision| Cursor::new(&revision.revision),
+	);
+
+	AppResponse::builder()
+		.body(ListDeploymentRevisionsResponse { revisions })
+		.headers(ListDeploymentRevisionsResponseHeaders { link })
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
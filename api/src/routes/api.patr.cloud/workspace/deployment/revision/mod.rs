@@ -0,0 +1,521 @@
+use axum::Router;
+use models::{api::workspace::deployment::*, utils::StringifiedU16};
+
+use crate::prelude::*;
+
+/// Compare the configuration of a deployment between two of its revisions.
+mod get_deployment_revision_diff;
+/// List the configuration revisions of a deployment, most recent first.
+mod list_deployment_revisions;
+
+use self::{get_deployment_revision_diff::*, list_deployment_revisions::*};
+
+#[instrument(skip(state))]
+pub async fn setup_routes(state: &AppState) -> Router {
+	Router::new()
+		.mount_auth_endpoint(list_deployment_revisions, state)
+		.mount_auth_endpoint(get_deployment_revision_diff, state)
+}
+
+/// Gets the deployment and its running details, as they currently are in the
+/// database. Returns `None` if the deployment doesn't exist.
+pub async fn get_deployment_details(
+	connection: &mut DatabaseConnection,
+	deployment_id: Uuid,
+) -> Result<Option<(WithId<Deployment>, DeploymentRunningDetails)>, sqlx::Error> {
+	let ports = query!(
+		r#"
+		SELECT
+			port,
+			port_type as "port_type: ExposedPortType"
+		FROM
+			deployment_exposed_port
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _
+	)
+	.fetch_all(&mut *connection)
+	.await?
+	.into_iter()
+	.map(|row| {
+		(
+			StringifiedU16::new(row.port.try_into().unwrap_or_default()),
+			row.port_type,
+		)
+	})
+	.collect();
+
+	let environment_variables = query!(
+		r#"
+		SELECT
+			name,
+			value,
+			secret_id
+		FROM
+			deployment_environment_variable
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _
+	)
+	.fetch_all(&mut *connection)
+	.await?
+	.into_iter()
+	.filter_map(|env| match (env.value, env.secret_id) {
+		(Some(value), None) => Some((env.name, EnvironmentVariableValue::String(value))),
+		(None, Some(secret_id)) => Some((
+			env.name,
+			EnvironmentVariableValue::Secret {
+				from_secret: secret_id.into(),
+			},
+		)),
+		_ => None,
+	})
+	.collect();
+
+	let config_mounts = query!(
+		r#"
+		SELECT
+			path,
+			file
+		FROM
+			deployment_config_mounts
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _
+	)
+	.fetch_all(&mut *connection)
+	.await?
+	.into_iter()
+	.map(|mount| (mount.path, mount.file.into()))
+	.collect();
+
+	let volumes = query!(
+		r#"
+		SELECT
+			volume_id,
+			volume_mount_path
+		FROM
+			deployment_volume_mount
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_all(&mut *connection)
+	.await?
+	.into_iter()
+	.map(|row| (row.volume_id.into(), row.volume_mount_path))
+	.collect();
+
+	let details = query!(
+		r#"
+		SELECT
+			id,
+			name,
+			registry,
+			repository_id,
+			image_name,
+			image_tag,
+			status as "status: DeploymentStatus",
+			runner,
+			min_horizontal_scale,
+			max_horizontal_scale,
+			machine_type,
+			deploy_on_push,
+			startup_probe_port,
+			startup_probe_path,
+			liveness_probe_port,
+			liveness_probe_path,
+			current_live_digest,
+			rollout_strategy as "rollout_strategy: DeploymentRolloutStrategyType",
+			rollout_max_surge,
+			rollout_max_unavailable,
+			rollout_canary_percentage
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			deleted IS NULL;
+		"#,
+		deployment_id as _
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.and_then(|row| {
+		let registry = if row.registry == PatrRegistry.to_string() {
+			DeploymentRegistry::PatrRegistry {
+				registry: PatrRegistry,
+				repository_id: row.repository_id?.into(),
+			}
+		} else {
+			DeploymentRegistry::ExternalRegistry {
+				registry: row.registry,
+				image_name: row.image_name?,
+			}
+		};
+
+		let rollout_strategy = DeploymentRolloutStrategy::from_parts(
+			row.rollout_strategy,
+			row.rollout_max_surge.map(u16::try_from).transpose().ok()?,
+			row.rollout_max_unavailable
+				.map(u16::try_from)
+				.transpose()
+				.ok()?,
+			row.rollout_canary_percentage
+				.map(u8::try_from)
+				.transpose()
+				.ok()?,
+		)?;
+
+		Some((
+			WithId::new(
+				row.id,
+				Deployment {
+					name: row.name,
+					registry,
+					image_tag: row.image_tag,
+					status: row.status,
+					runner: row.runner.into(),
+					machine_type: row.machine_type.into(),
+					current_live_digest: row.current_live_digest,
+				},
+			),
+			DeploymentRunningDetails {
+				deploy_on_push: row.deploy_on_push,
+				min_horizontal_scale: u16::try_from(row.min_horizontal_scale).ok()?,
+				max_horizontal_scale: u16::try_from(row.max_horizontal_scale).ok()?,
+				ports,
+				environment_variables,
+				startup_probe: row.startup_probe_port.zip(row.startup_probe_path).map(
+					|(port, path)| DeploymentProbe {
+						port: port.try_into().unwrap_or_default(),
+						path,
+					},
+				),
+				liveness_probe: row.liveness_probe_port.zip(row.liveness_probe_path).map(
+					|(port, path)| DeploymentProbe {
+						port: port.try_into().unwrap_or_default(),
+						path,
+					},
+				),
+				config_mounts,
+				volumes,
+				rollout_strategy,
+			},
+		))
+	});
+
+	Ok(details)
+}
+
+/// Records the current configuration of a deployment as a new revision, and
+/// returns the revision number. This must be called after every change that
+/// bumps the version of the deployment, in the same transaction, since the
+/// version is used as the revision number. Recording the same version twice is
+/// an error, rather than silently keeping the configuration it was first
+/// recorded with.
+pub async fn record_revision(
+	connection: &mut DatabaseConnection,
+	deployment_id: Uuid,
+) -> Result<u64, sqlx::Error> {
+	let (deployment, running_details) = get_deployment_details(connection, deployment_id)
+		.await?
+		.ok_or(sqlx::Error::RowNotFound)?;
+
+	let revision = query!(
+		r#"
+		INSERT INTO
+			deployment_revision(
+				deployment_id,
+				revision,
+				deployment,
+				running_details,
+				created
+			)
+		SELECT
+			id,
+			version,
+			$2,
+			$3,
+			NOW()
+		FROM
+			deployment
+		WHERE
+			id = $1
+		RETURNING
+			revision;
+		"#,
+		deployment_id as _,
+		serde_json::to_string(&deployment.data)
+			.map_err(|err| sqlx::Error::Encode(Box::new(err)))?,
+		serde_json::to_string(&running_details)
+			.map_err(|err| sqlx::Error::Encode(Box::new(err)))?,
+	)
+	.fetch_one(&mut *connection)
+	.await?
+	.revision;
+
+	Ok(revision.try_into().unwrap_or_default())
+}
+
+/// Gets the running details of the latest revision of a deployment that ran
+/// the given image digest. Returns `None` if no revision ran the digest.
+pub async fn get_running_details_for_digest(
+	connection: &mut DatabaseConnection,
+	deployment_id: Uuid,
+	digest: &str,
+) -> Result<Option<DeploymentRunningDetails>, sqlx::Error> {
+	let Some(revision) = query!(
+		r#"
+		SELECT
+			running_details
+		FROM
+			deployment_revision
+		WHERE
+			deployment_id = $1 AND
+			(deployment::JSONB)->>'currentLiveDigest' = $2
+		ORDER BY
+			revision DESC
+		LIMIT 1;
+		"#,
+		deployment_id as _,
+		digest,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	else {
+		return Ok(None);
+	};
+
+	serde_json::from_str(&revision.running_details)
+		.map(Some)
+		.map_err(|err| sqlx::Error::Decode(Box::new(err)))
+}
+
+/// Restores the ports, environment variables, probes and config mounts of a
+/// deployment from the running details of one of its revisions. The rest of
+/// the running details (like the scale and the volumes) are left as they are.
+/// The version of the deployment is not bumped, so the caller must do that
+/// (and record a new revision) in the same transaction.
+pub async fn restore_running_details(
+	connection: &mut DatabaseConnection,
+	deployment_id: Uuid,
+	running_details: &DeploymentRunningDetails,
+) -> Result<(), sqlx::Error> {
+	// The probes must point to one of the ports, so the ports and the probes
+	// are only checked once both are restored
+	query!(
+		r#"
+		SET CONSTRAINTS ALL DEFERRED;
+		"#,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_exposed_port
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		INSERT INTO
+			deployment_exposed_port(
+				deployment_id,
+				port,
+				port_type
+			)
+		VALUES
+			(
+				UNNEST($1::UUID[]),
+				UNNEST($2::INTEGER[]),
+				UNNEST($3::EXPOSED_PORT_TYPE[])
+			);
+		"#,
+		&running_details
+			.ports
+			.iter()
+			.map(|_| deployment_id.into())
+			.collect::<Vec<_>>(),
+		&running_details
+			.ports
+			.iter()
+			.map(|(port, _)| port.value() as i32)
+			.collect::<Vec<_>>(),
+		&running_details
+			.ports
+			.iter()
+			.map(|(_, port_type)| port_type.to_string())
+			.collect::<Vec<String>>() as _,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		UPDATE
+			deployment
+		SET
+			startup_probe_port = $2,
+			startup_probe_path = $3,
+			startup_probe_port_type = (
+				CASE
+					WHEN $2::INTEGER IS NULL THEN
+						NULL
+					ELSE
+						'http'::EXPOSED_PORT_TYPE
+				END
+			),
+			liveness_probe_port = $4,
+			liveness_probe_path = $5,
+			liveness_probe_port_type = (
+				CASE
+					WHEN $4::INTEGER IS NULL THEN
+						NULL
+					ELSE
+						'http'::EXPOSED_PORT_TYPE
+				END
+			)
+		WHERE
+			id = $1;
+		"#,
+		deployment_id as _,
+		running_details
+			.startup_probe
+			.as_ref()
+			.map(|probe| probe.port as i32),
+		running_details
+			.startup_probe
+			.as_ref()
+			.map(|probe| probe.path.as_str()),
+		running_details
+			.liveness_probe
+			.as_ref()
+			.map(|probe| probe.port as i32),
+		running_details
+			.liveness_probe
+			.as_ref()
+			.map(|probe| probe.path.as_str()),
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		SET CONSTRAINTS ALL IMMEDIATE;
+		"#,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_environment_variable
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		INSERT INTO
+			deployment_environment_variable(
+				deployment_id,
+				name,
+				value,
+				secret_id
+			)
+		VALUES
+			(
+				UNNEST($1::UUID[]),
+				UNNEST($2::TEXT[]),
+				UNNEST($3::TEXT[]),
+				UNNEST($4::UUID[])
+			);
+		"#,
+		&running_details
+			.environment_variables
+			.iter()
+			.map(|_| deployment_id.into())
+			.collect::<Vec<sqlx::types::Uuid>>(),
+		&running_details
+			.environment_variables
+			.iter()
+			.map(|(name, _)| name.clone())
+			.collect::<Vec<_>>(),
+		&running_details
+			.environment_variables
+			.iter()
+			.map(|(_, value)| value.value().cloned())
+			.collect::<Vec<Option<String>>>() as _,
+		&running_details
+			.environment_variables
+			.iter()
+			.map(|(_, value)| value.secret_id().map(Into::into))
+			.collect::<Vec<Option<sqlx::types::Uuid>>>() as _,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		DELETE FROM
+			deployment_config_mounts
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		INSERT INTO
+			deployment_config_mounts(
+				deployment_id,
+				path,
+				file
+			)
+		VALUES
+			(
+				UNNEST($1::UUID[]),
+				UNNEST($2::TEXT[]),
+				UNNEST($3::BYTEA[])
+			);
+		"#,
+		&running_details
+			.config_mounts
+			.iter()
+			.map(|_| deployment_id.into())
+			.collect::<Vec<_>>(),
+		&running_details
+			.config_mounts
+			.iter()
+			.map(|(path, _)| path.clone())
+			.collect::<Vec<_>>(),
+		&running_details
+			.config_mounts
+			.iter()
+			.map(|(_, file)| file.to_vec())
+			.collect::<Vec<_>>(),
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
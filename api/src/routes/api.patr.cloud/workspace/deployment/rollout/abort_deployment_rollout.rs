@@ -0,0 +1,158 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::rollout::*;
+
+use super::super::{
+	image_policy,
+	revision,
+	runner_update::{self, RunnerUpdate},
+};
+use crate::prelude::*;
+
+/// Abort the canary of a deployment's current rollout. The deployment is
+/// rolled back to the stable image of the rollout, which is recorded as a new
+/// revision of the deployment.
+pub async fn abort_deployment_rollout(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: AbortDeploymentRolloutPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					AbortDeploymentRolloutRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: AbortDeploymentRolloutRequestProcessed,
+			},
+		database,
+		redis,
+		client_ip: _,
+		config: _,
+		user_data: _,
+	}: AuthenticatedAppRequest<'_, AbortDeploymentRolloutRequest>,
+) -> Result<AppResponse<AbortDeploymentRolloutRequest>, ErrorType> {
+	info!("Aborting the canary of deployment: {}", deployment_id);
+
+	let update = abort_rollout(database, workspace_id, deployment_id).await?;
+
+	// TODO Temporary workaround until audit logs and triggers are implemented
+	runner_update::notify_runners(redis, workspace_id, update.into_iter().collect()).await?;
+
+	AppResponse::builder()
+		.body(AbortDeploymentRolloutResponse)
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
+
+/// Aborts the canary of a deployment's current rollout, rolling the deployment
+/// back to the stable image of the rollout. Returns the update that needs to
+/// be sent to its runner.
+pub(super) async fn abort_rollout(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+) -> Result<Option<RunnerUpdate>, ErrorType> {
+	// The deployment is locked, so that a new revision can't start rolling out
+	// while the canary is being aborted
+	let deployment = query!(
+		r#"
+		SELECT
+			repository_id
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL
+		FOR UPDATE;
+		"#,
+		deployment_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	let rollout = super::get_latest_rollout(&mut *connection, deployment_id)
+		.await?
+		.filter(|rollout| rollout.status == DeploymentRolloutStatus::AwaitingPromotion)
+		.ok_or(ErrorType::RolloutNotAwaitingPromotion)?;
+	let stable = rollout.stable.ok_or_else(|| {
+		ErrorType::server_error(format!(
+			"rollout `{}` of deployment `{}` has no stable image",
+			rollout.revision, deployment_id
+		))
+	})?;
+
+	// The stable image may have been deployed before the workspace started
+	// requiring signed images, so the signature is checked again
+	if let Some(repository_id) = deployment.repository_id {
+		image_policy::ensure_image_is_trusted(
+			&mut *connection,
+			workspace_id,
+			repository_id.into(),
+			stable.digest.as_deref(),
+		)
+		.await?;
+	}
+
+	query!(
+		r#"
+		UPDATE
+			deployment_rollout
+		SET
+			status = 'aborted',
+			updated = NOW()
+		WHERE
+			deployment_id = $1 AND
+			revision = $2;
+		"#,
+		deployment_id as _,
+		i64::try_from(rollout.revision).unwrap_or(i64::MAX),
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		UPDATE
+			deployment
+		SET
+			image_tag = $2,
+			current_live_digest = $3,
+			status = (
+				CASE
+					WHEN status = 'stopped' THEN
+						status
+					ELSE
+						'deploying'::DEPLOYMENT_STATUS
+				END
+			),
+			version = version + 1
+		WHERE
+			id = $1;
+		"#,
+		deployment_id as _,
+		stable.image_tag,
+		stable.digest,
+	)
+	.execute(&mut *connection)
+	.await
+	.map_err(|err| match err {
+		// The digest of the stable image was removed from the deploy history
+		sqlx::Error::Database(err) if err.is_foreign_key_violation() => {
+			ErrorType::ResourceDoesNotExist
+		}
+		err => ErrorType::server_error(err),
+	})?;
+
+	let revision = revision::record_revision(&mut *connection, deployment_id).await?;
+	super::start_rollout(&mut *connection, deployment_id, revision).await?;
+
+	Ok(runner_update::get_runner_update(&mut *connection, deployment_id).await?)
+}
@@ -0,0 +1,439 @@
+use axum::Router;
+use models::api::workspace::deployment::{rollout::*, *};
+
+use crate::prelude::*;
+
+/// Abort the rollout of a deployment, rolling it back to its stable revision.
+mod abort_deployment_rollout;
+/// Promote the canary of a deployment, rolling it out to every instance.
+mod promote_deployment_rollout;
+
+use self::{abort_deployment_rollout::*, promote_deployment_rollout::*};
+
+#[instrument(skip(state))]
+pub async fn setup_routes(state: &AppState) -> Router {
+	Router::new()
+		.mount_auth_endpoint(promote_deployment_rollout, state)
+		.mount_auth_endpoint(abort_deployment_rollout, state)
+}
+
+/// Gets the rollout of the latest revision of a deployment, if any.
+pub async fn get_latest_rollout(
+	connection: &mut DatabaseConnection,
+	deployment_id: Uuid,
+) -> Result<Option<DeploymentRollout>, sqlx::Error> {
+	let rollout = query!(
+		r#"
+		SELECT
+			revision,
+			status as "status: DeploymentRolloutStatus",
+			image_tag,
+			image_digest,
+			stable_image_tag,
+			stable_image_digest,
+			created,
+			updated
+		FROM
+			deployment_rollout
+		WHERE
+			deployment_id = $1
+		ORDER BY
+			revision DESC
+		LIMIT 1;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.map(|row| DeploymentRollout {
+		revision: row.revision.try_into().unwrap_or_default(),
+		status: row.status,
+		image: DeploymentRolloutImage {
+			image_tag: row.image_tag,
+			digest: row.image_digest,
+		},
+		stable: row
+			.stable_image_tag
+			.map(|image_tag| DeploymentRolloutImage {
+				image_tag,
+				digest: row.stable_image_digest,
+			}),
+		created: row.created,
+		updated: row.updated,
+	});
+
+	Ok(rollout)
+}
+
+/// Starts the rollout of a new revision of a deployment. If the revision
+/// changes the image of the deployment, the image it was running before is
+/// kept as the stable image of the rollout. Deployments that roll out with a
+/// canary then keep running the stable image (other than on the canary) until
+/// the rollout is promoted or aborted. Any rollout of an older revision that
+/// hasn't finished yet is superseded.
+pub async fn start_rollout(
+	connection: &mut DatabaseConnection,
+	deployment_id: Uuid,
+	revision: u64,
+) -> Result<(), sqlx::Error> {
+	let Some(deployment) = query!(
+		r#"
+		SELECT
+			image_tag,
+			current_live_digest,
+			rollout_strategy as "rollout_strategy: DeploymentRolloutStrategyType"
+		FROM
+			deployment
+		WHERE
+			id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	else {
+		return Ok(());
+	};
+
+	let image = DeploymentRolloutImage {
+		image_tag: deployment.image_tag,
+		digest: deployment.current_live_digest,
+	};
+
+	// If the canary of the previous rollout was never promoted (or was
+	// aborted), the deployment is still running the stable image of that
+	// rollout, rather than its new image
+	let stable = get_latest_rollout(connection, deployment_id)
+		.await?
+		.and_then(|previous| match previous.status {
+			DeploymentRolloutStatus::AwaitingPromotion | DeploymentRolloutStatus::Aborted => {
+				previous.stable
+			}
+			DeploymentRolloutStatus::Progressing |
+			DeploymentRolloutStatus::Completed |
+			DeploymentRolloutStatus::Superseded => Some(previous.image),
+		})
+		.filter(|stable| stable != &image);
+
+	let status = if deployment.rollout_strategy == DeploymentRolloutStrategyType::Canary &&
+		stable.is_some()
+	{
+		DeploymentRolloutStatus::AwaitingPromotion
+	} else {
+		DeploymentRolloutStatus::Progressing
+	};
+
+	query!(
+		r#"
+		UPDATE
+			deployment_rollout
+		SET
+			status = 'superseded',
+			updated = NOW()
+		WHERE
+			deployment_id = $1 AND
+			status IN ('progressing', 'awaiting_promotion');
+		"#,
+		deployment_id as _,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	query!(
+		r#"
+		INSERT INTO
+			deployment_rollout(
+				deployment_id,
+				revision,
+				status,
+				image_tag,
+				image_digest,
+				stable_image_tag,
+				stable_image_digest,
+				created,
+				updated
+			)
+		VALUES
+			($1, $2, $3, $4, $5, $6, $7, NOW(), NOW());
+		"#,
+		deployment_id as _,
+		i64::try_from(revision).unwrap_or(i64::MAX),
+		status as _,
+		image.image_tag,
+		image.digest,
+		stable.as_ref().map(|stable| stable.image_tag.as_str()),
+		stable.as_ref().and_then(|stable| stable.digest.as_deref()),
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
+
+/// Completes the rollout of a deployment once its runner reports that every
+/// instance of the deployment runs the given image. Only a rollout that is in
+/// progress with that same image is completed, so a report about an older
+/// rollout (or one that is held back for promotion) doesn't complete it. The
+/// report is ignored if the deployment doesn't belong to the runner. Returns
+/// the revisions whose rollouts were completed.
+pub async fn complete_rollout(
+	connection: &mut DatabaseConnection,
+	runner_id: Uuid,
+	deployment_id: Uuid,
+	image: &DeploymentRolloutImage,
+) -> Result<Vec<u64>, sqlx::Error> {
+	let revisions = query!(
+		r#"
+		UPDATE
+			deployment_rollout
+		SET
+			status = 'completed',
+			updated = NOW()
+		WHERE
+			deployment_id = $1 AND
+			status = 'progressing' AND
+			image_tag = $3 AND
+			image_digest IS NOT DISTINCT FROM $4 AND
+			EXISTS(
+				SELECT
+					1
+				FROM
+					deployment
+				WHERE
+					id = $1 AND
+					runner = $2 AND
+					deleted IS NULL
+			)
+		RETURNING
+			revision;
+		"#,
+		deployment_id as _,
+		runner_id as _,
+		image.image_tag,
+		image.digest,
+	)
+	.fetch_all(&mut *connection)
+	.await?
+	.into_iter()
+	.map(|row| row.revision.try_into().unwrap_or_default())
+	.collect();
+
+	Ok(revisions)
+}
+
+#[cfg(test)]
+mod tests {
+	use models::api::workspace::deployment::{rollout::*, DeploymentStatus};
+
+	use super::{abort_rollout, get_latest_rollout, promote_rollout};
+	use crate::{
+		prelude::*,
+		routes::api_patr_cloud::workspace::deployment::deploy_on_push,
+		utils::test_utils,
+	};
+
+	/// The digest that the deployment runs before the canary
+	const STABLE_DIGEST: &str =
+		"sha256:0000000000000000000000000000000000000000000000000000000000000001";
+	/// The digest that the canary runs
+	const CANARY_DIGEST: &str =
+		"sha256:0000000000000000000000000000000000000000000000000000000000000002";
+
+	/// Pushes a digest to the `latest` tag of a repository, and redeploys the
+	/// deployments that use it
+	async fn push(state: &AppState, workspace_id: Uuid, repository_id: Uuid, digest: &str) {
+		test_utils::create_tag(state, repository_id, "latest", digest).await;
+
+		let mut database = state.database.begin().await.unwrap();
+		deploy_on_push::redeploy_deployments(
+			&mut database,
+			workspace_id,
+			repository_id,
+			"latest",
+			digest,
+		)
+		.await
+		.unwrap();
+		database.commit().await.unwrap();
+	}
+
+	/// Creates a deployment that rolls out with a canary, and pushes a new
+	/// image to it, so that its rollout is awaiting promotion. Returns the IDs
+	/// of the workspace and the deployment
+	async fn create_canary_rollout(state: &AppState) -> (Uuid, Uuid) {
+		let workspace = test_utils::create_workspace(state).await;
+		let workspace_id = workspace.workspace_id;
+		let repository_id = test_utils::create_repository(state, workspace_id, "app").await;
+		let runner_id = test_utils::create_runner(state, workspace_id).await;
+		let deployment_id = test_utils::create_deployment(
+			state,
+			workspace_id,
+			runner_id,
+			repository_id,
+			"latest",
+			DeploymentStatus::Running,
+			true,
+		)
+		.await;
+
+		push(state, workspace_id, repository_id, STABLE_DIGEST).await;
+
+		query!(
+			r#"
+			UPDATE
+				deployment
+			SET
+				rollout_strategy = 'canary',
+				rollout_max_surge = NULL,
+				rollout_max_unavailable = NULL,
+				rollout_canary_percentage = 10
+			WHERE
+				id = $1;
+			"#,
+			deployment_id as _,
+		)
+		.execute(&state.database)
+		.await
+		.unwrap();
+
+		push(state, workspace_id, repository_id, CANARY_DIGEST).await;
+
+		(workspace_id, deployment_id)
+	}
+
+	/// Gets the latest rollout of a deployment
+	async fn get_rollout(state: &AppState, deployment_id: Uuid) -> DeploymentRollout {
+		let mut connection = state.database.acquire().await.unwrap();
+		get_latest_rollout(&mut connection, deployment_id)
+			.await
+			.unwrap()
+			.unwrap()
+	}
+
+	/// Gets the digest that a deployment is running
+	async fn get_live_digest(state: &AppState, deployment_id: Uuid) -> Option<String> {
+		query!(
+			r#"
+			SELECT
+				current_live_digest
+			FROM
+				deployment
+			WHERE
+				id = $1;
+			"#,
+			deployment_id as _,
+		)
+		.fetch_one(&state.database)
+		.await
+		.unwrap()
+		.current_live_digest
+	}
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_canary_is_promoted() {
+		let state = test_utils::setup_state().await;
+		let (workspace_id, deployment_id) = create_canary_rollout(&state).await;
+
+		let rollout = get_rollout(&state, deployment_id).await;
+		assert_eq!(rollout.status, DeploymentRolloutStatus::AwaitingPromotion);
+		assert_eq!(rollout.image.digest.as_deref(), Some(CANARY_DIGEST));
+		assert_eq!(
+			rollout.stable.and_then(|stable| stable.digest).as_deref(),
+			Some(STABLE_DIGEST)
+		);
+
+		let mut database = state.database.begin().await.unwrap();
+		let update = promote_rollout(&mut database, workspace_id, deployment_id)
+			.await
+			.unwrap();
+		database.commit().await.unwrap();
+		assert!(update.is_some());
+
+		let promoted = get_rollout(&state, deployment_id).await;
+		assert_eq!(promoted.revision, rollout.revision);
+		assert_eq!(promoted.status, DeploymentRolloutStatus::Progressing);
+		assert_eq!(
+			get_live_digest(&state, deployment_id).await.as_deref(),
+			Some(CANARY_DIGEST)
+		);
+
+		// A rollout that isn't awaiting promotion can't be promoted or aborted
+		let mut database = state.database.begin().await.unwrap();
+		assert_eq!(
+			promote_rollout(&mut database, workspace_id, deployment_id)
+				.await
+				.err(),
+			Some(ErrorType::RolloutNotAwaitingPromotion)
+		);
+		assert_eq!(
+			abort_rollout(&mut database, workspace_id, deployment_id)
+				.await
+				.err(),
+			Some(ErrorType::RolloutNotAwaitingPromotion)
+		);
+	}
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_canary_is_aborted() {
+		let state = test_utils::setup_state().await;
+		let (workspace_id, deployment_id) = create_canary_rollout(&state).await;
+		let rollout = get_rollout(&state, deployment_id).await;
+
+		// The rollout can only be aborted from the workspace of the deployment
+		let mut database = state.database.begin().await.unwrap();
+		assert_eq!(
+			abort_rollout(&mut database, Uuid::new_v4(), deployment_id)
+				.await
+				.err(),
+			Some(ErrorType::ResourceDoesNotExist)
+		);
+		drop(database);
+
+		let mut database = state.database.begin().await.unwrap();
+		let update = abort_rollout(&mut database, workspace_id, deployment_id)
+			.await
+			.unwrap();
+		database.commit().await.unwrap();
+		assert!(update.is_some());
+
+		// The aborted rollout is kept, and the stable image is rolled out again
+		// as a new revision
+		let status = query!(
+			r#"
+			SELECT
+				status as "status: DeploymentRolloutStatus"
+			FROM
+				deployment_rollout
+			WHERE
+				deployment_id = $1 AND
+				revision = $2;
+			"#,
+			deployment_id as _,
+			i64::try_from(rollout.revision).unwrap_or(i64::MAX),
+		)
+		.fetch_one(&state.database)
+		.await
+		.unwrap()
+		.status;
+		assert_eq!(status, DeploymentRolloutStatus::Aborted);
+
+		let rolled_back = get_rollout(&state, deployment_id).await;
+		assert!(rolled_back.revision > rollout.revision);
+		assert_eq!(rolled_back.status, DeploymentRolloutStatus::Progressing);
+		assert_eq!(rolled_back.image.digest.as_deref(), Some(STABLE_DIGEST));
+		assert_eq!(
+			get_live_digest(&state, deployment_id).await.as_deref(),
+			Some(STABLE_DIGEST)
+		);
+
+		let mut database = state.database.begin().await.unwrap();
+		assert_eq!(
+			abort_rollout(&mut database, workspace_id, deployment_id)
+				.await
+				.err(),
+			Some(ErrorType::RolloutNotAwaitingPromotion)
+		);
+	}
+}
@@ -0,0 +1,98 @@
+use axum::http::StatusCode;
+use models::api::workspace::deployment::rollout::*;
+
+use super::super::runner_update::{self, RunnerUpdate};
+use crate::prelude::*;
+
+/// Promote the canary of a deployment's current rollout. The new image is then
+/// rolled out to the rest of the deployment, using a rolling update.
+pub async fn promote_deployment_rollout(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: PromoteDeploymentRolloutPath {
+					workspace_id,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					PromoteDeploymentRolloutRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: PromoteDeploymentRolloutRequestProcessed,
+			},
+		database,
+		redis,
+		client_ip: _,
+		config: _,
+		user_data: _,
+	}: AuthenticatedAppRequest<'_, PromoteDeploymentRolloutRequest>,
+) -> Result<AppResponse<PromoteDeploymentRolloutRequest>, ErrorType> {
+	info!("Promoting the canary of deployment: {}", deployment_id);
+
+	let update = promote_rollout(database, workspace_id, deployment_id).await?;
+
+	// TODO Temporary workaround until audit logs and triggers are implemented
+	runner_update::notify_runners(redis, workspace_id, update.into_iter().collect()).await?;
+
+	AppResponse::builder()
+		.body(PromoteDeploymentRolloutResponse)
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
+
+/// Promotes the canary of a deployment's current rollout, and returns the
+/// update that needs to be sent to its runner.
+pub(super) async fn promote_rollout(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+) -> Result<Option<RunnerUpdate>, ErrorType> {
+	// The deployment is locked, so that a new revision can't start rolling out
+	// while the canary is being promoted
+	query!(
+		r#"
+		SELECT
+			id
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL
+		FOR UPDATE;
+		"#,
+		deployment_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	let rollout = super::get_latest_rollout(&mut *connection, deployment_id)
+		.await?
+		.filter(|rollout| rollout.status == DeploymentRolloutStatus::AwaitingPromotion)
+		.ok_or(ErrorType::RolloutNotAwaitingPromotion)?;
+
+	query!(
+		r#"
+		UPDATE
+			deployment_rollout
+		SET
+			status = 'progressing',
+			updated = NOW()
+		WHERE
+			deployment_id = $1 AND
+			revision = $2;
+		"#,
+		deployment_id as _,
+		i64::try_from(rollout.revision).unwrap_or(i64::MAX),
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(runner_update::get_runner_update(&mut *connection, deployment_id).await?)
+}
@@ -0,0 +1,224 @@
+use models::api::workspace::{
+	deployment::DeploymentStatus,
+	runner::StreamRunnerDataForWorkspaceServerMsg,
+};
+use rustis::{
+	client::Client as RedisClient,
+	commands::{PubSubCommands, StringCommands},
+};
+
+use super::revision;
+use crate::prelude::*;
+
+/// An update to a deployment that needs to be sent to the runner the
+/// deployment runs on, once the changes are committed to the database.
+pub struct RunnerUpdate {
+	/// The runner that the deployment runs on
+	pub runner: Uuid,
+	/// The message to send to the runner
+	pub message: StreamRunnerDataForWorkspaceServerMsg,
+}
+
+/// Gets the details of a deployment, as a [`DeploymentUpdated`][1] message for
+/// the runner of the deployment.
+///
+/// [1]: StreamRunnerDataForWorkspaceServerMsg::DeploymentUpdated
+pub async fn get_runner_update(
+	connection: &mut DatabaseConnection,
+	deployment_id: Uuid,
+) -> Result<Option<RunnerUpdate>, sqlx::Error> {
+	let update = revision::get_deployment_details(connection, deployment_id)
+		.await?
+		.map(|(deployment, running_details)| RunnerUpdate {
+			runner: deployment.runner,
+			message: StreamRunnerDataForWorkspaceServerMsg::DeploymentUpdated {
+				deployment,
+				running_details,
+			},
+		});
+
+	Ok(update)
+}
+
+/// Sends the updated deployments to the runners they run on.
+pub async fn notify_runners(
+	redis: &RedisClient,
+	workspace_id: Uuid,
+	updates: Vec<RunnerUpdate>,
+) -> Result<(), ErrorType> {
+	for RunnerUpdate { runner, message } in updates {
+		redis
+			.publish(
+				format!("{}/runner/{}/stream", workspace_id, runner),
+				serde_json::to_string(&message)?,
+			)
+			.await?;
+	}
+
+	Ok(())
+}
+
+/// Gets the runner that a deployment runs on, for a session (such as a
+/// terminal or a forwarded port) that is relayed to the runner. The deployment
+/// must not be stopped, and its runner must be connected to the API.
+pub async fn get_connected_runner(
+	connection: &mut DatabaseConnection,
+	redis: &RedisClient,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+) -> Result<Uuid, ErrorType> {
+	let deployment = query!(
+		r#"
+		SELECT
+			runner,
+			status as "status: DeploymentStatus"
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL;
+		"#,
+		deployment_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	if deployment.status == DeploymentStatus::Stopped {
+		return Err(ErrorType::DeploymentNotRunning);
+	}
+
+	let runner_id = Uuid::from(deployment.runner);
+	let connected = redis
+		.get::<_, Option<String>>(redis::keys::runner_connection_lock(&runner_id))
+		.await?
+		.is_some();
+	if !connected {
+		return Err(ErrorType::RunnerNotConnected);
+	}
+
+	Ok(runner_id)
+}
+
+#[cfg(test)]
+mod tests {
+	use models::api::workspace::deployment::DeploymentStatus;
+	use rustis::commands::{GenericCommands, StringCommands};
+
+	use super::get_connected_runner;
+	use crate::{prelude::*, utils::test_utils};
+
+	/// Creates a workspace with a deployment with the given status. Returns the
+	/// IDs of the workspace, the runner the deployment runs on and the
+	/// deployment
+	async fn create_deployment(state: &AppState, status: DeploymentStatus) -> (Uuid, Uuid, Uuid) {
+		let workspace_id = test_utils::create_workspace(state).await.workspace_id;
+		let repository_id = test_utils::create_repository(state, workspace_id, "app").await;
+		let runner_id = test_utils::create_runner(state, workspace_id).await;
+		let deployment_id = test_utils::create_deployment(
+			state,
+			workspace_id,
+			runner_id,
+			repository_id,
+			"latest",
+			status,
+			false,
+		)
+		.await;
+
+		(workspace_id, runner_id, deployment_id)
+	}
+
+	/// Marks a runner as connected to the API, the way the runner stream does
+	async fn connect_runner(state: &AppState, runner_id: Uuid) {
+		state
+			.redis
+			.set(redis::keys::runner_connection_lock(&runner_id), "test")
+			.await
+			.unwrap();
+	}
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_sessions_need_a_connected_runner() {
+		let state = test_utils::setup_state().await;
+		let (workspace_id, runner_id, deployment_id) =
+			create_deployment(&state, DeploymentStatus::Running).await;
+		let mut connection = state.database.acquire().await.unwrap();
+
+		assert_eq!(
+			get_connected_runner(&mut connection, &state.redis, workspace_id, deployment_id)
+				.await
+				.err(),
+			Some(ErrorType::RunnerNotConnected)
+		);
+
+		connect_runner(&state, runner_id).await;
+		assert_eq!(
+			get_connected_runner(&mut connection, &state.redis, workspace_id, deployment_id)
+				.await
+				.ok(),
+			Some(runner_id)
+		);
+
+		state
+			.redis
+			.del(redis::keys::runner_connection_lock(&runner_id))
+			.await
+			.unwrap();
+	}
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_stopped_deployments_have_no_sessions() {
+		let state = test_utils::setup_state().await;
+		let (workspace_id, runner_id, deployment_id) =
+			create_deployment(&state, DeploymentStatus::Stopped).await;
+		let mut connection = state.database.acquire().await.unwrap();
+		connect_runner(&state, runner_id).await;
+
+		assert_eq!(
+			get_connected_runner(&mut connection, &state.redis, workspace_id, deployment_id)
+				.await
+				.err(),
+			Some(ErrorType::DeploymentNotRunning)
+		);
+
+		state
+			.redis
+			.del(redis::keys::runner_connection_lock(&runner_id))
+			.await
+			.unwrap();
+	}
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_deployments_of_other_workspaces_have_no_sessions() {
+		let state = test_utils::setup_state().await;
+		let (_, runner_id, deployment_id) =
+			create_deployment(&state, DeploymentStatus::Running).await;
+		let other_workspace_id = test_utils::create_workspace(&state).await.workspace_id;
+		let mut connection = state.database.acquire().await.unwrap();
+		connect_runner(&state, runner_id).await;
+
+		assert_eq!(
+			get_connected_runner(
+				&mut connection,
+				&state.redis,
+				other_workspace_id,
+				deployment_id
+			)
+			.await
+			.err(),
+			Some(ErrorType::ResourceDoesNotExist)
+		);
+
+		state
+			.redis
+			.del(redis::keys::runner_connection_lock(&runner_id))
+			.await
+			.unwrap();
+	}
+}
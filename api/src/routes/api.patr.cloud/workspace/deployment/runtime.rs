@@ -0,0 +1,611 @@
+use models::api::workspace::{
+	deployment::{event::*, runtime::*, *},
+	runner::StreamRunnerDataForWorkspaceClientMsg,
+};
+use time::OffsetDateTime;
+
+use super::{event::add_event, rollout};
+use crate::prelude::*;
+
+/// The number of events that are returned along with the runtime of a
+/// deployment
+const RECENT_EVENTS_LIMIT: i64 = 10;
+
+/// Gets the state of a deployment as last reported by its runner, along with
+/// its latest events. This is `None` if the runner hasn't reported on the
+/// deployment yet.
+pub async fn get_runtime(
+	connection: &mut DatabaseConnection,
+	deployment_id: Uuid,
+) -> Result<Option<DeploymentRuntime>, sqlx::Error> {
+	let Some(runtime) = query!(
+		r#"
+		SELECT
+			desired_replicas,
+			ready_replicas,
+			updated_replicas,
+			last_error,
+			last_error_occurred,
+			reported
+		FROM
+			deployment_runtime
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	else {
+		return Ok(None);
+	};
+
+	let recent_events = query!(
+		r#"
+		SELECT
+			id,
+			kind as "kind: DeploymentEventKind",
+			container,
+			message,
+			timestamp
+		FROM
+			deployment_event
+		WHERE
+			deployment_id = $1
+		ORDER BY
+			timestamp DESC,
+			id DESC
+		LIMIT $2;
+		"#,
+		deployment_id as _,
+		RECENT_EVENTS_LIMIT,
+	)
+	.fetch_all(&mut *connection)
+	.await?
+	.into_iter()
+	.map(|row| DeploymentEvent {
+		id: row.id.into(),
+		kind: row.kind,
+		container: row.container,
+		message: row.message,
+		timestamp: row.timestamp,
+	})
+	.collect();
+
+	Ok(Some(DeploymentRuntime {
+		replicas: DeploymentReplicaCounts {
+			desired: runtime.desired_replicas.try_into().unwrap_or_default(),
+			ready: runtime.ready_replicas.try_into().unwrap_or_default(),
+			updated: runtime.updated_replicas.try_into().unwrap_or_default(),
+		},
+		reported: runtime.reported,
+		last_error: runtime
+			.last_error
+			.zip(runtime.last_error_occurred)
+			.map(|(message, occurred)| DeploymentReconcileError { message, occurred }),
+		recent_events,
+	}))
+}
+
+/// Records a message that a runner sent about one of its deployments, and
+/// returns the events of the deployment that were recorded because of it.
+/// Messages about deployments that don't run on the runner are ignored.
+///
+/// A status report updates the status of the deployment (unless it has been
+/// stopped) and clears the last reconcile error, since the runner only reports
+/// on a deployment after reconciling it. A reconcile failure marks the
+/// deployment as errored until the runner reports on it again. Once the
+/// deployment has rolled out, the rollout of its image is completed.
+///
+/// A runner retries a failing reconcile every few seconds, so a reconcile
+/// failure is only recorded as an event if the error is different from the
+/// last one.
+pub async fn record_runner_message(
+	connection: &mut DatabaseConnection,
+	runner_id: Uuid,
+	message: StreamRunnerDataForWorkspaceClientMsg,
+) -> Result<Vec<DeploymentEvent>, sqlx::Error> {
+	let deployment_id = message.deployment_id();
+
+	let Some(deployment) = query!(
+		r#"
+		SELECT
+			status as "status: DeploymentStatus"
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			runner = $2 AND
+			deleted IS NULL
+		FOR UPDATE;
+		"#,
+		deployment_id as _,
+		runner_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	else {
+		warn!(
+			"Runner `{}` sent a message about deployment `{}`, which it doesn't run",
+			runner_id, deployment_id
+		);
+		return Ok(Vec::new());
+	};
+
+	let previous = query!(
+		r#"
+		SELECT
+			desired_replicas,
+			last_error
+		FROM
+			deployment_runtime
+		WHERE
+			deployment_id = $1;
+		"#,
+		deployment_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?;
+
+	let now = OffsetDateTime::now_utc();
+	let mut events = Vec::new();
+
+	match message {
+		StreamRunnerDataForWorkspaceClientMsg::DeploymentStatusReported {
+			id: _,
+			status,
+			replicas,
+		} => {
+			// A stopped deployment stays stopped, even if the runner hasn't
+			// gotten around to stopping it yet
+			if deployment.status != DeploymentStatus::Stopped {
+				query!(
+					r#"
+					UPDATE
+						deployment
+					SET
+						status = $2
+					WHERE
+						id = $1;
+					"#,
+					deployment_id as _,
+					status as _,
+				)
+				.execute(&mut *connection)
+				.await?;
+			}
+
+			query!(
+				r#"
+				INSERT INTO
+					deployment_runtime(
+						deployment_id,
+						desired_replicas,
+						ready_replicas,
+						updated_replicas,
+						last_error,
+						last_error_occurred,
+						reported
+					)
+				VALUES
+					($1, $2, $3, $4, NULL, NULL, $5)
+				ON CONFLICT(deployment_id) DO UPDATE SET
+					desired_replicas = EXCLUDED.desired_replicas,
+					ready_replicas = EXCLUDED.ready_replicas,
+					updated_replicas = EXCLUDED.updated_replicas,
+					last_error = NULL,
+					last_error_occurred = NULL,
+					reported = EXCLUDED.reported;
+				"#,
+				deployment_id as _,
+				replicas.desired as i32,
+				replicas.ready as i32,
+				replicas.updated as i32,
+				now,
+			)
+			.execute(&mut *connection)
+			.await?;
+
+			if let Some(previous) =
+				previous.filter(|previous| previous.desired_replicas != i32::from(replicas.desired))
+			{
+				events.push(
+					add_event(
+						connection,
+						deployment_id,
+						DeploymentEventKind::Scaled,
+						None,
+						Some(format!(
+							"Scaled from {} to {} instances",
+							previous.desired_replicas, replicas.desired
+						)),
+						now,
+					)
+					.await?,
+				);
+			}
+		}
+		StreamRunnerDataForWorkspaceClientMsg::DeploymentRolledOut { id: _, image } => {
+			let completed =
+				rollout::complete_rollout(connection, runner_id, deployment_id, &image).await?;
+
+			for revision in completed {
+				events.push(
+					add_event(
+						connection,
+						deployment_id,
+						DeploymentEventKind::RolloutCompleted,
+						None,
+						Some(format!("Revision {} rolled out to all instances", revision)),
+						now,
+					)
+					.await?,
+				);
+			}
+		}
+		StreamRunnerDataForWorkspaceClientMsg::DeploymentReconcileFailed { id: _, error } => {
+			if deployment.status != DeploymentStatus::Stopped {
+				query!(
+					r#"
+					UPDATE
+						deployment
+					SET
+						status = 'errored'
+					WHERE
+						id = $1;
+					"#,
+					deployment_id as _,
+				)
+				.execute(&mut *connection)
+				.await?;
+			}
+
+			query!(
+				r#"
+				INSERT INTO
+					deployment_runtime(
+						deployment_id,
+						desired_replicas,
+						ready_replicas,
+						updated_replicas,
+						last_error,
+						last_error_occurred,
+						reported
+					)
+				VALUES
+					($1, 0, 0, 0, $2, $3, $3)
+				ON CONFLICT(deployment_id) DO UPDATE SET
+					last_error = EXCLUDED.last_error,
+					last_error_occurred = EXCLUDED.last_error_occurred,
+					reported = EXCLUDED.reported;
+				"#,
+				deployment_id as _,
+				&error,
+				now,
+			)
+			.execute(&mut *connection)
+			.await?;
+
+			let is_repeated = previous
+				.and_then(|previous| previous.last_error)
+				.is_some_and(|last_error| last_error == error);
+			if !is_repeated {
+				events.push(
+					add_event(
+						connection,
+						deployment_id,
+						DeploymentEventKind::ReconcileFailed,
+						None,
+						Some(error),
+						now,
+					)
+					.await?,
+				);
+			}
+		}
+		StreamRunnerDataForWorkspaceClientMsg::DeploymentContainerEventOccurred {
+			id: _,
+			event,
+		} => {
+			events.push(
+				add_event(
+					connection,
+					deployment_id,
+					event.kind.into(),
+					Some(event.container).filter(|container| !container.trim().is_empty()),
+					event.message,
+					event.timestamp,
+				)
+				.await?,
+			);
+		}
+		// Terminal sessions and forwarded ports are relayed to the user that
+		// opened them, and aren't recorded
+		StreamRunnerDataForWorkspaceClientMsg::DeploymentExecOutputSent { .. } |
+		StreamRunnerDataForWorkspaceClientMsg::DeploymentExecExited { .. } |
+		StreamRunnerDataForWorkspaceClientMsg::DeploymentExecFailed { .. } |
+		StreamRunnerDataForWorkspaceClientMsg::DeploymentPortForwardDataSent { .. } |
+		StreamRunnerDataForWorkspaceClientMsg::DeploymentPortForwardEnded { .. } => (),
+	}
+
+	Ok(events)
+}
+
+/// Marks the deployments of a runner as unreachable, once the runner has
+/// disconnected. Deployments that aren't supposed to be running are left as
+/// they are. The runner reports the actual status of the deployments once it
+/// reconnects.
+pub async fn mark_runner_unreachable(
+	connection: &mut DatabaseConnection,
+	runner_id: Uuid,
+) -> Result<(), sqlx::Error> {
+	query!(
+		r#"
+		UPDATE
+			deployment
+		SET
+			status = 'unreachable'
+		WHERE
+			runner = $1 AND
+			deleted IS NULL AND
+			status IN ('deploying', 'running', 'errored');
+		"#,
+		runner_id as _,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use models::api::workspace::{
+		deployment::{
+			rollout::{DeploymentRolloutImage, DeploymentRolloutStatus},
+			runtime::*,
+			DeploymentStatus,
+		},
+		runner::StreamRunnerDataForWorkspaceClientMsg,
+	};
+	use time::OffsetDateTime;
+
+	use super::{get_runtime, record_runner_message};
+	use crate::{
+		prelude::*,
+		routes::api_patr_cloud::workspace::deployment::{deploy_on_push, rollout},
+		utils::test_utils,
+	};
+
+	/// The digest that is pushed by the tests
+	const DIGEST: &str = "sha256:0000000000000000000000000000000000000000000000000000000000000001";
+
+	/// The replica counts of a deployment that has rolled out
+	const REPLICAS: DeploymentReplicaCounts = DeploymentReplicaCounts {
+		desired: 1,
+		ready: 1,
+		updated: 1,
+	};
+
+	/// Records a message from a runner, in a transaction of its own
+	async fn record(
+		state: &AppState,
+		runner_id: Uuid,
+		message: StreamRunnerDataForWorkspaceClientMsg,
+	) {
+		let mut database = state.database.begin().await.unwrap();
+		record_runner_message(&mut database, runner_id, message)
+			.await
+			.unwrap();
+		database.commit().await.unwrap();
+	}
+
+	/// Gets the status of a deployment
+	async fn get_status(state: &AppState, deployment_id: Uuid) -> DeploymentStatus {
+		query!(
+			r#"
+			SELECT
+				status as "status: DeploymentStatus"
+			FROM
+				deployment
+			WHERE
+				id = $1;
+			"#,
+			deployment_id as _,
+		)
+		.fetch_one(&state.database)
+		.await
+		.unwrap()
+		.status
+	}
+
+	/// Creates a workspace with two runners, and a deployment with the given
+	/// status on the first one. Returns the IDs of the workspace, the
+	/// repository of the deployment, the runner it runs on, the other runner
+	/// and the deployment
+	async fn create_deployment(
+		state: &AppState,
+		status: DeploymentStatus,
+	) -> (Uuid, Uuid, Uuid, Uuid, Uuid) {
+		let workspace = test_utils::create_workspace(state).await;
+		let workspace_id = workspace.workspace_id;
+		let repository_id = test_utils::create_repository(state, workspace_id, "app").await;
+		let runner_id = test_utils::create_runner(state, workspace_id).await;
+		let other_runner_id = test_utils::create_runner(state, workspace_id).await;
+		let deployment_id = test_utils::create_deployment(
+			state,
+			workspace_id,
+			runner_id,
+			repository_id,
+			"latest",
+			status,
+			true,
+		)
+		.await;
+
+		(
+			workspace_id,
+			repository_id,
+			runner_id,
+			other_runner_id,
+			deployment_id,
+		)
+	}
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_reports_from_other_runners_are_ignored() {
+		let state = test_utils::setup_state().await;
+		let (_, _, runner_id, other_runner_id, deployment_id) =
+			create_deployment(&state, DeploymentStatus::Deploying).await;
+
+		record(
+			&state,
+			other_runner_id,
+			StreamRunnerDataForWorkspaceClientMsg::DeploymentStatusReported {
+				id: deployment_id,
+				status: DeploymentStatus::Running,
+				replicas: REPLICAS,
+			},
+		)
+		.await;
+		record(
+			&state,
+			other_runner_id,
+			StreamRunnerDataForWorkspaceClientMsg::DeploymentReconcileFailed {
+				id: deployment_id,
+				error: String::from("failed"),
+			},
+		)
+		.await;
+		record(
+			&state,
+			other_runner_id,
+			StreamRunnerDataForWorkspaceClientMsg::DeploymentContainerEventOccurred {
+				id: deployment_id,
+				event: DeploymentContainerEvent {
+					container: String::from("app"),
+					kind: DeploymentContainerEventKind::Crashed,
+					message: None,
+					timestamp: OffsetDateTime::now_utc(),
+				},
+			},
+		)
+		.await;
+
+		let mut connection = state.database.acquire().await.unwrap();
+		assert_eq!(
+			get_status(&state, deployment_id).await,
+			DeploymentStatus::Deploying
+		);
+		assert_eq!(
+			get_runtime(&mut connection, deployment_id).await.unwrap(),
+			None
+		);
+
+		// The runner that the deployment runs on is listened to
+		record(
+			&state,
+			runner_id,
+			StreamRunnerDataForWorkspaceClientMsg::DeploymentStatusReported {
+				id: deployment_id,
+				status: DeploymentStatus::Running,
+				replicas: REPLICAS,
+			},
+		)
+		.await;
+
+		assert_eq!(
+			get_status(&state, deployment_id).await,
+			DeploymentStatus::Running
+		);
+		assert_eq!(
+			get_runtime(&mut connection, deployment_id)
+				.await
+				.unwrap()
+				.map(|runtime| runtime.replicas),
+			Some(REPLICAS)
+		);
+	}
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_stopped_deployment_stays_stopped() {
+		let state = test_utils::setup_state().await;
+		let (_, _, runner_id, _, deployment_id) =
+			create_deployment(&state, DeploymentStatus::Stopped).await;
+
+		record(
+			&state,
+			runner_id,
+			StreamRunnerDataForWorkspaceClientMsg::DeploymentStatusReported {
+				id: deployment_id,
+				status: DeploymentStatus::Running,
+				replicas: REPLICAS,
+			},
+		)
+		.await;
+		record(
+			&state,
+			runner_id,
+			StreamRunnerDataForWorkspaceClientMsg::DeploymentReconcileFailed {
+				id: deployment_id,
+				error: String::from("failed"),
+			},
+		)
+		.await;
+
+		assert_eq!(
+			get_status(&state, deployment_id).await,
+			DeploymentStatus::Stopped
+		);
+	}
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_rollout_is_only_completed_by_its_runner() {
+		let state = test_utils::setup_state().await;
+		let (workspace_id, repository_id, runner_id, other_runner_id, deployment_id) =
+			create_deployment(&state, DeploymentStatus::Running).await;
+
+		test_utils::create_tag(&state, repository_id, "latest", DIGEST).await;
+		let mut database = state.database.begin().await.unwrap();
+		deploy_on_push::redeploy_deployments(
+			&mut database,
+			workspace_id,
+			repository_id,
+			"latest",
+			DIGEST,
+		)
+		.await
+		.unwrap();
+		database.commit().await.unwrap();
+
+		let rolled_out = || StreamRunnerDataForWorkspaceClientMsg::DeploymentRolledOut {
+			id: deployment_id,
+			image: DeploymentRolloutImage {
+				image_tag: String::from("latest"),
+				digest: Some(DIGEST.to_string()),
+			},
+		};
+
+		let mut connection = state.database.acquire().await.unwrap();
+		record(&state, other_runner_id, rolled_out()).await;
+		assert_eq!(
+			rollout::get_latest_rollout(&mut connection, deployment_id)
+				.await
+				.unwrap()
+				.map(|rollout| rollout.status),
+			Some(DeploymentRolloutStatus::Progressing)
+		);
+
+		record(&state, runner_id, rolled_out()).await;
+		assert_eq!(
+			rollout::get_latest_rollout(&mut connection, deployment_id)
+				.await
+				.unwrap()
+				.map(|rollout| rollout.status),
+			Some(DeploymentRolloutStatus::Completed)
+		);
+	}
+}
@@ -15,7 +15,7 @@ pub async fn start_deployment(
 					workspace_id: _,
 					deployment_id,
 				},
-				query: StartDeploymentQuery { force_restart },
+				query: StartDeploymentQuery { force_restart: _ },
 				headers:
 					StartDeploymentRequestHeaders {
 						authorization: _,
@@ -26,15 +26,15 @@ pub async fn start_deployment(
 		database,
 		redis: _,
 		client_ip: _,
-		config,
-		user_data,
+		config: _,
+		user_data: _,
 	}: AuthenticatedAppRequest<'_, StartDeploymentRequest>,
 ) -> Result<AppResponse<StartDeploymentRequest>, ErrorType> {
 	info!("Starting deployment: {}", deployment_id);
 
 	let now = OffsetDateTime::now_utc();
 
-	let (registry, image_tag, region) = query!(
+	let (registry, _image_tag, _region) = query!(
 		r#"
 		SELECT
 			registry,
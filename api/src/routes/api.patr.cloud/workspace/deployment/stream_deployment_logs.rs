@@ -15,15 +15,19 @@ use tokio_tungstenite::tungstenite::{client::IntoClientRequest, Message as RawMe
 
 use crate::prelude::*;
 
+/// A message sent by Loki while tailing logs
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct LokiResponse {
+	/// The logs that were generated since the last message
 	streams: LokiStreams,
 }
 
+/// A stream of logs sent by Loki while tailing logs
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct LokiStreams {
+	/// The timestamp (in nanoseconds) and the line of each log in the stream
 	values: Vec<(i128, String)>,
 }
 
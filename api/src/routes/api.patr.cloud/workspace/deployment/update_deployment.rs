@@ -1,19 +1,20 @@
 use axum::http::StatusCode;
 use models::api::workspace::deployment::*;
 
+use super::{image_policy, revision, rollout};
 use crate::prelude::*;
 
 /// Update deployment details. This endpoint is used to update the deployment
 /// details. The deployment details that can be updated are the name, machine
 /// type, deploy on push, min horizontal scale, max horizontal scale, ports,
-/// environment variables, startup probe, liveness probe, config mounts, and
-/// volumes. At least one of the values must be updated.
+/// environment variables, startup probe, liveness probe, config mounts,
+/// volumes, and rollout strategy. At least one of the values must be updated.
 pub async fn update_deployment(
 	AuthenticatedAppRequest {
 		request:
 			ProcessedApiRequest {
 				path: UpdateDeploymentPath {
-					workspace_id: _,
+					workspace_id,
 					deployment_id,
 				},
 				query: (),
@@ -21,6 +22,7 @@ pub async fn update_deployment(
 					UpdateDeploymentRequestHeaders {
 						authorization: _,
 						user_agent: _,
+						if_match,
 					},
 				body:
 					UpdateDeploymentRequestProcessed {
@@ -36,6 +38,7 @@ pub async fn update_deployment(
 						liveness_probe,
 						config_mounts,
 						volumes,
+						rollout_strategy,
 					},
 			},
 		database,
@@ -62,6 +65,7 @@ pub async fn update_deployment(
 		.or(liveness_probe.as_ref().map(|_| 0))
 		.or(config_mounts.as_ref().map(|_| 0))
 		.or(volumes.as_ref().map(|_| 0))
+		.or(rollout_strategy.as_ref().map(|_| 0))
 		.is_none()
 	{
 		debug!(
@@ -71,15 +75,24 @@ pub async fn update_deployment(
 		return Err(ErrorType::WrongParameters);
 	}
 
-	query!(
+	if let Some(rollout_strategy) = rollout_strategy.filter(|strategy| !strategy.is_valid()) {
+		debug!("Invalid rollout strategy: {:?}", rollout_strategy);
+		return Err(ErrorType::WrongParameters);
+	}
+
+	let deployment = query!(
 		r#"
 		SELECT
-			id
+			version,
+			repository_id,
+			image_tag,
+			current_live_digest
 		FROM
 			deployment
 		WHERE
 			id = $1 AND
-			deleted IS NULL;
+			deleted IS NULL
+		FOR UPDATE;
 		"#,
 		deployment_id as _,
 	)
@@ -87,6 +100,33 @@ pub async fn update_deployment(
 	.await?
 	.ok_or(ErrorType::ResourceDoesNotExist)?;
 
+	if !ResourceVersion(deployment.version).satisfies(if_match.as_ref()) {
+		debug!(
+			"Deployment `{}` has been modified since it was last fetched",
+			deployment_id
+		);
+		return Err(ErrorType::PreconditionFailed);
+	}
+
+	// The updated deployment is rolled out with the image it runs, which may
+	// have been deployed before the workspace started requiring signed images
+	if let Some(repository_id) = deployment.repository_id {
+		let repository_id = repository_id.into();
+		let digest = match deployment.current_live_digest {
+			Some(digest) => Some(digest),
+			None => {
+				image_policy::get_tag_digest(database, repository_id, &deployment.image_tag).await?
+			}
+		};
+		image_policy::ensure_image_is_trusted(
+			database,
+			workspace_id,
+			repository_id,
+			digest.as_deref(),
+		)
+		.await?;
+	}
+
 	// BEGIN DEFERRED CONSTRAINT
 	query!(
 		r#"
@@ -209,20 +249,58 @@ pub async fn update_deployment(
 					ELSE
 						'http'::EXPOSED_PORT_TYPE
 				END
-			)
+			),
+			rollout_strategy = COALESCE($11, rollout_strategy),
+			rollout_max_surge = (
+				CASE
+					WHEN $11 IS NULL THEN
+						rollout_max_surge
+					ELSE
+						$12
+				END
+			),
+			rollout_max_unavailable = (
+				CASE
+					WHEN $11 IS NULL THEN
+						rollout_max_unavailable
+					ELSE
+						$13
+				END
+			),
+			rollout_canary_percentage = (
+				CASE
+					WHEN $11 IS NULL THEN
+						rollout_canary_percentage
+					ELSE
+						$14
+				END
+			),
+			version = version + 1
 		WHERE
-			id = $11;
+			id = $15;
 		"#,
 		name as _,
 		machine_type as _,
 		deploy_on_push,
 		runner as _,
-		min_horizontal_scale.map(|v| v as i16),
-		max_horizontal_scale.map(|v| v as i16),
+		min_horizontal_scale.map(i16::try_from).transpose()?,
+		max_horizontal_scale.map(i16::try_from).transpose()?,
 		startup_probe.as_ref().map(|probe| probe.port as i32),
 		startup_probe.as_ref().map(|probe| probe.path.as_str()),
 		liveness_probe.as_ref().map(|probe| probe.port as i32),
 		liveness_probe.as_ref().map(|probe| probe.path.as_str()),
+		rollout_strategy.map(|strategy| strategy.strategy_type()) as _,
+		rollout_strategy
+			.and_then(|strategy| strategy.max_surge())
+			.map(i16::try_from)
+			.transpose()?,
+		rollout_strategy
+			.and_then(|strategy| strategy.max_unavailable())
+			.map(i16::try_from)
+			.transpose()?,
+		rollout_strategy
+			.and_then(|strategy| strategy.canary_percentage())
+			.map(|percentage| percentage as i16),
 		deployment_id as _
 	)
 	.execute(&mut **database)
@@ -385,6 +463,9 @@ pub async fn update_deployment(
 		})?;
 	}
 
+	let revision = revision::record_revision(database, deployment_id).await?;
+	rollout::start_rollout(database, deployment_id, revision).await?;
+
 	AppResponse::builder()
 		.body(UpdateDeploymentResponse)
 		.headers(())
@@ -18,6 +18,7 @@ pub async fn setup_routes(state: &AppState) -> Router {
 		.mount_auth_endpoint(verify_domain_in_workspace, state)
 }
 
+/// The handler to check if a domain is a personal domain of the user.
 async fn is_domain_personal(
 	AppRequest {
 		request: ProcessedApiRequest {
@@ -47,6 +48,7 @@ async fn is_domain_personal(
 		.into_result()
 }
 
+/// The handler to add a DNS record to a domain in the workspace.
 async fn add_dns_record(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -74,6 +76,7 @@ async fn add_dns_record(
 		.into_result()
 }
 
+/// The handler to add a domain to the workspace.
 async fn add_domain_to_workspace(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -101,6 +104,7 @@ async fn add_domain_to_workspace(
 		.into_result()
 }
 
+/// The handler to delete a DNS record of a domain in the workspace.
 async fn delete_dns_record(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -128,6 +132,7 @@ async fn delete_dns_record(
 		.into_result()
 }
 
+/// The handler to delete a domain in the workspace.
 async fn delete_domain_in_workspace(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -155,6 +160,7 @@ async fn delete_domain_in_workspace(
 		.into_result()
 }
 
+/// The handler to list the DNS records of a domain in the workspace.
 async fn get_doamin_dns_record(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -184,6 +190,7 @@ async fn get_doamin_dns_record(
 		.into_result()
 }
 
+/// The handler to get the information of a domain in the workspace.
 async fn get_domain_info_in_workspace(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -213,6 +220,7 @@ async fn get_domain_info_in_workspace(
 		.into_result()
 }
 
+/// The handler to list the domains in the workspace.
 async fn get_domains_for_workspace(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -242,6 +250,7 @@ async fn get_domains_for_workspace(
 		.into_result()
 }
 
+/// The handler to update a DNS record of a domain in the workspace.
 async fn update_domain_dns_record(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -269,6 +278,8 @@ async fn update_domain_dns_record(
 		.into_result()
 }
 
+/// The handler to verify that a domain in the workspace is owned by the
+/// workspace.
 async fn verify_domain_in_workspace(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
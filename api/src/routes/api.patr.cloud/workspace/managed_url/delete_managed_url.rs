@@ -19,6 +19,7 @@ pub async fn delete_managed_url(
 					DeleteManagedURLRequestHeaders {
 						authorization: _,
 						user_agent: _,
+						if_match,
 					},
 				body: DeleteManagedURLRequestProcessed,
 			},
@@ -35,7 +36,8 @@ pub async fn delete_managed_url(
 		r#"
 		SELECT
 			managed_url.id,
-			managed_url.workspace_id
+			managed_url.workspace_id,
+			managed_url.version
 		FROM
 			managed_url
 		INNER JOIN
@@ -45,7 +47,9 @@ pub async fn delete_managed_url(
 		WHERE
 			managed_url.id = $1 AND
 			managed_url.deleted IS NULL AND
-			resource.owner_id = $2;
+			resource.owner_id = $2
+		FOR UPDATE OF
+			managed_url;
 		"#,
 		managed_url_id as _,
 		workspace_id as _,
@@ -54,6 +58,14 @@ pub async fn delete_managed_url(
 	.await?;
 
 	if let Some(managed_url) = managed_url {
+		if !ResourceVersion(managed_url.version).satisfies(if_match.as_ref()) {
+			debug!(
+				"ManagedURL `{}` has been modified since it was last fetched",
+				managed_url_id
+			);
+			return Err(ErrorType::PreconditionFailed);
+		}
+
 		query!(
 			r#"
 			UPDATE
@@ -0,0 +1,118 @@
+use axum::http::StatusCode;
+use models::{api::workspace::managed_url::*, prelude::*};
+
+use crate::prelude::*;
+
+/// The handler to get the details of a managed URL in a workspace. This will
+/// return the managed URL along with its current version as an ETag.
+pub async fn get_managed_url_info(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: GetManagedURLInfoPath {
+					workspace_id,
+					managed_url_id,
+				},
+				query: (),
+				headers:
+					GetManagedURLInfoRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: GetManagedURLInfoRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+	}: AuthenticatedAppRequest<'_, GetManagedURLInfoRequest>,
+) -> Result<AppResponse<GetManagedURLInfoRequest>, ErrorType> {
+	info!("Getting ManagedURL `{}`", managed_url_id);
+
+	let row = query!(
+		r#"
+		SELECT
+			id,
+			sub_domain,
+			domain_id,
+			path,
+			url_type as "url_type: ManagedUrlTypeDiscriminant",
+			deployment_id,
+			port,
+			static_site_id,
+			url,
+			is_configured,
+			permanent_redirect,
+			http_only,
+			version
+		FROM
+			managed_url
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL;
+		"#,
+		managed_url_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	let url = WithId::new(
+		row.id,
+		ManagedUrl {
+			sub_domain: row.sub_domain,
+			domain_id: row.domain_id.into(),
+			path: row.path,
+			url_type: match row.url_type {
+				ManagedUrlTypeDiscriminant::ProxyUrl => ManagedUrlType::ProxyUrl {
+					url: row
+						.url
+						.ok_or(ErrorType::server_error("url in db is NULL"))?,
+					http_only: row
+						.http_only
+						.ok_or(ErrorType::server_error("http_only in db is NULL"))?,
+				},
+				ManagedUrlTypeDiscriminant::Redirect => ManagedUrlType::Redirect {
+					url: row
+						.url
+						.ok_or(ErrorType::server_error("url in db is NULL"))?,
+					permanent_redirect: row
+						.permanent_redirect
+						.ok_or(ErrorType::server_error("permanent_redirect in db is NULL"))?,
+					http_only: row
+						.http_only
+						.ok_or(ErrorType::server_error("http_only in db is NULL"))?,
+				},
+				ManagedUrlTypeDiscriminant::ProxyStaticSite => ManagedUrlType::ProxyStaticSite {
+					static_site_id: row
+						.static_site_id
+						.ok_or(ErrorType::server_error("static_site_id in db is NULL"))?
+						.into(),
+				},
+				ManagedUrlTypeDiscriminant::ProxyDeployment => ManagedUrlType::ProxyDeployment {
+					deployment_id: row
+						.deployment_id
+						.ok_or(ErrorType::server_error("deployment_id in db is NULL"))?
+						.into(),
+					port: u16::try_from(
+						row.port
+							.ok_or(ErrorType::server_error("port in db is NULL"))?,
+					)?,
+				},
+			},
+			is_configured: row.is_configured,
+		},
+	);
+
+	AppResponse::builder()
+		.body(GetManagedURLInfoResponse { url })
+		.headers(GetManagedURLInfoResponseHeaders {
+			etag: ResourceVersion(row.version).to_etag(),
+		})
+		.status_code(StatusCode::OK)
+		.build()
+		.into_result()
+}
@@ -1,5 +1,6 @@
 use axum::http::StatusCode;
 use models::{api::workspace::managed_url::*, prelude::*};
+use sqlx::{QueryBuilder, Row};
 
 use crate::prelude::*;
 
@@ -12,12 +13,7 @@ pub async fn list_managed_url(
 				path: ListManagedURLPath { workspace_id },
 				query:
 					Paginated {
-						data:
-							ListManagedURLQuery {
-								order: _, // TODO implement these
-								order_by: _,
-								filter: _,
-							},
+						data: ListManagedURLQuery { filter, sort },
 						count,
 						page,
 					},
@@ -37,108 +33,137 @@ pub async fn list_managed_url(
 ) -> Result<AppResponse<ListManagedURLRequest>, ErrorType> {
 	info!("Listing ManagedURLs in workspace `{}`", workspace_id);
 
-	let mut total_count = 0;
-
-	let urls = query!(
+	let rows = QueryBuilder::<DatabaseType>::new(
 		r#"
 		SELECT
-			managed_url.id,
-			sub_domain,
-			domain_id,
-			path,
-			url_type as "url_type: ManagedUrlTypeDiscriminant",
-			deployment_id,
-			port,
-			static_site_id,
-			url,
-			is_configured,
-			permanent_redirect,
-			http_only,
-			COUNT(*) OVER() AS "total_count!"
-		FROM
-			managed_url
-		INNER JOIN
-			RESOURCES_WITH_PERMISSION_FOR_LOGIN_ID($2, $3) AS resource
-		ON
-			managed_url.id = resource.id
+			*,
+			COUNT(*) OVER() AS total_count
+		FROM (
+			SELECT
+				managed_url.id,
+				sub_domain,
+				domain_id,
+				path,
+				url_type,
+				deployment_id,
+				port,
+				static_site_id,
+				url,
+				is_configured,
+				permanent_redirect,
+				http_only,
+				resource.created
+			FROM
+				managed_url
+			INNER JOIN
+				RESOURCES_WITH_PERMISSION_FOR_LOGIN_ID("#,
+	)
+	.push_bind(user_data.login_id)
+	.push(", ")
+	.push_bind(Permission::ManagedURL(ManagedURLPermission::View))
+	.push(
+		r#") AS resource
+			ON
+				managed_url.id = resource.id
+			WHERE
+				workspace_id = "#,
+	)
+	.push_bind(workspace_id)
+	.push(
+		r#" AND
+				managed_url.deleted IS NULL
+		) AS managed_url
 		WHERE
-			workspace_id = $1 AND
-			managed_url.deleted IS NULL
-		ORDER BY
-			resource.created DESC
-		LIMIT $4
-		OFFSET $5;
-		"#,
-		workspace_id as _,
-		user_data.login_id as _,
-		Permission::ManagedURL(ManagedURLPermission::View) as _,
-		count as i32,
-		(count * page) as i32,
+			TRUE"#,
 	)
+	.push_list_filter(filter.as_ref(), column)
+	.push_list_sort(sort.as_ref(), "created DESC, id", column)
+	.push(" LIMIT ")
+	.push_bind(i32::try_from(count)?)
+	.push(" OFFSET ")
+	.push_bind(i32::try_from(count * page)?)
+	.build()
 	.fetch_all(&mut **database)
-	.await?
-	.into_iter()
-	.map(|row| {
-		total_count = row.total_count;
-		Ok(WithId::new(
-			row.id,
-			ManagedUrl {
-				sub_domain: row.sub_domain,
-				domain_id: row.domain_id.into(),
-				path: row.path,
-				url_type: match row.url_type {
-					ManagedUrlTypeDiscriminant::ProxyUrl => ManagedUrlType::ProxyUrl {
-						url: row
-							.url
-							.ok_or(ErrorType::server_error("url in db is NULL"))?,
-						http_only: row
-							.http_only
-							.ok_or(ErrorType::server_error("http_only in db is NULL"))?,
-					},
-					ManagedUrlTypeDiscriminant::Redirect => ManagedUrlType::Redirect {
-						url: row
-							.url
-							.ok_or(ErrorType::server_error("url in db is NULL"))?,
-						permanent_redirect: row
-							.permanent_redirect
-							.ok_or(ErrorType::server_error("permanent_redirect in db is NULL"))?,
-						http_only: row
-							.http_only
-							.ok_or(ErrorType::server_error("http_only in db is NULL"))?,
-					},
-					ManagedUrlTypeDiscriminant::ProxyStaticSite => {
-						ManagedUrlType::ProxyStaticSite {
-							static_site_id: row
-								.static_site_id
-								.ok_or(ErrorType::server_error("static_site_id in db is NULL"))?
-								.into(),
+	.await?;
+
+	let total_count = rows
+		.first()
+		.map(|row| row.try_get::<i64, _>("total_count"))
+		.transpose()?
+		.unwrap_or(0);
+
+	let urls = rows
+		.into_iter()
+		.map(|row| {
+			let url = row.try_get::<Option<String>, _>("url")?;
+			let http_only = row.try_get::<Option<bool>, _>("http_only")?;
+			Ok(WithId::new(
+				row.try_get::<Uuid, _>("id")?,
+				ManagedUrl {
+					sub_domain: row.try_get("sub_domain")?,
+					domain_id: row.try_get("domain_id")?,
+					path: row.try_get("path")?,
+					url_type: match row.try_get::<ManagedUrlTypeDiscriminant, _>("url_type")? {
+						ManagedUrlTypeDiscriminant::ProxyUrl => ManagedUrlType::ProxyUrl {
+							url: url.ok_or(ErrorType::server_error("url in db is NULL"))?,
+							http_only: http_only
+								.ok_or(ErrorType::server_error("http_only in db is NULL"))?,
+						},
+						ManagedUrlTypeDiscriminant::Redirect => ManagedUrlType::Redirect {
+							url: url.ok_or(ErrorType::server_error("url in db is NULL"))?,
+							permanent_redirect: row
+								.try_get::<Option<bool>, _>("permanent_redirect")?
+								.ok_or(ErrorType::server_error(
+									"permanent_redirect in db is NULL",
+								))?,
+							http_only: http_only
+								.ok_or(ErrorType::server_error("http_only in db is NULL"))?,
+						},
+						ManagedUrlTypeDiscriminant::ProxyStaticSite => {
+							ManagedUrlType::ProxyStaticSite {
+								static_site_id: row
+									.try_get::<Option<Uuid>, _>("static_site_id")?
+									.ok_or(ErrorType::server_error(
+									"static_site_id in db is NULL",
+								))?,
+							}
 						}
-					}
-					ManagedUrlTypeDiscriminant::ProxyDeployment => {
-						ManagedUrlType::ProxyDeployment {
-							deployment_id: row
-								.deployment_id
-								.ok_or(ErrorType::server_error("deployment_id in db is NULL"))?
-								.into(),
-							port: row
-								.port
-								.ok_or(ErrorType::server_error("port in db is NULL"))?
-								as u16,
+						ManagedUrlTypeDiscriminant::ProxyDeployment => {
+							ManagedUrlType::ProxyDeployment {
+								deployment_id: row
+									.try_get::<Option<Uuid>, _>("deployment_id")?
+									.ok_or(ErrorType::server_error(
+										"deployment_id in db is NULL",
+									))?,
+								port: u16::try_from(
+									row.try_get::<Option<i32>, _>("port")?
+										.ok_or(ErrorType::server_error("port in db is NULL"))?,
+								)?,
+							}
 						}
-					}
+					},
+					is_configured: row.try_get("is_configured")?,
 				},
-				is_configured: row.is_configured,
-			},
-		))
-	})
-	.collect::<Result<_, ErrorType>>()?;
+			))
+		})
+		.collect::<Result<_, ErrorType>>()?;
 
 	AppResponse::builder()
 		.body(ListManagedURLResponse { urls })
 		.headers(ListManagedURLResponseHeaders {
-			total_count: TotalCountHeader(total_count as _),
+			total_count: TotalCountHeader(total_count.try_into()?),
 		})
 		.status_code(StatusCode::OK)
 		.build()
 		.into_result()
 }
+
+/// The SQL expression of each field that the managed URLs can be filtered and
+/// sorted by.
+fn column(field: ManagedUrlListField) -> &'static str {
+	match field {
+		ManagedUrlListField::SubDomain => "sub_domain",
+		ManagedUrlListField::Path => "path",
+		ManagedUrlListField::Created => "created",
+	}
+}
@@ -2,16 +2,24 @@ use axum::Router;
 
 use crate::prelude::*;
 
+/// Create a managed URL in the workspace.
 mod create_managed_url;
+/// Delete a managed URL in the workspace.
 mod delete_managed_url;
+/// Get the information of a managed URL, along with its version.
+mod get_managed_url_info;
+/// List the managed URLs in the workspace.
 mod list_managed_url;
+/// Update the configuration of a managed URL.
 mod update_managed_url;
+/// Verify that the domain of a managed URL points to Patr.
 #[allow(unreachable_code, unused_variables)]
 mod verify_configuration;
 
 use self::{
 	create_managed_url::*,
 	delete_managed_url::*,
+	get_managed_url_info::*,
 	list_managed_url::*,
 	update_managed_url::*,
 	verify_configuration::*,
@@ -22,6 +30,7 @@ pub async fn setup_routes(state: &AppState) -> Router {
 	Router::new()
 		.mount_auth_endpoint(create_managed_url, state)
 		.mount_auth_endpoint(delete_managed_url, state)
+		.mount_auth_endpoint(get_managed_url_info, state)
 		.mount_auth_endpoint(list_managed_url, state)
 		.mount_auth_endpoint(update_managed_url, state)
 		.mount_auth_endpoint(verify_configuration, state)
@@ -19,6 +19,7 @@ pub async fn update_managed_url(
 					UpdateManagedURLRequestHeaders {
 						authorization: _,
 						user_agent: _,
+						if_match,
 					},
 				body:
 					UpdateManagedURLRequestProcessed {
@@ -36,10 +37,10 @@ pub async fn update_managed_url(
 	info!("Creating ManagedURL with ID: `{}`", managed_url_id);
 
 	// Check to make sure that the Managed URL exist
-	query!(
+	let version = query!(
 		r#"
         SELECT
-            managed_url.id
+            managed_url.version
         FROM
             managed_url
         INNER JOIN
@@ -49,15 +50,26 @@ pub async fn update_managed_url(
         WHERE
             managed_url.id = $1 AND
             managed_url.deleted IS NULL AND
-            resource.owner_id = $2;
+            resource.owner_id = $2
+        FOR UPDATE OF
+            managed_url;
         "#,
 		managed_url_id as _,
 		workspace_id as _,
 	)
 	.fetch_optional(&mut **database)
 	.await?
+	.map(|row| ResourceVersion(row.version))
 	.ok_or(ErrorType::ResourceDoesNotExist)?;
 
+	if !version.satisfies(if_match.as_ref()) {
+		debug!(
+			"ManagedURL `{}` has been modified since it was last fetched",
+			managed_url_id
+		);
+		return Err(ErrorType::PreconditionFailed);
+	}
+
 	let path = format!("/{}", path.trim_start_matches('/'));
 
 	let url_type;
@@ -130,7 +142,8 @@ pub async fn update_managed_url(
 			static_site_id = $6,
 			url = $7,
 			permanent_redirect = $8,
-			http_only = $9
+			http_only = $9,
+			version = version + 1
 		WHERE
 			id = $1;
 		"#,
@@ -3,18 +3,27 @@ use axum::Router;
 use crate::prelude::*;
 
 // mod container_registry;
+/// All the endpoints to manage the databases of a workspace.
 #[allow(unreachable_code, unused_variables)]
 mod database;
-mod deployment;
+/// All the endpoints to manage the deployments of a workspace.
+pub(crate) mod deployment;
+/// All the endpoints to manage the domains of a workspace.
 #[allow(unreachable_code, unused_variables)]
 mod domain;
+/// All the endpoints to manage the managed URLs of a workspace.
 mod managed_url;
+/// All the endpoints to manage the roles and permissions of a workspace.
 mod rbac;
+/// All the endpoints to manage the runners of a workspace.
 mod runner;
+/// All the endpoints to manage the secrets of a workspace.
 #[allow(unreachable_code, unused_variables)]
 mod secret;
+/// All the endpoints to manage the static sites of a workspace.
 #[allow(unreachable_code, unused_variables)]
 mod static_site;
+/// All the endpoints to manage the volumes of a workspace.
 mod volume;
 
 /// The handler to create a new workspace. The workspace name must be unique.
@@ -2,8 +2,11 @@ use axum::Router;
 
 use crate::prelude::*;
 
+/// All the endpoints to list the permissions that can be given to a role.
 mod permission;
+/// All the endpoints to manage the roles of a workspace.
 mod role;
+/// All the endpoints to manage the account of the logged in user.
 mod user;
 
 #[instrument(skip(state))]
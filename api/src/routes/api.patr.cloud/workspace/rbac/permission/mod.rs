@@ -2,8 +2,11 @@ use axum::Router;
 
 use crate::prelude::*;
 
+/// Get the permissions that the user has on the workspace.
 mod get_current_permissions;
+/// List all the permissions that can be given to a role.
 mod list_all_permissions;
+/// List all the types of resources that permissions apply to.
 mod list_all_resource_types;
 
 pub use self::{get_current_permissions::*, list_all_permissions::*, list_all_resource_types::*};
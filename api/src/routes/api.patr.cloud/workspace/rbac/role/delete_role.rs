@@ -18,10 +18,12 @@ pub async fn delete_role(
 					role_id,
 				},
 				query: DeleteRoleQuery { remove_users },
-				headers: DeleteRoleRequestHeaders {
-					authorization: _,
-					user_agent: _,
-				},
+				headers:
+					DeleteRoleRequestHeaders {
+						authorization: _,
+						user_agent: _,
+						if_match,
+					},
 				body: DeleteRoleRequestProcessed,
 			},
 		database,
@@ -33,9 +35,36 @@ pub async fn delete_role(
 ) -> Result<AppResponse<DeleteRoleRequest>, ErrorType> {
 	info!("Deleting role: {} in workspace: {}", role_id, workspace_id);
 
+	let version = query!(
+		r#"
+		SELECT
+			version
+		FROM
+			role
+		WHERE
+			id = $1 AND
+			owner_id = $2
+		FOR UPDATE;
+		"#,
+		role_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.map(|row| ResourceVersion(row.version))
+	.ok_or(ErrorType::RoleDoesNotExist)?;
+
+	if !version.satisfies(if_match.as_ref()) {
+		debug!(
+			"Role `{}` has been modified since it was last fetched",
+			role_id
+		);
+		return Err(ErrorType::PreconditionFailed);
+	}
+
 	// Remove the role from all the users. If the role is still in use, an error
-	// will be thrown, causing the transaction to be rolled back and the role not
-	// to be deleted
+	// will be thrown, causing the transaction to be rolled back and the role
+	// not to be deleted
 	let users_with_role = query!(
 		r#"
 		DELETE FROM
@@ -117,7 +146,9 @@ pub async fn delete_role(
 	redis
 		.setex(
 			redis::keys::workspace_id_revocation_timestamp(&workspace_id),
-			constants::CACHED_PERMISSIONS_VALIDITY.whole_seconds() as u64,
+			constants::CACHED_PERMISSIONS_VALIDITY
+				.unsigned_abs()
+				.as_secs(),
 			OffsetDateTime::now_utc().unix_timestamp(),
 		)
 		.await
@@ -115,7 +115,9 @@ pub async fn get_role_info(
 			),
 			permissions,
 		})
-		.headers(())
+		.headers(GetRoleInfoResponseHeaders {
+			etag: ResourceVersion(role.version).to_etag(),
+		})
 		.status_code(StatusCode::OK)
 		.build()
 		.into_result()
@@ -50,8 +50,8 @@ pub async fn list_all_roles(
         OFFSET $3;
         "#,
 		workspace_id as _,
-		count as i64,
-		(page * count) as i64,
+		i64::try_from(count)?,
+		i64::try_from(page * count)?,
 	)
 	.fetch_all(&mut **database)
 	.await?
@@ -71,7 +71,7 @@ pub async fn list_all_roles(
 	AppResponse::builder()
 		.body(ListAllRolesResponse { roles })
 		.headers(ListAllRolesResponseHeaders {
-			total_count: TotalCountHeader(total_count as _),
+			total_count: TotalCountHeader(total_count.try_into()?),
 		})
 		.status_code(StatusCode::OK)
 		.build()
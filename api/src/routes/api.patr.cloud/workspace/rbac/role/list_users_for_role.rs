@@ -50,8 +50,8 @@ pub async fn list_users_for_role(
         OFFSET $3;
         "#,
 		workspace_id as _,
-		count as i64,
-		(page * count) as i64,
+		i64::try_from(count)?,
+		i64::try_from(page * count)?,
 	)
 	.fetch_all(&mut **database)
 	.await?
@@ -65,7 +65,7 @@ pub async fn list_users_for_role(
 	AppResponse::builder()
 		.body(ListUsersForRoleResponse { users })
 		.headers(ListUsersForRoleResponseHeaders {
-			total_count: TotalCountHeader(total_count as _),
+			total_count: TotalCountHeader(total_count.try_into()?),
 		})
 		.status_code(StatusCode::OK)
 		.build()
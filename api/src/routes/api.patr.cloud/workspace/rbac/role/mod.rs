@@ -2,11 +2,17 @@ use axum::Router;
 
 use crate::prelude::*;
 
+/// Create a new role in the workspace.
 mod create_new_role;
+/// Delete a role in the workspace.
 mod delete_role;
+/// Get the information of a role in the workspace.
 mod get_role_info;
+/// List the roles in the workspace.
 mod list_all_roles;
+/// List the users that have a role in the workspace.
 mod list_users_for_role;
+/// Update the name, description and permissions of a role.
 mod update_role;
 
 use self::{
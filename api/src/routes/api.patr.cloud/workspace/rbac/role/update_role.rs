@@ -20,10 +20,12 @@ pub async fn update_role(
 					workspace_id,
 				},
 				query: (),
-				headers: UpdateRoleRequestHeaders {
-					authorization: _,
-					user_agent: _,
-				},
+				headers:
+					UpdateRoleRequestHeaders {
+						authorization: _,
+						user_agent: _,
+						if_match,
+					},
 				body:
 					UpdateRoleRequestProcessed {
 						name,
@@ -51,13 +53,41 @@ pub async fn update_role(
 		return Err(ErrorType::WrongParameters);
 	}
 
+	let version = query!(
+		r#"
+		SELECT
+			version
+		FROM
+			role
+		WHERE
+			id = $1 AND
+			owner_id = $2
+		FOR UPDATE;
+		"#,
+		role_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.map(|row| ResourceVersion(row.version))
+	.ok_or(ErrorType::RoleDoesNotExist)?;
+
+	if !version.satisfies(if_match.as_ref()) {
+		debug!(
+			"Role `{}` has been modified since it was last fetched",
+			role_id
+		);
+		return Err(ErrorType::PreconditionFailed);
+	}
+
 	query!(
 		r#"
         UPDATE
             role
         SET
             name = COALESCE($1, name),
-            description = COALESCE($2, description)
+            description = COALESCE($2, description),
+            version = version + 1
         WHERE
             id = $3;
         "#,
@@ -196,7 +226,9 @@ pub async fn update_role(
 	redis
 		.setex(
 			redis::keys::workspace_id_revocation_timestamp(&workspace_id),
-			constants::CACHED_PERMISSIONS_VALIDITY.whole_seconds() as u64,
+			constants::CACHED_PERMISSIONS_VALIDITY
+				.unsigned_abs()
+				.as_secs(),
 			OffsetDateTime::now_utc().unix_timestamp(),
 		)
 		.await
@@ -49,8 +49,8 @@ pub async fn list_users_in_workspace(
         OFFSET $3;
         "#,
 		workspace_id as _,
-		count as i64,
-		(count * page) as i64,
+		i64::try_from(count)?,
+		i64::try_from(count * page)?,
 	)
 	.fetch_all(&mut **database)
 	.await?
@@ -67,7 +67,7 @@ pub async fn list_users_in_workspace(
 	AppResponse::builder()
 		.body(ListUsersInWorkspaceResponse { users })
 		.headers(ListUsersInWorkspaceResponseHeaders {
-			total_count: TotalCountHeader(total_count as _),
+			total_count: TotalCountHeader(total_count.try_into()?),
 		})
 		.status_code(StatusCode::OK)
 		.build()
@@ -2,8 +2,11 @@ use axum::Router;
 
 use crate::prelude::*;
 
+/// List the users that are a part of the workspace.
 mod list_users_in_workspace;
+/// Remove a user from the workspace.
 mod remove_user_from_workspace;
+/// Update the roles of a user in the workspace.
 mod update_user_roles_in_workspace;
 
 use self::{
@@ -51,7 +51,9 @@ pub async fn remove_user_from_workspace(
 	redis
 		.setex(
 			redis::keys::user_id_revocation_timestamp(&user_id),
-			constants::CACHED_PERMISSIONS_VALIDITY.whole_seconds() as u64,
+			constants::CACHED_PERMISSIONS_VALIDITY
+				.unsigned_abs()
+				.as_secs(),
 			OffsetDateTime::now_utc().unix_timestamp(),
 		)
 		.await
@@ -3,6 +3,7 @@ use models::{api::workspace::runner::*, prelude::*};
 
 use crate::prelude::*;
 
+/// The handler to add a runner to the workspace.
 pub async fn add_runner_to_workspace(
 	AuthenticatedAppRequest {
 		request:
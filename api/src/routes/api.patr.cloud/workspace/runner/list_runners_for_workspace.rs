@@ -4,6 +4,7 @@ use rustis::commands::{GenericCommands, ScanOptions};
 
 use crate::prelude::*;
 
+/// The handler to list the runners in the workspace.
 pub async fn list_runners_for_workspace(
 	AuthenticatedAppRequest {
 		request:
@@ -61,8 +62,8 @@ pub async fn list_runners_for_workspace(
 		workspace_id as _,
 		user_data.login_id as _,
 		Permission::Runner(RunnerPermission::View) as _,
-		count as i32,
-		(count * page) as i32,
+		i64::try_from(count)?,
+		i64::try_from(count * page)?,
 	)
 	.fetch_all(&mut **database)
 	.await?
@@ -84,7 +85,7 @@ pub async fn list_runners_for_workspace(
 	AppResponse::builder()
 		.body(ListRunnersForWorkspaceResponse { runners })
 		.headers(ListRunnersForWorkspaceResponseHeaders {
-			total_count: TotalCountHeader(total_count as _),
+			total_count: TotalCountHeader(total_count.try_into()?),
 		})
 		.status_code(StatusCode::OK)
 		.build()
@@ -2,10 +2,16 @@ use axum::Router;
 
 use crate::prelude::*;
 
+/// Add a runner to the workspace.
 mod add_runner_to_workspace;
+/// Get the information of a runner in the workspace.
 mod get_runner_info;
+/// List the runners in the workspace.
 mod list_runners_for_workspace;
+/// Remove a runner from the workspace.
 mod remove_runner_from_workspace;
+/// Stream the resources that a runner needs to run, along with the changes made
+/// to them.
 mod stream_runner_data_for_workspace;
 
 use self::{
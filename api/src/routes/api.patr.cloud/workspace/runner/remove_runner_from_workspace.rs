@@ -3,6 +3,7 @@ use models::{api::workspace::runner::*, prelude::*};
 
 use crate::prelude::*;
 
+/// The handler to remove a runner from the workspace.
 pub async fn remove_runner_from_workspace(
 	AuthenticatedAppRequest {
 		request:
@@ -2,15 +2,25 @@ use std::time::Duration;
 
 use axum::{http::StatusCode, response::IntoResponse};
 use axum_typed_websockets::Message;
-use futures::{future::Either, prelude::stream::*};
+use futures::StreamExt;
 use models::{
-	api::workspace::runner::*,
+	api::workspace::{deployment::event::StreamDeploymentEventsServerMsg, runner::*},
 	utils::{GenericResponse, WebSocketUpgrade},
 };
-use rustis::commands::{SetCondition, SetExpiration, StringCommands};
+use rustis::{
+	client::Client as RedisClient,
+	commands::{PubSubCommands, SetCondition, SetExpiration, StringCommands},
+};
+use sqlx::{pool::PoolOptions, Pool};
 
-use crate::prelude::*;
+use crate::{
+	db,
+	prelude::*,
+	routes::api_patr_cloud::workspace::deployment::{event, exec, port_forward, runtime},
+};
 
+/// The handler to stream the resources that a runner needs to run, along with
+/// the changes made to them.
 pub async fn stream_runner_data_for_workspace(
 	AuthenticatedAppRequest {
 		request:
@@ -30,7 +40,7 @@ pub async fn stream_runner_data_for_workspace(
 		database: _,
 		redis,
 		client_ip: _,
-		config: _,
+		config,
 		user_data: _,
 	}: AuthenticatedAppRequest<'_, StreamRunnerDataForWorkspaceRequest>,
 ) -> Result<AppResponse<StreamRunnerDataForWorkspaceRequest>, ErrorType> {
@@ -74,6 +84,12 @@ pub async fn stream_runner_data_for_workspace(
 						return;
 					};
 
+					// Messages from the runner are recorded outside of the
+					// request's transaction, over a connection of their own
+					let database = PoolOptions::<DatabaseType>::new()
+						.max_connections(1)
+						.connect_lazy_with(db::connect_options(&config.database));
+
 					let ping_interval = if cfg!(debug_assertions) {
 						Duration::from_secs(1)
 					} else {
@@ -81,12 +97,10 @@ pub async fn stream_runner_data_for_workspace(
 					};
 
 					let mut sleeper = Box::pin(tokio::time::sleep(ping_interval));
-					let mut data_future = pub_sub.next();
 
 					loop {
-						match futures::future::select(sleeper, data_future).await {
-							Either::Left((_, right)) => {
-								data_future = right;
+						tokio::select! {
+							_ = &mut sleeper => {
 								sleeper = Box::pin(tokio::time::sleep(ping_interval));
 								let Ok(_) = websocket.send(Message::Ping(Vec::new())).await else {
 									debug!("Failed to send ping to websocket");
@@ -114,9 +128,7 @@ pub async fn stream_runner_data_for_workspace(
 									break;
 								};
 							}
-							Either::Right((data, left)) => {
-								sleeper = left;
-								data_future = pub_sub.next();
+							data = pub_sub.next() => {
 								let Some(Ok(data)) = data else {
 									continue;
 								};
@@ -133,6 +145,32 @@ pub async fn stream_runner_data_for_workspace(
 									break;
 								};
 							}
+							message = websocket.recv() => {
+								let message = match message {
+									Some(Ok(Message::Item(message))) => message,
+									Some(Ok(Message::Close(_))) | None => {
+										debug!("Runner closed the websocket");
+										break;
+									}
+									Some(Ok(_)) => continue,
+									Some(Err(err)) => {
+										debug!("Failed to receive data from websocket: {:?}", err);
+										break;
+									}
+								};
+								trace!("Received message from runner: {:?}", message);
+								_ = record_runner_message(
+									&database,
+									&redis,
+									workspace_id,
+									runner_id,
+									message,
+								)
+								.await
+								.inspect_err(|err| {
+									error!("Error recording runner message: {:?}", err)
+								});
+							}
 						}
 					}
 
@@ -141,6 +179,25 @@ pub async fn stream_runner_data_for_workspace(
 						.unsubscribe(&redis_channel)
 						.await
 						.inspect_err(|err| error!("Error streaming runner data: {:?}", err));
+
+					// If the runner has already reconnected, the new connection
+					// holds the lock, and the runner is still reachable
+					let Ok(lock) = redis
+						.get::<_, Option<String>>(redis::keys::runner_connection_lock(&runner_id))
+						.await
+						.inspect_err(|err| {
+							error!("Error getting runner connection lock: {:?}", err)
+						})
+					else {
+						return;
+					};
+					if lock.is_none() || lock == Some(random_connection_id.to_string()) {
+						_ = mark_runner_unreachable(&database, runner_id)
+							.await
+							.inspect_err(|err| {
+								error!("Error marking runner as unreachable: {:?}", err)
+							});
+					}
 				})
 				.into_response(),
 		))
@@ -149,3 +206,67 @@ pub async fn stream_runner_data_for_workspace(
 		.build()
 		.into_result()
 }
+
+/// Records a message that the runner sent, in a transaction of its own. The
+/// events recorded because of it are published once the transaction is
+/// committed, for the event streams of the deployment. Messages about terminal
+/// sessions and forwarded ports aren't recorded, and are relayed to the user
+/// that opened the session instead
+async fn record_runner_message(
+	database: &Pool<DatabaseType>,
+	redis: &RedisClient,
+	workspace_id: Uuid,
+	runner_id: Uuid,
+	message: StreamRunnerDataForWorkspaceClientMsg,
+) -> Result<(), ErrorType> {
+	let deployment_id = message.deployment_id();
+
+	let message = match exec::into_session_message(message) {
+		Ok((session_id, message)) => {
+			redis
+				.publish(
+					exec::session_channel(&workspace_id, &deployment_id, &session_id),
+					serde_json::to_string(&message)?,
+				)
+				.await?;
+			return Ok(());
+		}
+		Err(message) => message,
+	};
+
+	let message = match port_forward::into_session_message(message) {
+		Ok((session_id, message)) => {
+			redis
+				.publish(
+					port_forward::session_channel(&workspace_id, &deployment_id, &session_id),
+					serde_json::to_string(&message)?,
+				)
+				.await?;
+			return Ok(());
+		}
+		Err(message) => message,
+	};
+
+	let mut transaction = database.begin().await?;
+	let events = runtime::record_runner_message(&mut transaction, runner_id, message).await?;
+	transaction.commit().await?;
+
+	for event in events {
+		redis
+			.publish(
+				event::events_channel(&workspace_id, &deployment_id),
+				serde_json::to_string(&StreamDeploymentEventsServerMsg::EventOccurred { event })?,
+			)
+			.await?;
+	}
+
+	Ok(())
+}
+
+/// Marks the deployments of the runner as unreachable, once it has disconnected
+async fn mark_runner_unreachable(
+	database: &Pool<DatabaseType>,
+	runner_id: Uuid,
+) -> Result<(), sqlx::Error> {
+	runtime::mark_runner_unreachable(&mut *database.acquire().await?, runner_id).await
+}
@@ -8,11 +8,13 @@ pub async fn setup_routes(state: &AppState) -> Router {
 	Router::new()
 		.mount_auth_endpoint(create_secret, state)
 		.mount_auth_endpoint(delete_secret, state)
+		.mount_auth_endpoint(get_secret_info, state)
 		.mount_auth_endpoint(list_secrets_for_workspace, state)
 		.mount_auth_endpoint(update_secret, state)
 		.with_state(state.clone())
 }
 
+/// The handler to create a secret in the workspace.
 async fn create_secret(
 	AuthenticatedAppRequest {
 		request:
@@ -41,6 +43,7 @@ async fn create_secret(
 		.into_result()
 }
 
+/// The handler to delete a secret in the workspace.
 async fn delete_secret(
 	AuthenticatedAppRequest {
 		request:
@@ -49,29 +52,164 @@ async fn delete_secret(
 					workspace_id,
 					secret_id,
 				},
-				query: _,
-				headers: _,
-				body,
+				query: (),
+				headers:
+					DeleteSecretRequestHeaders {
+						authorization: _,
+						user_agent: _,
+						if_match,
+					},
+				body: DeleteSecretRequestProcessed,
 			},
 		database,
 		redis: _,
 		client_ip: _,
-		config,
-		user_data,
+		config: _,
+		user_data: _,
 	}: AuthenticatedAppRequest<'_, DeleteSecretRequest>,
 ) -> Result<AppResponse<DeleteSecretRequest>, ErrorType> {
-	info!("Starting: Delete secret");
+	info!("Deleting secret: `{secret_id}`");
 
-	// LOGIC
+	let version = query!(
+		r#"
+		SELECT
+			version
+		FROM
+			secret
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL
+		FOR UPDATE;
+		"#,
+		secret_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.map(|row| ResourceVersion(row.version))
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	if !version.satisfies(if_match.as_ref()) {
+		debug!("Secret `{secret_id}` has been modified since it was last fetched");
+		return Err(ErrorType::PreconditionFailed);
+	}
+
+	// Secrets that are still used by a deployment can't be deleted
+	query!(
+		r#"
+		DELETE FROM
+			secret
+		WHERE
+			id = $1;
+		"#,
+		secret_id as _
+	)
+	.execute(&mut **database)
+	.await
+	.map_err(|err| match err {
+		sqlx::Error::Database(err) if err.is_foreign_key_violation() => ErrorType::ResourceInUse,
+		err => ErrorType::server_error(err),
+	})?;
+
+	// Mark the resource as deleted in the database
+	query!(
+		r#"
+		UPDATE
+			resource
+		SET
+			deleted = NOW()
+		WHERE
+			id = $1;
+		"#,
+		secret_id as _
+	)
+	.execute(&mut **database)
+	.await?;
 
 	AppResponse::builder()
 		.body(DeleteSecretResponse)
 		.headers(())
+		.status_code(StatusCode::RESET_CONTENT)
+		.build()
+		.into_result()
+}
+
+/// The handler to get the information of a secret in the workspace. The value
+/// of the secret is never returned.
+async fn get_secret_info(
+	AuthenticatedAppRequest {
+		request:
+			ProcessedApiRequest {
+				path: GetSecretInfoPath {
+					workspace_id,
+					secret_id,
+				},
+				query: (),
+				headers:
+					GetSecretInfoRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: GetSecretInfoRequestProcessed,
+			},
+		database,
+		redis: _,
+		client_ip: _,
+		config: _,
+		user_data: _,
+	}: AuthenticatedAppRequest<'_, GetSecretInfoRequest>,
+) -> Result<AppResponse<GetSecretInfoRequest>, ErrorType> {
+	trace!("Getting secret info: `{secret_id}`");
+
+	let secret = query!(
+		r#"
+		SELECT
+			secret.id,
+			secret.name,
+			secret.version,
+			(
+				SELECT
+					deployment_environment_variable.deployment_id
+				FROM
+					deployment_environment_variable
+				WHERE
+					deployment_environment_variable.secret_id = secret.id
+				LIMIT 1
+			) AS "deployment_id?: Uuid"
+		FROM
+			secret
+		WHERE
+			secret.id = $1 AND
+			secret.workspace_id = $2 AND
+			secret.deleted IS NULL;
+		"#,
+		secret_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	AppResponse::builder()
+		.body(GetSecretInfoResponse {
+			secret: WithId::new(
+				secret.id,
+				Secret {
+					name: secret.name,
+					deployment_id: secret.deployment_id,
+				},
+			),
+		})
+		.headers(GetSecretInfoResponseHeaders {
+			etag: ResourceVersion(secret.version).to_etag(),
+		})
 		.status_code(StatusCode::OK)
 		.build()
 		.into_result()
 }
 
+/// The handler to list the secrets in the workspace.
 async fn list_secrets_for_workspace(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -101,29 +239,88 @@ async fn list_secrets_for_workspace(
 		.into_result()
 }
 
+/// The handler to update the value of a secret in the workspace.
 async fn update_secret(
 	AuthenticatedAppRequest {
-		request: ProcessedApiRequest {
-			path,
-			query: _,
-			headers,
-			body,
-		},
+		request:
+			ProcessedApiRequest {
+				path: UpdateSecretPath {
+					workspace_id,
+					secret_id,
+				},
+				query: (),
+				headers:
+					UpdateSecretRequestHeaders {
+						authorization: _,
+						user_agent: _,
+						if_match,
+					},
+				body: UpdateSecretRequestProcessed { name, value },
+			},
 		database,
 		redis: _,
 		client_ip: _,
-		config,
-		user_data,
+		config: _,
+		user_data: _,
 	}: AuthenticatedAppRequest<'_, UpdateSecretRequest>,
 ) -> Result<AppResponse<UpdateSecretRequest>, ErrorType> {
-	info!("Starting: Update secret");
+	info!("Updating secret: `{secret_id}`");
 
-	// LOGIC
+	if name.is_none() && value.is_none() {
+		return Err(ErrorType::WrongParameters);
+	}
+
+	let version = query!(
+		r#"
+		SELECT
+			version
+		FROM
+			secret
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL
+		FOR UPDATE;
+		"#,
+		secret_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.map(|row| ResourceVersion(row.version))
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	if !version.satisfies(if_match.as_ref()) {
+		debug!("Secret `{secret_id}` has been modified since it was last fetched");
+		return Err(ErrorType::PreconditionFailed);
+	}
+
+	// The value of a secret isn't stored in the database, but changing it still
+	// counts as a modification, so the version is bumped either way
+	query!(
+		r#"
+		UPDATE
+			secret
+		SET
+			name = COALESCE($1, name),
+			version = version + 1
+		WHERE
+			id = $2;
+		"#,
+		name.as_deref(),
+		secret_id as _,
+	)
+	.execute(&mut **database)
+	.await
+	.map_err(|err| match err {
+		sqlx::Error::Database(err) if err.is_unique_violation() => ErrorType::ResourceAlreadyExists,
+		err => ErrorType::server_error(err),
+	})?;
 
 	AppResponse::builder()
 		.body(UpdateSecretResponse)
 		.headers(())
-		.status_code(StatusCode::OK)
+		.status_code(StatusCode::ACCEPTED)
 		.build()
 		.into_result()
 }
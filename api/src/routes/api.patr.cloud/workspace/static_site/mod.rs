@@ -1,7 +1,7 @@
 use axum::{http::StatusCode, Router};
 use models::api::workspace::static_site::*;
 
-use crate::prelude::*;
+use crate::{prelude::*, utils::storage};
 
 #[instrument(skip(state))]
 pub async fn setup_routes(state: &AppState) -> Router {
@@ -18,6 +18,7 @@ pub async fn setup_routes(state: &AppState) -> Router {
 		.mount_auth_endpoint(upload_static_site, state)
 }
 
+/// The handler to create a static site in the workspace.
 async fn create_static_site(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -45,6 +46,7 @@ async fn create_static_site(
 		.into_result()
 }
 
+/// The handler to delete a static site in the workspace.
 async fn delete_static_site(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -72,6 +74,7 @@ async fn delete_static_site(
 		.into_result()
 }
 
+/// The handler to get the information of a static site in the workspace.
 async fn get_static_site_info(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -102,6 +105,7 @@ async fn get_static_site_info(
 		.into_result()
 }
 
+/// The handler to list the static sites in the workspace.
 async fn list_static_site(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -133,6 +137,7 @@ async fn list_static_site(
 		.into_result()
 }
 
+/// The handler to list the uploads made to a static site.
 async fn list_upload_history(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -162,6 +167,7 @@ async fn list_upload_history(
 		.into_result()
 }
 
+/// The handler to revert a static site to one of its previous uploads.
 async fn revert_static_site(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -189,6 +195,7 @@ async fn revert_static_site(
 		.into_result()
 }
 
+/// The handler to start a static site that is stopped.
 async fn start_static_site(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -216,6 +223,7 @@ async fn start_static_site(
 		.into_result()
 }
 
+/// The handler to stop a running static site.
 async fn stop_static_site(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -243,6 +251,7 @@ async fn stop_static_site(
 		.into_result()
 }
 
+/// The handler to update the configuration of a static site.
 async fn update_static_site(
 	AuthenticatedAppRequest {
 		request: ProcessedApiRequest {
@@ -270,14 +279,23 @@ async fn update_static_site(
 		.into_result()
 }
 
+/// The handler to upload a new version of the files of a static site.
 async fn upload_static_site(
 	AuthenticatedAppRequest {
-		request: ProcessedApiRequest {
-			path,
-			query: _,
-			headers,
-			body,
-		},
+		request:
+			ProcessedApiRequest {
+				path: UploadStaticSitePath {
+					workspace_id,
+					static_site_id,
+				},
+				query: (),
+				headers:
+					UploadStaticSiteRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: UploadStaticSiteRequestProcessed { file, message },
+			},
 		database,
 		redis: _,
 		client_ip: _,
@@ -287,10 +305,101 @@ async fn upload_static_site(
 ) -> Result<AppResponse<UploadStaticSiteRequest>, ErrorType> {
 	info!("Starting: Upload static site");
 
-	// LOGIC
+	query!(
+		r#"
+		SELECT
+			id
+		FROM
+			static_site
+		WHERE
+			id = $1 AND
+			workspace_id = $2 AND
+			deleted IS NULL
+		FOR UPDATE;
+		"#,
+		static_site_id as _,
+		workspace_id as _,
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	let upload_id = query!(
+		r#"
+		INSERT INTO
+			resource(
+				id,
+				resource_type_id,
+				owner_id,
+				created
+			)
+		VALUES
+			(
+				GENERATE_RESOURCE_ID(),
+				(SELECT id FROM resource_type WHERE name = 'static_site_upload'),
+				$1,
+				NOW()
+			)
+		RETURNING id;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_one(&mut **database)
+	.await?
+	.id;
+
+	query!(
+		r#"
+		INSERT INTO
+			static_site_upload_history(
+				upload_id,
+				static_site_id,
+				message,
+				uploaded_by,
+				created,
+				processed
+			)
+		VALUES
+			($1, $2, $3, $4, NOW(), NOW());
+		"#,
+		upload_id as _,
+		static_site_id as _,
+		&message,
+		user_data.id as _,
+	)
+	.execute(&mut **database)
+	.await?;
+
+	// Each upload is stored separately, so that the site can be reverted to
+	// an older upload
+	storage::get_storage(&config.storage)
+		.map_err(ErrorType::server_error)?
+		.put_object(
+			&format!("static-site/{static_site_id}/{upload_id}/index.html"),
+			file.as_bytes(),
+		)
+		.await
+		.map_err(ErrorType::server_error)?;
+
+	query!(
+		r#"
+		UPDATE
+			static_site
+		SET
+			current_live_upload = $2
+		WHERE
+			id = $1;
+		"#,
+		static_site_id as _,
+		upload_id as _,
+	)
+	.execute(&mut **database)
+	.await?;
 
 	AppResponse::builder()
-		.body(UploadStaticSiteResponse { upload_id: todo!() })
+		.body(UploadStaticSiteResponse {
+			upload_id: WithId::from(upload_id),
+		})
 		.headers(())
 		.status_code(StatusCode::OK)
 		.build()
@@ -4,6 +4,7 @@ use time::OffsetDateTime;
 
 use crate::prelude::*;
 
+/// The handler to create a volume in the workspace.
 pub async fn create_volume(
 	AuthenticatedAppRequest {
 		request:
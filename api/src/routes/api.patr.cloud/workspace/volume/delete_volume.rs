@@ -3,6 +3,7 @@ use models::api::workspace::volume::*;
 
 use crate::prelude::*;
 
+/// The handler to delete a volume in the workspace.
 pub async fn delete_volume(
 	AuthenticatedAppRequest {
 		request:
@@ -16,6 +17,7 @@ pub async fn delete_volume(
 					DeleteVolumeRequestHeaders {
 						authorization: _,
 						user_agent: _,
+						if_match,
 					},
 				body: DeleteVolumeRequestProcessed,
 			},
@@ -28,6 +30,28 @@ pub async fn delete_volume(
 ) -> Result<AppResponse<DeleteVolumeRequest>, ErrorType> {
 	trace!("Deleting volume ID: `{volume_id}`");
 
+	let version = query!(
+		r#"
+		SELECT
+			version
+		FROM
+			deployment_volume
+		WHERE
+			id = $1
+		FOR UPDATE;
+		"#,
+		volume_id as _
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.map(|row| ResourceVersion(row.version))
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	if !version.satisfies(if_match.as_ref()) {
+		debug!("Volume `{volume_id}` has been modified since it was last fetched");
+		return Err(ErrorType::PreconditionFailed);
+	}
+
 	query!(
 		r#"
 		DELETE FROM
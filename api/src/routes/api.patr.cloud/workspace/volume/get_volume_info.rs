@@ -3,6 +3,7 @@ use models::api::workspace::volume::*;
 
 use crate::prelude::*;
 
+/// The handler to get the information of a volume in the workspace.
 pub async fn get_volume_info(
 	AuthenticatedAppRequest {
 		request:
@@ -54,12 +55,14 @@ pub async fn get_volume_info(
 				row.id,
 				DeploymentVolume {
 					name: row.name,
-					size: row.volume_size as u64,
+					size: row.volume_size.try_into().unwrap_or_default(),
 					deployment_id: row.deployment_id,
 				},
 			),
 		})
-		.headers(())
+		.headers(GetVolumeInfoResponseHeaders {
+			etag: ResourceVersion(row.version).to_etag(),
+		})
 		.status_code(StatusCode::OK)
 		.build()
 		.into_result()
@@ -3,6 +3,7 @@ use models::{api::workspace::volume::*, utils::TotalCountHeader};
 
 use crate::prelude::*;
 
+/// The handler to list the volumes in the workspace.
 pub async fn list_volumes(
 	AuthenticatedAppRequest {
 		request:
@@ -57,8 +58,8 @@ pub async fn list_volumes(
 		OFFSET $3;
 		"#,
 		workspace_id as _,
-		count as i32,
-		(page * count) as i32
+		i64::try_from(count)?,
+		i64::try_from(page * count)?
 	)
 	.fetch_all(&mut **database)
 	.await?
@@ -69,7 +70,7 @@ pub async fn list_volumes(
 			row.id,
 			DeploymentVolume {
 				name: row.name,
-				size: row.volume_size as u64,
+				size: row.volume_size.try_into().unwrap_or_default(),
 				deployment_id: row.deployment_id.map(Into::into),
 			},
 		)
@@ -79,7 +80,7 @@ pub async fn list_volumes(
 	AppResponse::builder()
 		.body(ListVolumesInWorkspaceResponse { volumes })
 		.headers(ListVolumesInWorkspaceResponseHeaders {
-			total_count: TotalCountHeader(total_count as _),
+			total_count: TotalCountHeader(total_count.try_into()?),
 		})
 		.status_code(StatusCode::OK)
 		.build()
@@ -1,9 +1,14 @@
 use axum::Router;
 
+/// Create a volume in the workspace.
 mod create_volume;
+/// Delete a volume in the workspace.
 mod delete_volume;
+/// Get the information of a volume in the workspace.
 mod get_volume_info;
+/// List the volumes in the workspace.
 mod list_volumes;
+/// Update the size of a volume in the workspace.
 mod update_volume;
 
 pub use self::{
@@ -3,6 +3,7 @@ use models::api::workspace::volume::*;
 
 use crate::prelude::*;
 
+/// The handler to update the size of a volume in the workspace.
 pub async fn update_volume(
 	AuthenticatedAppRequest {
 		request:
@@ -12,10 +13,12 @@ pub async fn update_volume(
 					volume_id,
 				},
 				query: (),
-				headers: UpdateVolumeRequestHeaders {
-					authorization,
-					user_agent,
-				},
+				headers:
+					UpdateVolumeRequestHeaders {
+						authorization,
+						user_agent,
+						if_match,
+					},
 				body: UpdateVolumeRequestProcessed { name, size },
 			},
 		database,
@@ -48,6 +51,28 @@ pub async fn update_volume(
 	.body
 	.volume;
 
+	let version = query!(
+		r#"
+		SELECT
+			version
+		FROM
+			deployment_volume
+		WHERE
+			id = $1
+		FOR UPDATE;
+		"#,
+		volume_id as _
+	)
+	.fetch_optional(&mut **database)
+	.await?
+	.map(|row| ResourceVersion(row.version))
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	if !version.satisfies(if_match.as_ref()) {
+		debug!("Volume `{volume_id}` has been modified since it was last fetched");
+		return Err(ErrorType::PreconditionFailed);
+	}
+
 	if let Some(size) = size {
 		if volume.size > size {
 			return Err(ErrorType::CannotReduceVolumeSize);
@@ -60,11 +85,12 @@ pub async fn update_volume(
 			deployment_volume
 		SET
 			volume_size = COALESCE($1, volume_size),
-			name = COALESCE($2, name)
+			name = COALESCE($2, name),
+			version = version + 1
 		WHERE
 			id = $3;
 		"#,
-		size.map(|size| size as i32),
+		size.map(i32::try_from).transpose()?,
 		name.as_deref(),
 		volume_id as _
 	)
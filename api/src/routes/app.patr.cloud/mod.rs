@@ -41,7 +41,7 @@ async fn read_files(path: &str) -> Vec<String> {
 	let mut files = Vec::new();
 	let mut read_dir = fs::read_dir(path)
 		.await
-		.expect(&format!("failed to read directory: `{}`", path));
+		.unwrap_or_else(|err| panic!("failed to read directory `{}`: {}", path, err));
 	while let Some(entry) = read_dir.next_entry().await.expect("failed to read entry") {
 		let path = entry.path();
 		if path.is_dir() {
@@ -0,0 +1,208 @@
+use std::{
+	collections::HashMap,
+	fmt::{self, Display, Formatter},
+};
+
+use axum::{
+	extract::{Path, Request, State},
+	http::{header, HeaderValue, Method, StatusCode},
+	middleware::Next,
+	response::{IntoResponse, Response},
+};
+use headers::{authorization::Bearer, Authorization, HeaderMapExt};
+use jsonwebtoken::{DecodingKey, Validation};
+
+use super::{Error, RegistryError};
+use crate::{
+	models::registry_token::{RegistryAction, RegistryToken},
+	prelude::*,
+};
+
+/// The middleware that authorizes every request to the `/v2` routes of the
+/// registry. The request must have a [`RegistryToken`] (issued by the `/token`
+/// route) that grants the action required by the request on the repository
+/// being accessed. If it doesn't, a `401` is returned along with a
+/// `WWW-Authenticate` header, which tells the client where to get a token from
+/// and the scope that the token needs. The token is added to the extensions of
+/// the request, for routes that need to know who is accessing them.
+pub(super) async fn authorize(
+	State(state): State<AppState>,
+	params: Option<Path<HashMap<String, String>>>,
+	mut request: Request,
+	next: Next,
+) -> Response {
+	// The `/v2/` route doesn't have a repository, and only needs a valid token
+	let scope = if request.uri().path() == "/_catalog" {
+		Some(RequiredScope {
+			r#type: "registry",
+			name: "catalog".to_string(),
+			action: RegistryAction::Pull,
+		})
+	} else if let Some(Path(params)) = &params {
+		if let Some((workspace_id, repo_name)) =
+			params.get("workspaceId").zip(params.get("repoName"))
+		{
+			Some(RequiredScope {
+				r#type: "repository",
+				name: format!("{workspace_id}/{repo_name}"),
+				action: required_action(request.method(), request.uri().path()),
+			})
+		} else {
+			// The pull-through cache can only be pulled from, and each image in
+			// the cache is its own repository
+			params
+				.get("workspaceId")
+				.zip(params.get("upstream"))
+				.zip(params.get("path"))
+				.and_then(|((workspace_id, upstream), path)| {
+					let (image, _) = super::pull_through_cache::parse_cache_path(path)?;
+					Some(RequiredScope {
+						r#type: "repository",
+						name: format!("{workspace_id}/_cache/{upstream}/{image}"),
+						action: RegistryAction::Pull,
+					})
+				})
+		}
+	} else {
+		None
+	};
+
+	let token = request
+		.headers()
+		.typed_get::<Authorization<Bearer>>()
+		.and_then(|Authorization(bearer)| {
+			jsonwebtoken::decode::<RegistryToken>(
+				bearer.token(),
+				&DecodingKey::from_secret(state.config.jwt_secret.as_ref()),
+				&{
+					let mut validation = Validation::default();
+					validation.set_audience(&[constants::CONTAINER_REGISTRY_SERVICE]);
+					validation.set_issuer(&[constants::JWT_ISSUER]);
+					validation
+				},
+			)
+			.inspect_err(|err| debug!("Invalid registry token: {}", err))
+			.ok()
+		})
+		.map(|data| data.claims);
+
+	let authorized = match (&token, &scope) {
+		(None, _) => false,
+		(Some(_), None) => true,
+		(Some(token), Some(scope)) => token.has_access(scope.r#type, &scope.name, scope.action),
+	};
+
+	if authorized {
+		if let Some(token) = token {
+			request.extensions_mut().insert(token);
+		}
+		return next.run(request).await;
+	}
+
+	let mut challenge = format!(
+		r#"Bearer realm="{}",service="{}""#,
+		constants::CONTAINER_REGISTRY_TOKEN_REALM,
+		constants::CONTAINER_REGISTRY_SERVICE,
+	);
+	if let Some(scope) = &scope {
+		challenge.push_str(&format!(r#",scope="{}""#, scope));
+	}
+	if token.is_some() {
+		challenge.push_str(r#",error="insufficient_scope""#);
+	}
+
+	let Ok(challenge) = HeaderValue::from_str(&challenge) else {
+		return Error::new(
+			RegistryError::NameInvalid,
+			StatusCode::BAD_REQUEST,
+			"Invalid repository name",
+		)
+		.into_response();
+	};
+
+	let error = if token.is_some() {
+		Error::new(
+			RegistryError::Denied,
+			StatusCode::UNAUTHORIZED,
+			"Requested access to the resource is denied",
+		)
+	} else {
+		Error::new(
+			RegistryError::Unauthorized,
+			StatusCode::UNAUTHORIZED,
+			"Authentication required",
+		)
+	};
+
+	([(header::WWW_AUTHENTICATE, challenge)], error).into_response()
+}
+
+/// The resource that a request needs access to, along with the action that
+/// needs to be allowed on it.
+struct RequiredScope {
+	/// The type of the resource, either `repository` or `registry`
+	r#type: &'static str,
+	/// The name of the resource
+	name: String,
+	/// The action that needs to be allowed on the resource
+	action: RegistryAction,
+}
+
+impl Display for RequiredScope {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		// The catalog of the registry is always requested with all actions
+		if self.r#type == "registry" {
+			write!(f, "{}:{}:*", self.r#type, self.name)
+		} else {
+			write!(f, "{}:{}:{}", self.r#type, self.name, self.action.as_str())
+		}
+	}
+}
+
+/// The action that a request needs to be allowed on the repository, based on
+/// the method and path of the request.
+fn required_action(method: &Method, path: &str) -> RegistryAction {
+	match *method {
+		Method::GET | Method::HEAD => RegistryAction::Pull,
+		// Cancelling an upload is part of pushing, not deleting an image
+		Method::DELETE if !path.contains("/blobs/uploads/") => RegistryAction::Delete,
+		_ => RegistryAction::Push,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use axum::http::Method;
+
+	use super::required_action;
+	use crate::models::registry_token::RegistryAction;
+
+	#[test]
+	fn assert_required_actions() {
+		let blob = "/ws/repo/blobs/sha256:abc";
+		let upload = "/ws/repo/blobs/uploads/1234";
+		let manifest = "/ws/repo/manifests/latest";
+
+		assert_eq!(required_action(&Method::GET, blob), RegistryAction::Pull);
+		assert_eq!(
+			required_action(&Method::HEAD, manifest),
+			RegistryAction::Pull
+		);
+		assert_eq!(
+			required_action(&Method::PATCH, upload),
+			RegistryAction::Push
+		);
+		assert_eq!(
+			required_action(&Method::PUT, manifest),
+			RegistryAction::Push
+		);
+		assert_eq!(
+			required_action(&Method::DELETE, upload),
+			RegistryAction::Push
+		);
+		assert_eq!(
+			required_action(&Method::DELETE, manifest),
+			RegistryAction::Delete
+		);
+	}
+}
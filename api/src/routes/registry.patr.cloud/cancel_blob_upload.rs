@@ -0,0 +1,39 @@
+use axum::{
+	extract::{Path, State},
+	http::StatusCode,
+	response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{upload_session::UploadSession, Error};
+use crate::prelude::*;
+
+/// The parameters that are passed in the path of the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathParams {
+	/// The workspace ID of the repository
+	workspace_id: Uuid,
+	/// The name of the repository
+	repo_name: String,
+	/// The ID of the upload session
+	upload_id: Uuid,
+}
+
+/// Handles the `DELETE
+/// /v2/{workspace_id}/{repo_name}/blobs/uploads/{upload_id}` route.
+#[axum::debug_handler]
+pub(super) async fn handle(
+	Path(path): Path<PathParams>,
+	State(state): State<AppState>,
+) -> Result<impl IntoResponse, Error> {
+	let mut database = state.database.begin().await?;
+	let repository_id =
+		super::get_repository_id(&mut database, path.workspace_id, &path.repo_name).await?;
+
+	let session = UploadSession::load(&state.redis, path.upload_id, repository_id).await?;
+	let storage = super::get_storage(&state.config.storage)?;
+	session.discard(&state.redis, &*storage).await?;
+
+	Ok(StatusCode::NO_CONTENT)
+}
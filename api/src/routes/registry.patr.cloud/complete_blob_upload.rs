@@ -0,0 +1,108 @@
+use axum::{
+	body::Body,
+	extract::{Path, Query, State},
+	http::{HeaderMap, StatusCode},
+	response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+	quota,
+	upload_session::{self, UploadSession, UploadSessionLock},
+	Error,
+	RegistryError,
+};
+use crate::prelude::*;
+
+/// The parameters that are passed in the path of the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathParams {
+	/// The workspace ID of the repository
+	workspace_id: Uuid,
+	/// The name of the repository
+	repo_name: String,
+	/// The ID of the upload session
+	upload_id: Uuid,
+}
+
+/// The parameters that are passed in the query of the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryParams {
+	/// The digest of the entire blob that was uploaded
+	digest: String,
+}
+
+/// Handles the `PUT /v2/{workspace_id}/{repo_name}/blobs/uploads/{upload_id}`
+/// route.
+///
+/// The body of the request (if any) is the last chunk of the blob. Once it is
+/// uploaded, the chunks are joined and verified against the given digest. The
+/// upload is discarded if the blob exceeds the storage limits of the repository
+/// (or its workspace).
+#[axum::debug_handler]
+pub(super) async fn handle(
+	Path(path): Path<PathParams>,
+	Query(query): Query<QueryParams>,
+	State(state): State<AppState>,
+	headers: HeaderMap,
+	body: Body,
+) -> Result<impl IntoResponse, Error> {
+	let mut database = state.database.begin().await?;
+	let repository_id =
+		super::get_repository_id(&mut database, path.workspace_id, &path.repo_name).await?;
+
+	let _lock = UploadSessionLock::acquire(&state.redis, path.upload_id).await?;
+	let mut session = UploadSession::load(&state.redis, path.upload_id, repository_id).await?;
+	let storage = super::get_storage(&state.config.storage)?;
+
+	if !super::is_valid_digest(&query.digest) {
+		session.discard(&state.redis, &*storage).await?;
+		return Err(Error::new(
+			RegistryError::DigestInvalid,
+			StatusCode::BAD_REQUEST,
+			"Invalid digest",
+		));
+	}
+
+	if let Err(err) = quota::check_chunk_upload(
+		&mut database,
+		&state.config.container_registry,
+		path.workspace_id,
+		repository_id,
+		session.size,
+		&headers,
+	)
+	.await
+	{
+		session.discard(&state.redis, &*storage).await?;
+		return Err(err);
+	}
+
+	session.push_chunk(&*storage, body).await?;
+	if let Err(err) = quota::check_blob_upload(
+		&mut database,
+		&state.config.container_registry,
+		path.workspace_id,
+		repository_id,
+		session.size,
+	)
+	.await
+	{
+		session.discard(&state.redis, &*storage).await?;
+		return Err(err);
+	}
+	let size = session
+		.finish(&state.redis, &*storage, &query.digest)
+		.await?;
+	upload_session::register_blob(&mut database, &query.digest, size).await?;
+	upload_session::link_blob(&mut database, repository_id, &query.digest).await?;
+
+	database.commit().await?;
+
+	Ok((
+		StatusCode::CREATED,
+		upload_session::uploaded_blob_headers(path.workspace_id, &path.repo_name, &query.digest)?,
+	))
+}
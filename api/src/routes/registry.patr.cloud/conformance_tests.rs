@@ -0,0 +1,428 @@
+use axum::{
+	body::Body,
+	http::{header, Method, Request, Response, StatusCode},
+	Router,
+};
+use jsonwebtoken::EncodingKey;
+use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
+use tower::ServiceExt;
+
+use crate::{
+	models::registry_token::{RegistryAction, RegistryToken, RegistryTokenAccess},
+	prelude::*,
+	utils::test_utils,
+};
+
+/// The media type of the image manifests pushed by the tests
+pub(super) const MANIFEST_MEDIA_TYPE: &str = "application/vnd.oci.image.manifest.v1+json";
+
+/// The config blob of the images pushed by the tests
+pub(super) const CONFIG: &[u8] =
+	br#"{"architecture":"amd64","os":"linux","rootfs":{"type":"layers","diff_ids":[]}}"#;
+
+/// Issues a registry token to the user that grants the given actions on each
+/// of the repositories (in the format `<workspace ID>/<repository name>`).
+pub(super) fn issue_token(
+	state: &AppState,
+	user_id: Uuid,
+	access: &[(&str, &[RegistryAction])],
+) -> String {
+	let iat = OffsetDateTime::now_utc();
+	jsonwebtoken::encode(
+		&Default::default(),
+		&RegistryToken {
+			iss: constants::JWT_ISSUER.to_string(),
+			sub: user_id,
+			login_id: None,
+			aud: constants::CONTAINER_REGISTRY_SERVICE.to_string(),
+			exp: iat + RegistryToken::VALIDITY,
+			nbf: iat,
+			iat,
+			jti: Uuid::new_v4(),
+			access: access
+				.iter()
+				.map(|(name, actions)| RegistryTokenAccess {
+					r#type: "repository".to_string(),
+					name: name.to_string(),
+					actions: actions.to_vec(),
+				})
+				.collect(),
+		},
+		&EncodingKey::from_secret(state.config.jwt_secret.as_ref()),
+	)
+	.unwrap()
+}
+
+/// Sends a request to the registry with the given token
+pub(super) async fn send(
+	router: &Router,
+	method: Method,
+	uri: &str,
+	token: &str,
+	content_type: Option<&str>,
+	body: impl Into<Body>,
+) -> Response<Body> {
+	let mut request = Request::builder()
+		.method(method)
+		.uri(uri)
+		.header(header::AUTHORIZATION, format!("Bearer {token}"));
+	if let Some(content_type) = content_type {
+		request = request.header(header::CONTENT_TYPE, content_type);
+	}
+
+	router
+		.clone()
+		.oneshot(request.body(body.into()).unwrap())
+		.await
+		.unwrap()
+}
+
+/// Reads the entire body of a response
+pub(super) async fn read_body(response: Response<Body>) -> Vec<u8> {
+	axum::body::to_bytes(response.into_body(), usize::MAX)
+		.await
+		.unwrap()
+		.to_vec()
+}
+
+/// The digest of some data, as used by the registry
+pub(super) fn digest_of(data: &[u8]) -> String {
+	format!("sha256:{}", hex::encode(Sha256::digest(data)))
+}
+
+/// Uploads a blob to a repository in a single request
+pub(super) async fn push_blob(
+	router: &Router,
+	token: &str,
+	repository: &str,
+	data: &[u8],
+) -> StatusCode {
+	send(
+		router,
+		Method::POST,
+		&format!("/v2/{repository}/blobs/uploads/?digest={}", digest_of(data)),
+		token,
+		None,
+		data.to_vec(),
+	)
+	.await
+	.status()
+}
+
+/// An image manifest with the test config and the given layer
+pub(super) fn image_manifest(layer: &[u8]) -> Vec<u8> {
+	serde_json::to_vec(&serde_json::json!({
+		"schemaVersion": 2,
+		"mediaType": MANIFEST_MEDIA_TYPE,
+		"config": {
+			"mediaType": "application/vnd.oci.image.config.v1+json",
+			"digest": digest_of(CONFIG),
+			"size": CONFIG.len(),
+		},
+		"layers": [{
+			"mediaType": "application/vnd.oci.image.layer.v1.tar+gzip",
+			"digest": digest_of(layer),
+			"size": layer.len(),
+		}],
+	}))
+	.unwrap()
+}
+
+/// Random data to use as a layer, so that tests don't share blobs
+pub(super) fn random_layer() -> Vec<u8> {
+	Uuid::new_v4().to_string().repeat(64).into_bytes()
+}
+
+/// Mounts a blob into a repository from another repository, returning the
+/// status of the response
+async fn mount_blob(
+	router: &Router,
+	token: &str,
+	repository: &str,
+	from: &str,
+	digest: &str,
+) -> StatusCode {
+	send(
+		router,
+		Method::POST,
+		&format!("/v2/{repository}/blobs/uploads/?mount={digest}&from={from}"),
+		token,
+		None,
+		Body::empty(),
+	)
+	.await
+	.status()
+}
+
+/// Checks if a blob can be downloaded from a repository
+async fn has_blob(router: &Router, token: &str, repository: &str, digest: &str) -> bool {
+	send(
+		router,
+		Method::HEAD,
+		&format!("/v2/{repository}/blobs/{digest}"),
+		token,
+		None,
+		Body::empty(),
+	)
+	.await
+	.status() ==
+		StatusCode::OK
+}
+
+#[tokio::test]
+#[ignore = "requires a database and Redis instance"]
+async fn assert_image_push_and_pull() {
+	let state = test_utils::setup_state().await;
+	let router = super::setup_routes(&state).await;
+	let workspace = test_utils::create_workspace(&state).await;
+	test_utils::create_repository(&state, workspace.workspace_id, "app").await;
+	let repository = format!("{}/app", workspace.workspace_id);
+	let token = issue_token(
+		&state,
+		workspace.user_id,
+		&[(&repository, &[RegistryAction::Pull, RegistryAction::Push])],
+	);
+
+	// Upload the layer in chunks
+	let layer = random_layer();
+	let (first, last) = layer.split_at(layer.len() / 2);
+	let response = send(
+		&router,
+		Method::POST,
+		&format!("/v2/{repository}/blobs/uploads/"),
+		&token,
+		None,
+		Body::empty(),
+	)
+	.await;
+	assert_eq!(response.status(), StatusCode::ACCEPTED);
+	let location = response.headers()[header::LOCATION]
+		.to_str()
+		.unwrap()
+		.to_string();
+
+	let response = send(
+		&router,
+		Method::PATCH,
+		&location,
+		&token,
+		None,
+		first.to_vec(),
+	)
+	.await;
+	assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+	let response = send(
+		&router,
+		Method::PUT,
+		&format!("{location}?digest={}", digest_of(&layer)),
+		&token,
+		None,
+		last.to_vec(),
+	)
+	.await;
+	assert_eq!(response.status(), StatusCode::CREATED);
+
+	// Upload the config in a single request
+	assert_eq!(
+		push_blob(&router, &token, &repository, CONFIG).await,
+		StatusCode::CREATED
+	);
+
+	let manifest = image_manifest(&layer);
+	let response = send(
+		&router,
+		Method::PUT,
+		&format!("/v2/{repository}/manifests/latest"),
+		&token,
+		Some(MANIFEST_MEDIA_TYPE),
+		manifest.clone(),
+	)
+	.await;
+	assert_eq!(response.status(), StatusCode::CREATED);
+	assert_eq!(
+		response.headers()["docker-content-digest"],
+		digest_of(&manifest).as_str()
+	);
+
+	let response = send(
+		&router,
+		Method::GET,
+		&format!("/v2/{repository}/manifests/latest"),
+		&token,
+		None,
+		Body::empty(),
+	)
+	.await;
+	assert_eq!(response.status(), StatusCode::OK);
+	assert_eq!(read_body(response).await, manifest);
+
+	let response = send(
+		&router,
+		Method::HEAD,
+		&format!("/v2/{repository}/blobs/{}", digest_of(&layer)),
+		&token,
+		None,
+		Body::empty(),
+	)
+	.await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let response = send(
+		&router,
+		Method::GET,
+		&format!("/v2/{repository}/blobs/{}", digest_of(&layer)),
+		&token,
+		None,
+		Body::empty(),
+	)
+	.await;
+	assert_eq!(response.status(), StatusCode::OK);
+	assert_eq!(read_body(response).await, layer);
+
+	let response = send(
+		&router,
+		Method::GET,
+		&format!("/v2/{repository}/tags/list"),
+		&token,
+		None,
+		Body::empty(),
+	)
+	.await;
+	assert_eq!(response.status(), StatusCode::OK);
+	let tags = serde_json::from_slice::<serde_json::Value>(&read_body(response).await).unwrap();
+	assert_eq!(tags["tags"], serde_json::json!(["latest"]));
+}
+
+#[tokio::test]
+#[ignore = "requires a database and Redis instance"]
+async fn assert_blobs_are_scoped_to_repositories() {
+	let state = test_utils::setup_state().await;
+	let router = super::setup_routes(&state).await;
+
+	let owner = test_utils::create_workspace(&state).await;
+	test_utils::create_repository(&state, owner.workspace_id, "app").await;
+	let owner_repository = format!("{}/app", owner.workspace_id);
+	let owner_token = issue_token(
+		&state,
+		owner.user_id,
+		&[(
+			&owner_repository,
+			&[RegistryAction::Pull, RegistryAction::Push],
+		)],
+	);
+
+	let layer = random_layer();
+	assert_eq!(
+		push_blob(&router, &owner_token, &owner_repository, &layer).await,
+		StatusCode::CREATED
+	);
+	assert_eq!(
+		push_blob(&router, &owner_token, &owner_repository, CONFIG).await,
+		StatusCode::CREATED
+	);
+
+	// Another workspace that knows the digests of the blobs
+	let other = test_utils::create_workspace(&state).await;
+	test_utils::create_repository(&state, other.workspace_id, "app").await;
+	let other_repository = format!("{}/app", other.workspace_id);
+	let other_token = issue_token(
+		&state,
+		other.user_id,
+		&[(
+			&other_repository,
+			&[RegistryAction::Pull, RegistryAction::Push],
+		)],
+	);
+
+	for method in [Method::GET, Method::HEAD] {
+		let response = send(
+			&router,
+			method,
+			&format!("/v2/{other_repository}/blobs/{}", digest_of(&layer)),
+			&other_token,
+			None,
+			Body::empty(),
+		)
+		.await;
+		assert_eq!(response.status(), StatusCode::NOT_FOUND);
+	}
+
+	let response = send(
+		&router,
+		Method::PUT,
+		&format!("/v2/{other_repository}/manifests/latest"),
+		&other_token,
+		Some(MANIFEST_MEDIA_TYPE),
+		image_manifest(&layer),
+	)
+	.await;
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+	// The repository the blobs were pushed to can still use them
+	let response = send(
+		&router,
+		Method::PUT,
+		&format!("/v2/{owner_repository}/manifests/latest"),
+		&owner_token,
+		Some(MANIFEST_MEDIA_TYPE),
+		image_manifest(&layer),
+	)
+	.await;
+	assert_eq!(response.status(), StatusCode::CREATED);
+}
+
+#[tokio::test]
+#[ignore = "requires a database and Redis instance"]
+async fn assert_blob_mounts_need_the_blob_in_a_pulled_repository() {
+	let state = test_utils::setup_state().await;
+	let router = super::setup_routes(&state).await;
+	let workspace = test_utils::create_workspace(&state).await;
+	for name in ["source", "target", "empty"] {
+		test_utils::create_repository(&state, workspace.workspace_id, name).await;
+	}
+	let source = format!("{}/source", workspace.workspace_id);
+	let target = format!("{}/target", workspace.workspace_id);
+	let empty = format!("{}/empty", workspace.workspace_id);
+	let all_actions = &[RegistryAction::Pull, RegistryAction::Push][..];
+
+	let layer = random_layer();
+	let digest = digest_of(&layer);
+	let source_token = issue_token(&state, workspace.user_id, &[(&source, all_actions)]);
+	assert_eq!(
+		push_blob(&router, &source_token, &source, &layer).await,
+		StatusCode::CREATED
+	);
+
+	// Without pull access to the source, an upload session is started instead
+	let token = issue_token(&state, workspace.user_id, &[(&target, all_actions)]);
+	assert_eq!(
+		mount_blob(&router, &token, &target, &source, &digest).await,
+		StatusCode::ACCEPTED
+	);
+	assert!(!has_blob(&router, &token, &target, &digest).await);
+
+	// A repository that can be pulled from, but doesn't have the blob
+	let token = issue_token(
+		&state,
+		workspace.user_id,
+		&[(&target, all_actions), (&empty, &[RegistryAction::Pull])],
+	);
+	assert_eq!(
+		mount_blob(&router, &token, &target, &empty, &digest).await,
+		StatusCode::ACCEPTED
+	);
+	assert!(!has_blob(&router, &token, &target, &digest).await);
+
+	let token = issue_token(
+		&state,
+		workspace.user_id,
+		&[(&target, all_actions), (&source, &[RegistryAction::Pull])],
+	);
+	assert_eq!(
+		mount_blob(&router, &token, &target, &source, &digest).await,
+		StatusCode::CREATED
+	);
+	assert!(has_blob(&router, &token, &target, &digest).await);
+}
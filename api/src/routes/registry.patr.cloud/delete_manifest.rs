@@ -0,0 +1,168 @@
+use axum::{
+	extract::{Path, State},
+	http::StatusCode,
+	response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{retention, Error, RegistryError};
+use crate::prelude::*;
+
+/// The parameters that are passed in the path of the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathParams {
+	/// The workspace ID of the repository
+	workspace_id: Uuid,
+	/// The name of the repository
+	repo_name: String,
+	/// The reference of the manifest. Either a tag or the digest of the
+	/// manifest
+	reference: String,
+}
+
+/// Handles the `DELETE /v2/{workspace_id}/{repo_name}/manifests/{reference}`
+/// route.
+///
+/// Deleting a tag only removes the tag, and the manifest stays in the
+/// repository. Deleting a digest removes the manifest from the repository,
+/// along with all the tags pointing to it. The manifest and its blobs are only
+/// removed from storage once they are not used by any repository. A manifest
+/// that is a part of an image index in the repository cannot be deleted until
+/// the index is deleted. Tags that match an immutable tag pattern of the
+/// repository (and manifests that such tags point to) cannot be deleted.
+#[axum::debug_handler]
+pub(super) async fn handle(
+	Path(path): Path<PathParams>,
+	State(state): State<AppState>,
+) -> Result<impl IntoResponse, Error> {
+	let mut database = state.database.begin().await?;
+	let repository_id =
+		super::get_repository_id(&mut database, path.workspace_id, &path.repo_name).await?;
+
+	let deleted = if super::is_valid_digest(&path.reference) {
+		let is_in_index = query!(
+			r#"
+			SELECT
+				1 AS "exists"
+			FROM
+				container_registry_index_manifest
+			INNER JOIN
+				container_registry_repository_manifest
+			ON
+				container_registry_repository_manifest.manifest_digest =
+					container_registry_index_manifest.index_digest
+			WHERE
+				container_registry_repository_manifest.repository_id = $1 AND
+				container_registry_index_manifest.manifest_digest = $2
+			LIMIT 1;
+			"#,
+			repository_id as _,
+			&path.reference,
+		)
+		.fetch_optional(&mut *database)
+		.await?
+		.is_some();
+
+		if is_in_index {
+			return Err(Error::new(
+				RegistryError::Denied,
+				StatusCode::CONFLICT,
+				"The manifest is a part of an image index in the repository",
+			));
+		}
+
+		let tags = query!(
+			r#"
+			SELECT
+				tag
+			FROM
+				container_registry_repository_tag
+			WHERE
+				repository_id = $1 AND
+				manifest_digest = $2;
+			"#,
+			repository_id as _,
+			&path.reference,
+		)
+		.fetch_all(&mut *database)
+		.await?;
+
+		for row in tags {
+			if retention::is_tag_immutable(&mut database, repository_id, &row.tag).await? {
+				return Err(Error::new(
+					RegistryError::Denied,
+					StatusCode::CONFLICT,
+					format!(
+						"The manifest is tagged with the immutable tag `{}`",
+						row.tag
+					),
+				));
+			}
+		}
+
+		query!(
+			r#"
+			DELETE FROM
+				container_registry_repository_tag
+			WHERE
+				repository_id = $1 AND
+				manifest_digest = $2;
+			"#,
+			repository_id as _,
+			&path.reference,
+		)
+		.execute(&mut *database)
+		.await?;
+
+		query!(
+			r#"
+			DELETE FROM
+				container_registry_repository_manifest
+			WHERE
+				repository_id = $1 AND
+				manifest_digest = $2;
+			"#,
+			repository_id as _,
+			&path.reference,
+		)
+		.execute(&mut *database)
+		.await?
+		.rows_affected()
+	} else {
+		if retention::is_tag_immutable(&mut database, repository_id, &path.reference).await? {
+			return Err(Error::new(
+				RegistryError::Denied,
+				StatusCode::CONFLICT,
+				"The tag is immutable and cannot be deleted",
+			));
+		}
+
+		query!(
+			r#"
+			DELETE FROM
+				container_registry_repository_tag
+			WHERE
+				repository_id = $1 AND
+				tag = $2;
+			"#,
+			repository_id as _,
+			&path.reference,
+		)
+		.execute(&mut *database)
+		.await?
+		.rows_affected()
+	};
+
+	if deleted == 0 {
+		return Err(Error::new(
+			RegistryError::ManifestUnknown,
+			StatusCode::NOT_FOUND,
+			"Manifest not found",
+		));
+	}
+
+	database.commit().await?;
+
+	Ok(StatusCode::ACCEPTED)
+}
@@ -0,0 +1,622 @@
+use std::time::Duration as StdDuration;
+
+use rustis::commands::GenericCommands;
+use time::{Duration, OffsetDateTime};
+
+use super::Error;
+use crate::{prelude::*, utils::periodic_task};
+
+/// A summary of a garbage collection run. In dry run mode, this is what would
+/// have been deleted, had the garbage collector been allowed to delete things.
+#[derive(Debug, Clone, Default)]
+pub struct GarbageCollectionReport {
+	/// The manifests that aren't a part of any repository
+	pub manifests: Vec<CollectedObject>,
+	/// The blobs that aren't referenced by any manifest
+	pub blobs: Vec<CollectedObject>,
+	/// The objects left behind by upload sessions that have expired
+	pub upload_objects: Vec<CollectedObject>,
+}
+
+impl GarbageCollectionReport {
+	/// The total size of everything in the report, in bytes
+	pub fn reclaimable_bytes(&self) -> u64 {
+		self.manifests
+			.iter()
+			.chain(&self.blobs)
+			.chain(&self.upload_objects)
+			.map(|object| object.size)
+			.sum()
+	}
+}
+
+/// A manifest, blob or upload object that was deleted by the garbage collector
+/// (or would have been, in dry run mode)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollectedObject {
+	/// The digest of the manifest or blob, or the key of the upload object in
+	/// the storage
+	pub name: String,
+	/// The size of the object, in bytes
+	pub size: u64,
+}
+
+/// Runs a background task that periodically runs the garbage collector of the
+/// registry, based on the [`ContainerRegistryConfig`][1]. In dry run mode,
+/// every object that would have been deleted is logged.
+///
+/// [1]: crate::utils::config::ContainerRegistryConfig
+#[instrument(skip(state))]
+pub async fn run(state: &AppState) {
+	let config = &state.config.container_registry;
+	let interval = StdDuration::from_secs(config.gc_interval_minutes * 60);
+
+	periodic_task::run_periodically(interval, || async {
+		match collect_garbage(state, config.gc_dry_run).await {
+			Ok(report) if config.gc_dry_run => {
+				for (kind, objects) in [
+					("Manifest", &report.manifests),
+					("Blob", &report.blobs),
+					("Upload object", &report.upload_objects),
+				] {
+					for object in objects {
+						info!(
+							"Registry GC (dry run): {} `{}` can be deleted, reclaiming {} bytes",
+							kind, object.name, object.size
+						);
+					}
+				}
+				info!(
+					"Registry GC (dry run): {} manifests, {} blobs and {} upload objects can be deleted, reclaiming {} bytes",
+					report.manifests.len(),
+					report.blobs.len(),
+					report.upload_objects.len(),
+					report.reclaimable_bytes()
+				);
+			}
+			Ok(report) => info!(
+				"Registry GC: deleted {} manifests, {} blobs and {} upload objects, reclaiming {} bytes",
+				report.manifests.len(),
+				report.blobs.len(),
+				report.upload_objects.len(),
+				report.reclaimable_bytes()
+			),
+			Err(err) => error!("Error running registry GC: {:?}", err),
+		}
+	})
+	.await;
+}
+
+/// Runs a single mark-and-sweep pass over the registry.
+///
+/// A manifest is garbage if no repository (or pull-through cache) has it.
+/// Manifests that are a part of an image index are only collected once the
+/// index itself is collected. A blob is garbage if it is not cached and is not
+/// referenced by any manifest that is not garbage itself. Anything that was
+/// created (or uploaded again) within the grace period is left alone, so that
+/// images that are in the middle of being pushed aren't deleted before their
+/// manifest is uploaded. Every deletion re-checks these conditions in the
+/// database, so a push that races with the garbage collector keeps its data.
+#[instrument(skip(state))]
+pub async fn collect_garbage(
+	state: &AppState,
+	dry_run: bool,
+) -> Result<GarbageCollectionReport, Error> {
+	let grace_period = Duration::minutes(
+		state
+			.config
+			.container_registry
+			.gc_grace_period_minutes
+			.try_into()
+			.unwrap_or(i64::MAX),
+	);
+	let cutoff = OffsetDateTime::now_utc() - grace_period;
+
+	let storage = super::get_storage(&state.config.storage)?;
+	let mut report = GarbageCollectionReport::default();
+
+	let manifests = query!(
+		r#"
+		SELECT
+			manifest_digest,
+			size
+		FROM
+			container_registry_manifest
+		WHERE
+			created < $1 AND
+			NOT EXISTS (
+				SELECT
+					1
+				FROM
+					container_registry_repository_manifest
+				WHERE
+					container_registry_repository_manifest.manifest_digest =
+						container_registry_manifest.manifest_digest
+			) AND
+			NOT EXISTS (
+				SELECT
+					1
+				FROM
+					container_registry_cache_manifest
+				WHERE
+					container_registry_cache_manifest.manifest_digest =
+						container_registry_manifest.manifest_digest
+			);
+		"#,
+		cutoff,
+	)
+	.fetch_all(&state.database)
+	.await?;
+
+	for manifest in manifests {
+		if !dry_run {
+			let mut database = state.database.begin().await?;
+
+			query!(
+				r#"
+				DELETE FROM
+					container_registry_index_manifest
+				WHERE
+					index_digest = $1;
+				"#,
+				&manifest.manifest_digest,
+			)
+			.execute(&mut *database)
+			.await?;
+
+			query!(
+				r#"
+				DELETE FROM
+					container_registry_manifest_blob
+				WHERE
+					manifest_digest = $1;
+				"#,
+				&manifest.manifest_digest,
+			)
+			.execute(&mut *database)
+			.await?;
+
+			// The manifest may have been pushed again since it was marked
+			let deleted = query!(
+				r#"
+				DELETE FROM
+					container_registry_manifest
+				WHERE
+					manifest_digest = $1 AND
+					created < $2 AND
+					NOT EXISTS (
+						SELECT
+							1
+						FROM
+							container_registry_repository_manifest
+						WHERE
+							manifest_digest = $1
+					) AND
+					NOT EXISTS (
+						SELECT
+							1
+						FROM
+							container_registry_cache_manifest
+						WHERE
+							manifest_digest = $1
+					) AND
+					NOT EXISTS (
+						SELECT
+							1
+						FROM
+							container_registry_index_manifest
+						WHERE
+							manifest_digest = $1
+					);
+				"#,
+				&manifest.manifest_digest,
+				cutoff,
+			)
+			.execute(&mut *database)
+			.await?
+			.rows_affected();
+
+			if deleted == 0 {
+				database.rollback().await?;
+				continue;
+			}
+			database.commit().await?;
+
+			storage
+				.delete_object(&super::get_object_name_for_manifest(
+					&manifest.manifest_digest,
+				))
+				.await?;
+		}
+
+		report.manifests.push(CollectedObject {
+			name: manifest.manifest_digest,
+			size: manifest.size.try_into().unwrap_or_default(),
+		});
+	}
+
+	// In a dry run, the garbage manifests are still around, so blobs that are
+	// only referenced by them need to be counted as garbage too
+	let blobs = query!(
+		r#"
+		SELECT
+			blob_digest,
+			size
+		FROM
+			container_registry_repository_blob
+		WHERE
+			created < $1 AND
+			NOT EXISTS (
+				SELECT
+					1
+				FROM
+					container_registry_manifest_blob
+				INNER JOIN
+					container_registry_manifest
+				ON
+					container_registry_manifest.manifest_digest =
+						container_registry_manifest_blob.manifest_digest
+				WHERE
+					(
+						container_registry_manifest_blob.blob_digest =
+							container_registry_repository_blob.blob_digest OR
+						container_registry_manifest_blob.parent_blob_digest =
+							container_registry_repository_blob.blob_digest
+					) AND
+					(
+						container_registry_manifest.created >= $1 OR
+						EXISTS (
+							SELECT
+								1
+							FROM
+								container_registry_repository_manifest
+							WHERE
+								container_registry_repository_manifest.manifest_digest =
+									container_registry_manifest.manifest_digest
+						) OR
+						EXISTS (
+							SELECT
+								1
+							FROM
+								container_registry_cache_manifest
+							WHERE
+								container_registry_cache_manifest.manifest_digest =
+									container_registry_manifest.manifest_digest
+						)
+					)
+			) AND
+			NOT EXISTS (
+				SELECT
+					1
+				FROM
+					container_registry_cache_blob
+				WHERE
+					container_registry_cache_blob.blob_digest =
+						container_registry_repository_blob.blob_digest
+			);
+		"#,
+		cutoff,
+	)
+	.fetch_all(&state.database)
+	.await?;
+
+	for blob in blobs {
+		if !dry_run {
+			let mut database = state.database.begin().await?;
+
+			query!(
+				r#"
+				DELETE FROM
+					container_registry_repository_blob_link
+				WHERE
+					blob_digest = $1;
+				"#,
+				&blob.blob_digest,
+			)
+			.execute(&mut *database)
+			.await?;
+
+			// The blob may have been uploaded again since it was marked
+			let deleted = query!(
+				r#"
+				DELETE FROM
+					container_registry_repository_blob
+				WHERE
+					blob_digest = $1 AND
+					created < $2 AND
+					NOT EXISTS (
+						SELECT
+							1
+						FROM
+							container_registry_manifest_blob
+						WHERE
+							blob_digest = $1 OR
+							parent_blob_digest = $1
+					) AND
+					NOT EXISTS (
+						SELECT
+							1
+						FROM
+							container_registry_cache_blob
+						WHERE
+							blob_digest = $1
+					);
+				"#,
+				&blob.blob_digest,
+				cutoff,
+			)
+			.execute(&mut *database)
+			.await?
+			.rows_affected();
+
+			if deleted == 0 {
+				database.rollback().await?;
+				continue;
+			}
+			database.commit().await?;
+
+			storage
+				.delete_object(&super::get_object_name_for_blob(&blob.blob_digest))
+				.await?;
+		}
+
+		report.blobs.push(CollectedObject {
+			name: blob.blob_digest,
+			size: blob.size.try_into().unwrap_or_default(),
+		});
+	}
+
+	// Upload sessions that expired (instead of being completed or cancelled)
+	// leave their chunks behind in the storage
+	let uploads = storage.list_objects(super::UPLOADS_PREFIX).await?;
+	for object in uploads {
+		let Some(upload_id) = get_upload_id(&object.key) else {
+			continue;
+		};
+
+		if object.last_modified >= cutoff {
+			continue;
+		}
+
+		let session_exists = state
+			.redis
+			.exists(redis::keys::registry_upload_session(&upload_id))
+			.await? > 0;
+		if session_exists {
+			continue;
+		}
+
+		if !dry_run {
+			storage.delete_object(&object.key).await?;
+		}
+
+		report.upload_objects.push(CollectedObject {
+			name: object.key,
+			size: object.size,
+		});
+	}
+
+	Ok(report)
+}
+
+/// Gets the ID of the upload session that an object in the uploads directory of
+/// the storage belongs to
+fn get_upload_id(key: &str) -> Option<Uuid> {
+	key.strip_prefix(super::UPLOADS_PREFIX)
+		.and_then(|path| path.split('/').next())
+		.and_then(|upload_id| Uuid::parse_str(upload_id).ok())
+}
+
+#[cfg(test)]
+mod tests {
+	use axum::http::{Method, StatusCode};
+
+	use super::{
+		super::conformance_tests::{
+			digest_of,
+			image_manifest,
+			issue_token,
+			push_blob,
+			random_layer,
+			send,
+			CONFIG,
+			MANIFEST_MEDIA_TYPE,
+		},
+		collect_garbage,
+		get_upload_id,
+		CollectedObject,
+		GarbageCollectionReport,
+	};
+	use crate::{
+		models::registry_token::RegistryAction,
+		prelude::*,
+		utils::{storage::Storage, test_utils},
+	};
+
+	/// The grace period used by the tests. Only data that the tests age past
+	/// this is collected, so that the data of other tests is left alone.
+	const GRACE_PERIOD_MINUTES: u64 = 24 * 60;
+
+	/// Marks blobs and manifests as created before the grace period, as if they
+	/// were pushed long ago
+	async fn age(state: &AppState, digests: &[String]) {
+		query!(
+			r#"
+			UPDATE
+				container_registry_repository_blob
+			SET
+				created = NOW() - INTERVAL '2 days'
+			WHERE
+				blob_digest = ANY($1);
+			"#,
+			digests,
+		)
+		.execute(&state.database)
+		.await
+		.unwrap();
+
+		query!(
+			r#"
+			UPDATE
+				container_registry_manifest
+			SET
+				created = NOW() - INTERVAL '2 days'
+			WHERE
+				manifest_digest = ANY($1);
+			"#,
+			digests,
+		)
+		.execute(&state.database)
+		.await
+		.unwrap();
+	}
+
+	/// Checks if a blob is still in the storage
+	async fn blob_exists(storage: &dyn Storage, digest: &str) -> bool {
+		storage
+			.get_object_info(&super::super::get_object_name_for_blob(digest))
+			.await
+			.is_ok()
+	}
+
+	#[test]
+	fn assert_upload_objects_belong_to_their_session() {
+		let upload_id = Uuid::new_v4();
+		let prefix = super::super::UPLOADS_PREFIX;
+
+		assert_eq!(
+			get_upload_id(&format!("{}{}/blob", prefix, upload_id)),
+			Some(upload_id)
+		);
+		assert_eq!(
+			get_upload_id(&format!("{}{}/0", prefix, upload_id)),
+			Some(upload_id)
+		);
+		assert_eq!(
+			get_upload_id(&format!("{}not-an-upload/blob", prefix)),
+			None
+		);
+		assert_eq!(
+			get_upload_id(&super::super::get_object_name_for_blob(
+				"sha256:2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae"
+			)),
+			None
+		);
+	}
+
+	#[test]
+	fn assert_report_sums_everything_collected() {
+		let object = |size| CollectedObject {
+			name: Uuid::new_v4().to_string(),
+			size,
+		};
+		let report = GarbageCollectionReport {
+			manifests: vec![object(1)],
+			blobs: vec![object(20), object(300)],
+			upload_objects: vec![object(4000)],
+		};
+
+		assert_eq!(report.reclaimable_bytes(), 4321);
+		assert_eq!(GarbageCollectionReport::default().reclaimable_bytes(), 0);
+	}
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_garbage_collection() {
+		let mut state = test_utils::setup_state().await;
+		state.config.container_registry.gc_grace_period_minutes = GRACE_PERIOD_MINUTES;
+		let router = super::super::setup_routes(&state).await;
+		let storage = super::super::get_storage(&state.config.storage).unwrap();
+
+		let workspace = test_utils::create_workspace(&state).await;
+		test_utils::create_repository(&state, workspace.workspace_id, "app").await;
+		let repository = format!("{}/app", workspace.workspace_id);
+		let token = issue_token(
+			&state,
+			workspace.user_id,
+			&[(
+				&repository,
+				&[
+					RegistryAction::Pull,
+					RegistryAction::Push,
+					RegistryAction::Delete,
+				],
+			)],
+		);
+
+		// A blob that was never used by a manifest, a layer of an image, and a
+		// blob that was uploaded within the grace period
+		let unused = random_layer();
+		let layer = random_layer();
+		let recent = random_layer();
+		for blob in [&unused[..], &layer, CONFIG] {
+			assert_eq!(
+				push_blob(&router, &token, &repository, blob).await,
+				StatusCode::CREATED
+			);
+		}
+		let manifest = image_manifest(&layer);
+		let response = send(
+			&router,
+			Method::PUT,
+			&format!("/v2/{repository}/manifests/latest"),
+			&token,
+			Some(MANIFEST_MEDIA_TYPE),
+			manifest.clone(),
+		)
+		.await;
+		assert_eq!(response.status(), StatusCode::CREATED);
+		age(
+			&state,
+			&[digest_of(&unused), digest_of(&layer), digest_of(&manifest)],
+		)
+		.await;
+		assert_eq!(
+			push_blob(&router, &token, &repository, &recent).await,
+			StatusCode::CREATED
+		);
+
+		let unused_blob = CollectedObject {
+			name: digest_of(&unused),
+			size: unused.len() as u64,
+		};
+
+		// A dry run only reports the unused blob, without deleting anything
+		let report = collect_garbage(&state, true).await.unwrap();
+		assert!(report.blobs.contains(&unused_blob));
+		assert!(report.reclaimable_bytes() >= unused_blob.size);
+		for blob in [&layer, &recent] {
+			assert!(!report
+				.blobs
+				.iter()
+				.any(|collected| collected.name == digest_of(blob)));
+		}
+		assert!(blob_exists(&*storage, &digest_of(&unused)).await);
+
+		// Once the manifest is deleted, its layer is garbage as well
+		let response = send(
+			&router,
+			Method::DELETE,
+			&format!("/v2/{repository}/manifests/{}", digest_of(&manifest)),
+			&token,
+			None,
+			Vec::new(),
+		)
+		.await;
+		assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+		let report = collect_garbage(&state, false).await.unwrap();
+		assert!(report
+			.manifests
+			.iter()
+			.any(|collected| collected.name == digest_of(&manifest)));
+		assert!(report.blobs.contains(&unused_blob));
+		assert!(report.blobs.contains(&CollectedObject {
+			name: digest_of(&layer),
+			size: layer.len() as u64,
+		}));
+		assert!(!blob_exists(&*storage, &digest_of(&unused)).await);
+		assert!(!blob_exists(&*storage, &digest_of(&layer)).await);
+		assert!(blob_exists(&*storage, &digest_of(&recent)).await);
+	}
+}
@@ -1,23 +1,14 @@
 use axum::{
 	body::Body,
 	extract::{Path, State},
-	http::{
-		header,
-		header::InvalidHeaderValue,
-		HeaderMap,
-		HeaderName,
-		HeaderValue,
-		Method,
-		StatusCode,
-	},
+	http::{header, HeaderMap, HeaderValue, Method, StatusCode},
 	response::IntoResponse,
 };
 use preprocess::Preprocessable;
-use s3::Bucket;
 use serde::{Deserialize, Serialize};
 
 use super::{Error, ErrorItem, RegistryError};
-use crate::prelude::*;
+use crate::{prelude::*, utils::storage::StorageError};
 
 #[preprocess::sync]
 /// The parameters that are passed in the path of the request
@@ -34,6 +25,11 @@ pub struct PathParams {
 	digest: String,
 }
 
+/// Handles the `GET` and `HEAD` `/v2/{workspace_id}/{repo_name}/blobs/{digest}`
+/// routes.
+///
+/// The blob must have been uploaded (or mounted) to the repository, or be a
+/// part of a manifest in the repository.
 #[axum::debug_handler]
 pub(super) async fn handle(
 	method: Method,
@@ -51,93 +47,63 @@ pub(super) async fn handle(
 		});
 	};
 
-	let workspace_id = path.workspace_id;
 	let mut database = state.database.begin().await?;
+	let repository_id =
+		super::get_repository_id(&mut database, path.workspace_id, &path.repo_name).await?;
 
-	// Check if the workspace exists
-	let row = query!(
-		r#"
-		SELECT
-			*
-		FROM
-			workspace
-		WHERE
-			id = $1 AND
-			deleted IS NULL
-		"#,
-		workspace_id as _
-	)
-	.fetch_optional(&mut *database)
-	.await?;
+	// Only the blobs of the repository can be downloaded from it, even though
+	// blobs are stored once for the entire registry
+	let digests = [path.digest.to_string()];
+	if super::count_repository_blobs(&mut database, repository_id, &digests).await? == 0 {
+		return Err(Error::new(
+			RegistryError::BlobUnknown,
+			StatusCode::NOT_FOUND,
+			"Blob not found",
+		));
+	}
+	database.commit().await?;
 
-	let Some(_) = row else {
-		return Err(Error {
-			errors: [ErrorItem {
-				code: RegistryError::BlobUnknown,
-				message: "Invalid repository name".to_string(),
-				detail: "".to_string(),
-			}],
-			status_code: StatusCode::NOT_FOUND,
-		});
+	let storage = super::get_storage(&state.config.storage)?;
+	let key = super::get_object_name_for_blob(&path.digest);
+	let object = match storage.get_object_info(&key).await {
+		Ok(object) => object,
+		Err(StorageError::NotFound) => {
+			return Err(Error::new(
+				RegistryError::BlobUnknown,
+				StatusCode::NOT_FOUND,
+				"Blob not found",
+			));
+		}
+		Err(err) => return Err(err.into()),
 	};
 
-	let bucket = Bucket::new(
-		state.config.s3.bucket.as_str(),
-		s3::Region::Custom {
-			region: state.config.s3.region,
-			endpoint: state.config.s3.endpoint,
-		},
-		{
-			s3::creds::Credentials::new(
-				Some(&state.config.s3.key),
-				Some(&state.config.s3.secret),
-				None,
-				None,
-				None,
-			)?
-		},
-	)?;
-
-	let s3_key = super::get_s3_object_name_for_blob(&path.digest);
-	let (head, _) = bucket.head_object(&s3_key).await?;
-
-	let headers = [
+	let headers = HeaderMap::from_iter([
 		(
-			HeaderName::from_static("Docker-Distribution-API-Version"),
-			Some(String::from("registry/2.0")),
+			super::DOCKER_DISTRIBUTION_API_VERSION.clone(),
+			HeaderValue::from_static("registry/2.0"),
 		),
 		(
-			HeaderName::from_static("Docker-Content-Digest"),
-			Some(path.digest.to_string()),
+			super::DOCKER_CONTENT_DIGEST.clone(),
+			HeaderValue::from_str(&path.digest)?,
 		),
-		(header::ACCEPT_RANGES, head.accept_ranges),
-		(header::CACHE_CONTROL, head.cache_control),
-		(header::CONTENT_DISPOSITION, head.content_disposition),
-		(header::CONTENT_ENCODING, head.content_encoding),
-		(header::CONTENT_LANGUAGE, head.content_language),
+		(header::ACCEPT_RANGES, HeaderValue::from_static("none")),
+		(header::CONTENT_LENGTH, HeaderValue::from(object.size)),
 		(
-			header::CONTENT_LENGTH,
-			head.content_length.map(|length| length.to_string()),
+			header::CONTENT_TYPE,
+			HeaderValue::from_static("application/octet-stream"),
 		),
-		(header::CONTENT_TYPE, head.content_type),
-		(header::ETAG, head.e_tag),
-		(header::EXPIRES, head.expires),
-		(header::LAST_MODIFIED, head.last_modified),
-	]
-	.into_iter()
-	.filter_map(|(name, value)| value.map(|value| (name, value)))
-	.map(|(name, value)| Ok::<_, InvalidHeaderValue>((name, HeaderValue::from_str(&value)?)))
-	.collect::<Result<HeaderMap, _>>()?;
+		(
+			header::ETAG,
+			HeaderValue::from_str(&format!("\"{}\"", path.digest))?,
+		),
+	]);
 
 	if matches!(method, Method::HEAD) {
-		// HEAD request. head the blob from S3 and set the headers
+		// HEAD request. Only send the headers of the blob
 		Ok((StatusCode::OK, headers).into_response())
 	} else {
-		// GET request. return the blob from S3
-		let object = bucket.get_object_stream(&s3_key).await?;
-		if !(200..300).contains(&object.status_code) {
-			return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
-		}
-		Ok((StatusCode::OK, headers, Body::from_stream(object.bytes)).into_response())
+		// GET request. Stream the blob from the storage
+		let object = storage.get_object_stream(&key).await?;
+		Ok((StatusCode::OK, headers, Body::from_stream(object)).into_response())
 	}
 }
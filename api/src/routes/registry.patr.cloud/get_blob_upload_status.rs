@@ -0,0 +1,41 @@
+use axum::{
+	extract::{Path, State},
+	http::StatusCode,
+	response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{upload_session::UploadSession, Error};
+use crate::prelude::*;
+
+/// The parameters that are passed in the path of the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathParams {
+	/// The workspace ID of the repository
+	workspace_id: Uuid,
+	/// The name of the repository
+	repo_name: String,
+	/// The ID of the upload session
+	upload_id: Uuid,
+}
+
+/// Handles the `GET /v2/{workspace_id}/{repo_name}/blobs/uploads/{upload_id}`
+/// route. This is used by clients to resume an upload, by checking how much
+/// data has been uploaded so far (in the `Range` header).
+#[axum::debug_handler]
+pub(super) async fn handle(
+	Path(path): Path<PathParams>,
+	State(state): State<AppState>,
+) -> Result<impl IntoResponse, Error> {
+	let mut database = state.database.begin().await?;
+	let repository_id =
+		super::get_repository_id(&mut database, path.workspace_id, &path.repo_name).await?;
+
+	let session = UploadSession::load(&state.redis, path.upload_id, repository_id).await?;
+
+	Ok((
+		StatusCode::NO_CONTENT,
+		session.headers(path.workspace_id, &path.repo_name)?,
+	))
+}
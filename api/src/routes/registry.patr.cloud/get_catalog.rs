@@ -0,0 +1,121 @@
+use axum::{
+	extract::{Query, State},
+	http::{header, HeaderMap, HeaderValue},
+	response::IntoResponse,
+	Extension,
+	Json,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{Error, DOCKER_DISTRIBUTION_API_VERSION};
+use crate::{
+	models::registry_token::RegistryToken,
+	prelude::*,
+	utils::layers::{get_permissions_for_login_id, get_permissions_for_user_id},
+};
+
+/// The parameters that are passed in the query of the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryParams {
+	/// The maximum number of repositories to return
+	n: Option<usize>,
+	/// The last repository of the previous page. Only repositories after this
+	/// one are returned
+	last: Option<String>,
+}
+
+/// The response of the catalog endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogResponse {
+	/// The names of the repositories, in the format
+	/// `<workspace ID>/<repository name>`
+	repositories: Vec<String>,
+}
+
+/// Handles the `GET /v2/_catalog` route.
+///
+/// Only the repositories that the user (or the API token the registry token
+/// was issued for) has permission to view are listed, in lexical order. If
+/// there are more repositories than requested, a `Link` header pointing to the
+/// next page is sent.
+#[axum::debug_handler]
+pub(super) async fn handle(
+	Query(query): Query<QueryParams>,
+	State(state): State<AppState>,
+	Extension(token): Extension<RegistryToken>,
+) -> Result<impl IntoResponse, Error> {
+	let mut database = state.database.begin().await?;
+
+	let permissions = if let Some(login_id) = token.login_id {
+		let mut redis = state.redis.clone();
+		get_permissions_for_login_id(&mut database, &mut redis, &login_id, &token.sub).await?
+	} else {
+		get_permissions_for_user_id(&mut database, &token.sub).await?
+	};
+
+	let view_permission_id = query!(
+		r#"
+		SELECT
+			id
+		FROM
+			permission
+		WHERE
+			name = $1;
+		"#,
+		Permission::ContainerRegistryRepository(ContainerRegistryRepositoryPermission::View)
+			.to_string(),
+	)
+	.fetch_one(&mut *database)
+	.await?
+	.id
+	.into();
+
+	let mut repositories = Vec::new();
+	for (workspace_id, permission) in &permissions {
+		let names = query!(
+			r#"
+			SELECT
+				id,
+				name::TEXT AS "name!"
+			FROM
+				container_registry_repository
+			WHERE
+				workspace_id = $1 AND
+				deleted IS NULL;
+			"#,
+			workspace_id as _,
+		)
+		.fetch_all(&mut *database)
+		.await?
+		.into_iter()
+		.filter(|repository| {
+			permission.has_permission_on_resource(&view_permission_id, &repository.id.into())
+		})
+		.map(|repository| format!("{}/{}", workspace_id, repository.name))
+		.filter(|name| query.last.as_ref().map_or(true, |last| name > last));
+
+		repositories.extend(names);
+	}
+
+	database.commit().await?;
+
+	repositories.sort();
+
+	let mut headers = HeaderMap::from_iter([(
+		DOCKER_DISTRIBUTION_API_VERSION.clone(),
+		HeaderValue::from_static("registry/2.0"),
+	)]);
+	if let Some(n) = query.n {
+		if repositories.len() > n {
+			repositories.truncate(n);
+			if let Some(last) = repositories.last() {
+				headers.insert(
+					header::LINK,
+					super::get_next_page_link("/v2/_catalog", n, last)?,
+				);
+			}
+		}
+	}
+
+	Ok((headers, Json(CatalogResponse { repositories })))
+}
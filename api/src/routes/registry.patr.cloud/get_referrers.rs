@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+
+use axum::{
+	extract::{Path, Query, State},
+	http::{header, HeaderMap, HeaderValue, StatusCode},
+	response::IntoResponse,
+	Json,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{Error, RegistryError, DOCKER_DISTRIBUTION_API_VERSION, OCI_FILTERS_APPLIED};
+use crate::prelude::*;
+
+/// The media type of the image index returned by the referrers API
+const IMAGE_INDEX_MEDIA_TYPE: &str = "application/vnd.oci.image.index.v1+json";
+
+/// The parameters that are passed in the path of the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathParams {
+	/// The workspace ID of the repository
+	workspace_id: Uuid,
+	/// The name of the repository
+	repo_name: String,
+	/// The digest of the manifest to get the referrers of
+	digest: String,
+}
+
+/// The parameters that are passed in the query of the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryParams {
+	/// Only list the referrers with this artifact type, if given
+	artifact_type: Option<String>,
+}
+
+/// The image index listing the referrers of a manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferrersResponse {
+	/// The schema version of the image index. This is always `2`
+	schema_version: u8,
+	/// The media type of the image index
+	media_type: String,
+	/// The manifests that refer to the requested manifest
+	manifests: Vec<ReferrerDescriptor>,
+}
+
+/// A manifest that refers to another manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferrerDescriptor {
+	/// The media type of the manifest
+	media_type: String,
+	/// The digest of the manifest
+	digest: String,
+	/// The size of the manifest
+	size: i64,
+	/// The artifact type of the manifest, if any
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	artifact_type: Option<String>,
+	/// The annotations of the manifest, if any
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	annotations: Option<BTreeMap<String, String>>,
+}
+
+/// Handles the `GET /v2/{workspace_id}/{repo_name}/referrers/{digest}` route.
+///
+/// Lists the manifests in the repository that have the given manifest as
+/// their subject (like SBOMs and signatures of an image), as an image index.
+/// The manifest itself doesn't need to exist in the repository. If an
+/// `artifactType` is given, only the referrers of that type are listed.
+#[axum::debug_handler]
+pub(super) async fn handle(
+	Path(path): Path<PathParams>,
+	Query(query): Query<QueryParams>,
+	State(state): State<AppState>,
+) -> Result<impl IntoResponse, Error> {
+	if !super::is_valid_digest(&path.digest) {
+		return Err(Error::new(
+			RegistryError::DigestInvalid,
+			StatusCode::BAD_REQUEST,
+			"Invalid digest",
+		));
+	}
+
+	let mut database = state.database.begin().await?;
+	let repository_id =
+		super::get_repository_id(&mut database, path.workspace_id, &path.repo_name).await?;
+
+	let manifests = query!(
+		r#"
+		SELECT
+			container_registry_manifest.manifest_digest,
+			container_registry_manifest.media_type,
+			container_registry_manifest.size,
+			container_registry_manifest.artifact_type,
+			container_registry_manifest.annotations
+		FROM
+			container_registry_repository_manifest
+		INNER JOIN
+			container_registry_manifest
+		ON
+			container_registry_repository_manifest.manifest_digest =
+				container_registry_manifest.manifest_digest
+		WHERE
+			container_registry_repository_manifest.repository_id = $1 AND
+			container_registry_manifest.subject_digest = $2 AND
+			(
+				$3::TEXT IS NULL OR
+				container_registry_manifest.artifact_type = $3
+			)
+		ORDER BY
+			container_registry_repository_manifest.created;
+		"#,
+		repository_id as _,
+		&path.digest,
+		query.artifact_type.as_deref(),
+	)
+	.fetch_all(&mut *database)
+	.await?
+	.into_iter()
+	.map(|row| {
+		Ok::<_, Error>(ReferrerDescriptor {
+			media_type: row.media_type,
+			digest: row.manifest_digest,
+			size: row.size,
+			artifact_type: row.artifact_type,
+			annotations: row
+				.annotations
+				.as_deref()
+				.map(serde_json::from_str)
+				.transpose()?,
+		})
+	})
+	.collect::<Result<Vec<_>, _>>()?;
+
+	database.commit().await?;
+
+	let mut headers = HeaderMap::from_iter([
+		(
+			DOCKER_DISTRIBUTION_API_VERSION.clone(),
+			HeaderValue::from_static("registry/2.0"),
+		),
+		(
+			header::CONTENT_TYPE,
+			HeaderValue::from_static(IMAGE_INDEX_MEDIA_TYPE),
+		),
+	]);
+	if query.artifact_type.is_some() {
+		headers.insert(
+			OCI_FILTERS_APPLIED.clone(),
+			HeaderValue::from_static("artifactType"),
+		);
+	}
+
+	Ok((
+		headers,
+		Json(ReferrersResponse {
+			schema_version: 2,
+			media_type: IMAGE_INDEX_MEDIA_TYPE.to_string(),
+			manifests,
+		}),
+	))
+}
@@ -1,5 +1,5 @@
 use axum::{
-	http::{HeaderMap, HeaderName, HeaderValue, StatusCode},
+	http::{HeaderMap, HeaderValue, StatusCode},
 	response::IntoResponse,
 };
 
@@ -8,7 +8,7 @@ use axum::{
 pub(super) async fn handle() -> impl IntoResponse {
 	(
 		[(
-			HeaderName::from_static("Docker-Distribution-API-Version"),
+			super::DOCKER_DISTRIBUTION_API_VERSION.clone(),
 			HeaderValue::from_static("registry/2.0"),
 		)]
 		.into_iter()
@@ -0,0 +1,103 @@
+use axum::{
+	extract::{Path, Query, State},
+	http::{header, HeaderMap, HeaderValue},
+	response::IntoResponse,
+	Json,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{Error, DOCKER_DISTRIBUTION_API_VERSION};
+use crate::prelude::*;
+
+/// The parameters that are passed in the path of the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathParams {
+	/// The workspace ID of the repository
+	workspace_id: Uuid,
+	/// The name of the repository
+	repo_name: String,
+}
+
+/// The parameters that are passed in the query of the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryParams {
+	/// The maximum number of tags to return
+	n: Option<usize>,
+	/// The last tag of the previous page. Only tags after this one are
+	/// returned
+	last: Option<String>,
+}
+
+/// The response of the tag list endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagListResponse {
+	/// The name of the repository, in the format
+	/// `<workspace ID>/<repository name>`
+	name: String,
+	/// The tags of the repository
+	tags: Vec<String>,
+}
+
+/// Handles the `GET /v2/{workspace_id}/{repo_name}/tags/list` route.
+///
+/// The tags are listed in lexical order. If there are more tags than
+/// requested, a `Link` header pointing to the next page is sent.
+#[axum::debug_handler]
+pub(super) async fn handle(
+	Path(path): Path<PathParams>,
+	Query(query): Query<QueryParams>,
+	State(state): State<AppState>,
+) -> Result<impl IntoResponse, Error> {
+	let mut database = state.database.begin().await?;
+	let repository_id =
+		super::get_repository_id(&mut database, path.workspace_id, &path.repo_name).await?;
+
+	// One more tag than requested is fetched, to know if there is a next page
+	let mut tags = query!(
+		r#"
+		SELECT
+			tag
+		FROM
+			container_registry_repository_tag
+		WHERE
+			repository_id = $1 AND
+			($2::TEXT IS NULL OR tag COLLATE "C" > $2)
+		ORDER BY
+			tag COLLATE "C"
+		LIMIT $3;
+		"#,
+		repository_id as _,
+		query.last.as_deref(),
+		query
+			.n
+			.map(|n| i64::try_from(n.saturating_add(1)).unwrap_or(i64::MAX)),
+	)
+	.fetch_all(&mut *database)
+	.await?
+	.into_iter()
+	.map(|row| row.tag)
+	.collect::<Vec<_>>();
+
+	database.commit().await?;
+
+	let name = format!("{}/{}", path.workspace_id, path.repo_name);
+
+	let mut headers = HeaderMap::from_iter([(
+		DOCKER_DISTRIBUTION_API_VERSION.clone(),
+		HeaderValue::from_static("registry/2.0"),
+	)]);
+	if let Some(n) = query.n {
+		if tags.len() > n {
+			tags.truncate(n);
+			if let Some(last) = tags.last() {
+				headers.insert(
+					header::LINK,
+					super::get_next_page_link(&format!("/v2/{name}/tags/list"), n, last)?,
+				);
+			}
+		}
+	}
+
+	Ok((headers, Json(TagListResponse { name, tags })))
+}
@@ -0,0 +1,544 @@
+use std::{collections::BTreeMap, net::IpAddr, sync::OnceLock, time::Duration};
+
+use argon2::{
+	password_hash::SaltString,
+	Algorithm,
+	Argon2,
+	PasswordHash,
+	PasswordHasher,
+	PasswordVerifier,
+	Version,
+};
+use axum::{
+	extract::{RawQuery, Request, State},
+	http::{header, StatusCode},
+	response::IntoResponse,
+	Json,
+	RequestExt,
+};
+use headers::{authorization::Basic, Authorization, HeaderMapExt};
+use jsonwebtoken::EncodingKey;
+use models::rbac::WorkspacePermission;
+use rustis::{
+	client::Client as RedisClient,
+	commands::{ExpireOption, GenericCommands, StringCommands},
+};
+use serde::{Deserialize, Serialize};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use super::{Error, RegistryError};
+use crate::{
+	models::registry_token::{RegistryAction, RegistryToken, RegistryTokenAccess},
+	prelude::*,
+	utils::{
+		config::AppConfig,
+		extractors::ClientIP,
+		layers::{get_permissions_for_user_id, get_user_data_for_api_token},
+	},
+};
+
+/// The number of times the password of a username can be entered wrongly from
+/// an IP address before password logins for it are denied from that address
+const MAX_PASSWORD_ATTEMPTS: i64 = 10;
+
+/// How long failed password attempts are counted for, since the last failed
+/// attempt
+const PASSWORD_ATTEMPTS_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// The response of the token endpoint, as defined by the [Docker token
+/// authentication specification][1].
+///
+/// [1]: https://distribution.github.io/distribution/spec/auth/token/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenResponse {
+	/// The token that the client should use to access the registry
+	token: String,
+	/// The same as `token`, for compatibility with OAuth 2.0 clients
+	access_token: String,
+	/// The number of seconds that the token is valid for
+	expires_in: i64,
+	/// The RFC3339 timestamp at which the token was issued
+	issued_at: String,
+}
+
+/// Handles the `GET /token` route.
+///
+/// The client authenticates using HTTP basic auth, with either the username
+/// and password of the user, or the username and an API token of the user as
+/// the password. The scopes requested by the client (in the format
+/// `repository:<workspace ID>/<repository name>:<actions>`) are checked
+/// against the permissions of the user, and the token only grants the actions
+/// that the user is allowed to perform. Actions that are not allowed are left
+/// out of the token instead of failing the request, as per the specification.
+/// The `registry:catalog:*` scope is always granted, since the catalog only
+/// lists the repositories that the user is allowed to view.
+#[axum::debug_handler]
+pub(super) async fn handle(
+	State(state): State<AppState>,
+	RawQuery(query): RawQuery,
+	mut request: Request,
+) -> Result<impl IntoResponse, Error> {
+	let Ok(ClientIP(client_ip)) = request.extract_parts().await;
+
+	let query = query.unwrap_or_default();
+	let params = serde_urlencoded::from_str::<Vec<(String, String)>>(&query).map_err(|err| {
+		Error::new(
+			RegistryError::Unsupported,
+			StatusCode::BAD_REQUEST,
+			err.to_string(),
+		)
+	})?;
+
+	if params
+		.iter()
+		.any(|(key, value)| key == "service" && value != constants::CONTAINER_REGISTRY_SERVICE)
+	{
+		return Err(Error::new(
+			RegistryError::Unsupported,
+			StatusCode::BAD_REQUEST,
+			"Unknown service",
+		));
+	}
+
+	let Some(Authorization(credentials)) = request.headers().typed_get::<Authorization<Basic>>()
+	else {
+		return Ok((
+			[(
+				header::WWW_AUTHENTICATE,
+				format!(r#"Basic realm="{}""#, constants::CONTAINER_REGISTRY_SERVICE),
+			)],
+			Error::new(
+				RegistryError::Unauthorized,
+				StatusCode::UNAUTHORIZED,
+				"Authentication required",
+			),
+		)
+			.into_response());
+	};
+
+	let mut database = state.database.begin().await?;
+	let mut redis = state.redis.clone();
+
+	let (user_id, login_id, permissions) = if credentials.password().starts_with("patrv1.") {
+		let user_data = get_user_data_for_api_token(
+			&mut database,
+			&mut redis,
+			&state.config,
+			client_ip,
+			credentials.password(),
+		)
+		.await?;
+
+		if user_data.username != credentials.username() {
+			return Err(invalid_credentials());
+		}
+
+		(
+			user_data.id,
+			Some(user_data.login_id),
+			user_data.permissions,
+		)
+	} else {
+		let user_id = verify_user_password(
+			&mut database,
+			&mut redis,
+			&state.config,
+			credentials.username(),
+			credentials.password(),
+			client_ip,
+		)
+		.await?;
+		let permissions = get_permissions_for_user_id(&mut database, &user_id).await?;
+
+		(user_id, None, permissions)
+	};
+
+	let mut access = Vec::new();
+	for (r#type, name, actions) in params
+		.iter()
+		.filter(|(key, _)| key == "scope")
+		.flat_map(|(_, value)| value.split(' '))
+		.filter_map(parse_scope)
+	{
+		let actions = match (r#type, name) {
+			("registry", "catalog") => actions,
+			("repository", _) => {
+				get_allowed_actions(&mut database, &permissions, name, actions).await?
+			}
+			_ => continue,
+		};
+		if !actions.is_empty() {
+			access.push(RegistryTokenAccess {
+				r#type: r#type.to_string(),
+				name: name.to_string(),
+				actions,
+			});
+		}
+	}
+
+	database.commit().await?;
+
+	let iat = OffsetDateTime::now_utc();
+	let token = jsonwebtoken::encode(
+		&Default::default(),
+		&RegistryToken {
+			iss: constants::JWT_ISSUER.to_string(),
+			sub: user_id,
+			login_id,
+			aud: constants::CONTAINER_REGISTRY_SERVICE.to_string(),
+			exp: iat + RegistryToken::VALIDITY,
+			nbf: iat,
+			iat,
+			jti: Uuid::new_v4(),
+			access,
+		},
+		&EncodingKey::from_secret(state.config.jwt_secret.as_ref()),
+	)?;
+
+	Ok(Json(TokenResponse {
+		token: token.clone(),
+		access_token: token,
+		expires_in: RegistryToken::VALIDITY.whole_seconds(),
+		issued_at: iat.format(&Rfc3339).unwrap_or_default(),
+	})
+	.into_response())
+}
+
+/// Verifies the password of the user with the given username, returning the ID
+/// of the user. Users with two-factor authentication enabled cannot log in to
+/// the registry with their password, since the registry has no way to ask for
+/// the OTP, and need to use an API token instead.
+///
+/// Once the password of a username has been entered wrongly from an IP address
+/// [`MAX_PASSWORD_ATTEMPTS`] times, password logins for it from that address
+/// are denied until [`PASSWORD_ATTEMPTS_WINDOW`] has passed since the last
+/// failed attempt. The attempts are counted per address, so that anyone who
+/// knows a username can't lock its user out of the registry.
+async fn verify_user_password(
+	connection: &mut DatabaseConnection,
+	redis: &mut RedisClient,
+	config: &AppConfig,
+	username: &str,
+	password: &str,
+	client_ip: IpAddr,
+) -> Result<Uuid, Error> {
+	let attempts_key = redis::keys::registry_password_attempts(username, &client_ip);
+	let attempts = redis
+		.get::<_, Option<i64>>(&attempts_key)
+		.await?
+		.unwrap_or(0);
+	if attempts >= MAX_PASSWORD_ATTEMPTS {
+		return Err(Error::new(
+			RegistryError::TooManyRequests,
+			StatusCode::TOO_MANY_REQUESTS,
+			"Too many failed login attempts. Try again later",
+		));
+	}
+
+	let user = query!(
+		r#"
+		SELECT
+			id,
+			password,
+			mfa_secret
+		FROM
+			"user"
+		WHERE
+			username = $1;
+		"#,
+		username,
+	)
+	.fetch_optional(&mut *connection)
+	.await?;
+
+	let argon2 = Argon2::new_with_secret(
+		config.password_pepper.as_bytes(),
+		Algorithm::Argon2id,
+		Version::V0x13,
+		constants::HASHING_PARAMS,
+	)
+	.map_err(ErrorType::server_error)?;
+
+	// Unknown usernames are verified against a dummy hash, so that they take
+	// as long as a wrong password and usernames can't be found by timing the
+	// requests
+	let password_hash = match &user {
+		Some(user) => user.password.as_str(),
+		None => dummy_password_hash()?,
+	};
+	let success = argon2
+		.verify_password(
+			password.as_bytes(),
+			&PasswordHash::new(password_hash).map_err(ErrorType::server_error)?,
+		)
+		.is_ok();
+
+	let Some(user) = user.filter(|_| success) else {
+		redis.incr(&attempts_key).await?;
+		redis
+			.expire(
+				&attempts_key,
+				PASSWORD_ATTEMPTS_WINDOW.as_secs(),
+				ExpireOption::None,
+			)
+			.await?;
+		return Err(invalid_credentials());
+	};
+	redis.del(&attempts_key).await?;
+
+	if user.mfa_secret.is_some() {
+		return Err(Error::new(
+			RegistryError::Unauthorized,
+			StatusCode::UNAUTHORIZED,
+			"Two-factor authentication is enabled. Use an API token as the password instead",
+		));
+	}
+
+	Ok(user.id.into())
+}
+
+/// A hash of an empty password, with the same parameters as the hashes of the
+/// passwords of users. Verifying a password against this takes as long as
+/// verifying the password of a user, but never succeeds, since the hashes of
+/// users are peppered and this isn't.
+fn dummy_password_hash() -> Result<&'static str, Error> {
+	static HASH: OnceLock<String> = OnceLock::new();
+
+	if let Some(hash) = HASH.get() {
+		return Ok(hash);
+	}
+
+	let hash = Argon2::new(
+		Algorithm::Argon2id,
+		Version::V0x13,
+		constants::HASHING_PARAMS,
+	)
+	.hash_password(b"", SaltString::generate(&mut rand::thread_rng()).as_salt())
+	.map_err(ErrorType::server_error)?
+	.to_string();
+
+	Ok(HASH.get_or_init(|| hash))
+}
+
+/// Get the actions (out of the requested actions) that the user is allowed to
+/// perform on the repository with the given name. Repositories that don't
+/// exist don't allow any actions.
+///
+/// Images in the pull-through cache of a workspace (named
+/// `_cache/<upstream>/<image>`) can only be pulled. They don't belong to any
+/// repository, so the permission to pull them is checked on the workspace
+/// itself.
+async fn get_allowed_actions(
+	connection: &mut DatabaseConnection,
+	permissions: &BTreeMap<Uuid, WorkspacePermission>,
+	name: &str,
+	actions: Vec<RegistryAction>,
+) -> Result<Vec<RegistryAction>, Error> {
+	let Some((workspace_id, repo_name)) = name.split_once('/') else {
+		return Ok(Vec::new());
+	};
+	let Ok(workspace_id) = Uuid::parse_str(workspace_id) else {
+		return Ok(Vec::new());
+	};
+	let Some(workspace_permission) = permissions.get(&workspace_id) else {
+		return Ok(Vec::new());
+	};
+
+	let (resource_id, actions) = if repo_name.starts_with("_cache/") {
+		let actions = actions
+			.into_iter()
+			.filter(|action| *action == RegistryAction::Pull)
+			.collect::<Vec<_>>();
+		(workspace_id, actions)
+	} else {
+		let Some(repository) = query!(
+			r#"
+			SELECT
+				id
+			FROM
+				container_registry_repository
+			WHERE
+				workspace_id = $1 AND
+				name = $2 AND
+				deleted IS NULL;
+			"#,
+			workspace_id as _,
+			repo_name as _,
+		)
+		.fetch_optional(&mut *connection)
+		.await?
+		else {
+			return Ok(Vec::new());
+		};
+		(repository.id.into(), actions)
+	};
+
+	let mut allowed = Vec::with_capacity(actions.len());
+	for action in actions {
+		let permission_id = query!(
+			r#"
+			SELECT
+				id
+			FROM
+				permission
+			WHERE
+				name = $1;
+			"#,
+			action.permission().to_string(),
+		)
+		.fetch_optional(&mut *connection)
+		.await?
+		.map(|row| row.id.into());
+
+		let is_allowed = permission_id.is_some_and(|permission_id| {
+			workspace_permission.has_permission_on_resource(&permission_id, &resource_id)
+		});
+		if is_allowed {
+			allowed.push(action);
+		}
+	}
+
+	Ok(allowed)
+}
+
+/// Parses a scope of a token request, in the format
+/// `<type>:<name>:<actions>`, returning the type and name of the resource and
+/// the requested actions. Scopes with unknown actions are ignored.
+fn parse_scope(scope: &str) -> Option<(&str, &str, Vec<RegistryAction>)> {
+	let (resource, actions) = scope.rsplit_once(':')?;
+	let (r#type, name) = resource.split_once(':')?;
+
+	let mut parsed = actions
+		.split(',')
+		.map(RegistryAction::parse_scope_action)
+		.collect::<Option<Vec<_>>>()?
+		.concat();
+	parsed.sort();
+	parsed.dedup();
+
+	Some((r#type, name, parsed))
+}
+
+/// The error returned when the username or password is incorrect. The same
+/// error is returned in either case, so as to not leak which usernames exist.
+fn invalid_credentials() -> Error {
+	Error::new(
+		RegistryError::Unauthorized,
+		StatusCode::UNAUTHORIZED,
+		"Invalid username or password",
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::net::SocketAddr;
+
+	use argon2::{Algorithm, Argon2, PasswordHash, PasswordVerifier, Version};
+	use axum::{
+		body::Body,
+		extract::ConnectInfo,
+		http::{Request, StatusCode},
+	};
+	use headers::{Authorization, HeaderMapExt};
+	use tower::ServiceExt;
+
+	use super::{dummy_password_hash, parse_scope, MAX_PASSWORD_ATTEMPTS};
+	use crate::{models::registry_token::RegistryAction, prelude::*, utils::test_utils};
+
+	#[test]
+	fn assert_scope_parse() {
+		assert_eq!(
+			parse_scope("repository:ws/repo:pull,push"),
+			Some((
+				"repository",
+				"ws/repo",
+				vec![RegistryAction::Pull, RegistryAction::Push]
+			))
+		);
+		assert_eq!(
+			parse_scope("repository:ws/repo:*"),
+			Some((
+				"repository",
+				"ws/repo",
+				vec![
+					RegistryAction::Pull,
+					RegistryAction::Push,
+					RegistryAction::Delete
+				]
+			))
+		);
+		assert_eq!(
+			parse_scope("registry:catalog:*"),
+			Some((
+				"registry",
+				"catalog",
+				vec![
+					RegistryAction::Pull,
+					RegistryAction::Push,
+					RegistryAction::Delete
+				]
+			))
+		);
+		assert_eq!(parse_scope("repository:ws/repo:pull,unknown"), None);
+		assert_eq!(parse_scope("repository"), None);
+	}
+
+	#[test]
+	fn assert_dummy_password_hash_never_matches() {
+		let hash = dummy_password_hash().unwrap();
+		assert_eq!(dummy_password_hash().unwrap(), hash);
+
+		let argon2 = Argon2::new_with_secret(
+			b"pepper",
+			Algorithm::Argon2id,
+			Version::V0x13,
+			constants::HASHING_PARAMS,
+		)
+		.unwrap();
+		assert!(argon2
+			.verify_password(b"", &PasswordHash::new(hash).unwrap())
+			.is_err());
+	}
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_password_attempts_are_limited() {
+		let state = test_utils::setup_state().await;
+		let router = super::super::setup_routes(&state).await;
+		let username = format!("unknown_{}", Uuid::new_v4().to_string().replace('-', ""));
+
+		let request_token = |client_ip: [u8; 4]| {
+			let mut request = Request::builder()
+				.uri(format!(
+					"/token?service={}",
+					constants::CONTAINER_REGISTRY_SERVICE
+				))
+				.body(Body::empty())
+				.unwrap();
+			request
+				.headers_mut()
+				.typed_insert(Authorization::basic(&username, "password"));
+			request
+				.extensions_mut()
+				.insert(ConnectInfo(SocketAddr::from((client_ip, 0))));
+			router.clone().oneshot(request)
+		};
+
+		for _ in 0..MAX_PASSWORD_ATTEMPTS {
+			assert_eq!(
+				request_token([127, 0, 0, 1]).await.unwrap().status(),
+				StatusCode::UNAUTHORIZED
+			);
+		}
+		assert_eq!(
+			request_token([127, 0, 0, 1]).await.unwrap().status(),
+			StatusCode::TOO_MANY_REQUESTS
+		);
+
+		// Logins from other addresses aren't affected
+		assert_eq!(
+			request_token([127, 0, 0, 2]).await.unwrap().status(),
+			StatusCode::UNAUTHORIZED
+		);
+	}
+}
@@ -0,0 +1,337 @@
+use base64::{
+	engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+	Engine,
+};
+use jsonwebtoken::{Algorithm, DecodingKey};
+use models::api::workspace::container_registry::ContainerRepositoryImageSignatureStatus;
+use serde::Deserialize;
+
+use super::Error;
+use crate::{prelude::*, utils::storage::Storage};
+
+/// The annotation on the layers of a cosign signature manifest that contains
+/// the signature of the layer, encoded as base64
+pub(super) const COSIGN_SIGNATURE_ANNOTATION: &str = "dev.cosignproject.cosign/signature";
+/// The media type of the layers of a cosign signature manifest. Each layer is a
+/// payload (in the "simple signing" format) that says which image it signs
+pub(super) const COSIGN_PAYLOAD_MEDIA_TYPE: &str =
+	"application/vnd.dev.cosign.simplesigning.v1+json";
+/// The artifact type of cosign signatures that are attached to an image as a
+/// referrer, instead of being pushed to a `sha256-<hex>.sig` tag
+pub(super) const COSIGN_SIGNATURE_ARTIFACT_TYPE: &str =
+	"application/vnd.dev.cosign.artifact.sig.v1+json";
+/// The maximum size of a signature payload. A payload only contains the digest
+/// of the image and a few annotations, so anything larger than this isn't a
+/// valid payload.
+const MAX_PAYLOAD_SIZE: usize = 64 * 1024;
+
+/// The parts of a cosign "simple signing" payload that are needed to verify a
+/// signature
+#[derive(Debug, Deserialize)]
+struct SignaturePayload {
+	/// The fields of the payload that must be checked by the verifier
+	critical: CriticalPayload,
+}
+
+/// The critical section of a signature payload
+#[derive(Debug, Deserialize)]
+struct CriticalPayload {
+	/// The image that was signed
+	image: SignedImage,
+}
+
+/// The image that a signature payload signs
+#[derive(Debug, Deserialize)]
+struct SignedImage {
+	/// The digest of the manifest of the image
+	#[serde(rename = "docker-manifest-digest")]
+	docker_manifest_digest: String,
+}
+
+/// Gets the digest of the image that is signed by the signatures pushed to the
+/// given tag. Cosign pushes the signatures of an image to a tag named after the
+/// digest of the image (`sha256-<hex>.sig` for the digest `sha256:<hex>`).
+pub(super) fn get_signed_digest_from_tag(tag: &str) -> Option<String> {
+	let digest = format!(
+		"sha256:{}",
+		tag.strip_prefix("sha256-")?.strip_suffix(".sig")?
+	);
+	super::is_valid_digest(&digest).then_some(digest)
+}
+
+/// Checks if the given PEM encoded public key can be used to verify cosign
+/// signatures. Only ECDSA P-256 keys (the default of cosign) are supported.
+pub fn is_valid_public_key(public_key: &str) -> bool {
+	DecodingKey::from_ec_pem(public_key.as_bytes()).is_ok()
+}
+
+/// Stores a signature pushed to a repository, along with its payload. The
+/// payload is read from the blob that was uploaded for it, so that it can be
+/// verified later without reading it from the storage again. Payloads that
+/// don't sign the given digest are ignored.
+pub(super) async fn store_signature(
+	connection: &mut DatabaseConnection,
+	storage: &dyn Storage,
+	repository_id: Uuid,
+	signed_digest: &str,
+	signature_manifest_digest: &str,
+	payload_digest: &str,
+	signature: &str,
+) -> Result<bool, Error> {
+	let payload = storage
+		.get_object(&super::get_object_name_for_blob(payload_digest))
+		.await?
+		.to_vec();
+	if payload.len() > MAX_PAYLOAD_SIZE {
+		return Ok(false);
+	}
+	let Ok(payload) = String::from_utf8(payload) else {
+		return Ok(false);
+	};
+
+	let signs_digest = serde_json::from_str::<SignaturePayload>(&payload)
+		.is_ok_and(|parsed| parsed.critical.image.docker_manifest_digest == signed_digest);
+	if !signs_digest {
+		return Ok(false);
+	}
+
+	query!(
+		r#"
+		INSERT INTO
+			container_registry_image_signature(
+				repository_id,
+				manifest_digest,
+				signature_manifest_digest,
+				payload_digest,
+				payload,
+				signature,
+				created
+			)
+		VALUES
+			($1, $2, $3, $4, $5, $6, NOW())
+		ON CONFLICT(repository_id, signature_manifest_digest, payload_digest) DO NOTHING;
+		"#,
+		repository_id as _,
+		signed_digest,
+		signature_manifest_digest,
+		payload_digest,
+		payload,
+		signature,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(true)
+}
+
+/// Checks if the workspace requires images to be signed before they can be
+/// deployed. A workspace requires signed images as soon as it trusts at least
+/// one public key.
+pub async fn is_signature_required(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+	query!(
+		r#"
+		SELECT
+			EXISTS (
+				SELECT
+					1
+				FROM
+					container_registry_signing_key
+				WHERE
+					workspace_id = $1
+			) AS "required!";
+		"#,
+		workspace_id as _,
+	)
+	.fetch_one(&mut *connection)
+	.await
+	.map(|row| row.required)
+}
+
+/// Verifies the signatures of an image in a repository against the public keys
+/// trusted by the workspace of the repository. Only signatures whose signature
+/// manifest is still a part of the repository are considered.
+pub async fn verify_image_signature(
+	connection: &mut DatabaseConnection,
+	repository_id: Uuid,
+	digest: &str,
+) -> Result<ContainerRepositoryImageSignatureStatus, sqlx::Error> {
+	let signatures = query!(
+		r#"
+		SELECT
+			payload,
+			signature
+		FROM
+			container_registry_image_signature
+		WHERE
+			repository_id = $1 AND
+			manifest_digest = $2 AND
+			EXISTS (
+				SELECT
+					1
+				FROM
+					container_registry_repository_manifest
+				WHERE
+					container_registry_repository_manifest.repository_id = $1 AND
+					container_registry_repository_manifest.manifest_digest =
+						container_registry_image_signature.signature_manifest_digest
+			);
+		"#,
+		repository_id as _,
+		digest,
+	)
+	.fetch_all(&mut *connection)
+	.await?;
+
+	if signatures.is_empty() {
+		return Ok(ContainerRepositoryImageSignatureStatus::Unsigned);
+	}
+
+	let keys = query!(
+		r#"
+		SELECT
+			container_registry_signing_key.id,
+			container_registry_signing_key.public_key
+		FROM
+			container_registry_signing_key
+		INNER JOIN
+			container_registry_repository
+		ON
+			container_registry_signing_key.workspace_id =
+				container_registry_repository.workspace_id
+		WHERE
+			container_registry_repository.id = $1;
+		"#,
+		repository_id as _,
+	)
+	.fetch_all(&mut *connection)
+	.await?;
+
+	let verified_key = keys.into_iter().find(|key| {
+		signatures.iter().any(|signature| {
+			verify_signature(&key.public_key, &signature.payload, &signature.signature)
+		})
+	});
+
+	Ok(match verified_key {
+		Some(key) => ContainerRepositoryImageSignatureStatus::Verified {
+			key_id: key.id.into(),
+		},
+		None => ContainerRepositoryImageSignatureStatus::Unverified,
+	})
+}
+
+/// Verifies a cosign signature (an ASN.1 DER encoded ECDSA signature, as
+/// base64) of a payload against a PEM encoded public key.
+fn verify_signature(public_key: &str, payload: &str, signature: &str) -> bool {
+	let Ok(key) = DecodingKey::from_ec_pem(public_key.as_bytes()) else {
+		return false;
+	};
+	let Some(signature) = STANDARD
+		.decode(signature)
+		.ok()
+		.and_then(|signature| der_to_fixed_signature(&signature))
+	else {
+		return false;
+	};
+
+	jsonwebtoken::crypto::verify(
+		&URL_SAFE_NO_PAD.encode(signature),
+		payload.as_bytes(),
+		&key,
+		Algorithm::ES256,
+	)
+	.unwrap_or(false)
+}
+
+/// Converts an ASN.1 DER encoded ECDSA P-256 signature (a sequence of the `r`
+/// and `s` integers) into the fixed size format (`r` and `s` as 32 bytes each)
+/// used by JWTs.
+fn der_to_fixed_signature(der: &[u8]) -> Option<[u8; 64]> {
+	/// Reads a DER integer from the start of the input, returning the integer
+	/// and the rest of the input
+	fn read_integer(input: &[u8]) -> Option<(&[u8], &[u8])> {
+		let [0x02, length, rest @ ..] = input else {
+			return None;
+		};
+		let length = usize::from(*length);
+		if rest.len() < length {
+			return None;
+		}
+		Some(rest.split_at(length))
+	}
+
+	let [0x30, length, rest @ ..] = der else {
+		return None;
+	};
+	if usize::from(*length) != rest.len() {
+		return None;
+	}
+
+	let (r, rest) = read_integer(rest)?;
+	let (s, rest) = read_integer(rest)?;
+	if !rest.is_empty() {
+		return None;
+	}
+
+	let mut fixed = [0; 64];
+	for (integer, output) in [r, s].into_iter().zip(fixed.chunks_exact_mut(32)) {
+		// Integers are signed in DER, so a leading zero is added to integers
+		// that have their highest bit set
+		let integer = match integer {
+			[0, rest @ ..] => rest,
+			integer => integer,
+		};
+		if integer.len() > 32 {
+			return None;
+		}
+		output[32 - integer.len()..].copy_from_slice(integer);
+	}
+
+	Some(fixed)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{der_to_fixed_signature, get_signed_digest_from_tag};
+
+	#[test]
+	fn assert_signed_digest_from_tag() {
+		let hex = "bccd10f490ab0f3fba61b193d1b80af91b17ca9bdca9768a16ed05ce16552fcb";
+
+		assert_eq!(
+			get_signed_digest_from_tag(&format!("sha256-{hex}.sig")),
+			Some(format!("sha256:{hex}"))
+		);
+		assert_eq!(
+			get_signed_digest_from_tag(&format!("sha256-{hex}.att")),
+			None
+		);
+		assert_eq!(get_signed_digest_from_tag("sha256-abc.sig"), None);
+		assert_eq!(get_signed_digest_from_tag("latest"), None);
+	}
+
+	#[test]
+	fn assert_der_signature_conversion() {
+		let mut der = vec![0x30, 0x45, 0x02, 0x21, 0x00];
+		der.extend([0x80; 32]);
+		der.extend([0x02, 0x20]);
+		der.extend([0x01; 32]);
+
+		let fixed = der_to_fixed_signature(&der).unwrap();
+		assert_eq!(fixed[..32], [0x80; 32]);
+		assert_eq!(fixed[32..], [0x01; 32]);
+
+		// Short integers are padded with zeroes
+		let fixed =
+			der_to_fixed_signature(&[0x30, 0x06, 0x02, 0x01, 0x05, 0x02, 0x01, 0x07]).unwrap();
+		assert_eq!(fixed[31], 0x05);
+		assert_eq!(fixed[63], 0x07);
+		assert!(fixed[..31].iter().all(|byte| *byte == 0));
+
+		assert_eq!(der_to_fixed_signature(&der[..der.len() - 1]), None);
+		assert_eq!(der_to_fixed_signature(&[]), None);
+	}
+}
@@ -1,26 +1,102 @@
 use axum::{
 	body::Body,
-	http::{header::InvalidHeaderValue, StatusCode},
+	http::{
+		header::{self, InvalidHeaderValue},
+		HeaderMap,
+		HeaderName,
+		HeaderValue,
+		Method,
+		StatusCode,
+	},
+	middleware,
 	response::{IntoResponse, Response},
-	routing::get,
+	routing::{get, post},
 	Router,
 };
-use s3::{creds::error::CredentialsError, error::S3Error};
 use serde::{Deserialize, Serialize};
 
-use crate::prelude::*;
+use crate::{
+	prelude::*,
+	utils::{
+		config::StorageConfig,
+		storage::{self, Storage, StorageError},
+	},
+};
 
+/// Checks that requests to the registry have a token that grants access to
+/// the repository being accessed.
+mod authorizer;
+/// Cancel an ongoing blob upload, discarding the data uploaded so far.
+mod cancel_blob_upload;
+/// Finish a blob upload, verifying the digest of the uploaded data.
+mod complete_blob_upload;
+/// Tests that push and pull images through the routes of the registry, the
+/// same way a client would.
+#[cfg(test)]
+mod conformance_tests;
+/// Delete a manifest (or a tag) from a repository.
+mod delete_manifest;
+/// Periodically deletes blobs and manifests that are no longer referenced by
+/// any repository.
+pub mod garbage_collector;
 /// Download a specific blob, given its digest.
 mod get_blob_info;
+/// Get the progress of an ongoing blob upload.
+mod get_blob_upload_status;
+/// List the repositories in the registry that the user can view.
+mod get_catalog;
 /// Get the manifest for a specific reference.
 mod get_manifest_info;
+/// List the manifests in a repository that refer to a given manifest.
+mod get_referrers;
 /// Get the status of the registry.
 mod get_registry_status;
+/// List the tags of a repository.
+mod get_tag_list;
+/// Issue a token to access repositories of the registry, given the
+/// credentials of a user.
+mod get_token;
+/// Stores and verifies the cosign signatures of the images in the registry.
+pub mod image_signature;
+/// Pull images through the registry from an upstream registry, caching them
+/// in the workspace.
+mod pull_through_cache;
+/// Upload a manifest to a repository, optionally tagging it.
+mod put_manifest;
+/// Enforces the storage limits of repositories and workspaces.
+mod quota;
+/// Enforces the tag immutability and retention policies of repositories.
+pub mod retention;
+/// Start a blob upload, or upload an entire blob in a single request.
+mod start_blob_upload;
+/// Upload a chunk of data to an ongoing blob upload.
+mod upload_blob_chunk;
+/// Utilities to store the upload sessions of blobs and the data uploaded in
+/// them.
+mod upload_session;
+
+/// The header that is sent with every response of the registry, to let clients
+/// know that this is a registry that implements the v2 API.
+static DOCKER_DISTRIBUTION_API_VERSION: HeaderName =
+	HeaderName::from_static("docker-distribution-api-version");
+
+/// The header containing the digest of a blob or manifest.
+static DOCKER_CONTENT_DIGEST: HeaderName = HeaderName::from_static("docker-content-digest");
+
+/// The header containing the ID of an upload session.
+static DOCKER_UPLOAD_UUID: HeaderName = HeaderName::from_static("docker-upload-uuid");
+
+/// The header containing the digest of the subject of an uploaded manifest,
+/// letting clients know that the registry supports the referrers API.
+static OCI_SUBJECT: HeaderName = HeaderName::from_static("oci-subject");
+
+/// The header listing the filters that were applied to a list of referrers.
+static OCI_FILTERS_APPLIED: HeaderName = HeaderName::from_static("oci-filters-applied");
 
 /// The error type for the registry routes. This is used to return errors in the
 /// registry. The error details are taken from the Docker Registry API v2
 /// specification at https://github.com/opencontainers/distribution-spec/blob/main/spec.md#error-codes
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum RegistryError {
 	/// Blob unknown to registry
@@ -75,6 +151,21 @@ const fn default_status_code() -> StatusCode {
 	StatusCode::INTERNAL_SERVER_ERROR
 }
 
+impl Error {
+	/// Create an error with the given code and message, to be sent with the
+	/// given status code.
+	pub fn new(code: RegistryError, status_code: StatusCode, message: impl Into<String>) -> Self {
+		Self {
+			errors: [ErrorItem {
+				code,
+				message: message.into(),
+				detail: "".to_string(),
+			}],
+			status_code,
+		}
+	}
+}
+
 impl IntoResponse for Error {
 	fn into_response(self) -> Response {
 		Response::builder()
@@ -93,8 +184,8 @@ impl IntoResponse for Error {
 	}
 }
 
-impl From<S3Error> for Error {
-	fn from(err: S3Error) -> Self {
+impl From<StorageError> for Error {
+	fn from(err: StorageError) -> Self {
 		Self {
 			errors: [ErrorItem {
 				code: RegistryError::InternalServerError,
@@ -119,8 +210,8 @@ impl From<sqlx::Error> for Error {
 	}
 }
 
-impl From<CredentialsError> for Error {
-	fn from(err: CredentialsError) -> Self {
+impl From<InvalidHeaderValue> for Error {
+	fn from(err: InvalidHeaderValue) -> Self {
 		Self {
 			errors: [ErrorItem {
 				code: RegistryError::InternalServerError,
@@ -132,8 +223,8 @@ impl From<CredentialsError> for Error {
 	}
 }
 
-impl From<InvalidHeaderValue> for Error {
-	fn from(err: InvalidHeaderValue) -> Self {
+impl From<serde_json::Error> for Error {
+	fn from(err: serde_json::Error) -> Self {
 		Self {
 			errors: [ErrorItem {
 				code: RegistryError::InternalServerError,
@@ -145,8 +236,85 @@ impl From<InvalidHeaderValue> for Error {
 	}
 }
 
-impl From<serde_json::Error> for Error {
-	fn from(err: serde_json::Error) -> Self {
+impl From<rustis::Error> for Error {
+	fn from(err: rustis::Error) -> Self {
+		Self {
+			errors: [ErrorItem {
+				code: RegistryError::InternalServerError,
+				message: err.to_string(),
+				detail: err.to_string(),
+			}],
+			status_code: StatusCode::INTERNAL_SERVER_ERROR,
+		}
+	}
+}
+
+impl From<ErrorType> for Error {
+	fn from(err: ErrorType) -> Self {
+		let status_code = err.default_status_code();
+		let message: String = err.message().into();
+		if status_code.is_server_error() {
+			return Self {
+				errors: [ErrorItem {
+					code: RegistryError::InternalServerError,
+					message: message.clone(),
+					detail: message,
+				}],
+				status_code: StatusCode::INTERNAL_SERVER_ERROR,
+			};
+		}
+
+		// The errors of the API don't say if they're about a blob, a manifest or
+		// a repository, so most of them are mapped by their status code
+		let code = match err {
+			ErrorType::TagNotFound => RegistryError::ManifestUnknown,
+			ErrorType::ResourceDoesNotExist => RegistryError::NameUnknown,
+			_ => match status_code {
+				StatusCode::UNAUTHORIZED => RegistryError::Unauthorized,
+				StatusCode::FORBIDDEN => RegistryError::Denied,
+				StatusCode::NOT_FOUND => RegistryError::NameUnknown,
+				StatusCode::PAYLOAD_TOO_LARGE => RegistryError::SizeInvalid,
+				StatusCode::TOO_MANY_REQUESTS => RegistryError::TooManyRequests,
+				_ => RegistryError::Unsupported,
+			},
+		};
+		let status_code = match code {
+			RegistryError::ManifestUnknown | RegistryError::NameUnknown => StatusCode::NOT_FOUND,
+			_ => status_code,
+		};
+
+		Self::new(code, status_code, message)
+	}
+}
+
+impl From<jsonwebtoken::errors::Error> for Error {
+	fn from(err: jsonwebtoken::errors::Error) -> Self {
+		Self {
+			errors: [ErrorItem {
+				code: RegistryError::InternalServerError,
+				message: err.to_string(),
+				detail: err.to_string(),
+			}],
+			status_code: StatusCode::INTERNAL_SERVER_ERROR,
+		}
+	}
+}
+
+impl From<reqwest::Error> for Error {
+	fn from(err: reqwest::Error) -> Self {
+		Self {
+			errors: [ErrorItem {
+				code: RegistryError::InternalServerError,
+				message: err.to_string(),
+				detail: err.to_string(),
+			}],
+			status_code: StatusCode::INTERNAL_SERVER_ERROR,
+		}
+	}
+}
+
+impl From<std::io::Error> for Error {
+	fn from(err: std::io::Error) -> Self {
 		Self {
 			errors: [ErrorItem {
 				code: RegistryError::InternalServerError,
@@ -172,26 +340,334 @@ pub struct ErrorItem {
 	pub detail: String,
 }
 
+/// Sets up the routes for the container registry
 #[instrument(skip(state))]
 pub async fn setup_routes(state: &AppState) -> Router {
 	Router::new()
+		.route("/token", get(get_token::handle))
 		.nest(
 			"/v2",
 			Router::new()
 				.route("/", get(get_registry_status::handle))
+				.route("/_catalog", get(get_catalog::handle))
 				.route(
 					"/:workspaceId/:repoName/blobs/:digest",
 					get(get_blob_info::handle).head(get_blob_info::handle),
 				)
+				.route(
+					"/:workspaceId/:repoName/blobs/uploads/",
+					post(start_blob_upload::handle),
+				)
+				.route(
+					"/:workspaceId/:repoName/blobs/uploads/:uploadId",
+					get(get_blob_upload_status::handle)
+						.patch(upload_blob_chunk::handle)
+						.put(complete_blob_upload::handle)
+						.delete(cancel_blob_upload::handle),
+				)
+				.route(
+					"/:workspaceId/_cache/:upstream/*path",
+					get(pull_through_cache::handle).head(pull_through_cache::handle),
+				)
+				.route(
+					"/:workspaceId/:repoName/tags/list",
+					get(get_tag_list::handle),
+				)
+				.route(
+					"/:workspaceId/:repoName/referrers/:digest",
+					get(get_referrers::handle),
+				)
 				.route(
 					"/:workspaceId/:repoName/manifests/:reference",
-					get(get_manifest_info::handle),
-				),
+					get(get_manifest_info::handle)
+						.head(get_manifest_info::handle)
+						.put(put_manifest::handle)
+						.delete(delete_manifest::handle),
+				)
+				.route_layer(middleware::from_fn_with_state(
+					state.clone(),
+					authorizer::authorize,
+				)),
 		)
 		.with_state(state.clone())
 }
 
-/// Get the S3 object name for a blob.
-fn get_s3_object_name_for_blob(blob: &str) -> String {
+/// Get the storage backend that the registry stores its blobs and manifests
+/// in.
+fn get_storage(config: &StorageConfig) -> Result<Box<dyn Storage>, Error> {
+	Ok(storage::get_storage(config)?)
+}
+
+/// Get the ID of a repository in a workspace, given its name. Returns a
+/// `NAME_UNKNOWN` error if the repository does not exist.
+async fn get_repository_id(
+	connection: &mut DatabaseConnection,
+	workspace_id: Uuid,
+	repo_name: &str,
+) -> Result<Uuid, Error> {
+	query!(
+		r#"
+		SELECT
+			id
+		FROM
+			container_registry_repository
+		WHERE
+			workspace_id = $1 AND
+			name = $2 AND
+			deleted IS NULL;
+		"#,
+		workspace_id as _,
+		repo_name as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.map(|row| row.id.into())
+	.ok_or_else(|| {
+		Error::new(
+			RegistryError::NameUnknown,
+			StatusCode::NOT_FOUND,
+			"Repository not found",
+		)
+	})
+}
+
+/// Get the number of the given blobs that a repository can use. A repository
+/// can only use the blobs that were uploaded (or mounted) to it, and the blobs
+/// of the manifests in it. Blobs are stored once for the entire registry, so
+/// this keeps a repository from using the blobs of another repository just by
+/// knowing their digests.
+async fn count_repository_blobs(
+	connection: &mut DatabaseConnection,
+	repository_id: Uuid,
+	digests: &[String],
+) -> Result<i64, Error> {
+	let count = query!(
+		r#"
+		SELECT
+			COUNT(DISTINCT container_registry_repository_blob.blob_digest) AS "count!"
+		FROM
+			container_registry_repository_blob
+		WHERE
+			container_registry_repository_blob.blob_digest = ANY($2) AND
+			(
+				EXISTS (
+					SELECT
+						1
+					FROM
+						container_registry_repository_blob_link
+					WHERE
+						container_registry_repository_blob_link.repository_id = $1 AND
+						container_registry_repository_blob_link.blob_digest =
+							container_registry_repository_blob.blob_digest
+				) OR
+				EXISTS (
+					SELECT
+						1
+					FROM
+						container_registry_repository_manifest
+					INNER JOIN
+						container_registry_manifest_blob
+					ON
+						container_registry_manifest_blob.manifest_digest =
+							container_registry_repository_manifest.manifest_digest
+					WHERE
+						container_registry_repository_manifest.repository_id = $1 AND
+						container_registry_manifest_blob.blob_digest =
+							container_registry_repository_blob.blob_digest
+				)
+			);
+		"#,
+		repository_id as _,
+		digests,
+	)
+	.fetch_one(&mut *connection)
+	.await?
+	.count;
+
+	Ok(count)
+}
+
+/// Checks if the given string is a valid digest, in the format
+/// `sha256:<64 lowercase hex characters>`. This is the only digest algorithm
+/// supported by the registry.
+fn is_valid_digest(digest: &str) -> bool {
+	digest.strip_prefix("sha256:").is_some_and(|hex| {
+		hex.len() == 64 && hex.bytes().all(|c| matches!(c, b'0'..=b'9' | b'a'..=b'f'))
+	})
+}
+
+/// Checks if the given string is a valid tag, as defined by the distribution
+/// spec: `[a-zA-Z0-9_][a-zA-Z0-9._-]{0,127}`.
+fn is_valid_tag(tag: &str) -> bool {
+	let Some((first, rest)) = tag.as_bytes().split_first() else {
+		return false;
+	};
+	tag.len() <= 128 &&
+		(first.is_ascii_alphanumeric() || *first == b'_') &&
+		rest.iter()
+			.all(|c| c.is_ascii_alphanumeric() || matches!(c, b'_' | b'.' | b'-'))
+}
+
+/// Get the response for a manifest stored in the registry, exactly as it was
+/// uploaded, along with the headers that clients expect. For a `HEAD` request,
+/// only the headers are sent.
+async fn get_manifest_response(
+	state: &AppState,
+	method: &Method,
+	manifest_digest: &str,
+	media_type: &str,
+	size: i64,
+) -> Result<Response, Error> {
+	let headers = HeaderMap::from_iter([
+		(
+			DOCKER_DISTRIBUTION_API_VERSION.clone(),
+			HeaderValue::from_static("registry/2.0"),
+		),
+		(
+			DOCKER_CONTENT_DIGEST.clone(),
+			HeaderValue::from_str(manifest_digest)?,
+		),
+		(header::CONTENT_TYPE, HeaderValue::from_str(media_type)?),
+		(header::CONTENT_LENGTH, HeaderValue::from(size)),
+		(
+			header::ETAG,
+			HeaderValue::from_str(&format!("\"{}\"", manifest_digest))?,
+		),
+	]);
+
+	if method == Method::HEAD {
+		return Ok((StatusCode::OK, headers).into_response());
+	}
+
+	let object = get_storage(&state.config.storage)?
+		.get_object_stream(&get_object_name_for_manifest(manifest_digest))
+		.await?;
+
+	Ok((StatusCode::OK, headers, Body::from_stream(object)).into_response())
+}
+
+/// Get the value of the `Link` header that points to the next page of a
+/// paginated list, as defined by the distribution spec. `last` is the last item
+/// of the current page, and `n` is the number of items in each page.
+fn get_next_page_link(path: &str, n: usize, last: &str) -> Result<HeaderValue, Error> {
+	let query = serde_urlencoded::to_string([("n", n.to_string().as_str()), ("last", last)])
+		.map_err(ErrorType::server_error)?;
+	Ok(HeaderValue::from_str(&format!(
+		r#"<{path}?{query}>; rel="next""#
+	))?)
+}
+
+/// The media types of manifests that describe the image of a single platform.
+const IMAGE_MANIFEST_MEDIA_TYPES: [&str; 2] = [
+	"application/vnd.oci.image.manifest.v1+json",
+	"application/vnd.docker.distribution.manifest.v2+json",
+];
+
+/// The media types of image indexes (also called manifest lists), which point
+/// to the manifest of the image for each platform.
+const IMAGE_INDEX_MEDIA_TYPES: [&str; 2] = [
+	"application/vnd.oci.image.index.v1+json",
+	"application/vnd.docker.distribution.manifest.list.v2+json",
+];
+
+/// The prefix of the objects of the data uploaded in upload sessions.
+const UPLOADS_PREFIX: &str = "registry/uploads/";
+
+/// Get the object name for a blob.
+fn get_object_name_for_blob(blob: &str) -> String {
 	format!("registry/blobs/{blob}")
 }
+
+/// Get the object name for a manifest. The manifest is stored exactly as it
+/// was uploaded, so that its digest stays the same when it is downloaded.
+fn get_object_name_for_manifest(manifest: &str) -> String {
+	format!("registry/manifests/{manifest}")
+}
+
+#[cfg(test)]
+mod tests {
+	use axum::http::StatusCode;
+
+	use super::{get_next_page_link, is_valid_digest, is_valid_tag, Error, RegistryError};
+	use crate::prelude::*;
+
+	#[test]
+	fn assert_digest_validation() {
+		assert!(is_valid_digest(
+			"sha256:ca2b0f26964cf2e80ba3e084d5983dab293fdb87485dc6445f3f7bbfc89d7459"
+		));
+		assert!(!is_valid_digest(
+			"sha256:CA2B0F26964CF2E80BA3E084D5983DAB293FDB87485DC6445F3F7BBFC89D7459"
+		));
+		assert!(!is_valid_digest(
+			"sha512:ca2b0f26964cf2e80ba3e084d5983dab293fdb87485dc6445f3f7bbfc89d7459"
+		));
+		assert!(!is_valid_digest("sha256:ca2b0f26"));
+		assert!(!is_valid_digest("latest"));
+	}
+
+	#[test]
+	fn assert_tag_validation() {
+		assert!(is_valid_tag("latest"));
+		assert!(is_valid_tag("v1.2.3-beta_1"));
+		assert!(is_valid_tag("_internal"));
+		assert!(is_valid_tag(&"a".repeat(128)));
+		assert!(!is_valid_tag(""));
+		assert!(!is_valid_tag(".hidden"));
+		assert!(!is_valid_tag("-dash"));
+		assert!(!is_valid_tag("with/slash"));
+		assert!(!is_valid_tag(&"a".repeat(129)));
+	}
+
+	#[test]
+	fn assert_next_page_link() {
+		assert_eq!(
+			get_next_page_link("/v2/_catalog", 2, "workspace/app").unwrap(),
+			r#"</v2/_catalog?n=2&last=workspace%2Fapp>; rel="next""#
+		);
+		assert_eq!(
+			get_next_page_link("/v2/workspace/app/tags/list", 10, "v1.0.0").unwrap(),
+			r#"</v2/workspace/app/tags/list?n=10&last=v1.0.0>; rel="next""#
+		);
+	}
+
+	#[test]
+	fn assert_error_type_mapping() {
+		let mapped = |err: ErrorType| {
+			let error = Error::from(err);
+			(error.errors[0].code, error.status_code)
+		};
+
+		assert_eq!(
+			mapped(ErrorType::Unauthorized),
+			(RegistryError::Unauthorized, StatusCode::UNAUTHORIZED)
+		);
+		assert_eq!(
+			mapped(ErrorType::InvalidPassword),
+			(RegistryError::Unauthorized, StatusCode::UNAUTHORIZED)
+		);
+		assert_eq!(
+			mapped(ErrorType::InvalidRunnerMode),
+			(RegistryError::Denied, StatusCode::FORBIDDEN)
+		);
+		assert_eq!(
+			mapped(ErrorType::TagNotFound),
+			(RegistryError::ManifestUnknown, StatusCode::NOT_FOUND)
+		);
+		assert_eq!(
+			mapped(ErrorType::ResourceDoesNotExist),
+			(RegistryError::NameUnknown, StatusCode::NOT_FOUND)
+		);
+		assert_eq!(
+			mapped(ErrorType::WrongParameters),
+			(RegistryError::Unsupported, StatusCode::BAD_REQUEST)
+		);
+		assert_eq!(
+			mapped(ErrorType::InternalServerError),
+			(
+				RegistryError::InternalServerError,
+				StatusCode::INTERNAL_SERVER_ERROR
+			)
+		);
+	}
+}
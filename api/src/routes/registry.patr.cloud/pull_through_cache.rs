@@ -0,0 +1,614 @@
+use std::{collections::BTreeMap, io};
+
+use axum::{
+	body::Body,
+	extract::{Path, State},
+	http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+	response::{IntoResponse, Response},
+};
+use futures::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use time::{Duration, OffsetDateTime};
+
+use super::{
+	upload_session,
+	Error,
+	RegistryError,
+	DOCKER_CONTENT_DIGEST,
+	DOCKER_DISTRIBUTION_API_VERSION,
+};
+use crate::{prelude::*, utils::config::ContainerRegistryUpstreamConfig};
+
+/// The media types of manifests that are requested from the upstream registry
+const ACCEPTED_MANIFEST_MEDIA_TYPES: &str = concat!(
+	"application/vnd.oci.image.index.v1+json, ",
+	"application/vnd.docker.distribution.manifest.list.v2+json, ",
+	"application/vnd.oci.image.manifest.v1+json, ",
+	"application/vnd.docker.distribution.manifest.v2+json"
+);
+
+/// The parameters that are passed in the path of the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathParams {
+	/// The workspace ID that the images are cached in
+	workspace_id: Uuid,
+	/// The name of the upstream registry, as configured in the
+	/// [`ContainerRegistryConfig`][1]
+	///
+	/// [1]: crate::utils::config::ContainerRegistryConfig
+	upstream: String,
+	/// The rest of the path, containing the name of the image and the manifest
+	/// or blob being requested
+	path: String,
+}
+
+/// The content that is requested from the upstream registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum CacheRequest<'a> {
+	/// A manifest, with either a tag or a digest as the reference
+	Manifest(&'a str),
+	/// A blob, with its digest
+	Blob(&'a str),
+}
+
+/// Handles the `GET
+/// /v2/{workspace_id}/_cache/{upstream}/{image}/manifests/{reference}` and `GET
+/// /v2/{workspace_id}/_cache/{upstream}/{image}/blobs/{digest}` routes, as well
+/// as the `HEAD` requests for the same routes.
+///
+/// Manifests and blobs are fetched from the upstream registry the first time
+/// they are requested, and are served from the storage after that.
+/// Tags are checked against the upstream again once they are older than the
+/// TTL of the upstream. If the upstream cannot be reached, the cached manifest
+/// of a tag is served even if it is stale.
+#[axum::debug_handler]
+pub(super) async fn handle(
+	method: Method,
+	Path(path): Path<PathParams>,
+	State(state): State<AppState>,
+) -> Result<Response, Error> {
+	let Some(upstream) = state
+		.config
+		.container_registry
+		.upstreams
+		.get(&path.upstream)
+	else {
+		return Err(Error::new(
+			RegistryError::NameUnknown,
+			StatusCode::NOT_FOUND,
+			"Unknown upstream registry",
+		));
+	};
+	let Some((image, request)) = parse_cache_path(&path.path) else {
+		return Err(Error::new(
+			RegistryError::NameInvalid,
+			StatusCode::NOT_FOUND,
+			"Invalid image name",
+		));
+	};
+
+	let cache = Cache {
+		workspace_id: path.workspace_id,
+		upstream_name: &path.upstream,
+		upstream,
+		image,
+	};
+
+	match request {
+		CacheRequest::Manifest(reference) => get_manifest(&state, &method, &cache, reference).await,
+		CacheRequest::Blob(digest) => get_blob(&state, &method, &cache, digest).await,
+	}
+}
+
+/// Splits the path of a request to the cache into the name of the image and
+/// the content being requested.
+pub(super) fn parse_cache_path(path: &str) -> Option<(&str, CacheRequest<'_>)> {
+	let path = path.trim_start_matches('/');
+
+	let (image, request) = if let Some((image, reference)) = path.rsplit_once("/manifests/") {
+		(image, CacheRequest::Manifest(reference))
+	} else {
+		let (image, digest) = path.rsplit_once("/blobs/")?;
+		(image, CacheRequest::Blob(digest))
+	};
+
+	let is_valid_image = !image.is_empty() &&
+		image.split('/').all(|component| {
+			!component.is_empty() &&
+				component
+					.bytes()
+					.all(|c| matches!(c, b'a'..=b'z' | b'0'..=b'9' | b'.' | b'_' | b'-'))
+		});
+	let is_valid_request = match request {
+		CacheRequest::Manifest(reference) => {
+			super::is_valid_digest(reference) || super::is_valid_tag(reference)
+		}
+		CacheRequest::Blob(digest) => super::is_valid_digest(digest),
+	};
+
+	(is_valid_image && is_valid_request).then_some((image, request))
+}
+
+/// An image in the cache of a workspace, along with the upstream it is cached
+/// from.
+struct Cache<'a> {
+	/// The workspace that the image is cached in
+	workspace_id: Uuid,
+	/// The name of the upstream registry
+	upstream_name: &'a str,
+	/// The configuration of the upstream registry
+	upstream: &'a ContainerRegistryUpstreamConfig,
+	/// The name of the image in the upstream registry
+	image: &'a str,
+}
+
+impl Cache<'_> {
+	/// The name of the image in the upstream registry, as used in the API of
+	/// the registry. Official images on Docker Hub are stored under `library/`,
+	/// even though they are referred to without it.
+	fn upstream_image(&self) -> String {
+		if self.upstream_name == "docker.io" && !self.image.contains('/') {
+			format!("library/{}", self.image)
+		} else {
+			self.image.to_string()
+		}
+	}
+
+	/// Make a `GET` request to the upstream registry, for a path relative to
+	/// the image. If the upstream requires a token, one is requested using the
+	/// credentials of the upstream (if any) and the request is tried again.
+	async fn get(&self, path: &str, accept: Option<&str>) -> Result<reqwest::Response, Error> {
+		let client = reqwest::Client::new();
+		let url = format!(
+			"{}/v2/{}/{}",
+			self.upstream.url.trim_end_matches('/'),
+			self.upstream_image(),
+			path
+		);
+		let request = || {
+			let request = client.get(&url);
+			if let Some(accept) = accept {
+				request.header(header::ACCEPT, accept)
+			} else {
+				request
+			}
+		};
+
+		let response = request().send().await?;
+		if response.status() != StatusCode::UNAUTHORIZED {
+			return Ok(response);
+		}
+
+		let challenge = response
+			.headers()
+			.get(header::WWW_AUTHENTICATE)
+			.and_then(|value| value.to_str().ok())
+			.unwrap_or_default();
+		let authorized = if let Some(params) = parse_bearer_challenge(challenge) {
+			request().bearer_auth(self.get_token(&client, &params).await?)
+		} else if let Some(username) = &self.upstream.username {
+			request().basic_auth(username, self.upstream.password.as_ref())
+		} else {
+			return Ok(response);
+		};
+
+		Ok(authorized.send().await?)
+	}
+
+	/// Get a token from the authorization server of the upstream registry, as
+	/// described by the `WWW-Authenticate` challenge of the upstream.
+	async fn get_token(
+		&self,
+		client: &reqwest::Client,
+		challenge: &BTreeMap<String, String>,
+	) -> Result<String, Error> {
+		/// The response of the token endpoint of the upstream registry
+		#[derive(Deserialize)]
+		struct TokenResponse {
+			/// The token to access the upstream registry
+			#[serde(default)]
+			token: Option<String>,
+			/// The same as `token`, sent by OAuth 2.0 compatible servers
+			#[serde(default)]
+			access_token: Option<String>,
+		}
+
+		let Some(realm) = challenge.get("realm") else {
+			return Err(upstream_error(
+				"The upstream registry sent an invalid challenge",
+			));
+		};
+
+		let mut request = client.get(realm).query(
+			&challenge
+				.iter()
+				.filter(|(key, _)| matches!(key.as_str(), "service" | "scope"))
+				.collect::<Vec<_>>(),
+		);
+		if let Some(username) = &self.upstream.username {
+			request = request.basic_auth(username, self.upstream.password.as_ref());
+		}
+
+		let response = request
+			.send()
+			.await?
+			.error_for_status()?
+			.json::<TokenResponse>()
+			.await?;
+
+		response
+			.token
+			.or(response.access_token)
+			.ok_or_else(|| upstream_error("The upstream registry did not issue a token"))
+	}
+}
+
+/// Serves a manifest from the cache, fetching it from the upstream registry if
+/// it isn't cached or if the cached tag has expired.
+async fn get_manifest(
+	state: &AppState,
+	method: &Method,
+	cache: &Cache<'_>,
+	reference: &str,
+) -> Result<Response, Error> {
+	let mut database = state.database.begin().await?;
+
+	let cached = query!(
+		r#"
+		SELECT
+			container_registry_manifest.manifest_digest,
+			container_registry_manifest.media_type,
+			container_registry_manifest.size,
+			container_registry_cache_manifest.last_fetched
+		FROM
+			container_registry_cache_manifest
+		INNER JOIN
+			container_registry_manifest
+		ON
+			container_registry_cache_manifest.manifest_digest =
+				container_registry_manifest.manifest_digest
+		WHERE
+			container_registry_cache_manifest.workspace_id = $1 AND
+			container_registry_cache_manifest.upstream = $2 AND
+			container_registry_cache_manifest.image = $3 AND
+			container_registry_cache_manifest.reference = $4;
+		"#,
+		cache.workspace_id as _,
+		cache.upstream_name,
+		cache.image,
+		reference,
+	)
+	.fetch_optional(&mut *database)
+	.await?;
+
+	// A digest always points to the same manifest, so it never expires
+	let ttl = Duration::minutes(
+		cache
+			.upstream
+			.tag_ttl_minutes
+			.try_into()
+			.unwrap_or(i64::MAX),
+	);
+	let is_fresh = cached.as_ref().is_some_and(|cached| {
+		super::is_valid_digest(reference) || cached.last_fetched > OffsetDateTime::now_utc() - ttl
+	});
+
+	let (manifest_digest, media_type, size) = match cached {
+		Some(cached) if is_fresh => (cached.manifest_digest, cached.media_type, cached.size),
+		cached => match fetch_manifest(state, &mut database, cache, reference).await {
+			Ok(manifest) => manifest,
+			Err(err) => {
+				let Some(cached) = cached else {
+					return Err(err);
+				};
+				warn!(
+					"Unable to refresh `{}` from upstream `{}`, serving the cached manifest: {:?}",
+					cache.image, cache.upstream_name, err
+				);
+				(cached.manifest_digest, cached.media_type, cached.size)
+			}
+		},
+	};
+
+	database.commit().await?;
+
+	super::get_manifest_response(state, method, &manifest_digest, &media_type, size).await
+}
+
+/// Fetches a manifest from the upstream registry and stores it in the cache,
+/// returning the digest, media type and size of the manifest.
+async fn fetch_manifest(
+	state: &AppState,
+	connection: &mut DatabaseConnection,
+	cache: &Cache<'_>,
+	reference: &str,
+) -> Result<(String, String, i64), Error> {
+	let response = cache
+		.get(
+			&format!("manifests/{reference}"),
+			Some(ACCEPTED_MANIFEST_MEDIA_TYPES),
+		)
+		.await?;
+	if response.status() == StatusCode::NOT_FOUND {
+		return Err(Error::new(
+			RegistryError::ManifestUnknown,
+			StatusCode::NOT_FOUND,
+			"Manifest not found in the upstream registry",
+		));
+	}
+	let response = response.error_for_status()?;
+
+	let Some(media_type) = response
+		.headers()
+		.get(header::CONTENT_TYPE)
+		.and_then(|value| value.to_str().ok())
+		.map(String::from)
+	else {
+		return Err(upstream_error(
+			"The upstream registry did not send the media type of the manifest",
+		));
+	};
+
+	let body = response.bytes().await?;
+	if body.len() > super::put_manifest::MAX_MANIFEST_SIZE {
+		return Err(upstream_error(
+			"The manifest of the upstream registry is too large",
+		));
+	}
+
+	let digest = format!("sha256:{}", hex::encode(Sha256::digest(&body)));
+	if super::is_valid_digest(reference) && digest != reference {
+		return Err(upstream_error(
+			"The digest of the upstream manifest does not match the reference",
+		));
+	}
+
+	super::get_storage(&state.config.storage)?
+		.put_object(&super::get_object_name_for_manifest(&digest), &body)
+		.await?;
+
+	query!(
+		r#"
+		INSERT INTO
+			container_registry_manifest(
+				manifest_digest,
+				media_type,
+				size,
+				created
+			)
+		VALUES
+			($1, $2, $3, NOW())
+		ON CONFLICT(manifest_digest) DO UPDATE SET
+			created = EXCLUDED.created;
+		"#,
+		&digest,
+		&media_type,
+		i64::try_from(body.len()).unwrap_or(i64::MAX),
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	// The manifest can be pulled by its digest later (for example, by a
+	// runner pinned to the digest), so it is cached under its digest as well
+	for reference in [reference, digest.as_str()] {
+		query!(
+			r#"
+			INSERT INTO
+				container_registry_cache_manifest(
+					workspace_id,
+					upstream,
+					image,
+					reference,
+					manifest_digest,
+					last_fetched
+				)
+			VALUES
+				($1, $2, $3, $4, $5, NOW())
+			ON CONFLICT(workspace_id, upstream, image, reference) DO UPDATE SET
+				manifest_digest = EXCLUDED.manifest_digest,
+				last_fetched = EXCLUDED.last_fetched;
+			"#,
+			cache.workspace_id as _,
+			cache.upstream_name,
+			cache.image,
+			reference,
+			&digest,
+		)
+		.execute(&mut *connection)
+		.await?;
+	}
+
+	Ok((
+		digest,
+		media_type,
+		i64::try_from(body.len()).unwrap_or(i64::MAX),
+	))
+}
+
+/// Serves a blob from the cache, fetching it from the upstream registry if it
+/// isn't stored in the registry yet.
+async fn get_blob(
+	state: &AppState,
+	method: &Method,
+	cache: &Cache<'_>,
+	digest: &str,
+) -> Result<Response, Error> {
+	let mut database = state.database.begin().await?;
+	let storage = super::get_storage(&state.config.storage)?;
+
+	let stored = query!(
+		r#"
+		SELECT
+			size
+		FROM
+			container_registry_repository_blob
+		WHERE
+			blob_digest = $1;
+		"#,
+		digest,
+	)
+	.fetch_optional(&mut *database)
+	.await?;
+
+	let size = if let Some(stored) = stored {
+		stored.size.try_into().unwrap_or_default()
+	} else {
+		let response = cache.get(&format!("blobs/{digest}"), None).await?;
+		if response.status() == StatusCode::NOT_FOUND {
+			return Err(Error::new(
+				RegistryError::BlobUnknown,
+				StatusCode::NOT_FOUND,
+				"Blob not found in the upstream registry",
+			));
+		}
+		let response = response.error_for_status()?;
+
+		// The blob is only moved to its final location once its digest is
+		// verified, so that a bad upstream can't poison the cache
+		let upload_key = format!("{}{}/blob", super::UPLOADS_PREFIX, Uuid::new_v4());
+		let mut hasher = Sha256::new();
+		let mut size = 0;
+		let uploaded = {
+			let body = response
+				.bytes_stream()
+				.inspect_ok(|bytes| {
+					hasher.update(bytes);
+					size += bytes.len() as u64;
+				})
+				.map_err(io::Error::other)
+				.boxed();
+			storage.put_object_stream(&upload_key, body).await
+		};
+		let fetched_digest = format!("sha256:{}", hex::encode(hasher.finalize()));
+
+		let result = match uploaded {
+			Ok(_) if fetched_digest == digest => storage
+				.copy_object(&upload_key, &super::get_object_name_for_blob(digest))
+				.await
+				.map_err(Error::from),
+			Ok(_) => Err(upstream_error(
+				"The digest of the upstream blob does not match the requested digest",
+			)),
+			Err(err) => Err(err.into()),
+		};
+		storage.delete_object(&upload_key).await?;
+		result?;
+
+		upload_session::register_blob(&mut database, digest, size).await?;
+		size
+	};
+
+	query!(
+		r#"
+		INSERT INTO
+			container_registry_cache_blob(
+				workspace_id,
+				upstream,
+				image,
+				blob_digest,
+				created
+			)
+		VALUES
+			($1, $2, $3, $4, NOW())
+		ON CONFLICT(workspace_id, upstream, image, blob_digest) DO NOTHING;
+		"#,
+		cache.workspace_id as _,
+		cache.upstream_name,
+		cache.image,
+		digest,
+	)
+	.execute(&mut *database)
+	.await?;
+
+	database.commit().await?;
+
+	let headers = HeaderMap::from_iter([
+		(
+			DOCKER_DISTRIBUTION_API_VERSION.clone(),
+			HeaderValue::from_static("registry/2.0"),
+		),
+		(
+			DOCKER_CONTENT_DIGEST.clone(),
+			HeaderValue::from_str(digest)?,
+		),
+		(header::CONTENT_LENGTH, HeaderValue::from(size)),
+		(
+			header::CONTENT_TYPE,
+			HeaderValue::from_static("application/octet-stream"),
+		),
+	]);
+
+	if method == Method::HEAD {
+		return Ok((StatusCode::OK, headers).into_response());
+	}
+
+	let object = storage
+		.get_object_stream(&super::get_object_name_for_blob(digest))
+		.await?;
+
+	Ok((StatusCode::OK, headers, Body::from_stream(object)).into_response())
+}
+
+/// Parses a `WWW-Authenticate` header with a `Bearer` challenge, returning the
+/// parameters of the challenge (like the `realm`, `service` and `scope`).
+fn parse_bearer_challenge(challenge: &str) -> Option<BTreeMap<String, String>> {
+	let params = challenge.strip_prefix("Bearer ")?;
+
+	let mut parsed = BTreeMap::new();
+	let mut rest = params.trim();
+	while !rest.is_empty() {
+		let (key, value) = rest.split_once('=')?;
+		let (value, remaining) = if let Some(value) = value.strip_prefix('"') {
+			value.split_once('"')?
+		} else {
+			value.split_once(',').unwrap_or((value, ""))
+		};
+		parsed.insert(key.trim().to_string(), value.to_string());
+		rest = remaining.trim_start_matches(',').trim();
+	}
+
+	Some(parsed)
+}
+
+/// The error returned when the upstream registry does not respond as expected.
+fn upstream_error(message: &str) -> Error {
+	Error::new(RegistryError::Unsupported, StatusCode::BAD_GATEWAY, message)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{parse_bearer_challenge, parse_cache_path, CacheRequest};
+
+	#[test]
+	fn assert_cache_path_parse() {
+		let digest = "sha256:bccd10f490ab0f3fba61b193d1b80af91b17ca9bdca9768a16ed05ce16552fcb";
+
+		assert_eq!(
+			parse_cache_path("library/nginx/manifests/latest"),
+			Some(("library/nginx", CacheRequest::Manifest("latest")))
+		);
+		assert_eq!(
+			parse_cache_path(&format!("/nginx/blobs/{digest}")),
+			Some(("nginx", CacheRequest::Blob(digest)))
+		);
+		assert_eq!(parse_cache_path("nginx/blobs/latest"), None);
+		assert_eq!(parse_cache_path("/manifests/latest"), None);
+		assert_eq!(parse_cache_path("library//nginx/manifests/latest"), None);
+		assert_eq!(parse_cache_path("nginx/tags/list"), None);
+	}
+
+	#[test]
+	fn assert_bearer_challenge_parse() {
+		let challenge = parse_bearer_challenge(
+			r#"Bearer realm="https://auth.docker.io/token",service="registry.docker.io",scope="repository:library/nginx:pull""#,
+		)
+		.unwrap();
+
+		assert_eq!(challenge["realm"], "https://auth.docker.io/token");
+		assert_eq!(challenge["service"], "registry.docker.io");
+		assert_eq!(challenge["scope"], "repository:library/nginx:pull");
+		assert_eq!(parse_bearer_challenge(r#"Basic realm="registry""#), None);
+	}
+}
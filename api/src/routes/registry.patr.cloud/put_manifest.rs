@@ -0,0 +1,643 @@
+use std::collections::BTreeMap;
+
+use axum::{
+	body::Bytes,
+	extract::{Path, State},
+	http::{header, HeaderMap, HeaderValue, StatusCode},
+	response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{
+	image_signature,
+	quota::{StorageLimits, StorageUsage},
+	retention,
+	Error,
+	RegistryError,
+	DOCKER_CONTENT_DIGEST,
+	DOCKER_DISTRIBUTION_API_VERSION,
+	OCI_SUBJECT,
+};
+use crate::{
+	prelude::*,
+	routes::api_patr_cloud::workspace::deployment::{deploy_on_push, runner_update},
+};
+
+/// The maximum size of a manifest that can be uploaded. Manifests only
+/// reference blobs, so anything larger than this is almost certainly not a
+/// valid manifest.
+pub(super) const MAX_MANIFEST_SIZE: usize = 4 * 1024 * 1024;
+
+/// The maximum size of a config blob that is read to find the platform of an
+/// image. Config blobs are uploaded by clients like any other blob, so the
+/// platform of images with larger configs is left empty instead of loading the
+/// entire blob into memory.
+const MAX_CONFIG_SIZE: u64 = 4 * 1024 * 1024;
+
+/// The parameters that are passed in the path of the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathParams {
+	/// The workspace ID of the repository
+	workspace_id: Uuid,
+	/// The name of the repository
+	repo_name: String,
+	/// The reference of the manifest. Either a tag or the digest of the
+	/// manifest
+	reference: String,
+}
+
+/// The parts of an image manifest (or an image index) that the registry needs
+/// to know about. The manifest itself is stored exactly as it was uploaded, so
+/// any other fields are ignored here.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestReferences {
+	/// The media type of the manifest, if set in the manifest
+	#[serde(default)]
+	media_type: Option<String>,
+	/// The config blob of the image. Only present in image manifests
+	#[serde(default)]
+	config: Option<Descriptor>,
+	/// The layers of the image. Only present in image manifests
+	#[serde(default)]
+	layers: Vec<Descriptor>,
+	/// The manifests of each platform. Only present in image indexes
+	#[serde(default)]
+	manifests: Vec<Descriptor>,
+	/// The manifest that this manifest refers to, if this manifest is an
+	/// artifact (like an SBOM or a signature) attached to another manifest
+	#[serde(default)]
+	subject: Option<Descriptor>,
+	/// The type of the artifact, if this manifest is an artifact
+	#[serde(default)]
+	artifact_type: Option<String>,
+	/// The annotations of the manifest
+	#[serde(default)]
+	annotations: Option<BTreeMap<String, String>>,
+}
+
+/// A reference to a blob or a manifest from another manifest
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Descriptor {
+	/// The media type of the content being referenced
+	#[serde(default)]
+	media_type: Option<String>,
+	/// The digest of the content being referenced
+	digest: String,
+	/// The platform of the manifest being referenced. Only present in the
+	/// manifests of an image index
+	#[serde(default)]
+	platform: Option<ImagePlatform>,
+	/// The annotations of the content being referenced. Cosign stores the
+	/// signature of each layer of a signature manifest here
+	#[serde(default)]
+	annotations: Option<BTreeMap<String, String>>,
+}
+
+/// The platform of an image, as given in the config blob of the image, or in
+/// the descriptor of the image in an image index
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+struct ImagePlatform {
+	/// The CPU architecture of the image
+	#[serde(default)]
+	architecture: String,
+	/// The operating system of the image
+	#[serde(default)]
+	os: String,
+	/// The variant of the CPU architecture, if any
+	#[serde(default)]
+	variant: String,
+}
+
+impl ManifestReferences {
+	/// The type of the artifact that the manifest describes. If the manifest
+	/// doesn't set one, the media type of the config blob is used instead, as
+	/// required by the referrers API.
+	fn artifact_type(&self) -> Option<&str> {
+		self.artifact_type
+			.as_deref()
+			.or(self.config.as_ref()?.media_type.as_deref())
+	}
+
+	/// Checks that the manifest has the fields required by its media type. An
+	/// image manifest must have a config blob, and an image index must only
+	/// reference other manifests.
+	fn is_valid_for(&self, media_type: &str) -> bool {
+		if super::IMAGE_MANIFEST_MEDIA_TYPES.contains(&media_type) {
+			self.config.is_some() && self.manifests.is_empty()
+		} else if super::IMAGE_INDEX_MEDIA_TYPES.contains(&media_type) {
+			self.config.is_none() && self.layers.is_empty()
+		} else {
+			false
+		}
+	}
+}
+
+/// Handles the `PUT /v2/{workspace_id}/{repo_name}/manifests/{reference}`
+/// route.
+///
+/// All the blobs (or manifests, in case of an index) referenced by the
+/// manifest must already be uploaded (or mounted) to the repository. If the
+/// reference is a tag, the tag is updated to point to the uploaded manifest.
+/// For an image index, the platform of each manifest in the index is stored, so
+/// that the platforms of the image can be listed without reading the index
+/// again. If the manifest has a subject, it is listed as a referrer of the
+/// subject, even if the subject hasn't been uploaded yet. Deployments that use
+/// the pushed tag and have `deploy_on_push` enabled are redeployed with the new
+/// manifest. Cosign signatures are stored along with the digest they sign, so
+/// that they can be verified before the signed image is deployed. A tag that
+/// matches an immutable tag pattern of the repository can't be pointed to a
+/// different manifest once it is pushed. Manifests that take the repository (or
+/// its workspace) over its storage limit are denied.
+#[axum::debug_handler]
+pub(super) async fn handle(
+	Path(path): Path<PathParams>,
+	State(state): State<AppState>,
+	headers: HeaderMap,
+	body: Bytes,
+) -> Result<impl IntoResponse, Error> {
+	if body.len() > MAX_MANIFEST_SIZE {
+		return Err(Error::new(
+			RegistryError::SizeInvalid,
+			StatusCode::PAYLOAD_TOO_LARGE,
+			"Manifest is too large",
+		));
+	}
+
+	let digest = format!("sha256:{}", hex::encode(Sha256::digest(&body)));
+	let tag = if super::is_valid_digest(&path.reference) {
+		if path.reference != digest {
+			return Err(Error::new(
+				RegistryError::DigestInvalid,
+				StatusCode::BAD_REQUEST,
+				"The digest of the manifest does not match the reference",
+			));
+		}
+		None
+	} else if super::is_valid_tag(&path.reference) {
+		Some(path.reference.as_str())
+	} else {
+		return Err(Error::new(
+			RegistryError::ManifestInvalid,
+			StatusCode::BAD_REQUEST,
+			"Invalid reference",
+		));
+	};
+
+	let manifest = serde_json::from_slice::<ManifestReferences>(&body).map_err(|err| {
+		Error::new(
+			RegistryError::ManifestInvalid,
+			StatusCode::BAD_REQUEST,
+			err.to_string(),
+		)
+	})?;
+
+	let Some(media_type) = headers
+		.get(header::CONTENT_TYPE)
+		.and_then(|value| value.to_str().ok())
+		.map(String::from)
+		.or(manifest.media_type.clone())
+	else {
+		return Err(Error::new(
+			RegistryError::ManifestInvalid,
+			StatusCode::BAD_REQUEST,
+			"The media type of the manifest is not known",
+		));
+	};
+
+	if !manifest.is_valid_for(&media_type) {
+		return Err(Error::new(
+			RegistryError::ManifestInvalid,
+			StatusCode::BAD_REQUEST,
+			format!("Unsupported manifest of media type `{media_type}`"),
+		));
+	}
+
+	let mut database = state.database.begin().await?;
+	let repository_id =
+		super::get_repository_id(&mut database, path.workspace_id, &path.repo_name).await?;
+
+	let blobs = manifest
+		.config
+		.iter()
+		.chain(&manifest.layers)
+		.map(|descriptor| descriptor.digest.clone())
+		.collect::<Vec<_>>();
+	let manifests = manifest
+		.manifests
+		.iter()
+		.map(|descriptor| descriptor.digest.clone())
+		.collect::<Vec<_>>();
+
+	let existing_blobs =
+		super::count_repository_blobs(&mut database, repository_id, &blobs).await?;
+
+	let existing_manifests = query!(
+		r#"
+		SELECT
+			COUNT(DISTINCT manifest_digest) AS "count!"
+		FROM
+			container_registry_repository_manifest
+		WHERE
+			repository_id = $1 AND
+			manifest_digest = ANY($2);
+		"#,
+		repository_id as _,
+		&manifests[..],
+	)
+	.fetch_one(&mut *database)
+	.await?
+	.count;
+
+	let unique_count = |digests: &[String]| {
+		let mut digests = digests.to_vec();
+		digests.sort();
+		digests.dedup();
+		i64::try_from(digests.len()).unwrap_or(i64::MAX)
+	};
+	if existing_blobs != unique_count(&blobs) || existing_manifests != unique_count(&manifests) {
+		return Err(Error::new(
+			RegistryError::ManifestBlobUnknown,
+			StatusCode::BAD_REQUEST,
+			"The manifest references a blob or manifest that does not exist",
+		));
+	}
+
+	let limits = StorageLimits::get(
+		&mut database,
+		&state.config.container_registry,
+		repository_id,
+	)
+	.await?;
+	let usage = StorageUsage::get(&mut database, path.workspace_id, repository_id).await?;
+
+	let storage = super::get_storage(&state.config.storage)?;
+
+	// The platform of the image is only known from the config blob. Indexes
+	// contain multiple platforms, so they don't have a platform of their own.
+	let platform = if let Some(config) = &manifest.config {
+		let key = super::get_object_name_for_blob(&config.digest);
+		if storage.get_object_info(&key).await?.size <= MAX_CONFIG_SIZE {
+			let object = storage.get_object(&key).await?;
+			serde_json::from_slice::<ImagePlatform>(&object).unwrap_or_default()
+		} else {
+			warn!(
+				"Config blob `{}` is too large to read the platform from",
+				config.digest
+			);
+			ImagePlatform::default()
+		}
+	} else {
+		ImagePlatform::default()
+	};
+
+	storage
+		.put_object(&super::get_object_name_for_manifest(&digest), &body)
+		.await?;
+
+	query!(
+		r#"
+		INSERT INTO
+			container_registry_manifest(
+				manifest_digest,
+				media_type,
+				size,
+				created,
+				subject_digest,
+				artifact_type,
+				annotations
+			)
+		VALUES
+			($1, $2, $3, NOW(), $4, $5, $6)
+		ON CONFLICT(manifest_digest) DO UPDATE SET
+			created = EXCLUDED.created;
+		"#,
+		&digest,
+		&media_type,
+		i64::try_from(body.len()).unwrap_or(i64::MAX),
+		manifest.subject.as_ref().map(|subject| &subject.digest),
+		manifest.artifact_type(),
+		manifest
+			.annotations
+			.as_ref()
+			.map(serde_json::to_string)
+			.transpose()?,
+	)
+	.execute(&mut *database)
+	.await?;
+
+	for child in &manifest.manifests {
+		let platform = child.platform.clone().unwrap_or_default();
+		query!(
+			r#"
+			INSERT INTO
+				container_registry_index_manifest(
+					index_digest,
+					manifest_digest,
+					architecture,
+					os,
+					variant
+				)
+			VALUES
+				($1, $2, $3, $4, $5)
+			ON CONFLICT(index_digest, manifest_digest) DO NOTHING;
+			"#,
+			&digest,
+			&child.digest,
+			platform.architecture,
+			platform.os,
+			platform.variant,
+		)
+		.execute(&mut *database)
+		.await?;
+	}
+
+	for blob in &blobs {
+		query!(
+			r#"
+			INSERT INTO
+				container_registry_manifest_blob(
+					manifest_digest,
+					blob_digest,
+					parent_blob_digest
+				)
+			VALUES
+				($1, $2, NULL)
+			ON CONFLICT(manifest_digest, blob_digest) DO NOTHING;
+			"#,
+			&digest,
+			blob,
+		)
+		.execute(&mut *database)
+		.await?;
+	}
+
+	query!(
+		r#"
+		INSERT INTO
+			container_registry_repository_manifest(
+				repository_id,
+				manifest_digest,
+				architecture,
+				os,
+				variant,
+				created
+			)
+		VALUES
+			($1, $2, $3, $4, $5, NOW())
+		ON CONFLICT(repository_id, manifest_digest) DO NOTHING;
+		"#,
+		repository_id as _,
+		&digest,
+		platform.architecture,
+		platform.os,
+		platform.variant,
+	)
+	.execute(&mut *database)
+	.await?;
+
+	// Only the blobs that the repository (or the workspace) didn't already
+	// have count towards the limits, so shared base layers are free
+	limits.check(
+		usage,
+		StorageUsage::get(&mut database, path.workspace_id, repository_id).await?,
+	)?;
+
+	if let Some(tag) = tag {
+		let current_digest = query!(
+			r#"
+			SELECT
+				manifest_digest
+			FROM
+				container_registry_repository_tag
+			WHERE
+				repository_id = $1 AND
+				tag = $2
+			FOR UPDATE;
+			"#,
+			repository_id as _,
+			tag,
+		)
+		.fetch_optional(&mut *database)
+		.await?
+		.map(|row| row.manifest_digest);
+
+		let is_overwritten = current_digest.is_some_and(|current| current != digest);
+		if is_overwritten && retention::is_tag_immutable(&mut database, repository_id, tag).await? {
+			return Err(Error::new(
+				RegistryError::Denied,
+				StatusCode::CONFLICT,
+				"The tag is immutable and already points to a different manifest",
+			));
+		}
+
+		query!(
+			r#"
+			INSERT INTO
+				container_registry_repository_tag(
+					repository_id,
+					tag,
+					manifest_digest,
+					last_updated
+				)
+			VALUES
+				($1, $2, $3, NOW())
+			ON CONFLICT(repository_id, tag) DO UPDATE SET
+				manifest_digest = EXCLUDED.manifest_digest,
+				last_updated = EXCLUDED.last_updated;
+			"#,
+			repository_id as _,
+			tag,
+			&digest,
+		)
+		.execute(&mut *database)
+		.await?;
+	}
+
+	// Cosign signatures are either pushed to a tag named after the signed
+	// digest, or attached to the signed manifest as a referrer
+	let signed_digest = match (&manifest.subject, tag) {
+		(Some(subject), _)
+			if manifest.artifact_type() ==
+				Some(image_signature::COSIGN_SIGNATURE_ARTIFACT_TYPE) =>
+		{
+			Some(subject.digest.clone())
+		}
+		(_, Some(tag)) => image_signature::get_signed_digest_from_tag(tag),
+		_ => None,
+	};
+	let mut is_signature_stored = false;
+	if let Some(signed_digest) = &signed_digest {
+		for layer in &manifest.layers {
+			if layer.media_type.as_deref() != Some(image_signature::COSIGN_PAYLOAD_MEDIA_TYPE) {
+				continue;
+			}
+			let Some(signature) = layer.annotations.as_ref().and_then(|annotations| {
+				annotations.get(image_signature::COSIGN_SIGNATURE_ANNOTATION)
+			}) else {
+				continue;
+			};
+
+			is_signature_stored |= image_signature::store_signature(
+				&mut database,
+				&*storage,
+				repository_id,
+				signed_digest,
+				&digest,
+				&layer.digest,
+				signature,
+			)
+			.await?;
+		}
+	}
+
+	// Deployments are redeployed when the tag they use is pushed, or when the
+	// image their tag points to is signed (since the image may not have been
+	// deployable before it was signed)
+	let mut redeploys = Vec::new();
+	if let Some(tag) = tag {
+		redeploys.push((tag.to_string(), digest.clone()));
+	}
+	if let Some(signed_digest) = signed_digest.filter(|_| is_signature_stored) {
+		let tags = query!(
+			r#"
+			SELECT
+				tag
+			FROM
+				container_registry_repository_tag
+			WHERE
+				repository_id = $1 AND
+				manifest_digest = $2;
+			"#,
+			repository_id as _,
+			&signed_digest,
+		)
+		.fetch_all(&mut *database)
+		.await?;
+		redeploys.extend(tags.into_iter().map(|row| (row.tag, signed_digest.clone())));
+	}
+
+	let mut runner_updates = Vec::new();
+	for (tag, digest) in redeploys {
+		runner_updates.extend(
+			deploy_on_push::redeploy_deployments(
+				&mut database,
+				path.workspace_id,
+				repository_id,
+				&tag,
+				&digest,
+			)
+			.await?,
+		);
+	}
+
+	database.commit().await?;
+
+	runner_update::notify_runners(&state.redis, path.workspace_id, runner_updates).await?;
+
+	let mut headers = HeaderMap::from_iter([
+		(
+			DOCKER_DISTRIBUTION_API_VERSION.clone(),
+			HeaderValue::from_static("registry/2.0"),
+		),
+		(
+			header::LOCATION,
+			HeaderValue::from_str(&format!(
+				"/v2/{}/{}/manifests/{}",
+				path.workspace_id, path.repo_name, digest
+			))?,
+		),
+		(
+			DOCKER_CONTENT_DIGEST.clone(),
+			HeaderValue::from_str(&digest)?,
+		),
+	]);
+	if let Some(subject) = &manifest.subject {
+		headers.insert(OCI_SUBJECT.clone(), HeaderValue::from_str(&subject.digest)?);
+	}
+
+	Ok((StatusCode::CREATED, headers))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{ImagePlatform, ManifestReferences};
+
+	#[test]
+	fn assert_image_manifest_parse() {
+		let manifest = serde_json::from_str::<ManifestReferences>(
+			r#"{
+				"schemaVersion": 2,
+				"mediaType": "application/vnd.oci.image.manifest.v1+json",
+				"config": {
+					"mediaType": "application/vnd.oci.image.config.v1+json",
+					"size": 2297,
+					"digest": "sha256:ca2b0f26964cf2e80ba3e084d5983dab293fdb87485dc6445f3f7bbfc89d7459"
+				},
+				"layers": [
+					{
+						"mediaType": "application/vnd.oci.image.layer.v1.tar+gzip",
+						"size": 29538961,
+						"digest": "sha256:bccd10f490ab0f3fba61b193d1b80af91b17ca9bdca9768a16ed05ce16552fcb"
+					}
+				]
+			}"#,
+		)
+		.unwrap();
+
+		assert_eq!(
+			manifest.media_type.as_deref(),
+			Some("application/vnd.oci.image.manifest.v1+json")
+		);
+		assert_eq!(
+			manifest.config.as_ref().unwrap().digest,
+			"sha256:ca2b0f26964cf2e80ba3e084d5983dab293fdb87485dc6445f3f7bbfc89d7459"
+		);
+		assert_eq!(manifest.layers.len(), 1);
+		assert!(manifest.manifests.is_empty());
+		assert!(manifest.is_valid_for("application/vnd.oci.image.manifest.v1+json"));
+		assert!(!manifest.is_valid_for("application/vnd.oci.image.index.v1+json"));
+	}
+
+	#[test]
+	fn assert_image_index_parse() {
+		let manifest = serde_json::from_str::<ManifestReferences>(
+			r#"{
+				"schemaVersion": 2,
+				"mediaType": "application/vnd.oci.image.index.v1+json",
+				"manifests": [
+					{
+						"digest": "sha256:aa772c98400ef833586d1d517d3e8de670f7e712bf581ce6053165081773259d",
+						"mediaType": "application/vnd.oci.image.manifest.v1+json",
+						"platform": {
+							"architecture": "amd64",
+							"os": "linux"
+						},
+						"size": 424
+					}
+				]
+			}"#,
+		)
+		.unwrap();
+
+		assert!(manifest.config.is_none());
+		assert!(manifest.layers.is_empty());
+		assert_eq!(
+			manifest.manifests[0].digest,
+			"sha256:aa772c98400ef833586d1d517d3e8de670f7e712bf581ce6053165081773259d"
+		);
+		assert_eq!(
+			manifest.manifests[0].platform,
+			Some(ImagePlatform {
+				architecture: "amd64".to_string(),
+				os: "linux".to_string(),
+				variant: String::new(),
+			})
+		);
+		assert!(manifest.is_valid_for("application/vnd.oci.image.index.v1+json"));
+		assert!(!manifest.is_valid_for("application/vnd.oci.image.manifest.v1+json"));
+	}
+}
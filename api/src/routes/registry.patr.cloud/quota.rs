@@ -0,0 +1,244 @@
+use axum::http::{header, HeaderMap, StatusCode};
+
+use super::{Error, RegistryError};
+use crate::{prelude::*, utils::config::ContainerRegistryConfig};
+
+/// The storage used by a repository and the workspace it is in, in bytes.
+/// Blobs are only counted once, no matter how many manifests (or repositories)
+/// they are a part of.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StorageUsage {
+	/// The storage used by the repository
+	pub repository: u64,
+	/// The storage used by all the repositories in the workspace
+	pub workspace: u64,
+}
+
+impl StorageUsage {
+	/// Get the storage currently used by a repository and its workspace.
+	pub async fn get(
+		connection: &mut DatabaseConnection,
+		workspace_id: Uuid,
+		repository_id: Uuid,
+	) -> Result<Self, Error> {
+		let row = query!(
+			r#"
+			SELECT
+				CONTAINER_REGISTRY_REPOSITORY_SIZE($1) AS "repository!",
+				CONTAINER_REGISTRY_WORKSPACE_SIZE($2) AS "workspace!";
+			"#,
+			repository_id as _,
+			workspace_id as _,
+		)
+		.fetch_one(&mut *connection)
+		.await?;
+
+		Ok(Self {
+			repository: row.repository.try_into().unwrap_or_default(),
+			workspace: row.workspace.try_into().unwrap_or_default(),
+		})
+	}
+
+	/// The storage that would be used once the given number of bytes is added
+	/// to the repository.
+	pub fn with_additional(self, size: u64) -> Self {
+		Self {
+			repository: self.repository.saturating_add(size),
+			workspace: self.workspace.saturating_add(size),
+		}
+	}
+}
+
+/// The storage limits of a repository and the workspace it is in, in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StorageLimits {
+	/// The limit set in the policy of the repository, if any
+	pub repository: Option<u64>,
+	/// The limit of the workspace, if any
+	pub workspace: Option<u64>,
+}
+
+impl StorageLimits {
+	/// Get the storage limits that apply to a repository.
+	pub async fn get(
+		connection: &mut DatabaseConnection,
+		config: &ContainerRegistryConfig,
+		repository_id: Uuid,
+	) -> Result<Self, Error> {
+		let repository = query!(
+			r#"
+			SELECT
+				storage_limit
+			FROM
+				container_registry_repository_policy
+			WHERE
+				repository_id = $1;
+			"#,
+			repository_id as _,
+		)
+		.fetch_optional(&mut *connection)
+		.await?
+		.and_then(|row| row.storage_limit)
+		.map(|limit| limit.try_into().unwrap_or_default());
+
+		Ok(Self {
+			repository,
+			workspace: config.workspace_storage_limit,
+		})
+	}
+
+	/// Checks if going from the current usage to the new usage is allowed,
+	/// returning a `DENIED` error otherwise. A push is only denied if it grows
+	/// the usage past a limit, so that a repository that is already over its
+	/// limit (say, after the limit was lowered) can still be re-tagged and
+	/// cleaned up.
+	pub fn check(&self, current: StorageUsage, new: StorageUsage) -> Result<(), Error> {
+		let exceeded = [
+			(
+				"repository",
+				self.repository,
+				current.repository,
+				new.repository,
+			),
+			(
+				"workspace",
+				self.workspace,
+				current.workspace,
+				new.workspace,
+			),
+		]
+		.into_iter()
+		.find_map(|(scope, limit, current, new)| {
+			limit
+				.filter(|limit| new > current && new > *limit)
+				.map(|limit| (scope, limit, new))
+		});
+
+		let Some((scope, limit, new)) = exceeded else {
+			return Ok(());
+		};
+
+		Err(Error::new(
+			RegistryError::Denied,
+			StatusCode::FORBIDDEN,
+			format!(
+				"Storage limit exceeded: the {scope} would use {} of its {} limit",
+				format_size(new),
+				format_size(limit)
+			),
+		))
+	}
+}
+
+/// Checks if a blob of the given size can be uploaded to a repository without
+/// exceeding the storage limits of the repository or its workspace. The blob
+/// is counted as new data, even if it is already in the registry, since it
+/// isn't known yet which manifests it will be a part of.
+pub async fn check_blob_upload(
+	connection: &mut DatabaseConnection,
+	config: &ContainerRegistryConfig,
+	workspace_id: Uuid,
+	repository_id: Uuid,
+	size: u64,
+) -> Result<(), Error> {
+	let limits = StorageLimits::get(connection, config, repository_id).await?;
+	if limits == StorageLimits::default() {
+		return Ok(());
+	}
+
+	let usage = StorageUsage::get(connection, workspace_id, repository_id).await?;
+	limits.check(usage, usage.with_additional(size))
+}
+
+/// Checks if a chunk can be added to an upload that has `uploaded` bytes so
+/// far, before any of the chunk is written. This is only possible if the client
+/// sent the length of the chunk in the `Content-Length` header, so the upload
+/// is still checked with [`check_blob_upload`] once the chunk is written.
+pub async fn check_chunk_upload(
+	connection: &mut DatabaseConnection,
+	config: &ContainerRegistryConfig,
+	workspace_id: Uuid,
+	repository_id: Uuid,
+	uploaded: u64,
+	headers: &HeaderMap,
+) -> Result<(), Error> {
+	let Some(length) = headers
+		.get(header::CONTENT_LENGTH)
+		.and_then(|value| value.to_str().ok()?.parse::<u64>().ok())
+	else {
+		return Ok(());
+	};
+
+	check_blob_upload(
+		connection,
+		config,
+		workspace_id,
+		repository_id,
+		uploaded.saturating_add(length),
+	)
+	.await
+}
+
+/// Formats a number of bytes in a human readable way (eg: `1.50 GiB`).
+fn format_size(bytes: u64) -> String {
+	const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+
+	if bytes < 1024 {
+		return format!("{bytes} B");
+	}
+
+	let mut size = bytes as f64;
+	let mut unit = "B";
+	for next_unit in UNITS {
+		if size < 1024.0 {
+			break;
+		}
+		size /= 1024.0;
+		unit = next_unit;
+	}
+	format!("{size:.2} {unit}")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{format_size, StorageLimits, StorageUsage};
+
+	#[test]
+	fn assert_storage_limits_check() {
+		let limits = StorageLimits {
+			repository: Some(100),
+			workspace: Some(1000),
+		};
+		let usage = StorageUsage {
+			repository: 50,
+			workspace: 500,
+		};
+
+		assert!(limits.check(usage, usage.with_additional(50)).is_ok());
+		assert!(limits.check(usage, usage.with_additional(51)).is_err());
+
+		// Over the limit already, but not growing
+		let usage = StorageUsage {
+			repository: 150,
+			workspace: 500,
+		};
+		assert!(limits.check(usage, usage).is_ok());
+		assert!(limits.check(usage, usage.with_additional(1)).is_err());
+
+		let usage = StorageUsage {
+			repository: 0,
+			workspace: 990,
+		};
+		assert!(limits.check(usage, usage.with_additional(20)).is_err());
+		assert!(StorageLimits::default()
+			.check(usage, usage.with_additional(u64::MAX))
+			.is_ok());
+	}
+
+	#[test]
+	fn assert_size_format() {
+		assert_eq!(format_size(512), "512 B");
+		assert_eq!(format_size(1536), "1.50 KiB");
+		assert_eq!(format_size(10 * 1024 * 1024 * 1024), "10.00 GiB");
+	}
+}
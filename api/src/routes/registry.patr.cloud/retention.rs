@@ -0,0 +1,552 @@
+use std::time::Duration as StdDuration;
+
+use time::{Duration, OffsetDateTime};
+
+use super::{image_signature, Error};
+use crate::{prelude::*, utils::periodic_task};
+
+/// A summary of a retention run. In dry run mode, this is what would have been
+/// deleted, had the retention policies been enforced.
+#[derive(Debug, Clone, Default)]
+pub struct RetentionReport {
+	/// The tags that are older than the last N tags of their repository
+	pub tags: Vec<ExpiredTag>,
+	/// The untagged manifests that are older than the retention period of their
+	/// repository
+	pub manifests: Vec<ExpiredManifest>,
+}
+
+/// A tag that was deleted by the retention policy of its repository (or would
+/// have been, in dry run mode)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpiredTag {
+	/// The repository that the tag is in
+	pub repository_id: Uuid,
+	/// The name of the tag
+	pub tag: String,
+	/// The digest of the manifest that the tag points to
+	pub manifest_digest: String,
+	/// The position of the tag (starting from 1 for the most recently pushed
+	/// one) among the tags of the repository that the policy applies to
+	pub position: usize,
+	/// The number of tags that the policy of the repository keeps
+	pub keep_last_tags: u32,
+}
+
+/// An untagged manifest that was removed from its repository by the retention
+/// policy of the repository (or would have been, in dry run mode)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpiredManifest {
+	/// The repository that the manifest is in
+	pub repository_id: Uuid,
+	/// The digest of the manifest
+	pub manifest_digest: String,
+	/// When the manifest was pushed to the repository
+	pub created: OffsetDateTime,
+	/// The number of days that the policy of the repository keeps untagged
+	/// manifests for
+	pub retention_days: u32,
+}
+
+/// Runs a background task that periodically enforces the retention policies of
+/// all repositories, based on the [`ContainerRegistryConfig`][1]. In dry run
+/// mode, every tag and manifest that would have been deleted is logged, along
+/// with the reason it would have been deleted.
+///
+/// [1]: crate::utils::config::ContainerRegistryConfig
+#[instrument(skip(state))]
+pub async fn run(state: &AppState) {
+	let config = &state.config.container_registry;
+	let interval = StdDuration::from_secs(config.retention_interval_minutes * 60);
+
+	periodic_task::run_periodically(interval, || async {
+		match apply_retention_policies(state, config.retention_dry_run).await {
+			Ok(report) if config.retention_dry_run => {
+				for tag in &report.tags {
+					info!(
+						"Registry retention (dry run): tag `{}` ({}) of repository `{}` can be deleted, since it is tag #{} and only the last {} tags are kept",
+						tag.tag, tag.manifest_digest, tag.repository_id, tag.position, tag.keep_last_tags
+					);
+				}
+				for manifest in &report.manifests {
+					info!(
+						"Registry retention (dry run): untagged manifest `{}` of repository `{}` can be deleted, since it was pushed at {} and untagged manifests are kept for {} days",
+						manifest.manifest_digest, manifest.repository_id, manifest.created, manifest.retention_days
+					);
+				}
+				info!(
+					"Registry retention (dry run): {} tags and {} untagged manifests can be deleted",
+					report.tags.len(),
+					report.manifests.len()
+				);
+			}
+			Ok(report) => info!(
+				"Registry retention: deleted {} tags and {} untagged manifests",
+				report.tags.len(),
+				report.manifests.len()
+			),
+			Err(err) => error!("Error applying registry retention policies: {:?}", err),
+		}
+	})
+	.await;
+}
+
+/// Enforces the retention policy of every repository that has one.
+///
+/// Only the last N tags (by the time they were last pushed) of a repository are
+/// kept, and untagged manifests are removed from a repository once they are
+/// older than the retention period. Nothing that a deployment depends on is
+/// removed: tags used by a deployment, tags matching an immutable pattern, and
+/// digests that are live on (or in the deploy history of) a deployment are
+/// always kept. Manifests that are a part of an image index (or that refer to
+/// another manifest, like signatures) are kept as long as their index (or the
+/// manifest they refer to) is in the repository. Removed manifests are deleted
+/// from storage by the garbage collector.
+#[instrument(skip(state))]
+pub async fn apply_retention_policies(
+	state: &AppState,
+	dry_run: bool,
+) -> Result<RetentionReport, Error> {
+	let mut report = RetentionReport::default();
+
+	let policies = query!(
+		r#"
+		SELECT
+			container_registry_repository_policy.repository_id,
+			container_registry_repository_policy.immutable_tag_patterns,
+			container_registry_repository_policy.keep_last_tags,
+			container_registry_repository_policy.untagged_manifest_retention_days
+		FROM
+			container_registry_repository_policy
+		INNER JOIN
+			container_registry_repository
+		ON
+			container_registry_repository.id =
+				container_registry_repository_policy.repository_id
+		WHERE
+			container_registry_repository.deleted IS NULL;
+		"#,
+	)
+	.fetch_all(&state.database)
+	.await?;
+
+	for policy in policies {
+		let repository_id: Uuid = policy.repository_id.into();
+
+		if let Some(keep_last_tags) = policy.keep_last_tags {
+			let tags = query!(
+				r#"
+				SELECT
+					tag,
+					manifest_digest,
+					last_updated
+				FROM
+					container_registry_repository_tag
+				WHERE
+					repository_id = $1 AND
+					NOT EXISTS (
+						SELECT
+							1
+						FROM
+							deployment
+						WHERE
+							deployment.repository_id = $1 AND
+							deployment.image_tag = container_registry_repository_tag.tag AND
+							deployment.deleted IS NULL
+					) AND
+					NOT EXISTS (
+						SELECT
+							1
+						FROM
+							deployment
+						WHERE
+							deployment.repository_id = $1 AND
+							deployment.current_live_digest =
+								container_registry_repository_tag.manifest_digest AND
+							deployment.deleted IS NULL
+					) AND
+					NOT EXISTS (
+						SELECT
+							1
+						FROM
+							deployment_deploy_history
+						WHERE
+							deployment_deploy_history.repository_id = $1 AND
+							deployment_deploy_history.image_digest =
+								container_registry_repository_tag.manifest_digest
+					)
+				ORDER BY
+					last_updated DESC;
+				"#,
+				repository_id as _,
+			)
+			.fetch_all(&state.database)
+			.await?;
+
+			// Signatures are pushed to tags of their own, which aren't images
+			// and don't count towards the tags that are kept
+			let expired_tags = tags
+				.into_iter()
+				.filter(|tag| image_signature::get_signed_digest_from_tag(&tag.tag).is_none())
+				.enumerate()
+				.skip(keep_last_tags.try_into().unwrap_or_default())
+				.filter(|(_, tag)| {
+					!policy
+						.immutable_tag_patterns
+						.iter()
+						.any(|pattern| matches_tag_pattern(pattern, &tag.tag))
+				});
+
+			for (index, tag) in expired_tags {
+				if !dry_run {
+					// The tag may have been pushed again since it was listed
+					let deleted = query!(
+						r#"
+						DELETE FROM
+							container_registry_repository_tag
+						WHERE
+							repository_id = $1 AND
+							tag = $2 AND
+							manifest_digest = $3 AND
+							last_updated = $4;
+						"#,
+						repository_id as _,
+						&tag.tag,
+						&tag.manifest_digest,
+						tag.last_updated,
+					)
+					.execute(&state.database)
+					.await?
+					.rows_affected();
+
+					if deleted == 0 {
+						continue;
+					}
+				}
+
+				report.tags.push(ExpiredTag {
+					repository_id,
+					tag: tag.tag,
+					manifest_digest: tag.manifest_digest,
+					position: index + 1,
+					keep_last_tags: keep_last_tags.try_into().unwrap_or_default(),
+				});
+			}
+		}
+
+		if let Some(retention_days) = policy.untagged_manifest_retention_days {
+			let cutoff = OffsetDateTime::now_utc() - Duration::days(retention_days as i64);
+
+			let manifests = query!(
+				r#"
+				SELECT
+					manifest_digest,
+					created
+				FROM
+					container_registry_repository_manifest
+				WHERE
+					repository_id = $1 AND
+					created < $2 AND
+					NOT EXISTS (
+						SELECT
+							1
+						FROM
+							container_registry_repository_tag
+						WHERE
+							container_registry_repository_tag.repository_id = $1 AND
+							container_registry_repository_tag.manifest_digest =
+								container_registry_repository_manifest.manifest_digest
+					) AND
+					NOT EXISTS (
+						SELECT
+							1
+						FROM
+							container_registry_index_manifest
+						INNER JOIN
+							container_registry_repository_manifest AS index_manifest
+						ON
+							index_manifest.manifest_digest =
+								container_registry_index_manifest.index_digest
+						WHERE
+							index_manifest.repository_id = $1 AND
+							container_registry_index_manifest.manifest_digest =
+								container_registry_repository_manifest.manifest_digest
+					) AND
+					NOT EXISTS (
+						SELECT
+							1
+						FROM
+							container_registry_manifest
+						INNER JOIN
+							container_registry_repository_manifest AS subject_manifest
+						ON
+							subject_manifest.manifest_digest =
+								container_registry_manifest.subject_digest
+						WHERE
+							subject_manifest.repository_id = $1 AND
+							container_registry_manifest.manifest_digest =
+								container_registry_repository_manifest.manifest_digest
+					) AND
+					NOT EXISTS (
+						SELECT
+							1
+						FROM
+							deployment
+						WHERE
+							deployment.repository_id = $1 AND
+							deployment.current_live_digest =
+								container_registry_repository_manifest.manifest_digest AND
+							deployment.deleted IS NULL
+					) AND
+					NOT EXISTS (
+						SELECT
+							1
+						FROM
+							deployment_deploy_history
+						WHERE
+							deployment_deploy_history.repository_id = $1 AND
+							deployment_deploy_history.image_digest =
+								container_registry_repository_manifest.manifest_digest
+					);
+				"#,
+				repository_id as _,
+				cutoff,
+			)
+			.fetch_all(&state.database)
+			.await?;
+
+			for manifest in manifests {
+				if !dry_run {
+					// The manifest may have been tagged or pushed again since
+					// it was listed
+					let deleted = query!(
+						r#"
+						DELETE FROM
+							container_registry_repository_manifest
+						WHERE
+							repository_id = $1 AND
+							manifest_digest = $2 AND
+							created < $3 AND
+							NOT EXISTS (
+								SELECT
+									1
+								FROM
+									container_registry_repository_tag
+								WHERE
+									repository_id = $1 AND
+									manifest_digest = $2
+							);
+						"#,
+						repository_id as _,
+						&manifest.manifest_digest,
+						cutoff,
+					)
+					.execute(&state.database)
+					.await?
+					.rows_affected();
+
+					if deleted == 0 {
+						continue;
+					}
+				}
+
+				report.manifests.push(ExpiredManifest {
+					repository_id,
+					manifest_digest: manifest.manifest_digest,
+					created: manifest.created,
+					retention_days: retention_days.try_into().unwrap_or_default(),
+				});
+			}
+		}
+	}
+
+	Ok(report)
+}
+
+/// Checks if the given tag of a repository matches one of the immutable tag
+/// patterns of the repository. An immutable tag can't be pointed to a
+/// different manifest once it is pushed, and can't be deleted.
+pub(super) async fn is_tag_immutable(
+	connection: &mut DatabaseConnection,
+	repository_id: Uuid,
+	tag: &str,
+) -> Result<bool, Error> {
+	let patterns = query!(
+		r#"
+		SELECT
+			immutable_tag_patterns
+		FROM
+			container_registry_repository_policy
+		WHERE
+			repository_id = $1;
+		"#,
+		repository_id as _,
+	)
+	.fetch_optional(&mut *connection)
+	.await?
+	.map(|row| row.immutable_tag_patterns)
+	.unwrap_or_default();
+
+	Ok(patterns
+		.iter()
+		.any(|pattern| matches_tag_pattern(pattern, tag)))
+}
+
+/// Matches a tag against a glob-like pattern, where `*` matches any number of
+/// characters and `?` matches exactly one character (eg: `v*` matches
+/// `v1.2.3`, and `release-?` matches `release-1`).
+pub fn matches_tag_pattern(pattern: &str, tag: &str) -> bool {
+	let pattern = pattern.as_bytes();
+	let tag = tag.as_bytes();
+
+	let (mut pattern_index, mut tag_index) = (0, 0);
+	// The position of the last `*` in the pattern, and the position in the tag
+	// that it was matched up to, to backtrack to if the rest doesn't match
+	let mut backtrack = None;
+
+	while tag_index < tag.len() {
+		match pattern.get(pattern_index) {
+			Some(b'*') => {
+				backtrack = Some((pattern_index, tag_index));
+				pattern_index += 1;
+			}
+			Some(&character) if character == b'?' || character == tag[tag_index] => {
+				pattern_index += 1;
+				tag_index += 1;
+			}
+			_ => {
+				let Some((star_index, matched_index)) = backtrack else {
+					return false;
+				};
+				backtrack = Some((star_index, matched_index + 1));
+				pattern_index = star_index + 1;
+				tag_index = matched_index + 1;
+			}
+		}
+	}
+
+	pattern[pattern_index..]
+		.iter()
+		.all(|character| *character == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{apply_retention_policies, matches_tag_pattern};
+	use crate::{prelude::*, utils::test_utils};
+
+	#[test]
+	fn assert_tag_pattern_matches() {
+		assert!(matches_tag_pattern("v*", "v1.2.3"));
+		assert!(matches_tag_pattern("*", "latest"));
+		assert!(matches_tag_pattern("latest", "latest"));
+		assert!(matches_tag_pattern("release-?", "release-1"));
+		assert!(matches_tag_pattern("*-stable", "1.2-stable"));
+		assert!(matches_tag_pattern("v*.*.*", "v10.2.3"));
+		assert!(matches_tag_pattern("v1*", "v1"));
+
+		assert!(!matches_tag_pattern("v*", "latest"));
+		assert!(!matches_tag_pattern("release-?", "release-10"));
+		assert!(!matches_tag_pattern("*-stable", "1.2-stable-rc"));
+		assert!(!matches_tag_pattern("v*.*.*", "v1.2"));
+		assert!(!matches_tag_pattern("", "latest"));
+	}
+
+	/// Gets the tags of a repository, ordered by their name
+	async fn get_tags(state: &AppState, repository_id: Uuid) -> Vec<String> {
+		query!(
+			r#"
+			SELECT
+				tag
+			FROM
+				container_registry_repository_tag
+			WHERE
+				repository_id = $1
+			ORDER BY
+				tag;
+			"#,
+			repository_id as _,
+		)
+		.fetch_all(&state.database)
+		.await
+		.unwrap()
+		.into_iter()
+		.map(|row| row.tag)
+		.collect()
+	}
+
+	#[tokio::test]
+	#[ignore = "requires a database and Redis instance"]
+	async fn assert_expired_tags_are_reported() {
+		let state = test_utils::setup_state().await;
+		let workspace = test_utils::create_workspace(&state).await;
+		let repository_id =
+			test_utils::create_repository(&state, workspace.workspace_id, "app").await;
+
+		// The tags are pushed from the oldest to the newest
+		for (tag, digest) in [
+			(
+				"v1",
+				"sha256:0000000000000000000000000000000000000000000000000000000000000001",
+			),
+			(
+				"v2",
+				"sha256:0000000000000000000000000000000000000000000000000000000000000002",
+			),
+			(
+				"pinned",
+				"sha256:0000000000000000000000000000000000000000000000000000000000000003",
+			),
+			(
+				"v3",
+				"sha256:0000000000000000000000000000000000000000000000000000000000000004",
+			),
+		] {
+			test_utils::create_tag(&state, repository_id, tag, digest).await;
+		}
+
+		query!(
+			r#"
+			INSERT INTO
+				container_registry_repository_policy(
+					repository_id,
+					immutable_tag_patterns,
+					keep_last_tags,
+					untagged_manifest_retention_days,
+					storage_limit
+				)
+			VALUES
+				($1, ARRAY['pinned'], 1, NULL, NULL);
+			"#,
+			repository_id as _,
+		)
+		.execute(&state.database)
+		.await
+		.unwrap();
+
+		// A dry run lists the tags without deleting them
+		let report = apply_retention_policies(&state, true).await.unwrap();
+		let expired = report
+			.tags
+			.iter()
+			.filter(|tag| tag.repository_id == repository_id)
+			.map(|tag| (tag.tag.as_str(), tag.position, tag.keep_last_tags))
+			.collect::<Vec<_>>();
+		assert_eq!(expired, [("v2", 3, 1), ("v1", 4, 1)]);
+		assert_eq!(
+			get_tags(&state, repository_id).await,
+			["pinned", "v1", "v2", "v3"]
+		);
+
+		let report = apply_retention_policies(&state, false).await.unwrap();
+		assert_eq!(
+			report
+				.tags
+				.iter()
+				.filter(|tag| tag.repository_id == repository_id)
+				.count(),
+			2
+		);
+		assert_eq!(get_tags(&state, repository_id).await, ["pinned", "v3"]);
+	}
+}
@@ -0,0 +1,229 @@
+use axum::{
+	body::Body,
+	extract::{Path, Query, State},
+	http::{HeaderMap, StatusCode},
+	response::IntoResponse,
+	Extension,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+	quota,
+	upload_session::{self, UploadSession},
+	Error,
+	RegistryError,
+};
+use crate::{
+	models::registry_token::{RegistryAction, RegistryToken},
+	prelude::*,
+};
+
+/// The parameters that are passed in the path of the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathParams {
+	/// The workspace ID of the repository
+	workspace_id: Uuid,
+	/// The name of the repository
+	repo_name: String,
+}
+
+/// The parameters that are passed in the query of the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryParams {
+	/// The digest of the blob, if the entire blob is being uploaded in this
+	/// request (a monolithic upload)
+	digest: Option<String>,
+	/// The digest of a blob to mount from another repository, instead of
+	/// uploading it again
+	mount: Option<String>,
+	/// The repository to mount the blob from, in the format
+	/// `<workspace ID>/<repository name>`
+	from: Option<String>,
+}
+
+/// Handles the `POST /v2/{workspace_id}/{repo_name}/blobs/uploads/` route.
+///
+/// If a `digest` is given in the query, the body of the request is the entire
+/// blob, and the blob is stored right away (if it fits within the storage
+/// limits of the repository and its workspace). Otherwise, an upload session is
+/// started and the client uploads the blob in chunks to the session.
+///
+/// If a `mount` digest and a `from` repository are given, and the blob can be
+/// used by that repository (that the client can pull from), the blob is
+/// mounted into this repository without being uploaded again. Blobs are stored
+/// once for the entire registry, so a mount only links the blob to this
+/// repository. If the blob can't be mounted, an upload session is started
+/// instead, as required by the distribution spec.
+#[axum::debug_handler]
+pub(super) async fn handle(
+	Path(path): Path<PathParams>,
+	Query(query): Query<QueryParams>,
+	State(state): State<AppState>,
+	Extension(token): Extension<RegistryToken>,
+	headers: HeaderMap,
+	body: Body,
+) -> Result<impl IntoResponse, Error> {
+	let mut database = state.database.begin().await?;
+	let repository_id =
+		super::get_repository_id(&mut database, path.workspace_id, &path.repo_name).await?;
+
+	if let Some(digest) = query.mount {
+		if !super::is_valid_digest(&digest) {
+			return Err(Error::new(
+				RegistryError::DigestInvalid,
+				StatusCode::BAD_REQUEST,
+				"Invalid digest",
+			));
+		}
+
+		let source = query
+			.from
+			.filter(|from| token.has_access("repository", from, RegistryAction::Pull));
+		if let Some(source) = source {
+			if mount_blob(&mut database, repository_id, &source, &digest).await? {
+				database.commit().await?;
+
+				return Ok((
+					StatusCode::CREATED,
+					upload_session::uploaded_blob_headers(
+						path.workspace_id,
+						&path.repo_name,
+						&digest,
+					)?,
+				));
+			}
+		}
+	}
+
+	let storage = super::get_storage(&state.config.storage)?;
+	let mut session = UploadSession::new(repository_id);
+
+	let Some(digest) = query.digest else {
+		session.save(&state.redis).await?;
+		return Ok((
+			StatusCode::ACCEPTED,
+			session.headers(path.workspace_id, &path.repo_name)?,
+		));
+	};
+
+	if !super::is_valid_digest(&digest) {
+		return Err(Error::new(
+			RegistryError::DigestInvalid,
+			StatusCode::BAD_REQUEST,
+			"Invalid digest",
+		));
+	}
+
+	quota::check_chunk_upload(
+		&mut database,
+		&state.config.container_registry,
+		path.workspace_id,
+		repository_id,
+		session.size,
+		&headers,
+	)
+	.await?;
+	session.push_chunk(&*storage, body).await?;
+	if let Err(err) = quota::check_blob_upload(
+		&mut database,
+		&state.config.container_registry,
+		path.workspace_id,
+		repository_id,
+		session.size,
+	)
+	.await
+	{
+		session.discard(&state.redis, &*storage).await?;
+		return Err(err);
+	}
+	let size = session.finish(&state.redis, &*storage, &digest).await?;
+	upload_session::register_blob(&mut database, &digest, size).await?;
+	upload_session::link_blob(&mut database, repository_id, &digest).await?;
+
+	database.commit().await?;
+
+	Ok((
+		StatusCode::CREATED,
+		upload_session::uploaded_blob_headers(path.workspace_id, &path.repo_name, &digest)?,
+	))
+}
+
+/// Mounts a blob from the given repository (in the format
+/// `<workspace ID>/<repository name>`) into the repository with the given ID.
+/// Returns `false` if the source repository doesn't exist, or if it can't use
+/// the blob itself.
+async fn mount_blob(
+	connection: &mut DatabaseConnection,
+	repository_id: Uuid,
+	source: &str,
+	digest: &str,
+) -> Result<bool, Error> {
+	let Some((workspace_id, repo_name)) = parse_repository(source) else {
+		return Ok(false);
+	};
+	let Ok(source_id) = super::get_repository_id(connection, workspace_id, repo_name).await else {
+		return Ok(false);
+	};
+
+	let digests = [digest.to_string()];
+	if super::count_repository_blobs(connection, source_id, &digests).await? == 0 {
+		return Ok(false);
+	}
+
+	// The blob is marked as recently uploaded, so that the garbage collector
+	// doesn't remove it before a manifest referencing it is pushed
+	query!(
+		r#"
+		UPDATE
+			container_registry_repository_blob
+		SET
+			created = NOW()
+		WHERE
+			blob_digest = $1;
+		"#,
+		digest,
+	)
+	.execute(&mut *connection)
+	.await?;
+	upload_session::link_blob(connection, repository_id, digest).await?;
+
+	Ok(true)
+}
+
+/// Parses a repository in the format `<workspace ID>/<repository name>` into
+/// the workspace ID and the name of the repository
+fn parse_repository(repository: &str) -> Option<(Uuid, &str)> {
+	let (workspace_id, repo_name) = repository.split_once('/')?;
+	let workspace_id = Uuid::parse_str(workspace_id).ok()?;
+
+	Some((workspace_id, repo_name))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::parse_repository;
+	use crate::prelude::*;
+
+	#[test]
+	fn assert_repository_is_parsed() {
+		let workspace_id = Uuid::new_v4();
+
+		assert_eq!(
+			parse_repository(&format!("{}/app", workspace_id)),
+			Some((workspace_id, "app"))
+		);
+		assert_eq!(
+			parse_repository(&format!("{}/team/app", workspace_id)),
+			Some((workspace_id, "team/app"))
+		);
+	}
+
+	#[test]
+	fn assert_invalid_repository_is_rejected() {
+		assert_eq!(parse_repository("app"), None);
+		assert_eq!(parse_repository("not-a-workspace/app"), None);
+		assert_eq!(parse_repository(""), None);
+	}
+}
@@ -0,0 +1,98 @@
+use axum::{
+	body::Body,
+	extract::{Path, State},
+	http::{HeaderMap, StatusCode},
+	response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+	quota,
+	upload_session::{self, UploadSession, UploadSessionLock},
+	Error,
+	RegistryError,
+};
+use crate::prelude::*;
+
+/// The parameters that are passed in the path of the request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathParams {
+	/// The workspace ID of the repository
+	workspace_id: Uuid,
+	/// The name of the repository
+	repo_name: String,
+	/// The ID of the upload session
+	upload_id: Uuid,
+}
+
+/// Handles the `PATCH /v2/{workspace_id}/{repo_name}/blobs/uploads/{upload_id}`
+/// route.
+///
+/// Chunks must be uploaded in order. If the request has a `Content-Range`
+/// header, the chunk must start right after the data uploaded so far. If the
+/// data uploaded so far exceeds the storage limits of the repository (or its
+/// workspace), the upload is discarded.
+#[axum::debug_handler]
+pub(super) async fn handle(
+	Path(path): Path<PathParams>,
+	State(state): State<AppState>,
+	headers: HeaderMap,
+	body: Body,
+) -> Result<impl IntoResponse, Error> {
+	let mut database = state.database.begin().await?;
+	let repository_id =
+		super::get_repository_id(&mut database, path.workspace_id, &path.repo_name).await?;
+
+	let _lock = UploadSessionLock::acquire(&state.redis, path.upload_id).await?;
+	let mut session = UploadSession::load(&state.redis, path.upload_id, repository_id).await?;
+
+	if let Some(content_range) = headers.get(axum::http::header::CONTENT_RANGE) {
+		let range = content_range
+			.to_str()
+			.ok()
+			.and_then(upload_session::parse_content_range);
+		if !matches!(range, Some((start, _)) if start == session.size) {
+			return Err(Error::new(
+				RegistryError::BlobUploadInvalid,
+				StatusCode::RANGE_NOT_SATISFIABLE,
+				"The chunk does not start at the end of the data uploaded so far",
+			));
+		}
+	}
+
+	let storage = super::get_storage(&state.config.storage)?;
+	if let Err(err) = quota::check_chunk_upload(
+		&mut database,
+		&state.config.container_registry,
+		path.workspace_id,
+		repository_id,
+		session.size,
+		&headers,
+	)
+	.await
+	{
+		session.discard(&state.redis, &*storage).await?;
+		return Err(err);
+	}
+
+	session.push_chunk(&*storage, body).await?;
+	if let Err(err) = quota::check_blob_upload(
+		&mut database,
+		&state.config.container_registry,
+		path.workspace_id,
+		repository_id,
+		session.size,
+	)
+	.await
+	{
+		session.discard(&state.redis, &*storage).await?;
+		return Err(err);
+	}
+	session.save(&state.redis).await?;
+
+	Ok((
+		StatusCode::ACCEPTED,
+		session.headers(path.workspace_id, &path.repo_name)?,
+	))
+}
@@ -0,0 +1,416 @@
+use std::{
+	io,
+	sync::atomic::{AtomicBool, Ordering},
+};
+
+use axum::{
+	body::Body,
+	http::{HeaderMap, HeaderValue, StatusCode},
+};
+use futures::{future, stream, StreamExt, TryStreamExt};
+use rustis::{
+	client::Client as RedisClient,
+	commands::{GenericCommands, SetCondition, SetExpiration, StringCommands},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use time::Duration;
+
+use super::{
+	Error,
+	RegistryError,
+	DOCKER_CONTENT_DIGEST,
+	DOCKER_DISTRIBUTION_API_VERSION,
+	DOCKER_UPLOAD_UUID,
+};
+use crate::{prelude::*, utils::storage::Storage};
+
+/// How long an upload session is valid for without any activity. After this
+/// duration, the session expires and the client has to start the upload again.
+const UPLOAD_SESSION_VALIDITY: Duration = Duration::days(1);
+
+/// The longest an upload session can stay locked by a request, in case the
+/// request never releases the lock.
+const UPLOAD_SESSION_LOCK_VALIDITY: Duration = Duration::hours(1);
+
+/// The most data that can be uploaded in a single chunk, in bytes.
+pub const MAX_CHUNK_SIZE: u64 = 10 * 1024 * 1024 * 1024;
+
+/// An ongoing upload of a blob to a repository. The session is stored in Redis
+/// and each chunk of data uploaded in the session is stored as a separate
+/// object in the storage, until the upload is completed and the chunks are
+/// joined into the blob.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadSession {
+	/// The ID of the upload
+	#[serde(skip)]
+	pub id: Uuid,
+	/// The repository that the blob is being uploaded to
+	pub repository_id: Uuid,
+	/// The number of bytes uploaded so far
+	pub size: u64,
+	/// The number of chunks uploaded so far
+	pub chunks: u32,
+}
+
+impl UploadSession {
+	/// Create a new upload session for the given repository. The session is not
+	/// stored until [`Self::save`] is called.
+	pub fn new(repository_id: Uuid) -> Self {
+		Self {
+			id: Uuid::new_v4(),
+			repository_id,
+			size: 0,
+			chunks: 0,
+		}
+	}
+
+	/// Load an upload session of the given repository. Returns a
+	/// `BLOB_UPLOAD_UNKNOWN` error if the session does not exist, has expired,
+	/// or belongs to a different repository.
+	pub async fn load(
+		redis: &RedisClient,
+		upload_id: Uuid,
+		repository_id: Uuid,
+	) -> Result<Self, Error> {
+		redis
+			.get::<_, Option<String>>(redis::keys::registry_upload_session(&upload_id))
+			.await?
+			.map(|session| serde_json::from_str::<Self>(&session))
+			.transpose()?
+			.filter(|session| session.repository_id == repository_id)
+			.map(|session| Self {
+				id: upload_id,
+				..session
+			})
+			.ok_or_else(|| {
+				Error::new(
+					RegistryError::BlobUploadUnknown,
+					StatusCode::NOT_FOUND,
+					"Upload not found",
+				)
+			})
+	}
+
+	/// Store the upload session, resetting its expiry.
+	pub async fn save(&self, redis: &RedisClient) -> Result<(), Error> {
+		redis
+			.setex(
+				redis::keys::registry_upload_session(&self.id),
+				UPLOAD_SESSION_VALIDITY.unsigned_abs().as_secs(),
+				serde_json::to_string(self)?,
+			)
+			.await?;
+		Ok(())
+	}
+
+	/// The value of the `Range` header for this session, which is the
+	/// inclusive range of bytes uploaded so far.
+	pub fn range(&self) -> String {
+		format!("0-{}", self.size.saturating_sub(1))
+	}
+
+	/// The headers that are sent with every response about this session.
+	pub fn headers(&self, workspace_id: Uuid, repo_name: &str) -> Result<HeaderMap, Error> {
+		Ok(HeaderMap::from_iter([
+			(
+				DOCKER_DISTRIBUTION_API_VERSION.clone(),
+				HeaderValue::from_static("registry/2.0"),
+			),
+			(
+				axum::http::header::LOCATION,
+				HeaderValue::from_str(&format!(
+					"/v2/{}/{}/blobs/uploads/{}",
+					workspace_id, repo_name, self.id
+				))?,
+			),
+			(
+				axum::http::header::RANGE,
+				HeaderValue::from_str(&self.range())?,
+			),
+			(
+				DOCKER_UPLOAD_UUID.clone(),
+				HeaderValue::from_str(&self.id.to_string())?,
+			),
+		]))
+	}
+
+	/// Upload a chunk of data to the session, streaming the body of the
+	/// request to the storage. Returns the number of bytes in the chunk. A
+	/// chunk larger than [`MAX_CHUNK_SIZE`] is rejected with a `SIZE_INVALID`
+	/// error, and nothing of it is kept.
+	pub async fn push_chunk(&mut self, storage: &dyn Storage, body: Body) -> Result<u64, Error> {
+		let key = get_object_name_for_chunk(&self.id, self.chunks);
+
+		// The size of the chunk is counted as it's streamed, since the client
+		// doesn't have to send it upfront
+		let too_large = AtomicBool::new(false);
+		let mut remaining = MAX_CHUNK_SIZE;
+		let data = body
+			.into_data_stream()
+			.map_err(io::Error::other)
+			.and_then(|bytes| {
+				future::ready(match remaining.checked_sub(bytes.len() as u64) {
+					Some(left) => {
+						remaining = left;
+						Ok(bytes)
+					}
+					None => {
+						too_large.store(true, Ordering::Relaxed);
+						Err(io::Error::other("The chunk is too large"))
+					}
+				})
+			})
+			.boxed();
+
+		let size = match storage.put_object_stream(&key, data).await {
+			Ok(size) => size,
+			Err(_) if too_large.load(Ordering::Relaxed) => {
+				storage.delete_object(&key).await?;
+				return Err(Error::new(
+					RegistryError::SizeInvalid,
+					StatusCode::PAYLOAD_TOO_LARGE,
+					format!("A chunk can't be larger than {} bytes", MAX_CHUNK_SIZE),
+				));
+			}
+			Err(err) => return Err(err.into()),
+		};
+
+		if size == 0 {
+			// Nothing was uploaded, so there's no need to keep the chunk
+			storage.delete_object(&key).await?;
+		} else {
+			self.size += size;
+			self.chunks += 1;
+		}
+
+		Ok(size)
+	}
+
+	/// Finish the upload, joining all the chunks into a single blob. The blob
+	/// is only stored if the digest of the uploaded data matches the given
+	/// digest, and a `DIGEST_INVALID` error is returned otherwise. Either way,
+	/// the session and its chunks are removed.
+	pub async fn finish(
+		self,
+		redis: &RedisClient,
+		storage: &dyn Storage,
+		digest: &str,
+	) -> Result<u64, Error> {
+		let upload_key = format!("{}{}/blob", super::UPLOADS_PREFIX, self.id);
+
+		// The chunks are hashed as they are being joined, so that the data
+		// doesn't have to be read again to verify the digest
+		let mut hasher = Sha256::new();
+		let upload_id = self.id;
+		let uploaded = {
+			let chunks = stream::iter(0..self.chunks)
+				.then(|index| async move {
+					storage
+						.get_object_stream(&get_object_name_for_chunk(&upload_id, index))
+						.await
+				})
+				.map_err(io::Error::other)
+				.try_flatten()
+				.inspect_ok(|bytes| hasher.update(bytes))
+				.boxed();
+			storage.put_object_stream(&upload_key, chunks).await
+		};
+		let uploaded_digest = format!("sha256:{}", hex::encode(hasher.finalize()));
+
+		let result = match uploaded {
+			Ok(_) if uploaded_digest == digest => storage
+				.copy_object(&upload_key, &super::get_object_name_for_blob(digest))
+				.await
+				.map(|_| self.size)
+				.map_err(Error::from),
+			Ok(_) => Err(Error::new(
+				RegistryError::DigestInvalid,
+				StatusCode::BAD_REQUEST,
+				"The digest of the uploaded data does not match the digest provided",
+			)),
+			Err(err) => Err(err.into()),
+		};
+
+		storage.delete_object(&upload_key).await?;
+		self.discard(redis, storage).await?;
+
+		result
+	}
+
+	/// Discard the session, deleting all the chunks uploaded so far.
+	pub async fn discard(self, redis: &RedisClient, storage: &dyn Storage) -> Result<(), Error> {
+		for index in 0..self.chunks {
+			storage
+				.delete_object(&get_object_name_for_chunk(&self.id, index))
+				.await?;
+		}
+		redis
+			.del(redis::keys::registry_upload_session(&self.id))
+			.await?;
+		Ok(())
+	}
+}
+
+/// A lock on an upload session, held while a chunk is being uploaded to it (or
+/// while it's being finished), so that concurrent requests to the same session
+/// can't both append to it. The lock is released when it is dropped.
+pub struct UploadSessionLock {
+	/// The Redis client used to release the lock
+	redis: RedisClient,
+	/// The ID of the upload that is locked
+	upload_id: Uuid,
+}
+
+impl UploadSessionLock {
+	/// Locks the upload session with the given ID. Returns a
+	/// `BLOB_UPLOAD_INVALID` error if another request holds the lock.
+	pub async fn acquire(redis: &RedisClient, upload_id: Uuid) -> Result<Self, Error> {
+		let acquired = redis
+			.set_with_options(
+				redis::keys::registry_upload_session_lock(&upload_id),
+				"locked",
+				SetCondition::NX,
+				SetExpiration::Ex(UPLOAD_SESSION_LOCK_VALIDITY.unsigned_abs().as_secs()),
+				false,
+			)
+			.await?;
+		if !acquired {
+			return Err(Error::new(
+				RegistryError::BlobUploadInvalid,
+				StatusCode::CONFLICT,
+				"Another request is already uploading to this session",
+			));
+		}
+
+		Ok(Self {
+			redis: redis.clone(),
+			upload_id,
+		})
+	}
+}
+
+impl Drop for UploadSessionLock {
+	fn drop(&mut self) {
+		let redis = self.redis.clone();
+		let key = redis::keys::registry_upload_session_lock(&self.upload_id);
+		tokio::spawn(async move {
+			_ = redis
+				.del(&key)
+				.await
+				.inspect_err(|err| error!("Error releasing upload session lock: {:?}", err));
+		});
+	}
+}
+
+/// Store a blob that has been uploaded in the database, so that it can be
+/// referenced by manifests. Uploading a blob that already exists is a no-op.
+pub async fn register_blob(
+	connection: &mut DatabaseConnection,
+	digest: &str,
+	size: u64,
+) -> Result<(), Error> {
+	query!(
+		r#"
+		INSERT INTO
+			container_registry_repository_blob(
+				blob_digest,
+				created,
+				size
+			)
+		VALUES
+			($1, NOW(), $2)
+		ON CONFLICT(blob_digest) DO UPDATE SET
+			created = EXCLUDED.created;
+		"#,
+		digest,
+		i64::try_from(size).unwrap_or(i64::MAX),
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
+
+/// Links a blob to a repository, so that the repository can use it in its
+/// manifests and clients that can pull from the repository can download it.
+pub async fn link_blob(
+	connection: &mut DatabaseConnection,
+	repository_id: Uuid,
+	digest: &str,
+) -> Result<(), Error> {
+	query!(
+		r#"
+		INSERT INTO
+			container_registry_repository_blob_link(
+				repository_id,
+				blob_digest,
+				created
+			)
+		VALUES
+			($1, $2, NOW())
+		ON CONFLICT(repository_id, blob_digest) DO UPDATE SET
+			created = EXCLUDED.created;
+		"#,
+		repository_id as _,
+		digest,
+	)
+	.execute(&mut *connection)
+	.await?;
+
+	Ok(())
+}
+
+/// The headers that are sent in the response once a blob has been uploaded,
+/// pointing to the location of the blob.
+pub fn uploaded_blob_headers(
+	workspace_id: Uuid,
+	repo_name: &str,
+	digest: &str,
+) -> Result<HeaderMap, Error> {
+	Ok(HeaderMap::from_iter([
+		(
+			DOCKER_DISTRIBUTION_API_VERSION.clone(),
+			HeaderValue::from_static("registry/2.0"),
+		),
+		(
+			axum::http::header::LOCATION,
+			HeaderValue::from_str(&format!(
+				"/v2/{}/{}/blobs/{}",
+				workspace_id, repo_name, digest
+			))?,
+		),
+		(
+			DOCKER_CONTENT_DIGEST.clone(),
+			HeaderValue::from_str(digest)?,
+		),
+	]))
+}
+
+/// Parses the value of a `Content-Range` header of a chunk upload, in the
+/// format `<start>-<end>`, returning the inclusive range of bytes.
+pub fn parse_content_range(value: &str) -> Option<(u64, u64)> {
+	let (start, end) = value.trim().split_once('-')?;
+	let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+	(start <= end).then_some((start, end))
+}
+
+/// Get the object name for a chunk of an upload.
+fn get_object_name_for_chunk(upload_id: &Uuid, index: u32) -> String {
+	format!("{}{upload_id}/{index}", super::UPLOADS_PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::parse_content_range;
+
+	#[test]
+	fn assert_content_range_parse() {
+		assert_eq!(parse_content_range("0-1023"), Some((0, 1023)));
+		assert_eq!(parse_content_range("1024-1024"), Some((1024, 1024)));
+		assert_eq!(parse_content_range("1024-0"), None);
+		assert_eq!(parse_content_range("bytes=0-1023"), None);
+		assert_eq!(parse_content_range("0-"), None);
+	}
+}
@@ -1,7 +1,9 @@
 use std::{
+	collections::BTreeMap,
 	env,
 	fmt::{Display, Formatter},
 	net::SocketAddr,
+	path::PathBuf,
 };
 
 use config::{Config, Environment, File};
@@ -60,8 +62,9 @@ pub struct AppConfig {
 	/// based on an environment variable and if the application is compiled with
 	/// debug mode.
 	pub environment: RunningEnvironment,
-	/// The configuration for S3, used for storing layers of docker images
-	pub s3: S3Config,
+	/// The storage backend, used for storing layers of docker images and the
+	/// files of static sites
+	pub storage: StorageConfig,
 	/// The configuration for the database to connect to
 	pub database: DatabaseConfig,
 	/// The configuration for Redis. This is used for caching, rate limiting and
@@ -74,6 +77,9 @@ pub struct AppConfig {
 	pub opentelemetry: OpenTelemetryConfig,
 	/// The configuration for IpInfo to get IpAddress details
 	pub ipinfo: IpInfoConfig,
+	/// The configuration for the container registry
+	#[serde(alias = "containerregistry")]
+	pub container_registry: ContainerRegistryConfig,
 }
 
 /// The environment the application is running in
@@ -99,6 +105,18 @@ impl Display for RunningEnvironment {
 	}
 }
 
+/// The storage backend that objects and large files used by the API will be
+/// stored in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum StorageConfig {
+	/// Store objects in an S3 compatible bucket
+	S3(S3Config),
+	/// Store objects in a directory on the local filesystem. This is meant for
+	/// self-hosted setups and test environments
+	Filesystem(FilesystemStorageConfig),
+}
+
 /// The configuration for S3, where objects and large files used by the API will
 /// be stored in
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -116,6 +134,14 @@ pub struct S3Config {
 	pub secret: String,
 }
 
+/// The configuration for storing objects on the local filesystem
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilesystemStorageConfig {
+	/// The directory to store objects in
+	pub path: PathBuf,
+}
+
 /// The configuration for the database to connect to. This will be the primary
 /// data store for all information contained in the API
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -238,3 +264,57 @@ pub struct IpInfoConfig {
 	/// The token for connecting to ipinfo.io
 	pub token: String,
 }
+
+/// The configuration for the container registry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerRegistryConfig {
+	/// The number of minutes between each run of the garbage collector
+	#[serde(alias = "gcintervalminutes")]
+	pub gc_interval_minutes: u64,
+	/// The number of minutes a blob, manifest or upload must be left
+	/// unreferenced for before the garbage collector deletes it. This makes
+	/// sure that images that are in the middle of being pushed aren't deleted
+	#[serde(alias = "gcgraceperiodminutes")]
+	pub gc_grace_period_minutes: u64,
+	/// If set, the garbage collector only reports the space that can be
+	/// reclaimed, without deleting anything
+	#[serde(alias = "gcdryrun")]
+	pub gc_dry_run: bool,
+	/// The number of minutes between each run of the retention policies of
+	/// repositories
+	#[serde(alias = "retentionintervalminutes")]
+	pub retention_interval_minutes: u64,
+	/// If set, the retention policies only report the tags and manifests that
+	/// would be deleted, without deleting anything
+	#[serde(alias = "retentiondryrun")]
+	pub retention_dry_run: bool,
+	/// The maximum storage (in bytes) that the repositories of a workspace can
+	/// use in total, if any. Blobs shared between repositories are only
+	/// counted once
+	#[serde(default, alias = "workspacestoragelimit")]
+	pub workspace_storage_limit: Option<u64>,
+	/// The upstream registries that workspaces can pull images through, keyed
+	/// by the name used in the path of the cache (eg: `docker.io`)
+	#[serde(default)]
+	pub upstreams: BTreeMap<String, ContainerRegistryUpstreamConfig>,
+}
+
+/// The configuration for an upstream registry that the container registry
+/// caches images from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerRegistryUpstreamConfig {
+	/// The URL of the upstream registry (eg: `https://registry-1.docker.io`)
+	pub url: String,
+	/// The username to authenticate with the upstream registry, if any
+	#[serde(default)]
+	pub username: Option<String>,
+	/// The password to authenticate with the upstream registry, if any
+	#[serde(default)]
+	pub password: Option<String>,
+	/// The number of minutes a cached tag is used for before it is checked
+	/// against the upstream registry again
+	#[serde(alias = "tagttlminutes")]
+	pub tag_ttl_minutes: u64,
+}
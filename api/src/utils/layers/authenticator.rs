@@ -2,6 +2,7 @@ use std::{
 	collections::{BTreeMap, BTreeSet},
 	future::Future,
 	marker::PhantomData,
+	net::IpAddr,
 	ops::Sub,
 	task::{Context, Poll},
 };
@@ -24,6 +25,7 @@ use tower::{Layer, Service};
 use crate::{
 	models::{access_token_data::AccessTokenData, redis::UserPermissionCache},
 	prelude::*,
+	utils::config::AppConfig,
 };
 
 /// The type of client used for a request. This is used to determine
@@ -146,154 +148,14 @@ where
 
 			let user_data = match client_type {
 				ClientType::ApiToken => {
-					trace!("Parsing authentication header as an API token");
-					let (refresh_token, login_id) = token
-						.strip_prefix("patrv1.")
-						.ok_or_else(|| {
-							warn!("Invalid API token provided: {}", token);
-							ErrorType::MalformedApiToken
-						})?
-						.split_once('.')
-						.ok_or_else(|| {
-							warn!("Invalid API token provided: {}", token);
-							ErrorType::MalformedApiToken
-						})?;
-
-					let refresh_token = Uuid::parse_str(refresh_token).map_err(|err| {
-						warn!("Invalid API token provided: {}", token);
-						warn!(
-							"Cannot parse refresh token `{}` as UUID: {}",
-							refresh_token, err
-						);
-						ErrorType::MalformedApiToken
-					})?;
-					trace!("Refresh token parsed as UUID");
-
-					let login_id = Uuid::parse_str(login_id).map_err(|err| {
-						warn!("Invalid API token provided: {}", token);
-						warn!("Cannot parse loginId `{}` as UUID: {}", login_id, err);
-						ErrorType::MalformedApiToken
-					})?;
-					trace!("Login ID parsed as UUID");
-
-					info!("Extracting information about API token");
-					let Some(token) = query!(
-						r#"
-						SELECT
-							user_api_token.token_id,
-							user_api_token.user_id,
-							user_api_token.token_hash,
-							user_api_token.token_nbf,
-							user_api_token.token_exp,
-							user_api_token.allowed_ips,
-							user_api_token.revoked,
-							"user".*
-						FROM
-							user_api_token
-						INNER JOIN
-							user_login
-						ON
-							user_api_token.token_id = user_login.login_id
-						INNER JOIN
-							"user"
-						ON
-							user_api_token.user_id = "user".id
-						WHERE
-							user_api_token.token_id = $1 AND
-							user_login.login_type = 'api_token';
-						"#,
-						login_id as _
-					)
-					.fetch_optional(&mut **req.database) // What the actual fuck?
-					.await?
-					else {
-						warn!("API token not found");
-						// No specific error for API token not found, since we don't want to leak
-						// information about whether a loginId is valid or if it's expired
-						return Err(ErrorType::AuthorizationTokenInvalid);
-					};
-					trace!("Token extracted from database");
-
-					if let Some(nbf) = token.token_nbf {
-						trace!("Token has an NBF");
-						if OffsetDateTime::now_utc() < nbf {
-							info!("API token is not valid yet");
-							return Err(ErrorType::AuthorizationTokenInvalid);
-						}
-					} else {
-						trace!("Token does not have an NBF");
-					}
-					trace!("Token passed NBF check");
-
-					if let Some(exp) = token.token_exp {
-						trace!("Token has an EXP");
-						if OffsetDateTime::now_utc() > exp {
-							info!("API token has expired");
-							return Err(ErrorType::AuthorizationTokenInvalid);
-						}
-					} else {
-						trace!("Token does not have an EXP");
-					}
-					trace!("Token passed EXP check");
-
-					if let Some(revoked) = token.revoked {
-						trace!("Token has a revoked timestamp");
-						if OffsetDateTime::now_utc() > revoked {
-							info!("API token has been revoked");
-							return Err(ErrorType::AuthorizationTokenInvalid);
-						}
-					} else {
-						trace!("Token does not have a revoked timestamp");
-					}
-					trace!("Token passed revoked timestamp check");
-
-					if let Some(allowed_ips) = token.allowed_ips {
-						if !allowed_ips
-							.iter()
-							.any(|ip_network| ip_network.contains(req.client_ip))
-						{
-							info!("API token not accessed from an allowed IP Address");
-							return Err(ErrorType::DisallowedIpAddressForApiToken);
-						}
-					}
-
-					let Ok(password_hash) = PasswordHash::new(&token.token_hash) else {
-						error!("Unable to parse password hash: {}", token.token_hash);
-						return Err(ErrorType::server_error("password hash parsing failed"));
-					};
-					let success = Argon2::new_with_secret(
-						req.config.password_pepper.as_bytes(),
-						Algorithm::Argon2id,
-						Version::V0x13,
-						constants::HASHING_PARAMS,
-					)
-					.map_err(ErrorType::server_error)?
-					.verify_password(refresh_token.as_bytes(), &password_hash)
-					.is_ok();
-
-					if !success {
-						warn!("API token has invalid refresh token");
-						return Err(ErrorType::AuthorizationTokenInvalid);
-					}
-					info!("API token valid");
-
-					let permissions = get_permissions_for_login_id(
+					get_user_data_for_api_token(
 						req.database,
 						req.redis,
-						&login_id,
-						&token.user_id.into(),
+						&req.config,
+						req.client_ip,
+						token,
 					)
-					.await?;
-
-					RequestUserData::builder()
-						.id(token.user_id)
-						.username(token.username)
-						.first_name(token.first_name)
-						.last_name(token.last_name)
-						.created(token.created)
-						.login_id(token.token_id)
-						.permissions(permissions)
-						.build()
+					.await?
 				}
 				ClientType::WebDashboard => {
 					trace!("Parsing authentication header as a JWT");
@@ -336,8 +198,8 @@ where
 					}
 					trace!("JWT issuer valid");
 
-					// The token should have been issued within the last `REFRESH_TOKEN_VALIDITY`
-					// duration
+					// The token should have been issued within the last
+					// `REFRESH_TOKEN_VALIDITY` duration
 					if OffsetDateTime::now_utc()
 						.sub(jti.get_timestamp().ok_or(ErrorType::MalformedAccessToken)?) >
 						AccessTokenData::REFRESH_TOKEN_VALIDITY
@@ -384,8 +246,10 @@ where
 					.await?
 					else {
 						warn!("web login not found");
-						// No specific error for API token not found, since we don't want to leak
-						// information about whether a loginId is valid or if it's expired
+						// No specific error for API token not found, since we
+						// don't want to leak
+						// information about whether a loginId is valid or if
+						// it's expired
 						return Err(ErrorType::AuthorizationTokenInvalid);
 					};
 					trace!("Web login exists in the database");
@@ -467,11 +331,170 @@ where
 	}
 }
 
+/// Authenticates an API token (in the format `patrv1.<refresh token>.<login
+/// ID>`), returning the data of the user that the token belongs to, along with
+/// the permissions of the token. This is used to authenticate API requests, as
+/// well as other places where an API token can be used in place of a password
+/// (like logging in to the container registry).
+#[instrument(skip(database, redis, config, token))]
+pub async fn get_user_data_for_api_token(
+	database: &mut DatabaseConnection,
+	redis: &mut RedisClient,
+	config: &AppConfig,
+	client_ip: IpAddr,
+	token: &str,
+) -> Result<RequestUserData, ErrorType> {
+	trace!("Parsing authentication header as an API token");
+	let (refresh_token, login_id) = token
+		.strip_prefix("patrv1.")
+		.ok_or_else(|| {
+			warn!("Invalid API token provided: {}", token);
+			ErrorType::MalformedApiToken
+		})?
+		.split_once('.')
+		.ok_or_else(|| {
+			warn!("Invalid API token provided: {}", token);
+			ErrorType::MalformedApiToken
+		})?;
+
+	let refresh_token = Uuid::parse_str(refresh_token).map_err(|err| {
+		warn!("Invalid API token provided: {}", token);
+		warn!(
+			"Cannot parse refresh token `{}` as UUID: {}",
+			refresh_token, err
+		);
+		ErrorType::MalformedApiToken
+	})?;
+	trace!("Refresh token parsed as UUID");
+
+	let login_id = Uuid::parse_str(login_id).map_err(|err| {
+		warn!("Invalid API token provided: {}", token);
+		warn!("Cannot parse loginId `{}` as UUID: {}", login_id, err);
+		ErrorType::MalformedApiToken
+	})?;
+	trace!("Login ID parsed as UUID");
+
+	info!("Extracting information about API token");
+	let Some(token) = query!(
+		r#"
+		SELECT
+			user_api_token.token_id,
+			user_api_token.user_id,
+			user_api_token.token_hash,
+			user_api_token.token_nbf,
+			user_api_token.token_exp,
+			user_api_token.allowed_ips,
+			user_api_token.revoked,
+			"user".*
+		FROM
+			user_api_token
+		INNER JOIN
+			user_login
+		ON
+			user_api_token.token_id = user_login.login_id
+		INNER JOIN
+			"user"
+		ON
+			user_api_token.user_id = "user".id
+		WHERE
+			user_api_token.token_id = $1 AND
+			user_login.login_type = 'api_token';
+		"#,
+		login_id as _
+	)
+	.fetch_optional(&mut *database)
+	.await?
+	else {
+		warn!("API token not found");
+		// No specific error for API token not found, since we don't want to
+		// leak information about whether a loginId is valid or if it's
+		// expired
+		return Err(ErrorType::AuthorizationTokenInvalid);
+	};
+	trace!("Token extracted from database");
+
+	if let Some(nbf) = token.token_nbf {
+		trace!("Token has an NBF");
+		if OffsetDateTime::now_utc() < nbf {
+			info!("API token is not valid yet");
+			return Err(ErrorType::AuthorizationTokenInvalid);
+		}
+	} else {
+		trace!("Token does not have an NBF");
+	}
+	trace!("Token passed NBF check");
+
+	if let Some(exp) = token.token_exp {
+		trace!("Token has an EXP");
+		if OffsetDateTime::now_utc() > exp {
+			info!("API token has expired");
+			return Err(ErrorType::AuthorizationTokenInvalid);
+		}
+	} else {
+		trace!("Token does not have an EXP");
+	}
+	trace!("Token passed EXP check");
+
+	if let Some(revoked) = token.revoked {
+		trace!("Token has a revoked timestamp");
+		if OffsetDateTime::now_utc() > revoked {
+			info!("API token has been revoked");
+			return Err(ErrorType::AuthorizationTokenInvalid);
+		}
+	} else {
+		trace!("Token does not have a revoked timestamp");
+	}
+	trace!("Token passed revoked timestamp check");
+
+	if let Some(allowed_ips) = token.allowed_ips {
+		if !allowed_ips
+			.iter()
+			.any(|ip_network| ip_network.contains(client_ip))
+		{
+			info!("API token not accessed from an allowed IP Address");
+			return Err(ErrorType::DisallowedIpAddressForApiToken);
+		}
+	}
+
+	let Ok(password_hash) = PasswordHash::new(&token.token_hash) else {
+		error!("Unable to parse password hash: {}", token.token_hash);
+		return Err(ErrorType::server_error("password hash parsing failed"));
+	};
+	let success = Argon2::new_with_secret(
+		config.password_pepper.as_bytes(),
+		Algorithm::Argon2id,
+		Version::V0x13,
+		constants::HASHING_PARAMS,
+	)
+	.map_err(ErrorType::server_error)?
+	.verify_password(refresh_token.as_bytes(), &password_hash)
+	.is_ok();
+
+	if !success {
+		warn!("API token has invalid refresh token");
+		return Err(ErrorType::AuthorizationTokenInvalid);
+	}
+	info!("API token valid");
+
+	let permissions =
+		get_permissions_for_login_id(database, redis, &login_id, &token.user_id.into()).await?;
+
+	Ok(RequestUserData::builder()
+		.id(token.user_id)
+		.username(token.username)
+		.first_name(token.first_name)
+		.last_name(token.last_name)
+		.created(token.created)
+		.login_id(token.token_id)
+		.permissions(permissions)
+		.build())
+}
+
 /// Get all the permissions for a given login ID. This will first check the
 /// Redis cache, and if the data is not found, it will query the database and
 /// then store the result in the Redis cache.
 #[tracing::instrument(skip(db_connection, redis_connection))]
-async fn get_permissions_for_login_id(
+pub async fn get_permissions_for_login_id(
 	db_connection: &mut DatabaseConnection,
 	redis_connection: &mut RedisClient,
 	login_id: &Uuid,
@@ -485,24 +508,27 @@ async fn get_permissions_for_login_id(
 		.map(serde_json::from_str::<UserPermissionCache>)
 	{
 		// Check whether the data stored in redis is still valid
-		// Simple example: When a user has their permissions stored in Redis, and they
-		// have been removed from a workspace, that data in redis should be considered
-		// invalid. This check is to ensure that the data stored in redis is still
-		// valid.
+		// Simple example: When a user has their permissions stored in Redis,
+		// and they have been removed from a workspace, that data in
+		// redis should be considered invalid. This check is to ensure
+		// that the data stored in redis is still valid.
 		// So when a user's permissions are updated (like being removed from a
-		// workspace), a timestamp is set in redis. When a request is processed, if this
-		// timestamp exists in Redis, and the data inserted into redis was inserted
-		// after this timestamp, it is considered valid.
+		// workspace), a timestamp is set in redis. When a request is processed,
+		// if this timestamp exists in Redis, and the data inserted into
+		// redis was inserted after this timestamp, it is considered
+		// valid.
 
-		// Check user revocation, then loginId revocation, then workspace ID revocation
+		// Check user revocation, then loginId revocation, then workspace ID
+		// revocation
 		'is_valid: {
 			let revoked = redis_connection
 				.get::<_, Option<i64>>(redis::keys::user_id_revocation_timestamp(user_id))
 				.await?
 				.and_then(|time| OffsetDateTime::from_unix_timestamp(time).ok())
 				.filter(|time| {
-					// If the timestamp exists, and the token was inserted into Redis before the
-					// timestamp, then the data in Redis is considered invalid
+					// If the timestamp exists, and the token was inserted into
+					// Redis before the timestamp, then the
+					// data in Redis is considered invalid
 					data.creation_time < *time
 				})
 				.is_some();
@@ -516,8 +542,9 @@ async fn get_permissions_for_login_id(
 				.await?
 				.and_then(|time| OffsetDateTime::from_unix_timestamp(time).ok())
 				.filter(|time| {
-					// If the timestamp exists, and the token was inserted into Redis before the
-					// timestamp, then the data in Redis is considered invalid
+					// If the timestamp exists, and the token was inserted into
+					// Redis before the timestamp, then the
+					// data in Redis is considered invalid
 					data.creation_time < *time
 				})
 				.is_some();
@@ -537,8 +564,10 @@ async fn get_permissions_for_login_id(
 					.await?
 					.and_then(|time| OffsetDateTime::from_unix_timestamp(time).ok())
 					.filter(|time| {
-						// If the timestamp exists, and the token was inserted into Redis before the
-						// timestamp, then the data in Redis is considered invalid
+						// If the timestamp exists, and the token was inserted
+						// into Redis before the
+						// timestamp, then the data in Redis is considered
+						// invalid
 						data.creation_time < *time
 					})
 					.is_some();
@@ -556,8 +585,9 @@ async fn get_permissions_for_login_id(
 				.await?
 				.and_then(|time| OffsetDateTime::from_unix_timestamp(time).ok())
 				.filter(|time| {
-					// If the timestamp exists, and the token was inserted into Redis before the
-					// timestamp, then the data in Redis is considered invalid
+					// If the timestamp exists, and the token was inserted into
+					// Redis before the timestamp, then the
+					// data in Redis is considered invalid
 					data.creation_time < *time
 				})
 				.is_some();
@@ -610,7 +640,8 @@ async fn get_permissions_for_login_id(
 		workspace_permissions.insert(workspace_id.into(), WorkspacePermission::SuperAdmin);
 	});
 
-	// Once all super-admins are added, add the excludes, then remove the includes
+	// Once all super-admins are added, add the excludes, then remove the
+	// includes
 	query!(
 		r#"
 		SELECT
@@ -651,31 +682,12 @@ async fn get_permissions_for_login_id(
 	.into_iter()
 	.filter_map(|row| row.workspace_id.zip(row.resource_id).zip(row.permission_id))
 	.for_each(|((workspace_id, resource_id), permission_id)| {
-		let permissions = workspace_permissions
-			.entry(workspace_id.into())
-			.or_insert_with(|| WorkspacePermission::Member {
-				permissions: BTreeMap::new(),
-			});
-		match permissions {
-			WorkspacePermission::SuperAdmin => {
-				error!("SuperAdmin found when Member expected. This shouldn't be possible!");
-			}
-			WorkspacePermission::Member { permissions } => {
-				let permission_type = permissions
-					.entry(permission_id.into())
-					.or_insert_with(|| ResourcePermissionType::Exclude(BTreeSet::new()));
-				match permission_type {
-					ResourcePermissionType::Include(_) => {
-						error!(
-							"Found include permissions before include is even called. This should be possible!"
-						);
-					}
-					ResourcePermissionType::Exclude(resources) => {
-						resources.insert(resource_id.into());
-					}
-				}
-			}
-		}
+		exclude_resource_permission(
+			&mut workspace_permissions,
+			workspace_id.into(),
+			permission_id.into(),
+			resource_id.into(),
+		);
 	});
 
 	query!(
@@ -718,35 +730,20 @@ async fn get_permissions_for_login_id(
 	.into_iter()
 	.filter_map(|row| row.workspace_id.zip(row.resource_id).zip(row.permission_id))
 	.for_each(|((workspace_id, resource_id), permission_id)| {
-		let permissions = workspace_permissions
-			.entry(workspace_id.into())
-			.or_insert_with(|| WorkspacePermission::Member {
-				permissions: BTreeMap::new(),
-			});
-		match permissions {
-			WorkspacePermission::SuperAdmin => {
-				error!("SuperAdmin found when Member expected. This shouldn't be possible!");
-			}
-			WorkspacePermission::Member { permissions } => {
-				let permission_type = permissions
-					.entry(permission_id.into())
-					.or_insert_with(|| ResourcePermissionType::Include(BTreeSet::new()));
-				match permission_type {
-					ResourcePermissionType::Include(resources) => {
-						resources.insert(resource_id.into());
-					}
-					ResourcePermissionType::Exclude(resources) => {
-						resources.remove(&resource_id.into());
-					}
-				}
-			}
-		}
+		include_resource_permission(
+			&mut workspace_permissions,
+			workspace_id.into(),
+			permission_id.into(),
+			resource_id.into(),
+		);
 	});
 
 	redis_connection
 		.setex(
 			redis::keys::permission_for_login_id(login_id),
-			constants::CACHED_PERMISSIONS_VALIDITY.whole_seconds() as u64,
+			constants::CACHED_PERMISSIONS_VALIDITY
+				.unsigned_abs()
+				.as_secs(),
 			serde_json::to_string(&UserPermissionCache {
 				permission: workspace_permissions.clone(),
 				creation_time: OffsetDateTime::now_utc(),
@@ -762,3 +759,163 @@ async fn get_permissions_for_login_id(
 
 	Ok(workspace_permissions)
 }
+
+/// Get all the permissions that a user has, based on the workspaces that they
+/// are the super admin of and the roles they have in the other workspaces.
+/// Unlike [`get_permissions_for_login_id`], this is not tied to a login, and is
+/// used when a user authenticates with their password outside the web
+/// dashboard (for example, when logging in to the container registry).
+#[tracing::instrument(skip(db_connection))]
+pub async fn get_permissions_for_user_id(
+	db_connection: &mut DatabaseConnection,
+	user_id: &Uuid,
+) -> Result<BTreeMap<Uuid, WorkspacePermission>, ErrorType> {
+	let mut workspace_permissions = query!(
+		r#"
+		SELECT
+			id
+		FROM
+			workspace
+		WHERE
+			super_admin_id = $1 AND
+			deleted IS NULL;
+		"#,
+		user_id as _
+	)
+	.fetch_all(&mut *db_connection)
+	.await?
+	.into_iter()
+	.map(|row| (row.id.into(), WorkspacePermission::SuperAdmin))
+	.collect::<BTreeMap<Uuid, WorkspacePermission>>();
+
+	// Once all super-admins are added, add the excludes, then remove the
+	// includes
+	query!(
+		r#"
+		SELECT
+			workspace_user.workspace_id,
+			role_resource_permissions_exclude.resource_id,
+			role_resource_permissions_exclude.permission_id
+		FROM
+			workspace_user
+		INNER JOIN
+			role_resource_permissions_exclude
+		ON
+			role_resource_permissions_exclude.role_id = workspace_user.role_id
+		WHERE
+			workspace_user.user_id = $1;
+		"#,
+		user_id as _
+	)
+	.fetch_all(&mut *db_connection)
+	.await?
+	.into_iter()
+	.for_each(|row| {
+		exclude_resource_permission(
+			&mut workspace_permissions,
+			row.workspace_id.into(),
+			row.permission_id.into(),
+			row.resource_id.into(),
+		);
+	});
+
+	query!(
+		r#"
+		SELECT
+			workspace_user.workspace_id,
+			role_resource_permissions_include.resource_id,
+			role_resource_permissions_include.permission_id
+		FROM
+			workspace_user
+		INNER JOIN
+			role_resource_permissions_include
+		ON
+			role_resource_permissions_include.role_id = workspace_user.role_id
+		WHERE
+			workspace_user.user_id = $1;
+		"#,
+		user_id as _
+	)
+	.fetch_all(&mut *db_connection)
+	.await?
+	.into_iter()
+	.for_each(|row| {
+		include_resource_permission(
+			&mut workspace_permissions,
+			row.workspace_id.into(),
+			row.permission_id.into(),
+			row.resource_id.into(),
+		);
+	});
+
+	Ok(workspace_permissions)
+}
+
+/// Adds a resource to the excluded resources of a permission in a workspace.
+/// Excludes are added before includes, since an include removes the resource
+/// from the excluded resources.
+fn exclude_resource_permission(
+	workspace_permissions: &mut BTreeMap<Uuid, WorkspacePermission>,
+	workspace_id: Uuid,
+	permission_id: Uuid,
+	resource_id: Uuid,
+) {
+	let permissions = workspace_permissions
+		.entry(workspace_id)
+		.or_insert_with(|| WorkspacePermission::Member {
+			permissions: BTreeMap::new(),
+		});
+	match permissions {
+		WorkspacePermission::SuperAdmin => {
+			error!("SuperAdmin found when Member expected. This shouldn't be possible!");
+		}
+		WorkspacePermission::Member { permissions } => {
+			let permission_type = permissions
+				.entry(permission_id)
+				.or_insert_with(|| ResourcePermissionType::Exclude(BTreeSet::new()));
+			match permission_type {
+				ResourcePermissionType::Include(_) => {
+					error!(
+						"Found include permissions before include is even called. This should be possible!"
+					);
+				}
+				ResourcePermissionType::Exclude(resources) => {
+					resources.insert(resource_id);
+				}
+			}
+		}
+	}
+}
+
+/// Adds a resource to the included resources of a permission in a workspace,
+/// or removes it from the excluded resources if the permission is an exclude.
+fn include_resource_permission(
+	workspace_permissions: &mut BTreeMap<Uuid, WorkspacePermission>,
+	workspace_id: Uuid,
+	permission_id: Uuid,
+	resource_id: Uuid,
+) {
+	let permissions = workspace_permissions
+		.entry(workspace_id)
+		.or_insert_with(|| WorkspacePermission::Member {
+			permissions: BTreeMap::new(),
+		});
+	match permissions {
+		WorkspacePermission::SuperAdmin => {
+			error!("SuperAdmin found when Member expected. This shouldn't be possible!");
+		}
+		WorkspacePermission::Member { permissions } => {
+			let permission_type = permissions
+				.entry(permission_id)
+				.or_insert_with(|| ResourcePermissionType::Include(BTreeSet::new()));
+			match permission_type {
+				ResourcePermissionType::Include(resources) => {
+					resources.insert(resource_id);
+				}
+				ResourcePermissionType::Exclude(resources) => {
+					resources.remove(&resource_id);
+				}
+			}
+		}
+	}
+}
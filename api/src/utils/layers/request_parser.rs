@@ -116,16 +116,18 @@ where
 				.into_response());
 			};
 
-			let Ok(query) = serde_urlencoded::from_str(req.uri().query().unwrap_or_default())
-				.inspect_err(|err| {
+			// The parse error is sent back to the client, so that invalid `filter`
+			// and `sort` parameters of list endpoints can be corrected
+			let query = match serde_urlencoded::from_str(req.uri().query().unwrap_or_default()) {
+				Ok(query) => query,
+				Err(err) => {
 					debug!("Failed to parse query `{:?}`: {}", req.uri().query(), err);
-				})
-			else {
-				return Ok(ApiErrorResponse::error_with_message(
-					ErrorType::WrongParameters,
-					"Invalid Query Parameters",
-				)
-				.into_response());
+					return Ok(ApiErrorResponse::error_with_message(
+						ErrorType::WrongParameters,
+						format!("Invalid Query Parameters: {}", err),
+					)
+					.into_response());
+				}
 			};
 
 			let Ok(headers) = <E::RequestHeaders as Headers>::from_header_map(req.headers())
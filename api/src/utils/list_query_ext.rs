@@ -0,0 +1,125 @@
+use models::utils::{
+	FilterCondition,
+	FilterOperator,
+	FilterValue,
+	ListField,
+	ListFilter,
+	ListOrder,
+	ListSort,
+	SortKey,
+};
+use sqlx::QueryBuilder;
+
+use crate::prelude::*;
+
+/// An extension trait for [`QueryBuilder`]s that adds the `filter` and `sort`
+/// query parameters of a list endpoint to the query.
+///
+/// Each field is mapped to an SQL expression by the endpoint (using the
+/// `column` function), and all the values in a filter are bound as parameters,
+/// so a request can never add arbitrary SQL to the query.
+pub trait ListQueryExt {
+	/// Adds the conditions of the filter (if any) to the query, each prefixed
+	/// with an `AND`. This should be used right after a `WHERE` clause.
+	fn push_list_filter<F>(
+		&mut self,
+		filter: Option<&ListFilter<F>>,
+		column: impl Fn(F) -> &'static str,
+	) -> &mut Self
+	where
+		F: ListField;
+
+	/// Adds an `ORDER BY` clause to the query, sorting by the fields of the
+	/// sort (if any), and then by the `fallback` ordering. The `fallback` is
+	/// used as the default ordering of the list, as well as a tie-breaker to
+	/// keep the order stable across pages.
+	fn push_list_sort<F>(
+		&mut self,
+		sort: Option<&ListSort<F>>,
+		fallback: &str,
+		column: impl Fn(F) -> &'static str,
+	) -> &mut Self
+	where
+		F: ListField;
+}
+
+impl ListQueryExt for QueryBuilder<'_, DatabaseType> {
+	fn push_list_filter<F>(
+		&mut self,
+		filter: Option<&ListFilter<F>>,
+		column: impl Fn(F) -> &'static str,
+	) -> &mut Self
+	where
+		F: ListField,
+	{
+		let Some(ListFilter(conditions)) = filter else {
+			return self;
+		};
+
+		for FilterCondition {
+			field,
+			operator,
+			values,
+		} in conditions
+		{
+			let column = column(*field);
+			self.push(" AND ");
+			match (operator, values.as_slice()) {
+				(FilterOperator::In, values) => {
+					let values = values.iter().map(ToString::to_string).collect::<Vec<_>>();
+					self.push(column)
+						.push(" = ANY(")
+						.push_bind(values)
+						.push(")");
+				}
+				(FilterOperator::StartsWith, [value]) => {
+					self.push("STARTS_WITH(").push(column).push(", ");
+					push_filter_value(self, value).push(")");
+				}
+				(operator, [value]) => {
+					self.push(column)
+						.push(" ")
+						.push(operator.symbol())
+						.push(" ");
+					push_filter_value(self, value);
+				}
+				// Every other operator is parsed with exactly one value
+				_ => {
+					self.push("FALSE");
+				}
+			}
+		}
+
+		self
+	}
+
+	fn push_list_sort<F>(
+		&mut self,
+		sort: Option<&ListSort<F>>,
+		fallback: &str,
+		column: impl Fn(F) -> &'static str,
+	) -> &mut Self
+	where
+		F: ListField,
+	{
+		self.push(" ORDER BY ");
+		for SortKey { field, order } in sort.into_iter().flat_map(|ListSort(keys)| keys) {
+			self.push(column(*field)).push(match order {
+				ListOrder::Ascending => " ASC, ",
+				ListOrder::Descending => " DESC, ",
+			});
+		}
+		self.push(fallback)
+	}
+}
+
+/// Binds the value of a filter condition to the query.
+fn push_filter_value<'a, 'args>(
+	builder: &'a mut QueryBuilder<'args, DatabaseType>,
+	value: &FilterValue,
+) -> &'a mut QueryBuilder<'args, DatabaseType> {
+	match value {
+		FilterValue::Text(text) => builder.push_bind(text.clone()),
+		FilterValue::Timestamp(timestamp) => builder.push_bind(*timestamp),
+	}
+}
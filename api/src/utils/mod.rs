@@ -15,6 +15,19 @@ pub mod layers;
 /// [2]: axum::Router
 pub mod extractors;
 
+/// Contains the storage backends (S3 and the local filesystem) that objects
+/// like the blobs of the container registry and the files of static sites are
+/// stored in.
+pub mod storage;
+
+/// Contains the extension trait that will be used to add the `filter` and
+/// `sort` query parameters of list endpoints to database queries.
+mod list_query_ext;
+
+/// Contains the helper that runs background tasks (like the garbage collector
+/// of the container registry) periodically.
+pub mod periodic_task;
+
 /// Contains the extension traits that will be used with the axum [`Router`][1]
 /// to mount the various endpoints on the router.
 ///
@@ -25,7 +38,16 @@ mod router_ext;
 /// they're executing.
 mod timeout_ext;
 
-pub use self::{router_ext::RouterExt, timeout_ext::TimeoutExt};
+/// Contains the helpers used by tests that need a database and Redis, to set up
+/// the state of the API and create the resources that the tests work with.
+#[cfg(test)]
+pub mod test_utils;
+
+pub use self::{
+	list_query_ext::ListQueryExt,
+	router_ext::{RouterExt, API_ENDPOINT_DOCS},
+	timeout_ext::TimeoutExt,
+};
 
 /// A list of constants that will be used throughout the application. This is
 /// mostly kept to prevent typos.
@@ -40,6 +62,14 @@ pub mod constants {
 	/// The `aud` field in Patr's JWT
 	pub const PATR_JWT_AUDIENCE: &str = "https://app.patr.cloud";
 
+	/// The name of the container registry service. This is the `aud` field of
+	/// the tokens issued for the registry, and the `service` that clients
+	/// request tokens for.
+	pub const CONTAINER_REGISTRY_SERVICE: &str = "registry.patr.cloud";
+
+	/// The URL that clients of the container registry request tokens from.
+	pub const CONTAINER_REGISTRY_TOKEN_REALM: &str = "https://registry.patr.cloud/token";
+
 	/// The parameters that will be used to hash, using argon2 as the hashing
 	/// algorithm. This is used for all sorts of hashing, from API tokens, user
 	/// passwords, sign up tokens, etc.
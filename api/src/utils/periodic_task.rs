@@ -0,0 +1,25 @@
+use std::{future::Future, time::Duration};
+
+use crate::prelude::*;
+
+/// Runs a task every `interval`, until the server is shut down. The first run
+/// happens after one interval has passed, and each interval starts once the
+/// previous run has finished, so runs of the same task never overlap. The task
+/// is expected to handle (and log) its own errors.
+pub async fn run_periodically<F, Fut>(interval: Duration, mut task: F)
+where
+	F: FnMut() -> Fut,
+	Fut: Future<Output = ()>,
+{
+	tokio::select! {
+		_ = async {
+			loop {
+				tokio::time::sleep(interval).await;
+				task().await;
+			}
+		} => {},
+		_ = tokio::signal::ctrl_c() => {
+			info!("Received SIGINT, shutting down");
+		}
+	}
+}
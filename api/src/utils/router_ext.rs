@@ -6,7 +6,7 @@ use axum::{
 };
 use axum_extra::routing::TypedPath;
 use models::{
-	utils::{AppAuthentication, BearerToken, HasHeader, NoAuthentication},
+	utils::{AppAuthentication, BearerToken, EndpointDocs, HasHeader, NoAuthentication},
 	ApiRequest,
 };
 use preprocess::Preprocessable;
@@ -33,6 +33,12 @@ use crate::{
 	},
 };
 
+/// The documentation of all the endpoints that are mounted and accessible
+/// through the API. This is used to generate the OpenAPI document for the API,
+/// and is populated as the endpoints are mounted on the router. Each endpoint
+/// is only recorded once, even if the router it is on is set up more than once.
+pub static API_ENDPOINT_DOCS: RwLock<Vec<EndpointDocs>> = RwLock::new(Vec::new());
+
 /// Extension trait for axum Router to mount an API endpoint directly along with
 /// the required request parser, Rate limiter, Audit logger and Auth
 /// middlewares, using tower layers.
@@ -102,6 +108,10 @@ where
 				);
 			});
 
+		if <E as ApiEndpoint>::API_ALLOWED {
+			record_endpoint_docs(E::docs());
+		}
+
 		// Setup the layers for the backend
 		if <E as ApiEndpoint>::API_ALLOWED || cfg!(debug_assertions) {
 			self.route(
@@ -169,6 +179,10 @@ where
 				);
 			});
 
+		if <E as ApiEndpoint>::API_ALLOWED {
+			record_endpoint_docs(E::docs());
+		}
+
 		// Setup the layers for the backend
 		if <E as ApiEndpoint>::API_ALLOWED || cfg!(debug_assertions) {
 			self.route(
@@ -197,3 +211,17 @@ where
 		}
 	}
 }
+
+/// Records the documentation of an endpoint that has been mounted, unless the
+/// same endpoint has already been recorded.
+fn record_endpoint_docs(docs: EndpointDocs) {
+	let mut endpoints = API_ENDPOINT_DOCS
+		.write()
+		.expect("API endpoint docs poisoned");
+	let already_recorded = endpoints
+		.iter()
+		.any(|endpoint| endpoint.method == docs.method && endpoint.path == docs.path);
+	if !already_recorded {
+		endpoints.push(docs);
+	}
+}
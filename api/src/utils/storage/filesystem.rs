@@ -0,0 +1,293 @@
+use std::path::{Path, PathBuf};
+
+use axum::{async_trait, body::Bytes};
+use futures::StreamExt;
+use time::OffsetDateTime;
+use tokio::fs;
+use tokio_util::io::{ReaderStream, StreamReader};
+
+use super::{ObjectInfo, ObjectStream, Storage, StorageError};
+use crate::{prelude::*, utils::config::FilesystemStorageConfig};
+
+/// The directory (relative to the root of the storage) that objects are
+/// written to before they are moved to their key. Since this is on the same
+/// filesystem as the objects, the move is atomic, and a partially written
+/// object is never visible at its key.
+const TEMP_DIRECTORY: &str = ".tmp";
+
+/// Stores objects in a directory on the local filesystem. The key of an object
+/// is its path relative to the directory.
+pub struct FilesystemStorage {
+	/// The directory that the objects are stored in
+	root: PathBuf,
+}
+
+impl FilesystemStorage {
+	/// Use the directory given in the config to store objects.
+	pub fn new(config: &FilesystemStorageConfig) -> Self {
+		Self {
+			root: config.path.clone(),
+		}
+	}
+
+	/// Get the path of the file of an object. Keys with empty components, or
+	/// components that start with a `.` (like `..`) are rejected, so that an
+	/// object can't be stored outside the directory.
+	fn get_path(&self, key: &str) -> Result<PathBuf, StorageError> {
+		let is_valid = !key.is_empty() &&
+			key.split('/').all(|component| {
+				!component.is_empty() &&
+					!component.starts_with('.') &&
+					!component.contains(['\\', '\0'])
+			});
+		if !is_valid {
+			return Err(StorageError::InvalidKey(key.to_string()));
+		}
+
+		Ok(self.root.join(key))
+	}
+
+	/// Get a new path to write an object to before it is moved to its key.
+	async fn get_temp_path(&self) -> Result<PathBuf, StorageError> {
+		let directory = self.root.join(TEMP_DIRECTORY);
+		fs::create_dir_all(&directory).await?;
+		Ok(directory.join(Uuid::new_v4().to_string()))
+	}
+
+	/// Move a file that was written to a temporary path to the path of a key.
+	async fn move_into_place(&self, temp_path: &Path, path: &Path) -> Result<(), StorageError> {
+		let result = async {
+			if let Some(parent) = path.parent() {
+				fs::create_dir_all(parent).await?;
+			}
+			fs::rename(temp_path, path).await
+		}
+		.await;
+
+		if result.is_err() {
+			_ = fs::remove_file(temp_path).await;
+		}
+		Ok(result?)
+	}
+}
+
+#[async_trait]
+impl Storage for FilesystemStorage {
+	async fn get_object(&self, key: &str) -> Result<Bytes, StorageError> {
+		Ok(fs::read(self.get_path(key)?).await?.into())
+	}
+
+	async fn get_object_stream(&self, key: &str) -> Result<ObjectStream<'static>, StorageError> {
+		let file = fs::File::open(self.get_path(key)?).await?;
+		Ok(ReaderStream::new(file).boxed())
+	}
+
+	async fn get_object_info(&self, key: &str) -> Result<ObjectInfo, StorageError> {
+		let metadata = fs::metadata(self.get_path(key)?).await?;
+		if !metadata.is_file() {
+			return Err(StorageError::NotFound);
+		}
+
+		Ok(ObjectInfo {
+			key: key.to_string(),
+			size: metadata.len(),
+			last_modified: metadata.modified()?.into(),
+		})
+	}
+
+	async fn put_object(&self, key: &str, data: &[u8]) -> Result<(), StorageError> {
+		let path = self.get_path(key)?;
+		let temp_path = self.get_temp_path().await?;
+
+		if let Err(err) = fs::write(&temp_path, data).await {
+			_ = fs::remove_file(&temp_path).await;
+			return Err(err.into());
+		}
+		self.move_into_place(&temp_path, &path).await
+	}
+
+	async fn put_object_stream(
+		&self,
+		key: &str,
+		data: ObjectStream<'_>,
+	) -> Result<u64, StorageError> {
+		let path = self.get_path(key)?;
+		let temp_path = self.get_temp_path().await?;
+
+		let written = async {
+			let mut file = fs::File::create(&temp_path).await?;
+			let written = tokio::io::copy(&mut StreamReader::new(data), &mut file).await?;
+			file.sync_all().await?;
+			Ok::<_, std::io::Error>(written)
+		}
+		.await;
+
+		let written = match written {
+			Ok(written) => written,
+			Err(err) => {
+				_ = fs::remove_file(&temp_path).await;
+				return Err(err.into());
+			}
+		};
+		self.move_into_place(&temp_path, &path).await?;
+
+		Ok(written)
+	}
+
+	async fn copy_object(&self, from: &str, to: &str) -> Result<(), StorageError> {
+		let from = self.get_path(from)?;
+		let to = self.get_path(to)?;
+		let temp_path = self.get_temp_path().await?;
+
+		if let Err(err) = fs::copy(&from, &temp_path).await {
+			_ = fs::remove_file(&temp_path).await;
+			return Err(err.into());
+		}
+		self.move_into_place(&temp_path, &to).await
+	}
+
+	async fn delete_object(&self, key: &str) -> Result<(), StorageError> {
+		match fs::remove_file(self.get_path(key)?).await {
+			Ok(()) => Ok(()),
+			Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+			Err(err) => Err(err.into()),
+		}
+	}
+
+	async fn list_objects(&self, prefix: &str) -> Result<Vec<ObjectInfo>, StorageError> {
+		// Only the directory that the prefix is in needs to be walked
+		let (directory, start) = match prefix.rsplit_once('/') {
+			Some((directory, _)) => (self.get_path(directory)?, format!("{directory}/")),
+			None => (self.root.clone(), String::new()),
+		};
+
+		let mut objects = Vec::new();
+		let mut directories = vec![(directory, start)];
+		while let Some((directory, key_prefix)) = directories.pop() {
+			let mut entries = match fs::read_dir(&directory).await {
+				Ok(entries) => entries,
+				Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+				Err(err) => return Err(err.into()),
+			};
+
+			while let Some(entry) = entries.next_entry().await? {
+				let Ok(name) = entry.file_name().into_string() else {
+					continue;
+				};
+				if name.starts_with('.') {
+					// Temporary files and anything else that isn't an object
+					continue;
+				}
+
+				let key = format!("{key_prefix}{name}");
+				let metadata = entry.metadata().await?;
+				if metadata.is_dir() {
+					if key.starts_with(prefix) || prefix.starts_with(&format!("{key}/")) {
+						directories.push((entry.path(), format!("{key}/")));
+					}
+				} else if metadata.is_file() && key.starts_with(prefix) {
+					objects.push(ObjectInfo {
+						key,
+						size: metadata.len(),
+						last_modified: metadata
+							.modified()
+							.map(OffsetDateTime::from)
+							.unwrap_or_else(|_| OffsetDateTime::now_utc()),
+					});
+				}
+			}
+		}
+
+		objects.sort_by(|a, b| a.key.cmp(&b.key));
+		Ok(objects)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use futures::{stream, StreamExt, TryStreamExt};
+
+	use super::FilesystemStorage;
+	use crate::{
+		prelude::*,
+		utils::{
+			config::FilesystemStorageConfig,
+			storage::{Storage, StorageError},
+		},
+	};
+
+	#[tokio::test]
+	async fn assert_filesystem_storage_objects() {
+		let root = std::env::temp_dir().join(Uuid::new_v4().to_string());
+		let storage = FilesystemStorage::new(&FilesystemStorageConfig { path: root.clone() });
+
+		storage
+			.put_object("registry/blobs/a", b"hello")
+			.await
+			.unwrap();
+		assert_eq!(
+			&storage.get_object("registry/blobs/a").await.unwrap()[..],
+			b"hello"
+		);
+
+		let data = stream::iter([Ok("hello ".into()), Ok("world".into())]).boxed();
+		let written = storage
+			.put_object_stream("registry/uploads/1/blob", data)
+			.await
+			.unwrap();
+		assert_eq!(written, 11);
+
+		storage
+			.copy_object("registry/uploads/1/blob", "registry/blobs/b")
+			.await
+			.unwrap();
+		let copied = storage
+			.get_object_stream("registry/blobs/b")
+			.await
+			.unwrap()
+			.map_ok(|bytes| bytes.to_vec())
+			.try_concat()
+			.await
+			.unwrap();
+		assert_eq!(&copied[..], b"hello world");
+		assert_eq!(
+			storage
+				.get_object_info("registry/blobs/b")
+				.await
+				.unwrap()
+				.size,
+			11
+		);
+
+		let keys = storage
+			.list_objects("registry/blobs/")
+			.await
+			.unwrap()
+			.into_iter()
+			.map(|object| object.key)
+			.collect::<Vec<_>>();
+		assert_eq!(keys, ["registry/blobs/a", "registry/blobs/b"]);
+		assert_eq!(storage.list_objects("registry/u").await.unwrap().len(), 1);
+		assert!(storage
+			.list_objects("static-site/")
+			.await
+			.unwrap()
+			.is_empty());
+
+		storage.delete_object("registry/blobs/a").await.unwrap();
+		storage.delete_object("registry/blobs/a").await.unwrap();
+		assert!(matches!(
+			storage.get_object("registry/blobs/a").await,
+			Err(StorageError::NotFound)
+		));
+
+		for key in ["", "../a", "registry//a", "registry/.tmp/a", "registry/a/"] {
+			assert!(matches!(
+				storage.put_object(key, b"").await,
+				Err(StorageError::InvalidKey(_))
+			));
+		}
+
+		tokio::fs::remove_dir_all(root).await.unwrap();
+	}
+}
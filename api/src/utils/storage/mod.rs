@@ -0,0 +1,139 @@
+use std::{
+	error::Error as StdError,
+	fmt::{self, Display, Formatter},
+	io,
+};
+
+use ::s3::{creds::error::CredentialsError, error::S3Error};
+use axum::body::Bytes;
+use futures::stream::BoxStream;
+use time::OffsetDateTime;
+
+use crate::utils::config::StorageConfig;
+
+/// Stores objects in a directory on the local filesystem.
+mod filesystem;
+/// Stores objects in an S3 compatible bucket.
+mod s3;
+
+pub use self::{filesystem::FilesystemStorage, s3::S3Storage};
+
+/// A stream of the data of an object
+pub type ObjectStream<'a> = BoxStream<'a, Result<Bytes, io::Error>>;
+
+/// The details of an object in the storage
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectInfo {
+	/// The key of the object
+	pub key: String,
+	/// The size of the object in bytes
+	pub size: u64,
+	/// When the object was last written to
+	pub last_modified: OffsetDateTime,
+}
+
+/// The storage backend that objects (like the blobs and manifests of the
+/// container registry, and the files of static sites) are stored in. Objects
+/// are identified by a key, which is a `/` separated path (eg:
+/// `uploads/<id>/blob`).
+#[axum::async_trait]
+pub trait Storage: Send + Sync {
+	/// Get the entire data of an object. This should only be used for small
+	/// objects, since the object is read into memory.
+	async fn get_object(&self, key: &str) -> Result<Bytes, StorageError>;
+
+	/// Get the data of an object as a stream.
+	async fn get_object_stream(&self, key: &str) -> Result<ObjectStream<'static>, StorageError>;
+
+	/// Get the details of an object, without reading its data.
+	async fn get_object_info(&self, key: &str) -> Result<ObjectInfo, StorageError>;
+
+	/// Store an object, replacing the object if it already exists.
+	async fn put_object(&self, key: &str, data: &[u8]) -> Result<(), StorageError>;
+
+	/// Store an object from a stream of data, replacing the object if it
+	/// already exists. Returns the number of bytes stored.
+	async fn put_object_stream(
+		&self,
+		key: &str,
+		data: ObjectStream<'_>,
+	) -> Result<u64, StorageError>;
+
+	/// Copy an object to another key, replacing the destination if it already
+	/// exists.
+	async fn copy_object(&self, from: &str, to: &str) -> Result<(), StorageError>;
+
+	/// Delete an object. Deleting an object that doesn't exist is not an
+	/// error.
+	async fn delete_object(&self, key: &str) -> Result<(), StorageError>;
+
+	/// List all the objects whose key starts with the given prefix.
+	async fn list_objects(&self, prefix: &str) -> Result<Vec<ObjectInfo>, StorageError>;
+}
+
+/// Get the storage backend selected in the config.
+pub fn get_storage(config: &StorageConfig) -> Result<Box<dyn Storage>, StorageError> {
+	Ok(match config {
+		StorageConfig::S3(config) => Box::new(S3Storage::new(config)?),
+		StorageConfig::Filesystem(config) => Box::new(FilesystemStorage::new(config)),
+	})
+}
+
+/// An error that occurred while accessing the storage
+#[derive(Debug)]
+pub enum StorageError {
+	/// The object does not exist
+	NotFound,
+	/// The key of the object is not valid for the storage backend
+	InvalidKey(String),
+	/// An error from the filesystem, or while reading a stream of data
+	Io(io::Error),
+	/// An error from S3
+	S3(S3Error),
+	/// S3 responded with an unexpected status code
+	UnexpectedStatus(u16),
+	/// The credentials for S3 are not valid
+	Credentials(CredentialsError),
+}
+
+impl Display for StorageError {
+	fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			StorageError::NotFound => write!(formatter, "object not found"),
+			StorageError::InvalidKey(key) => write!(formatter, "invalid object key `{key}`"),
+			StorageError::Io(err) => write!(formatter, "{err}"),
+			StorageError::S3(err) => write!(formatter, "{err}"),
+			StorageError::UnexpectedStatus(status_code) => {
+				write!(formatter, "unexpected status code {status_code} from S3")
+			}
+			StorageError::Credentials(err) => write!(formatter, "{err}"),
+		}
+	}
+}
+
+impl StdError for StorageError {}
+
+impl From<io::Error> for StorageError {
+	fn from(err: io::Error) -> Self {
+		if err.kind() == io::ErrorKind::NotFound {
+			StorageError::NotFound
+		} else {
+			StorageError::Io(err)
+		}
+	}
+}
+
+impl From<S3Error> for StorageError {
+	fn from(err: S3Error) -> Self {
+		match err {
+			S3Error::HttpFailWithBody(404, _) => StorageError::NotFound,
+			err => StorageError::S3(err),
+		}
+	}
+}
+
+impl From<CredentialsError> for StorageError {
+	fn from(err: CredentialsError) -> Self {
+		StorageError::Credentials(err)
+	}
+}
@@ -0,0 +1,122 @@
+use ::s3::{creds::Credentials, Bucket, Region};
+use axum::{async_trait, body::Bytes};
+use futures::{StreamExt, TryStreamExt};
+use time::{
+	format_description::well_known::{Rfc2822, Rfc3339},
+	OffsetDateTime,
+};
+use tokio_util::io::StreamReader;
+
+use super::{ObjectInfo, ObjectStream, Storage, StorageError};
+use crate::utils::config::S3Config;
+
+/// Stores objects in an S3 compatible bucket
+pub struct S3Storage {
+	/// The bucket that the objects are stored in
+	bucket: Box<Bucket>,
+}
+
+impl S3Storage {
+	/// Connect to the bucket given in the config.
+	pub fn new(config: &S3Config) -> Result<Self, StorageError> {
+		let credentials =
+			Credentials::new(Some(&config.key), Some(&config.secret), None, None, None)?;
+		let region = Region::Custom {
+			region: config.region.clone(),
+			endpoint: config.endpoint.clone(),
+		};
+
+		Ok(Self {
+			bucket: Bucket::new(&config.bucket, region, credentials)?,
+		})
+	}
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+	async fn get_object(&self, key: &str) -> Result<Bytes, StorageError> {
+		Ok(self.bucket.get_object(key).await?.bytes().clone())
+	}
+
+	async fn get_object_stream(&self, key: &str) -> Result<ObjectStream<'static>, StorageError> {
+		let object = self.bucket.get_object_stream(key).await?;
+		match object.status_code {
+			200..=299 => Ok(object.bytes.map_err(std::io::Error::other).boxed()),
+			404 => Err(StorageError::NotFound),
+			status_code => Err(StorageError::UnexpectedStatus(status_code)),
+		}
+	}
+
+	async fn get_object_info(&self, key: &str) -> Result<ObjectInfo, StorageError> {
+		let (head, status_code) = self.bucket.head_object(key).await?;
+		if status_code == 404 {
+			return Err(StorageError::NotFound);
+		}
+
+		Ok(ObjectInfo {
+			key: key.to_string(),
+			size: head
+				.content_length
+				.unwrap_or_default()
+				.try_into()
+				.unwrap_or_default(),
+			last_modified: head
+				.last_modified
+				.and_then(|last_modified| OffsetDateTime::parse(&last_modified, &Rfc2822).ok())
+				.unwrap_or_else(OffsetDateTime::now_utc),
+		})
+	}
+
+	async fn put_object(&self, key: &str, data: &[u8]) -> Result<(), StorageError> {
+		self.bucket.put_object(key, data).await?;
+		Ok(())
+	}
+
+	async fn put_object_stream(
+		&self,
+		key: &str,
+		data: ObjectStream<'_>,
+	) -> Result<u64, StorageError> {
+		let mut size = 0;
+		self.bucket
+			.put_object_stream(
+				&mut StreamReader::new(data.inspect_ok(|bytes| size += bytes.len() as u64)),
+				key,
+			)
+			.await?;
+		Ok(size)
+	}
+
+	async fn copy_object(&self, from: &str, to: &str) -> Result<(), StorageError> {
+		self.bucket.copy_object_internal(from, to).await?;
+		Ok(())
+	}
+
+	async fn delete_object(&self, key: &str) -> Result<(), StorageError> {
+		match self.bucket.delete_object(key).await {
+			Ok(_) => Ok(()),
+			Err(err) => match StorageError::from(err) {
+				StorageError::NotFound => Ok(()),
+				err => Err(err),
+			},
+		}
+	}
+
+	async fn list_objects(&self, prefix: &str) -> Result<Vec<ObjectInfo>, StorageError> {
+		Ok(self
+			.bucket
+			.list(prefix.to_string(), None)
+			.await?
+			.into_iter()
+			.flat_map(|result| result.contents)
+			.map(|object| ObjectInfo {
+				// Objects that don't have a valid modification time are
+				// treated as if they were just written
+				last_modified: OffsetDateTime::parse(&object.last_modified, &Rfc3339)
+					.unwrap_or_else(|_| OffsetDateTime::now_utc()),
+				key: object.key,
+				size: object.size,
+			})
+			.collect())
+	}
+}
@@ -0,0 +1,497 @@
+use models::api::workspace::deployment::{DeploymentStatus, PatrRegistry};
+use tokio::sync::Mutex;
+
+use crate::{
+	prelude::*,
+	utils::config::{self, FilesystemStorageConfig, StorageConfig},
+};
+
+/// Whether the database has been initialized by a test. Tests run in parallel,
+/// so this makes sure that only the first test initializes the database.
+static DATABASE_INITIALIZED: Mutex<bool> = Mutex::const_new(false);
+
+/// Sets up the state of the API for tests that need a database and Redis. This
+/// uses the development config (see [`config::parse_config`]), except that
+/// objects are stored in a temporary directory of their own.
+pub async fn setup_state() -> AppState {
+	let mut config = config::parse_config();
+	config.storage = StorageConfig::Filesystem(FilesystemStorageConfig {
+		path: std::env::temp_dir().join(format!("patr-test-{}", Uuid::new_v4())),
+	});
+
+	let state = AppState {
+		database: crate::db::connect(&config.database).await,
+		redis: crate::redis::connect(&config.redis).await,
+		config,
+	};
+
+	let mut initialized = DATABASE_INITIALIZED.lock().await;
+	if !*initialized {
+		crate::db::initialize(&state)
+			.await
+			.expect("unable to initialize the database");
+		*initialized = true;
+	}
+
+	state
+}
+
+/// A user and a workspace that they are the super admin of, created for a test
+#[derive(Debug, Clone, Copy)]
+pub struct TestWorkspace {
+	/// The ID of the user
+	pub user_id: Uuid,
+	/// The ID of the workspace
+	pub workspace_id: Uuid,
+}
+
+/// Creates a new user and a workspace owned by them. The names are random, so
+/// that tests don't conflict with each other.
+pub async fn create_workspace(state: &AppState) -> TestWorkspace {
+	let mut database = state.database.begin().await.unwrap();
+	let user_id = Uuid::new_v4();
+	let name = format!("test_{}", user_id.to_string().replace('-', ""));
+	let email = format!("{}@patr.test", name);
+
+	query!(
+		r#"
+		SET CONSTRAINTS ALL DEFERRED;
+		"#
+	)
+	.execute(&mut *database)
+	.await
+	.unwrap();
+
+	query!(
+		r#"
+		INSERT INTO
+			"user"(
+				id,
+				username,
+				password,
+				first_name,
+				last_name,
+				created,
+				recovery_email,
+				recovery_phone_country_code,
+				recovery_phone_number,
+				workspace_limit,
+				password_reset_token,
+				password_reset_token_expiry,
+				password_reset_attempts,
+				mfa_secret
+			)
+		VALUES
+			($1, $2, '', 'Test', 'User', NOW(), $3, NULL, NULL, 10, NULL, NULL, NULL, NULL);
+		"#,
+		user_id as _,
+		&name,
+		&email,
+	)
+	.execute(&mut *database)
+	.await
+	.unwrap();
+
+	query!(
+		r#"
+		INSERT INTO
+			user_email(
+				user_id,
+				email
+			)
+		VALUES
+			($1, $2);
+		"#,
+		user_id as _,
+		&email,
+	)
+	.execute(&mut *database)
+	.await
+	.unwrap();
+
+	let workspace_id: Uuid = query!(
+		r#"
+		INSERT INTO
+			resource(
+				id,
+				resource_type_id,
+				owner_id,
+				created
+			)
+		VALUES
+			(
+				GENERATE_RESOURCE_ID(),
+				(SELECT id FROM resource_type WHERE name = 'workspace'),
+				gen_random_uuid(),
+				NOW()
+			)
+		RETURNING id;
+		"#,
+	)
+	.fetch_one(&mut *database)
+	.await
+	.unwrap()
+	.id
+	.into();
+
+	query!(
+		r#"
+		INSERT INTO
+			workspace(
+				id,
+				name,
+				super_admin_id,
+				deleted
+			)
+		VALUES
+			($1, $2, $3, NULL);
+		"#,
+		workspace_id as _,
+		&name,
+		user_id as _,
+	)
+	.execute(&mut *database)
+	.await
+	.unwrap();
+
+	query!(
+		r#"
+		UPDATE
+			resource
+		SET
+			owner_id = $1
+		WHERE
+			id = $1;
+		"#,
+		workspace_id as _,
+	)
+	.execute(&mut *database)
+	.await
+	.unwrap();
+
+	query!(
+		r#"
+		SET CONSTRAINTS ALL IMMEDIATE;
+		"#
+	)
+	.execute(&mut *database)
+	.await
+	.unwrap();
+
+	database.commit().await.unwrap();
+
+	TestWorkspace {
+		user_id,
+		workspace_id,
+	}
+}
+
+/// Creates a container registry repository with the given name in a workspace,
+/// and returns its ID.
+pub async fn create_repository(state: &AppState, workspace_id: Uuid, name: &str) -> Uuid {
+	let mut database = state.database.begin().await.unwrap();
+
+	let repository_id: Uuid = query!(
+		r#"
+		INSERT INTO
+			resource(
+				id,
+				resource_type_id,
+				owner_id,
+				created
+			)
+		VALUES
+			(
+				GENERATE_RESOURCE_ID(),
+				(SELECT id FROM resource_type WHERE name = 'container_repository'),
+				$1,
+				NOW()
+			)
+		RETURNING id;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_one(&mut *database)
+	.await
+	.unwrap()
+	.id
+	.into();
+
+	query!(
+		r#"
+		INSERT INTO
+			container_registry_repository(
+				id,
+				workspace_id,
+				name,
+				deleted
+			)
+		VALUES
+			($1, $2, $3, NULL);
+		"#,
+		repository_id as _,
+		workspace_id as _,
+		name,
+	)
+	.execute(&mut *database)
+	.await
+	.unwrap();
+
+	database.commit().await.unwrap();
+
+	repository_id
+}
+
+/// Creates a runner in a workspace, and returns its ID.
+pub async fn create_runner(state: &AppState, workspace_id: Uuid) -> Uuid {
+	let mut database = state.database.begin().await.unwrap();
+
+	let runner_id: Uuid = query!(
+		r#"
+		INSERT INTO
+			resource(
+				id,
+				resource_type_id,
+				owner_id,
+				created
+			)
+		VALUES
+			(
+				GENERATE_RESOURCE_ID(),
+				(SELECT id FROM resource_type WHERE name = 'runner'),
+				$1,
+				NOW()
+			)
+		RETURNING id;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_one(&mut *database)
+	.await
+	.unwrap()
+	.id
+	.into();
+
+	query!(
+		r#"
+		INSERT INTO
+			runner(
+				id,
+				name,
+				workspace_id,
+				cloudflare_tunnel_id
+			)
+		VALUES
+			($1, $2, $3, 'test');
+		"#,
+		runner_id as _,
+		format!("test-{}", runner_id),
+		workspace_id as _,
+	)
+	.execute(&mut *database)
+	.await
+	.unwrap();
+
+	database.commit().await.unwrap();
+
+	runner_id
+}
+
+/// Creates a deployment of a tag of a repository on a runner, with a rolling
+/// update strategy and no ports, and returns its ID.
+pub async fn create_deployment(
+	state: &AppState,
+	workspace_id: Uuid,
+	runner_id: Uuid,
+	repository_id: Uuid,
+	image_tag: &str,
+	status: DeploymentStatus,
+	deploy_on_push: bool,
+) -> Uuid {
+	let mut database = state.database.begin().await.unwrap();
+
+	let machine_type_id = query!(
+		r#"
+		INSERT INTO
+			deployment_machine_type(
+				id,
+				cpu_count,
+				memory_count
+			)
+		VALUES
+			(gen_random_uuid(), 1, 4)
+		RETURNING id;
+		"#,
+	)
+	.fetch_one(&mut *database)
+	.await
+	.unwrap()
+	.id;
+
+	let deployment_id: Uuid = query!(
+		r#"
+		INSERT INTO
+			resource(
+				id,
+				resource_type_id,
+				owner_id,
+				created
+			)
+		VALUES
+			(
+				GENERATE_RESOURCE_ID(),
+				(SELECT id FROM resource_type WHERE name = 'deployment'),
+				$1,
+				NOW()
+			)
+		RETURNING id;
+		"#,
+		workspace_id as _,
+	)
+	.fetch_one(&mut *database)
+	.await
+	.unwrap()
+	.id
+	.into();
+
+	query!(
+		r#"
+		INSERT INTO
+			deployment(
+				id,
+				name,
+				registry,
+				repository_id,
+				image_name,
+				image_tag,
+				status,
+				workspace_id,
+				runner,
+				min_horizontal_scale,
+				max_horizontal_scale,
+				machine_type,
+				deploy_on_push,
+				rollout_strategy,
+				rollout_max_surge,
+				rollout_max_unavailable
+			)
+		VALUES
+			($1, $2, $3, $4, NULL, $5, $6, $7, $8, 1, 1, $9, $10, 'rolling', 1, 0);
+		"#,
+		deployment_id as _,
+		format!("test-{}", deployment_id),
+		PatrRegistry.to_string(),
+		repository_id as _,
+		image_tag,
+		status as _,
+		workspace_id as _,
+		runner_id as _,
+		machine_type_id,
+		deploy_on_push,
+	)
+	.execute(&mut *database)
+	.await
+	.unwrap();
+
+	database.commit().await.unwrap();
+
+	deployment_id
+}
+
+/// Makes a workspace require images to be signed before they are deployed, by
+/// trusting a public key in it.
+pub async fn require_signed_images(state: &AppState, workspace_id: Uuid) {
+	query!(
+		r#"
+		INSERT INTO
+			container_registry_signing_key(
+				id,
+				workspace_id,
+				name,
+				public_key,
+				created
+			)
+		VALUES
+			(gen_random_uuid(), $1, 'test', 'test', NOW());
+		"#,
+		workspace_id as _,
+	)
+	.execute(&state.database)
+	.await
+	.unwrap();
+}
+
+/// Points a tag of a repository to a manifest with the given digest, creating
+/// the manifest if it doesn't exist. The manifest doesn't have any blobs.
+pub async fn create_tag(state: &AppState, repository_id: Uuid, tag: &str, digest: &str) {
+	let mut database = state.database.begin().await.unwrap();
+
+	query!(
+		r#"
+		INSERT INTO
+			container_registry_manifest(
+				manifest_digest,
+				media_type,
+				size,
+				created,
+				subject_digest,
+				artifact_type,
+				annotations
+			)
+		VALUES
+			($1, 'application/vnd.oci.image.manifest.v1+json', 0, NOW(), NULL, NULL, NULL)
+		ON CONFLICT(manifest_digest) DO NOTHING;
+		"#,
+		digest,
+	)
+	.execute(&mut *database)
+	.await
+	.unwrap();
+
+	query!(
+		r#"
+		INSERT INTO
+			container_registry_repository_manifest(
+				repository_id,
+				manifest_digest,
+				architecture,
+				os,
+				variant,
+				created
+			)
+		VALUES
+			($1, $2, 'amd64', 'linux', '', NOW())
+		ON CONFLICT(repository_id, manifest_digest) DO NOTHING;
+		"#,
+		repository_id as _,
+		digest,
+	)
+	.execute(&mut *database)
+	.await
+	.unwrap();
+
+	query!(
+		r#"
+		INSERT INTO
+			container_registry_repository_tag(
+				repository_id,
+				tag,
+				manifest_digest,
+				last_updated
+			)
+		VALUES
+			($1, $2, $3, NOW())
+		ON CONFLICT(repository_id, tag) DO UPDATE SET
+			manifest_digest = EXCLUDED.manifest_digest,
+			last_updated = EXCLUDED.last_updated;
+		"#,
+		repository_id as _,
+		tag,
+		digest,
+	)
+	.execute(&mut *database)
+	.await
+	.unwrap();
+
+	database.commit().await.unwrap();
+}
@@ -0,0 +1,143 @@
+use std::io::IsTerminal;
+
+use clap::Args;
+use crossterm::terminal;
+use futures::{SinkExt, StreamExt};
+use models::api::workspace::deployment::*;
+use patr_client::ApiWebSocket;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::prelude::*;
+
+/// The arguments that can be passed to the exec deployment command.
+#[derive(Debug, Clone, Args)]
+pub struct ExecArgs {
+	/// The ID of the deployment to open the terminal in
+	pub deployment_id: Uuid,
+	/// The command to run, after a `--`. If not provided, an interactive shell
+	/// is started
+	#[arg(last = true)]
+	pub command: Vec<String>,
+}
+
+/// Opens a terminal session in the deployment, relaying the terminal of the
+/// user to it until the command exits.
+pub(super) async fn execute(
+	_: GlobalArgs,
+	args: ExecArgs,
+	state: AppState,
+) -> Result<CommandOutput, ApiErrorResponse> {
+	let AppState::LoggedIn {
+		token,
+		refresh_token: _,
+		current_workspace,
+	} = state
+	else {
+		return Err(ApiErrorResponse::error_with_message(
+			ErrorType::Unauthorized,
+			"You are not logged in. Please log in to open a terminal in a deployment.",
+		));
+	};
+	let Some(workspace_id) = current_workspace else {
+		return Err(ApiErrorResponse::error_with_message(
+			ErrorType::WrongParameters,
+			"No workspace is selected. Please switch to a workspace first.",
+		));
+	};
+
+	let websocket = stream_request(
+		ApiRequest::<ExecDeploymentRequest>::builder()
+			.path(ExecDeploymentPath {
+				workspace_id,
+				deployment_id: args.deployment_id,
+			})
+			.headers(ExecDeploymentRequestHeaders {
+				authorization: token,
+				user_agent: UserAgent::from_static(constants::USER_AGENT_STRING),
+			})
+			.query(ExecDeploymentQuery {
+				command: (!args.command.is_empty()).then(|| args.command.join(" ")),
+			})
+			.body(Default::default())
+			.build(),
+	)
+	.await?;
+
+	// The keys typed into the terminal are sent to the command as they are
+	// typed, instead of a line at a time, so that things like tab completion
+	// work in the session
+	let is_terminal = std::io::stdin().is_terminal();
+	if is_terminal {
+		terminal::enable_raw_mode()?;
+	}
+	let result = run_session(websocket).await;
+	if is_terminal {
+		terminal::disable_raw_mode()?;
+	}
+	let exit_code = result?;
+
+	CommandOutput {
+		text: match exit_code {
+			Some(exit_code) => format!("The command exited with code {}", exit_code),
+			None => "The session was closed".to_owned(),
+		},
+		json: serde_json::json!({ "exitCode": exit_code }),
+	}
+	.into_result()
+}
+
+/// Relays the terminal of the session to the terminal of the user, until the
+/// command exits. Returns the exit code of the command, if the runner could
+/// find it.
+async fn run_session(
+	websocket: ApiWebSocket<ExecDeploymentServerMsg, ExecDeploymentClientMsg>,
+) -> Result<Option<i64>, ApiErrorResponse> {
+	let (mut sender, mut receiver) = websocket.split();
+
+	if let Ok((cols, rows)) = terminal::size() {
+		sender
+			.send(ExecDeploymentClientMsg::Resized { rows, cols })
+			.await
+			.map_err(ApiErrorResponse::error)?;
+	}
+
+	let mut stdin = tokio::io::stdin();
+	let mut stdout = tokio::io::stdout();
+	let mut stdin_closed = false;
+	let mut buffer = vec![0; 1024];
+
+	loop {
+		tokio::select! {
+			message = receiver.next() => match message {
+				Some(Ok(ExecDeploymentServerMsg::Output { data })) => {
+					stdout.write_all(&data).await?;
+					stdout.flush().await?;
+				}
+				Some(Ok(ExecDeploymentServerMsg::Exited { exit_code })) => return Ok(exit_code),
+				Some(Ok(ExecDeploymentServerMsg::Failed { error })) => {
+					return Err(ApiErrorResponse::error_with_message(
+						ErrorType::InternalServerError,
+						error,
+					));
+				}
+				Some(Err(err)) => return Err(ApiErrorResponse::error(err)),
+				None => return Ok(None),
+			},
+			read = stdin.read(&mut buffer), if !stdin_closed => {
+				let read = read?;
+				// Once the input runs out (for example, when it's piped from a
+				// file), the output of the command is still shown until it exits
+				if read == 0 {
+					stdin_closed = true;
+					continue;
+				}
+				sender
+					.send(ExecDeploymentClientMsg::Input {
+						data: buffer[..read].into(),
+					})
+					.await
+					.map_err(ApiErrorResponse::error)?;
+			}
+		}
+	}
+}
@@ -0,0 +1,55 @@
+use clap::Subcommand;
+use models::ApiErrorResponse;
+
+use self::{exec::ExecArgs, port_forward::PortForwardArgs};
+use crate::prelude::*;
+
+/// The command to open a terminal in a deployment.
+mod exec;
+/// The command to forward a port of a deployment to the local machine.
+mod port_forward;
+
+/// All the commands that can be called on the deployments of a workspace.
+#[derive(Debug, Clone, Subcommand)]
+#[command(rename_all = "kebab-case")]
+pub enum DeploymentCommands {
+	/// Manage the deployments of the workspace.
+	#[command(subcommand, name = "deployment")]
+	DeploymentAction(DeploymentActionCommands),
+}
+
+/// The actions that can be performed on a deployment.
+#[derive(Debug, Clone, Subcommand)]
+#[command(rename_all = "kebab-case")]
+pub enum DeploymentActionCommands {
+	/// Open an interactive terminal in a running container of a deployment.
+	Exec(ExecArgs),
+	/// Forward a local port to a port of a running container of a deployment,
+	/// including ports that aren't exposed.
+	PortForward(PortForwardArgs),
+}
+
+impl CommandExecutor for DeploymentCommands {
+	async fn execute(
+		self,
+		global_args: GlobalArgs,
+		state: AppState,
+	) -> Result<CommandOutput, ApiErrorResponse> {
+		match self {
+			Self::DeploymentAction(commands) => commands.execute(global_args, state).await,
+		}
+	}
+}
+
+impl CommandExecutor for DeploymentActionCommands {
+	async fn execute(
+		self,
+		global_args: GlobalArgs,
+		state: AppState,
+	) -> Result<CommandOutput, ApiErrorResponse> {
+		match self {
+			Self::Exec(args) => exec::execute(global_args, args, state).await,
+			Self::PortForward(args) => port_forward::execute(global_args, args, state).await,
+		}
+	}
+}
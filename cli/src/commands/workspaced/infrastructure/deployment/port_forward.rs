@@ -0,0 +1,195 @@
+use std::{net::Ipv4Addr, str::FromStr};
+
+use clap::Args;
+use futures::{SinkExt, StreamExt};
+use models::api::workspace::deployment::*;
+use tokio::{
+	io::{AsyncReadExt, AsyncWriteExt},
+	net::{TcpListener, TcpStream},
+	task,
+};
+
+use crate::prelude::*;
+
+/// The arguments that can be passed to the port forward deployment command.
+#[derive(Debug, Clone, Args)]
+pub struct PortForwardArgs {
+	/// The ID of the deployment to forward the port of
+	pub deployment_id: Uuid,
+	/// The port to forward, as `LOCAL_PORT:CONTAINER_PORT`. If only one port
+	/// is given, the same port is used on both sides
+	pub ports: PortMapping,
+}
+
+/// A local port, along with the port of the deployment's container that it is
+/// forwarded to
+#[derive(Debug, Clone, Copy)]
+pub struct PortMapping {
+	/// The port on this machine that connections are accepted on
+	pub local: u16,
+	/// The port of the container that connections are forwarded to
+	pub remote: u16,
+}
+
+impl FromStr for PortMapping {
+	type Err = String;
+
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		let parse = |port: &str| {
+			port.parse::<u16>()
+				.map_err(|_| format!("`{}` is not a valid port", port))
+		};
+		match value.split_once(':') {
+			Some((local, remote)) => Ok(Self {
+				local: parse(local)?,
+				remote: parse(remote)?,
+			}),
+			None => {
+				let port = parse(value)?;
+				Ok(Self {
+					local: port,
+					remote: port,
+				})
+			}
+		}
+	}
+}
+
+/// Listens on the local port and forwards every connection made to it to the
+/// port of the deployment, until the user stops the command.
+pub(super) async fn execute(
+	_: GlobalArgs,
+	args: PortForwardArgs,
+	state: AppState,
+) -> Result<CommandOutput, ApiErrorResponse> {
+	let AppState::LoggedIn {
+		token,
+		refresh_token: _,
+		current_workspace,
+	} = state
+	else {
+		return Err(ApiErrorResponse::error_with_message(
+			ErrorType::Unauthorized,
+			"You are not logged in. Please log in to forward a port of a deployment.",
+		));
+	};
+	let Some(workspace_id) = current_workspace else {
+		return Err(ApiErrorResponse::error_with_message(
+			ErrorType::WrongParameters,
+			"No workspace is selected. Please switch to a workspace first.",
+		));
+	};
+
+	let PortMapping { local, remote } = args.ports;
+	let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, local)).await?;
+	eprintln!(
+		"Forwarding from {} to port {} of the deployment. Press Ctrl+C to stop.",
+		listener.local_addr()?,
+		remote
+	);
+
+	// Every connection is forwarded over a WebSocket of its own, so that
+	// connections don't hold each other up
+	let mut connections = 0;
+	loop {
+		tokio::select! {
+			connection = listener.accept() => {
+				let (connection, address) = connection?;
+				connections += 1;
+				let token = token.clone();
+				task::spawn(async move {
+					let result = forward_connection(
+						connection,
+						workspace_id,
+						args.deployment_id,
+						remote,
+						token,
+					)
+					.await;
+					if let Err(err) = result {
+						eprintln!(
+							"Failed to forward the connection from {}: {}",
+							address, err.body.message
+						);
+					}
+				});
+			}
+			result = tokio::signal::ctrl_c() => {
+				result?;
+				break;
+			}
+		}
+	}
+
+	CommandOutput {
+		text: format!(
+			"Stopped forwarding port {}. {} connections were forwarded",
+			remote, connections
+		),
+		json: serde_json::json!({ "connections": connections }),
+	}
+	.into_result()
+}
+
+/// Relays a connection to the port of the deployment, until either side closes
+/// it.
+async fn forward_connection(
+	mut connection: TcpStream,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+	port: u16,
+	token: BearerToken,
+) -> Result<(), ApiErrorResponse> {
+	let websocket = stream_request(
+		ApiRequest::<ForwardDeploymentPortRequest>::builder()
+			.path(ForwardDeploymentPortPath {
+				workspace_id,
+				deployment_id,
+			})
+			.headers(ForwardDeploymentPortRequestHeaders {
+				authorization: token,
+				user_agent: UserAgent::from_static(constants::USER_AGENT_STRING),
+			})
+			.query(ForwardDeploymentPortQuery { port })
+			.body(Default::default())
+			.build(),
+	)
+	.await?;
+	let (mut sender, mut receiver) = websocket.split();
+	let (mut reader, mut writer) = connection.split();
+
+	let mut buffer = vec![0; 4096];
+	loop {
+		tokio::select! {
+			message = receiver.next() => match message {
+				Some(Ok(ForwardDeploymentPortServerMsg::Data { data })) => {
+					writer.write_all(&data).await?;
+				}
+				Some(Ok(ForwardDeploymentPortServerMsg::Closed { error: None })) | None => break,
+				Some(Ok(ForwardDeploymentPortServerMsg::Closed { error: Some(error) })) => {
+					return Err(ApiErrorResponse::error_with_message(
+						ErrorType::InternalServerError,
+						error,
+					));
+				}
+				Some(Err(err)) => return Err(ApiErrorResponse::error(err)),
+			},
+			read = reader.read(&mut buffer) => {
+				let read = read?;
+				// The connection was closed on this side
+				if read == 0 {
+					break;
+				}
+				sender
+					.send(ForwardDeploymentPortClientMsg::Data {
+						data: buffer[..read].into(),
+					})
+					.await
+					.map_err(ApiErrorResponse::error)?;
+			}
+		}
+	}
+
+	_ = sender.close().await;
+	Ok(())
+}
@@ -1,11 +1,19 @@
 use clap::Subcommand;
+use models::ApiErrorResponse;
+
+use self::deployment::DeploymentCommands;
+use crate::prelude::*;
+
+/// The commands for the deployments of a workspace.
+mod deployment;
 
 /// A list of all the commands that can be called on a workspace.
 #[derive(Debug, Clone, Subcommand)]
 #[command(rename_all = "kebab-case")]
 pub enum InfrastructureCommands {
-	// #[command(flatten)]
-	// DeploymentCommands(DeploymentCommands),
+	/// All the commands that are meant for deployments
+	#[command(flatten)]
+	DeploymentCommands(DeploymentCommands),
 	// #[command(flatten)]
 	// DatabaseCommands(DatabaseCommands),
 	// #[command(flatten)]
@@ -15,3 +23,15 @@ pub enum InfrastructureCommands {
 	// #[command(flatten)]
 	// SecretCommands(SecretCommands),
 }
+
+impl CommandExecutor for InfrastructureCommands {
+	async fn execute(
+		self,
+		global_args: GlobalArgs,
+		state: AppState,
+	) -> Result<CommandOutput, ApiErrorResponse> {
+		match self {
+			Self::DeploymentCommands(commands) => commands.execute(global_args, state).await,
+		}
+	}
+}
@@ -1,21 +1,25 @@
 use clap::Subcommand;
 use models::ApiErrorResponse;
 
-use self::workspace::WorkspaceCommands;
+use self::{infrastructure::InfrastructureCommands, workspace::WorkspaceCommands};
 use super::{CommandExecutor, GlobalArgs};
 use crate::prelude::*;
 
+/// All the commands for the infrastructure of a workspace.
 mod infrastructure;
+/// All the commands to manage the workspaces of the user.
 mod workspace;
 
 /// A list of all the commands that can be called on a workspace.
 #[derive(Debug, Clone, Subcommand)]
 #[command(rename_all = "kebab-case")]
 pub enum WorkspacedCommands {
+	/// All the commands that are meant for the workspaces of the user
 	#[command(flatten)]
 	WorkspaceCommands(WorkspaceCommands),
-	// #[command(flatten)]
-	// InfrastructureCommands(InfrastructureCommands),
+	/// All the commands that are meant for the infrastructure of a workspace
+	#[command(flatten)]
+	InfrastructureCommands(InfrastructureCommands),
 	// #[command(flatten)]
 	// DomainConfigurationCommands(DomainConfigurationCommands),
 }
@@ -28,10 +32,8 @@ impl CommandExecutor for WorkspacedCommands {
 	) -> Result<CommandOutput, ApiErrorResponse> {
 		match self {
 			Self::WorkspaceCommands(commands) => commands.execute(global_args, state).await,
-			/* Self::InfrastructureCommands(commands) => {
-			 * 	commands.execute(global_args, writer).await
-			 * }
-			 * Self::DomainConfigurationCommands(commands) => {
+			Self::InfrastructureCommands(commands) => commands.execute(global_args, state).await,
+			/* Self::DomainConfigurationCommands(commands) => {
 			 * 	commands.execute(global_args, writer).await
 			 * } */
 		}
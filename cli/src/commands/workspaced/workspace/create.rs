@@ -11,6 +11,7 @@ pub struct CreateArgs {
 	pub name: String,
 }
 
+/// Creates a new workspace with the given name.
 pub(super) async fn execute(
 	_: GlobalArgs,
 	args: CreateArgs,
@@ -18,7 +19,7 @@ pub(super) async fn execute(
 ) -> Result<CommandOutput, ApiErrorResponse> {
 	let AppState::LoggedIn {
 		token,
-		refresh_token,
+		refresh_token: _,
 		current_workspace: _,
 	} = state
 	else {
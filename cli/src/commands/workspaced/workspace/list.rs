@@ -3,14 +3,15 @@ use models::api::user::*;
 
 use crate::prelude::*;
 
+/// Lists all the workspaces that the user is a part of.
 pub(super) async fn execute(
-	global_args: GlobalArgs,
+	_: GlobalArgs,
 	(): (),
 	state: AppState,
 ) -> Result<CommandOutput, ApiErrorResponse> {
 	let AppState::LoggedIn {
 		token,
-		refresh_token,
+		refresh_token: _,
 		current_workspace: _,
 	} = state
 	else {
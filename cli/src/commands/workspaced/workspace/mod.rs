@@ -4,35 +4,53 @@ use models::ApiErrorResponse;
 use self::{create::CreateArgs, rename::RenameArgs, switch::SwitchArgs};
 use crate::prelude::*;
 
+/// The command to create a workspace.
 mod create;
+/// The command to list the workspaces of the user.
 mod list;
+/// The command to rename a workspace.
 mod rename;
+/// The command to switch to a different workspace.
 mod switch;
 
+/// All the commands that can be called on the workspaces of the user.
 #[derive(Debug, Clone, Subcommand)]
 #[command(rename_all = "kebab-case")]
 pub enum WorkspaceCommands {
+	/// Manage the workspaces of the user.
 	#[command(subcommand, name = "workspace")]
 	WorkspaceAction(WorkspaceActionCommands),
+	/// Manage the workspace that commands are run on.
 	#[command(subcommand)]
 	Context(ContextCommands),
+	/// List the workspaces of the user.
 	#[command(name = "workspaces")]
 	ListWorkspaces,
 }
 
+/// The actions that can be performed on the workspaces of the user.
 #[derive(Debug, Clone, Subcommand)]
 #[command(rename_all = "kebab-case")]
 pub enum WorkspaceActionCommands {
+	/// Create a new workspace.
 	Create(CreateArgs),
+	/// Switch to a different workspace.
 	Switch(SwitchArgs),
+	/// List the workspaces of the user.
 	List,
+	/// Rename a workspace.
 	Rename(RenameArgs),
 }
 
+/// The commands to change the workspace that commands are run on.
 #[derive(Debug, Clone, Subcommand)]
 #[command(rename_all = "kebab-case")]
 pub enum ContextCommands {
-	Switch { name: String },
+	/// Switch to a different workspace.
+	Switch {
+		/// Name of the workspace to switch to
+		name: String,
+	},
 }
 
 impl CommandExecutor for WorkspaceCommands {
@@ -14,10 +14,11 @@ pub struct RenameArgs {
 	pub new_name: String,
 }
 
+/// Renames a workspace of the user.
 pub(super) async fn execute(
-	global_args: GlobalArgs,
-	args: RenameArgs,
-	state: AppState,
+	_: GlobalArgs,
+	_: RenameArgs,
+	_: AppState,
 ) -> Result<CommandOutput, ApiErrorResponse> {
 	todo!()
 }
@@ -11,6 +11,7 @@ pub struct SwitchArgs {
 	pub name: String,
 }
 
+/// Switches the current workspace of the user to the given workspace.
 pub(super) async fn execute(
 	_: GlobalArgs,
 	args: SwitchArgs,
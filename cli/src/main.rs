@@ -23,7 +23,7 @@ pub mod prelude {
 	pub use crate::{
 		app::{CommandExecutor, CommandOutput, OutputType},
 		commands::{AppArgs, GlobalArgs, GlobalCommands},
-		utils::{constants, make_request, AppState, ToJsonValue},
+		utils::{constants, make_request, stream_request, AppState, ToJsonValue},
 	};
 }
 
@@ -40,9 +40,10 @@ async fn main() -> ExitCode {
 			eprintln!("Loading default state...");
 		})
 		.unwrap_or_default();
+	utils::initialize_client(&state);
 
 	let output_format = global_args.output;
-	let Ok(output) = command.execute(global_args, state).await.map_err(|err| {
+	let output = command.execute(global_args, state).await.map_err(|err| {
 		eprintln!(
 			"{}",
 			match output_format {
@@ -57,7 +58,13 @@ async fn main() -> ExitCode {
 				}
 			}
 		)
-	}) else {
+	});
+
+	if let Err(err) = utils::save_renewed_access_token() {
+		eprintln!("Failed to save the renewed access token: {}", err);
+	}
+
+	let Ok(output) = output else {
 		return ExitCode::FAILURE;
 	};
 
@@ -1,149 +1,99 @@
 use std::{str::FromStr, sync::OnceLock};
 
-use models::{
-	prelude::*,
-	utils::{False, Headers},
-	ApiErrorResponse,
-	ApiErrorResponseBody,
-	ApiResponseBody,
-	ApiSuccessResponseBody,
-};
+use models::{utils::WebSocketUpgrade, ApiErrorResponse};
+use patr_client::{ApiClient, ApiWebSocket};
 use preprocess::Preprocessable;
-use reqwest::Client;
 use serde::{de::DeserializeOwned, Serialize};
 use url::Url;
 
-/// A reqwest client that can be used to make requests to the API
-static REQUEST_CLIENT: OnceLock<Client> = OnceLock::new();
+use crate::prelude::*;
+
+/// The client used to make requests to the API. This is initialized with the
+/// user's login when the CLI starts, so that expired access tokens can be
+/// renewed automatically.
+static API_CLIENT: OnceLock<ApiClient> = OnceLock::new();
+
+/// Initialize the client used to make requests to the API with the given state.
+/// If the user is logged in, the client will use their refresh token to renew
+/// the access token when it expires.
+pub fn initialize_client(state: &AppState) {
+	let refresh_token = match state {
+		AppState::LoggedIn { refresh_token, .. } => BearerToken::from_str(refresh_token).ok(),
+		AppState::LoggedOut => None,
+	};
+	_ = API_CLIENT.set(build_client(refresh_token));
+}
 
 /// Make an API request to an endpoint
 pub async fn make_request<E>(
-	ApiRequest {
-		path,
-		query,
-		headers,
-		body,
-	}: ApiRequest<E>,
+	request: ApiRequest<E>,
 ) -> Result<ApiSuccessResponse<E>, ApiErrorResponse>
 where
 	E: ApiEndpoint,
 	<E::RequestBody as Preprocessable>::Processed: Send,
-	E::ResponseBody: DeserializeOwned + Serialize,
-	E::RequestBody: DeserializeOwned + Serialize,
+	E::ResponseBody: DeserializeOwned,
+	E::RequestBody: Serialize,
 {
-	let body = serde_json::to_value(&body)
-		.map_err(|err| err.to_string())
-		.map_err(|err| ApiErrorResponse {
-			status_code: http::StatusCode::INTERNAL_SERVER_ERROR,
-			body: ApiErrorResponseBody {
-				success: False,
-				error: ErrorType::server_error(err.clone()),
-				message: err,
-			},
-		})?;
-	let builder = REQUEST_CLIENT
-		.get_or_init(initialize_client)
-		.request(
-			reqwest::Method::from_str(E::METHOD.as_ref()).unwrap(),
-			Url::from_str(super::constants::API_BASE_URL)
-				.unwrap()
-				.join(path.to_string().as_str())
-				.unwrap(),
-		)
-		.query(&query)
-		.headers(
-			headers
-				.to_header_map()
-				.into_iter()
-				.filter_map(|(key, value)| {
-					Some((
-						reqwest::header::HeaderName::from_str(key?.as_str()).unwrap(),
-						reqwest::header::HeaderValue::from_str(value.to_str().unwrap()).unwrap(),
-					))
-				})
-				.chain([(
-					reqwest::header::CONTENT_TYPE,
-					reqwest::header::HeaderValue::from_static("application/json"),
-				)])
-				.collect(),
-		);
+	API_CLIENT
+		.get_or_init(|| build_client(None))
+		.make_request(request)
+		.await
+}
 
-	let response = if body.is_null() {
-		builder
-	} else {
-		builder.json(&body)
-	}
-	.send()
-	.await;
+/// Connect to a streaming endpoint of the API
+pub async fn stream_request<E, ServerMsg, ClientMsg>(
+	request: ApiRequest<E>,
+) -> Result<ApiWebSocket<ServerMsg, ClientMsg>, ApiErrorResponse>
+where
+	E: ApiEndpoint<RequestBody = WebSocketUpgrade<ServerMsg, ClientMsg>>,
+	<E::RequestBody as Preprocessable>::Processed: Send,
+	ServerMsg: DeserializeOwned,
+	ClientMsg: Serialize,
+{
+	API_CLIENT
+		.get_or_init(|| build_client(None))
+		.stream_request(request)
+		.await
+}
 
-	let response = match response {
-		Ok(response) => response,
-		Err(error) => {
-			return Err(ApiErrorResponse {
-				status_code: http::StatusCode::INTERNAL_SERVER_ERROR,
-				body: ApiErrorResponseBody {
-					success: False,
-					error: ErrorType::server_error(error.to_string()),
-					message: error.to_string(),
-				},
-			});
-		}
+/// Save the access token that was renewed by the client (if any) to the stored
+/// state, so that it is used the next time the CLI is run.
+pub fn save_renewed_access_token() -> Result<(), anyhow::Error> {
+	let Some(client) = API_CLIENT.get() else {
+		return Ok(());
+	};
+	let Some(renewed_token) = client.renewed_access_token() else {
+		return Ok(());
 	};
 
-	let status_code = response.status();
-	let Ok(headers) = E::ResponseHeaders::from_header_map(
-		&response
-			.headers()
-			.into_iter()
-			.map(|(key, value)| {
-				(
-					http::HeaderName::from_str(key.as_str()).unwrap(),
-					http::header::HeaderValue::from_str(value.to_str().unwrap()).unwrap(),
-				)
-			})
-			.collect(),
-	) else {
-		return Err(ApiErrorResponse {
-			status_code: http::StatusCode::INTERNAL_SERVER_ERROR,
-			body: ApiErrorResponseBody {
-				success: False,
-				error: ErrorType::server_error("invalid headers"),
-				message: "invalid headers".to_string(),
-			},
-		});
+	// The command might have changed the state (for example, by logging out),
+	// so the latest state is loaded before the token is updated.
+	let AppState::LoggedIn {
+		token: _,
+		refresh_token,
+		current_workspace,
+	} = AppState::load()?
+	else {
+		return Ok(());
 	};
+	if BearerToken::from_str(&refresh_token).ok().as_ref() != client.refresh_token() {
+		return Ok(());
+	}
 
-	match response.json::<ApiResponseBody<E::ResponseBody>>().await {
-		Ok(ApiResponseBody::Success(ApiSuccessResponseBody {
-			success: _,
-			response: body,
-		})) => Ok(ApiSuccessResponse {
-			status_code: http::StatusCode::from_u16(status_code.as_u16()).unwrap(),
-			headers,
-			body,
-		}),
-		Ok(ApiResponseBody::Error(error)) => Err(ApiErrorResponse {
-			status_code: http::StatusCode::from_u16(status_code.as_u16()).unwrap(),
-			body: error,
-		}),
-		Err(error) => {
-			error!("{}", error.to_string());
-			Err(ApiErrorResponse {
-				status_code: http::StatusCode::INTERNAL_SERVER_ERROR,
-				body: ApiErrorResponseBody {
-					success: False,
-					error: ErrorType::server_error(error.to_string()),
-					message: error.to_string(),
-				},
-			})
-		}
+	AppState::LoggedIn {
+		token: renewed_token,
+		refresh_token,
+		current_workspace,
 	}
+	.save()?;
+
+	Ok(())
 }
 
-/// Initialize a reqwest client that can be used across the application to make
-/// requests
-fn initialize_client() -> Client {
-	Client::builder()
+/// Build the client used to make requests to the API
+fn build_client(refresh_token: Option<BearerToken>) -> ApiClient {
+	ApiClient::builder()
+		.base_url(Url::parse(constants::API_BASE_URL).expect("invalid API base URL"))
+		.refresh_token(refresh_token)
 		.build()
-		.expect("failed to initialize client")
 }
@@ -0,0 +1,470 @@
+use std::{
+	fmt::Display,
+	str::FromStr,
+	sync::{Arc, OnceLock, RwLock},
+	time::Duration,
+};
+
+use headers::HeaderMapExt;
+use http::{header::AUTHORIZATION, HeaderMap, Method, StatusCode};
+use models::{
+	api::auth::{RenewAccessTokenPath, RenewAccessTokenRequest, RenewAccessTokenRequestHeaders},
+	prelude::*,
+	utils::{constants, False, Headers, WebSocketUpgrade},
+	ApiErrorResponse,
+	ApiErrorResponseBody,
+	ApiResponseBody,
+};
+use preprocess::Preprocessable;
+use serde::{de::DeserializeOwned, Serialize};
+use tokio_tungstenite::tungstenite::{client::IntoClientRequest, Error as TungsteniteError};
+use typed_builder::TypedBuilder;
+use url::Url;
+
+use crate::ApiWebSocket;
+
+/// A reqwest client that is shared by all the [`ApiClient`]s, so that
+/// connections to the API are pooled and reused across clients.
+static REQUEST_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+/// The user agent used to renew an access token, if the request that failed
+/// did not have a user agent of its own.
+const DEFAULT_USER_AGENT: &str = concat!("patr-client/", env!("CARGO_PKG_VERSION"));
+
+/// A client used to make requests to the Patr API. Cloning the client is cheap,
+/// and clones share the access token that was last renewed.
+///
+/// Example:
+/// ```rust
+/// # use models::{api::workspace::deployment::ListDeploymentRequest, prelude::*};
+/// # use patr_client::ApiClient;
+/// # async fn example(
+/// #     refresh_token: BearerToken,
+/// #     request: ApiRequest<ListDeploymentRequest>,
+/// # ) -> Result<(), ApiErrorResponse> {
+/// let client = ApiClient::builder()
+///     .refresh_token(Some(refresh_token))
+///     .build();
+/// let response = client.make_request(request).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, TypedBuilder)]
+pub struct ApiClient {
+	/// The base URL of the API that requests are made to. Defaults to
+	/// [`constants::API_BASE_URL`].
+	#[builder(default = Url::parse(constants::API_BASE_URL).expect("API base URL should be valid"))]
+	base_url: Url,
+	/// The number of times a request will be retried if it fails due to a
+	/// transient error, such as the API being unreachable.
+	#[builder(default = 3)]
+	max_retries: u32,
+	/// The delay before the first retry. The delay is doubled on every
+	/// subsequent retry.
+	#[builder(default = Duration::from_millis(500))]
+	retry_delay: Duration,
+	/// The refresh token of the user's login. If this is set, an access token
+	/// that has expired will automatically be renewed and the request will be
+	/// retried with the new access token.
+	#[builder(default)]
+	refresh_token: Option<BearerToken>,
+	/// The access token that was last renewed using the refresh token. This
+	/// replaces the `Authorization` header of every request made after it has
+	/// been renewed.
+	#[builder(default, setter(skip))]
+	access_token: Arc<RwLock<Option<BearerToken>>>,
+}
+
+impl Default for ApiClient {
+	fn default() -> Self {
+		Self::builder().build()
+	}
+}
+
+impl ApiClient {
+	/// The base URL of the API that requests are made to.
+	pub fn base_url(&self) -> &Url {
+		&self.base_url
+	}
+
+	/// The refresh token used to renew expired access tokens, if any.
+	pub fn refresh_token(&self) -> Option<&BearerToken> {
+		self.refresh_token.as_ref()
+	}
+
+	/// The access token that was last renewed by the client, if any. Callers
+	/// that persist the user's login (such as the CLI) should store this so
+	/// that the renewed token is used the next time.
+	pub fn renewed_access_token(&self) -> Option<BearerToken> {
+		self.access_token
+			.read()
+			.expect("access token lock poisoned")
+			.clone()
+	}
+
+	/// Make an API request to an endpoint. Requests that fail due to transient
+	/// errors are retried, and expired access tokens are renewed if the client
+	/// has a refresh token.
+	pub async fn make_request<E>(
+		&self,
+		ApiRequest {
+			path,
+			query,
+			headers,
+			body,
+		}: ApiRequest<E>,
+	) -> Result<ApiSuccessResponse<E>, ApiErrorResponse>
+	where
+		E: ApiEndpoint,
+		<E::RequestBody as Preprocessable>::Processed: Send,
+		E::RequestBody: Serialize,
+		E::ResponseBody: DeserializeOwned,
+	{
+		let url = self
+			.base_url
+			.join(path.to_string().as_str())
+			.map_err(client_error)?;
+		let body = serde_json::to_value(&body).map_err(client_error)?;
+		let mut headers = headers.to_header_map();
+		self.apply_renewed_access_token(&mut headers);
+
+		match self.execute::<E>(&url, &query, &headers, &body).await {
+			Err(error) if self.can_renew_access_token(&error, &headers) => {
+				let access_token = self.renew_access_token(&headers).await?;
+				headers.typed_insert(access_token);
+				self.execute::<E>(&url, &query, &headers, &body).await
+			}
+			result => result,
+		}
+	}
+
+	/// Connect to a streaming endpoint of the API. The returned
+	/// [`ApiWebSocket`] can be used to both receive messages from and send
+	/// messages to the API.
+	pub async fn stream_request<E, ServerMsg, ClientMsg>(
+		&self,
+		ApiRequest {
+			path,
+			query,
+			headers,
+			body: _,
+		}: ApiRequest<E>,
+	) -> Result<ApiWebSocket<ServerMsg, ClientMsg>, ApiErrorResponse>
+	where
+		E: ApiEndpoint<RequestBody = WebSocketUpgrade<ServerMsg, ClientMsg>>,
+		<E::RequestBody as Preprocessable>::Processed: Send,
+		ServerMsg: DeserializeOwned,
+		ClientMsg: Serialize,
+	{
+		let mut url = self
+			.base_url
+			.join(path.to_string().as_str())
+			.map_err(client_error)?;
+		let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+		url.set_scheme(scheme)
+			.map_err(|()| client_error("cannot convert the API URL to a WebSocket URL"))?;
+		let query = serde_urlencoded::to_string(&query).map_err(client_error)?;
+		if !query.is_empty() {
+			url.set_query(Some(&query));
+		}
+		let mut headers = headers.to_header_map();
+		self.apply_renewed_access_token(&mut headers);
+
+		let mut renewed = false;
+		let mut attempt = 0;
+		loop {
+			let mut request = url.as_str().into_client_request().map_err(client_error)?;
+			request.headers_mut().extend(headers.clone());
+			*request.method_mut() = E::METHOD;
+
+			let error = match tokio_tungstenite::connect_async(request).await {
+				Ok((stream, _)) => return Ok(ApiWebSocket::new(stream)),
+				Err(TungsteniteError::Http(response)) => {
+					let (parts, body) = response.into_parts();
+					ApiErrorResponse {
+						status_code: parts.status,
+						body: parse_error_body(&body.unwrap_or_default(), parts.status),
+					}
+				}
+				Err(TungsteniteError::Io(error)) if attempt < self.max_retries => {
+					attempt += 1;
+					warn!("Failed to connect to the API: {}. Retrying...", error);
+					self.wait_before_retry(attempt).await;
+					continue;
+				}
+				Err(error) => return Err(client_error(error)),
+			};
+
+			if !renewed && self.can_renew_access_token(&error, &headers) {
+				let access_token = self.renew_access_token(&headers).await?;
+				headers.typed_insert(access_token);
+				renewed = true;
+				continue;
+			}
+
+			return Err(error);
+		}
+	}
+
+	/// Send a request to the API, retrying it if it fails due to a transient
+	/// error, and parse the response.
+	async fn execute<E>(
+		&self,
+		url: &Url,
+		query: &E::RequestQuery,
+		headers: &HeaderMap,
+		body: &serde_json::Value,
+	) -> Result<ApiSuccessResponse<E>, ApiErrorResponse>
+	where
+		E: ApiEndpoint,
+		<E::RequestBody as Preprocessable>::Processed: Send,
+		E::ResponseBody: DeserializeOwned,
+	{
+		let mut attempt = 0;
+		let response = loop {
+			let builder = REQUEST_CLIENT
+				.get_or_init(reqwest::Client::new)
+				.request(E::METHOD, url.clone())
+				.query(query)
+				.headers(headers.clone());
+			let response = if body.is_null() {
+				builder
+			} else {
+				builder.json(body)
+			}
+			.send()
+			.await;
+
+			let should_retry = match &response {
+				Ok(response) => is_retryable_status(&E::METHOD, response.status()),
+				Err(error) => is_retryable_error(&E::METHOD, error),
+			};
+			if !should_retry || attempt >= self.max_retries {
+				break response.map_err(client_error)?;
+			}
+
+			attempt += 1;
+			warn!(
+				"Request to `{}` failed. Retrying ({}/{})...",
+				url, attempt, self.max_retries
+			);
+			self.wait_before_retry(attempt).await;
+		};
+
+		let status_code = response.status();
+		let headers = E::ResponseHeaders::from_header_map(response.headers())
+			.map_err(|err| client_error(format!("invalid response headers: {err}")))?;
+		let body = response.bytes().await.map_err(client_error)?;
+
+		match serde_json::from_slice::<ApiResponseBody<E::ResponseBody>>(&body) {
+			Ok(ApiResponseBody::Success(ApiSuccessResponseBody {
+				success: _,
+				response: body,
+			})) => Ok(ApiSuccessResponse {
+				status_code,
+				headers,
+				body,
+			}),
+			Ok(ApiResponseBody::Error(error)) => Err(ApiErrorResponse {
+				status_code,
+				body: error,
+			}),
+			Err(error) => {
+				error!("Failed to parse response body: {}", error);
+				Err(ApiErrorResponse {
+					status_code,
+					body: parse_error_body(&body, status_code),
+				})
+			}
+		}
+	}
+
+	/// Renews the access token using the refresh token of the client. The
+	/// renewed token is stored in the client and used for all subsequent
+	/// requests.
+	async fn renew_access_token(
+		&self,
+		headers: &HeaderMap,
+	) -> Result<BearerToken, ApiErrorResponse> {
+		let Some(refresh_token) = self.refresh_token.clone() else {
+			return Err(ApiErrorResponse::error(
+				ErrorType::AuthorizationTokenInvalid,
+			));
+		};
+		info!("Access token has expired. Renewing it using the refresh token");
+
+		let headers = RenewAccessTokenRequestHeaders {
+			refresh_token,
+			user_agent: headers
+				.typed_get::<UserAgent>()
+				.unwrap_or_else(|| UserAgent::from_static(DEFAULT_USER_AGENT)),
+		}
+		.to_header_map();
+		let url = self
+			.base_url
+			.join(RenewAccessTokenPath.to_string().as_str())
+			.map_err(client_error)?;
+
+		let access_token = self
+			.execute::<RenewAccessTokenRequest>(&url, &(), &headers, &serde_json::Value::Null)
+			.await?
+			.body
+			.access_token;
+		let access_token = BearerToken::from_str(&access_token).map_err(client_error)?;
+
+		*self
+			.access_token
+			.write()
+			.expect("access token lock poisoned") = Some(access_token.clone());
+
+		Ok(access_token)
+	}
+
+	/// Replaces the access token in the given headers with the last renewed
+	/// access token, if any. Requests without an `Authorization` header are
+	/// left untouched.
+	fn apply_renewed_access_token(&self, headers: &mut HeaderMap) {
+		if !headers.contains_key(AUTHORIZATION) {
+			return;
+		}
+		if let Some(access_token) = self.renewed_access_token() {
+			headers.typed_insert(access_token);
+		}
+	}
+
+	/// Checks if the given error was caused by an expired access token, which
+	/// can be renewed using the refresh token of the client.
+	fn can_renew_access_token(&self, error: &ApiErrorResponse, headers: &HeaderMap) -> bool {
+		self.refresh_token.is_some() &&
+			headers.contains_key(AUTHORIZATION) &&
+			error.status_code == StatusCode::UNAUTHORIZED &&
+			matches!(error.body.error, ErrorType::AuthorizationTokenInvalid)
+	}
+
+	/// Waits before retrying a request, backing off exponentially with every
+	/// attempt.
+	async fn wait_before_retry(&self, attempt: u32) {
+		tokio::time::sleep(
+			self.retry_delay
+				.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1))),
+		)
+		.await;
+	}
+}
+
+/// Checks if a request that got a response with the given status code can be
+/// retried. Only idempotent requests are retried, since the request might have
+/// been processed by the API even though the response says otherwise.
+fn is_retryable_status(method: &Method, status_code: StatusCode) -> bool {
+	is_idempotent(method) &&
+		matches!(
+			status_code,
+			StatusCode::TOO_MANY_REQUESTS |
+				StatusCode::BAD_GATEWAY |
+				StatusCode::SERVICE_UNAVAILABLE |
+				StatusCode::GATEWAY_TIMEOUT
+		)
+}
+
+/// Checks if a request that failed with the given error can be retried. A
+/// request that failed to connect never reached the API, and can always be
+/// retried.
+fn is_retryable_error(method: &Method, error: &reqwest::Error) -> bool {
+	error.is_connect() || (is_idempotent(method) && error.is_timeout())
+}
+
+/// Checks if a request with the given method can safely be sent more than once.
+fn is_idempotent(method: &Method) -> bool {
+	matches!(
+		*method,
+		Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+	)
+}
+
+/// Parses the body of an error response. If the body isn't a valid error
+/// response (for example, if the error came from a proxy in front of the API),
+/// an error is created from the status code instead.
+fn parse_error_body(body: &[u8], status_code: StatusCode) -> ApiErrorResponseBody {
+	serde_json::from_slice(body).unwrap_or_else(|_| ApiErrorResponseBody {
+		success: False,
+		error: ErrorType::server_error(status_code),
+		message: String::from_utf8_lossy(body).into_owned(),
+	})
+}
+
+/// Creates an error response for an error that occurred on the client side,
+/// such as failing to serialize a request or to reach the API.
+fn client_error(error: impl Display) -> ApiErrorResponse {
+	ApiErrorResponse::error_with_message(ErrorType::server_error(&error), error)
+}
+
+#[cfg(test)]
+mod tests {
+	use http::HeaderValue;
+
+	use super::*;
+
+	#[test]
+	fn assert_only_idempotent_requests_are_retried() {
+		assert!(is_retryable_status(
+			&Method::GET,
+			StatusCode::SERVICE_UNAVAILABLE
+		));
+		assert!(is_retryable_status(
+			&Method::PUT,
+			StatusCode::TOO_MANY_REQUESTS
+		));
+		assert!(!is_retryable_status(
+			&Method::POST,
+			StatusCode::SERVICE_UNAVAILABLE
+		));
+		assert!(!is_retryable_status(
+			&Method::PATCH,
+			StatusCode::BAD_GATEWAY
+		));
+	}
+
+	#[test]
+	fn assert_client_errors_are_not_retried() {
+		assert!(!is_retryable_status(&Method::GET, StatusCode::BAD_REQUEST));
+		assert!(!is_retryable_status(
+			&Method::GET,
+			StatusCode::INTERNAL_SERVER_ERROR
+		));
+		assert!(!is_retryable_status(&Method::DELETE, StatusCode::NOT_FOUND));
+	}
+
+	#[test]
+	fn assert_error_body_is_parsed() {
+		let body = ApiErrorResponse::error(ErrorType::ResourceDoesNotExist).body;
+
+		assert_eq!(
+			parse_error_body(&serde_json::to_vec(&body).unwrap(), StatusCode::NOT_FOUND),
+			body
+		);
+	}
+
+	#[test]
+	fn assert_invalid_error_body_falls_back_to_status() {
+		let body = parse_error_body(b"<html>Bad Gateway</html>", StatusCode::BAD_GATEWAY);
+
+		assert_eq!(body.error, ErrorType::InternalServerError);
+		assert_eq!(body.message, "<html>Bad Gateway</html>");
+	}
+
+	#[test]
+	fn assert_only_expired_access_tokens_are_renewed() {
+		let client = ApiClient::builder()
+			.refresh_token(Some(BearerToken::from_str("refresh-token").unwrap()))
+			.build();
+		let expired = ApiErrorResponse::error(ErrorType::AuthorizationTokenInvalid);
+		let mut headers = HeaderMap::new();
+		headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer token"));
+
+		assert!(client.can_renew_access_token(&expired, &headers));
+		assert!(!client.can_renew_access_token(&expired, &HeaderMap::new()));
+		assert!(!client
+			.can_renew_access_token(&ApiErrorResponse::error(ErrorType::Unauthorized), &headers));
+		assert!(!ApiClient::default().can_renew_access_token(&expired, &headers));
+	}
+}
@@ -0,0 +1,19 @@
+//! A typed client for the Patr API. Every request made using this client is
+//! described by an [`ApiEndpoint`][1], so the path, query, headers and bodies
+//! of the request and the response are all checked at compile time.
+//!
+//! The client takes care of renewing expired access tokens (if a refresh token
+//! is provided), retrying requests that fail due to transient errors, iterating
+//! over paginated list endpoints and connecting to WebSocket endpoints.
+//!
+//! [1]: models::ApiEndpoint
+
+/// The [`ApiClient`] itself, along with the logic to make requests, retry them
+/// and renew access tokens.
+mod client;
+/// Iterating over all the pages of a paginated list endpoint.
+mod paginate;
+/// A typed WebSocket connection to a streaming endpoint.
+mod websocket;
+
+pub use self::{client::*, websocket::*};
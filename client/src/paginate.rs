@@ -0,0 +1,195 @@
+use futures::{stream, Stream};
+use models::{prelude::*, utils::HasHeader};
+use preprocess::Preprocessable;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::ApiClient;
+
+impl ApiClient {
+	/// Iterate over all the pages of a paginated list endpoint, starting from
+	/// the page given in the request. Each item of the stream is the response
+	/// for one page. The stream ends once all the items counted by the
+	/// [`TotalCountHeader`] of the response have been fetched.
+	///
+	/// Example:
+	/// ```rust
+	/// # use std::pin::pin;
+	/// #
+	/// # use futures::TryStreamExt;
+	/// # use models::{api::workspace::deployment::ListDeploymentRequest, prelude::*};
+	/// # use patr_client::ApiClient;
+	/// # async fn example(
+	/// #     client: ApiClient,
+	/// #     request: ApiRequest<ListDeploymentRequest>,
+	/// # ) -> Result<(), ApiErrorResponse> {
+	/// let mut pages = pin!(client.paginate(request));
+	/// while let Some(page) = pages.try_next().await? {
+	///     // Do something with `page.body` ....
+	/// }
+	/// # Ok(())
+	/// # }
+	/// ```
+	pub fn paginate<E, Query>(
+		&self,
+		ApiRequest {
+			path,
+			query,
+			headers,
+			body,
+		}: ApiRequest<E>,
+	) -> impl Stream<Item = Result<ApiSuccessResponse<E>, ApiErrorResponse>> + '_
+	where
+		E: ApiEndpoint<RequestQuery = Paginated<Query>>,
+		<E::RequestBody as Preprocessable>::Processed: Send,
+		E::RequestBody: Serialize + Clone,
+		E::ResponseBody: DeserializeOwned,
+		E::ResponseHeaders: HasHeader<TotalCountHeader>,
+		Query: Clone + 'static,
+	{
+		stream::try_unfold(Some(query), move |query| {
+			let request = query.map(|query| {
+				ApiRequest::<E>::builder()
+					.path(path.clone())
+					.query(query)
+					.headers(headers.clone())
+					.body(body.clone())
+					.build()
+			});
+			async move {
+				let Some(request) = request else {
+					return Ok(None);
+				};
+				let query = request.query.clone();
+
+				let response = self.make_request(request).await?;
+
+				let total_count = HasHeader::<TotalCountHeader>::get_header(&response.headers).0;
+				let next_query = get_next_page(query, total_count);
+
+				Ok(Some((response, next_query)))
+			}
+		})
+	}
+
+	/// Iterate over all the pages of a cursor paginated list endpoint, starting
+	/// from the page given in the request. Each item of the stream is the
+	/// response for one page. The stream follows the `next` link of the
+	/// [`LinkHeader`] of each response, and ends once there are no more pages.
+	///
+	/// Example:
+	/// ```rust
+	/// # use std::pin::pin;
+	/// #
+	/// # use futures::TryStreamExt;
+	/// # use models::{prelude::*, utils::HasHeader};
+	/// # use patr_client::ApiClient;
+	/// # async fn example<E, Query>(
+	/// #     client: ApiClient,
+	/// #     request: ApiRequest<E>,
+	/// # ) -> Result<(), ApiErrorResponse>
+	/// # where
+	/// #     E: ApiEndpoint<RequestQuery = CursorPaginated<Query>>,
+	/// #     <E::RequestBody as preprocess::Preprocessable>::Processed: Send,
+	/// #     E::RequestBody: serde::Serialize + Clone,
+	/// #     E::ResponseBody: serde::de::DeserializeOwned,
+	/// #     E::ResponseHeaders: HasHeader<LinkHeader>,
+	/// #     Query: Clone + 'static,
+	/// # {
+	/// let mut pages = pin!(client.paginate_cursor(request));
+	/// while let Some(page) = pages.try_next().await? {
+	///     // Do something with `page.body` ....
+	/// }
+	/// # Ok(())
+	/// # }
+	/// ```
+	pub fn paginate_cursor<E, Query>(
+		&self,
+		ApiRequest {
+			path,
+			query,
+			headers,
+			body,
+		}: ApiRequest<E>,
+	) -> impl Stream<Item = Result<ApiSuccessResponse<E>, ApiErrorResponse>> + '_
+	where
+		E: ApiEndpoint<RequestQuery = CursorPaginated<Query>>,
+		<E::RequestBody as Preprocessable>::Processed: Send,
+		E::RequestBody: Serialize + Clone,
+		E::ResponseBody: DeserializeOwned,
+		E::ResponseHeaders: HasHeader<LinkHeader>,
+		Query: Clone + 'static,
+	{
+		stream::try_unfold(Some(query), move |query| {
+			let request = query.map(|query| {
+				ApiRequest::<E>::builder()
+					.path(path.clone())
+					.query(query)
+					.headers(headers.clone())
+					.body(body.clone())
+					.build()
+			});
+			async move {
+				let Some(request) = request else {
+					return Ok(None);
+				};
+				let CursorPaginated { data, count, .. } = request.query.clone();
+
+				let response = self.make_request(request).await?;
+
+				let next_query = HasHeader::<LinkHeader>::get_header(&response.headers)
+					.next_cursor()
+					.map(|after| CursorPaginated {
+						data,
+						count,
+						after: Some(after),
+						before: None,
+					});
+
+				Ok(Some((response, next_query)))
+			}
+		})
+	}
+}
+
+/// Get the query for the page after the given one, if the pages up to (and
+/// including) the given one don't have all of the `total_count` items
+fn get_next_page<Query>(
+	Paginated { data, count, page }: Paginated<Query>,
+	total_count: usize,
+) -> Option<Paginated<Query>> {
+	let fetched = page.saturating_add(1).saturating_mul(count);
+	(count > 0 && fetched < total_count).then_some(Paginated {
+		data,
+		count,
+		page: page + 1,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Creates the query for the given page, with the given number of items in
+	/// each page
+	fn get_page(page: usize, count: usize) -> Paginated {
+		Paginated {
+			data: (),
+			count,
+			page,
+		}
+	}
+
+	#[test]
+	fn assert_pages_continue_until_all_items_are_fetched() {
+		assert_eq!(get_next_page(get_page(0, 10), 25), Some(get_page(1, 10)));
+		assert_eq!(get_next_page(get_page(1, 10), 25), Some(get_page(2, 10)));
+		assert_eq!(get_next_page(get_page(2, 10), 25), None);
+		assert_eq!(get_next_page(get_page(1, 10), 20), None);
+	}
+
+	#[test]
+	fn assert_empty_pages_end_the_list() {
+		assert_eq!(get_next_page(get_page(0, 10), 0), None);
+		assert_eq!(get_next_page(get_page(0, 0), 25), None);
+	}
+}
@@ -0,0 +1,117 @@
+use std::{
+	marker::PhantomData,
+	pin::Pin,
+	task::{Context, Poll},
+};
+
+use futures::{ready, Sink, SinkExt, Stream, StreamExt};
+use models::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+	tungstenite::{Error as TungsteniteError, Message},
+	MaybeTlsStream,
+	WebSocketStream,
+};
+
+/// A WebSocket connection to a streaming endpoint of the API. Messages received
+/// from the API are parsed as `ServerMsg`, and messages sent to the API are
+/// serialized from `ClientMsg`.
+///
+/// This implements both [`Stream`] and [`Sink`], so it can be split into a
+/// receiving half and a sending half using [`StreamExt::split`].
+pub struct ApiWebSocket<ServerMsg, ClientMsg> {
+	/// The underlying WebSocket connection.
+	inner: WebSocketStream<MaybeTlsStream<TcpStream>>,
+	/// The types of messages sent over the connection. A function pointer is
+	/// used so that the connection is [`Send`] and [`Unpin`] regardless of the
+	/// message types.
+	phantom: PhantomData<fn() -> (ServerMsg, ClientMsg)>,
+}
+
+impl<ServerMsg, ClientMsg> ApiWebSocket<ServerMsg, ClientMsg> {
+	/// Wraps a WebSocket connection that has already been established.
+	pub(crate) fn new(inner: WebSocketStream<MaybeTlsStream<TcpStream>>) -> Self {
+		Self {
+			inner,
+			phantom: PhantomData,
+		}
+	}
+
+	/// Closes the connection to the API.
+	pub async fn close(mut self) -> Result<(), ErrorType> {
+		self.inner
+			.close(None)
+			.await
+			.map_err(ErrorType::server_error)
+	}
+}
+
+impl<ServerMsg, ClientMsg> Stream for ApiWebSocket<ServerMsg, ClientMsg>
+where
+	ServerMsg: DeserializeOwned,
+{
+	type Item = Result<ServerMsg, ErrorType>;
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		loop {
+			let message = match ready!(self.inner.poll_next_unpin(cx)) {
+				Some(Ok(message)) => message,
+				Some(Err(TungsteniteError::ConnectionClosed)) | None => return Poll::Ready(None),
+				Some(Err(err)) => {
+					warn!("Error from websocket stream: {}", err);
+					return Poll::Ready(Some(Err(ErrorType::server_error(err))));
+				}
+			};
+
+			let parsed = match message {
+				Message::Text(text) => serde_json::from_str(&text)
+					.inspect_err(|err| warn!("Error parsing text as JSON: {}", err)),
+				Message::Binary(bin) => serde_json::from_slice(&bin).inspect_err(|err| {
+					warn!(
+						"Error parsing binary `{}` as JSON: {}",
+						String::from_utf8_lossy(&bin),
+						err
+					)
+				}),
+				Message::Close(_) => return Poll::Ready(None),
+				// Pings are answered by the underlying connection itself
+				Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => continue,
+			};
+
+			return Poll::Ready(Some(parsed.map_err(ErrorType::server_error)));
+		}
+	}
+}
+
+impl<ServerMsg, ClientMsg> Sink<ClientMsg> for ApiWebSocket<ServerMsg, ClientMsg>
+where
+	ClientMsg: Serialize,
+{
+	type Error = ErrorType;
+
+	fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner
+			.poll_ready_unpin(cx)
+			.map_err(ErrorType::server_error)
+	}
+
+	fn start_send(mut self: Pin<&mut Self>, item: ClientMsg) -> Result<(), Self::Error> {
+		let text = serde_json::to_string(&item).map_err(ErrorType::server_error)?;
+		self.inner
+			.start_send_unpin(Message::Text(text))
+			.map_err(ErrorType::server_error)
+	}
+
+	fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner
+			.poll_flush_unpin(cx)
+			.map_err(ErrorType::server_error)
+	}
+
+	fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner
+			.poll_close_unpin(cx)
+			.map_err(ErrorType::server_error)
+	}
+}
@@ -11,7 +11,9 @@ use worker::*;
 
 use self::{models::IngressKVData, utils::constants};
 
+/// The configuration of a domain that is stored in the KV namespace
 mod models;
+/// Constants and helpers used by the worker
 mod utils;
 
 /// The main function that is called when a request is made to the worker.
@@ -1,25 +1,44 @@
 use serde::{Deserialize, Serialize};
 
+/// The data stored in the KV namespace for a domain, that decides where the
+/// requests made to it are sent
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum IngressKVData {
+	/// Redirect the requests to a URL
 	#[serde(rename_all = "camelCase")]
 	Redirect {
+		/// The URL to redirect to
 		to: String,
+		/// Whether the redirect is permanent or temporary
 		permanent_redirect: bool,
+		/// Whether to redirect to the URL over HTTP instead of HTTPS
 		http_only: bool,
 	},
+	/// Proxy the requests to a URL
 	#[serde(rename_all = "camelCase")]
-	Proxy { to: String, http_only: bool },
+	Proxy {
+		/// The URL to proxy the requests to
+		to: String,
+		/// Whether to proxy the requests over HTTP instead of HTTPS
+		http_only: bool,
+	},
+	/// Serve the files of a static site from the R2 bucket
 	#[serde(rename_all = "camelCase")]
 	StaticSite {
+		/// The ID of the static site
 		static_site_id: String,
+		/// The ID of the upload of the static site to serve the files of
 		upload_id: String,
 	},
+	/// Proxy the requests to a port of a deployment
 	#[serde(rename_all = "camelCase")]
 	Deployment {
+		/// The ID of the deployment
 		deployment_id: String,
+		/// The port of the deployment to proxy the requests to
 		port: u16,
+		/// The region that the deployment is running in
 		region: String,
 	},
 }
@@ -21,6 +21,16 @@ where
 	endpoint: PhantomData<E>,
 }
 
+impl<E> Default for AuthenticationLayer<E>
+where
+	E: ApiEndpoint,
+	<E::RequestBody as Preprocessable>::Processed: Send,
+{
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 impl<E> AuthenticationLayer<E>
 where
 	E: ApiEndpoint,
@@ -88,17 +98,19 @@ where
 	{
 		let (parts, body) = req.into_parts();
 
-		if let Ok(_) = Cookie::parse_encoded(
+		if Cookie::parse_encoded(
 			parts
 				.headers
 				.get(http::header::COOKIE)
 				.and_then(|v| v.to_str().ok())
 				.unwrap_or_default(),
-		) {
+		)
+		.is_ok()
+		{
 			// TODO parse cookie and check if it's valid
 			let req = Request::from_parts(parts, body);
 			let future = self.inner.call(req);
-			Box::pin(async move { future.await })
+			Box::pin(future)
 		} else {
 			leptos_axum::redirect(&LoggedOutRoute::Login.to_string());
 
@@ -0,0 +1,41 @@
+use models::api::workspace::container_registry::*;
+
+use crate::prelude::*;
+
+/// List the container repositories of a workspace, along with the storage used
+/// by the workspace
+#[server(
+	ListContainerRepositoriesFn,
+	endpoint = "/infrastructure/container-registry/list"
+)]
+pub async fn list_container_repositories(
+	access_token: Option<String>,
+	workspace_id: Uuid,
+) -> Result<ListContainerRepositoriesResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = BearerToken::from_str(access_token.unwrap().as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	make_api_call::<ListContainerRepositoriesRequest>(
+		ApiRequest::builder()
+			.path(ListContainerRepositoriesPath { workspace_id })
+			.query(Paginated {
+				data: ListContainerRepositoriesQuery {
+					filter: None,
+					sort: None,
+				},
+				page: 0,
+				count: 100,
+			})
+			.headers(ListContainerRepositoriesRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(ListContainerRepositoriesRequest)
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
@@ -2,6 +2,7 @@ use models::api::workspace::database::*;
 
 use crate::prelude::*;
 
+#[allow(clippy::too_many_arguments)]
 #[server(CreateDatabaseFn, endpoint = "/infrastructure/database/create")]
 pub async fn create_database(
 	name: String,
@@ -18,8 +19,9 @@ pub async fn create_database(
 	let access_token = BearerToken::from_str(access_token.unwrap().as_str())
 		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
 
-	let workspace_id = workspace_id
-		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+	let workspace_id = workspace_id.ok_or(ServerFnError::WrappedServerError(
+		ErrorType::WrongParameters,
+	))?;
 
 	let runner_id = Uuid::parse_str(runner_id.as_str())
 		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
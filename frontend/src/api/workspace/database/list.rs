@@ -12,8 +12,9 @@ pub async fn list_database(
 	let access_token = BearerToken::from_str(access_token.unwrap().as_str())
 		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
 
-	let workspace_id = workspace_id
-		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+	let workspace_id = workspace_id.ok_or(ServerFnError::WrappedServerError(
+		ErrorType::WrongParameters,
+	))?;
 
 	make_api_call::<ListDatabaseRequest>(
 		ApiRequest::builder()
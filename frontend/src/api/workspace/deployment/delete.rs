@@ -13,8 +13,9 @@ pub async fn delete_deployment(
 	let access_token = BearerToken::from_str(access_token.unwrap().as_str())
 		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
 
-	let workspace_id = workspace_id
-		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+	let workspace_id = workspace_id.ok_or(ServerFnError::WrappedServerError(
+		ErrorType::WrongParameters,
+	))?;
 
 	make_api_call::<DeleteDeploymentRequest>(
 		ApiRequest::builder()
@@ -26,6 +27,7 @@ pub async fn delete_deployment(
 			.headers(DeleteDeploymentRequestHeaders {
 				authorization: access_token,
 				user_agent: UserAgent::from_static("todo"),
+				if_match: None,
 			})
 			.body(DeleteDeploymentRequest)
 			.build(),
@@ -15,11 +15,13 @@ pub async fn update_deployment(
 	let access_token = BearerToken::from_str(access_token.unwrap().as_str())
 		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
 
-	let workspace_id = workspace_id
-		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+	let workspace_id = workspace_id.ok_or(ServerFnError::WrappedServerError(
+		ErrorType::WrongParameters,
+	))?;
 
-	let deployment_id = deployment_id
-		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+	let deployment_id = deployment_id.ok_or(ServerFnError::WrappedServerError(
+		ErrorType::WrongParameters,
+	))?;
 
 	make_api_call::<UpdateDeploymentRequest>(
 		ApiRequest::builder()
@@ -31,6 +33,7 @@ pub async fn update_deployment(
 			.headers(UpdateDeploymentRequestHeaders {
 				authorization: access_token,
 				user_agent: UserAgent::from_static("todo"),
+				if_match: None,
 			})
 			.body(deployment_info)
 			.build(),
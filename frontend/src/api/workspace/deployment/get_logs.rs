@@ -13,13 +13,15 @@ pub async fn get_deployment_logs(
 ) -> Result<GetDeploymentLogsResponse, ServerFnError<ErrorType>> {
 	use std::str::FromStr;
 
-	let access_token = access_token
-		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = access_token.ok_or(ServerFnError::WrappedServerError(
+		ErrorType::MalformedAccessToken,
+	))?;
 	let access_token = BearerToken::from_str(access_token.as_str())
 		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
 
-	let workspace_id = workspace_id
-		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+	let workspace_id = workspace_id.ok_or(ServerFnError::WrappedServerError(
+		ErrorType::WrongParameters,
+	))?;
 
 	make_api_call::<GetDeploymentLogsRequest>(
 		ApiRequest::builder()
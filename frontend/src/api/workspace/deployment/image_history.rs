@@ -22,7 +22,14 @@ pub async fn get_deployment_image_history(
 				deployment_id,
 				workspace_id,
 			})
-			.query(Paginated::default())
+			.query(Paginated {
+				data: ListDeploymentDeployHistoryQuery {
+					after: None,
+					before: None,
+				},
+				count: Paginated::<()>::DEFAULT_PAGE_SIZE,
+				page: 0,
+			})
 			.headers(ListDeploymentDeployHistoryRequestHeaders {
 				authorization: access_token,
 				user_agent: UserAgent::from_static("todo"),
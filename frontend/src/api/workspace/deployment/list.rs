@@ -1,7 +1,6 @@
 use std::{thread, time};
 
 use models::api::workspace::deployment::*;
-use server_fn::codec::FromRes;
 
 use crate::prelude::*;
 
@@ -23,7 +22,10 @@ pub async fn list_deployments(
 		ApiRequest::builder()
 			.path(ListDeploymentPath { workspace_id })
 			.query(Paginated {
-				data: (),
+				data: ListDeploymentQuery {
+					filter: None,
+					sort: None,
+				},
 				page: page.unwrap_or(0),
 				count: count.unwrap_or(10),
 			})
@@ -6,6 +6,7 @@ mod get_logs;
 mod image_history;
 mod list;
 mod list_machines;
+mod rollback;
 mod start;
 mod stop;
 mod stream_logs;
@@ -19,6 +20,7 @@ pub use self::{
 	image_history::*,
 	list::*,
 	list_machines::*,
+	rollback::*,
 	start::*,
 	stop::*,
 	stream_logs::*,
@@ -0,0 +1,42 @@
+use models::api::workspace::deployment::deploy_history::*;
+
+use crate::prelude::*;
+
+/// Roll a deployment back to an image from its deploy history
+#[server(RollbackDeploymentFn, endpoint = "/infrastructure/deployment/rollback")]
+pub async fn rollback_deployment(
+	access_token: Option<String>,
+	workspace_id: Uuid,
+	deployment_id: Uuid,
+	image_digest: String,
+	restore_running_details: bool,
+) -> Result<RollbackDeploymentResponse, ServerFnError<ErrorType>> {
+	use std::str::FromStr;
+
+	let access_token = access_token.ok_or(ServerFnError::WrappedServerError(
+		ErrorType::MalformedAccessToken,
+	))?;
+	let access_token = BearerToken::from_str(access_token.as_str())
+		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+
+	make_api_call::<RollbackDeploymentRequest>(
+		ApiRequest::builder()
+			.path(RollbackDeploymentPath {
+				workspace_id,
+				deployment_id,
+				image_digest,
+			})
+			.query(())
+			.headers(RollbackDeploymentRequestHeaders {
+				authorization: access_token,
+				user_agent: UserAgent::from_static("todo"),
+			})
+			.body(RollbackDeploymentRequest {
+				restore_running_details,
+			})
+			.build(),
+	)
+	.await
+	.map(|res| res.body)
+	.map_err(ServerFnError::WrappedServerError)
+}
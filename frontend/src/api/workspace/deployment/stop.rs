@@ -10,8 +10,9 @@ pub async fn stop_deployment(
 ) -> Result<StopDeploymentResponse, ServerFnError<ErrorType>> {
 	use std::str::FromStr;
 
-	let access_token = access_token
-		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = access_token.ok_or(ServerFnError::WrappedServerError(
+		ErrorType::MalformedAccessToken,
+	))?;
 	let access_token = BearerToken::from_str(access_token.as_str())
 		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
 
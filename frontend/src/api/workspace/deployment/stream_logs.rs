@@ -12,13 +12,15 @@ pub async fn stream_deployment_logs(
 ) -> Result<(), ServerFnError<ErrorType>> {
 	use std::str::FromStr;
 
-	let access_token = access_token
-		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = access_token.ok_or(ServerFnError::WrappedServerError(
+		ErrorType::MalformedAccessToken,
+	))?;
 	let _access_token = BearerToken::from_str(access_token.as_str())
 		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
 
-	let _workspace_id = workspace_id
-		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::WrongParameters))?;
+	let _workspace_id = workspace_id.ok_or(ServerFnError::WrappedServerError(
+		ErrorType::WrongParameters,
+	))?;
 
 	// let x = make_api_call::<StreamDeploymentLogsRequest>(
 	// 	ApiRequest::builder()
@@ -9,8 +9,9 @@ pub async fn get_workspace_info(
 ) -> Result<GetWorkspaceInfoResponse, ServerFnError<ErrorType>> {
 	use std::str::FromStr;
 
-	let access_token = access_token
-		.ok_or_else(|| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
+	let access_token = access_token.ok_or(ServerFnError::WrappedServerError(
+		ErrorType::MalformedAccessToken,
+	))?;
 	let access_token = BearerToken::from_str(access_token.as_str())
 		.map_err(|_| ServerFnError::WrappedServerError(ErrorType::MalformedAccessToken))?;
 
@@ -29,5 +30,5 @@ pub async fn get_workspace_info(
 
 	api_response
 		.map(|res| res.body)
-		.map_err(|err| ServerFnError::WrappedServerError(err))
+		.map_err(ServerFnError::WrappedServerError)
 }
@@ -28,5 +28,5 @@ pub async fn list_user_workspace(
 
 	api_response
 		.map(|res| res.body)
-		.map_err(|err| ServerFnError::WrappedServerError(err))
+		.map_err(ServerFnError::WrappedServerError)
 }
@@ -36,6 +36,7 @@ pub fn get_managed_url_type(
 	}
 }
 
+#[allow(clippy::too_many_arguments)]
 #[server(CreateManagedURLs, endpoint = "/domain-config/managed-url/create")]
 pub async fn create_managed_url(
 	workspace_id: Option<Uuid>,
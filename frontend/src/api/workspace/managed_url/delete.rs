@@ -29,6 +29,7 @@ pub async fn delete_managed_url(
 			.headers(DeleteManagedURLRequestHeaders {
 				authorization: access_token,
 				user_agent: UserAgent::from_static("todo"),
+				if_match: None,
 			})
 			.body(DeleteManagedURLRequest)
 			.build(),
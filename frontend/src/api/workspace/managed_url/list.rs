@@ -22,9 +22,8 @@ pub async fn list_managed_urls(
 				count: 10,
 				page: 0,
 				data: ListManagedURLQuery {
-					order: None,
-					order_by: None,
 					filter: None,
+					sort: None,
 				},
 			})
 			.headers(ListManagedURLRequestHeaders {
@@ -2,6 +2,7 @@ use models::api::workspace::managed_url::*;
 
 use crate::prelude::*;
 
+#[allow(clippy::too_many_arguments)]
 #[server(UpdateManagedUrlFn, endpoint = "/domain-config/managed-url/update")]
 pub async fn update_managed_url(
 	workspace_id: Option<String>,
@@ -41,6 +42,7 @@ pub async fn update_managed_url(
 			.headers(UpdateManagedURLRequestHeaders {
 				authorization: access_token,
 				user_agent: UserAgent::from_static("todo"),
+				if_match: None,
 			})
 			.body(req_body)
 			.build(),
@@ -1,3 +1,4 @@
+mod container_registry;
 mod create_workspace;
 mod database;
 mod deployment;
@@ -9,6 +10,7 @@ mod rbac;
 mod runner;
 
 pub use self::{
+	container_registry::*,
 	create_workspace::*,
 	database::*,
 	deployment::*,
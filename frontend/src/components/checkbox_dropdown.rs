@@ -126,7 +126,7 @@ pub fn CheckboxDropdown(
 						</For>
 
 						<Show
-							when={move || store_options.with_value(|opt| opt.get().len() == 0)}
+							when={move || store_options.with_value(|opt| opt.get().is_empty())}
 						>
 							<li
 								class={"px-xl py-sm border-border-color border-b-2 bg-[#292548]
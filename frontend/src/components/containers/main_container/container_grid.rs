@@ -1,4 +1,3 @@
-use std::str::FromStr;
 
 use strum::Display;
 
@@ -36,7 +35,7 @@ pub fn ContainerGrid(
 	let style = move || {
 		format!(
 			"grid-template-columns: repeat({}, minmax({}, {}));",
-			auto_sizing.to_string(),
+			auto_sizing,
 			min_width.get(),
 			max_width.get()
 		)
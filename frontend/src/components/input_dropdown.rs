@@ -126,10 +126,8 @@ pub fn InputDropdown(
 
 	let handle_keydown_input = move |e: KeyboardEvent| {
 		e.stop_propagation();
-		if !disabled.get() && !loading.get() {
-			if e.key() == "Enter" || e.key() == "Space" {
-				show_dropdown.update(|val| *val = !*val);
-			}
+		if !disabled.get() && !loading.get() && (e.key() == "Enter" || e.key() == "Space") {
+			show_dropdown.update(|val| *val = !*val);
 		}
 	};
 
@@ -197,7 +195,7 @@ pub fn InputDropdown(
 						</For>
 
 						<Show
-							when={move || store_options.with_value(|opt| opt.get().len() == 0)}
+							when={move || store_options.with_value(|opt| opt.get().is_empty())}
 						>
 							<li
 								class={"px-xl py-sm flex justify-start items-center border-border-color border-b-2 w-full br-bottom-sm text-disabled"}
@@ -1,4 +1,5 @@
 mod page_description;
+#[allow(clippy::module_inception)]
 mod page_title;
 mod tabs;
 mod title_container;
@@ -50,16 +50,12 @@ pub fn PageTitleContainer(
 				</TitleContainer>
 
 				{
-					description_title.get().and_then(|title| {
-						Some(
-							view! {
+					description_title.get().map(|title| view! {
 								<PageDescription
 									description={title}
 									doc_link={description_link.get()}
 								/>
-							}.into_view()
-						)
-					})
+							}.into_view())
 				}
 			</div>
 
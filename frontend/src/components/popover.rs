@@ -48,7 +48,7 @@ pub fn Popover(
 			return (0., 0.);
 		};
 		let trigger_rect = trigger_rect.get_bounding_client_rect();
-		let (top, left, transform) = match popover_placement {
+		let (top, left, _transform) = match popover_placement {
 			PopoverPlacement::Top => {
 				let Some(window_height) = window_height() else {
 					return (0., 0.);
@@ -187,11 +187,9 @@ pub fn get_popover_offset(
 	popover_rect: &DomRect,
 	placement: PopoverPlacement,
 ) -> Option<(f64, f64)> {
-	let (top, left, transform) = match placement {
+	let (top, left, _transform) = match placement {
 		PopoverPlacement::Top => {
-			let Some(window_height) = window_height() else {
-				return None;
-			};
+			let window_height = window_height()?;
 
 			let popover_height = popover_rect.height();
 			let trigger_top = trigger_rect.top();
@@ -210,9 +208,7 @@ pub fn get_popover_offset(
 			(top, left, transform)
 		}
 		PopoverPlacement::Bottom => {
-			let Some(window_height) = window_height() else {
-				return None;
-			};
+			let window_height = window_height()?;
 
 			let popover_height = popover_rect.height();
 			let trigger_top = trigger_rect.top();
@@ -232,9 +228,7 @@ pub fn get_popover_offset(
 			(top, left, transform)
 		}
 		PopoverPlacement::Left => {
-			let Some(window_width) = window_width() else {
-				return None;
-			};
+			let window_width = window_width()?;
 
 			let popover_width = popover_rect.width();
 			let trigger_left = trigger_rect.left();
@@ -253,9 +247,7 @@ pub fn get_popover_offset(
 			(top, left, transform)
 		}
 		PopoverPlacement::Right => {
-			let Some(window_width) = window_width() else {
-				return None;
-			};
+			let window_width = window_width()?;
 
 			let popover_width = popover_rect.width();
 			let trigger_left = trigger_rect.left();
@@ -1,6 +1,7 @@
 mod custom_skeleton;
 mod infrastructure;
 mod runner;
+#[allow(clippy::module_inception)]
 mod skeleton;
 
 pub use self::{custom_skeleton::*, infrastructure::*, runner::*, skeleton::*};
@@ -50,8 +50,8 @@ pub fn Tooltip(
 					let tooltip_dim = tooltip_ref.get_bounding_client_rect();
 					let parent_dim = parent_ref.get_bounding_client_rect();
 
-					let mouse_x = ev.client_x() as f64;
-					let mouse_y = ev.client_y() as f64;
+					let mouse_x = ev.client_x();
+					let mouse_y = ev.client_y();
 
 					let mouse_outside_tooltip =
 						{ mouse_y + 2.6 * root_font_size < tooltip_dim.top() } ||
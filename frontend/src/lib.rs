@@ -1,4 +1,7 @@
 #![feature(impl_trait_in_assoc_type)]
+// The structs generated by `#[server]` can't be documented, and the macro
+// copies the attributes of the arguments over to them
+#![allow(missing_docs, clippy::missing_docs_in_private_items)]
 
 //! Main dashboard console for Patr
 
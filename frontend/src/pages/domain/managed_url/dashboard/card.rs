@@ -71,50 +71,24 @@ pub fn ManagedUrlCard(
 		},
 	);
 
-	let managed_url = Signal::derive(move || {
-		let domain = domain.get().clone();
-
-		if domain.is_none() {
-			"Cannot Load Domain".to_string()
-		} else if domain.clone().unwrap().is_err() {
-			"Cannot Load Domain".to_string()
-		} else {
-			domain
-				.clone()
-				.unwrap()
-				.unwrap()
-				.workspace_domain
-				.domain
-				.name
-				.clone()
-		}
+	let managed_url = Signal::derive(move || match domain.get() {
+		Some(Ok(domain)) => domain.workspace_domain.domain.name.clone(),
+		_ => "Cannot Load Domain".to_string(),
 	});
 
 	let managed_url_link = Signal::derive({
 		move || {
-			let domain = domain.get().clone();
+			let domain = domain.get();
 
-			let domain_name = if domain.is_none() {
-				"Cannot Load Domain".to_string()
-			} else if domain.clone().unwrap().is_err() {
-				"Cannot Load Domain".to_string()
-			} else {
-				domain
-					.clone()
-					.unwrap()
-					.unwrap()
-					.workspace_domain
-					.domain
-					.name
-					.clone()
+			let domain_name = match &domain {
+				Some(Ok(domain)) => domain.workspace_domain.domain.name.clone(),
+				_ => "Cannot Load Domain".to_string(),
 			};
 
 			store_managed_url.with_value(|managed_url| {
 				managed_url.clone().with(|managed_url| {
-					if managed_url.sub_domain == "@" {
-						domain_name.clone().to_string()
-					} else if domain.is_none() {
-						domain_name.clone().to_string()
+					if managed_url.sub_domain == "@" || domain.is_none() {
+						domain_name.clone()
 					} else {
 						format!("{}.{}", managed_url.sub_domain, domain_name.clone())
 					}
@@ -1,5 +1,9 @@
+use leptos_query::QueryResult;
+use models::api::workspace::container_registry::*;
+use time::format_description;
+
 use super::container_registry_item::ContainerRegistryCard;
-use crate::prelude::*;
+use crate::{prelude::*, queries::*};
 
 /// A Container Registry Item
 #[derive(PartialEq, Eq, Hash, Clone)]
@@ -14,6 +18,51 @@ pub struct ContainerRegistryItem {
 	pub date_created: String,
 }
 
+impl From<WithId<ContainerRepository>> for ContainerRegistryItem {
+	fn from(repository: WithId<ContainerRepository>) -> Self {
+		let format = format_description::parse("[year]-[month]-[day]").unwrap();
+
+		Self {
+			id: repository.id.to_string(),
+			name: repository.data.name,
+			size: format_size(repository.data.size),
+			date_created: repository
+				.data
+				.created
+				.format(&format)
+				.unwrap_or_else(|_| "Invalid Date".to_owned()),
+		}
+	}
+}
+
+/// Formats a number of bytes in a human readable way (eg: `1.50 GB`)
+pub fn format_size(bytes: u64) -> String {
+	const UNITS: [&str; 5] = ["KB", "MB", "GB", "TB", "PB"];
+
+	if bytes < 1024 {
+		return format!("{bytes} B");
+	}
+
+	let mut size = bytes as f64;
+	let mut unit = "B";
+	for next_unit in UNITS {
+		if size < 1024.0 {
+			break;
+		}
+		size /= 1024.0;
+		unit = next_unit;
+	}
+	format!("{size:.2} {unit}")
+}
+
+/// The storage used by the repositories of a workspace, out of its limit
+fn format_usage(usage: ContainerRegistryStorageUsage) -> String {
+	match usage.limit {
+		Some(limit) => format!("{} of {} used", format_size(usage.used), format_size(limit)),
+		None => format!("{} used", format_size(usage.used)),
+	}
+}
+
 #[component]
 pub fn ContainerRegistry() -> impl IntoView {
 	view! {
@@ -25,26 +74,10 @@ pub fn ContainerRegistry() -> impl IntoView {
 
 #[component]
 pub fn ContainerRegistryDashboard() -> impl IntoView {
-	let data = create_rw_signal(vec![
-		ContainerRegistryItem {
-			id: "12".to_owned(),
-			name: "smart-tools-demo".to_owned(),
-			size: "2 GB".to_owned(),
-			date_created: "3 Months Ago".to_owned(),
-		},
-		ContainerRegistryItem {
-			id: "13".to_owned(),
-			name: "patr-website".to_owned(),
-			size: "4.91 GB".to_owned(),
-			date_created: "8 Months Ago".to_owned(),
-		},
-		ContainerRegistryItem {
-			id: "123".to_owned(),
-			name: "docker-registry".to_owned(),
-			size: "26.32 MB".to_owned(),
-			date_created: "1 Year Ago".to_owned(),
-		},
-	]);
+	let QueryResult {
+		data: repositories_list,
+		..
+	} = list_container_repositories_query().use_query(move || AllContainerRepositoriesTag);
 
 	view! {
 		<ContainerHead>
@@ -82,6 +115,17 @@ pub fn ContainerRegistryDashboard() -> impl IntoView {
 		</ContainerHead>
 
 		<ContainerBody class="px-xxl py-xl gap-md">
+			<Transition>
+				{move || match repositories_list.get() {
+					Some(Ok(data)) => view! {
+						<p class="txt-white txt-sm">
+							"Storage: "{format_usage(data.workspace_usage)}
+						</p>
+					}
+						.into_view(),
+					_ => view! {}.into_view(),
+				}}
+			</Transition>
 			<TableDashboard
 				column_grids={vec![5, 2, 4, 1]}
 				headings={vec![
@@ -92,9 +136,29 @@ pub fn ContainerRegistryDashboard() -> impl IntoView {
 				]}
 
 				render_rows={view! {
-					<For each={move || data.get()} key={|state| state.id.clone()} let:child>
-						<ContainerRegistryCard item={child} />
-					</For>
+					<Transition>
+						{move || match repositories_list.get() {
+							Some(Ok(data)) => {
+								view! {
+									<For
+										each={move || {
+											data.repositories
+												.clone()
+												.into_iter()
+												.map(ContainerRegistryItem::from)
+												.collect::<Vec<_>>()
+										}}
+										key={|state| state.id.clone()}
+										let:child
+									>
+										<ContainerRegistryCard item={child} />
+									</For>
+								}
+									.into_view()
+							}
+							_ => view! {}.into_view(),
+						}}
+					</Transition>
 				}
 					.into_view()}
 			/>
@@ -41,9 +41,8 @@ pub fn DeploymentCard(
 
 	let on_click_start_stop = move |ev: &MouseEvent| {
 		ev.prevent_default();
-		let status = store_deployment.with_value(move |deployment| deployment.get().status.clone());
-		let deployment_id =
-			store_deployment.with_value(move |deployment| deployment.get().id.clone());
+		let status = store_deployment.with_value(move |deployment| deployment.get().status);
+		let deployment_id = store_deployment.with_value(move |deployment| deployment.get().id);
 
 		match status {
 			DeploymentStatus::Running => {
@@ -122,9 +121,8 @@ pub fn DeploymentCard(
 				</h4>
 
 				<StatusBadge status={
-					let deployment = deployment.clone();
 					Signal::derive(move || Some(
-						Status::from_deployment_status(deployment.get().status.clone()),
+						Status::from_deployment_status(deployment.get().status),
 					))
 				} />
 			</div>
@@ -183,16 +181,16 @@ pub fn DeploymentCard(
 				<Link
 					disabled={store_deployment
 						.with_value(move |deployment| {
-							deployment.get().status.clone() == DeploymentStatus::Deploying
-								|| deployment.get().status.clone() == DeploymentStatus::Errored
-								|| deployment.get().status.clone() == DeploymentStatus::Unreachable
+							deployment.get().status == DeploymentStatus::Deploying
+								|| deployment.get().status == DeploymentStatus::Errored
+								|| deployment.get().status == DeploymentStatus::Unreachable
 						})}
 					style_variant={LinkStyleVariant::Contained}
 				>
 					{
 						let deployment = store_deployment
 							.with_value(move |deployment| deployment.get());
-						match deployment.status.clone() {
+						match deployment.status {
 							DeploymentStatus::Running => {
 								view! {
 									<Icon
@@ -217,13 +215,10 @@ pub fn DeploymentCard(
 							}
 						}
 					}
-					{format!(
-						"{}",
-						store_deployment
+					{store_deployment
 							.with_value(move |deployment| {
 								deployment.get().status.to_string().to_case(Case::Title)
-							}),
-					)
+							}).to_string()
 						.into_view()}
 				</Link>
 
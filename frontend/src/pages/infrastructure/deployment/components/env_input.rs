@@ -1,6 +1,5 @@
 use std::{collections::BTreeMap, rc::Rc};
 
-use ev::MouseEvent;
 use models::api::workspace::deployment::EnvironmentVariableValue;
 
 use crate::prelude::*;
@@ -59,7 +58,7 @@ pub fn EnvInput(
 									</div>
 
 									<div class="flex-col-1 fr-ct-ct pl-sm">
-										<button on:click={move |ev| {
+										<button on:click={move |_| {
 											on_delete.call(child.0.clone())
 										}}>
 											<Icon
@@ -1,6 +1,5 @@
 use std::{collections::BTreeMap, rc::Rc};
 
-use ev::MouseEvent;
 use models::api::workspace::deployment::ExposedPortType;
 use strum::VariantNames;
 
@@ -110,7 +109,7 @@ pub fn PortInput(
 										})}
 
 									<div class="flex-1 flex items-center justify-center pl-sm">
-										<button on:click={move |ev| {
+										<button on:click={move |_| {
 											on_delete.call(child.0.to_string())
 										}}>
 											<Icon
@@ -19,7 +19,7 @@ fn RunnerDropdown() -> impl IntoView {
 			placeholder="Choose A Runner"
 			class="w-full"
 			value={deployment_info
-				.with(|info| info.runner_id.clone().map(|id| id.to_string()).unwrap_or_default())}
+				.with(|info| info.runner_id.map(|id| id.to_string()).unwrap_or_default())}
 			on_select={move |id: String| {
 				if let Ok(runner_id) = Uuid::parse_str(id.as_str()) {
 					deployment_info.update(|info| info.runner_id = Some(runner_id))
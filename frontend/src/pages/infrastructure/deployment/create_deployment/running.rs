@@ -1,6 +1,5 @@
 use std::str::FromStr;
 
-use ev::MouseEvent;
 use models::api::workspace::deployment::{EnvironmentVariableValue, ExposedPortType};
 
 use super::{super::components::*, RunnerPageError};
@@ -21,21 +20,21 @@ pub fn RunningDetails(
 			<div class="fc-fs-fs gap-xl w-full h-full text-white">
 				<PortInput
 					on_add={move |(port_number, port_type): (String, String)| {
-						let port_number = StringifiedU16::from_str(port_number.as_str());
-						let port_type = ExposedPortType::from_str(port_type.as_str());
-						if port_number.is_ok() && port_type.is_ok() {
+						if let (Ok(port_number), Ok(port_type)) = (
+							StringifiedU16::from_str(port_number.as_str()),
+							ExposedPortType::from_str(port_type.as_str()),
+						) {
 							deployment_info
 								.update(|info| {
-									info.ports.insert(port_number.unwrap(), port_type.unwrap());
+									info.ports.insert(port_number, port_type);
 								});
 						}
 					}}
 					on_delete={move |port_number: String| {
-						let port_number = StringifiedU16::from_str(port_number.as_str());
-						if port_number.is_ok() {
+						if let Ok(port_number) = StringifiedU16::from_str(port_number.as_str()) {
 							deployment_info
 								.update(|info| {
-									info.ports.remove(&port_number.unwrap());
+									info.ports.remove(&port_number);
 								});
 						}
 					}}
@@ -1,7 +1,6 @@
 use std::rc::Rc;
 
 use ev::MouseEvent;
-use leptos_query::QueryResult;
 
 use crate::prelude::*;
 
@@ -42,7 +41,7 @@ pub fn DeploymentDashboardFooter(
 	};
 
 	let on_click_page = move |page: usize| {
-		if page <= 0 {
+		if page == 0 {
 			current_page.set(0);
 		}
 
@@ -70,7 +69,7 @@ pub fn DeploymentDashboardFooter(
 
 			<For
 				each={move || (1..=total_pages.get()).collect::<Vec<_>>()}
-				key={|state| state.clone()}
+				key={|state| *state}
 				let:page
 			>
 				<Link
@@ -5,7 +5,7 @@ use convert_case::*;
 use leptos_query::QueryResult;
 
 use self::{footer::*, head::*};
-use super::{components::*, utils::*};
+use super::components::*;
 use crate::{
 	prelude::*,
 	queries::{list_deployments_query, AllDeploymentsTag},
@@ -30,7 +30,7 @@ fn LoadingDeployments() -> impl IntoView {
 		>
 			<For
 				each={move || (0..constants::RESOURCES_PER_PAGE).collect::<Vec<usize>>()}
-				key={|state| state.clone()}
+				key={|state| *state}
 				let:_
 			>
 				<DeploymentSkeletonCard />
@@ -43,7 +43,6 @@ fn LoadingDeployments() -> impl IntoView {
 #[component]
 pub fn DeploymentDashboard() -> impl IntoView {
 	let deployment_page = create_rw_signal(0);
-	let (state, _) = AuthState::load();
 
 	create_effect(move |_| {
 		use_navigate()(
@@ -95,7 +94,7 @@ pub fn DeploymentDashboard() -> impl IntoView {
 								title="Error Loading Deployments"
 								content={view! {
 									<p class="text-white">
-										{format!("{}", err.to_string().to_case(Case::Title))}
+										{err.to_string().to_case(Case::Title).to_string()}
 									</p>
 								}
 									.into_view()}
@@ -3,7 +3,7 @@ use std::str::FromStr;
 use ev::MouseEvent;
 use models::api::workspace::deployment::*;
 
-use super::{super::components::*, DeploymentInfoContext};
+use super::{super::components::*, DeploymentInfoContext, DeploymentRuntimeSummary};
 use crate::{prelude::*, queries::update_deployment_query};
 
 /// Details tab for a deployment
@@ -41,6 +41,8 @@ pub fn ManageDeploymentDetailsTab() -> impl IntoView {
 
 				view! {
 					<div class="flex flex-col items-start justify-start w-full px-xl pb-xl mt-xl text-white gap-md fit-wide-screen mx-auto">
+						<DeploymentRuntimeSummary />
+
 						<div class="flex w-full">
 							<div class="flex-2 flex items-start justify-start">
 								<label
@@ -135,12 +137,11 @@ pub fn ManageDeploymentDetailsTab() -> impl IntoView {
 								Signal::derive(move || ports.clone())
 							}
 							on_delete={move |port_number: String| {
-								let port_number = StringifiedU16::from_str(port_number.as_str());
-								if port_number.is_ok() {
+								if let Ok(port_number) = StringifiedU16::from_str(port_number.as_str()) {
 									deployment_info
 										.update(|info| {
 											if let Some(info) = info {
-												info.running_details.ports.remove(&port_number.unwrap());
+												info.running_details.ports.remove(&port_number);
 											}
 										});
 									update_deployment_body
@@ -154,15 +155,16 @@ pub fn ManageDeploymentDetailsTab() -> impl IntoView {
 							on_add={move |
 								(port_number, port_type): (String, String)|
 							{
-								let port_number = StringifiedU16::from_str(port_number.as_str());
-								let port_type = ExposedPortType::from_str(port_type.as_str());
-								if port_number.is_ok() && port_type.is_ok() {
+								if let (Ok(port_number), Ok(port_type)) = (
+									StringifiedU16::from_str(port_number.as_str()),
+									ExposedPortType::from_str(port_type.as_str()),
+								) {
 									deployment_info
 										.update(|info| {
 											if let Some(info) = info {
 												info.running_details
 													.ports
-													.insert(port_number.unwrap(), port_type.unwrap());
+													.insert(port_number, port_type);
 											}
 										});
 									update_deployment_body
@@ -247,8 +249,7 @@ pub fn ManageDeploymentDetailsTab() -> impl IntoView {
 									.update(|body| {
 										body.startup_probe = deployment_info
 											.get()
-											.map(|info| info.running_details.startup_probe)
-											.flatten();
+											.and_then(|info| info.running_details.startup_probe);
 									});
 							}}
 							on_input_path={move |(port, path): (String, String)| {
@@ -268,8 +269,7 @@ pub fn ManageDeploymentDetailsTab() -> impl IntoView {
 									.update(|body| {
 										body.startup_probe = deployment_info
 											.get()
-											.map(|info| info.running_details.startup_probe)
-											.flatten();
+											.and_then(|info| info.running_details.startup_probe);
 									});
 							}}
 							on_delete={move |_| {
@@ -315,8 +315,7 @@ pub fn ManageDeploymentDetailsTab() -> impl IntoView {
 									.update(|body| {
 										body.liveness_probe = deployment_info
 											.get()
-											.map(|info| info.running_details.liveness_probe)
-											.flatten();
+											.and_then(|info| info.running_details.liveness_probe);
 									});
 							}}
 							on_input_path={move |(port, path): (String, String)| {
@@ -336,8 +335,7 @@ pub fn ManageDeploymentDetailsTab() -> impl IntoView {
 									.update(|body| {
 										body.liveness_probe = deployment_info
 											.get()
-											.map(|info| info.running_details.liveness_probe)
-											.flatten();
+											.and_then(|info| info.running_details.liveness_probe);
 									});
 							}}
 							on_delete={move |_| {
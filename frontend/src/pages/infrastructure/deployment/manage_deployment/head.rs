@@ -5,7 +5,6 @@ use models::api::workspace::deployment::*;
 
 use super::DeploymentInfoContext;
 use crate::{
-	pages::ShowWorkspaceInfoPropsBuilder_Error_Missing_required_field_workspace,
 	prelude::*,
 	queries::{delete_deployment_query, start_deployment_query, stop_deployment_query},
 };
@@ -91,18 +90,17 @@ pub fn StartStopButton() -> impl IntoView {
 
 	let start_deployment_action = start_deployment_query();
 	let stop_deployment_action = stop_deployment_query();
-	let delete_deployment_action = delete_deployment_query();
 
 	let on_click_start_stop = move |ev: &MouseEvent| {
 		ev.prevent_default();
 		if let Some(deployment_info) = deployment_info.get() {
-			let status = deployment_info.deployment.status.clone();
+			let status = deployment_info.deployment.status;
 			match status {
 				DeploymentStatus::Running => {
-					stop_deployment_action.dispatch(deployment_info.deployment.id.clone());
+					stop_deployment_action.dispatch(deployment_info.deployment.id);
 				}
 				DeploymentStatus::Created | DeploymentStatus::Stopped => {
-					start_deployment_action.dispatch(deployment_info.deployment.id.clone());
+					start_deployment_action.dispatch(deployment_info.deployment.id);
 				}
 				_ => {}
 			}
@@ -123,16 +121,16 @@ pub fn StartStopButton() -> impl IntoView {
 					on_click_start_stop(ev);
 				})}
 				style_variant={LinkStyleVariant::Contained}
-				disabled={match deployment_info.deployment.status {
+				disabled={!matches!(
+					deployment_info.deployment.status,
 					DeploymentStatus::Running
-					| DeploymentStatus::Created
-					| DeploymentStatus::Stopped => false,
-					_ => true,
-				}}
+						| DeploymentStatus::Created
+						| DeploymentStatus::Stopped
+				)}
 			>
 				<Icon
 					icon={match Status::from_deployment_status(
-						deployment_info.clone().deployment.clone().status.clone(),
+						deployment_info.clone().deployment.clone().status,
 					) {
 						Status::Running => IconType::PauseCircle,
 						_ => IconType::PlayCircle,
@@ -143,7 +141,7 @@ pub fn StartStopButton() -> impl IntoView {
 				/>
 				{
 					let status = Status::from_deployment_status(
-						deployment_info.deployment.clone().status.clone(),
+						deployment_info.deployment.clone().status,
 					);
 					match status {
 						Status::Running => "STOP",
@@ -163,7 +161,7 @@ pub fn StartStopButton() -> impl IntoView {
 			<Show when={move || show_delete_dialog.get()}>
 				<DeleteDialog
 					deployment_name={deployment_info.clone().deployment.clone().name.clone()}
-					deployment_id={deployment_info.deployment.clone().id.clone()}
+					deployment_id={deployment_info.deployment.clone().id}
 					show_delete_dialog={show_delete_dialog}
 				/>
 			</Show>
@@ -34,7 +34,22 @@ pub fn ManageDeploymentImageHistory() -> impl IntoView {
 									key={|log| log.clone()}
 									let:child
 								>
-									<ImageHistoryCard deploy_history={child.clone()} />
+									<ImageHistoryCard
+										active={Signal::derive({
+											let image_digest = child.image_digest.clone();
+											move || {
+												deployment_info
+													.get()
+													.and_then(|info| info.deployment.data.current_live_digest)
+													.is_some_and(|digest| digest == image_digest)
+											}
+										})}
+										deployment_id={deployment_info
+											.get()
+											.map(|info| info.deployment.id)
+											.unwrap()}
+										deploy_history={child.clone()}
+									/>
 								</For>
 							}
 								.into_view()
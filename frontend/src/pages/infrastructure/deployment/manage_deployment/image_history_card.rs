@@ -1,6 +1,8 @@
+use std::rc::Rc;
+
 use models::api::workspace::deployment::deploy_history::DeploymentDeployHistory;
 
-use crate::{pages::*, prelude::*};
+use crate::{pages::*, prelude::*, queries::rollback_deployment_query};
 
 #[component]
 pub fn ImageHistoryCard(
@@ -10,10 +12,25 @@ pub fn ImageHistoryCard(
 	/// Whether the card is active or not
 	#[prop(into, optional, default = false.into())]
 	active: MaybeSignal<bool>,
+	/// The ID of the deployment the history belongs to
+	#[prop(into)]
+	deployment_id: MaybeSignal<Uuid>,
 	/// The Deployment Info
 	#[prop(into)]
 	deploy_history: MaybeSignal<DeploymentDeployHistory>,
 ) -> impl IntoView {
+	let rollback_deployment_action = rollback_deployment_query();
+	let on_click_rollback = {
+		let deploy_history = deploy_history.clone();
+		move |_: &ev::MouseEvent| {
+			rollback_deployment_action.dispatch((
+				deployment_id.get(),
+				deploy_history.get().image_digest,
+				false,
+			));
+		}
+	};
+
 	let class = move || {
 		class.with(|cname| format!(
 			"w-full px-xl py-md bg-secondary-light rounded-sm flex flex-col items-start justify-start pos-rel deploy-summary-card text-white {}",
@@ -60,7 +77,13 @@ pub fn ImageHistoryCard(
 					(!active.get())
 						.then(|| {
 							view! {
-								<Link class="text-sm tracking-[1px]">"Revert of this version"</Link>
+								<Link
+									class="text-sm tracking-[1px]"
+									on_click={Rc::new(on_click_rollback.clone())}
+									disabled={rollback_deployment_action.pending()}
+								>
+									"Roll back to this version"
+								</Link>
 							}
 						})
 				}}
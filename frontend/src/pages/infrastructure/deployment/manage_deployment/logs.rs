@@ -1,15 +1,11 @@
 use std::rc::Rc;
 
 use ev::MouseEvent;
-use leptos_query::QueryResult;
 use models::api::workspace::deployment::DeploymentLog;
-use time::{macros::format_description, Duration, OffsetDateTime};
+use time::{Duration, OffsetDateTime};
 
 use super::{super::components::*, DeploymentInfoContext};
-use crate::{
-	prelude::*,
-	queries::{get_deployment_logs_query, GetDeploymentLogsTag},
-};
+use crate::prelude::*;
 
 /// List Logs for a deployment
 #[component]
@@ -40,17 +36,18 @@ pub fn ManageDeploymentsLogs() -> impl IntoView {
 		},
 	);
 
-	create_effect(move |_| match deployment_logs.get() {
-		Some(Ok(new_logs)) => logs_list.update(|logs| {
-			logs.extend(new_logs.logs.into_iter());
-			// REMOVE THIS FROM HERE
-			logs.extend((0..25).map(|x| DeploymentLog {
-				timestamp: end_time.get() - Duration::seconds(x * 100),
-				log: format!("This is a log {x}"),
-			}))
-			// TO HERE
-		}),
-		_ => {}
+	create_effect(move |_| {
+		if let Some(Ok(new_logs)) = deployment_logs.get() {
+			logs_list.update(|logs| {
+				logs.extend(new_logs.logs);
+				// REMOVE THIS FROM HERE
+				logs.extend((0..25).map(|x| DeploymentLog {
+					timestamp: end_time.get() - Duration::seconds(x * 100),
+					log: format!("This is a log {x}"),
+				}))
+				// TO HERE
+			})
+		}
 	});
 
 	let on_click_load = move |_: &MouseEvent| {
@@ -61,14 +58,12 @@ pub fn ManageDeploymentsLogs() -> impl IntoView {
 		}
 	};
 
-	let date_formater = format_description!("[year]-[month]-[day] [hour]:[minute]");
-
 	view! {
 		<div class="w-full h-full px-xl my-xl overflow-hidden">
 			<div class="w-full h-full px-md flex flex-col items-start justify-start">
 				{
 					move || match deployment_logs.get() {
-						Some(Ok(deployment_logs)) => {
+						Some(Ok(_)) => {
 							view! {
 								<div class="w-full pb-xxs flex justify-between items-center mb-xs gap-xl">
 									<Link
@@ -5,6 +5,7 @@ mod image_history_card;
 mod image_tag;
 mod logs;
 mod monitor;
+mod runtime;
 mod scaling;
 mod urls;
 
@@ -20,6 +21,7 @@ pub use self::{
 	image_tag::*,
 	logs::*,
 	monitor::*,
+	runtime::*,
 	scaling::*,
 	urls::*,
 };
@@ -71,7 +73,7 @@ pub fn ManageDeploymentsContent(
 									title="Error Fetching Resource"
 									content={view! {
 										<p class="text-white">
-											{format!("{}", err.to_string().to_case(Case::Title))}
+											{err.to_string().to_case(Case::Title).to_string()}
 										</p>
 									}
 										.into_view()}
@@ -98,8 +100,7 @@ pub fn ManageDeployments() -> impl IntoView {
 				.as_ref()
 				.map(|param| param.deployment_id.clone())
 				.unwrap_or_default()
-				.map(|x| Uuid::parse_str(x.as_str()).ok())
-				.flatten()
+				.and_then(|x| Uuid::parse_str(x.as_str()).ok())
 		})
 	});
 
@@ -0,0 +1,102 @@
+use convert_case::*;
+
+use super::DeploymentInfoContext;
+use crate::prelude::*;
+
+/// The state of the deployment as last reported by its runner, including the
+/// number of replicas that are ready, the last error the runner ran into and
+/// its recent events.
+#[component]
+pub fn DeploymentRuntimeSummary() -> impl IntoView {
+	let deployment_info = expect_context::<DeploymentInfoContext>().0;
+
+	move || {
+		let Some(runtime) = deployment_info.get().and_then(|info| info.runtime) else {
+			return view! {
+				<div class="flex w-full">
+					<div class="flex-2 flex items-start justify-start">
+						<label html_for="replicas">"Replicas"</label>
+					</div>
+					<div class="flex-10">
+						<Textbox
+							disabled=true
+							value={"The runner hasn't reported on this deployment yet".into_view()}
+						/>
+					</div>
+				</div>
+			}
+			.into_view();
+		};
+
+		view! {
+			<div class="flex w-full">
+				<div class="flex-2 flex items-start justify-start">
+					<label html_for="replicas">"Replicas"</label>
+				</div>
+				<div class="flex-10">
+					<Textbox
+						disabled=true
+						value={format!(
+							"{} of {} ready, {} up to date (reported at {})",
+							runtime.replicas.ready,
+							runtime.replicas.desired,
+							runtime.replicas.updated,
+							runtime.reported,
+						)
+							.into_view()}
+					/>
+				</div>
+			</div>
+
+			{runtime
+				.last_error
+				.map(|error| {
+					view! {
+						<div class="flex w-full">
+							<div class="flex-2 flex items-start justify-start">
+								<label html_for="last-error">"Last Error"</label>
+							</div>
+							<div class="flex-10 flex flex-col items-start justify-start">
+								<p class="text-error text-sm">{error.message}</p>
+								<small class="text-xxs text-grey">{error.occurred.to_string()}</small>
+							</div>
+						</div>
+					}
+				})}
+
+			{(!runtime.recent_events.is_empty())
+				.then(|| {
+					view! {
+						<div class="flex w-full">
+							<div class="flex-2 flex items-start justify-start">
+								<label html_for="recent-events">"Recent Events"</label>
+							</div>
+							<div class="flex-10 flex flex-col items-start justify-start gap-xxs">
+								{runtime
+									.recent_events
+									.into_iter()
+									.map(|event| {
+										view! {
+											<div class="flex justify-start items-center w-full text-sm">
+												<span class="text-grey w-[30ch]">
+													{event.timestamp.to_string()}
+												</span>
+												<span class="ml-sm">
+													{format!("{:?}", event.kind).to_case(Case::Title)}
+												</span>
+												<span class="ml-sm">{event.container.unwrap_or_default()}</span>
+												<span class="ml-sm text-grey">
+													{event.message.unwrap_or_default()}
+												</span>
+											</div>
+										}
+									})
+									.collect_view()}
+							</div>
+						</div>
+					}
+				})}
+		}
+		.into_view()
+	}
+}
@@ -1,4 +1,4 @@
-use std::{f32::consts::E, rc::Rc};
+use std::rc::Rc;
 
 use ev::MouseEvent;
 use leptos_query::QueryResult;
@@ -19,14 +19,11 @@ fn UpdateScale(
 	/// Update Deployment Info Body
 	update_deployment_body: RwSignal<UpdateDeploymentRequest>,
 ) -> impl IntoView {
-	let store_deployment = store_value(deployment_info.clone());
 	let min_horizontal_value =
 		create_rw_signal(deployment_info.running_details.min_horizontal_scale);
 	let max_horizontal_value =
 		create_rw_signal(deployment_info.running_details.max_horizontal_scale);
 
-	let deployment_info_context = expect_context::<DeploymentInfoContext>().0;
-
 	view! {
 		<div class="w-full flex items-center justify-center">
 			<div class="flex-2 flex flex-col items-center justify-center">
@@ -87,23 +84,23 @@ fn UpdateMachineType(
 					view! {
 						<For
 							each={move || machine_types.clone()}
-							key={|state| state.id.clone()}
+							key={|state| state.id}
 							let:machine_type
 						>
 							<MachineTypeCard
 								machine_type={machine_type.clone()}
 								is_selected={Signal::derive(
 									move || store_deployment.with_value(|deployment| {
-										deployment.deployment.machine_type == machine_type.id.clone()
+										deployment.deployment.machine_type == machine_type.id
 									})
 								)}
 								on_select={move |id: Uuid| {
 									update_deployment_body.update(|body| {
-										body.machine_type = Some(id.clone());
+										body.machine_type = Some(id);
 									});
 									deployment_info.update(|info| {
 										if let Some(info) = info {
-											info.deployment.data.machine_type = id.clone();
+											info.deployment.data.machine_type = id;
 										}
 									});
 								}}
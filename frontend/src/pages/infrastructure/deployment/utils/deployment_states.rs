@@ -89,15 +89,16 @@ impl DeploymentInfo {
 				.map(|(port, path)| DeploymentProbe { port, path }),
 			volumes: self.volumes.clone(),
 			config_mounts: BTreeMap::from([]),
+			rollout_strategy: DeploymentRolloutStrategy::default(),
 		};
 
 		Some(CreateDeploymentRequest {
 			registry,
 			running_details,
 			name: self.name.clone()?,
-			runner: self.runner_id.clone()?,
+			runner: self.runner_id?,
 			image_tag: self.image_tag.clone()?,
-			machine_type: self.machine_type.clone()?,
+			machine_type: self.machine_type?,
 			deploy_on_create: self.deploy_on_create,
 		})
 	}
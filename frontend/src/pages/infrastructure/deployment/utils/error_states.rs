@@ -13,6 +13,12 @@ pub struct DetailsPageError {
 	pub runner: String,
 }
 
+impl Default for DetailsPageError {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 impl DetailsPageError {
 	/// Creates a new instance of the [`DetailsPageError`], or wipe all the
 	/// errors
@@ -34,6 +40,12 @@ pub struct RunnerPageError {
 	pub ports: String,
 }
 
+impl Default for RunnerPageError {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 impl RunnerPageError {
 	/// Creates a new instance of the [`RunnerPageError`], or wipe all the
 	/// errors
@@ -50,6 +62,12 @@ pub struct ScalePageError {
 	pub machine_type: String,
 }
 
+impl Default for ScalePageError {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 impl ScalePageError {
 	/// Creates a new instance of the [`ScalePageError`], or wipe all the errors
 	pub const fn new() -> Self {
@@ -32,7 +32,7 @@ pub fn ApiTokenCard(
 		let format =
 			format_description::parse("[year]-[month]-[day] [hour]:[minute]:[second]").unwrap();
 
-		token.get().data.created.clone().format(&format.clone())
+		token.get().data.created.format(&format.clone())
 	}) {
 		Ok(date) => date.into_view(),
 		Err(_) => "Invalid Date".into_view(),
@@ -64,7 +64,7 @@ pub fn ApiTokenCard(
 				{move || store_token.with_value(|token| token.get().name.clone())}
 			</td>
 			<td class="flex-4 flex items-center justify-center">{expiry.clone()}</td>
-			<td class="flex-4 flex items-center justify-center">{date.clone()}</td>
+			<td class="flex-4 flex items-center justify-center">{date}</td>
 		</tr>
 	}
 }
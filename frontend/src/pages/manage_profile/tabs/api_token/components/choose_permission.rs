@@ -79,8 +79,6 @@ pub fn ChoosePermission(
 	#[prop(into)]
 	workspace_id: MaybeSignal<Uuid>,
 ) -> impl IntoView {
-	let (state, _) = AuthState::load();
-
 	let div_class = class.with(|cname| {
 		format!(
 			"gap-sm grid grid-col-{} w-full {}",
@@ -106,11 +104,10 @@ pub fn ChoosePermission(
 		.collect::<Vec<InputDropdownOption>>();
 
 	let show_resource_type = create_memo(move |_| {
-		match ResourceType::from_str(input_resource_type.get().to_case(Case::Camel).as_str()) {
-			Ok(ResourceType::DnsRecord) => false,
-			Ok(ResourceType::Workspace) => false,
-			_ => true,
-		}
+		!matches!(
+			ResourceType::from_str(input_resource_type.get().to_case(Case::Camel).as_str()),
+			Ok(ResourceType::DnsRecord | ResourceType::Workspace)
+		)
 	});
 
 	let show_resources = move || {
@@ -135,10 +132,7 @@ pub fn ChoosePermission(
 		let permissions = input_permissions
 			.get()
 			.iter()
-			.filter_map(|x| match Uuid::parse_str(x) {
-				Ok(y) => Some(y),
-				Err(_) => None,
-			})
+			.filter_map(|x| Uuid::parse_str(x).ok())
 			.collect::<Vec<_>>();
 
 		let permission_types = match ApplyToOptions::from_str(input_apply_to.get().as_str()) {
@@ -5,7 +5,7 @@ use models::{api::workspace::Workspace, rbac::WorkspacePermission};
 
 use super::super::{
 	components::{ChoosePermission, PermissionItem},
-	utils::{ApiTokenInfo, ApiTokenPermissions},
+	utils::ApiTokenPermissions,
 };
 use crate::prelude::*;
 
@@ -67,7 +67,7 @@ pub fn PermissionCard(
 			ev.prevent_default();
 
 			api_token_permissions.update(|permissions| {
-				permissions.as_mut().map(|permissions| {
+				if let Some(permissions) = permissions.as_mut() {
 					let permission_exists = permissions.contains_key(&workspace_id);
 					if permission_exists {
 						permissions.insert(
@@ -79,7 +79,7 @@ pub fn PermissionCard(
 					} else {
 						permissions.insert(workspace_id, WorkspacePermission::SuperAdmin);
 					}
-				});
+				}
 			});
 		}
 	};
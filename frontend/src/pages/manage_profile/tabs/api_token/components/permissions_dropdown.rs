@@ -4,7 +4,6 @@ use convert_case::{Case, Casing};
 use ev::MouseEvent;
 use leptos_query::QueryResult;
 use models::rbac::ResourceType;
-use strum::VariantNames;
 
 use super::ParsedPermission;
 use crate::{prelude::*, queries::get_all_permissions_query};
@@ -87,9 +86,9 @@ pub fn PermissionsDropdown(
 				options={permission_options}
 				value={Signal::derive(move || input_permissions.get())}
 				on_select={move |(_, id): (MouseEvent, String)| {
-					if input_permissions.get().iter().any(|e| e.to_owned() == id) {
+					if input_permissions.get().iter().any(|e| *e == id) {
 						input_permissions
-							.update(|options| options.retain(|e| e.to_owned() != id));
+							.update(|options| options.retain(|e| *e != id));
 					} else {
 						input_permissions.update(|options| options.push(id.clone()));
 					}
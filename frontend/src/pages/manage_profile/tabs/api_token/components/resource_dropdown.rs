@@ -4,11 +4,10 @@ use convert_case::{Case, Casing};
 use ev::MouseEvent;
 use leptos_query::QueryResult;
 use models::rbac::ResourceType;
-use strum::VariantNames;
 
 use crate::{
 	prelude::*,
-	queries::{get_deployment_query, list_deployments_query, AllDeploymentsTag},
+	queries::{list_deployments_query, AllDeploymentsTag},
 };
 
 #[component]
@@ -31,18 +30,19 @@ pub fn ResourceDropdownOptions(
 			{ ResourceType::from_str(input_resource_type.get().to_case(Case::Camel).as_str()) };
 
 		match resource_type {
-			Ok(ResourceType::Deployment) => match deployments_list.get() {
-				Some(Ok((_, deployments_list))) => resource_list_options.update(|resource_list| {
-					resource_list.extend(deployments_list.deployments.iter().map(|deployment| {
-						InputDropdownOption {
-							id: deployment.id.to_string(),
-							disabled: false,
-							label: deployment.name.clone(),
-						}
-					}));
-				}),
-				_ => {}
-			},
+			Ok(ResourceType::Deployment) => {
+				if let Some(Ok((_, deployments_list))) = deployments_list.get() {
+					resource_list_options.update(|resource_list| {
+						resource_list.extend(deployments_list.deployments.iter().map(
+							|deployment| InputDropdownOption {
+								id: deployment.id.to_string(),
+								disabled: false,
+								label: deployment.name.clone(),
+							},
+						));
+					})
+				}
+			}
 			Ok(_) => {
 				resource_list_options.set(vec![]);
 			}
@@ -94,10 +94,10 @@ pub fn ResourceDropdownOptions(
 				value={Signal::derive(move || input_resources.get())}
 				options={resource_list_options}
 				on_select={move |(_, id): (MouseEvent, String)| {
-					if input_resources.get().iter().any(|e| e.to_owned() == id)
+					if input_resources.get().iter().any(|e| *e == id)
 					{
 						input_resources
-							.update(|options| options.retain(|e| e.to_owned() != id));
+							.update(|options| options.retain(|e| *e != id));
 					} else {
 						input_resources.update(|options| options.push(id.clone()));
 					}
@@ -107,7 +107,7 @@ pub fn ResourceDropdownOptions(
 					<button
 						class="text-primary w-full justify-center"
 						on:click={move |ev| {
-							ev.prevent_default();;
+							ev.prevent_default();
 							current_page.update(|v| *v += 1)
 						}}
 					>
@@ -30,11 +30,9 @@ pub struct TokenParams {
 
 /// Convert OffsetDateTime to a string date
 pub fn convert_offset_to_date(date_time: Option<OffsetDateTime>) -> String {
-	if date_time.is_some() {
-		date_time.unwrap().date().to_string()
-	} else {
-		"".to_string()
-	}
+	date_time
+		.map(|date_time| date_time.date().to_string())
+		.unwrap_or_default()
 }
 
 /// Convert String to OffsetDateTime
@@ -117,12 +115,11 @@ pub fn EditApiToken() -> impl IntoView {
 	provide_context(ApiTokenInfo(token_info_signal));
 	provide_context(ApiTokenPermissions(token_permissions));
 
-	create_effect(move |_| match token_info.get() {
-		Some(Ok(data)) => {
+	create_effect(move |_| {
+		if let Some(Ok(data)) = token_info.get() {
 			token_info_signal.set(Some(data.token.clone()));
 			token_permissions.set(Some(data.token.permissions.clone()));
 		}
-		_ => {}
 	});
 
 	let on_submit = move |ev: MouseEvent| {
@@ -161,7 +158,7 @@ pub fn EditApiToken() -> impl IntoView {
 			<form class="w-full h-full">
 				<Transition>
 					{move || match token_info.get() {
-						Some(Ok(token_info)) => view! {
+						Some(Ok(_)) => view! {
 							<TokenInfo />
 							<EditApiTokenPermission />
 						}.into_view(),
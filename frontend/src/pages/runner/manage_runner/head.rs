@@ -41,7 +41,7 @@ pub fn RunnerManageHead(
 
 				<form on:submit={move |ev| {
 					ev.prevent_default();
-					delete_runner_action.dispatch(runner_info.get().id.clone());
+					delete_runner_action.dispatch(runner_info.get().id);
 				}}>
 					<Link
 						style_variant={LinkStyleVariant::Contained}
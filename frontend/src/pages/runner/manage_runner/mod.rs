@@ -63,7 +63,7 @@ fn ManageRunnerContent(
 								title="Error Loading Runner Info"
 								content={view! {
 									<p class="text-white">
-										{format!("{}", err.to_string().to_case(Case::Title))}
+										{err.to_string().to_case(Case::Title).to_string()}
 									</p>
 								}
 									.into_view()}
@@ -89,8 +89,7 @@ pub fn ManageRunner() -> impl IntoView {
 				.as_ref()
 				.map(|param| param.runner_id.clone())
 				.unwrap_or_default()
-				.map(|x| Uuid::parse_str(x.as_str()).ok())
-				.flatten()
+				.and_then(|x| Uuid::parse_str(x.as_str()).ok())
 		})
 	});
 
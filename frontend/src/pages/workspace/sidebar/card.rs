@@ -29,7 +29,7 @@ pub fn WorkspaceCard(
 			<div class="flex flex-col items-start justify-start w-full">
 				<p class="text-sm text-white w-[20ch] text-ellipsis overflow-hidden">
 					{move || match current_workspace.get() {
-						Some(workspace) => format!("{}", workspace.name).into_view(),
+						Some(workspace) => workspace.name.to_string().into_view(),
 						None => "Select A Workspace".into_view(),
 					}}
 				</p>
@@ -41,7 +41,7 @@ pub fn WorkspaceCard(
 
 			<Show when={move || show_workspace_switcher.get()}>
 				<WorkspaceSwitcher
-					set_workspace_id={set_workspace_id.clone()}
+					set_workspace_id={set_workspace_id}
 					workspaces={workspaces.clone()}
 					show_workspace_switcher={show_workspace_switcher}
 				/>
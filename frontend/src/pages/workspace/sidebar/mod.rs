@@ -43,19 +43,19 @@ pub fn WorkspaceSidebarComponent() -> impl IntoView {
 				_ => {
 					let first_id = workspace_list.get().and_then(|list| {
 						list.ok().and_then(|x| {
-							let x = x.workspaces.first().and_then(|x| Some(x.id));
+							let x = x.workspaces.first().map(|x| x.id);
 							x
 						})
 					});
 					set_current_workspace.set(first_id);
-					set_state.update(|state| match *state {
-						Some(AuthState::LoggedIn {
+					set_state.update(|state| {
+						if let Some(AuthState::LoggedIn {
 							ref mut last_used_workspace_id,
 							..
-						}) => {
+						}) = *state
+						{
 							*last_used_workspace_id = first_id;
 						}
-						_ => {}
 					});
 
 					first_id
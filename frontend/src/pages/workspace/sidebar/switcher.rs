@@ -37,7 +37,7 @@ pub fn WorkspaceSwitcher(
 						>
 							<WorkspaceItem
 								show_workspace_switcher={show_workspace_switcher}
-								set_workspace_id={set_workspace_id.clone()}
+								set_workspace_id={set_workspace_id}
 								workspace={child}
 							/>
 						</For>
@@ -82,11 +82,7 @@ pub fn ManageWorkspaceSettingsTab() -> impl IntoView {
 		move |value| async move { list_user_workspace(value).await },
 	);
 
-	let current_workspace_id =
-		Signal::derive(move || match state.get().get_last_used_workspace_id() {
-			Some(id) => Some(id),
-			_ => None,
-		});
+	let current_workspace_id = Signal::derive(move || state.get().get_last_used_workspace_id());
 
 	let current_workspace = Signal::derive(move || {
 		if let Some(workspace_id) = current_workspace_id.get() {
@@ -0,0 +1,29 @@
+use leptos_query::*;
+use models::api::workspace::container_registry::*;
+
+use crate::prelude::*;
+
+/// Tag for Listing All Container Repositories query
+#[derive(Debug, Hash, Eq, PartialEq, Clone)]
+pub struct AllContainerRepositoriesTag;
+
+/// Query to list all container repositories for a workspace
+pub fn list_container_repositories_query() -> QueryScope<
+	AllContainerRepositoriesTag,
+	Result<ListContainerRepositoriesResponse, ServerFnError<ErrorType>>,
+> {
+	let (state, _) = AuthState::load();
+	let access_token = state.get().get_access_token();
+	// TODO: remove this unwrap
+	let workspace_id = state.get().get_last_used_workspace_id().unwrap();
+
+	create_query(
+		move |_| {
+			let access_token = access_token.clone();
+			async move { list_container_repositories(access_token, workspace_id).await }
+		},
+		QueryOptions {
+			..Default::default()
+		},
+	)
+}
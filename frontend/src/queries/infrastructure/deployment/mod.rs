@@ -1,5 +1,5 @@
 use leptos_query::*;
-use models::api::workspace::deployment::*;
+use models::api::workspace::deployment::{deploy_history::RollbackDeploymentResponse, *};
 use time::OffsetDateTime;
 
 use crate::prelude::*;
@@ -78,10 +78,10 @@ pub fn create_deployment_query(
 			.await;
 
 			if let Ok(ref response) = response {
-				let _ = use_query_client().invalidate_query_type::<AllDeploymentsTag, Result<ListDeploymentResponse, ServerFnError<ErrorType>>>();
+				use_query_client().invalidate_query_type::<AllDeploymentsTag, Result<ListDeploymentResponse, ServerFnError<ErrorType>>>();
 
 				navigate(
-					format!("/deployments/{}", response.id.id.to_string()).as_str(),
+					format!("/deployments/{}", response.id.id).as_str(),
 					Default::default(),
 				);
 			}
@@ -103,7 +103,7 @@ pub fn delete_deployment_query(
 		let navigate = use_navigate();
 		let access_token = access_token.clone();
 
-		let deployment_id = deployment_id.clone();
+		let deployment_id = *deployment_id;
 
 		let deployment_query = get_deployment_query();
 
@@ -115,7 +115,7 @@ pub fn delete_deployment_query(
 
 			if response.is_ok() {
 				let _ = deployment_query.invalidate_query(deployment_id);
-				let _ = use_query_client().invalidate_query_type::<AllDeploymentsTag, Result<ListDeploymentResponse, ServerFnError<ErrorType>>>();
+				use_query_client().invalidate_query_type::<AllDeploymentsTag, Result<ListDeploymentResponse, ServerFnError<ErrorType>>>();
 
 				navigate("/deployments", Default::default());
 			}
@@ -136,7 +136,7 @@ pub fn start_deployment_query(
 	create_action(move |deployment_id: &Uuid| {
 		let access_token = access_token.clone();
 
-		let deployment_id = deployment_id.clone();
+		let deployment_id = *deployment_id;
 		let deployment_query = get_deployment_query();
 
 		async move {
@@ -159,7 +159,7 @@ pub fn stop_deployment_query(
 	create_action(move |deployment_id: &Uuid| {
 		let access_token = access_token.clone();
 
-		let deployment_id = deployment_id.clone();
+		let deployment_id = *deployment_id;
 		let deployment_query = get_deployment_query();
 
 		async move {
@@ -171,6 +171,42 @@ pub fn stop_deployment_query(
 	})
 }
 
+/// Query to roll a deployment back to an image digest from its deploy history,
+/// optionally restoring the running details it had with that image. Returns an
+/// action to be dispatched on submit.
+pub fn rollback_deployment_query(
+) -> Action<(Uuid, String, bool), Result<RollbackDeploymentResponse, ServerFnError<ErrorType>>> {
+	let (state, _) = AuthState::load();
+
+	let access_token = state.get().get_access_token();
+	let workspace_id = state.get().get_last_used_workspace_id().unwrap();
+
+	create_action(
+		move |(deployment_id, image_digest, restore_running_details): &(Uuid, String, bool)| {
+			let access_token = access_token.clone();
+
+			let deployment_id = *deployment_id;
+			let image_digest = image_digest.clone();
+			let restore_running_details = *restore_running_details;
+			let deployment_query = get_deployment_query();
+
+			async move {
+				let response = rollback_deployment(
+					access_token,
+					workspace_id,
+					deployment_id,
+					image_digest,
+					restore_running_details,
+				)
+				.await;
+				let _ = deployment_query.invalidate_query(deployment_id);
+
+				response
+			}
+		},
+	)
+}
+
 /// Tag for Listing All Deployments query
 #[derive(Debug, Hash, Eq, PartialEq, Clone)]
 pub struct AllMachinesTag;
@@ -239,9 +275,8 @@ pub fn update_deployment_query() -> Action<
 			let request = request.clone();
 
 			let access_token = access_token.clone();
-			let deployment_id = deployment_id.clone();
+			let deployment_id = *deployment_id;
 
-			let deployments_list_query = list_deployments_query();
 			let deployment_query = get_deployment_query();
 
 			async move {
@@ -256,7 +291,7 @@ pub fn update_deployment_query() -> Action<
 				.await;
 
 				if response.is_ok() {
-					let _ = use_query_client().invalidate_query_type::<AllDeploymentsTag, Result<ListDeploymentResponse, ServerFnError<ErrorType>>>();
+					use_query_client().invalidate_query_type::<AllDeploymentsTag, Result<ListDeploymentResponse, ServerFnError<ErrorType>>>();
 					let _ = deployment_query.invalidate_query(deployment_id);
 				}
 
@@ -1,3 +1,4 @@
+mod container_registry;
 mod deployment;
 
-pub use self::deployment::*;
+pub use self::{container_registry::*, deployment::*};
@@ -69,7 +69,7 @@ pub fn create_api_token_query(
 		async move {
 			let response = create_api_token(access_token.clone(), request.clone()).await;
 
-			if let Ok(_) = response {
+			if response.is_ok() {
 				api_tokens_list_query.invalidate_query(AllApiTokensTag);
 			}
 
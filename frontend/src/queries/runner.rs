@@ -68,7 +68,7 @@ pub fn create_runner_query(
 			if let Ok(ref response) = response {
 				runners_list_query.invalidate_query(AllRunnersTag);
 				navigate(
-					format!("/runners/{}", response.id.id.to_string()).as_str(),
+					format!("/runners/{}", response.id.id).as_str(),
 					Default::default(),
 				);
 			}
@@ -92,7 +92,7 @@ pub fn delete_runner_query() -> Action<Uuid, Result<DeleteRunnerResponse, Server
 		let navigate = use_navigate();
 
 		let access_token = access_token.clone();
-		let runner_id = runner_id.clone();
+		let runner_id = *runner_id;
 
 		let runners_list_query = list_runners_query();
 		let runner_query = get_runner_query();
@@ -100,7 +100,7 @@ pub fn delete_runner_query() -> Action<Uuid, Result<DeleteRunnerResponse, Server
 		async move {
 			let response = delete_runner(access_token, workspace_id, runner_id).await;
 
-			if let Ok(_) = response {
+			if response.is_ok() {
 				let _ = runners_list_query.invalidate_query(AllRunnersTag);
 				let _ = runner_query.invalidate_query(runner_id);
 
@@ -1,5 +1,6 @@
 mod infrastructure;
 mod runner;
+#[allow(clippy::module_inception)]
 mod workspace;
 
 pub use self::{infrastructure::*, runner::*, workspace::*};
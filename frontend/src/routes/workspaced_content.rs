@@ -38,7 +38,7 @@ pub fn WorkspacedRoutes() -> impl IntoView {
 			_ => {
 				let first_id = workspace_list.get().and_then(|list| {
 					list.ok().and_then(|x| {
-						let x = x.workspaces.first().and_then(|x| Some(x.id));
+						let x = x.workspaces.first().map(|x| x.id);
 						x
 					})
 				});
@@ -51,7 +51,7 @@ pub fn WorkspacedRoutes() -> impl IntoView {
 					} => AuthState::LoggedIn {
 						access_token,
 						refresh_token,
-						last_used_workspace_id: first_id.clone(),
+						last_used_workspace_id: first_id,
 					},
 				};
 				logging::log!("{:?}", new_state);
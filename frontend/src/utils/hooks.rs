@@ -2,11 +2,15 @@ use models::api::workspace::deployment::ListDeploymentResponse;
 
 use crate::prelude::*;
 
-/// Get the list of deployments as a resource
-pub fn get_deployments() -> Resource<
+/// The resource holding the list of deployments, keyed by the access token and
+/// the workspace they are listed for
+pub type DeploymentsResource = Resource<
 	(Option<String>, Option<Uuid>),
 	Result<ListDeploymentResponse, ServerFnError<ErrorType>>,
-> {
+>;
+
+/// Get the list of deployments as a resource
+pub fn get_deployments() -> DeploymentsResource {
 	let (state, _) = AuthState::load();
 	let access_token = state.get().get_access_token();
 	let workspace_id = state.get().get_last_used_workspace_id();
@@ -16,7 +20,7 @@ pub fn get_deployments() -> Resource<
 		move |(access_token, workspace_id)| async move {
 			list_deployments(access_token, workspace_id.unwrap(), None, None)
 				.await
-				.map(|(headers, body)| body)
+				.map(|(_, body)| body)
 		},
 		Some(Ok(ListDeploymentResponse {
 			deployments: vec![],
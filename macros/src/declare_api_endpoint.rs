@@ -15,6 +15,11 @@ use syn::{
 	Token,
 };
 
+use crate::{
+	endpoint_docs::{self, FieldCase},
+	pagination::Pagination,
+};
+
 /// A helper struct to parse an API endpoint
 pub struct ApiEndpoint {
 	/// The documentation for the API endpoint. This is used for all the
@@ -36,8 +41,8 @@ pub struct ApiEndpoint {
 
 	/// The query params for the endpoint
 	query: Option<FieldsNamed>,
-	/// Whether the query is paginated or not.
-	paginate_query: Option<bool>,
+	/// How the query is paginated, if at all.
+	paginate_query: Option<Pagination>,
 	/// The body of the request.
 	request: Option<FieldsNamed>,
 	/// The required request headers for the endpoint.
@@ -109,11 +114,7 @@ impl Parse for ApiEndpoint {
 					}
 					input.parse::<Token![=]>()?;
 
-					let Lit::Bool(lit) = input.parse()? else {
-						return Err(Error::new(input.span(), "Expected boolean value"));
-					};
-
-					paginate_query = Some(lit.value);
+					paginate_query = Some(input.parse()?);
 				}
 				"request_headers" => {
 					if request_headers.is_some() {
@@ -215,6 +216,16 @@ pub fn parse(input: TokenStream) -> TokenStream {
 		response,
 	} = parse_macro_input!(input as ApiEndpoint);
 
+	let path_docs = endpoint_docs::field_docs(path_body.as_ref(), FieldCase::AsIs);
+	let query_docs = endpoint_docs::field_docs(query.as_ref(), FieldCase::CamelCase);
+	let request_headers_docs = endpoint_docs::header_docs(request_headers.as_ref());
+	let request_docs = endpoint_docs::field_docs(request.as_ref(), FieldCase::CamelCase);
+	let response_headers_docs = endpoint_docs::header_docs(response_headers.as_ref());
+	let response_docs = endpoint_docs::field_docs(response.as_ref(), FieldCase::CamelCase);
+	let authentication_docs = endpoint_docs::authentication_docs(auth.as_ref());
+	let pagination = paginate_query.unwrap_or_default();
+	let pagination_docs = pagination.docs();
+
 	let (path_default_impl, path_body) = if let Some(body) = path_body {
 		(
 			quote::quote! {},
@@ -246,25 +257,7 @@ pub fn parse(input: TokenStream) -> TokenStream {
 	};
 
 	let query_type_name = format_ident!("{}Query", name);
-	let query_name = if query.is_some() {
-		if paginate_query.unwrap_or(false) {
-			quote::quote! {
-				models::api::Paginated<#query_type_name>
-			}
-		} else {
-			quote::quote! {
-				#query_type_name
-			}
-		}
-	} else if paginate_query.unwrap_or(false) {
-		quote::quote! {
-			models::api::Paginated<()>
-		}
-	} else {
-		quote::quote! {
-			()
-		}
-	};
+	let query_name = pagination.query_type(query.is_some().then_some(&query_type_name));
 	let query_decl = if let Some(query) = query {
 		quote::quote! {
 			#[::preprocess::sync]
@@ -470,6 +463,24 @@ pub fn parse(input: TokenStream) -> TokenStream {
 
 			type ResponseHeaders = #response_headers_name;
 			type ResponseBody = #response_name;
+
+			fn docs() -> models::utils::EndpointDocs {
+				models::utils::EndpointDocs {
+					name: stringify!(#name),
+					documentation: #documentation,
+					method: ::http::Method::#method,
+					path: #path,
+					pagination: #pagination_docs,
+					websocket: false,
+					authentication: #authentication_docs,
+					path_params: #path_docs,
+					query: #query_docs,
+					request_headers: #request_headers_docs,
+					request_body: #request_docs,
+					response_headers: #response_headers_docs,
+					response_body: #response_docs,
+				}
+			}
 		}
 	}
 	.into()
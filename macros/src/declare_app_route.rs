@@ -211,9 +211,9 @@ pub fn parse(input: TokenStream) -> TokenStream {
 				let value = ::serde_json::to_value(map.clone()).map_err(|err| {
 					::leptos_router::ParamsError::Params(::std::sync::Arc::new(err))
 				})?;
-				Ok(::serde_json::from_value(value).map_err(|err| {
+				::serde_json::from_value(value).map_err(|err| {
 					::leptos_router::ParamsError::Params(::std::sync::Arc::new(err))
-				})?)
+				})
 			}
 		}
 	}
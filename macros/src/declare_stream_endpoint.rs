@@ -17,6 +17,11 @@ use syn::{
 	Variant,
 };
 
+use crate::{
+	endpoint_docs::{self, FieldCase},
+	pagination::Pagination,
+};
+
 /// A helper struct to parse an API endpoint
 pub struct ApiEndpoint {
 	/// The documentation for the API endpoint. This is used for all the
@@ -38,8 +43,8 @@ pub struct ApiEndpoint {
 
 	/// The query params for the endpoint
 	query: Option<FieldsNamed>,
-	/// Whether the query is paginated or not.
-	paginate_query: Option<bool>,
+	/// How the query is paginated, if at all.
+	paginate_query: Option<Pagination>,
 	/// The message that the client sends
 	client_msg: Option<Vec<Variant>>,
 	/// The required request headers for the endpoint.
@@ -111,11 +116,7 @@ impl Parse for ApiEndpoint {
 					}
 					input.parse::<Token![=]>()?;
 
-					let Lit::Bool(lit) = input.parse()? else {
-						return Err(Error::new(input.span(), "Expected boolean value"));
-					};
-
-					paginate_query = Some(lit.value);
+					paginate_query = Some(input.parse()?);
 				}
 				"request_headers" => {
 					if request_headers.is_some() {
@@ -231,6 +232,14 @@ pub fn parse(input: TokenStream) -> TokenStream {
 		client_msg,
 	} = parse_macro_input!(input as ApiEndpoint);
 
+	let path_docs = endpoint_docs::field_docs(path_body.as_ref(), FieldCase::AsIs);
+	let query_docs = endpoint_docs::field_docs(query.as_ref(), FieldCase::CamelCase);
+	let request_headers_docs = endpoint_docs::header_docs(request_headers.as_ref());
+	let response_headers_docs = endpoint_docs::header_docs(response_headers.as_ref());
+	let authentication_docs = endpoint_docs::authentication_docs(auth.as_ref());
+	let pagination = paginate_query.unwrap_or_default();
+	let pagination_docs = pagination.docs();
+
 	let (path_default_impl, path_body) = if let Some(body) = path_body {
 		(
 			quote::quote! {},
@@ -263,26 +272,8 @@ pub fn parse(input: TokenStream) -> TokenStream {
 		}
 	};
 
-	let query_name = if query.is_some() {
-		let name = format_ident!("{}Query", name);
-		if paginate_query.unwrap_or(false) {
-			quote::quote! {
-				models::api::Paginated<#name>
-			}
-		} else {
-			quote::quote! {
-				#name
-			}
-		}
-	} else if paginate_query.unwrap_or(false) {
-		quote::quote! {
-			models::api::Paginated<()>
-		}
-	} else {
-		quote::quote! {
-			()
-		}
-	};
+	let query_type_name = format_ident!("{}Query", name);
+	let query_name = pagination.query_type(query.is_some().then_some(&query_type_name));
 	let query_decl = if let Some(query) = query {
 		quote::quote! {
 			/// The query params for the #name endpoint.
@@ -298,7 +289,7 @@ pub fn parse(input: TokenStream) -> TokenStream {
 				serde::Deserialize,
 			)]
 			#[serde(rename_all = "camelCase")]
-			pub struct #query_name #query
+			pub struct #query_type_name #query
 
 			impl models::utils::RequiresResponseHeaders for #query_name {
 				type RequiredResponseHeaders = ();
@@ -491,6 +482,24 @@ pub fn parse(input: TokenStream) -> TokenStream {
 
 			type ResponseHeaders = #response_headers_name;
 			type ResponseBody = models::utils::GenericResponse;
+
+			fn docs() -> models::utils::EndpointDocs {
+				models::utils::EndpointDocs {
+					name: stringify!(#name),
+					documentation: #documentation,
+					method: ::http::Method::#method,
+					path: #path,
+					pagination: #pagination_docs,
+					websocket: true,
+					authentication: #authentication_docs,
+					path_params: #path_docs,
+					query: #query_docs,
+					request_headers: #request_headers_docs,
+					request_body: ::std::vec::Vec::new(),
+					response_headers: #response_headers_docs,
+					response_body: ::std::vec::Vec::new(),
+				}
+			}
 		}
 	}
 	.into()
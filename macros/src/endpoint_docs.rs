@@ -0,0 +1,218 @@
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{
+	parenthesized,
+	token::Paren,
+	Attribute,
+	Block,
+	Expr,
+	ExprLit,
+	FieldsNamed,
+	Lit,
+	LitStr,
+	Meta,
+	Token,
+};
+
+use crate::has_headers::get_option_inner_type;
+
+/// The casing used to serialize the fields of a struct. This is used to figure
+/// out the name of the field as seen by the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldCase {
+	/// The field is serialized as it is declared. Used for path parameters.
+	AsIs,
+	/// The field is serialized in camelCase. Used for the query and the
+	/// request and response bodies, which have `#[serde(rename_all =
+	/// "camelCase")]` set.
+	CamelCase,
+}
+
+/// The serde attributes of a field that affect how the field is documented.
+#[derive(Debug, Default)]
+struct SerdeFieldAttributes {
+	/// The name the field is renamed to, if any.
+	rename: Option<String>,
+	/// Whether the field is flattened into the parent struct.
+	flatten: bool,
+	/// Whether the field can be omitted by the client.
+	optional: bool,
+	/// Whether the field is skipped entirely.
+	skip: bool,
+}
+
+/// Generates a `Vec<models::utils::FieldDocs>` expression documenting each
+/// field of a struct that is serialized using serde (path, query, request and
+/// response bodies).
+pub fn field_docs(fields: Option<&FieldsNamed>, case: FieldCase) -> TokenStream2 {
+	let Some(fields) = fields else {
+		return quote::quote! {
+			::std::vec::Vec::new()
+		};
+	};
+
+	let fields = fields
+		.named
+		.iter()
+		.filter_map(|field| {
+			let attributes = get_serde_attributes(&field.attrs);
+			if attributes.skip {
+				return None;
+			}
+
+			let ident = field.ident.as_ref()?.to_string();
+			let ident = ident.trim_start_matches("r#");
+			let name = attributes.rename.unwrap_or_else(|| match case {
+				FieldCase::AsIs => ident.to_string(),
+				FieldCase::CamelCase => to_camel_case(ident),
+			});
+			let description = get_documentation(&field.attrs);
+			let required = !attributes.optional && get_option_inner_type(&field.ty).is_none();
+			let flatten = attributes.flatten;
+			let ty = &field.ty;
+
+			Some(quote::quote! {
+				models::utils::FieldDocs {
+					name: ::std::string::ToString::to_string(#name),
+					description: ::std::string::ToString::to_string(#description),
+					required: #required,
+					flatten: #flatten,
+					rust_type: ::std::any::type_name::<#ty>(),
+					schema: {
+						#[allow(unused_imports)]
+						use models::utils::{HasJsonSchema as _, MissingJsonSchema as _};
+						(&&models::utils::SchemaOf::<#ty>(::std::marker::PhantomData)).schema()
+					},
+				}
+			})
+		})
+		.collect::<Vec<_>>();
+
+	quote::quote! {
+		::std::vec![#(#fields),*]
+	}
+}
+
+/// Generates a `Vec<models::utils::FieldDocs>` expression documenting each
+/// header in a headers struct. The name of each header is taken from the
+/// [`headers::Header`] implementation of the field type.
+pub fn header_docs(fields: Option<&FieldsNamed>) -> TokenStream2 {
+	let Some(fields) = fields else {
+		return quote::quote! {
+			::std::vec::Vec::new()
+		};
+	};
+
+	let fields = fields.named.iter().map(|field| {
+		let (ty, required) = match get_option_inner_type(&field.ty) {
+			Some(inner_ty) => (inner_ty, false),
+			None => (&field.ty, true),
+		};
+		let description = get_documentation(&field.attrs);
+
+		quote::quote! {
+			models::utils::FieldDocs {
+				name: ::std::string::ToString::to_string(
+					<#ty as ::headers::Header>::name().as_str()
+				),
+				description: ::std::string::ToString::to_string(#description),
+				required: #required,
+				flatten: false,
+				rust_type: ::std::any::type_name::<#ty>(),
+				schema: ::std::option::Option::None,
+			}
+		}
+	});
+
+	quote::quote! {
+		::std::vec![#(#fields),*]
+	}
+}
+
+/// Generates a `models::utils::EndpointAuthentication` expression from the
+/// authentication block of an endpoint, if any.
+pub fn authentication_docs(auth: Option<&Block>) -> TokenStream2 {
+	if let Some(block) = auth {
+		quote::quote! {
+			{
+				let authenticator: models::utils::AppAuthentication<Self> = #block;
+				models::utils::EndpointAuthentication::from(&authenticator)
+			}
+		}
+	} else {
+		quote::quote! {
+			models::utils::EndpointAuthentication::None
+		}
+	}
+}
+
+/// Joins all the doc comments of an item into a single string.
+fn get_documentation(attrs: &[Attribute]) -> String {
+	attrs
+		.iter()
+		.filter(|attr| attr.path().is_ident("doc"))
+		.filter_map(|attr| {
+			let Meta::NameValue(meta) = &attr.meta else {
+				return None;
+			};
+			let Expr::Lit(ExprLit {
+				lit: Lit::Str(lit), ..
+			}) = &meta.value
+			else {
+				return None;
+			};
+			Some(lit.value().trim().to_string())
+		})
+		.collect::<Vec<_>>()
+		.join("\n")
+}
+
+/// Parses the `#[serde(...)]` attributes of a field. Any attribute that is not
+/// relevant for the documentation is ignored.
+fn get_serde_attributes(attrs: &[Attribute]) -> SerdeFieldAttributes {
+	let mut attributes = SerdeFieldAttributes::default();
+
+	for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+		// Malformed attributes would be caught by serde itself
+		let _ = attr.parse_nested_meta(|meta| {
+			if meta.path.is_ident("rename") && meta.input.peek(Token![=]) {
+				attributes.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+			} else if meta.path.is_ident("flatten") {
+				attributes.flatten = true;
+			} else if meta.path.is_ident("skip") {
+				attributes.skip = true;
+			} else if meta.path.is_ident("default") || meta.path.is_ident("skip_serializing_if") {
+				attributes.optional = true;
+			}
+
+			// Consume the value of the attribute, if any, so that the parser
+			// can continue to the next one
+			if meta.input.peek(Token![=]) {
+				meta.value()?.parse::<Expr>()?;
+			} else if meta.input.peek(Paren) {
+				let content;
+				parenthesized!(content in meta.input);
+				content.parse::<TokenStream2>()?;
+			}
+			Ok(())
+		});
+	}
+
+	attributes
+}
+
+/// Converts a snake_case identifier to camelCase, the same way serde does.
+fn to_camel_case(ident: &str) -> String {
+	let mut output = String::with_capacity(ident.len());
+	let mut capitalize = false;
+	for (index, character) in ident.chars().enumerate() {
+		if character == '_' {
+			capitalize = index > 0;
+		} else if capitalize {
+			output.extend(character.to_uppercase());
+			capitalize = false;
+		} else {
+			output.push(character);
+		}
+	}
+	output
+}
@@ -1,6 +1,17 @@
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
-use syn::{parse_macro_input, spanned::Spanned, Data, DataStruct, DeriveInput, Error, Field};
+use syn::{
+	parse_macro_input,
+	spanned::Spanned,
+	Data,
+	DataStruct,
+	DeriveInput,
+	Error,
+	Field,
+	GenericArgument,
+	PathArguments,
+	Type,
+};
 
 /// Provides a derive macro for the `HasHeaders` trait.
 pub fn parse(input: TokenStream) -> TokenStream {
@@ -23,6 +34,7 @@ pub fn parse(input: TokenStream) -> TokenStream {
 	let has_header_impls = fields
 		.clone()
 		.into_iter()
+		.filter(|field| get_option_inner_type(&field.ty).is_none())
 		.map(|field| {
 			let Field {
 				ty,
@@ -44,10 +56,20 @@ pub fn parse(input: TokenStream) -> TokenStream {
 		.into_iter()
 		.map(|field| {
 			let Field {
-				ident: field_ident, ..
+				ident: field_ident,
+				ty,
+				..
 			} = field;
-			quote::quote! {
-				::headers::HeaderMapExt::typed_insert(&mut map, self.#field_ident.clone());
+			if get_option_inner_type(&ty).is_some() {
+				quote::quote! {
+					if let Some(header) = self.#field_ident.clone() {
+						::headers::HeaderMapExt::typed_insert(&mut map, header);
+					}
+				}
+			} else {
+				quote::quote! {
+					::headers::HeaderMapExt::typed_insert(&mut map, self.#field_ident.clone());
+				}
 			}
 		})
 		.collect::<TokenStream2>();
@@ -56,15 +78,21 @@ pub fn parse(input: TokenStream) -> TokenStream {
 		.into_iter()
 		.map(|field| {
 			let Field { ident, ty, .. } = field;
-			quote::quote! {
-				#ident: ::headers::HeaderMapExt::typed_get::<#ty>(map)
-					.ok_or_else(|| {
-						tracing::debug!(
-							"Failed to parse header `{}`",
-							<#ty as ::headers::Header>::name().as_str()
-						);
-						::headers::Error::invalid()
-					})?,
+			if let Some(inner_ty) = get_option_inner_type(&ty) {
+				quote::quote! {
+					#ident: ::headers::HeaderMapExt::typed_get::<#inner_ty>(map),
+				}
+			} else {
+				quote::quote! {
+					#ident: ::headers::HeaderMapExt::typed_get::<#ty>(map)
+						.ok_or_else(|| {
+							tracing::debug!(
+								"Failed to parse header `{}`",
+								<#ty as ::headers::Header>::name().as_str()
+							);
+							::headers::Error::invalid()
+						})?,
+				}
 			}
 		})
 		.collect::<TokenStream2>();
@@ -89,3 +117,23 @@ pub fn parse(input: TokenStream) -> TokenStream {
 	}
 	.into()
 }
+
+/// Returns the inner type of an `Option<T>`, if the given type is an option.
+/// Optional headers are not required to be present in the request, and hence
+/// do not implement `HasHeader`.
+pub(crate) fn get_option_inner_type(ty: &Type) -> Option<&Type> {
+	let Type::Path(type_path) = ty else {
+		return None;
+	};
+	let segment = type_path.path.segments.last()?;
+	if segment.ident != "Option" {
+		return None;
+	}
+	let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+		return None;
+	};
+	match arguments.args.first()? {
+		GenericArgument::Type(inner_ty) => Some(inner_ty),
+		_ => None,
+	}
+}
@@ -15,8 +15,13 @@ mod declare_app_route;
 /// The proc macro for declaring a streaming endpoint. A streaming endpoint is
 /// basically a websocket endpoint.
 mod declare_stream_endpoint;
+/// Helpers to generate the documentation of an endpoint, which is used to
+/// build the OpenAPI document for the API.
+mod endpoint_docs;
 /// A derive macro for the `HasHeaders` trait.
 mod has_headers;
+/// The different ways in which the query of an endpoint can be paginated.
+mod pagination;
 /// A proc macro for stripping whitespaces and newlines from SQL queries.
 mod query;
 /// A macro to generate a recursive enum iterator.
@@ -49,7 +54,8 @@ mod version;
 ///         pub url_body: String,
 ///     },
 ///
-///     // Can also use paginated_query = ... for automatic pagination
+///     // Can also use `pagination = true` (or `pagination = cursor` for cursor
+///     // based pagination) for automatic pagination
 ///     query = {
 ///         pub param1: u32,
 ///     },
@@ -127,7 +133,8 @@ pub fn declare_app_route(input: TokenStream) -> TokenStream {
 ///         pub url_body: String,
 ///     },
 ///
-///     // Can also use paginated_query = ... for automatic pagination
+///     // Can also use `pagination = true` (or `pagination = cursor` for cursor
+///     // based pagination) for automatic pagination
 ///     query = {
 ///         pub param1: u32,
 ///     },
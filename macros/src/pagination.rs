@@ -0,0 +1,70 @@
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{
+	parse::{Parse, ParseStream},
+	Error,
+	Ident,
+	LitBool,
+};
+
+/// The kind of pagination used by the query of an endpoint. This is parsed from
+/// the `pagination = ...` field of an endpoint declaration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Pagination {
+	/// The endpoint is not paginated (`pagination = false`, or not set).
+	#[default]
+	None,
+	/// The endpoint is paginated using a page number and a count
+	/// (`pagination = true` or `pagination = offset`).
+	Offset,
+	/// The endpoint is paginated using opaque cursors (`pagination = cursor`).
+	Cursor,
+}
+
+impl Parse for Pagination {
+	fn parse(input: ParseStream) -> Result<Self, Error> {
+		if input.peek(LitBool) {
+			let lit = input.parse::<LitBool>()?;
+			return Ok(if lit.value { Self::Offset } else { Self::None });
+		}
+
+		let ident = input.parse::<Ident>()?;
+		match ident.to_string().as_str() {
+			"offset" => Ok(Self::Offset),
+			"cursor" => Ok(Self::Cursor),
+			_ => Err(Error::new(
+				ident.span(),
+				"Expected `true`, `false`, `offset` or `cursor`",
+			)),
+		}
+	}
+}
+
+impl Pagination {
+	/// Generates the type used as the query of the endpoint, wrapping the
+	/// declared query type (if any) in the paginated type.
+	pub fn query_type(self, query: Option<&Ident>) -> TokenStream2 {
+		let query = match query {
+			Some(query) => quote::quote! { #query },
+			None => quote::quote! { () },
+		};
+		match self {
+			Self::None => query,
+			Self::Offset => quote::quote! {
+				models::api::Paginated<#query>
+			},
+			Self::Cursor => quote::quote! {
+				models::api::CursorPaginated<#query>
+			},
+		}
+	}
+
+	/// Generates a `models::utils::EndpointPagination` expression, used to
+	/// document the pagination of the endpoint.
+	pub fn docs(self) -> TokenStream2 {
+		match self {
+			Self::None => quote::quote! { models::utils::EndpointPagination::None },
+			Self::Offset => quote::quote! { models::utils::EndpointPagination::Offset },
+			Self::Cursor => quote::quote! { models::utils::EndpointPagination::Cursor },
+		}
+	}
+}
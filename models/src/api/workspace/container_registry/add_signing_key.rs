@@ -0,0 +1,38 @@
+use crate::{prelude::*, utils::constants::RESOURCE_NAME_REGEX};
+
+macros::declare_api_endpoint!(
+	/// Adds a public key that the workspace trusts to sign container images.
+	/// Once a workspace trusts at least one key, deployments are only updated
+	/// to images that are signed by a trusted key.
+	AddContainerRegistrySigningKey,
+	POST "/workspace/:workspace_id/container-registry/signing-key" {
+		/// The workspace to add the signing key to.
+		pub workspace_id: Uuid,
+	},
+	request_headers = {
+		/// The authorization token
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.workspace_id,
+			permission: Permission::ContainerRegistryRepository(ContainerRegistryRepositoryPermission::Create),
+		}
+	},
+	request = {
+		/// The name of the signing key.
+		#[preprocess(trim, regex = RESOURCE_NAME_REGEX)]
+		pub name: String,
+		/// The PEM encoded public key. Only ECDSA P-256 keys (the default of
+		/// `cosign generate-key-pair`) are supported.
+		#[preprocess(trim)]
+		pub public_key: String,
+	},
+	response = {
+		/// The id of the added signing key.
+		#[serde(flatten)]
+		pub id: WithId<()>,
+	}
+);
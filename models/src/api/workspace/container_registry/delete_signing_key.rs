@@ -0,0 +1,25 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Removes a public key from the keys that the workspace trusts to sign
+	/// container images.
+	DeleteContainerRegistrySigningKey,
+	DELETE "/workspace/:workspace_id/container-registry/signing-key/:key_id" {
+		/// The workspace to remove the signing key from.
+		pub workspace_id: Uuid,
+		/// The id of the signing key to remove.
+		pub key_id: Uuid,
+	},
+	request_headers = {
+		/// The authorization token
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.workspace_id,
+			permission: Permission::ContainerRegistryRepository(ContainerRegistryRepositoryPermission::Delete),
+		}
+	}
+);
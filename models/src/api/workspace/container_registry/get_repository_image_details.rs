@@ -1,5 +1,6 @@
 use time::OffsetDateTime;
 
+use super::{ContainerRepositoryImagePlatform, ContainerRepositoryImageSignatureStatus};
 use crate::prelude::*;
 
 macros::declare_api_endpoint!(
@@ -36,5 +37,12 @@ macros::declare_api_endpoint!(
 		pub created: OffsetDateTime,
 		/// The tags of the container repository's image.
 		pub tags: Vec<String>,
+		/// The platforms that the image can run on. For an image index, this
+		/// has the manifest of each platform in the index, and the size of the
+		/// image is the total size of all of them.
+		pub platforms: Vec<ContainerRepositoryImagePlatform>,
+		/// The result of verifying the signatures of the image against the
+		/// signing keys trusted by the workspace.
+		pub signature: ContainerRepositoryImageSignatureStatus,
 	}
 );
@@ -1,4 +1,4 @@
-use super::ContainerRepository;
+use super::{ContainerRegistryStorageUsage, ContainerRepository};
 use crate::prelude::*;
 
 macros::declare_api_endpoint!(
@@ -25,5 +25,10 @@ macros::declare_api_endpoint!(
 	response = {
 		/// The information of the container repository.
 		pub repository: ContainerRepository,
+		/// The storage used by the repository, and its storage limit
+		pub repository_usage: ContainerRegistryStorageUsage,
+		/// The storage used by all the repositories in the workspace, and the
+		/// storage limit of the workspace
+		pub workspace_usage: ContainerRegistryStorageUsage,
 	}
 );
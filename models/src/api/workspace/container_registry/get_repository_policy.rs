@@ -0,0 +1,30 @@
+use super::ContainerRepositoryPolicy;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Gets the tag immutability and retention policy of a container
+	/// repository in the workspace.
+	GetContainerRepositoryPolicy,
+	GET "/workspace/:workspace_id/container-registry/:repository_id/policy" {
+		/// The workspace to get the container repository in.
+		pub workspace_id: Uuid,
+		/// The id of the repository to get the policy of.
+		pub repository_id: Uuid,
+	},
+	request_headers = {
+		/// The authorization token
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.workspace_id,
+			permission: Permission::ContainerRegistryRepository(ContainerRegistryRepositoryPermission::View),
+		}
+	},
+	response = {
+		/// The policy of the container repository.
+		pub policy: ContainerRepositoryPolicy,
+	}
+);
@@ -1,4 +1,4 @@
-use super::ContainerRepository;
+use super::{ContainerRegistryStorageUsage, ContainerRepository};
 use crate::prelude::*;
 
 macros::declare_api_endpoint!(
@@ -19,6 +19,14 @@ macros::declare_api_endpoint!(
 			extract_workspace_id: |req| req.path.workspace_id
 		}
 	},
+	query = {
+		/// Only list the repositories that match this filter. The repositories
+		/// can be filtered by their `name`, `created` and `lastUpdated` time
+		pub filter: Option<ListFilter<ContainerRepositoryListField>>,
+		/// The fields to sort the list of repositories by. Defaults to the
+		/// oldest repositories first
+		pub sort: Option<ListSort<ContainerRepositoryListField>>,
+	},
 	pagination = true,
 	response_headers = {
 		/// The total number of container repositories in the requested workspace
@@ -26,6 +34,33 @@ macros::declare_api_endpoint!(
 	},
 	response = {
 		/// List of container repositories in the current workspace
-		pub repositories: Vec<WithId<ContainerRepository>>
+		pub repositories: Vec<WithId<ContainerRepository>>,
+		/// The storage used by all the repositories in the workspace, and the
+		/// storage limit of the workspace. Blobs shared between repositories
+		/// are only counted once, so this can be less than the sum of the
+		/// sizes of the repositories
+		pub workspace_usage: ContainerRegistryStorageUsage,
 	}
 );
+
+/// The fields that the list of container repositories can be filtered and
+/// sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::Display)]
+#[strum(serialize_all = "camelCase")]
+pub enum ContainerRepositoryListField {
+	/// The name of the repository
+	Name,
+	/// When the repository was created
+	Created,
+	/// When a tag or a manifest was last pushed to the repository
+	LastUpdated,
+}
+
+impl ListField for ContainerRepositoryListField {
+	fn kind(self) -> ListFieldKind {
+		match self {
+			Self::Name => ListFieldKind::Text,
+			Self::Created | Self::LastUpdated => ListFieldKind::Timestamp,
+		}
+	}
+}
@@ -35,10 +35,24 @@ macros::declare_api_endpoint!(
 			permission: Permission::ContainerRegistryRepository(ContainerRegistryRepositoryPermission::View),
 		}
 	},
+	query = {
+		/// Only return the tags that come after the one with this cursor,
+		/// instead of using the page number. The cursors are given in the
+		/// `Link` header of the response
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub after: Option<Cursor>,
+		/// Only return the tags that come before the one with this cursor,
+		/// instead of using the page number. This is ignored if `after` is set
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub before: Option<Cursor>,
+	},
 	pagination = true,
 	response_headers = {
-		/// The total number of container repositories in the requested workspace
+		/// The total number of tags in the repository
 		pub total_count: TotalCountHeader,
+		/// The links to the next and previous pages of tags. This is only
+		/// set for the first page, and for pages requested with a cursor
+		pub link: Option<LinkHeader>,
 	},
 	response = {
 		/// List of tags in the current container repository
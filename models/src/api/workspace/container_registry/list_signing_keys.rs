@@ -0,0 +1,27 @@
+use super::ContainerRegistrySigningKey;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Lists the public keys that the workspace trusts to sign container
+	/// images.
+	ListContainerRegistrySigningKeys,
+	GET "/workspace/:workspace_id/container-registry/signing-key" {
+		/// The workspace to list the signing keys of.
+		pub workspace_id: Uuid,
+	},
+	request_headers = {
+		/// The authorization token
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::WorkspaceMembershipAuthenticator {
+			extract_workspace_id: |req| req.path.workspace_id
+		}
+	},
+	response = {
+		/// The signing keys trusted by the workspace.
+		pub keys: Vec<WithId<ContainerRegistrySigningKey>>,
+	}
+);
@@ -1,32 +1,51 @@
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 
+use crate::prelude::*;
+
+/// The endpoint to add a public key that the workspace trusts to sign images
+mod add_signing_key;
 /// The endpoint to create a repository
 mod create_repository;
 /// The endpoint to delete a repository
 mod delete_repository;
 /// The endpoint to delete an image from a repository
 mod delete_repository_image;
+/// The endpoint to remove a trusted public key from the workspace
+mod delete_signing_key;
 /// The endpoint to get the exposed ports of an image in a repository
 mod get_exposed_ports;
 /// The endpoint to get the details of an image in a repository
 mod get_repository_image_details;
 /// The endpoint to get the details of a repository
 mod get_repository_info;
+/// The endpoint to get the tag immutability and retention policy of a
+/// repository
+mod get_repository_policy;
 /// The endpoint to list all the repositories in a workspace
 mod list_repositories;
 /// The endpoint to list all the tags of a repository
 mod list_repository_tags;
+/// The endpoint to list all the public keys trusted by the workspace
+mod list_signing_keys;
+/// The endpoint to update the tag immutability and retention policy of a
+/// repository
+mod update_repository_policy;
 
 pub use self::{
+	add_signing_key::*,
 	create_repository::*,
 	delete_repository::*,
 	delete_repository_image::*,
+	delete_signing_key::*,
 	get_exposed_ports::*,
 	get_repository_image_details::*,
 	get_repository_info::*,
+	get_repository_policy::*,
 	list_repositories::*,
 	list_repository_tags::*,
+	list_signing_keys::*,
+	update_repository_policy::*,
 };
 /// Contains tag information of a repository
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -48,6 +67,87 @@ pub struct ContainerRepositoryImageInfo {
 	/// The created timestamp
 	pub created: OffsetDateTime,
 }
+/// The platform that an image in a repository can run on. An image pushed as
+/// an image index has one of these for each manifest in the index.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerRepositoryImagePlatform {
+	/// The digest of the manifest of the image for this platform
+	pub digest: String,
+	/// The operating system of the platform (eg: `linux`)
+	pub os: String,
+	/// The CPU architecture of the platform (eg: `amd64`, `arm64`)
+	pub architecture: String,
+	/// The variant of the CPU architecture, if any (eg: `v8` for `arm64`)
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub variant: Option<String>,
+	/// The size of the image for this platform
+	pub size: u64,
+}
+/// The result of verifying the cosign signatures of an image against the
+/// public keys trusted by the workspace.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "status")]
+pub enum ContainerRepositoryImageSignatureStatus {
+	/// The image is signed by one of the trusted keys of the workspace
+	#[serde(rename_all = "camelCase")]
+	Verified {
+		/// The trusted key that the image is signed by
+		key_id: Uuid,
+	},
+	/// The image has signatures, but none of them are valid signatures by a
+	/// trusted key of the workspace
+	Unverified,
+	/// The image doesn't have any signatures
+	Unsigned,
+}
+
+/// A public key that the workspace trusts to sign images. Once a workspace
+/// trusts at least one key, only images signed by a trusted key are deployed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerRegistrySigningKey {
+	/// The name of the key
+	pub name: String,
+	/// The PEM encoded public key (as generated by `cosign generate-key-pair`)
+	pub public_key: String,
+	/// When the key was added to the workspace
+	pub created: OffsetDateTime,
+}
+
+/// The tag immutability, retention and storage policy of a repository. Tags
+/// used by a deployment and images that are (or were) deployed are never
+/// deleted by the retention policy.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerRepositoryPolicy {
+	/// The patterns of the tags that can't be overwritten or deleted once
+	/// pushed (eg: `v*`)
+	pub immutable_tag_patterns: Vec<String>,
+	/// The number of most recently pushed tags to keep, if any
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub keep_last_tags: Option<u32>,
+	/// The number of days after which untagged manifests are deleted, if any
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub untagged_manifest_retention_days: Option<u32>,
+	/// The maximum storage (in bytes) that the repository can use, if any
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub storage_limit: Option<u64>,
+}
+
+/// The storage used by a repository (or by all the repositories of a
+/// workspace), along with the limit it can't grow past. Blobs shared between
+/// images are only counted once.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerRegistryStorageUsage {
+	/// The storage used, in bytes
+	pub used: u64,
+	/// The maximum storage that can be used, in bytes, if limited
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub limit: Option<u64>,
+}
+
 /// Represents a repository of container images in Patr's in-build container
 /// registry.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
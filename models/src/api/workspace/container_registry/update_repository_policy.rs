@@ -0,0 +1,45 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Replaces the tag immutability, retention and storage policy of a
+	/// container repository in the workspace.
+	UpdateContainerRepositoryPolicy,
+	PATCH "/workspace/:workspace_id/container-registry/:repository_id/policy" {
+		/// The workspace to update the container repository in.
+		pub workspace_id: Uuid,
+		/// The id of the repository to update the policy of.
+		pub repository_id: Uuid,
+	},
+	request_headers = {
+		/// The authorization token
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.workspace_id,
+			permission: Permission::ContainerRegistryRepository(ContainerRegistryRepositoryPermission::Edit),
+		}
+	},
+	request = {
+		/// The patterns of the tags that can't be overwritten or deleted once
+		/// pushed. `*` matches any number of characters and `?` matches a
+		/// single character.
+		#[preprocess(none)]
+		pub immutable_tag_patterns: Vec<String>,
+		/// The number of most recently pushed tags to keep. Older tags are
+		/// deleted, unless they are immutable or used by a deployment.
+		#[preprocess(optional(range(min = 1)))]
+		pub keep_last_tags: Option<u32>,
+		/// The number of days after which manifests that aren't tagged are
+		/// deleted, unless they are used by a deployment.
+		#[preprocess(optional(range(min = 1)))]
+		pub untagged_manifest_retention_days: Option<u32>,
+		/// The maximum storage (in bytes) that the repository can use. Pushing
+		/// blobs or manifests that would take the repository over this limit
+		/// is denied.
+		#[preprocess(optional(range(min = 1)))]
+		pub storage_limit: Option<u64>,
+	}
+);
@@ -22,10 +22,25 @@ macros::declare_api_endpoint!(
 			permission: Permission::Deployment(DeploymentPermission::View),
 		}
 	},
+	query = {
+		/// Only return the deploys that come after the one with this cursor,
+		/// instead of using the page number. The cursors are given in the
+		/// `Link` header of the response
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub after: Option<Cursor>,
+		/// Only return the deploys that come before the one with this cursor,
+		/// instead of using the page number. This is ignored if `after` is set
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub before: Option<Cursor>,
+	},
 	pagination = true,
 	response_headers = {
-		/// The total number of databases in the requested workspace
+		/// The total number of deploys in the history of the deployment
 		pub total_count: TotalCountHeader,
+		/// The links to the next and previous pages of the deploy history. This
+		/// is only set for the first page, and for pages requested with a
+		/// cursor
+		pub link: Option<LinkHeader>,
 	},
 	response = {
 		/// The deployment history containing:
@@ -5,8 +5,10 @@ use time::OffsetDateTime;
 mod delete_deploy_history;
 /// The endpoint to list the deployment history of a deployment
 mod list_deploy_history;
+/// The endpoint to roll a deployment back to an image from its deploy history
+mod rollback_deployment;
 
-pub use self::{delete_deploy_history::*, list_deploy_history::*};
+pub use self::{delete_deploy_history::*, list_deploy_history::*, rollback_deployment::*};
 
 /// The deployment history of a deployment. This is a list of the images digests
 /// the deployment has ran and the timestamp of when the digest previously ran
@@ -18,3 +20,30 @@ pub struct DeploymentDeployHistory {
 	/// The timestamp of when the digest previously ran
 	pub created: OffsetDateTime,
 }
+
+#[cfg(test)]
+mod test {
+	use super::RollbackDeploymentPath;
+	use crate::prelude::*;
+
+	#[test]
+	fn assert_rollback_path_contains_digest() {
+		let workspace_id = Uuid::new_v4();
+		let deployment_id = Uuid::new_v4();
+		let image_digest =
+			"sha256:2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae".to_string();
+
+		assert_eq!(
+			RollbackDeploymentPath {
+				workspace_id,
+				deployment_id,
+				image_digest: image_digest.clone(),
+			}
+			.to_string(),
+			format!(
+				"/workspace/{}/deployment/{}/deploy-history/{}/rollback",
+				workspace_id, deployment_id, image_digest
+			)
+		);
+	}
+}
@@ -0,0 +1,33 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to roll a deployment back to an image from its deploy history
+	RollbackDeployment,
+	POST "/workspace/:workspace_id/deployment/:deployment_id/deploy-history/:image_digest/rollback" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID of the deployment to roll back
+		pub deployment_id: Uuid,
+		/// The image digest from the deploy history to roll back to
+		pub image_digest: String,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit)
+		}
+	},
+	request = {
+		/// Whether to also restore the ports, environment variables, probes and
+		/// config mounts of the deployment, as they were in the latest revision
+		/// that ran the image. If set to false, only the image is rolled back
+		#[preprocess(none)]
+		pub restore_running_details: bool,
+	}
+);
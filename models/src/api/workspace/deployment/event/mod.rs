@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use super::runtime::DeploymentContainerEventKind;
+use crate::prelude::*;
+
+/// The endpoint to list the events of a deployment
+mod list_deployment_events;
+/// The endpoint to stream the events of a deployment as they happen
+mod stream_deployment_events;
+
+pub use self::{list_deployment_events::*, stream_deployment_events::*};
+
+/// Something that happened to a deployment, as reported by the runner it runs
+/// on. The events of a deployment make up a timeline of what happened to it,
+/// and are only kept for a limited time.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentEvent {
+	/// The ID of the event
+	pub id: Uuid,
+	/// What happened to the deployment
+	pub kind: DeploymentEventKind,
+	/// The name of the container (or pod) the event happened to, if the event
+	/// is about a single container
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub container: Option<String>,
+	/// More details about the event, if there are any
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub message: Option<String>,
+	/// The timestamp of when the event happened
+	pub timestamp: OffsetDateTime,
+}
+
+/// All the kinds of events that can happen to a deployment
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(sqlx::Type))]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+	not(target_arch = "wasm32"),
+	sqlx(type_name = "DEPLOYMENT_EVENT_KIND", rename_all = "snake_case")
+)]
+pub enum DeploymentEventKind {
+	/// The image of the deployment was pulled
+	ImagePulled,
+	/// The image of the deployment could not be pulled
+	ImagePullFailed,
+	/// A container of the deployment was started
+	ContainerStarted,
+	/// A container of the deployment was stopped
+	ContainerStopped,
+	/// A container of the deployment exited with an error
+	ContainerCrashed,
+	/// A container of the deployment was killed for using more memory than
+	/// it is allowed to
+	OutOfMemory,
+	/// A health check of a container of the deployment failed
+	ProbeFailed,
+	/// The number of instances the deployment runs changed
+	Scaled,
+	/// A revision of the deployment finished rolling out to all its instances
+	RolloutCompleted,
+	/// The runner failed to reconcile the deployment
+	ReconcileFailed,
+}
+
+impl From<DeploymentContainerEventKind> for DeploymentEventKind {
+	fn from(kind: DeploymentContainerEventKind) -> Self {
+		match kind {
+			DeploymentContainerEventKind::ImagePulled => Self::ImagePulled,
+			DeploymentContainerEventKind::ImagePullFailed => Self::ImagePullFailed,
+			DeploymentContainerEventKind::Started => Self::ContainerStarted,
+			DeploymentContainerEventKind::Stopped => Self::ContainerStopped,
+			DeploymentContainerEventKind::Crashed => Self::ContainerCrashed,
+			DeploymentContainerEventKind::OutOfMemory => Self::OutOfMemory,
+			DeploymentContainerEventKind::Unhealthy => Self::ProbeFailed,
+		}
+	}
+}
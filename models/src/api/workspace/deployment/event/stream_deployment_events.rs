@@ -0,0 +1,33 @@
+use super::DeploymentEvent;
+use crate::prelude::*;
+
+macros::declare_stream_endpoint!(
+	/// Route to stream the events of a deployment as they happen
+	StreamDeploymentEvents,
+	GET "/workspace/:workspace_id/deployment/:deployment_id/event/stream" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID to stream the events of
+		pub deployment_id: Uuid,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::View),
+		}
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	server_msg = {
+		/// Something happened to the deployment
+		EventOccurred {
+			/// The event that happened
+			event: DeploymentEvent,
+		},
+	},
+	client_msg = {},
+);
@@ -0,0 +1,62 @@
+use crate::prelude::*;
+
+macros::declare_stream_endpoint!(
+	/// Route to open an interactive terminal session in a running container of
+	/// a deployment. The session is proxied through the runner that the
+	/// deployment runs on
+	ExecDeployment,
+	GET "/workspace/:workspace_id/deployment/:deployment_id/exec" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID to open the session in
+		pub deployment_id: Uuid,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Exec),
+		}
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	query = {
+		/// The command to run, interpreted by `/bin/sh`. If not provided, an
+		/// interactive shell is started
+		pub command: Option<String>,
+	},
+	server_msg = {
+		/// The process in the container wrote to the terminal
+		Output {
+			/// The data that was written
+			data: Base64String,
+		},
+		/// The process in the container exited, and the session is over
+		Exited {
+			/// The exit code of the process, if the runner could find it
+			exit_code: Option<i64>,
+		},
+		/// The runner could not start the session, or lost it
+		Failed {
+			/// A description of what went wrong
+			error: String,
+		},
+	},
+	client_msg = {
+		/// Input typed into the terminal, to be sent to the process
+		Input {
+			/// The data that was typed
+			data: Base64String,
+		},
+		/// The terminal was resized
+		Resized {
+			/// The number of rows in the terminal
+			rows: u16,
+			/// The number of columns in the terminal
+			cols: u16,
+		},
+	},
+);
@@ -0,0 +1,51 @@
+use crate::prelude::*;
+
+macros::declare_stream_endpoint!(
+	/// Route to forward a TCP connection to a port of a running container of a
+	/// deployment. Each connection is forwarded over a WebSocket of its own,
+	/// through the runner that the deployment runs on
+	ForwardDeploymentPort,
+	GET "/workspace/:workspace_id/deployment/:deployment_id/port-forward" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID to forward the port of
+		pub deployment_id: Uuid,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::PortForward),
+		}
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	query = {
+		/// The port of the container to connect to. This doesn't need to be
+		/// one of the ports exposed by the deployment
+		pub port: u16,
+	},
+	server_msg = {
+		/// The container sent data over the connection
+		Data {
+			/// The data that was sent
+			data: Base64String,
+		},
+		/// The connection to the container was closed, and the session is over
+		Closed {
+			/// A description of what went wrong, if the connection wasn't
+			/// closed normally
+			error: Option<String>,
+		},
+	},
+	client_msg = {
+		/// Data to send to the container over the connection
+		Data {
+			/// The data to send
+			data: Base64String,
+		},
+	},
+);
@@ -1,4 +1,9 @@
-use super::{Deployment, DeploymentRunningDetails};
+use super::{
+	rollout::DeploymentRollout,
+	runtime::DeploymentRuntime,
+	Deployment,
+	DeploymentRunningDetails,
+};
 use crate::prelude::*;
 
 macros::declare_api_endpoint!(
@@ -22,6 +27,10 @@ macros::declare_api_endpoint!(
 			permission: Permission::Deployment(DeploymentPermission::View),
 		}
 	},
+	response_headers = {
+		/// The current version of the deployment
+		pub etag: ETag,
+	},
 	response = {
 		/// The deployment metadata information containing:
 		/// name - The deployment name
@@ -43,7 +52,15 @@ macros::declare_api_endpoint!(
 		/// liveness_probe - The liveness probe configuration
 		/// config_mounts - The configuration mounts
 		/// volumes - The volumes
+		/// rollout_strategy - The strategy used to roll out changes
 		#[serde(flatten)]
 		pub running_details: DeploymentRunningDetails,
+		/// The rollout of the latest revision of the deployment, if any
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub rollout: Option<DeploymentRollout>,
+		/// The state of the deployment as last reported by its runner, if the
+		/// runner has reported on it
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub runtime: Option<DeploymentRuntime>,
 	}
 );
@@ -19,6 +19,14 @@ macros::declare_api_endpoint!(
 			extract_workspace_id: |req| req.path.workspace_id,
 		}
 	},
+	query = {
+		/// Only list the deployments that match this filter. The deployments
+		/// can be filtered by their `name`, `status` and `created` time
+		pub filter: Option<ListFilter<DeploymentListField>>,
+		/// The fields to sort the list of deployments by. Defaults to the most
+		/// recently created deployments first
+		pub sort: Option<ListSort<DeploymentListField>>,
+	},
 	pagination = true,
 	response_headers = {
 		/// The total number of deployment in the requested workspace
@@ -35,3 +43,32 @@ macros::declare_api_endpoint!(
 		pub deployments: Vec<WithId<Deployment>>,
 	}
 );
+
+/// The fields that the list of deployments can be filtered and sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::Display)]
+#[strum(serialize_all = "camelCase")]
+pub enum DeploymentListField {
+	/// The name of the deployment
+	Name,
+	/// The current status of the deployment
+	Status,
+	/// When the deployment was created
+	Created,
+}
+
+impl ListField for DeploymentListField {
+	fn kind(self) -> ListFieldKind {
+		match self {
+			Self::Name => ListFieldKind::Text,
+			Self::Status => ListFieldKind::Enum(&[
+				"created",
+				"deploying",
+				"running",
+				"stopped",
+				"errored",
+				"unreachable",
+			]),
+			Self::Created => ListFieldKind::Timestamp,
+		}
+	}
+}
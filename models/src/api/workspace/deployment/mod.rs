@@ -6,11 +6,27 @@ use time::OffsetDateTime;
 /// The history of a deployment's deploys. This contains the image digest and
 /// the timestamp of when the deploy was created
 pub mod deploy_history;
+/// The events of a deployment, like its containers being started or its
+/// rollouts completing. Together, they make up a timeline of the deployment
+pub mod event;
+/// The revisions of a deployment's configuration. A revision is recorded every
+/// time the deployment is changed, and any two revisions can be compared
+pub mod revision;
+/// The rollouts of a deployment's revisions. A rollout tracks how a revision is
+/// being rolled out, and lets a canary be promoted or aborted
+pub mod rollout;
+/// The state of a deployment as reported by the runner it runs on, like the
+/// number of instances that are ready and what happened to its containers
+pub mod runtime;
 
 /// The endpoint to create a deployment
 mod create_deployment;
 /// The endpoint to delete a deployment
 mod delete_deployment;
+/// The endpoint to open an interactive terminal session in a deployment
+mod exec_deployment;
+/// The endpoint to forward a port of a deployment's containers to the user
+mod forward_deployment_port;
 /// The endpoint to get the details of a deployment
 mod get_deployment_info;
 /// The endpoint to get the logs of a deployment
@@ -21,6 +37,8 @@ mod get_deployment_metric;
 mod list_all_deployment_machine_type;
 /// The endpoint to list all the deployments in a workspace
 mod list_deployment;
+/// The endpoint to restart a deployment without changing its configuration
+mod restart_deployment;
 /// The endpoint to start a deployment
 mod start_deployment;
 /// The endpoint to stop a deployment
@@ -33,11 +51,14 @@ mod update_deployment;
 pub use self::{
 	create_deployment::*,
 	delete_deployment::*,
+	exec_deployment::*,
+	forward_deployment_port::*,
 	get_deployment_info::*,
 	get_deployment_logs::*,
 	get_deployment_metric::*,
 	list_all_deployment_machine_type::*,
 	list_deployment::*,
+	restart_deployment::*,
 	start_deployment::*,
 	stop_deployment::*,
 	stream_deployment_logs::*,
@@ -120,6 +141,143 @@ pub struct DeploymentRunningDetails {
 	/// mounted on
 	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
 	pub volumes: BTreeMap<Uuid, String>,
+	/// The strategy used to roll out changes to the deployment
+	#[serde(default)]
+	pub rollout_strategy: DeploymentRolloutStrategy,
+}
+
+/// The strategy used to replace the running instances of a deployment when it
+/// is changed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum DeploymentRolloutStrategy {
+	/// All the running instances are stopped before the new ones are started.
+	/// The deployment is unavailable while the new instances start
+	Recreate,
+	/// The instances are replaced a few at a time
+	#[serde(rename_all = "camelCase")]
+	Rolling {
+		/// The number of instances that can be started over the desired number
+		/// of instances while rolling out
+		max_surge: u16,
+		/// The number of instances that can be unavailable while rolling out
+		max_unavailable: u16,
+	},
+	/// A complete set of new instances is started alongside the old ones, and
+	/// the old ones are only removed once all the new ones are ready
+	BlueGreen,
+	/// A new image is only run on a percentage of the instances, until the
+	/// rollout is promoted (or aborted). Changes that don't change the image
+	/// are rolled out like a rolling update
+	Canary {
+		/// The percentage of instances that run the new image until the
+		/// rollout is promoted
+		percentage: u8,
+	},
+}
+
+impl DeploymentRolloutStrategy {
+	/// Creates a strategy from the columns it is stored as in the database.
+	/// Returns `None` if the columns don't describe a valid strategy
+	pub fn from_parts(
+		strategy_type: DeploymentRolloutStrategyType,
+		max_surge: Option<u16>,
+		max_unavailable: Option<u16>,
+		canary_percentage: Option<u8>,
+	) -> Option<Self> {
+		match strategy_type {
+			DeploymentRolloutStrategyType::Recreate => Some(Self::Recreate),
+			DeploymentRolloutStrategyType::Rolling => Some(Self::Rolling {
+				max_surge: max_surge?,
+				max_unavailable: max_unavailable?,
+			}),
+			DeploymentRolloutStrategyType::BlueGreen => Some(Self::BlueGreen),
+			DeploymentRolloutStrategyType::Canary => Some(Self::Canary {
+				percentage: canary_percentage?,
+			}),
+		}
+	}
+
+	/// Get the type of the strategy, without its parameters
+	pub fn strategy_type(&self) -> DeploymentRolloutStrategyType {
+		match self {
+			Self::Recreate => DeploymentRolloutStrategyType::Recreate,
+			Self::Rolling { .. } => DeploymentRolloutStrategyType::Rolling,
+			Self::BlueGreen => DeploymentRolloutStrategyType::BlueGreen,
+			Self::Canary { .. } => DeploymentRolloutStrategyType::Canary,
+		}
+	}
+
+	/// Get the max surge, if the strategy is a rolling update
+	pub fn max_surge(&self) -> Option<u16> {
+		match self {
+			Self::Rolling { max_surge, .. } => Some(*max_surge),
+			_ => None,
+		}
+	}
+
+	/// Get the max unavailable instances, if the strategy is a rolling update
+	pub fn max_unavailable(&self) -> Option<u16> {
+		match self {
+			Self::Rolling {
+				max_unavailable, ..
+			} => Some(*max_unavailable),
+			_ => None,
+		}
+	}
+
+	/// Get the percentage of instances that run the new image, if the strategy
+	/// is a canary
+	pub fn canary_percentage(&self) -> Option<u8> {
+		match self {
+			Self::Canary { percentage } => Some(*percentage),
+			_ => None,
+		}
+	}
+
+	/// Checks if the parameters of the strategy are valid. A rolling update
+	/// has to be able to either start or stop at least one instance at a
+	/// time, and a canary has to run on some, but not all, of the instances
+	pub fn is_valid(&self) -> bool {
+		match self {
+			Self::Recreate | Self::BlueGreen => true,
+			Self::Rolling {
+				max_surge,
+				max_unavailable,
+			} => *max_surge > 0 || *max_unavailable > 0,
+			Self::Canary { percentage } => (1..100).contains(percentage),
+		}
+	}
+}
+
+impl Default for DeploymentRolloutStrategy {
+	fn default() -> Self {
+		Self::Rolling {
+			max_surge: 1,
+			max_unavailable: 0,
+		}
+	}
+}
+
+/// The type of a rollout strategy, without its parameters. This is how the
+/// strategy is stored in the database
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(sqlx::Type))]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+	not(target_arch = "wasm32"),
+	sqlx(type_name = "DEPLOYMENT_ROLLOUT_STRATEGY", rename_all = "snake_case")
+)]
+pub enum DeploymentRolloutStrategyType {
+	/// Stop all the instances before starting the new ones
+	Recreate,
+	/// Replace the instances a few at a time
+	Rolling,
+	/// Start a complete set of new instances before removing the old ones
+	BlueGreen,
+	/// Run a new image on a percentage of the instances until it is promoted
+	Canary,
 }
 
 /// The type of environment variable
@@ -381,3 +539,108 @@ pub struct DeploymentLog {
 	/// The logs of a deployment
 	pub log: String,
 }
+
+#[cfg(test)]
+mod test {
+	use super::{
+		DeploymentRolloutStrategy,
+		DeploymentRolloutStrategyType,
+		ExecDeploymentRequest,
+		ForwardDeploymentPortRequest,
+	};
+	use crate::{prelude::*, utils::EndpointAuthentication};
+
+	#[test]
+	fn assert_rolling_strategy_must_replace_instances() {
+		let rolling = |max_surge, max_unavailable| DeploymentRolloutStrategy::Rolling {
+			max_surge,
+			max_unavailable,
+		};
+
+		assert!(rolling(1, 0).is_valid());
+		assert!(rolling(0, 1).is_valid());
+		assert!(rolling(2, 3).is_valid());
+		assert!(!rolling(0, 0).is_valid());
+	}
+
+	#[test]
+	fn assert_canary_strategy_must_split_instances() {
+		let canary = |percentage| DeploymentRolloutStrategy::Canary { percentage };
+
+		assert!(canary(1).is_valid());
+		assert!(canary(50).is_valid());
+		assert!(canary(99).is_valid());
+		assert!(!canary(0).is_valid());
+		assert!(!canary(100).is_valid());
+		assert!(!canary(u8::MAX).is_valid());
+	}
+
+	#[test]
+	fn assert_strategy_is_read_from_parts() {
+		assert_eq!(
+			DeploymentRolloutStrategy::from_parts(
+				DeploymentRolloutStrategyType::Rolling,
+				Some(1),
+				Some(0),
+				None
+			),
+			Some(DeploymentRolloutStrategy::default())
+		);
+		assert_eq!(
+			DeploymentRolloutStrategy::from_parts(
+				DeploymentRolloutStrategyType::Canary,
+				None,
+				None,
+				Some(10)
+			),
+			Some(DeploymentRolloutStrategy::Canary { percentage: 10 })
+		);
+		assert_eq!(
+			DeploymentRolloutStrategy::from_parts(
+				DeploymentRolloutStrategyType::Rolling,
+				Some(1),
+				None,
+				None
+			),
+			None
+		);
+		assert_eq!(
+			DeploymentRolloutStrategy::from_parts(
+				DeploymentRolloutStrategyType::Canary,
+				None,
+				None,
+				None
+			),
+			None
+		);
+	}
+
+	#[test]
+	fn assert_exec_needs_exec_permission() {
+		let permission = Permission::Deployment(DeploymentPermission::Exec);
+
+		assert_eq!(
+			ExecDeploymentRequest::docs().authentication,
+			EndpointAuthentication::ResourcePermission { permission }
+		);
+		assert!(Permission::list_all_permissions().contains(&permission));
+		assert_eq!(permission.to_string(), "deployment::exec");
+		assert_eq!("deployment::exec".parse::<Permission>(), Ok(permission));
+	}
+
+	#[test]
+	fn assert_port_forward_needs_port_forward_permission() {
+		let permission = Permission::Deployment(DeploymentPermission::PortForward);
+
+		assert_eq!(
+			ForwardDeploymentPortRequest::docs().authentication,
+			EndpointAuthentication::ResourcePermission { permission }
+		);
+		assert!(Permission::list_all_permissions().contains(&permission));
+		assert_eq!(permission.to_string(), "deployment::portForward");
+		assert_eq!(
+			"deployment::portForward".parse::<Permission>(),
+			Ok(permission)
+		);
+	}
+}
@@ -0,0 +1,26 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to do a rolling restart of a deployment. The instances of the
+	/// deployment are replaced one at a time, without changing its
+	/// configuration
+	RestartDeployment,
+	POST "/workspace/:workspace_id/deployment/:deployment_id/restart" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID of the deployment to restart
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Start),
+		}
+	}
+);
@@ -0,0 +1,38 @@
+use super::DeploymentRevisionChange;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to get the fields that changed between two revisions of a
+	/// deployment
+	GetDeploymentRevisionDiff,
+	GET "/workspace/:workspace_id/deployment/:deployment_id/revision/diff" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID to compare the revisions of
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::View),
+		}
+	},
+	query = {
+		/// The revision to compare from
+		#[preprocess(none)]
+		pub from: u64,
+		/// The revision to compare to
+		#[preprocess(none)]
+		pub to: u64,
+	},
+	response = {
+		/// The fields that are different in the two revisions
+		pub changes: Vec<DeploymentRevisionChange>,
+	}
+);
@@ -0,0 +1,34 @@
+use super::DeploymentRevision;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to list the revisions of a deployment, newest first
+	ListDeploymentRevisions,
+	GET "/workspace/:workspace_id/deployment/:deployment_id/revision" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID to get the revisions for
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::View),
+		}
+	},
+	pagination = cursor,
+	response_headers = {
+		/// The links to the next and previous pages of the revisions
+		pub link: LinkHeader,
+	},
+	response = {
+		/// The revisions of the deployment
+		pub revisions: Vec<DeploymentRevision>,
+	}
+);
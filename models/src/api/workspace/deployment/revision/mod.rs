@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::OffsetDateTime;
+
+use super::{Deployment, DeploymentRunningDetails};
+
+/// The endpoint to get the changes between two revisions of a deployment
+mod get_deployment_revision_diff;
+/// The endpoint to list the revisions of a deployment
+mod list_deployment_revisions;
+
+pub use self::{get_deployment_revision_diff::*, list_deployment_revisions::*};
+
+/// An immutable snapshot of the configuration of a deployment. A new revision
+/// is recorded every time the deployment is changed, and the revision number
+/// is the version of the deployment at the time.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentRevision {
+	/// The revision number of the deployment
+	pub revision: u64,
+	/// The timestamp of when the revision was recorded
+	pub created: OffsetDateTime,
+	/// The deployment, as it was at this revision
+	#[serde(flatten)]
+	pub deployment: Deployment,
+	/// The running details of the deployment, as they were at this revision
+	#[serde(flatten)]
+	pub running_details: DeploymentRunningDetails,
+}
+
+/// A single field that changed between two revisions of a deployment
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentRevisionChange {
+	/// The path of the field that changed, with nested fields separated by a
+	/// `.` (eg: `environmentVariables.DATABASE_URL`)
+	pub field: String,
+	/// The value of the field in the older revision. This is not present if
+	/// the field was added
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub from: Option<Value>,
+	/// The value of the field in the newer revision. This is not present if
+	/// the field was removed
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub to: Option<Value>,
+}
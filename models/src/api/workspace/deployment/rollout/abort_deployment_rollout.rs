@@ -0,0 +1,26 @@
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to abort the canary of a deployment's current rollout. The
+	/// deployment is rolled back to the image it was running before the
+	/// rollout started
+	AbortDeploymentRollout,
+	POST "/workspace/:workspace_id/deployment/:deployment_id/rollout/abort" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The deployment ID of the deployment to abort the canary of
+		pub deployment_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.deployment_id,
+			permission: Permission::Deployment(DeploymentPermission::Edit),
+		}
+	}
+);
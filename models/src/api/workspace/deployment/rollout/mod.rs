@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// The endpoint to abort the canary of a rollout
+mod abort_deployment_rollout;
+/// The endpoint to promote the canary of a rollout
+mod promote_deployment_rollout;
+
+pub use self::{abort_deployment_rollout::*, promote_deployment_rollout::*};
+
+/// The rollout of a revision of a deployment. A rollout is started for every
+/// revision of the deployment, and tracks how the revision is being rolled out
+/// to the running instances of the deployment.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentRollout {
+	/// The revision of the deployment that is being rolled out
+	pub revision: u64,
+	/// The status of the rollout
+	pub status: DeploymentRolloutStatus,
+	/// The image that is being rolled out
+	pub image: DeploymentRolloutImage,
+	/// The image the deployment was running before the rollout started. This
+	/// is not present if the rollout didn't change the image. While a canary
+	/// is awaiting promotion, the rest of the deployment keeps running this
+	/// image, and aborting the canary rolls the deployment back to it
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub stable: Option<DeploymentRolloutImage>,
+	/// The timestamp of when the rollout was started
+	pub created: OffsetDateTime,
+	/// The timestamp of when the status of the rollout last changed
+	pub updated: OffsetDateTime,
+}
+
+impl DeploymentRollout {
+	/// Get the image that the deployment (other than its canary) should run
+	/// while the rollout is in progress. This is only present if a canary of
+	/// the rollout is awaiting promotion.
+	pub fn stable_image(&self) -> Option<&DeploymentRolloutImage> {
+		self.stable
+			.as_ref()
+			.filter(|_| self.status == DeploymentRolloutStatus::AwaitingPromotion)
+	}
+}
+
+/// The image that a rollout runs, identified by its tag and the digest it
+/// pointed to (if it is known)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentRolloutImage {
+	/// The tag of the image
+	pub image_tag: String,
+	/// The digest of the image, if the deployment was pinned to one
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub digest: Option<String>,
+}
+
+/// All the statuses a rollout can be in
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(sqlx::Type))]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+	not(target_arch = "wasm32"),
+	sqlx(type_name = "DEPLOYMENT_ROLLOUT_STATUS", rename_all = "snake_case")
+)]
+pub enum DeploymentRolloutStatus {
+	/// The revision is being rolled out to all the instances
+	Progressing,
+	/// The new image is running on a canary, and the rollout is waiting to be
+	/// promoted or aborted
+	AwaitingPromotion,
+	/// The revision has been rolled out to all the instances
+	Completed,
+	/// The canary was aborted, and the deployment was rolled back to the
+	/// stable image
+	Aborted,
+	/// A newer revision was rolled out before this one finished
+	Superseded,
+}
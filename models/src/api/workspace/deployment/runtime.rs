@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use super::event::DeploymentEvent;
+
+/// The state of a deployment as last reported by the runner it runs on
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentRuntime {
+	/// The number of instances of the deployment
+	pub replicas: DeploymentReplicaCounts,
+	/// The last time the runner reported the state of the deployment
+	pub reported: OffsetDateTime,
+	/// The error the runner ran into the last time it failed to reconcile the
+	/// deployment. This is cleared once the deployment is reconciled
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub last_error: Option<DeploymentReconcileError>,
+	/// The latest events of the deployment, newest first
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub recent_events: Vec<DeploymentEvent>,
+}
+
+/// The number of instances of a deployment, as observed by its runner
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentReplicaCounts {
+	/// The number of instances the deployment should be running
+	pub desired: u16,
+	/// The number of instances that are ready to serve traffic
+	pub ready: u16,
+	/// The number of instances that are running the latest configuration of
+	/// the deployment
+	pub updated: u16,
+}
+
+impl DeploymentReplicaCounts {
+	/// Check if all the instances of the deployment are ready, and running the
+	/// latest configuration of the deployment
+	pub fn is_rolled_out(&self) -> bool {
+		self.ready >= self.desired && self.updated >= self.desired
+	}
+}
+
+/// An error a runner ran into while reconciling a deployment
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentReconcileError {
+	/// A description of what went wrong
+	pub message: String,
+	/// The timestamp of when the error occurred
+	pub occurred: OffsetDateTime,
+}
+
+/// Something that happened to a container of a deployment, as reported by the
+/// runner. These are recorded as events of the deployment
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentContainerEvent {
+	/// The name of the container (or pod) the event happened to
+	pub container: String,
+	/// What happened to the container
+	pub kind: DeploymentContainerEventKind,
+	/// More details about the event, if the runner has any
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub message: Option<String>,
+	/// The timestamp of when the event happened
+	pub timestamp: OffsetDateTime,
+}
+
+/// All the kinds of events that can happen to a container of a deployment
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum DeploymentContainerEventKind {
+	/// The image of the container was pulled
+	ImagePulled,
+	/// The container was started
+	Started,
+	/// The container was stopped
+	Stopped,
+	/// The container exited with an error
+	Crashed,
+	/// The container was killed for using more memory than it is allowed to
+	OutOfMemory,
+	/// The health check of the container is failing
+	Unhealthy,
+	/// The image of the container could not be pulled
+	ImagePullFailed,
+}
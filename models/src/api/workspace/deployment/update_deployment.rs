@@ -1,6 +1,11 @@
 use std::collections::BTreeMap;
 
-use super::{DeploymentProbe, EnvironmentVariableValue, ExposedPortType};
+use super::{
+	DeploymentProbe,
+	DeploymentRolloutStrategy,
+	EnvironmentVariableValue,
+	ExposedPortType,
+};
 use crate::{prelude::*, utils::constants::RESOURCE_NAME_REGEX};
 
 macros::declare_api_endpoint!(
@@ -17,6 +22,9 @@ macros::declare_api_endpoint!(
 		pub authorization: BearerToken,
 		/// The user-agent used to access this API
 		pub user_agent: UserAgent,
+		/// The version of the resource that the client last fetched. If the
+		/// resource has been modified since, the request will be rejected
+		pub if_match: Option<IfMatch>,
 	},
 	authentication = {
 		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
@@ -62,6 +70,9 @@ macros::declare_api_endpoint!(
 		/// To update the volumes attached to the deployment
 		#[preprocess(none)]
 		pub volumes: Option<BTreeMap<Uuid, String>>,
+		/// To update the strategy used to roll out changes
+		#[preprocess(none)]
+		pub rollout_strategy: Option<DeploymentRolloutStrategy>,
 	}
 );
 
@@ -81,6 +92,7 @@ impl UpdateDeploymentRequest {
 			config_mounts: None,
 			runner: None,
 			volumes: None,
+			rollout_strategy: None,
 		}
 	}
 
@@ -100,6 +112,7 @@ impl UpdateDeploymentRequest {
 			.or(self.liveness_probe.as_ref().map(|_| 0))
 			.or(self.config_mounts.as_ref().map(|_| 0))
 			.or(self.volumes.as_ref().map(|_| 0))
+			.or(self.rollout_strategy.as_ref().map(|_| 0))
 			.is_none()
 	}
 }
@@ -14,6 +14,9 @@ macros::declare_api_endpoint!(
 		pub authorization: BearerToken,
 		/// The user-agent used to access this API
 		pub user_agent: UserAgent,
+		/// The version of the resource that the client last fetched. If the
+		/// resource has been modified since, the request will be rejected
+		pub if_match: Option<IfMatch>,
 	},
 	authentication = {
 		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
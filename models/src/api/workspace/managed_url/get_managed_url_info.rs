@@ -0,0 +1,34 @@
+use super::ManagedUrl;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to get the details of a managed URL
+	GetManagedURLInfo,
+	GET "/workspace/:workspace_id/infrastructure/managed-url/:managed_url_id" {
+		/// The workspace ID of the user
+		pub workspace_id: Uuid,
+		/// The managed URL to get the details of
+		pub managed_url_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.managed_url_id,
+			permission: Permission::ManagedURL(ManagedURLPermission::View),
+		}
+	},
+	response_headers = {
+		/// The current version of the managed URL
+		pub etag: ETag,
+	},
+	response = {
+		/// The managed URL information
+		#[serde(flatten)]
+		pub url: WithId<ManagedUrl>,
+	}
+);
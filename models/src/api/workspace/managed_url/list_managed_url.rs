@@ -20,12 +20,12 @@ macros::declare_api_endpoint!(
 		}
 	},
 	query = {
-		/// The order to sort the list of managed URLs
-		pub order: Option<ListOrder>,
-		/// The field to order the list of managed URLs by
-		pub order_by: Option<ListOrderBy>,
-		/// Search by a specific query
-		pub filter: Option<String>,
+		/// Only list the managed URLs that match this filter. The managed URLs
+		/// can be filtered by their `subDomain`, `path` and `created` time
+		pub filter: Option<ListFilter<ManagedUrlListField>>,
+		/// The fields to sort the list of managed URLs by. Defaults to the most
+		/// recently created managed URLs first
+		pub sort: Option<ListSort<ManagedUrlListField>>,
 	},
 	pagination = true,
 	response_headers = {
@@ -41,3 +41,24 @@ macros::declare_api_endpoint!(
 		pub urls: Vec<WithId<ManagedUrl>>,
 	}
 );
+
+/// The fields that the list of managed URLs can be filtered and sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::Display)]
+#[strum(serialize_all = "camelCase")]
+pub enum ManagedUrlListField {
+	/// The subdomain of the managed URL
+	SubDomain,
+	/// The path of the managed URL
+	Path,
+	/// When the managed URL was created
+	Created,
+}
+
+impl ListField for ManagedUrlListField {
+	fn kind(self) -> ListFieldKind {
+		match self {
+			Self::SubDomain | Self::Path => ListFieldKind::Text,
+			Self::Created => ListFieldKind::Timestamp,
+		}
+	}
+}
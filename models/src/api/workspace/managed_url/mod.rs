@@ -5,6 +5,8 @@ use strum::{Display, EnumDiscriminants, EnumString, VariantNames};
 mod create_managed_url;
 /// The endpoint to delete a managed URL
 mod delete_managed_url;
+/// The endpoint to get the details of a managed URL
+mod get_managed_url_info;
 /// The endpoint to list all the managed URLs in a workspace
 mod list_managed_url;
 /// The endpoint to update a managed URL
@@ -15,27 +17,13 @@ mod verify_configuration;
 pub use self::{
 	create_managed_url::*,
 	delete_managed_url::*,
+	get_managed_url_info::*,
 	list_managed_url::*,
 	update_managed_url::*,
 	verify_configuration::*,
 };
 use crate::utils::Uuid;
 
-/// Which field to order the list by for paginated requests
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub enum ListOrderBy {
-	/// Order the list by the status of the resource
-	Status,
-	/// Order the list by the name of the resource
-	Name,
-	/// Order the list by when the resource was last updated
-	LastUpdated,
-	/// Order the list by when the resource was created
-	#[default]
-	Created,
-}
-
 /// Managed URL information
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
@@ -24,6 +24,10 @@ macros::declare_api_endpoint!(
 			permission: Permission::ViewRoles,
 		}
 	},
+	response_headers = {
+		/// The current version of the role
+		pub etag: ETag,
+	},
 	response = {
 		/// The role which contains:
 		///     name - The role name
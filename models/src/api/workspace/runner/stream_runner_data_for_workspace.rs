@@ -1,5 +1,11 @@
 use crate::{
-	api::workspace::deployment::{Deployment, DeploymentRunningDetails},
+	api::workspace::deployment::{
+		rollout::DeploymentRolloutImage,
+		runtime::{DeploymentContainerEvent, DeploymentReplicaCounts},
+		Deployment,
+		DeploymentRunningDetails,
+		DeploymentStatus,
+	},
 	prelude::*,
 	rbac::ResourceType,
 };
@@ -49,8 +55,164 @@ macros::declare_stream_endpoint!(
 			/// The ID of the deployment that was deleted
 			id: Uuid
 		},
+		/// The user has asked for a rolling restart of a deployment. The
+		/// configuration of the deployment is unchanged
+		DeploymentRestarted {
+			/// The ID of the deployment to restart
+			id: Uuid
+		},
+		/// The user has opened a terminal session in a deployment. The runner
+		/// should run the command in one of the deployment's running
+		/// containers, with a TTY attached
+		DeploymentExecStarted {
+			/// The ID of the deployment to run the command in
+			id: Uuid,
+			/// The ID of the session, which the rest of the session's messages
+			/// refer to
+			session_id: Uuid,
+			/// The command to run, along with its arguments
+			command: Vec<String>,
+		},
+		/// The user has typed into the terminal of a session
+		DeploymentExecInputReceived {
+			/// The ID of the deployment the session is in
+			id: Uuid,
+			/// The ID of the session
+			session_id: Uuid,
+			/// The data that was typed
+			data: Base64String,
+		},
+		/// The user has resized the terminal of a session
+		DeploymentExecResized {
+			/// The ID of the deployment the session is in
+			id: Uuid,
+			/// The ID of the session
+			session_id: Uuid,
+			/// The number of rows in the terminal
+			rows: u16,
+			/// The number of columns in the terminal
+			cols: u16,
+		},
+		/// The user has closed a session. The runner should stop the command
+		/// if it's still running
+		DeploymentExecClosed {
+			/// The ID of the deployment the session is in
+			id: Uuid,
+			/// The ID of the session
+			session_id: Uuid,
+		},
+		/// The user has opened a connection to a port of a deployment. The
+		/// runner should connect to the port of one of the deployment's
+		/// running containers
+		DeploymentPortForwardStarted {
+			/// The ID of the deployment to connect to
+			id: Uuid,
+			/// The ID of the session, which the rest of the session's messages
+			/// refer to
+			session_id: Uuid,
+			/// The port of the container to connect to
+			port: u16,
+		},
+		/// The user has sent data over a forwarded connection
+		DeploymentPortForwardDataReceived {
+			/// The ID of the deployment the session is in
+			id: Uuid,
+			/// The ID of the session
+			session_id: Uuid,
+			/// The data that was sent
+			data: Base64String,
+		},
+		/// The user has closed a forwarded connection. The runner should close
+		/// its connection to the container
+		DeploymentPortForwardClosed {
+			/// The ID of the deployment the session is in
+			id: Uuid,
+			/// The ID of the session
+			session_id: Uuid,
+		},
+	},
+	client_msg = {
+		/// The runner has checked on a deployment that it runs
+		DeploymentStatusReported {
+			/// The ID of the deployment
+			id: Uuid,
+			/// The status the deployment is in
+			status: DeploymentStatus,
+			/// The number of instances of the deployment
+			replicas: DeploymentReplicaCounts,
+		},
+		/// Every instance of a deployment is running the image it was last
+		/// rolled out with
+		DeploymentRolledOut {
+			/// The ID of the deployment
+			id: Uuid,
+			/// The image that the deployment was rolled out with
+			image: DeploymentRolloutImage,
+		},
+		/// The runner failed to reconcile a deployment, and will try again
+		/// later
+		DeploymentReconcileFailed {
+			/// The ID of the deployment
+			id: Uuid,
+			/// A description of what went wrong
+			error: String,
+		},
+		/// Something happened to one of the containers of a deployment
+		DeploymentContainerEventOccurred {
+			/// The ID of the deployment
+			id: Uuid,
+			/// What happened to the container
+			event: DeploymentContainerEvent,
+		},
+		/// The command of a terminal session wrote to the terminal
+		DeploymentExecOutputSent {
+			/// The ID of the deployment the session is in
+			id: Uuid,
+			/// The ID of the session
+			session_id: Uuid,
+			/// The data that was written
+			data: Base64String,
+		},
+		/// The command of a terminal session has exited
+		DeploymentExecExited {
+			/// The ID of the deployment the session is in
+			id: Uuid,
+			/// The ID of the session
+			session_id: Uuid,
+			/// The exit code of the command, if the runner could find it
+			exit_code: Option<i64>,
+		},
+		/// The runner couldn't start the command of a terminal session, or lost
+		/// track of it
+		DeploymentExecFailed {
+			/// The ID of the deployment the session is in
+			id: Uuid,
+			/// The ID of the session
+			session_id: Uuid,
+			/// A description of what went wrong
+			error: String,
+		},
+		/// The container sent data over a forwarded connection
+		DeploymentPortForwardDataSent {
+			/// The ID of the deployment the session is in
+			id: Uuid,
+			/// The ID of the session
+			session_id: Uuid,
+			/// The data that was sent
+			data: Base64String,
+		},
+		/// The connection to the container of a forwarded port was closed, or
+		/// couldn't be opened
+		DeploymentPortForwardEnded {
+			/// The ID of the deployment the session is in
+			id: Uuid,
+			/// The ID of the session
+			session_id: Uuid,
+			/// A description of what went wrong, if the connection wasn't
+			/// closed normally
+			error: Option<String>,
+		},
 	},
-	client_msg = {},
 );
 
 impl StreamRunnerDataForWorkspaceServerMsg {
@@ -60,6 +222,31 @@ impl StreamRunnerDataForWorkspaceServerMsg {
 			Self::DeploymentCreated { .. } => ResourceType::Deployment,
 			Self::DeploymentUpdated { .. } => ResourceType::Deployment,
 			Self::DeploymentDeleted { .. } => ResourceType::Deployment,
+			Self::DeploymentRestarted { .. } => ResourceType::Deployment,
+			Self::DeploymentExecStarted { .. } => ResourceType::Deployment,
+			Self::DeploymentExecInputReceived { .. } => ResourceType::Deployment,
+			Self::DeploymentExecResized { .. } => ResourceType::Deployment,
+			Self::DeploymentExecClosed { .. } => ResourceType::Deployment,
+			Self::DeploymentPortForwardStarted { .. } => ResourceType::Deployment,
+			Self::DeploymentPortForwardDataReceived { .. } => ResourceType::Deployment,
+			Self::DeploymentPortForwardClosed { .. } => ResourceType::Deployment,
+		}
+	}
+}
+
+impl StreamRunnerDataForWorkspaceClientMsg {
+	/// Get the ID of the deployment that this message is about
+	pub fn deployment_id(&self) -> Uuid {
+		match self {
+			Self::DeploymentStatusReported { id, .. } => *id,
+			Self::DeploymentRolledOut { id, .. } => *id,
+			Self::DeploymentReconcileFailed { id, .. } => *id,
+			Self::DeploymentContainerEventOccurred { id, .. } => *id,
+			Self::DeploymentExecOutputSent { id, .. } => *id,
+			Self::DeploymentExecExited { id, .. } => *id,
+			Self::DeploymentExecFailed { id, .. } => *id,
+			Self::DeploymentPortForwardDataSent { id, .. } => *id,
+			Self::DeploymentPortForwardEnded { id, .. } => *id,
 		}
 	}
 }
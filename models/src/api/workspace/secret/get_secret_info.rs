@@ -0,0 +1,35 @@
+use super::Secret;
+use crate::prelude::*;
+
+macros::declare_api_endpoint!(
+	/// Route to get the details of a secret. The value of the secret is never
+	/// returned
+	GetSecretInfo,
+	GET "/workspace/:workspace_id/secret/:secret_id" {
+		/// The ID of the workspace
+		pub workspace_id: Uuid,
+		/// The ID of the secret to get the details of
+		pub secret_id: Uuid,
+	},
+	request_headers = {
+		/// Token used to authorize user
+		pub authorization: BearerToken,
+		/// The user-agent used to access this API
+		pub user_agent: UserAgent,
+	},
+	authentication = {
+		AppAuthentication::<Self>::ResourcePermissionAuthenticator {
+			extract_resource_id: |req| req.path.secret_id,
+			permission: Permission::Secret(SecretPermission::View),
+		}
+	},
+	response_headers = {
+		/// The current version of the secret
+		pub etag: ETag,
+	},
+	response = {
+		/// The secret information
+		#[serde(flatten)]
+		pub secret: WithId<Secret>,
+	}
+);
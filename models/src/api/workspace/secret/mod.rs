@@ -6,6 +6,8 @@ use crate::prelude::*;
 mod create_secret;
 /// The endpoint to delete a secret in the workspace
 mod delete_secret;
+/// The endpoint to get the details of a secret in the workspace
+mod get_secret_info;
 /// The endpoint to list all the secrets in the workspace
 mod list_secrets_for_workspace;
 /// The endpoint to update a secret in the workspace
@@ -14,6 +16,7 @@ mod update_secret;
 pub use self::{
 	create_secret::*,
 	delete_secret::*,
+	get_secret_info::*,
 	list_secrets_for_workspace::*,
 	update_secret::*,
 };
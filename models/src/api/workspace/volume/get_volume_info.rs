@@ -22,6 +22,10 @@ macros::declare_api_endpoint!(
 			permission: Permission::Volume(VolumePermission::Delete),
 		}
 	},
+	response_headers = {
+		/// The current version of the volume
+		pub etag: ETag,
+	},
 	response = {
 		/// The volume information
 		#[serde(flatten)]
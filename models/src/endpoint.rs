@@ -5,6 +5,7 @@ use preprocess::Preprocessable;
 use serde::{de::DeserializeOwned, Serialize};
 
 use crate::utils::{
+	EndpointDocs,
 	FromAxumRequest,
 	HasHeaders,
 	Headers,
@@ -121,6 +122,10 @@ where
 	/// response or a stream response, such as websockets.
 	type ResponseBody;
 
+	/// The documentation of this endpoint, generated from its declaration. This
+	/// is used to generate the OpenAPI document for the API.
+	fn docs() -> EndpointDocs;
+
 	/// The authenticator that should be used for this endpoint. This should be
 	/// a struct that implements the [`HasAuthentication`] trait
 	fn get_authenticator() -> Self::Authenticator
@@ -92,6 +92,24 @@ pub enum ErrorType {
 	RunnerAlreadyConnected,
 	/// The operation is not allowed in the current runner mode
 	InvalidRunnerMode,
+	/// The resource has been modified since the version provided in the
+	/// `If-Match` header was fetched
+	PreconditionFailed,
+	/// The cursor provided to a paginated list is invalid
+	InvalidCursor,
+	/// The workspace requires images to be signed, and the image is not signed
+	/// by a key that the workspace trusts
+	ImageNotSigned,
+	/// The deployment is stopped, and can't be restarted or exec'd into until
+	/// it is started
+	DeploymentNotRunning,
+	/// The deployment doesn't have a canary that is awaiting promotion
+	RolloutNotAwaitingPromotion,
+	/// The runner that the resource runs on is not connected to Patr
+	RunnerNotConnected,
+	/// The deployment already has as many port forwarding sessions open as it
+	/// is allowed to
+	TooManyPortForwardSessions,
 }
 
 impl ErrorType {
@@ -135,6 +153,13 @@ impl ErrorType {
 			Self::RoleInUse => StatusCode::CONFLICT,
 			Self::RunnerAlreadyConnected => StatusCode::CONFLICT,
 			Self::InvalidRunnerMode => StatusCode::FORBIDDEN,
+			Self::PreconditionFailed => StatusCode::PRECONDITION_FAILED,
+			Self::InvalidCursor => StatusCode::BAD_REQUEST,
+			Self::ImageNotSigned => StatusCode::FORBIDDEN,
+			Self::DeploymentNotRunning => StatusCode::CONFLICT,
+			Self::RolloutNotAwaitingPromotion => StatusCode::CONFLICT,
+			Self::RunnerNotConnected => StatusCode::SERVICE_UNAVAILABLE,
+			Self::TooManyPortForwardSessions => StatusCode::TOO_MANY_REQUESTS,
 		}
 	}
 
@@ -183,6 +208,13 @@ impl ErrorType {
 			Self::RoleInUse => "The role is currently assigned to users and cannot be deleted",
 			Self::RunnerAlreadyConnected => "Another instance of the same runner ID is already connected",
 			Self::InvalidRunnerMode => "That operation is not allowed in the mode the runner is currently in",
+			Self::PreconditionFailed => "The resource has been modified since you last fetched it. Please refresh and try again",
+			Self::InvalidCursor => "The cursor provided is invalid. Please use the links provided in the response to paginate",
+			Self::ImageNotSigned => "The image is not signed by a key trusted by the workspace",
+			Self::DeploymentNotRunning => "The deployment is stopped. Please start it and try again",
+			Self::RolloutNotAwaitingPromotion => "The deployment does not have a canary awaiting promotion",
+			Self::RunnerNotConnected => "The runner is not connected to Patr. Please check that it is running and try again",
+			Self::TooManyPortForwardSessions => "Too many ports are being forwarded from the deployment. Please close some of them and try again",
 		}
 	}
 
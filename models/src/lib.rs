@@ -18,7 +18,7 @@ pub mod utils;
 /// used across the crate. This is mostly used to avoid having to import a lot
 /// of things from different modules.
 pub mod prelude {
-	pub use headers::UserAgent;
+	pub use headers::{ETag, IfMatch, UserAgent};
 	pub use preprocess;
 	pub use tracing::{debug, error, info, instrument, trace, warn};
 
@@ -43,11 +43,19 @@ pub mod prelude {
 			AppAuthentication,
 			Base64String,
 			BearerToken,
+			Cursor,
+			CursorPaginated,
 			GeoLocation,
+			LinkHeader,
+			ListField,
+			ListFieldKind,
+			ListFilter,
 			ListOrder,
+			ListSort,
 			LoginId,
 			OneOrMore,
 			Paginated,
+			ResourceVersion,
 			StringifiedU16,
 			TotalCountHeader,
 			Uuid,
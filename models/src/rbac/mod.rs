@@ -315,6 +315,15 @@ pub enum DeploymentPermission {
 	/// The user will only be able to stop the deployment with no other updates
 	/// allowed.
 	Stop,
+	/// This permission allows the user to open an interactive shell in the
+	/// running containers of the deployment. Anything the deployment can access
+	/// can be accessed through the shell, so this should only be granted to
+	/// users that are trusted with the deployment's secrets.
+	Exec,
+	/// This permission allows the user to forward any port of the running
+	/// containers of the deployment to their machine, including the ports that
+	/// aren't exposed to the internet.
+	PortForward,
 }
 
 /// A list of all permissions that can be granted on a container registry
@@ -702,6 +711,21 @@ impl WorkspacePermission {
 		matches!(self, WorkspacePermission::Member { .. })
 	}
 
+	/// Returns true if the given permission is granted on the given resource.
+	/// A super admin has all permissions on all resources of the workspace.
+	pub fn has_permission_on_resource(&self, permission_id: &Uuid, resource_id: &Uuid) -> bool {
+		match self {
+			Self::SuperAdmin => true,
+			Self::Member { permissions } => match permissions.get(permission_id) {
+				Some(ResourcePermissionType::Include(resources)) => resources.contains(resource_id),
+				Some(ResourcePermissionType::Exclude(resources)) => {
+					!resources.contains(resource_id)
+				}
+				None => false,
+			},
+		}
+	}
+
 	/// Returns true if the current [`WorkspacePermission`] instance has more or
 	/// equal permissions than the other [`WorkspacePermission`] instance.
 	pub fn is_superset_of(&self, other: &WorkspacePermission) -> bool {
@@ -0,0 +1,352 @@
+use std::fmt::{self, Display, Formatter};
+
+use base64::prelude::*;
+use headers::{Error, Header};
+use http::{HeaderName, HeaderValue};
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::{paginated::deserialize_page_size, AddTuple, RequiresResponseHeaders};
+use crate::ErrorType;
+
+/// This struct represents a cursor paginated query parameter for the API.
+///
+/// Unlike [`Paginated`][super::Paginated], which skips a number of items to
+/// get to a page, a cursor paginated request fetches the items that come
+/// right after (or right before) a given item. This keeps pages stable even
+/// when items are added or removed while the list is being read, and doesn't
+/// get slower the further into the list the client goes.
+///
+/// A request that is cursor paginated will always return the links to the next
+/// and previous pages (if any) in the `Link` header (see the [`LinkHeader`]
+/// struct for reference).
+///
+/// ## Example
+/// A request with no cursor and a count of 5 would return the first 5 items,
+/// along with a link to the next page, which has `after` set to the cursor of
+/// the 5th item. Following that link returns the next 5 items, and so on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd)]
+pub struct CursorPaginated<T = ()> {
+	/// Any other query parameters that should be included in the request.
+	#[serde(flatten)]
+	pub data: T,
+	/// The number of items that should be returned per page. This is capped
+	/// at [`Paginated::MAX_PAGE_SIZE`][super::Paginated::MAX_PAGE_SIZE].
+	#[serde(
+		default = "default_page_size",
+		deserialize_with = "deserialize_page_size"
+	)]
+	pub count: usize,
+	/// Only return the items that come after the item with this cursor.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub after: Option<Cursor>,
+	/// Only return the items that come before the item with this cursor. This
+	/// is ignored if `after` is set.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub before: Option<Cursor>,
+}
+
+impl<T> CursorPaginated<T> {
+	/// The default page size that should be used if no page size is specified.
+	/// This is the same as the default page size of offset paginated requests.
+	pub const DEFAULT_PAGE_SIZE: usize = super::Paginated::<()>::DEFAULT_PAGE_SIZE;
+
+	/// The number of items that should be fetched from the database for this
+	/// page. One extra item is fetched to know if there are any more items
+	/// after the page, which is then dropped by [`Self::into_page`].
+	pub fn fetch_limit(&self) -> usize {
+		self.count.saturating_add(1)
+	}
+
+	/// Decodes the cursor that the page starts from, if any, along with
+	/// whether the items should be fetched backwards (i.e, the items before
+	/// the cursor, instead of after it).
+	pub fn decode_cursor<K>(&self) -> Result<(Option<K>, bool), ErrorType>
+	where
+		K: DeserializeOwned,
+	{
+		match (&self.after, &self.before) {
+			(Some(after), _) => Ok((Some(after.decode()?), false)),
+			(None, Some(before)) => Ok((Some(before.decode()?), true)),
+			(None, None) => Ok((None, false)),
+		}
+	}
+
+	/// Converts the items fetched from the database (at most
+	/// [`Self::fetch_limit`] items, in the order of the list) into a page,
+	/// along with the [`LinkHeader`] pointing to the next and previous pages.
+	/// The `path` is the path of the request, and `cursor_of` returns the
+	/// cursor of an item.
+	pub fn into_page<I>(
+		self,
+		path: impl Display,
+		mut items: Vec<I>,
+		cursor_of: impl Fn(&I) -> Cursor,
+	) -> (Vec<I>, LinkHeader)
+	where
+		T: Serialize,
+	{
+		let backwards = self.after.is_none() && self.before.is_some();
+		let has_more = items.len() > self.count;
+		if has_more {
+			// The extra item is at the end of the list when going forwards,
+			// and at the start when going backwards.
+			if backwards {
+				items.remove(0);
+			} else {
+				items.truncate(self.count);
+			}
+		}
+
+		let (has_next, has_prev) = if backwards {
+			(true, has_more)
+		} else {
+			(has_more, self.after.is_some())
+		};
+
+		let link = |after: Option<Cursor>, before: Option<Cursor>| {
+			let query = serde_urlencoded::to_string(CursorPaginated {
+				data: &self.data,
+				count: self.count,
+				after,
+				before,
+			})
+			.unwrap_or_default();
+			format!("{path}?{query}")
+		};
+
+		let next = items
+			.last()
+			.filter(|_| has_next)
+			.map(|item| link(Some(cursor_of(item)), None));
+		let prev = items
+			.first()
+			.filter(|_| has_prev)
+			.map(|item| link(None, Some(cursor_of(item))));
+
+		(items, LinkHeader { next, prev })
+	}
+}
+
+impl<T> Default for CursorPaginated<T>
+where
+	T: Default,
+{
+	fn default() -> Self {
+		Self {
+			data: T::default(),
+			count: Self::DEFAULT_PAGE_SIZE,
+			after: None,
+			before: None,
+		}
+	}
+}
+
+impl<T> RequiresResponseHeaders for CursorPaginated<T>
+where
+	T: AddTuple<LinkHeader>,
+{
+	type RequiredResponseHeaders = <T as AddTuple<LinkHeader>>::ResultantTuple;
+}
+
+/// Get the default page size that should be used if no page size is
+/// specified.
+const fn default_page_size() -> usize {
+	CursorPaginated::<()>::DEFAULT_PAGE_SIZE
+}
+
+/// An opaque cursor that points to an item in a list. Clients should not try
+/// to parse or construct a cursor, and should only use the cursors given in
+/// the [`LinkHeader`] of a response.
+///
+/// Internally, this is the base64 encoded JSON of the key that the list is
+/// ordered by (for example, the creation time and the ID of an item).
+#[derive(
+	Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(transparent)]
+pub struct Cursor(String);
+
+impl Cursor {
+	/// Creates a cursor from the key of an item in the list.
+	pub fn new<K>(key: &K) -> Self
+	where
+		K: Serialize,
+	{
+		Self(
+			BASE64_URL_SAFE_NO_PAD
+				.encode(serde_json::to_vec(key).expect("cursor key should be serializable")),
+		)
+	}
+
+	/// Decodes the key of the item that this cursor points to. Returns
+	/// [`ErrorType::InvalidCursor`] if the cursor was tampered with or was
+	/// created for a different list.
+	pub fn decode<K>(&self) -> Result<K, ErrorType>
+	where
+		K: DeserializeOwned,
+	{
+		let bytes = BASE64_URL_SAFE_NO_PAD
+			.decode(&self.0)
+			.map_err(|_| ErrorType::InvalidCursor)?;
+		serde_json::from_slice(&bytes).map_err(|_| ErrorType::InvalidCursor)
+	}
+}
+
+impl Display for Cursor {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.0)
+	}
+}
+
+/// The `Link` header of a cursor paginated response, containing the links to
+/// the next and previous pages of the list, if any. This follows the format
+/// of [RFC 8288](https://www.rfc-editor.org/rfc/rfc8288), with the links being
+/// relative to the API.
+///
+/// Example: `Link: </workspace/.../tag?count=25&after=...>; rel="next"`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkHeader {
+	/// The link to the next page of the list, if there are more items.
+	pub next: Option<String>,
+	/// The link to the previous page of the list, if this isn't the first page.
+	pub prev: Option<String>,
+}
+
+impl LinkHeader {
+	/// The cursor that the next page starts after, if there is a next page.
+	pub fn next_cursor(&self) -> Option<Cursor> {
+		self.next
+			.as_deref()
+			.and_then(|link| get_query_param(link, "after"))
+	}
+
+	/// The cursor that the previous page ends before, if there is a previous
+	/// page.
+	pub fn prev_cursor(&self) -> Option<Cursor> {
+		self.prev
+			.as_deref()
+			.and_then(|link| get_query_param(link, "before"))
+	}
+}
+
+/// Gets the value of a query parameter from a link as a [`Cursor`].
+fn get_query_param(link: &str, name: &str) -> Option<Cursor> {
+	let (_, query) = link.split_once('?')?;
+	serde_urlencoded::from_str::<Vec<(String, String)>>(query)
+		.ok()?
+		.into_iter()
+		.find(|(key, _)| key == name)
+		.map(|(_, value)| Cursor(value))
+}
+
+/// The name of the `Link` header.
+static LINK_HEADER_NAME: HeaderName = http::header::LINK;
+
+impl Header for LinkHeader {
+	fn name() -> &'static HeaderName {
+		&LINK_HEADER_NAME
+	}
+
+	fn decode<'i, I>(values: &mut I) -> Result<Self, Error>
+	where
+		Self: Sized,
+		I: Iterator<Item = &'i HeaderValue>,
+	{
+		let mut header = Self::default();
+
+		for value in values {
+			let value = value.to_str().map_err(|_| headers::Error::invalid())?;
+			for link in value
+				.split(',')
+				.map(str::trim)
+				.filter(|link| !link.is_empty())
+			{
+				let (target, params) = link.split_once(';').ok_or_else(headers::Error::invalid)?;
+				let target = target
+					.trim()
+					.strip_prefix('<')
+					.and_then(|target| target.strip_suffix('>'))
+					.ok_or_else(headers::Error::invalid)?
+					.to_string();
+				let rel = params
+					.split(';')
+					.filter_map(|param| param.trim().strip_prefix("rel="))
+					.map(|rel| rel.trim_matches('"'))
+					.next();
+				match rel {
+					Some("next") => header.next = Some(target),
+					Some("prev") => header.prev = Some(target),
+					_ => (),
+				}
+			}
+		}
+
+		Ok(header)
+	}
+
+	fn encode<E>(&self, values: &mut E)
+	where
+		E: Extend<HeaderValue>,
+	{
+		let value = [(&self.next, "next"), (&self.prev, "prev")]
+			.into_iter()
+			.filter_map(|(link, rel)| Some(format!("<{}>; rel=\"{}\"", link.as_ref()?, rel)))
+			.collect::<Vec<_>>()
+			.join(", ");
+		values.extend(std::iter::once(HeaderValue::from_str(&value).unwrap()));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use headers::{Header, HeaderValue};
+
+	use super::{Cursor, CursorPaginated, LinkHeader};
+	use crate::utils::Paginated;
+
+	#[test]
+	fn assert_cursor_round_trip() {
+		let key = ("2024-01-01T00:00:00Z".to_string(), "latest".to_string());
+		let cursor = Cursor::new(&key);
+		assert_eq!(cursor.decode::<(String, String)>(), Ok(key));
+		assert!(Cursor("not a cursor".to_string())
+			.decode::<(String, String)>()
+			.is_err());
+	}
+
+	#[test]
+	fn assert_page_size_is_capped() {
+		let query = serde_urlencoded::from_str::<CursorPaginated>("count=1000").unwrap();
+		assert_eq!(query.count, Paginated::<()>::MAX_PAGE_SIZE);
+
+		let query = serde_urlencoded::from_str::<Paginated>("count=1000&page=2").unwrap();
+		assert_eq!(query.count, Paginated::<()>::MAX_PAGE_SIZE);
+		assert_eq!(query.page, 2);
+
+		let query = serde_urlencoded::from_str::<CursorPaginated>("").unwrap();
+		assert_eq!(query.count, CursorPaginated::<()>::DEFAULT_PAGE_SIZE);
+	}
+
+	#[test]
+	fn assert_link_header_round_trip() {
+		let header = LinkHeader {
+			next: Some("/tag?count=5&after=abc".to_string()),
+			prev: Some("/tag?count=5&before=xyz".to_string()),
+		};
+
+		let mut values = Vec::new();
+		header.encode(&mut values);
+		assert_eq!(
+			values,
+			[HeaderValue::from_static(
+				r#"</tag?count=5&after=abc>; rel="next", </tag?count=5&before=xyz>; rel="prev""#
+			)]
+		);
+
+		let decoded = LinkHeader::decode(&mut values.iter()).unwrap();
+		assert_eq!(decoded, header);
+		assert_eq!(decoded.next_cursor(), Some(Cursor("abc".to_string())));
+		assert_eq!(decoded.prev_cursor(), Some(Cursor("xyz".to_string())));
+	}
+}
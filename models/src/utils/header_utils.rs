@@ -181,6 +181,41 @@ impl Header for LoginId {
 	}
 }
 
+/// This struct represents the version of a resource.
+///
+/// Every time a resource is modified, its version is incremented. The version
+/// is sent to the client as an [`ETag`] header when the resource is fetched,
+/// and the client can send it back in an [`IfMatch`] header when modifying (or
+/// deleting) the resource. If the resource has been modified in the meantime,
+/// the request will be rejected with a `412 Precondition Failed` status code,
+/// preventing two clients from silently overwriting each other's changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ResourceVersion(pub i64);
+
+impl ResourceVersion {
+	/// Returns the [`ETag`] that represents this version of the resource.
+	pub fn to_etag(&self) -> ETag {
+		format!("\"{}\"", self.0)
+			.parse()
+			.expect("a quoted integer should always be a valid ETag")
+	}
+
+	/// Checks if the given [`IfMatch`] header (if any) allows a resource at
+	/// this version to be modified. If no header is provided, the modification
+	/// is always allowed.
+	pub fn satisfies(&self, if_match: Option<&IfMatch>) -> bool {
+		if_match.map_or(true, |if_match| {
+			if_match.precondition_passes(&self.to_etag())
+		})
+	}
+}
+
+impl From<ResourceVersion> for ETag {
+	fn from(version: ResourceVersion) -> Self {
+		version.to_etag()
+	}
+}
+
 /// This struct is implemented for all types that can be used as a header in a
 /// request to the API.
 ///
@@ -494,3 +529,37 @@ pub trait RequiresRequestHeaders {
 impl RequiresRequestHeaders for () {
 	type RequiredRequestHeaders = ();
 }
+
+#[cfg(test)]
+mod tests {
+	use headers::IfMatch;
+
+	use super::ResourceVersion;
+
+	#[test]
+	fn assert_etag_is_quoted_version() {
+		assert_eq!(ResourceVersion(3).to_etag(), "\"3\"".parse().unwrap());
+	}
+
+	#[test]
+	fn assert_missing_if_match_is_satisfied() {
+		assert!(ResourceVersion(1).satisfies(None));
+	}
+
+	#[test]
+	fn assert_if_match_any_is_satisfied() {
+		assert!(ResourceVersion(1).satisfies(Some(&IfMatch::any())));
+	}
+
+	#[test]
+	fn assert_current_version_is_satisfied() {
+		let if_match = IfMatch::from(ResourceVersion(2).to_etag());
+		assert!(ResourceVersion(2).satisfies(Some(&if_match)));
+	}
+
+	#[test]
+	fn assert_stale_version_is_not_satisfied() {
+		let if_match = IfMatch::from(ResourceVersion(1).to_etag());
+		assert!(!ResourceVersion(2).satisfies(Some(&if_match)));
+	}
+}
@@ -0,0 +1,533 @@
+use std::{
+	fmt::{self, Display, Formatter},
+	str::FromStr,
+};
+
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use super::ListOrder;
+
+/// A field of a list endpoint that can be filtered and sorted on. Each list
+/// endpoint that supports the `filter` and `sort` query parameters declares an
+/// enum of the fields it allows, so that a request referring to any other field
+/// is rejected while parsing the query.
+///
+/// The [`FromStr`] and [`Display`] implementations are used to parse and write
+/// the name of the field, as seen by the client.
+pub trait ListField: Copy + FromStr + Display {
+	/// The kind of values this field holds. This decides the operators that can
+	/// be used on the field and how the values are parsed.
+	fn kind(self) -> ListFieldKind;
+}
+
+/// The kind of values a [`ListField`] holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListFieldKind {
+	/// A text field. This can be compared for equality, matched by a prefix
+	/// (`^=`) or checked against a list of values (`in`).
+	Text,
+	/// A field that can only have one of the given values. This can be compared
+	/// for equality or checked against a list of values (`in`).
+	Enum(&'static [&'static str]),
+	/// A timestamp field, with values in the RFC 3339 format. This can be
+	/// compared using any of the comparison operators, to filter ranges.
+	Timestamp,
+}
+
+impl ListFieldKind {
+	/// Checks if the given operator can be used on a field of this kind.
+	pub fn supports(self, operator: FilterOperator) -> bool {
+		use FilterOperator::*;
+
+		match self {
+			Self::Text => matches!(operator, Equals | NotEquals | StartsWith | In),
+			Self::Enum(_) => matches!(operator, Equals | NotEquals | In),
+			Self::Timestamp => !matches!(operator, StartsWith | In),
+		}
+	}
+
+	/// Parses a value for a field of this kind.
+	fn parse_value(self, value: &str) -> Option<FilterValue> {
+		match self {
+			Self::Text => Some(FilterValue::Text(value.to_string())),
+			Self::Enum(values) => values
+				.contains(&value)
+				.then(|| FilterValue::Text(value.to_string())),
+			Self::Timestamp => OffsetDateTime::parse(value, &Rfc3339)
+				.ok()
+				.map(FilterValue::Timestamp),
+		}
+	}
+}
+
+/// The operator used to compare a field to the value(s) of a filter condition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterOperator {
+	/// `field=value`
+	Equals,
+	/// `field!=value`
+	NotEquals,
+	/// `field<value`
+	LessThan,
+	/// `field<=value`
+	LessThanOrEquals,
+	/// `field>value`
+	GreaterThan,
+	/// `field>=value`
+	GreaterThanOrEquals,
+	/// `field^=value`, matching the values of the field that start with the
+	/// given value
+	StartsWith,
+	/// `field in (value1,value2,...)`, matching any of the given values
+	In,
+}
+
+impl FilterOperator {
+	/// The operators that are written between the field and the value, in the
+	/// order that they should be matched in (longest first).
+	const INFIX: [(&'static str, Self); 7] = [
+		("!=", Self::NotEquals),
+		("<=", Self::LessThanOrEquals),
+		(">=", Self::GreaterThanOrEquals),
+		("^=", Self::StartsWith),
+		("=", Self::Equals),
+		("<", Self::LessThan),
+		(">", Self::GreaterThan),
+	];
+
+	/// The symbol of the operator, as written in a filter.
+	pub fn symbol(self) -> &'static str {
+		match self {
+			Self::Equals => "=",
+			Self::NotEquals => "!=",
+			Self::LessThan => "<",
+			Self::LessThanOrEquals => "<=",
+			Self::GreaterThan => ">",
+			Self::GreaterThanOrEquals => ">=",
+			Self::StartsWith => "^=",
+			Self::In => " in ",
+		}
+	}
+}
+
+/// A value in a filter condition, parsed according to the kind of the field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterValue {
+	/// The value of a [`ListFieldKind::Text`] or [`ListFieldKind::Enum`] field
+	Text(String),
+	/// The value of a [`ListFieldKind::Timestamp`] field
+	Timestamp(OffsetDateTime),
+}
+
+impl Display for FilterValue {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Text(text) => write!(f, "{}", text),
+			Self::Timestamp(timestamp) => {
+				write!(f, "{}", timestamp.format(&Rfc3339).map_err(|_| fmt::Error)?)
+			}
+		}
+	}
+}
+
+/// A single condition of a [`ListFilter`], such as `name^=web` or
+/// `status in (running,errored)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterCondition<F> {
+	/// The field that is being filtered on
+	pub field: F,
+	/// The operator used to compare the field to the values
+	pub operator: FilterOperator,
+	/// The values to compare the field to. This has exactly one value for all
+	/// operators except [`FilterOperator::In`].
+	pub values: Vec<FilterValue>,
+}
+
+impl<F> FromStr for FilterCondition<F>
+where
+	F: ListField,
+{
+	type Err = String;
+
+	fn from_str(condition: &str) -> Result<Self, Self::Err> {
+		let condition = condition.trim();
+		let field_end = condition
+			.find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+			.unwrap_or(condition.len());
+		let (name, rest) = condition.split_at(field_end);
+		let rest = rest.trim_start();
+
+		let field = F::from_str(name)
+			.map_err(|_| format!("`{}` is not a field that can be filtered", name))?;
+
+		let (operator, values) = if let Some(list) = rest
+			.strip_prefix("in")
+			.map(str::trim_start)
+			.filter(|list| list.starts_with('('))
+		{
+			let list = list
+				.strip_prefix('(')
+				.and_then(|list| list.strip_suffix(')'))
+				.ok_or_else(|| format!("missing `)` in the condition `{}`", condition))?;
+			(FilterOperator::In, list.split(',').map(str::trim).collect())
+		} else {
+			let (symbol, operator) = FilterOperator::INFIX
+				.into_iter()
+				.find(|(symbol, _)| rest.starts_with(symbol))
+				.ok_or_else(|| format!("`{}` is not a valid condition", condition))?;
+			(operator, vec![rest[symbol.len()..].trim()])
+		};
+
+		let kind = field.kind();
+		if !kind.supports(operator) {
+			return Err(format!(
+				"the operator `{}` cannot be used on the field `{}`",
+				operator.symbol().trim(),
+				field
+			));
+		}
+
+		let values = values
+			.into_iter()
+			.map(|value| {
+				if value.is_empty() {
+					return Err(format!("missing value in the condition `{}`", condition));
+				}
+				kind.parse_value(value).ok_or_else(|| {
+					format!("`{}` is not a valid value for the field `{}`", value, field)
+				})
+			})
+			.collect::<Result<_, _>>()?;
+
+		Ok(Self {
+			field,
+			operator,
+			values,
+		})
+	}
+}
+
+impl<F> Display for FilterCondition<F>
+where
+	F: ListField,
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(f, "{}{}", self.field, self.operator.symbol())?;
+		if self.operator == FilterOperator::In {
+			write!(f, "(")?;
+		}
+		for (index, value) in self.values.iter().enumerate() {
+			if index > 0 {
+				write!(f, ",")?;
+			}
+			write!(f, "{}", value)?;
+		}
+		if self.operator == FilterOperator::In {
+			write!(f, ")")?;
+		}
+		Ok(())
+	}
+}
+
+/// The `filter` query parameter of a list endpoint. This is a comma separated
+/// list of conditions, all of which need to match for an item to be listed.
+/// The fields that can be filtered on are decided by the endpoint, using the
+/// [`ListField`] type `F`.
+///
+/// Each condition is a field, an operator and a value. The supported operators
+/// are `=`, `!=`, `<`, `<=`, `>`, `>=`, `^=` (starts with) and `in (...)`,
+/// depending on the kind of the field. Timestamps are in the RFC 3339 format.
+///
+/// Values cannot contain commas or parentheses.
+///
+/// ## Example
+/// `status in (running,errored),name^=web,created>=2024-01-01T00:00:00Z` lists
+/// the running or errored items whose name starts with `web`, that were created
+/// after the start of 2024.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListFilter<F>(pub Vec<FilterCondition<F>>);
+
+impl<F> FromStr for ListFilter<F>
+where
+	F: ListField,
+{
+	type Err = String;
+
+	fn from_str(filter: &str) -> Result<Self, Self::Err> {
+		let mut conditions = Vec::new();
+		let mut depth = 0usize;
+		let mut start = 0;
+
+		// Split on the commas that are not inside the list of an `in` condition
+		for (index, c) in filter.char_indices() {
+			match c {
+				'(' => depth += 1,
+				')' => depth = depth.saturating_sub(1),
+				',' if depth == 0 => {
+					conditions.push(filter[start..index].parse()?);
+					start = index + 1;
+				}
+				_ => (),
+			}
+		}
+		conditions.push(filter[start..].parse()?);
+
+		Ok(Self(conditions))
+	}
+}
+
+impl<F> Display for ListFilter<F>
+where
+	F: ListField,
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		for (index, condition) in self.0.iter().enumerate() {
+			if index > 0 {
+				write!(f, ",")?;
+			}
+			write!(f, "{}", condition)?;
+		}
+		Ok(())
+	}
+}
+
+/// A single field to sort a list by, along with the order to sort it in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey<F> {
+	/// The field to sort the list by
+	pub field: F,
+	/// The order to sort the field in
+	pub order: ListOrder,
+}
+
+/// The `sort` query parameter of a list endpoint. This is a comma separated
+/// list of fields to sort the list by, in order of priority. A field prefixed
+/// with a `-` is sorted in descending order, and in ascending order otherwise.
+/// The fields that can be sorted on are decided by the endpoint, using the
+/// [`ListField`] type `F`.
+///
+/// ## Example
+/// `-created,name` lists the most recently created items first, and the items
+/// created at the same time by their name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListSort<F>(pub Vec<SortKey<F>>);
+
+impl<F> FromStr for ListSort<F>
+where
+	F: ListField,
+{
+	type Err = String;
+
+	fn from_str(sort: &str) -> Result<Self, Self::Err> {
+		sort.split(',')
+			.map(|key| {
+				let key = key.trim();
+				let (name, order) = match key.strip_prefix('-') {
+					Some(name) => (name, ListOrder::Descending),
+					None => (key.strip_prefix('+').unwrap_or(key), ListOrder::Ascending),
+				};
+				let field = F::from_str(name)
+					.map_err(|_| format!("`{}` is not a field that can be sorted", name))?;
+				Ok(SortKey { field, order })
+			})
+			.collect::<Result<_, _>>()
+			.map(Self)
+	}
+}
+
+impl<F> Display for ListSort<F>
+where
+	F: ListField,
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		for (index, SortKey { field, order }) in self.0.iter().enumerate() {
+			if index > 0 {
+				write!(f, ",")?;
+			}
+			if *order == ListOrder::Descending {
+				write!(f, "-")?;
+			}
+			write!(f, "{}", field)?;
+		}
+		Ok(())
+	}
+}
+
+/// Implements serde and JSON schema support for a type that is sent as a
+/// string query parameter, using its [`FromStr`] and [`Display`]
+/// implementations.
+macro_rules! impl_string_query_param {
+	($name:ident) => {
+		impl<F> Serialize for $name<F>
+		where
+			F: ListField,
+		{
+			fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+			where
+				S: Serializer,
+			{
+				serializer.collect_str(self)
+			}
+		}
+
+		impl<'de, F> Deserialize<'de> for $name<F>
+		where
+			F: ListField,
+		{
+			fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+			where
+				D: Deserializer<'de>,
+			{
+				String::deserialize(deserializer)?
+					.parse()
+					.map_err(D::Error::custom)
+			}
+		}
+
+		impl<F> JsonSchema for $name<F> {
+			fn schema_name() -> String {
+				stringify!($name).to_string()
+			}
+
+			fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+				String::json_schema(generator)
+			}
+		}
+	};
+}
+
+impl_string_query_param!(ListFilter);
+impl_string_query_param!(ListSort);
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		fmt::{self, Display, Formatter},
+		str::FromStr,
+	};
+
+	use super::*;
+
+	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+	enum TestField {
+		Name,
+		Status,
+		Created,
+	}
+
+	impl FromStr for TestField {
+		type Err = ();
+
+		fn from_str(s: &str) -> Result<Self, Self::Err> {
+			match s {
+				"name" => Ok(Self::Name),
+				"status" => Ok(Self::Status),
+				"created" => Ok(Self::Created),
+				_ => Err(()),
+			}
+		}
+	}
+
+	impl Display for TestField {
+		fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+			match self {
+				Self::Name => write!(f, "name"),
+				Self::Status => write!(f, "status"),
+				Self::Created => write!(f, "created"),
+			}
+		}
+	}
+
+	impl ListField for TestField {
+		fn kind(self) -> ListFieldKind {
+			match self {
+				Self::Name => ListFieldKind::Text,
+				Self::Status => ListFieldKind::Enum(&["running", "stopped", "errored"]),
+				Self::Created => ListFieldKind::Timestamp,
+			}
+		}
+	}
+
+	#[test]
+	fn assert_filter_parse() {
+		let filter = "status in (running, errored),name^=web,created>=2024-01-01T00:00:00Z"
+			.parse::<ListFilter<TestField>>()
+			.unwrap();
+		assert_eq!(
+			filter,
+			ListFilter(vec![
+				FilterCondition {
+					field: TestField::Status,
+					operator: FilterOperator::In,
+					values: vec![
+						FilterValue::Text("running".to_string()),
+						FilterValue::Text("errored".to_string())
+					],
+				},
+				FilterCondition {
+					field: TestField::Name,
+					operator: FilterOperator::StartsWith,
+					values: vec![FilterValue::Text("web".to_string())],
+				},
+				FilterCondition {
+					field: TestField::Created,
+					operator: FilterOperator::GreaterThanOrEquals,
+					values: vec![FilterValue::Timestamp(
+						OffsetDateTime::parse("2024-01-01T00:00:00Z", &Rfc3339).unwrap()
+					)],
+				},
+			])
+		);
+		assert_eq!(
+			filter.to_string(),
+			"status in (running,errored),name^=web,created>=2024-01-01T00:00:00Z"
+		);
+	}
+
+	#[test]
+	fn assert_invalid_filter() {
+		for filter in [
+			"owner=me",
+			"status=deleted",
+			"status^=run",
+			"created in (2024-01-01T00:00:00Z)",
+			"created<yesterday",
+			"name",
+			"name=",
+			"status in (running",
+		] {
+			assert!(
+				filter.parse::<ListFilter<TestField>>().is_err(),
+				"`{filter}` should not be a valid filter"
+			);
+		}
+	}
+
+	#[test]
+	fn assert_sort_parse() {
+		let sort = "-created,+name,status"
+			.parse::<ListSort<TestField>>()
+			.unwrap();
+		assert_eq!(
+			sort,
+			ListSort(vec![
+				SortKey {
+					field: TestField::Created,
+					order: ListOrder::Descending,
+				},
+				SortKey {
+					field: TestField::Name,
+					order: ListOrder::Ascending,
+				},
+				SortKey {
+					field: TestField::Status,
+					order: ListOrder::Ascending,
+				},
+			])
+		);
+		assert_eq!(sort.to_string(), "-created,name,status");
+		assert!("-owner".parse::<ListSort<TestField>>().is_err());
+	}
+}
@@ -15,6 +15,10 @@ mod base64string;
 /// A set of constant booleans that are used to ensure that the values are
 /// forced to be either true or false.
 mod bools;
+/// A set of utilities to parse a cursor paginated response from the API. A
+/// cursor paginated request enforces a `Link` header to be present in the
+/// response, which provides the links to the next and previous pages.
+mod cursor_paginated;
 /// Represents a location on the planet. This is used to represent the location
 /// of a user, a login, etc. Basically just a latitude and longitude.
 mod geo_location;
@@ -22,6 +26,9 @@ mod geo_location;
 /// headers are present in a struct as well as provide what headers are required
 /// for an endpoint.
 mod header_utils;
+/// A common query language to filter and sort the items of list endpoints,
+/// using the `filter` and `sort` query parameters.
+mod list_query;
 /// A set of middlewares that are used by the API to perform certain tasks, like
 /// authentication, audit logging, etc.
 mod middlewares;
@@ -29,6 +36,9 @@ mod middlewares;
 /// a value that can be either a single value or a list of values, such as
 /// audience in a JWT, a dependency string in a CI yaml file, etc.
 mod one_or_many;
+/// Utilities to document the API endpoints and generate an OpenAPI document
+/// from their declarations.
+mod openapi;
 /// A set of utilities to parse a paginated response from the API. A paginated
 /// request enforces a response header to be present, which provides the total
 /// number of items in the response.
@@ -52,10 +62,13 @@ pub use self::{
 	axum_response::*,
 	base64string::*,
 	bools::*,
+	cursor_paginated::*,
 	geo_location::*,
 	header_utils::*,
+	list_query::*,
 	middlewares::*,
 	one_or_many::*,
+	openapi::*,
 	paginated::*,
 	stringified_u16::*,
 	tuple_utils::*,
@@ -0,0 +1,539 @@
+use std::marker::PhantomData;
+
+use http::Method;
+use preprocess::Preprocessable;
+use schemars::{gen::SchemaSettings, JsonSchema};
+use serde_json::{json, Map, Value};
+use strum::IntoEnumIterator;
+
+use crate::prelude::*;
+
+/// The documentation of an API endpoint. This is generated by the
+/// [`macros::declare_api_endpoint`] and [`macros::declare_stream_endpoint`]
+/// macros from the declaration of the endpoint, and is used to generate the
+/// OpenAPI document for the API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointDocs {
+	/// The name of the endpoint, as declared in the macro.
+	pub name: &'static str,
+	/// The documentation comment of the endpoint.
+	pub documentation: &'static str,
+	/// The HTTP method of the endpoint.
+	pub method: Method,
+	/// The path of the endpoint, in the axum format (`/path/:param`).
+	pub path: &'static str,
+	/// How the query of the endpoint is paginated, if at all.
+	pub pagination: EndpointPagination,
+	/// Whether the endpoint is upgraded to a websocket connection.
+	pub websocket: bool,
+	/// The authentication required to access this endpoint.
+	pub authentication: EndpointAuthentication,
+	/// The parameters in the path of the endpoint.
+	pub path_params: Vec<FieldDocs>,
+	/// The query parameters of the endpoint.
+	pub query: Vec<FieldDocs>,
+	/// The headers that are sent with the request.
+	pub request_headers: Vec<FieldDocs>,
+	/// The fields of the JSON request body.
+	pub request_body: Vec<FieldDocs>,
+	/// The headers that are sent with the response.
+	pub response_headers: Vec<FieldDocs>,
+	/// The fields of the JSON response body.
+	pub response_body: Vec<FieldDocs>,
+}
+
+/// The documentation of a single field in a path, query, body or a header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDocs {
+	/// The name of the field, as seen by the client.
+	pub name: String,
+	/// The documentation comment of the field.
+	pub description: String,
+	/// Whether the field is required to be present.
+	pub required: bool,
+	/// Whether the field is flattened into its parent (`#[serde(flatten)]`).
+	pub flatten: bool,
+	/// The name of the Rust type of the field. Used as a hint when the type
+	/// does not have a JSON schema.
+	pub rust_type: &'static str,
+	/// The JSON schema of the field, if the type implements [`JsonSchema`].
+	pub schema: Option<Value>,
+}
+
+/// The way the query of an endpoint is paginated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointPagination {
+	/// The endpoint is not paginated.
+	None,
+	/// The endpoint is paginated using a page number and a count. See
+	/// [`Paginated`].
+	Offset,
+	/// The endpoint is paginated using opaque cursors. See
+	/// [`CursorPaginated`].
+	Cursor,
+}
+
+/// The authentication required to access an endpoint. This is a
+/// documentation-only counterpart of [`AppAuthentication`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum EndpointAuthentication {
+	/// The endpoint does not require authentication.
+	None,
+	/// Any logged in user can access this endpoint.
+	PlainToken,
+	/// Only the super admin of the workspace can access this endpoint.
+	WorkspaceSuperAdmin,
+	/// Only members of the workspace can access this endpoint.
+	WorkspaceMembership,
+	/// Only users with the given permission on the resource can access this
+	/// endpoint.
+	ResourcePermission {
+		/// The permission required on the resource.
+		permission: Permission,
+	},
+}
+
+impl<E> From<&AppAuthentication<E>> for EndpointAuthentication
+where
+	E: ApiEndpoint,
+	<E::RequestBody as Preprocessable>::Processed: Send,
+{
+	fn from(authentication: &AppAuthentication<E>) -> Self {
+		match authentication {
+			AppAuthentication::PlainTokenAuthenticator => Self::PlainToken,
+			AppAuthentication::WorkspaceSuperAdminAuthenticator { .. } => Self::WorkspaceSuperAdmin,
+			AppAuthentication::WorkspaceMembershipAuthenticator { .. } => Self::WorkspaceMembership,
+			AppAuthentication::ResourcePermissionAuthenticator { permission, .. } => {
+				Self::ResourcePermission {
+					permission: *permission,
+				}
+			}
+		}
+	}
+}
+
+/// A helper used by the endpoint macros to get the JSON schema of a type,
+/// falling back to `None` if the type does not implement [`JsonSchema`]. This
+/// works using autoref-based specialization, and hence only with concrete
+/// types, which is always the case for the fields of an endpoint.
+///
+/// ## Example
+/// ```rust
+/// # use models::utils::{SchemaOf, HasJsonSchema as _, MissingJsonSchema as _};
+/// struct NoSchema;
+///
+/// assert!((&&SchemaOf::<String>(Default::default())).schema().is_some());
+/// assert!((&&SchemaOf::<NoSchema>(Default::default())).schema().is_none());
+/// ```
+pub struct SchemaOf<T>(pub PhantomData<T>);
+
+/// Implemented for [`SchemaOf`] when the type has a JSON schema.
+pub trait HasJsonSchema {
+	/// Returns the JSON schema of the type.
+	fn schema(&self) -> Option<Value>;
+}
+
+impl<T> HasJsonSchema for &SchemaOf<T>
+where
+	T: JsonSchema,
+{
+	fn schema(&self) -> Option<Value> {
+		let root = SchemaSettings::draft2019_09()
+			.with(|settings| {
+				settings.inline_subschemas = true;
+				settings.meta_schema = None;
+			})
+			.into_generator()
+			.into_root_schema_for::<T>();
+		serde_json::to_value(root.schema).ok()
+	}
+}
+
+/// Implemented for [`SchemaOf`] for all types, as a fallback when the type does
+/// not have a JSON schema.
+pub trait MissingJsonSchema {
+	/// Always returns `None`.
+	fn schema(&self) -> Option<Value>;
+}
+
+impl<T> MissingJsonSchema for SchemaOf<T> {
+	fn schema(&self) -> Option<Value> {
+		None
+	}
+}
+
+/// Generates an OpenAPI 3.1 document for the given endpoints.
+pub fn generate_openapi_document<'a>(
+	title: &str,
+	version: &str,
+	server_url: &str,
+	endpoints: impl IntoIterator<Item = &'a EndpointDocs>,
+) -> Value {
+	let mut paths = Map::new();
+	for endpoint in endpoints {
+		let path = paths
+			.entry(openapi_path(endpoint.path))
+			.or_insert_with(|| Value::Object(Map::new()));
+		path[endpoint.method.as_str().to_lowercase()] = operation(endpoint);
+	}
+
+	json!({
+		"openapi": "3.1.0",
+		"info": {
+			"title": title,
+			"version": version,
+		},
+		"servers": [{ "url": server_url }],
+		"paths": paths,
+		"components": {
+			"securitySchemes": {
+				"bearerAuth": {
+					"type": "http",
+					"scheme": "bearer",
+				},
+			},
+			"schemas": {
+				"ErrorResponse": {
+					"type": "object",
+					"properties": {
+						"success": { "const": false },
+						"error": {
+							"type": "string",
+							"enum": ErrorType::iter()
+								.map(|error| error.to_string())
+								.collect::<Vec<_>>(),
+						},
+						"message": { "type": "string" },
+					},
+					"required": ["success", "error", "message"],
+				},
+			},
+			"responses": {
+				"Error": {
+					"description": "The request could not be completed",
+					"content": {
+						"application/json": {
+							"schema": { "$ref": "#/components/schemas/ErrorResponse" },
+						},
+					},
+				},
+			},
+		},
+	})
+}
+
+/// Converts an axum path (`/path/:param`) to an OpenAPI path
+/// (`/path/{param}`).
+fn openapi_path(path: &str) -> String {
+	path.split('/')
+		.map(|segment| match segment.strip_prefix(':') {
+			Some(param) => format!("{{{param}}}"),
+			None => segment.to_string(),
+		})
+		.collect::<Vec<_>>()
+		.join("/")
+}
+
+/// Generates the OpenAPI operation object for an endpoint.
+fn operation(endpoint: &EndpointDocs) -> Value {
+	let mut parameters = Vec::new();
+	parameters.extend(
+		endpoint
+			.path_params
+			.iter()
+			.map(|field| parameter(field, "path", true)),
+	);
+	parameters.extend(
+		endpoint
+			.query
+			.iter()
+			.flat_map(flatten_field)
+			.map(|field| parameter(&field, "query", field.required)),
+	);
+	match endpoint.pagination {
+		EndpointPagination::None => (),
+		EndpointPagination::Offset => {
+			parameters.push(json!({
+				"name": "page",
+				"in": "query",
+				"description": "The page number to return, starting from 0",
+				"schema": { "type": "integer", "minimum": 0, "default": 0 },
+			}));
+		}
+		EndpointPagination::Cursor => {
+			parameters.push(json!({
+				"name": "after",
+				"in": "query",
+				"description": "Return the items after this cursor, from the `next` link",
+				"schema": { "type": "string" },
+			}));
+			parameters.push(json!({
+				"name": "before",
+				"in": "query",
+				"description": "Return the items before this cursor, from the `prev` link",
+				"schema": { "type": "string" },
+			}));
+		}
+	}
+	if endpoint.pagination != EndpointPagination::None {
+		parameters.push(json!({
+			"name": "count",
+			"in": "query",
+			"description": "The number of items to return per page",
+			"schema": {
+				"type": "integer",
+				"minimum": 0,
+				"default": Paginated::<()>::DEFAULT_PAGE_SIZE,
+			},
+		}));
+	}
+	parameters.extend(
+		endpoint
+			.request_headers
+			.iter()
+			// The authorization header is covered by the security scheme
+			.filter(|field| !field.name.eq_ignore_ascii_case("authorization"))
+			.map(|field| parameter(field, "header", field.required)),
+	);
+
+	let mut description = endpoint.documentation.trim().to_string();
+	let security = match &endpoint.authentication {
+		EndpointAuthentication::None => json!([]),
+		authentication => {
+			description.push_str("\n\n");
+			description.push_str(&match authentication {
+				EndpointAuthentication::None => unreachable!(),
+				EndpointAuthentication::PlainToken => "Requires a valid token.".to_string(),
+				EndpointAuthentication::WorkspaceSuperAdmin => {
+					"Requires the user to be the super admin of the workspace.".to_string()
+				}
+				EndpointAuthentication::WorkspaceMembership => {
+					"Requires the user to be a member of the workspace.".to_string()
+				}
+				EndpointAuthentication::ResourcePermission { permission } => {
+					format!("Requires the `{permission}` permission on the resource.")
+				}
+			});
+			json!([{ "bearerAuth": [] }])
+		}
+	};
+
+	let response_headers = endpoint
+		.response_headers
+		.iter()
+		.map(|field| {
+			(
+				field.name.clone(),
+				json!({
+					"description": field.description,
+					"required": field.required,
+					"schema": { "type": "string" },
+				}),
+			)
+		})
+		.collect::<Map<_, _>>();
+
+	let mut responses = json!({
+		"4XX": { "$ref": "#/components/responses/Error" },
+		"5XX": { "$ref": "#/components/responses/Error" },
+	});
+	if endpoint.websocket {
+		responses["101"] = json!({
+			"description": "The connection is upgraded to a websocket",
+			"headers": response_headers,
+		});
+	} else {
+		let mut body = object_schema(&endpoint.response_body);
+		body["properties"]["success"] = json!({ "const": true });
+		body["required"]
+			.as_array_mut()
+			.expect("object schema should always have a required array")
+			.push(json!("success"));
+		responses["2XX"] = json!({
+			"description": "The request was successful",
+			"headers": response_headers,
+			"content": {
+				"application/json": { "schema": body },
+			},
+		});
+	}
+
+	let mut operation = json!({
+		"operationId": to_lower_camel_case(endpoint.name),
+		"summary": endpoint.documentation.lines().next().unwrap_or_default().trim(),
+		"description": description,
+		"tags": [tag(endpoint.path)],
+		"parameters": parameters,
+		"security": security,
+		"responses": responses,
+	});
+
+	if let EndpointAuthentication::ResourcePermission { permission } = &endpoint.authentication {
+		operation["x-permission"] = json!(permission.to_string());
+	}
+
+	if !endpoint.request_body.is_empty() {
+		operation["requestBody"] = json!({
+			"required": true,
+			"content": {
+				"application/json": {
+					"schema": object_schema(&endpoint.request_body),
+				},
+			},
+		});
+	}
+
+	operation
+}
+
+/// Generates an OpenAPI parameter object for a field.
+fn parameter(field: &FieldDocs, location: &str, required: bool) -> Value {
+	json!({
+		"name": field.name,
+		"in": location,
+		"description": field.description,
+		"required": required,
+		"schema": field_schema(field),
+	})
+}
+
+/// Returns the JSON schema for a field, falling back to an unconstrained
+/// schema with the name of the Rust type if the type does not have a schema.
+fn field_schema(field: &FieldDocs) -> Value {
+	field
+		.schema
+		.clone()
+		.unwrap_or_else(|| json!({ "x-rust-type": field.rust_type }))
+}
+
+/// Generates a JSON schema for an object with the given fields. Flattened
+/// fields are merged into the object where possible.
+fn object_schema(fields: &[FieldDocs]) -> Value {
+	let mut properties = Map::new();
+	let mut required = Vec::new();
+	let mut all_of = Vec::new();
+
+	for field in fields {
+		if field.flatten {
+			let schema = field_schema(field);
+			match schema.get("properties").and_then(Value::as_object) {
+				Some(flattened) => {
+					properties.extend(flattened.clone());
+					if let Some(flattened) = schema.get("required").and_then(Value::as_array) {
+						required.extend(flattened.iter().cloned());
+					}
+				}
+				None => all_of.push(schema),
+			}
+			continue;
+		}
+
+		let mut schema = field_schema(field);
+		if !field.description.is_empty() {
+			schema["description"] = json!(field.description);
+		}
+		properties.insert(field.name.clone(), schema);
+		if field.required {
+			required.push(json!(field.name));
+		}
+	}
+
+	let mut schema = json!({
+		"type": "object",
+		"properties": properties,
+		"required": required,
+	});
+	if !all_of.is_empty() {
+		schema["allOf"] = json!(all_of);
+	}
+	schema
+}
+
+/// Expands a flattened field (such as a query struct) into the fields of its
+/// schema. Other fields are returned as they are.
+fn flatten_field(field: &FieldDocs) -> Vec<FieldDocs> {
+	let properties = field
+		.schema
+		.as_ref()
+		.filter(|_| field.flatten)
+		.and_then(|schema| schema.get("properties"))
+		.and_then(Value::as_object);
+	let Some(properties) = properties else {
+		return vec![field.clone()];
+	};
+
+	let required = field
+		.schema
+		.as_ref()
+		.and_then(|schema| schema.get("required"))
+		.and_then(Value::as_array)
+		.cloned()
+		.unwrap_or_default();
+
+	properties
+		.iter()
+		.map(|(name, schema)| FieldDocs {
+			name: name.clone(),
+			description: schema
+				.get("description")
+				.and_then(Value::as_str)
+				.unwrap_or_default()
+				.to_string(),
+			required: required.contains(&json!(name)),
+			flatten: false,
+			rust_type: field.rust_type,
+			schema: Some(schema.clone()),
+		})
+		.collect()
+}
+
+/// Returns the tag used to group an endpoint, which is the first segment of
+/// the path that is not a parameter or the workspace prefix.
+fn tag(path: &str) -> &str {
+	path.split('/')
+		.filter(|segment| !segment.is_empty() && !segment.starts_with(':'))
+		.find(|segment| *segment != "workspace")
+		.unwrap_or("workspace")
+}
+
+/// Converts a PascalCase name to lowerCamelCase, used for operation IDs.
+fn to_lower_camel_case(name: &str) -> String {
+	let mut chars = name.chars();
+	chars
+		.next()
+		.map(|first| first.to_lowercase().chain(chars).collect())
+		.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+	use super::{openapi_path, tag, to_lower_camel_case};
+
+	#[test]
+	fn assert_path_params_are_templated() {
+		assert_eq!(
+			openapi_path("/workspace/:workspace_id/deployment/:deployment_id"),
+			"/workspace/{workspace_id}/deployment/{deployment_id}"
+		);
+		assert_eq!(openapi_path("/auth/sign-in"), "/auth/sign-in");
+	}
+
+	#[test]
+	fn assert_tag_is_the_first_resource_segment() {
+		assert_eq!(
+			tag("/workspace/:workspace_id/deployment/:deployment_id"),
+			"deployment"
+		);
+		assert_eq!(tag("/auth/sign-in"), "auth");
+		assert_eq!(tag("/workspace/:workspace_id"), "workspace");
+	}
+
+	#[test]
+	fn assert_operation_id_is_lower_camel_case() {
+		assert_eq!(
+			to_lower_camel_case("GetDeploymentInfo"),
+			"getDeploymentInfo"
+		);
+		assert_eq!(to_lower_camel_case(""), "");
+	}
+}
@@ -1,6 +1,6 @@
 use headers::{Error, Header};
 use http::{HeaderName, HeaderValue};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 use super::{AddTuple, RequiresResponseHeaders};
 
@@ -21,8 +21,12 @@ pub struct Paginated<T = ()> {
 	/// Any other query parameters that should be included in the request.
 	#[serde(flatten)]
 	pub data: T,
-	/// The number of items that should be returned per page.
-	#[serde(default = "default_page_size")]
+	/// The number of items that should be returned per page. This is capped
+	/// at [`Self::MAX_PAGE_SIZE`].
+	#[serde(
+		default = "default_page_size",
+		deserialize_with = "deserialize_page_size"
+	)]
 	pub count: usize,
 	/// The page number that should be returned. This is zero-indexed. So to get
 	/// the first page, you should set this to 0, and to get the second page,
@@ -36,6 +40,10 @@ impl<T> Paginated<T> {
 	/// This is currently set to 25. So if no page size is specified, the API
 	/// will return a maximum of 25 items, starting from the first item.
 	pub const DEFAULT_PAGE_SIZE: usize = 25;
+	/// The largest page size that can be requested. Requests for a larger page
+	/// size will only return this many items, so that a single request can't
+	/// make the API load an entire list at once.
+	pub const MAX_PAGE_SIZE: usize = 100;
 }
 
 /// Get the default page size that should be used if no page size is
@@ -45,6 +53,15 @@ const fn default_page_size() -> usize {
 	Paginated::<()>::DEFAULT_PAGE_SIZE
 }
 
+/// Deserializes the page size of a request, capping it at
+/// [`Paginated::MAX_PAGE_SIZE`].
+pub(super) fn deserialize_page_size<'de, D>(deserializer: D) -> Result<usize, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	usize::deserialize(deserializer).map(|count| count.min(Paginated::<()>::MAX_PAGE_SIZE))
+}
+
 impl<T> Default for Paginated<T>
 where
 	T: Default,
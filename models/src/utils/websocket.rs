@@ -9,10 +9,40 @@ use crate::ErrorType;
 /// A websocket upgrade request. This can be used as a body type for websocket
 /// endpoints.
 pub struct WebSocketUpgrade<ServerMsg, ClientMsg>(
-	#[cfg(feature = "axum")] pub TypedWebSocketUpgrade<ServerMsg, ClientMsg>,
+	#[cfg(feature = "axum")] pub ServerWebSocketUpgrade<ServerMsg, ClientMsg>,
 	#[cfg(not(feature = "axum"))] pub std::marker::PhantomData<(ServerMsg, ClientMsg)>,
 );
 
+/// The upgrade of a websocket request, as received by the server. A request
+/// that is built by a client (see [`WebSocketUpgrade::default`]) can't be
+/// upgraded, since the connection is upgraded by the client that sends it.
+/// This is needed for clients that are built along with the server, which
+/// have the `axum` feature enabled as well.
+#[cfg(feature = "axum")]
+pub struct ServerWebSocketUpgrade<ServerMsg, ClientMsg>(
+	Option<TypedWebSocketUpgrade<ServerMsg, ClientMsg>>,
+);
+
+#[cfg(feature = "axum")]
+impl<ServerMsg, ClientMsg> ServerWebSocketUpgrade<ServerMsg, ClientMsg> {
+	/// Finalize upgrading the connection and call the provided callback with
+	/// the stream. See [`TypedWebSocketUpgrade::on_upgrade`].
+	pub fn on_upgrade<F, Fut>(self, callback: F) -> axum::response::Response
+	where
+		F: FnOnce(axum_typed_websockets::WebSocket<ServerMsg, ClientMsg>) -> Fut + Send + 'static,
+		Fut: std::future::Future<Output = ()> + Send + 'static,
+		ServerMsg: Send,
+		ClientMsg: Send,
+	{
+		use axum::response::IntoResponse;
+
+		match self.0 {
+			Some(upgrade) => upgrade.on_upgrade(callback).into_response(),
+			None => axum::http::StatusCode::UPGRADE_REQUIRED.into_response(),
+		}
+	}
+}
+
 #[cfg(feature = "axum")]
 impl<ServerMsg, ClientMsg> FromAxumRequest for WebSocketUpgrade<ServerMsg, ClientMsg>
 where
@@ -26,7 +56,7 @@ where
 		request
 			.extract()
 			.await
-			.map(WebSocketUpgrade)
+			.map(|upgrade| WebSocketUpgrade(ServerWebSocketUpgrade(Some(upgrade))))
 			.map_err(ErrorType::server_error)
 	}
 }
@@ -43,6 +73,13 @@ where
 	}
 }
 
+#[cfg(feature = "axum")]
+impl<ServerMsg, ClientMsg> std::default::Default for WebSocketUpgrade<ServerMsg, ClientMsg> {
+	fn default() -> Self {
+		Self(ServerWebSocketUpgrade(None))
+	}
+}
+
 #[cfg(not(feature = "axum"))]
 impl<ServerMsg, ClientMsg> std::default::Default for WebSocketUpgrade<ServerMsg, ClientMsg> {
 	fn default() -> Self {
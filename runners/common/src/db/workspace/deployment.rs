@@ -60,6 +60,11 @@ pub async fn initialize_deployment_tables(
 				liveness_probe_port_type IN ('http')
 			),
 			current_live_digest TEXT,
+			rollout_strategy TEXT NOT NULL DEFAULT 'rolling',
+			rollout_max_surge INTEGER DEFAULT 1,
+			rollout_max_unavailable INTEGER DEFAULT 0,
+			rollout_canary_percentage INTEGER,
+			version INTEGER NOT NULL DEFAULT 1,
 			deleted DATETIME,
 
 			CHECK( 
@@ -89,6 +94,27 @@ pub async fn initialize_deployment_tables(
 			CHECK(LENGTH(TRIM(image_name)) > 0),
 			CHECK(LENGTH(TRIM(image_tag)) > 0),
 
+			CHECK(
+				(
+					rollout_strategy = 'rolling' AND
+					rollout_max_surge >= 0 AND
+					rollout_max_unavailable >= 0 AND
+					rollout_max_surge + rollout_max_unavailable > 0 AND
+					rollout_canary_percentage IS NULL
+				) OR (
+					rollout_strategy = 'canary' AND
+					rollout_max_surge IS NULL AND
+					rollout_max_unavailable IS NULL AND
+					rollout_canary_percentage > 0 AND
+					rollout_canary_percentage < 100
+				) OR (
+					rollout_strategy IN ('recreate', 'blue_green') AND
+					rollout_max_surge IS NULL AND
+					rollout_max_unavailable IS NULL AND
+					rollout_canary_percentage IS NULL
+				)
+			),
+
 			CHECK(
 				( 
 					startup_probe_port IS NULL AND
@@ -1,8 +1,13 @@
 use std::{future::Future, time::Duration};
 
 use futures::Stream;
-use models::api::workspace::deployment::*;
+use models::api::workspace::deployment::{
+	rollout::DeploymentRolloutImage,
+	runtime::{DeploymentContainerEvent, DeploymentReplicaCounts},
+	*,
+};
 use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
 use crate::prelude::*;
 
@@ -28,13 +33,18 @@ pub trait RunnerExecutor: Sized {
 	fn create(settings: &RunnerSettings<Self::Settings>) -> impl Future<Output = Self>;
 
 	/// This function is called when a deployment is created, or updated.
-	/// The runner should return an error with a duration if the deployment
-	/// failed to reconcile. This will be used to retry the deployment after
-	/// the given duration.
+	/// The runner should roll out the changes using the rollout strategy in
+	/// the running details. If a canary of the deployment's image is awaiting
+	/// promotion, `stable_image` is the image that the rest of the deployment
+	/// should keep running, and the image of the deployment should only be run
+	/// on the canary. The runner should return an error with a duration if the
+	/// deployment failed to reconcile. This will be used to retry the
+	/// deployment after the given duration.
 	fn upsert_deployment(
 		&self,
 		deployment: WithId<Deployment>,
 		running_details: DeploymentRunningDetails,
+		stable_image: Option<DeploymentRolloutImage>,
 	) -> impl Future<Output = Result<(), Duration>>;
 
 	/// This function is called when a deployment is deleted. The runner should
@@ -42,7 +52,83 @@ pub trait RunnerExecutor: Sized {
 	/// will be used to retry the deployment after the given duration.
 	fn delete_deployment(&self, deployment_id: Uuid) -> impl Future<Output = Result<(), Duration>>;
 
+	/// This function is called when a rolling restart of a deployment is
+	/// requested. The runner should replace the running instances of the
+	/// deployment one at a time, without changing its configuration. The
+	/// runner should return an error if the restart failed. Restarts are not
+	/// retried, so the duration of the error is ignored.
+	fn restart_deployment(&self, deployment_id: Uuid)
+		-> impl Future<Output = Result<(), Duration>>;
+
+	/// This function is called after a deployment is reconciled, so that its
+	/// state can be reported to the Patr API. The runner should return the
+	/// status of the deployment, along with the number of its instances. The
+	/// runner should return an error with a duration if the state of the
+	/// deployment couldn't be found. This will be used to retry the deployment
+	/// after the given duration.
+	fn get_deployment_status(
+		&self,
+		deployment_id: Uuid,
+	) -> impl Future<Output = Result<(DeploymentStatus, DeploymentReplicaCounts), Duration>>;
+
 	/// This function should return a stream of all the running deployment IDs
 	/// in the runner, sorted by the deployment ID.
 	fn list_running_deployments<'a>(&self) -> impl Future<Output = impl Stream<Item = Uuid> + 'a>;
+
+	/// This function should return a stream of the events that happen to the
+	/// containers of the deployments in the runner, along with the ID of the
+	/// deployment that each event happened to. The events are reported to the
+	/// Patr API as they happen.
+	fn watch_container_events<'a>(
+		&self,
+	) -> impl Future<Output = impl Stream<Item = (Uuid, DeploymentContainerEvent)> + 'a>;
+
+	/// This function is called when the user opens a terminal session in a
+	/// deployment. The runner should run the command in one of the running
+	/// containers of the deployment with a TTY attached, write the input it
+	/// receives to the command, and send everything the command writes to
+	/// `output`. Once `input` ends, the user has closed the session, and the
+	/// command should be stopped. The runner should return the exit code of
+	/// the command once it exits, if it could be found, or a description of
+	/// why the command couldn't be run. The session runs in the background, so
+	/// the future can't borrow the runner.
+	fn exec_deployment(
+		&self,
+		deployment_id: Uuid,
+		command: Vec<String>,
+		input: UnboundedReceiver<ExecInput>,
+		output: UnboundedSender<Vec<u8>>,
+	) -> impl Future<Output = Result<Option<i64>, String>> + Send + 'static;
+
+	/// This function is called when the user forwards a port of a deployment
+	/// to their machine. The runner should connect to the port of one of the
+	/// running containers of the deployment, write the data it receives from
+	/// `input` to the connection, and send everything read from the connection
+	/// to `output`. Once `input` ends, the user has closed their connection,
+	/// and the connection to the container should be closed as well. The
+	/// runner should return once the connection is closed, or a description
+	/// of why the port couldn't be connected to. The connection is relayed in
+	/// the background, so the future can't borrow the runner.
+	fn forward_port(
+		&self,
+		deployment_id: Uuid,
+		port: u16,
+		input: UnboundedReceiver<Vec<u8>>,
+		output: UnboundedSender<Vec<u8>>,
+	) -> impl Future<Output = Result<(), String>> + Send + 'static;
+}
+
+/// Input to a terminal session in a deployment, sent by the user that opened
+/// it
+#[derive(Debug, Clone)]
+pub enum ExecInput {
+	/// The user typed into the terminal
+	Data(Vec<u8>),
+	/// The user resized the terminal
+	Resize {
+		/// The number of rows in the terminal
+		rows: u16,
+		/// The number of columns in the terminal
+		cols: u16,
+	},
 }
@@ -31,7 +31,7 @@ pub mod prelude {
 
 	pub use crate::{
 		app::{AppRequest, AppState, ProcessedApiRequest},
-		executor::RunnerExecutor,
+		executor::{ExecInput, RunnerExecutor},
 		runner::Runner,
 		utils::{client, config::*, constants, ext_traits::*},
 	};
@@ -42,6 +42,7 @@ pub mod prelude {
 	///
 	/// Example:
 	/// ```rust
+	/// # use common::prelude::DatabaseConnection;
 	/// pub fn database_fn(connection: &mut DatabaseConnection) {
 	///     // Do something with `connection` ....
 	/// }
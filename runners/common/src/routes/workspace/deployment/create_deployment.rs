@@ -34,6 +34,7 @@ pub async fn create_deployment(
 								liveness_probe,
 								config_mounts,
 								volumes,
+								rollout_strategy,
 							},
 						deploy_on_create,
 					},
@@ -45,6 +46,11 @@ pub async fn create_deployment(
 ) -> Result<AppResponse<CreateDeploymentRequest>, ErrorType> {
 	trace!("Creating deployment: {}", name);
 
+	if !rollout_strategy.is_valid() {
+		debug!("Invalid rollout strategy: {:?}", rollout_strategy);
+		return Err(ErrorType::WrongParameters);
+	}
+
 	let deployment_id = Uuid::new_v4();
 
 	let status = if deploy_on_create {
@@ -73,6 +79,10 @@ pub async fn create_deployment(
 				liveness_probe_path,
 				liveness_probe_port_type,
 				current_live_digest,
+				rollout_strategy,
+				rollout_max_surge,
+				rollout_max_unavailable,
+				rollout_canary_percentage,
 				deleted
 			)
 		VALUES
@@ -94,6 +104,10 @@ pub async fn create_deployment(
 				$15,
 				$16,
 				NULL,
+				$17,
+				$18,
+				$19,
+				$20,
 				NULL
 			);
 		"#,
@@ -114,6 +128,10 @@ pub async fn create_deployment(
 	.bind(liveness_probe.as_ref().map(|probe| probe.port))
 	.bind(liveness_probe.as_ref().map(|probe| probe.path.as_str()))
 	.bind(liveness_probe.as_ref().map(|_| ExposedPortType::Http))
+	.bind(rollout_strategy.strategy_type())
+	.bind(rollout_strategy.max_surge())
+	.bind(rollout_strategy.max_unavailable())
+	.bind(rollout_strategy.canary_percentage())
 	.execute(&mut **database)
 	.await?;
 
@@ -250,6 +268,7 @@ pub async fn create_deployment(
 				liveness_probe,
 				config_mounts,
 				volumes,
+				rollout_strategy,
 			},
 		})
 		.expect("Failed to send deployment created message");
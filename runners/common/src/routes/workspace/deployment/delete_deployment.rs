@@ -18,6 +18,7 @@ pub async fn delete_deployment(
 					DeleteDeploymentRequestHeaders {
 						authorization: _,
 						user_agent: _,
+						if_match,
 					},
 				body: DeleteDeploymentRequestProcessed,
 			},
@@ -28,6 +29,30 @@ pub async fn delete_deployment(
 ) -> Result<AppResponse<DeleteDeploymentRequest>, ErrorType> {
 	info!("Deleting deployment: {deployment_id}");
 
+	let version = query(
+		r#"
+		SELECT
+			version
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			deleted IS NULL;
+		"#,
+	)
+	.bind(deployment_id)
+	.fetch_optional(&mut **database)
+	.await?
+	.map(|row| row.try_get::<i64, _>("version"))
+	.transpose()?
+	.map(ResourceVersion)
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	if !version.satisfies(if_match.as_ref()) {
+		debug!("Deployment `{deployment_id}` has been modified since it was last fetched");
+		return Err(ErrorType::PreconditionFailed);
+	}
+
 	query(
 		r#"
 		DELETE FROM
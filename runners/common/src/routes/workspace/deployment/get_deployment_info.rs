@@ -138,7 +138,7 @@ pub async fn get_deployment_info(
 	})
 	.collect::<Result<BTreeMap<_, _>, ErrorType>>()?;
 
-	let deployment = query(
+	let (version, deployment) = query(
 		r#"
 		SELECT
 			id,
@@ -157,7 +157,12 @@ pub async fn get_deployment_info(
 			liveness_probe_port,
 			liveness_probe_path,
 			liveness_probe_port_type,
-			current_live_digest
+			current_live_digest,
+			rollout_strategy,
+			rollout_max_surge,
+			rollout_max_unavailable,
+			rollout_canary_percentage,
+			version
 		FROM
 			deployment
 		WHERE
@@ -182,7 +187,19 @@ pub async fn get_deployment_info(
 		let min_horizontal_scale = row.try_get::<u16, _>("min_horizontal_scale")?;
 		let max_horizontal_scale = row.try_get::<u16, _>("max_horizontal_scale")?;
 
-		Ok::<_, ErrorType>(GetDeploymentInfoResponse {
+		let rollout_strategy = DeploymentRolloutStrategy::from_parts(
+			row.try_get::<DeploymentRolloutStrategyType, _>("rollout_strategy")?,
+			row.try_get::<Option<u16>, _>("rollout_max_surge")?,
+			row.try_get::<Option<u16>, _>("rollout_max_unavailable")?,
+			row.try_get::<Option<u8>, _>("rollout_canary_percentage")?,
+		)
+		.ok_or(ErrorType::server_error(
+			"corrupted deployment, invalid rollout strategy",
+		))?;
+
+		let version = ResourceVersion(row.try_get::<i64, _>("version")?);
+
+		let deployment = GetDeploymentInfoResponse {
 			deployment: WithId::new(
 				deployment_id,
 				Deployment {
@@ -215,14 +232,24 @@ pub async fn get_deployment_info(
 					.map(|(port, path)| DeploymentProbe { port, path }),
 				config_mounts,
 				volumes,
+				rollout_strategy,
 			},
-		})
+			// Self-hosted runners don't keep track of rollouts, so canaries are
+			// never held back for promotion
+			rollout: None,
+			// Self-hosted runners only record the status of their deployments
+			runtime: None,
+		};
+
+		Ok::<_, ErrorType>((version, deployment))
 	})
 	.ok_or(ErrorType::ResourceDoesNotExist)??;
 
 	AppResponse::builder()
 		.body(deployment)
-		.headers(())
+		.headers(GetDeploymentInfoResponseHeaders {
+			etag: version.to_etag(),
+		})
 		.status_code(StatusCode::OK)
 		.build()
 		.into_result()
@@ -10,11 +10,12 @@ pub async fn list_deployment(
 		request:
 			ProcessedApiRequest {
 				path: ListDeploymentPath { workspace_id: _ },
-				query: Paginated {
-					data: (),
-					count,
-					page,
-				},
+				query:
+					Paginated {
+						data: ListDeploymentQuery { filter, sort },
+						count,
+						page,
+					},
 				headers:
 					ListDeploymentRequestHeaders {
 						authorization: _,
@@ -29,6 +30,13 @@ pub async fn list_deployment(
 ) -> Result<AppResponse<ListDeploymentRequest>, ErrorType> {
 	trace!("Listing all deployments");
 
+	// The runner doesn't support filtering or sorting its deployments yet, so
+	// those are rejected rather than silently listing everything
+	if filter.is_some() || sort.is_some() {
+		debug!("Filtering and sorting deployments is not supported by the runner");
+		return Err(ErrorType::WrongParameters);
+	}
+
 	let rows = query(
 		r#"
 		SELECT
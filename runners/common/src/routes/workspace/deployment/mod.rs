@@ -10,6 +10,8 @@ mod get_deployment_info;
 mod list_all_deployment_machine_types;
 /// The handler for listing all deployments.
 mod list_deployment;
+/// The handler for doing a rolling restart of a deployment.
+mod restart_deployment;
 /// The handler for starting a deployment.
 mod start_deployment;
 /// The handler for stopping a deployment.
@@ -23,6 +25,7 @@ pub use self::{
 	get_deployment_info::*,
 	list_all_deployment_machine_types::*,
 	list_deployment::*,
+	restart_deployment::*,
 	start_deployment::*,
 	stop_deployment::*,
 	update_deployment::*,
@@ -42,5 +45,6 @@ where
 		.mount_auth_endpoint(get_deployment_info, state)
 		.mount_auth_endpoint(start_deployment, state)
 		.mount_auth_endpoint(stop_deployment, state)
+		.mount_auth_endpoint(restart_deployment, state)
 		.mount_endpoint(list_all_deployment_machine_types, state)
 }
@@ -0,0 +1,69 @@
+use http::StatusCode;
+use models::{
+	api::workspace::{deployment::*, runner::StreamRunnerDataForWorkspaceServerMsg},
+	prelude::*,
+};
+
+use crate::prelude::*;
+
+/// The handler to do a rolling restart of a deployment. The executor replaces
+/// the instances of the deployment one at a time, without changing its
+/// configuration.
+pub async fn restart_deployment(
+	AppRequest {
+		request:
+			ProcessedApiRequest {
+				path: RestartDeploymentPath {
+					workspace_id: _,
+					deployment_id,
+				},
+				query: (),
+				headers:
+					RestartDeploymentRequestHeaders {
+						authorization: _,
+						user_agent: _,
+					},
+				body: RestartDeploymentRequestProcessed,
+			},
+		database,
+		runner_changes_sender,
+		config: _,
+	}: AppRequest<'_, RestartDeploymentRequest>,
+) -> Result<AppResponse<RestartDeploymentRequest>, ErrorType> {
+	trace!("Restarting deployment: {}", deployment_id);
+
+	let status = query(
+		r#"
+		SELECT
+			status
+		FROM
+			deployment
+		WHERE
+			id = $1 AND
+			deleted IS NULL;
+		"#,
+	)
+	.bind(deployment_id)
+	.fetch_optional(&mut **database)
+	.await?
+	.map(|row| row.try_get::<DeploymentStatus, _>("status"))
+	.transpose()?
+	.ok_or(ErrorType::ResourceDoesNotExist)?;
+
+	if status == DeploymentStatus::Stopped {
+		return Err(ErrorType::DeploymentNotRunning);
+	}
+
+	runner_changes_sender
+		.send(StreamRunnerDataForWorkspaceServerMsg::DeploymentRestarted { id: deployment_id })
+		.expect("Failed to send deployment restarted message");
+
+	trace!("Restart sent to runner");
+
+	AppResponse::builder()
+		.body(RestartDeploymentResponse)
+		.headers(())
+		.status_code(StatusCode::ACCEPTED)
+		.build()
+		.into_result()
+}
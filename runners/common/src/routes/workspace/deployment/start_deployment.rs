@@ -13,7 +13,7 @@ pub async fn start_deployment(
 					workspace_id: _,
 					deployment_id,
 				},
-				query: StartDeploymentQuery { force_restart },
+				query: StartDeploymentQuery { force_restart: _ },
 				headers:
 					StartDeploymentRequestHeaders {
 						authorization: _,
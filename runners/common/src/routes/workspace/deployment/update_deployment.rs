@@ -9,8 +9,8 @@ use crate::{
 /// Update deployment details. This endpoint is used to update the deployment
 /// details. The deployment details that can be updated are the name, machine
 /// type, deploy on push, min horizontal scale, max horizontal scale, ports,
-/// environment variables, startup probe, liveness probe, config mounts, and
-/// volumes. At least one of the values must be updated.
+/// environment variables, startup probe, liveness probe, config mounts,
+/// volumes, and rollout strategy. At least one of the values must be updated.
 pub async fn update_deployment(
 	AppRequest {
 		request:
@@ -24,6 +24,7 @@ pub async fn update_deployment(
 					UpdateDeploymentRequestHeaders {
 						authorization: _,
 						user_agent: _,
+						if_match,
 					},
 				body:
 					UpdateDeploymentRequestProcessed {
@@ -39,6 +40,7 @@ pub async fn update_deployment(
 						liveness_probe,
 						config_mounts,
 						volumes,
+						rollout_strategy,
 					},
 			},
 		database,
@@ -62,6 +64,7 @@ pub async fn update_deployment(
 		.or(liveness_probe.as_ref().map(|_| 0))
 		.or(config_mounts.as_ref().map(|_| 0))
 		.or(volumes.as_ref().map(|_| 0))
+		.or(rollout_strategy.as_ref().map(|_| 0))
 		.is_none()
 	{
 		debug!(
@@ -71,10 +74,15 @@ pub async fn update_deployment(
 		return Err(ErrorType::WrongParameters);
 	}
 
-	query(
+	if let Some(rollout_strategy) = rollout_strategy.filter(|strategy| !strategy.is_valid()) {
+		debug!("Invalid rollout strategy: {:?}", rollout_strategy);
+		return Err(ErrorType::WrongParameters);
+	}
+
+	let version = query(
 		r#"
 		SELECT
-			id
+			version
 		FROM
 			deployment
 		WHERE
@@ -85,8 +93,19 @@ pub async fn update_deployment(
 	.bind(deployment_id)
 	.fetch_optional(&mut **database)
 	.await?
+	.map(|row| row.try_get::<i64, _>("version"))
+	.transpose()?
+	.map(ResourceVersion)
 	.ok_or(ErrorType::ResourceDoesNotExist)?;
 
+	if !version.satisfies(if_match.as_ref()) {
+		debug!(
+			"Deployment `{}` has been modified since it was last fetched",
+			deployment_id
+		);
+		return Err(ErrorType::PreconditionFailed);
+	}
+
 	if let Some(ports) = ports {
 		if ports.is_empty() {
 			return Err(ErrorType::WrongParameters);
@@ -192,9 +211,35 @@ pub async fn update_deployment(
 					ELSE
 						'http'
 				END
-			)
+			),
+			rollout_strategy = COALESCE($10, rollout_strategy),
+			rollout_max_surge = (
+				CASE
+					WHEN $10 IS NULL THEN
+						rollout_max_surge
+					ELSE
+						$11
+				END
+			),
+			rollout_max_unavailable = (
+				CASE
+					WHEN $10 IS NULL THEN
+						rollout_max_unavailable
+					ELSE
+						$12
+				END
+			),
+			rollout_canary_percentage = (
+				CASE
+					WHEN $10 IS NULL THEN
+						rollout_canary_percentage
+					ELSE
+						$13
+				END
+			),
+			version = version + 1
 		WHERE
-			id = $10;
+			id = $14;
 		"#,
 	)
 	.bind(name)
@@ -206,6 +251,10 @@ pub async fn update_deployment(
 	.bind(startup_probe.as_ref().map(|probe| probe.path.as_str()))
 	.bind(liveness_probe.as_ref().map(|probe| probe.port))
 	.bind(liveness_probe.as_ref().map(|probe| probe.path.as_str()))
+	.bind(rollout_strategy.map(|strategy| strategy.strategy_type()))
+	.bind(rollout_strategy.and_then(|strategy| strategy.max_surge()))
+	.bind(rollout_strategy.and_then(|strategy| strategy.max_unavailable()))
+	.bind(rollout_strategy.and_then(|strategy| strategy.canary_percentage()))
 	.bind(deployment_id)
 	.execute(&mut **database)
 	.await?;
@@ -1,7 +1,13 @@
 use std::{collections::BTreeMap, pin::pin};
 
 use futures::StreamExt;
-use models::api::workspace::deployment::*;
+use models::api::workspace::{
+	deployment::{
+		rollout::{DeploymentRollout, DeploymentRolloutImage},
+		*,
+	},
+	runner::StreamRunnerDataForWorkspaceClientMsg,
+};
 use tokio::time::{Duration, Instant};
 
 use crate::{prelude::*, utils::delayed_future::DelayedFuture};
@@ -71,6 +77,8 @@ where
 			let GetDeploymentInfoResponse {
 				deployment,
 				running_details,
+				rollout,
+				runtime: _,
 			} = match self.get_deployment_info(deployment_id).await {
 				Ok(response) => response,
 				Err(ErrorType::ResourceDoesNotExist) => {
@@ -86,18 +94,62 @@ where
 				}
 			};
 
+			let image = DeploymentRolloutImage {
+				image_tag: deployment.data.image_tag.clone(),
+				digest: deployment.data.current_live_digest.clone(),
+			};
+
 			if let Err(err) = self
 				.executor
-				.upsert_deployment(deployment, running_details)
+				.upsert_deployment(
+					deployment,
+					running_details,
+					rollout
+						.as_ref()
+						.and_then(DeploymentRollout::stable_image)
+						.cloned(),
+				)
 				.await
 			{
 				break 'reconcile Err(err);
 			}
 
+			let (status, replicas) = match self.executor.get_deployment_status(deployment_id).await
+			{
+				Ok(status) => status,
+				Err(err) => break 'reconcile Err(err),
+			};
+			let rolled_out = status == DeploymentStatus::Running && replicas.is_rolled_out();
+			self.report(
+				StreamRunnerDataForWorkspaceClientMsg::DeploymentStatusReported {
+					id: deployment_id,
+					status,
+					replicas,
+				},
+			)
+			.await;
+			if rolled_out {
+				self.report(StreamRunnerDataForWorkspaceClientMsg::DeploymentRolledOut {
+					id: deployment_id,
+					image,
+				})
+				.await;
+			}
+
 			Ok(())
 		};
 
 		if let Err(wait_time) = result {
+			self.report(
+				StreamRunnerDataForWorkspaceClientMsg::DeploymentReconcileFailed {
+					id: deployment_id,
+					error: format!(
+						"Failed to reconcile the deployment. Retrying in {} seconds",
+						wait_time.as_secs()
+					),
+				},
+			)
+			.await;
 			self.reconciliation_list.push(DelayedFuture::new(
 				Instant::now() + wait_time,
 				deployment_id,
@@ -107,6 +159,22 @@ where
 		self.recheck_next_reconcile_future();
 	}
 
+	/// Do a rolling restart of a deployment. A restart is a one-off action, so
+	/// unlike reconciliations, it isn't retried if it fails, since retrying it
+	/// later could restart the deployment at an unexpected time.
+	pub(super) async fn restart_deployment(&mut self, deployment_id: Uuid) {
+		trace!("Restarting deployment `{}`", deployment_id);
+
+		if self
+			.executor
+			.restart_deployment(deployment_id)
+			.await
+			.is_err()
+		{
+			error!("Failed to restart deployment `{}`", deployment_id);
+		}
+	}
+
 	/// Get all the local deployments. This function will get all the local
 	/// deployments from the SQLite database.
 	async fn get_all_local_deployments(&mut self) -> Result<Vec<Uuid>, ErrorType> {
@@ -268,7 +336,11 @@ where
 						liveness_probe_port,
 						liveness_probe_path,
 						liveness_probe_port_type,
-						current_live_digest
+						current_live_digest,
+						rollout_strategy,
+						rollout_max_surge,
+						rollout_max_unavailable,
+						rollout_canary_percentage
 					FROM
 						deployment
 					WHERE
@@ -294,6 +366,16 @@ where
 					let min_horizontal_scale = row.try_get::<u16, _>("min_horizontal_scale")?;
 					let max_horizontal_scale = row.try_get::<u16, _>("max_horizontal_scale")?;
 
+					let rollout_strategy = DeploymentRolloutStrategy::from_parts(
+						row.try_get::<DeploymentRolloutStrategyType, _>("rollout_strategy")?,
+						row.try_get::<Option<u16>, _>("rollout_max_surge")?,
+						row.try_get::<Option<u16>, _>("rollout_max_unavailable")?,
+						row.try_get::<Option<u8>, _>("rollout_canary_percentage")?,
+					)
+					.ok_or(ErrorType::server_error(
+						"corrupted deployment, invalid rollout strategy",
+					))?;
+
 					Ok::<_, ErrorType>(GetDeploymentInfoResponse {
 						deployment: WithId::new(
 							deployment_id,
@@ -328,7 +410,12 @@ where
 								.map(|(port, path)| DeploymentProbe { port, path }),
 							config_mounts,
 							volumes,
+							rollout_strategy,
 						},
+						// Self-hosted runners don't keep track of rollouts, so canaries are
+						// never held back for promotion
+						rollout: None,
+						runtime: None,
 					})
 				})
 				.ok_or(ErrorType::ResourceDoesNotExist)?
@@ -338,30 +425,60 @@ where
 				runner_id: _,
 				api_token,
 				user_agent,
-			} => client::make_request(
-				ApiRequest::<GetDeploymentInfoRequest>::builder()
-					.path(GetDeploymentInfoPath {
-						workspace_id: *workspace_id,
-						deployment_id,
-					})
-					.headers(GetDeploymentInfoRequestHeaders {
-						authorization: api_token.clone(),
-						user_agent: user_agent.clone(),
-					})
-					.query(())
-					.body(GetDeploymentInfoRequest)
-					.build(),
-			)
-			.await
-			.map(|response| response.body)
-			.map_err(|err| {
-				debug!(
-					"Failed to get deployment info for `{}`: {:?}",
-					deployment_id, err
-				);
-				debug!("Retrying in 5 seconds");
-				err.body.error
-			}),
+			} => client::API_CLIENT
+				.make_request(
+					ApiRequest::<GetDeploymentInfoRequest>::builder()
+						.path(GetDeploymentInfoPath {
+							workspace_id: *workspace_id,
+							deployment_id,
+						})
+						.headers(GetDeploymentInfoRequestHeaders {
+							authorization: api_token.clone(),
+							user_agent: user_agent.clone(),
+						})
+						.query(())
+						.body(GetDeploymentInfoRequest)
+						.build(),
+				)
+				.await
+				.map(|response| response.body)
+				.map_err(|err| {
+					debug!(
+						"Failed to get deployment info for `{}`: {:?}",
+						deployment_id, err
+					);
+					debug!("Retrying in 5 seconds");
+					err.body.error
+				}),
+		}
+	}
+
+	/// Record the status of a deployment in the local database. This is used by
+	/// self-hosted runners, which don't have a Patr API to report to. The
+	/// status of a stopped deployment isn't overwritten, since it isn't
+	/// supposed to be running.
+	pub(super) async fn record_local_deployment_status(&self, id: Uuid, status: DeploymentStatus) {
+		let result = query(
+			r#"
+			UPDATE
+				deployment
+			SET
+				status = $1
+			WHERE
+				id = $2 AND
+				status != 'stopped';
+			"#,
+		)
+		.bind(status)
+		.bind(id)
+		.execute(&self.state.database)
+		.await;
+
+		if let Err(err) = result {
+			error!(
+				"Failed to record the status of deployment `{}`: {:?}",
+				id, err
+			);
 		}
 	}
 
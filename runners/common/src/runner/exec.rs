@@ -0,0 +1,112 @@
+use futures::future;
+use models::api::workspace::runner::*;
+use tokio::{sync::mpsc::unbounded_channel, task};
+
+use crate::prelude::*;
+
+impl<E> super::Runner<E>
+where
+	E: RunnerExecutor + Clone + 'static,
+{
+	/// Handle a message from the server about a terminal session in a
+	/// deployment. Messages that aren't about terminal sessions are given
+	/// back, so that the resource they're about can be reconciled.
+	pub(super) fn handle_exec_message(
+		&mut self,
+		msg: StreamRunnerDataForWorkspaceServerMsg,
+	) -> Option<StreamRunnerDataForWorkspaceServerMsg> {
+		match msg {
+			StreamRunnerDataForWorkspaceServerMsg::DeploymentExecStarted {
+				id,
+				session_id,
+				command,
+			} => {
+				self.start_exec_session(id, session_id, command);
+			}
+			StreamRunnerDataForWorkspaceServerMsg::DeploymentExecInputReceived {
+				id: _,
+				session_id,
+				data,
+			} => {
+				self.send_exec_input(session_id, ExecInput::Data(data.into_vec()));
+			}
+			StreamRunnerDataForWorkspaceServerMsg::DeploymentExecResized {
+				id: _,
+				session_id,
+				rows,
+				cols,
+			} => {
+				self.send_exec_input(session_id, ExecInput::Resize { rows, cols });
+			}
+			StreamRunnerDataForWorkspaceServerMsg::DeploymentExecClosed { id: _, session_id } => {
+				info!("Closing terminal session `{}`", session_id);
+				// Dropping the sender ends the input of the session, which
+				// stops the command
+				self.exec_sessions.remove(&session_id);
+			}
+			msg => return Some(msg),
+		}
+
+		None
+	}
+
+	/// Start a terminal session in a deployment. The session runs in the
+	/// background, and whatever the command writes is reported to the server
+	/// until the command exits or the session is closed.
+	fn start_exec_session(&mut self, deployment_id: Uuid, session_id: Uuid, command: Vec<String>) {
+		info!(
+			"Starting terminal session `{}` in deployment `{}`",
+			session_id, deployment_id
+		);
+
+		let (input_sender, input) = unbounded_channel();
+		let (output, mut output_receiver) = unbounded_channel::<Vec<u8>>();
+		self.exec_sessions.insert(session_id, input_sender);
+
+		let session = self
+			.executor
+			.exec_deployment(deployment_id, command, input, output);
+		let session_reporter = self.session_reporter.clone();
+
+		task::spawn(async move {
+			// The executor drops the output once the command exits, which ends
+			// the relay
+			let relay_output = async {
+				while let Some(data) = output_receiver.recv().await {
+					_ = session_reporter.send(
+						StreamRunnerDataForWorkspaceClientMsg::DeploymentExecOutputSent {
+							id: deployment_id,
+							session_id,
+							data: data.into(),
+						},
+					);
+				}
+			};
+			let (result, ()) = future::join(session, relay_output).await;
+
+			_ = session_reporter.send(match result {
+				Ok(exit_code) => StreamRunnerDataForWorkspaceClientMsg::DeploymentExecExited {
+					id: deployment_id,
+					session_id,
+					exit_code,
+				},
+				Err(error) => {
+					error!("Terminal session `{}` failed: {}", session_id, error);
+					StreamRunnerDataForWorkspaceClientMsg::DeploymentExecFailed {
+						id: deployment_id,
+						session_id,
+						error,
+					}
+				}
+			});
+		});
+	}
+
+	/// Send the input of the user to a terminal session. Input to sessions that
+	/// have already ended is dropped.
+	fn send_exec_input(&mut self, session_id: Uuid, input: ExecInput) {
+		if let Some(session) = self.exec_sessions.get(&session_id) {
+			_ = session.send(input);
+		}
+	}
+}
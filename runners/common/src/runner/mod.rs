@@ -1,15 +1,17 @@
-use std::{future::IntoFuture, net::SocketAddr, pin::pin};
+use std::{collections::HashMap, future::IntoFuture, net::SocketAddr, pin::pin};
 
 use futures::{
 	future::{self, BoxFuture, Either},
-	stream::BoxStream,
+	stream::{self, BoxStream, SplitSink},
 	FutureExt,
+	SinkExt,
 	StreamExt,
 };
 use models::{api::workspace::runner::*, rbac::ResourceType};
+use patr_client::ApiWebSocket;
 use tokio::{
 	net::TcpListener,
-	sync::mpsc::unbounded_channel,
+	sync::mpsc::{unbounded_channel, UnboundedSender},
 	task,
 	time::{self, Duration},
 };
@@ -25,6 +27,17 @@ use crate::{db, prelude::*, utils::delayed_future::DelayedFuture};
 
 /// All deployment related functions for the runner
 mod deployment;
+/// All functions related to the terminal sessions in deployments
+mod exec;
+/// All functions related to the ports forwarded from deployments
+mod port_forward;
+
+/// The sending half of the connection to the Patr API, which a managed runner
+/// reports the state of its deployments over
+type Reporter = SplitSink<
+	ApiWebSocket<StreamRunnerDataForWorkspaceServerMsg, StreamRunnerDataForWorkspaceClientMsg>,
+	StreamRunnerDataForWorkspaceClientMsg,
+>;
 
 /// The runner is the main struct that is used to run the resources.
 ///
@@ -46,6 +59,21 @@ where
 	/// The future that will resolve to the next resource that needs to be
 	/// reconciled
 	next_reconcile_future: BoxFuture<'static, Uuid>,
+	/// The connection that the state of deployments is reported to the Patr
+	/// API over. This is only present while a managed runner is connected to
+	/// the API
+	reporter: Option<Reporter>,
+	/// The terminal sessions that are open in deployments, along with the
+	/// senders that the input of the users is sent to them through
+	exec_sessions: HashMap<Uuid, UnboundedSender<ExecInput>>,
+	/// The connections that are open to the forwarded ports of deployments,
+	/// along with the senders that the data of the users is sent to them
+	/// through
+	port_forward_sessions: HashMap<Uuid, UnboundedSender<Vec<u8>>>,
+	/// The sender for the output of terminal sessions and forwarded ports,
+	/// which run in the background. The output is reported to the server along
+	/// with the events of the deployments' containers
+	session_reporter: UnboundedSender<StreamRunnerDataForWorkspaceClientMsg>,
 }
 
 impl<E> Runner<E>
@@ -58,7 +86,7 @@ where
 	/// is responsible for. This function will run forever until the runner is
 	/// stopped.
 	pub async fn run() {
-		let (mut runner, mut runner_changes_receiver) = Self::init().await;
+		let (mut runner, mut runner_changes_receiver, session_reports) = Self::init().await;
 
 		// Run the server here
 		let state = runner.state.clone();
@@ -86,6 +114,23 @@ where
 		let mut exit_signal = pin!(exit_signal());
 		debug!("Exit signal listener started");
 
+		// The events are watched on a clone of the executor, so that the
+		// runner can still be borrowed mutably while they're being watched.
+		// They're reported along with the output of terminal sessions and
+		// forwarded ports. The stream never ends, so that it doesn't resolve
+		// repeatedly once the executor stops watching for events
+		let executor = runner.executor.clone();
+		let mut reports = pin!(stream::select(
+			executor.watch_container_events().await.map(|(id, event)| {
+				StreamRunnerDataForWorkspaceClientMsg::DeploymentContainerEventOccurred {
+					id,
+					event,
+				}
+			}),
+			session_reports,
+		)
+		.chain(stream::pending()));
+
 		info!("Connecting to the server");
 		// Connect to the server infinitely until the exit signal is received
 		'main: loop {
@@ -134,7 +179,10 @@ where
 					&mut exit_signal,
 					future::select(
 						reconcile_all.as_mut(),
-						future::select(pinned_stream.next(), &mut runner.next_reconcile_future),
+						future::select(
+							pinned_stream.next(),
+							future::select(&mut runner.next_reconcile_future, reports.next()),
+						),
 					),
 				)
 				.await
@@ -197,9 +245,16 @@ where
 						continue 'main;
 					}
 					// A specific resource needs to be reconciled again
-					Either::Right((deployment_id, _)) => {
+					Either::Right((Either::Left((deployment_id, _)), _)) => {
 						runner.reconcile_deployment(deployment_id).await;
 					}
+					// Something happened to the container of a deployment, or a
+					// terminal session has output
+					Either::Right((Either::Right((Some(message), _)), _)) => {
+						runner.report(message).await;
+					}
+					// The stream of reports never ends
+					Either::Right((Either::Right((None, _)), _)) => (),
 				}
 			}
 		}
@@ -214,6 +269,7 @@ where
 	async fn init() -> (
 		Self,
 		UnboundedReceiverStream<StreamRunnerDataForWorkspaceServerMsg>,
+		UnboundedReceiverStream<StreamRunnerDataForWorkspaceClientMsg>,
 	) {
 		let config = RunnerSettings::<E::Settings>::parse(E::RUNNER_INTERNAL_NAME)
 			.expect("Failed to parse settings");
@@ -256,6 +312,8 @@ where
 		let (runner_changes_sender, runner_changes_receiver) = unbounded_channel();
 		let runner_changes_receiver = UnboundedReceiverStream::new(runner_changes_receiver);
 
+		let (session_reporter, session_reports) = unbounded_channel();
+
 		let state = AppState {
 			database,
 			runner_changes_sender,
@@ -272,8 +330,13 @@ where
 				state,
 				reconciliation_list,
 				next_reconcile_future,
+				reporter: None,
+				exec_sessions: HashMap::new(),
+				port_forward_sessions: HashMap::new(),
+				session_reporter,
 			},
 			runner_changes_receiver,
+			UnboundedReceiverStream::new(session_reports),
 		)
 	}
 
@@ -289,11 +352,29 @@ where
 	/// from the server and run the reconciliation for the resource that the
 	/// message is for.
 	async fn handle_server_message(&mut self, msg: StreamRunnerDataForWorkspaceServerMsg) {
+		// The messages of terminal sessions and forwarded ports carry
+		// everything that the user sends, so they're handled before anything
+		// is logged
+		let Some(msg) = self
+			.handle_exec_message(msg)
+			.and_then(|msg| self.handle_port_forward_message(msg))
+		else {
+			return;
+		};
+
 		info!("Handling server message: {:?}", msg);
 		// if this resource is already queued for reconciliation, remove that
 		let resource_id = get_resource_id_from_message(&msg);
 
 		match msg.resource_type() {
+			ResourceType::Deployment
+				if matches!(
+					msg,
+					StreamRunnerDataForWorkspaceServerMsg::DeploymentRestarted { .. }
+				) =>
+			{
+				self.restart_deployment(resource_id).await;
+			}
 			ResourceType::Deployment => {
 				self.reconcile_deployment(resource_id).await;
 			}
@@ -322,30 +403,67 @@ where
 			RunnerMode::SelfHosted {
 				password_pepper: _,
 				jwt_secret: _,
-			} => Ok(runner_changes_receiver
-				.map(|msg| Ok::<_, ErrorType>(msg))
-				.boxed()),
+			} => Ok(runner_changes_receiver.map(Ok::<_, ErrorType>).boxed()),
 			RunnerMode::Managed {
 				workspace_id,
 				runner_id,
 				api_token,
 				user_agent,
-			} => client::stream_request(
-				ApiRequest::<StreamRunnerDataForWorkspaceRequest>::builder()
-					.path(StreamRunnerDataForWorkspacePath {
-						workspace_id: *workspace_id,
-						runner_id: *runner_id,
-					})
-					.headers(StreamRunnerDataForWorkspaceRequestHeaders {
-						authorization: api_token.clone(),
-						user_agent: user_agent.clone(),
-					})
-					.query(())
-					.body(Default::default())
-					.build(),
-			)
-			.await
-			.map(StreamExt::boxed),
+			} => client::API_CLIENT
+				.stream_request(
+					ApiRequest::<StreamRunnerDataForWorkspaceRequest>::builder()
+						.path(StreamRunnerDataForWorkspacePath {
+							workspace_id: *workspace_id,
+							runner_id: *runner_id,
+						})
+						.headers(StreamRunnerDataForWorkspaceRequestHeaders {
+							authorization: api_token.clone(),
+							user_agent: user_agent.clone(),
+						})
+						.query(())
+						.body(Default::default())
+						.build(),
+				)
+				.await
+				.map(|websocket| {
+					let (reporter, stream) = websocket.split();
+					self.reporter = Some(reporter);
+					stream.boxed()
+				}),
+		}
+	}
+
+	/// Report the state of a deployment to the Patr API. Self-hosted runners
+	/// don't have an API to report to, so only the status of the deployment is
+	/// recorded, in the local database. A report is dropped if the runner isn't
+	/// connected to the API, since the deployment is reported on again once it
+	/// is reconciled after reconnecting.
+	async fn report(&mut self, message: StreamRunnerDataForWorkspaceClientMsg) {
+		trace!("Reporting message: {:?}", message);
+		match &self.state.config.mode {
+			RunnerMode::SelfHosted {
+				password_pepper: _,
+				jwt_secret: _,
+			} => {
+				if let StreamRunnerDataForWorkspaceClientMsg::DeploymentStatusReported {
+					id,
+					status,
+					..
+				} = message
+				{
+					self.record_local_deployment_status(id, status).await;
+				}
+			}
+			RunnerMode::Managed { .. } => {
+				let Some(reporter) = &mut self.reporter else {
+					debug!("Not connected to the server. Dropping report");
+					return;
+				};
+				if let Err(err) = reporter.send(message).await {
+					error!("Failed to report to the server: {:?}", err);
+					self.reporter = None;
+				}
+			}
 		}
 	}
 
@@ -402,5 +520,13 @@ fn get_resource_id_from_message(message: &StreamRunnerDataForWorkspaceServerMsg)
 		DeploymentCreated { deployment, .. } => deployment.id,
 		DeploymentUpdated { deployment, .. } => deployment.id,
 		DeploymentDeleted { id } => *id,
+		DeploymentRestarted { id } => *id,
+		DeploymentExecStarted { id, .. } => *id,
+		DeploymentExecInputReceived { id, .. } => *id,
+		DeploymentExecResized { id, .. } => *id,
+		DeploymentExecClosed { id, .. } => *id,
+		DeploymentPortForwardStarted { id, .. } => *id,
+		DeploymentPortForwardDataReceived { id, .. } => *id,
+		DeploymentPortForwardClosed { id, .. } => *id,
 	}
 }
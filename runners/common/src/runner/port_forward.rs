@@ -0,0 +1,97 @@
+use futures::future;
+use models::api::workspace::runner::*;
+use tokio::{sync::mpsc::unbounded_channel, task};
+
+use crate::prelude::*;
+
+impl<E> super::Runner<E>
+where
+	E: RunnerExecutor + Clone + 'static,
+{
+	/// Handle a message from the server about a forwarded port of a
+	/// deployment. Messages that aren't about forwarded ports are given back,
+	/// so that the resource they're about can be reconciled.
+	pub(super) fn handle_port_forward_message(
+		&mut self,
+		msg: StreamRunnerDataForWorkspaceServerMsg,
+	) -> Option<StreamRunnerDataForWorkspaceServerMsg> {
+		match msg {
+			StreamRunnerDataForWorkspaceServerMsg::DeploymentPortForwardStarted {
+				id,
+				session_id,
+				port,
+			} => {
+				self.start_port_forward_session(id, session_id, port);
+			}
+			StreamRunnerDataForWorkspaceServerMsg::DeploymentPortForwardDataReceived {
+				id: _,
+				session_id,
+				data,
+			} => {
+				if let Some(session) = self.port_forward_sessions.get(&session_id) {
+					_ = session.send(data.into_vec());
+				}
+			}
+			StreamRunnerDataForWorkspaceServerMsg::DeploymentPortForwardClosed {
+				id: _,
+				session_id,
+			} => {
+				info!("Closing forwarded port session `{}`", session_id);
+				// Dropping the sender ends the input of the session, which
+				// closes the connection to the container
+				self.port_forward_sessions.remove(&session_id);
+			}
+			msg => return Some(msg),
+		}
+
+		None
+	}
+
+	/// Connect to a port of a deployment for a user. The connection is relayed
+	/// in the background, and whatever the container sends is reported to the
+	/// server until either side closes the connection.
+	fn start_port_forward_session(&mut self, deployment_id: Uuid, session_id: Uuid, port: u16) {
+		info!(
+			"Forwarding port {} of deployment `{}` for session `{}`",
+			port, deployment_id, session_id
+		);
+
+		let (input_sender, input) = unbounded_channel();
+		let (output, mut output_receiver) = unbounded_channel::<Vec<u8>>();
+		self.port_forward_sessions.insert(session_id, input_sender);
+
+		let session = self
+			.executor
+			.forward_port(deployment_id, port, input, output);
+		let session_reporter = self.session_reporter.clone();
+
+		task::spawn(async move {
+			// The executor drops the output once the connection is closed,
+			// which ends the relay
+			let relay_output = async {
+				while let Some(data) = output_receiver.recv().await {
+					_ = session_reporter.send(
+						StreamRunnerDataForWorkspaceClientMsg::DeploymentPortForwardDataSent {
+							id: deployment_id,
+							session_id,
+							data: data.into(),
+						},
+					);
+				}
+			};
+			let (result, ()) = future::join(session, relay_output).await;
+
+			_ = session_reporter.send(
+				StreamRunnerDataForWorkspaceClientMsg::DeploymentPortForwardEnded {
+					id: deployment_id,
+					session_id,
+					error: result
+						.inspect_err(|error| {
+							error!("Forwarded port session `{}` failed: {}", session_id, error)
+						})
+						.err(),
+				},
+			);
+		});
+	}
+}
@@ -1,7 +1,7 @@
 /// The data that is stored inside the access token, which will be encoded as a
 /// JWT.
 pub mod access_token_data;
-/// The client for the Patr API to get runner data for a given workspace.
+/// The client used by a managed runner to make requests to the Patr API.
 pub mod client;
 /// The configuration for the runner.
 pub mod config;
@@ -5,82 +5,165 @@
 //! incoming WebSocket connections from the Patr API. The runner is responsible
 //! for creating, updating, and deleting deployments in the given runner.
 
-use std::{collections::HashMap, time::Duration};
+use std::{
+	collections::{BTreeMap, HashMap},
+	future::Future,
+	time::{Duration, Instant},
+};
 
 use bollard::{
+	auth::DockerCredentials,
 	container::{
 		Config,
 		CreateContainerOptions,
 		ListContainersOptions,
 		RemoveContainerOptions,
+		RenameContainerOptions,
 		StopContainerOptions,
 	},
+	exec::{CreateExecOptions, ResizeExecOptions, StartExecResults},
 	image::CreateImageOptions,
-	secret::CreateImageInfo,
+	secret::{ContainerSummary, CreateImageInfo, EventMessage, HealthStatusEnum},
+	system::EventsOptions,
 	Docker,
 };
 use common::prelude::*;
 use futures::{Stream, StreamExt};
-use models::api::workspace::deployment::*;
+use models::api::workspace::deployment::{
+	rollout::DeploymentRolloutImage,
+	runtime::{DeploymentContainerEvent, DeploymentContainerEventKind, DeploymentReplicaCounts},
+	*,
+};
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tokio::{
+	io::{AsyncReadExt, AsyncWriteExt},
+	net::TcpStream,
+	sync::{
+		broadcast,
+		mpsc::{UnboundedReceiver, UnboundedSender},
+	},
+};
+use tokio_stream::wrappers::BroadcastStream;
+
+/// The label that containers are tagged with the ID of their deployment in
+const DEPLOYMENT_ID_LABEL: &str = "patr.deploymentId";
+/// The label that the canary of a deployment is tagged with
+const ROLLOUT_TRACK_LABEL: &str = "patr.rolloutTrack";
+/// The value of [`ROLLOUT_TRACK_LABEL`] for the canary of a deployment
+const CANARY_TRACK: &str = "canary";
+/// How long a container without a health check has to stay up before it's
+/// considered ready to replace an old container
+const CONTAINER_SETTLE_TIME: Duration = Duration::from_secs(10);
+/// How long to wait for a container to be ready to replace an old container
+/// before giving up on it
+const CONTAINER_READY_TIMEOUT: Duration = Duration::from_secs(120);
 
 /// The configuration for the runner.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct DockerSettings {}
+pub struct DockerSettings {
+	/// The pull-through cache of the Patr registry that images of external
+	/// registries are pulled through, if any
+	#[serde(default, alias = "pullthroughcache")]
+	pub pull_through_cache: Option<PullThroughCacheSettings>,
+}
+
+/// The configuration for pulling images through the pull-through cache of the
+/// Patr registry, instead of pulling them from their registry directly.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullThroughCacheSettings {
+	/// The address of the cache, including the workspace it belongs to (eg:
+	/// `registry.patr.cloud/<workspace ID>/_cache`)
+	pub registry: String,
+	/// The registries (eg: `docker.io`) that the cache is configured to proxy.
+	/// Images of any other registry are pulled directly
+	pub upstreams: Vec<String>,
+	/// The username to log in to the Patr registry with
+	#[serde(default)]
+	pub username: Option<String>,
+	/// The password (or API token) to log in to the Patr registry with
+	#[serde(default)]
+	pub password: Option<String>,
+}
+
+/// The platform of the machine that the runner is running on, in the format
+/// used by image indexes (eg: `linux/arm64`). Images pushed as an image index
+/// are pulled for this platform, so that a runner on a Raspberry Pi gets the
+/// `arm64` image and a runner on a server gets the `amd64` one.
+fn get_host_platform() -> String {
+	let architecture = match std::env::consts::ARCH {
+		"x86_64" => "amd64",
+		"x86" => "386",
+		"aarch64" => "arm64",
+		"powerpc64" => "ppc64le",
+		architecture => architecture,
+	};
+	format!("{}/{}", std::env::consts::OS, architecture)
+}
 
 /// A Patr runner that uses Docker to run deployments.
 #[derive(Debug, Clone)]
 struct DockerRunner {
 	/// The [`Docker`] client.
 	docker: Docker,
+	/// The pull-through cache to pull images of external registries through
+	pull_through_cache: Option<PullThroughCacheSettings>,
+	/// The sender for the events of deployments that aren't reported by the
+	/// Docker daemon, like images being pulled
+	events: broadcast::Sender<(Uuid, DeploymentContainerEvent)>,
 }
 
-impl RunnerExecutor for DockerRunner {
-	type Settings = DockerSettings;
-
-	const RUNNER_INTERNAL_NAME: &'static str = env!("CARGO_CRATE_NAME");
+impl DockerRunner {
+	/// Get the image to run for a deployment, along with the credentials to
+	/// pull it with. Images of registries that are proxied by the pull-through
+	/// cache are pulled from the cache instead.
+	fn get_image(
+		&self,
+		registry: &DeploymentRegistry,
+		image_tag: &str,
+		current_live_digest: Option<&str>,
+	) -> (String, Option<DockerCredentials>) {
+		let reference = if let Some(digest) = current_live_digest {
+			format!("@{}", digest)
+		} else {
+			format!(":{}", image_tag)
+		};
+		let registry_url = registry.registry_url();
+		let image_name = registry.image_name().unwrap();
 
-	async fn create(_: &RunnerSettings<Self::Settings>) -> Self {
-		let docker = Docker::connect_with_local_defaults().unwrap();
-		Self { docker }
+		match &self.pull_through_cache {
+			Some(cache) if cache.upstreams.contains(&registry_url) => (
+				format!(
+					"{}/{}/{}{}",
+					cache.registry, registry_url, image_name, reference
+				),
+				Some(DockerCredentials {
+					username: cache.username.clone(),
+					password: cache.password.clone(),
+					serveraddress: cache.registry.split('/').next().map(String::from),
+					..Default::default()
+				}),
+			),
+			_ => (
+				format!("{}/{}{}", registry_url, image_name, reference),
+				None,
+			),
+		}
 	}
 
-	#[allow(unused_variables)]
-	async fn upsert_deployment(
+	/// List all the containers of a deployment, including its canary
+	async fn list_deployment_containers(
 		&self,
-		WithId {
-			id,
-			data:
-				Deployment {
-					name,
-					registry,
-					image_tag,
-					status,
-					runner: _,
-					machine_type,
-					current_live_digest,
-				},
-		}: WithId<Deployment>,
-		DeploymentRunningDetails {
-			deploy_on_push: _,
-			min_horizontal_scale: _,
-			max_horizontal_scale: _,
-			ports,
-			environment_variables,
-			startup_probe,
-			liveness_probe,
-			config_mounts,
-			volumes,
-		}: DeploymentRunningDetails,
-	) -> Result<(), Duration> {
-		// Check if the container exists, first.
-		let container = self
-			.docker
+		id: Uuid,
+	) -> Result<Vec<ContainerSummary>, Duration> {
+		self.docker
 			.list_containers(Some(ListContainersOptions {
+				all: true,
 				filters: HashMap::from([(
 					String::from("label"),
-					vec![format!("patr.deploymentId={}", id)],
+					vec![format!("{}={}", DEPLOYMENT_ID_LABEL, id)],
 				)]),
 				..Default::default()
 			}))
@@ -88,54 +171,31 @@ impl RunnerExecutor for DockerRunner {
 			.map_err(|err| {
 				error!("Error listing containers: {:?}", err);
 				Duration::from_secs(5)
-			})?
-			.into_iter()
-			.next();
-
-		if let Some(container) = container {
-			self.docker
-				.stop_container(
-					container.id.as_deref().unwrap(),
-					Some(StopContainerOptions { t: 30 }),
-				)
-				.await
-				.map_err(|err| {
-					error!("Error stopping container: {:?}", err);
-					Duration::from_secs(5)
-				})?;
-			self.docker
-				.remove_container(
-					container.id.as_deref().unwrap_or_default(),
-					Some(RemoveContainerOptions {
-						force: true,
-						v: false,
-						..Default::default()
-					}),
-				)
-				.await
-				.map_err(|err| {
-					error!("Error removing container: {:?}", err);
-					Duration::from_secs(5)
-				})?;
-		}
+			})
+	}
 
+	/// Pull the latest version of an image for a container of a deployment.
+	/// Failing to pull the image isn't an error, since the image might already
+	/// be available locally. The deployment gets an event if a newer image was
+	/// pulled, or if the image couldn't be pulled
+	async fn pull_image(
+		&self,
+		id: Uuid,
+		container: &str,
+		image: &str,
+		credentials: Option<DockerCredentials>,
+	) {
 		info!("Pulling latest image...");
+		let mut event = None;
+		let platform = get_host_platform();
 		let mut pull_image = self.docker.create_image(
 			Some(CreateImageOptions {
-				from_image: format!(
-					"{}/{}{}",
-					registry.registry_url(),
-					registry.image_name().unwrap(),
-					if let Some(ref digest) = current_live_digest {
-						format!("@{}", digest)
-					} else {
-						format!(":{}", image_tag)
-					}
-				),
+				from_image: image,
+				platform: &platform,
 				..Default::default()
 			}),
 			None,
-			None,
+			credentials,
 		);
 		while let Some(result) = pull_image.next().await {
 			match result {
@@ -144,75 +204,53 @@ impl RunnerExecutor for DockerRunner {
 					..
 				}) => {
 					trace!("Image pull status: {}", status);
+					if status.contains("Downloaded newer image") {
+						event =
+							Some((DeploymentContainerEventKind::ImagePulled, image.to_string()));
+					}
+				}
+				Err(err) => {
+					warn!("Unable to pull image: {}", err);
+					event = Some((
+						DeploymentContainerEventKind::ImagePullFailed,
+						err.to_string(),
+					));
 				}
-				Err(err) => warn!("Unable to pull image: {}", err),
 				_ => (),
 			}
 		}
 		info!("Image updated");
 
+		if let Some((kind, message)) = event {
+			// There's no one to send the event to if the events of
+			// deployments aren't being watched
+			_ = self.events.send((
+				id,
+				DeploymentContainerEvent {
+					container: container.to_string(),
+					kind,
+					message: Some(message),
+					timestamp: OffsetDateTime::now_utc(),
+				},
+			));
+		}
+	}
+
+	/// Create a container with the given name and start it. Returns the ID of
+	/// the container
+	async fn create_and_start_container(
+		&self,
+		name: &str,
+		config: Config<String>,
+	) -> Result<String, Duration> {
 		let container = self
 			.docker
 			.create_container(
 				Some(CreateContainerOptions {
-					name: name.clone(),
-					..Default::default()
+					name,
+					platform: Some(&get_host_platform()),
 				}),
-				Config {
-					hostname: Some(format!("{}.onpatr.cloud", id)),
-					image: Some(format!(
-						"{}/{}{}",
-						registry.registry_url(),
-						registry.image_name().unwrap(),
-						if let Some(digest) = current_live_digest {
-							format!("@{}", digest)
-						} else {
-							format!(":{}", image_tag)
-						}
-					)),
-					exposed_ports: Some(
-						ports
-							.into_iter()
-							.map(|(port, port_type)| {
-								{
-									(
-										format!(
-											"{}/{}",
-											port,
-											match port_type {
-												ExposedPortType::Tcp => "tcp",
-												ExposedPortType::Udp => "udp",
-												ExposedPortType::Http => "tcp",
-											}
-										),
-										HashMap::<(), ()>::new(),
-									)
-								}
-							})
-							.collect(),
-					),
-					env: Some(
-						environment_variables
-							.into_iter()
-							.map(|(key, value)| {
-								format!(
-									"{}={}",
-									key,
-									match value {
-										EnvironmentVariableValue::String(value) => value,
-										EnvironmentVariableValue::Secret { from_secret: _ } =>
-											todo!(),
-									}
-								)
-							})
-							.collect::<Vec<_>>(),
-					),
-					labels: Some(HashMap::from([(
-						String::from("patr.deploymentId"),
-						id.to_string(),
-					)])),
-					..Default::default()
-				},
+				config,
 			)
 			.await
 			.map_err(|err| {
@@ -230,11 +268,498 @@ impl RunnerExecutor for DockerRunner {
 			})?;
 		info!("Container started");
 
+		Ok(container.id)
+	}
+
+	/// Stop a container (giving it some time to shut down gracefully) and
+	/// remove it
+	async fn remove_container(&self, container_id: &str) -> Result<(), Duration> {
+		self.docker
+			.stop_container(container_id, Some(StopContainerOptions { t: 30 }))
+			.await
+			.map_err(|err| {
+				error!("Error stopping container: {:?}", err);
+				Duration::from_secs(5)
+			})?;
+		self.docker
+			.remove_container(
+				container_id,
+				Some(RemoveContainerOptions {
+					force: true,
+					v: false,
+					..Default::default()
+				}),
+			)
+			.await
+			.map_err(|err| {
+				error!("Error removing container: {:?}", err);
+				Duration::from_secs(5)
+			})?;
+
+		Ok(())
+	}
+
+	/// Wait until a newly started container is ready to take over from the
+	/// containers it replaces. A container with a health check is ready once it
+	/// is healthy, and a container without one is ready once it has stayed up
+	/// for a while.
+	async fn wait_until_ready(&self, container_id: &str) -> Result<(), Duration> {
+		let started = Instant::now();
+		loop {
+			let state = self
+				.docker
+				.inspect_container(container_id, None)
+				.await
+				.map_err(|err| {
+					error!("Error inspecting container: {:?}", err);
+					Duration::from_secs(5)
+				})?
+				.state
+				.unwrap_or_default();
+
+			if !state.running.unwrap_or(false) {
+				error!("Container exited before it was ready");
+				return Err(Duration::from_secs(5));
+			}
+
+			match state.health.and_then(|health| health.status) {
+				Some(HealthStatusEnum::HEALTHY) => return Ok(()),
+				Some(HealthStatusEnum::UNHEALTHY) => {
+					error!("Container is unhealthy");
+					return Err(Duration::from_secs(5));
+				}
+				Some(HealthStatusEnum::STARTING) => (),
+				_ if started.elapsed() >= CONTAINER_SETTLE_TIME => return Ok(()),
+				_ => (),
+			}
+
+			if started.elapsed() >= CONTAINER_READY_TIMEOUT {
+				error!("Container did not become ready in time");
+				return Err(Duration::from_secs(5));
+			}
+			tokio::time::sleep(Duration::from_secs(1)).await;
+		}
+	}
+
+	/// Replace the given containers with a new container, using the given
+	/// rollout strategy. The new container is given the name of the containers
+	/// it replaces once they are removed.
+	///
+	/// Unless the strategy allows the deployment to be unavailable, the new
+	/// container is started alongside the old ones, which are only removed
+	/// once it is ready. If it doesn't become ready, it is removed and the old
+	/// containers are kept running.
+	async fn replace_containers(
+		&self,
+		containers: Vec<ContainerSummary>,
+		name: &str,
+		config: Config<String>,
+		rollout_strategy: DeploymentRolloutStrategy,
+	) -> Result<(), Duration> {
+		let container_ids = containers
+			.into_iter()
+			.filter_map(|container| container.id)
+			.collect::<Vec<_>>();
+
+		// Each deployment runs a single container, so a rolling update that
+		// can't start any extra containers has to stop the old container first.
+		// That is only done if the container is allowed to be unavailable, and
+		// an extra container is started otherwise
+		let stop_first = match rollout_strategy {
+			DeploymentRolloutStrategy::Recreate => true,
+			DeploymentRolloutStrategy::Rolling {
+				max_surge,
+				max_unavailable,
+			} => max_surge == 0 && max_unavailable > 0,
+			DeploymentRolloutStrategy::BlueGreen | DeploymentRolloutStrategy::Canary { .. } => {
+				false
+			}
+		};
+
+		if stop_first || container_ids.is_empty() {
+			for container_id in &container_ids {
+				self.remove_container(container_id).await?;
+			}
+			self.create_and_start_container(name, config).await?;
+
+			return Ok(());
+		}
+
+		// Container names are unique, so the new container is started with a
+		// temporary name, and renamed once the old containers are removed
+		let new_container_id = self
+			.create_and_start_container(&format!("{}-{}", name, Uuid::new_v4()), config)
+			.await?;
+
+		if let Err(wait_time) = self.wait_until_ready(&new_container_id).await {
+			// Keep the old containers running, since the new one isn't able to
+			// take over from them
+			self.remove_container(&new_container_id).await?;
+			return Err(wait_time);
+		}
+
+		for container_id in &container_ids {
+			self.remove_container(container_id).await?;
+		}
+
+		self.docker
+			.rename_container(&new_container_id, RenameContainerOptions { name })
+			.await
+			.map_err(|err| {
+				error!("Error renaming container: {:?}", err);
+				Duration::from_secs(5)
+			})?;
+
+		Ok(())
+	}
+}
+
+/// Check if a container is the canary of a deployment
+fn is_canary(container: &ContainerSummary) -> bool {
+	container
+		.labels
+		.as_ref()
+		.and_then(|labels| labels.get(ROLLOUT_TRACK_LABEL))
+		.is_some_and(|track| track == CANARY_TRACK)
+}
+
+/// Check if a container is running and ready to serve requests. A container
+/// with a health check is only ready once it's healthy
+fn is_ready(container: &ContainerSummary) -> bool {
+	let status = container.status.as_deref().unwrap_or_default();
+	container.state.as_deref() == Some("running") &&
+		!status.contains("(health: starting)") &&
+		!status.contains("(unhealthy)")
+}
+
+/// Convert an event from the Docker daemon about a container of a deployment
+/// into an event of that deployment. Events that aren't relevant to the
+/// deployment (like a container being created) are ignored
+fn get_container_event(event: EventMessage) -> Option<(Uuid, DeploymentContainerEvent)> {
+	let attributes = event.actor?.attributes?;
+	let deployment_id = attributes
+		.get(DEPLOYMENT_ID_LABEL)
+		.and_then(|value| Uuid::parse_str(value).ok())?;
+
+	let (kind, message) = match event.action?.as_str() {
+		"start" => (DeploymentContainerEventKind::Started, None),
+		"stop" => (DeploymentContainerEventKind::Stopped, None),
+		"oom" => (DeploymentContainerEventKind::OutOfMemory, None),
+		"health_status: unhealthy" => (DeploymentContainerEventKind::Unhealthy, None),
+		"die" => {
+			let exit_code = attributes
+				.get("exitCode")
+				.map(String::as_str)
+				.unwrap_or_default();
+			// A container that is stopped exits with 143 (SIGTERM), which is
+			// reported as a stop instead
+			if matches!(exit_code, "0" | "143") {
+				return None;
+			}
+			(
+				DeploymentContainerEventKind::Crashed,
+				Some(format!("Exited with code {}", exit_code)),
+			)
+		}
+		_ => return None,
+	};
+
+	let timestamp = event
+		.time_nano
+		.and_then(|nanos| OffsetDateTime::from_unix_timestamp_nanos(nanos.into()).ok())
+		.or_else(|| {
+			event
+				.time
+				.and_then(|secs| OffsetDateTime::from_unix_timestamp(secs).ok())
+		})
+		.unwrap_or_else(OffsetDateTime::now_utc);
+
+	Some((
+		deployment_id,
+		DeploymentContainerEvent {
+			container: attributes.get("name").cloned().unwrap_or_default(),
+			kind,
+			message,
+			timestamp,
+		},
+	))
+}
+
+/// Get the configuration of a container of a deployment that runs the given
+/// image
+fn get_container_config(
+	id: Uuid,
+	image: String,
+	ports: &BTreeMap<StringifiedU16, ExposedPortType>,
+	environment_variables: &BTreeMap<String, EnvironmentVariableValue>,
+	canary: bool,
+) -> Config<String> {
+	Config {
+		hostname: Some(format!("{}.onpatr.cloud", id)),
+		image: Some(image),
+		exposed_ports: Some(
+			ports
+				.iter()
+				.map(|(port, port_type)| {
+					{
+						(
+							format!(
+								"{}/{}",
+								port,
+								match port_type {
+									ExposedPortType::Tcp => "tcp",
+									ExposedPortType::Udp => "udp",
+									ExposedPortType::Http => "tcp",
+								}
+							),
+							HashMap::<(), ()>::new(),
+						)
+					}
+				})
+				.collect(),
+		),
+		env: Some(
+			environment_variables
+				.iter()
+				.map(|(key, value)| {
+					format!(
+						"{}={}",
+						key,
+						match value {
+							EnvironmentVariableValue::String(value) => value,
+							EnvironmentVariableValue::Secret { from_secret: _ } => todo!(),
+						}
+					)
+				})
+				.collect::<Vec<_>>(),
+		),
+		labels: Some(
+			[(DEPLOYMENT_ID_LABEL.to_string(), id.to_string())]
+				.into_iter()
+				.chain(canary.then(|| (ROLLOUT_TRACK_LABEL.to_string(), CANARY_TRACK.to_string())))
+				.collect(),
+		),
+		..Default::default()
+	}
+}
+
+impl RunnerExecutor for DockerRunner {
+	type Settings = DockerSettings;
+
+	const RUNNER_INTERNAL_NAME: &'static str = env!("CARGO_CRATE_NAME");
+
+	async fn create(settings: &RunnerSettings<Self::Settings>) -> Self {
+		let docker = Docker::connect_with_local_defaults().unwrap();
+		Self {
+			docker,
+			pull_through_cache: settings.data.pull_through_cache.clone(),
+			events: broadcast::channel(100).0,
+		}
+	}
+
+	#[allow(unused_variables)]
+	async fn upsert_deployment(
+		&self,
+		WithId {
+			id,
+			data:
+				Deployment {
+					name,
+					registry,
+					image_tag,
+					status,
+					runner: _,
+					machine_type,
+					current_live_digest,
+				},
+		}: WithId<Deployment>,
+		DeploymentRunningDetails {
+			deploy_on_push: _,
+			min_horizontal_scale: _,
+			max_horizontal_scale: _,
+			ports,
+			environment_variables,
+			startup_probe,
+			liveness_probe,
+			config_mounts,
+			volumes,
+			rollout_strategy,
+		}: DeploymentRunningDetails,
+		stable_image: Option<DeploymentRolloutImage>,
+	) -> Result<(), Duration> {
+		let (canaries, containers): (Vec<_>, Vec<_>) = self
+			.list_deployment_containers(id)
+			.await?
+			.into_iter()
+			.partition(is_canary);
+
+		let (image, credentials) =
+			self.get_image(&registry, &image_tag, current_live_digest.as_deref());
+
+		let Some(stable_image) = stable_image else {
+			self.pull_image(id, &name, &image, credentials).await;
+			self.replace_containers(
+				containers,
+				&name,
+				get_container_config(id, image, &ports, &environment_variables, false),
+				rollout_strategy,
+			)
+			.await?;
+
+			for canary in canaries {
+				self.remove_container(canary.id.as_deref().unwrap_or_default())
+					.await?;
+				info!("Canary removed");
+			}
+
+			return Ok(());
+		};
+
+		// While the canary is awaiting promotion, the primary container keeps
+		// running the stable image, and the new image only runs on the canary.
+		// There's no load balancer in front of the containers, so the canary
+		// percentage doesn't apply here, and a single canary is run instead
+		let (stable, stable_credentials) = self.get_image(
+			&registry,
+			&stable_image.image_tag,
+			stable_image.digest.as_deref(),
+		);
+		self.pull_image(id, &name, &stable, stable_credentials)
+			.await;
+		self.replace_containers(
+			containers,
+			&name,
+			get_container_config(id, stable, &ports, &environment_variables, false),
+			DeploymentRolloutStrategy::default(),
+		)
+		.await?;
+
+		let canary_name = format!("{}-canary", name);
+		self.pull_image(id, &canary_name, &image, credentials).await;
+		self.replace_containers(
+			canaries,
+			&canary_name,
+			get_container_config(id, image, &ports, &environment_variables, true),
+			DeploymentRolloutStrategy::Recreate,
+		)
+		.await?;
+		info!("Canary started");
+
 		Ok(())
 	}
 
+	async fn restart_deployment(&self, id: Uuid) -> Result<(), Duration> {
+		let containers = self.list_deployment_containers(id).await?;
+
+		// Each container is replaced by a copy of itself, which has to be
+		// ready before the old container is removed, so that the deployment
+		// always has a container running
+		for container in containers {
+			let Some(container_id) = container.id.as_deref() else {
+				continue;
+			};
+
+			let details = self
+				.docker
+				.inspect_container(container_id, None)
+				.await
+				.map_err(|err| {
+					error!("Error inspecting container: {:?}", err);
+					Duration::from_secs(5)
+				})?;
+			let Some(config) = details.config else {
+				continue;
+			};
+			let name = details
+				.name
+				.unwrap_or_default()
+				.trim_start_matches('/')
+				.to_string();
+
+			self.replace_containers(
+				vec![container],
+				&name,
+				Config {
+					host_config: details.host_config,
+					..Config::from(config)
+				},
+				DeploymentRolloutStrategy::default(),
+			)
+			.await?;
+			info!("Container `{}` restarted", name);
+		}
+
+		Ok(())
+	}
+
+	async fn get_deployment_status(
+		&self,
+		id: Uuid,
+	) -> Result<(DeploymentStatus, DeploymentReplicaCounts), Duration> {
+		let containers = self.list_deployment_containers(id).await?;
+
+		// Every container of the deployment is created from its latest
+		// configuration when it's upserted, so all of them are up to date
+		let to_count = |count: usize| u16::try_from(count).unwrap_or(u16::MAX);
+		let replicas = DeploymentReplicaCounts {
+			desired: to_count(containers.len().max(1)),
+			ready: to_count(
+				containers
+					.iter()
+					.filter(|container| is_ready(container))
+					.count(),
+			),
+			updated: to_count(containers.len()),
+		};
+		let exited = containers
+			.iter()
+			.any(|container| container.state.as_deref() == Some("exited"));
+
+		let status = if replicas.ready == 0 && exited {
+			DeploymentStatus::Errored
+		} else if replicas.is_rolled_out() {
+			DeploymentStatus::Running
+		} else {
+			DeploymentStatus::Deploying
+		};
+
+		Ok((status, replicas))
+	}
+
+	async fn watch_container_events<'a>(
+		&self,
+	) -> impl Stream<Item = (Uuid, DeploymentContainerEvent)> + 'a {
+		let container_events = self
+			.docker
+			.events(Some(EventsOptions {
+				filters: HashMap::from([
+					(String::from("type"), vec![String::from("container")]),
+					(
+						String::from("label"),
+						vec![String::from(DEPLOYMENT_ID_LABEL)],
+					),
+				]),
+				..Default::default()
+			}))
+			.filter_map(|event| async move {
+				match event {
+					Ok(event) => get_container_event(event),
+					Err(err) => {
+						error!("Error watching container events: {:?}", err);
+						None
+					}
+				}
+			});
+
+		futures::stream::select(
+			container_events,
+			BroadcastStream::new(self.events.subscribe())
+				.filter_map(|event| async move { event.ok() }),
+		)
+	}
+
 	async fn list_running_deployments<'a>(&self) -> impl Stream<Item = Uuid> + 'a {
-		let Ok(mut containers) = self
+		let Ok(containers) = self
 			.docker
 			.list_containers(Some(ListContainersOptions::<String> {
 				filters: HashMap::new(),
@@ -248,51 +773,26 @@ impl RunnerExecutor for DockerRunner {
 		else {
 			return futures::stream::empty().boxed();
 		};
-		containers.sort_by(|a, b| {
-			let a = a.labels.as_ref().and_then(|labels| {
-				labels
-					.get("patr.deploymentId")
-					.and_then(|value| Uuid::parse_str(value).ok())
-			});
-			let b = b.labels.as_ref().and_then(|labels| {
-				labels
-					.get("patr.deploymentId")
+		// A deployment can have more than one container (like a canary), so
+		// the IDs are deduplicated
+		let mut deployments = containers
+			.into_iter()
+			.filter_map(|container| {
+				container
+					.labels
+					.unwrap_or_default()
+					.get(DEPLOYMENT_ID_LABEL)
 					.and_then(|value| Uuid::parse_str(value).ok())
-			});
-
-			a.cmp(&b)
-		});
+			})
+			.collect::<Vec<_>>();
+		deployments.sort();
+		deployments.dedup();
 
-		futures::stream::iter(containers.into_iter().filter_map(|container| {
-			container
-				.labels
-				.unwrap_or_default()
-				.get("patr.deploymentId")
-				.and_then(|value| Uuid::parse_str(value).ok())
-		}))
-		.boxed()
+		futures::stream::iter(deployments).boxed()
 	}
 
 	async fn delete_deployment(&self, id: Uuid) -> Result<(), Duration> {
-		// Check if the container exists, first.
-		let container = self
-			.docker
-			.list_containers(Some(ListContainersOptions {
-				filters: HashMap::from([(
-					String::from("label"),
-					vec![format!("patr.deploymentId={}", id)],
-				)]),
-				..Default::default()
-			}))
-			.await
-			.map_err(|err| {
-				error!("Error listing containers: {:?}", err);
-				Duration::from_secs(5)
-			})?
-			.into_iter()
-			.next();
-
-		if let Some(container) = container {
+		for container in self.list_deployment_containers(id).await? {
 			self.docker
 				.remove_container(
 					container.id.as_deref().unwrap_or_default(),
@@ -310,9 +810,282 @@ impl RunnerExecutor for DockerRunner {
 		}
 		Ok(())
 	}
+
+	fn exec_deployment(
+		&self,
+		id: Uuid,
+		command: Vec<String>,
+		mut input: UnboundedReceiver<ExecInput>,
+		output: UnboundedSender<Vec<u8>>,
+	) -> impl Future<Output = Result<Option<i64>, String>> + Send + 'static {
+		let runner = self.clone();
+		async move {
+			let container = runner
+				.list_deployment_containers(id)
+				.await
+				.map_err(|_| "Failed to list the containers of the deployment".to_string())?
+				.into_iter()
+				.find(is_ready)
+				.and_then(|container| container.id)
+				.ok_or_else(|| "The deployment has no running instances".to_string())?;
+
+			let exec = runner
+				.docker
+				.create_exec(
+					&container,
+					CreateExecOptions {
+						cmd: Some(command),
+						attach_stdin: Some(true),
+						attach_stdout: Some(true),
+						attach_stderr: Some(true),
+						tty: Some(true),
+						..Default::default()
+					},
+				)
+				.await
+				.map_err(|err| err.to_string())?;
+
+			let StartExecResults::Attached {
+				output: mut stdout,
+				input: mut stdin,
+			} = runner
+				.docker
+				.start_exec(&exec.id, None)
+				.await
+				.map_err(|err| err.to_string())?
+			else {
+				return Err("Failed to attach to the terminal of the command".to_string());
+			};
+
+			loop {
+				tokio::select! {
+					data = stdout.next() => match data {
+						Some(Ok(data)) => {
+							_ = output.send(data.into_bytes().to_vec());
+						}
+						Some(Err(err)) => return Err(err.to_string()),
+						// The output is closed once the command exits
+						None => break,
+					},
+					data = input.recv() => match data {
+						Some(ExecInput::Data(data)) => {
+							stdin.write_all(&data).await.map_err(|err| err.to_string())?;
+						}
+						Some(ExecInput::Resize { rows, cols }) => {
+							_ = runner
+								.docker
+								.resize_exec(
+									&exec.id,
+									ResizeExecOptions {
+										height: rows,
+										width: cols,
+									},
+								)
+								.await
+								.inspect_err(|err| error!("Error resizing terminal: {:?}", err));
+						}
+						// The user has closed the session. Closing the
+						// connection closes the terminal, which hangs up the
+						// command
+						None => return Ok(None),
+					},
+				}
+			}
+
+			let exit_code = runner
+				.docker
+				.inspect_exec(&exec.id)
+				.await
+				.map_err(|err| err.to_string())?
+				.exit_code;
+
+			Ok(exit_code)
+		}
+	}
+
+	fn forward_port(
+		&self,
+		id: Uuid,
+		port: u16,
+		mut input: UnboundedReceiver<Vec<u8>>,
+		output: UnboundedSender<Vec<u8>>,
+	) -> impl Future<Output = Result<(), String>> + Send + 'static {
+		let runner = self.clone();
+		async move {
+			// Containers are reachable from the host through their address on
+			// the networks they're connected to, including on the ports that
+			// aren't published
+			let address = runner
+				.list_deployment_containers(id)
+				.await
+				.map_err(|_| "Failed to list the containers of the deployment".to_string())?
+				.into_iter()
+				.filter(is_ready)
+				.find_map(|container| {
+					container
+						.network_settings?
+						.networks?
+						.into_values()
+						.find_map(|network| network.ip_address.filter(|ip| !ip.is_empty()))
+				})
+				.ok_or_else(|| "The deployment has no running instances".to_string())?;
+
+			let mut stream = TcpStream::connect((address.as_str(), port))
+				.await
+				.map_err(|err| format!("Failed to connect to port {}: {}", port, err))?;
+			let (mut reader, mut writer) = stream.split();
+
+			let mut buffer = vec![0; 4096];
+			loop {
+				tokio::select! {
+					read = reader.read(&mut buffer) => {
+						let read = read.map_err(|err| err.to_string())?;
+						// The container has closed the connection
+						if read == 0 {
+							break;
+						}
+						_ = output.send(buffer[..read].to_vec());
+					}
+					data = input.recv() => match data {
+						Some(data) => {
+							writer.write_all(&data).await.map_err(|err| err.to_string())?;
+						}
+						// The user has closed their connection
+						None => break,
+					},
+				}
+			}
+
+			_ = writer.shutdown().await;
+			Ok(())
+		}
+	}
 }
 
 #[tokio::main]
 async fn main() {
 	Runner::<DockerRunner>::run().await;
 }
+
+#[cfg(test)]
+mod tests {
+	use bollard::secret::EventActor;
+
+	use super::*;
+
+	/// Creates a container of a deployment with the given state and status
+	fn get_container(state: &str, status: &str, track: Option<&str>) -> ContainerSummary {
+		ContainerSummary {
+			state: Some(state.to_string()),
+			status: Some(status.to_string()),
+			labels: Some(
+				track
+					.map(|track| (ROLLOUT_TRACK_LABEL.to_string(), track.to_string()))
+					.into_iter()
+					.collect(),
+			),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn assert_canary_is_read_from_labels() {
+		assert!(is_canary(&get_container(
+			"running",
+			"Up 5 seconds",
+			Some(CANARY_TRACK)
+		)));
+		assert!(!is_canary(&get_container("running", "Up 5 seconds", None)));
+		assert!(!is_canary(&ContainerSummary::default()));
+	}
+
+	#[test]
+	fn assert_containers_are_ready_once_healthy() {
+		assert!(is_ready(&get_container("running", "Up 5 seconds", None)));
+		assert!(is_ready(&get_container(
+			"running",
+			"Up 5 seconds (healthy)",
+			None
+		)));
+		assert!(!is_ready(&get_container(
+			"running",
+			"Up 5 seconds (health: starting)",
+			None
+		)));
+		assert!(!is_ready(&get_container(
+			"running",
+			"Up 5 seconds (unhealthy)",
+			None
+		)));
+		assert!(!is_ready(&get_container(
+			"exited",
+			"Exited (1) 5 seconds ago",
+			None
+		)));
+	}
+
+	/// Creates an event of a container of the given deployment
+	fn get_event(deployment_id: &Uuid, action: &str, exit_code: Option<&str>) -> EventMessage {
+		let mut attributes = HashMap::from([
+			(DEPLOYMENT_ID_LABEL.to_string(), deployment_id.to_string()),
+			("name".to_string(), format!("deployment-{}", deployment_id)),
+		]);
+		if let Some(exit_code) = exit_code {
+			attributes.insert("exitCode".to_string(), exit_code.to_string());
+		}
+
+		EventMessage {
+			action: Some(action.to_string()),
+			actor: Some(EventActor {
+				attributes: Some(attributes),
+				..Default::default()
+			}),
+			time: Some(1_700_000_000),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn assert_container_events_belong_to_their_deployment() {
+		let deployment_id = Uuid::new_v4();
+
+		assert_eq!(
+			get_container_event(get_event(&deployment_id, "start", None)),
+			Some((
+				deployment_id,
+				DeploymentContainerEvent {
+					container: format!("deployment-{}", deployment_id),
+					kind: DeploymentContainerEventKind::Started,
+					message: None,
+					timestamp: OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap(),
+				}
+			))
+		);
+		assert_eq!(
+			get_container_event(EventMessage {
+				action: Some("start".to_string()),
+				..Default::default()
+			}),
+			None
+		);
+	}
+
+	#[test]
+	fn assert_only_crashes_are_reported_on_exit() {
+		let deployment_id = Uuid::new_v4();
+		let get_kind_and_message = |exit_code| {
+			get_container_event(get_event(&deployment_id, "die", Some(exit_code)))
+				.map(|(_, event)| (event.kind, event.message))
+		};
+
+		assert_eq!(get_kind_and_message("0"), None);
+		assert_eq!(get_kind_and_message("143"), None);
+		assert_eq!(
+			get_kind_and_message("1"),
+			Some((
+				DeploymentContainerEventKind::Crashed,
+				Some("Exited with code 1".to_string())
+			))
+		);
+	}
+}
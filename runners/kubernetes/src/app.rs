@@ -1,6 +1,13 @@
+use std::{collections::HashMap, sync::Mutex};
+
 use kube::Client;
-use models::prelude::*;
+use models::{api::workspace::runner::StreamRunnerDataForWorkspaceClientMsg, prelude::*};
+use patr_client::ApiClient;
 use thiserror::Error;
+use tokio::sync::mpsc::UnboundedSender;
+use url::Url;
+
+use crate::{exec::ExecInput, utils::constants};
 
 /// Represents the state of the application. This is used to share information
 /// across the entire application, such as the API token, the region ID, etc.
@@ -13,13 +20,27 @@ pub struct AppState {
 	pub workspace_id: Uuid,
 	/// The kubernetes client used to communicate with the cluster.
 	pub client: Client,
+	/// The client used to communicate with the Patr API.
+	pub patr_client: ApiClient,
+	/// The sender used to report the state of deployments to the Patr API.
+	pub report_sender: UnboundedSender<StreamRunnerDataForWorkspaceClientMsg>,
+	/// The terminal sessions that are open in the pods of deployments, along
+	/// with the senders that the input of the users is sent to them through.
+	pub exec_sessions: Mutex<HashMap<Uuid, UnboundedSender<ExecInput>>>,
+	/// The connections that are open to the ports of the pods of deployments,
+	/// along with the senders that the data of the users is sent to them
+	/// through.
+	pub port_forward_sessions: Mutex<HashMap<Uuid, UnboundedSender<Vec<u8>>>>,
 }
 
 impl AppState {
 	/// Tries to create a new `AppState` from the environment variables. If the
 	/// environment variables are not set in release mode, it will panic. In
-	/// debug mode, it will use the default values.
-	pub async fn try_default() -> Self {
+	/// debug mode, it will use the default values. Reports sent using the
+	/// given sender are expected to be forwarded to the Patr API.
+	pub async fn try_default(
+		report_sender: UnboundedSender<StreamRunnerDataForWorkspaceClientMsg>,
+	) -> Self {
 		let patr_token = std::env::var("PATR_TOKEN");
 		let region_id = std::env::var("REGION_ID");
 		let workspace_id = std::env::var("WORKSPACE_ID");
@@ -69,21 +90,33 @@ impl AppState {
 			.await
 			.expect("Failed to get kubernetes client details");
 
+		let patr_client = ApiClient::builder()
+			.base_url(Url::parse(constants::API_BASE_URL).expect("invalid Patr API base URL"))
+			.build();
+
 		Self {
 			patr_token,
 			region_id,
 			workspace_id,
 			client,
+			patr_client,
+			report_sender,
+			exec_sessions: Mutex::default(),
+			port_forward_sessions: Mutex::default(),
 		}
 	}
 }
 
+/// An error that occurred while running the controller
 #[derive(Error, Debug)]
 pub enum AppError {
+	/// An error from the Kubernetes API
 	#[error("error while communicating with the Kubernetes API: {0}")]
 	Kubernetes(#[from] kube::Error),
+	/// An error returned by the Patr API
 	#[error("error while communicating with the Patr API: {0}")]
 	Patr(ErrorType),
+	/// Any other error within the controller
 	#[error("internal error: {0}")]
 	InternalError(String),
 }
@@ -6,3 +6,16 @@ pub const WORKSPACE_ID: &str = "workspaceId";
 
 /// A camelCased string containing the text "runner".
 pub const RUNNER: &str = "runner";
+
+/// The annotation on the pod template of a deployment that is bumped to do a
+/// rolling restart of the deployment.
+pub const RESTARTED_AT: &str = "patr.cloud/restartedAt";
+
+/// The label on the pods of the canary or the green deployment of a deployment,
+/// which keeps them out of the selector of the deployment itself.
+pub const ROLLOUT_TRACK: &str = "patr.cloud/rolloutTrack";
+
+/// The annotation on the Kubernetes deployment of a deployment with the hash of
+/// the pod template it was last updated with, used to tell when a blue/green
+/// rollout has to bring up a green deployment.
+pub const TEMPLATE_HASH: &str = "patr.cloud/templateHash";
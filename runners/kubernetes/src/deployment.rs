@@ -1,4 +1,9 @@
-use std::{collections::BTreeMap, str::FromStr, sync::Arc, time::Duration};
+use std::{
+	collections::BTreeMap,
+	str::FromStr,
+	sync::Arc,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use futures::{future, StreamExt};
 use k8s_openapi::{
@@ -36,7 +41,12 @@ use kube::{
 	Client,
 };
 use models::{
-	api::workspace::{container_registry::*, deployment::*, volume::*},
+	api::workspace::{
+		container_registry::*,
+		deployment::{rollout::DeploymentRolloutImage, runtime::DeploymentReplicaCounts, *},
+		runner::StreamRunnerDataForWorkspaceClientMsg,
+		volume::*,
+	},
 	prelude::*,
 };
 use sha2::{Digest, Sha512};
@@ -50,7 +60,7 @@ use tokio::{
 };
 use tokio_stream::wrappers::{BroadcastStream, UnboundedReceiverStream};
 
-use crate::{client::make_request, constants, prelude::*};
+use crate::{constants, prelude::*};
 
 /// Starts the deployment controller. This function will spawn a new task that
 /// will run the controller. This function will return a sender that can be
@@ -117,11 +127,81 @@ async fn run_controller(
 	.await;
 }
 
+/// Does a rolling restart of a deployment, by bumping an annotation on the pod
+/// template of its Kubernetes deployment (or stateful set, if it has volumes).
+/// The annotation is patched with a field manager of its own, so that it isn't
+/// removed the next time the deployment is reconciled.
+pub(super) async fn restart_deployment(
+	state: &AppState,
+	deployment_id: Uuid,
+) -> Result<(), AppError> {
+	info!("Restarting deployment with id: {}", deployment_id);
+
+	let namespace = state.workspace_id.to_string();
+	let patch = Patch::Merge(get_restart_patch(SystemTime::now()));
+	let patch_params = PatchParams {
+		field_manager: Some(format!("restart-{}", deployment_id)),
+		..PatchParams::default()
+	};
+
+	let restarted = Api::<KubeDeployment>::namespaced(state.client.clone(), &namespace)
+		.patch_opt(
+			&format!("deployment-{}", deployment_id),
+			&patch_params,
+			&patch,
+		)
+		.await?
+		.is_some();
+	if restarted {
+		return Ok(());
+	}
+
+	trace!("No deployment found. Restarting the stateful set instead");
+	Api::<StatefulSet>::namespaced(state.client.clone(), &namespace)
+		.patch_opt(&format!("sts-{}", deployment_id), &patch_params, &patch)
+		.await?
+		.ok_or_else(|| {
+			AppError::InternalError(format!(
+				"Deployment `{}` is not running in the cluster",
+				deployment_id
+			))
+		})?;
+
+	Ok(())
+}
+
+/// Get the patch that bumps the restart annotation on the pod template of a
+/// deployment to the given time, which makes Kubernetes replace its pods
+fn get_restart_patch(restarted_at: SystemTime) -> serde_json::Value {
+	let restarted_at = restarted_at
+		.duration_since(UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_secs()
+		.to_string();
+	serde_json::json!({
+		"spec": {
+			"template": {
+				"metadata": {
+					"annotations": {
+						constants::RESTARTED_AT: restarted_at,
+					},
+				},
+			},
+		},
+	})
+}
+
 /// Handles errors that occur during the reconciliation process. This function
 /// is called whenever an error occurs during the reconciliation process. This
 /// function should decide what to do with the error.
-#[instrument(skip(_ctx))]
-fn error_policy(_obj: Arc<PatrDeployment>, _err: &AppError, _ctx: Arc<AppState>) -> Action {
+#[instrument(skip(ctx))]
+fn error_policy(obj: Arc<PatrDeployment>, err: &AppError, ctx: Arc<AppState>) -> Action {
+	_ = ctx.report_sender.send(
+		StreamRunnerDataForWorkspaceClientMsg::DeploymentReconcileFailed {
+			id: obj.spec.deployment.id,
+			error: err.to_string(),
+		},
+	);
 	Action::requeue(Duration::from_secs(5))
 }
 
@@ -193,25 +273,27 @@ async fn reconcile(
 		)
 		.await?;
 
-	let machine_type = make_request(
-		ApiRequest::<ListAllDeploymentMachineTypeRequest>::builder()
-			.path(ListAllDeploymentMachineTypePath {
-				workspace_id: ctx.workspace_id,
-			})
-			.headers(ListAllDeploymentMachineTypeRequestHeaders {
-				user_agent: UserAgent::from_static("deployment-controller"),
-			})
-			.query(())
-			.body(ListAllDeploymentMachineTypeRequest)
-			.build(),
-	)
-	.await
-	.map_err(|err| err.body.error)?
-	.body
-	.machine_types
-	.into_iter()
-	.find(|machine_type| machine_type.id == spec.deployment.machine_type)
-	.ok_or_else(|| AppError::InternalError("invalid machine type".to_string()))?;
+	let machine_type = ctx
+		.patr_client
+		.make_request(
+			ApiRequest::<ListAllDeploymentMachineTypeRequest>::builder()
+				.path(ListAllDeploymentMachineTypePath {
+					workspace_id: ctx.workspace_id,
+				})
+				.headers(ListAllDeploymentMachineTypeRequestHeaders {
+					user_agent: UserAgent::from_static("deployment-controller"),
+				})
+				.query(())
+				.body(ListAllDeploymentMachineTypeRequest)
+				.build(),
+		)
+		.await
+		.map_err(|err| err.body.error)?
+		.body
+		.machine_types
+		.into_iter()
+		.find(|machine_type| machine_type.id == spec.deployment.machine_type)
+		.ok_or_else(|| AppError::InternalError("invalid machine type".to_string()))?;
 
 	trace!("Deploying deployment: {}", spec.deployment.id);
 
@@ -230,6 +312,35 @@ async fn reconcile(
 	.into_iter()
 	.collect::<BTreeMap<_, _>>();
 
+	// The pods of the canary and the green deployment don't have the runner
+	// label, so that they aren't selected by the deployment itself
+	let get_track_selector = |track: &str| {
+		[
+			(
+				constants::DEPLOYMENT_ID.to_string(),
+				spec.deployment.id.to_string(),
+			),
+			(constants::ROLLOUT_TRACK.to_string(), track.to_string()),
+		]
+		.into_iter()
+		.collect::<BTreeMap<_, _>>()
+	};
+	let get_track_labels = |track: &str| {
+		labels
+			.iter()
+			.filter(|(key, _)| *key != constants::RUNNER)
+			.map(|(key, value)| (key.clone(), value.clone()))
+			.chain(get_track_selector(track))
+			.collect::<BTreeMap<_, _>>()
+	};
+	let get_selector = |keys: &[&str]| {
+		labels
+			.iter()
+			.filter(|(key, _)| keys.contains(&key.as_str()))
+			.map(|(key, value)| (key.clone(), value.clone()))
+			.collect::<BTreeMap<_, _>>()
+	};
+
 	trace!("generating deployment configuration");
 
 	if !spec.running_details.volumes.is_empty() {
@@ -259,7 +370,7 @@ async fn reconcile(
 		volumes.push(Volume {
 			name: "config-mounts".to_string(),
 			config_map: Some(ConfigMapVolumeSource {
-				name: Some(format!("config-mount-{}", spec.deployment.id)),
+				name: format!("config-mount-{}", spec.deployment.id),
 				items: Some(
 					spec.running_details
 						.config_mounts
@@ -278,26 +389,28 @@ async fn reconcile(
 	}
 
 	for (&volume_id, mount_path) in &spec.running_details.volumes {
-		let volume = make_request(
-			ApiRequest::<GetVolumeInfoRequest>::builder()
-				.path(GetVolumeInfoPath {
-					workspace_id: ctx.workspace_id,
-					volume_id,
-				})
-				.headers(GetVolumeInfoRequestHeaders {
-					authorization: BearerToken::from_str(&ctx.patr_token).map_err(|err| {
-						ErrorType::server_error(format!("invalid patr token. Error: `{}`", err))
-					})?,
-					user_agent: UserAgent::from_static("deployment-controller"),
-				})
-				.query(())
-				.body(GetVolumeInfoRequest)
-				.build(),
-		)
-		.await
-		.map_err(|err| err.body.error)?
-		.body
-		.volume;
+		let volume = ctx
+			.patr_client
+			.make_request(
+				ApiRequest::<GetVolumeInfoRequest>::builder()
+					.path(GetVolumeInfoPath {
+						workspace_id: ctx.workspace_id,
+						volume_id,
+					})
+					.headers(GetVolumeInfoRequestHeaders {
+						authorization: BearerToken::from_str(&ctx.patr_token).map_err(|err| {
+							ErrorType::server_error(format!("invalid patr token. Error: `{}`", err))
+						})?,
+						user_agent: UserAgent::from_static("deployment-controller"),
+					})
+					.query(())
+					.body(GetVolumeInfoRequest)
+					.build(),
+			)
+			.await
+			.map_err(|err| err.body.error)?
+			.body
+			.volume;
 
 		volume_mounts.push(VolumeMount {
 			name: format!("pvc-{}", volume_id),
@@ -332,69 +445,38 @@ async fn reconcile(
 		});
 	}
 
-	trace!("Creating deployment service");
-
-	Api::<Service>::namespaced(ctx.client.clone(), namespace)
-		.patch(
-			&format!("service-{}", spec.deployment.id),
-			&PatchParams::apply(&format!("service-{}", spec.deployment.id)),
-			&Patch::Apply(Service {
-				metadata: ObjectMeta {
-					name: Some(format!("service-{}", spec.deployment.id)),
-					owner_references: Some(vec![owner_reference.clone()]),
-					..ObjectMeta::default()
-				},
-				spec: Some(ServiceSpec {
-					ports: Some(
-						spec.running_details
-							.ports
-							.keys()
-							.map(|port| ServicePort {
-								port: port.value() as i32,
-								target_port: Some(IntOrString::Int(port.value() as i32)),
-								name: Some(format!("port-{}", port)),
-								..ServicePort::default()
-							})
-							.collect::<Vec<_>>(),
-					),
-					selector: Some(labels.clone()),
-					cluster_ip: if spec.running_details.volumes.is_empty() {
-						None
-					} else {
-						Some("None".to_string())
-					},
-					..ServiceSpec::default()
-				}),
-				..Service::default()
-			}),
-		)
-		.await?;
-
 	let image_name = match &spec.deployment.registry {
 		DeploymentRegistry::PatrRegistry {
 			registry,
 			repository_id,
 		} => {
-			let repository = make_request(
-				ApiRequest::<GetContainerRepositoryInfoRequest>::builder()
-					.path(GetContainerRepositoryInfoPath {
-						workspace_id: Uuid::parse_str(namespace).unwrap(),
-						repository_id: *repository_id,
-					})
-					.headers(GetContainerRepositoryInfoRequestHeaders {
-						authorization: BearerToken::from_str(&ctx.patr_token).map_err(|err| {
-							ErrorType::server_error(format!("invalid patr token. Error: `{}`", err))
-						})?,
-						user_agent: UserAgent::from_static("deployment-controller"),
-					})
-					.query(())
-					.body(GetContainerRepositoryInfoRequest)
-					.build(),
-			)
-			.await
-			.map_err(|err| err.body.error)?
-			.body
-			.repository;
+			let repository = ctx
+				.patr_client
+				.make_request(
+					ApiRequest::<GetContainerRepositoryInfoRequest>::builder()
+						.path(GetContainerRepositoryInfoPath {
+							workspace_id: Uuid::parse_str(namespace).unwrap(),
+							repository_id: *repository_id,
+						})
+						.headers(GetContainerRepositoryInfoRequestHeaders {
+							authorization: BearerToken::from_str(&ctx.patr_token).map_err(
+								|err| {
+									ErrorType::server_error(format!(
+										"invalid patr token. Error: `{}`",
+										err
+									))
+								},
+							)?,
+							user_agent: UserAgent::from_static("deployment-controller"),
+						})
+						.query(())
+						.body(GetContainerRepositoryInfoRequest)
+						.build(),
+				)
+				.await
+				.map_err(|err| err.body.error)?
+				.body
+				.repository;
 
 			format!("{}/{}", registry, repository.name)
 		}
@@ -404,10 +486,28 @@ async fn reconcile(
 		} => format!("{}/{}", registry, image_name),
 	};
 
-	let image_name = if let Some(current_live_digest) = &spec.deployment.current_live_digest {
-		format!("{}@{}", image_name, current_live_digest)
-	} else {
-		format!("{}:{}", image_name, spec.deployment.image_tag)
+	let get_image = |image_tag: &str, digest: Option<&str>| {
+		if let Some(digest) = digest {
+			format!("{}@{}", image_name, digest)
+		} else {
+			format!("{}:{}", image_name, image_tag)
+		}
+	};
+	let new_image = get_image(
+		&spec.deployment.image_tag,
+		spec.deployment.current_live_digest.as_deref(),
+	);
+
+	// A stateful set can't have a canary running next to it, since the canary
+	// can't share its volumes. So stateful sets are always rolled out to the
+	// new image directly
+	let stable_image = spec
+		.stable_image
+		.as_ref()
+		.filter(|_| spec.running_details.volumes.is_empty());
+	let image_name = match stable_image {
+		Some(stable_image) => get_image(&stable_image.image_tag, stable_image.digest.as_deref()),
+		None => new_image.clone(),
 	};
 
 	let metadata = ObjectMeta {
@@ -596,7 +696,7 @@ async fn reconcile(
 					// for user clusters, need to create a separate
 					// secret for each private repo in future
 					vec![LocalObjectReference {
-						name: Some("patr-regcred".to_string()),
+						name: "patr-regcred".to_string(),
 					}]
 				}),
 				..PodSpec::default()
@@ -633,36 +733,195 @@ async fn reconcile(
 			}),
 		};
 
-	if spec.running_details.volumes.is_empty() {
-		let kubernetes_deployment = KubeDeployment {
-			metadata,
-			spec: Some(DeploymentSpec {
-				replicas,
-				selector,
-				template,
-				strategy: Some(DeploymentStrategy {
-					type_: Some("RollingUpdate".to_owned()),
-					rolling_update: Some(RollingUpdateDeployment {
-						max_surge: Some(IntOrString::Int(1)),
-						max_unavailable: Some(IntOrString::String("25%".to_owned())),
-					}),
-				}),
-				..DeploymentSpec::default()
+	let (status, replicas, service_selector) = if spec.running_details.volumes.is_empty() {
+		let deployment_api = Api::<KubeDeployment>::namespaced(ctx.client.clone(), namespace);
+		let deployment_name = format!("deployment-{}", spec.deployment.id);
+		let canary_name = format!("deployment-{}-canary", spec.deployment.id);
+		let green_name = format!("deployment-{}-green", spec.deployment.id);
+
+		// While a canary is running, it takes over a share of the pods of the
+		// deployment, so that it gets that share of the traffic
+		let (stable_replicas, canary_replicas) = match stable_image {
+			Some(_) => {
+				let (stable_replicas, canary_replicas) = get_canary_replicas(
+					spec.running_details.min_horizontal_scale,
+					spec.running_details
+						.rollout_strategy
+						.canary_percentage()
+						.unwrap_or(100),
+				);
+				(stable_replicas, Some(canary_replicas))
+			}
+			None => (spec.running_details.min_horizontal_scale, None),
+		};
+
+		let canary_template = stable_image.map(|_| {
+			let mut template = template.clone();
+			if let Some(container) = template
+				.spec
+				.as_mut()
+				.and_then(|spec| spec.containers.first_mut())
+			{
+				container.image = Some(new_image.clone());
+			}
+			if let Some(metadata) = template.metadata.as_mut() {
+				metadata.labels = Some(get_track_labels("canary"));
+			}
+			template
+		});
+
+		// The hash of the pod template tells if the deployment has been updated
+		// since its pods were last rolled out
+		let template_hash = hex::encode(Sha512::digest(
+			serde_json::to_vec(&template).unwrap_or_default(),
+		));
+		let get_template_hash = |deployment: &KubeDeployment| {
+			deployment
+				.metadata
+				.annotations
+				.as_ref()
+				.and_then(|annotations| annotations.get(constants::TEMPLATE_HASH))
+				.cloned()
+				.unwrap_or_default()
+		};
+
+		let current_deployment = deployment_api.get_opt(&deployment_name).await?;
+		let green_deployment = deployment_api.get_opt(&green_name).await?;
+		let phase = get_blue_green_phase(
+			spec.running_details.rollout_strategy,
+			current_deployment
+				.as_ref()
+				.map(get_template_hash)
+				.as_deref(),
+			&template_hash,
+			green_deployment.as_ref().map(|green| {
+				get_template_hash(green) == template_hash &&
+					get_kube_deployment_status(green).0 == DeploymentStatus::Running
 			}),
-			..KubeDeployment::default()
+			current_deployment.as_ref().is_some_and(|deployment| {
+				get_kube_deployment_status(deployment).0 == DeploymentStatus::Running
+			}),
+		);
+		let hash_annotations = Some(
+			[(constants::TEMPLATE_HASH.to_string(), template_hash.clone())]
+				.into_iter()
+				.collect::<BTreeMap<_, _>>(),
+		);
+
+		let green_status = if phase == BlueGreenPhase::Settled {
+			trace!("deleting the green deployment if there are any");
+			deployment_api
+				.delete_opt(&green_name, &DeleteParams::default())
+				.await?;
+
+			None
+		} else {
+			// The green deployment has to be able to take all the traffic of
+			// the deployment
+			let green_replicas = current_deployment
+				.as_ref()
+				.and_then(|deployment| deployment.spec.as_ref()?.replicas)
+				.unwrap_or(spec.running_details.min_horizontal_scale.into());
+			let mut template = template.clone();
+			if let Some(metadata) = template.metadata.as_mut() {
+				metadata.labels = Some(get_track_labels("green"));
+			}
+
+			trace!("creating green deployment");
+			let green_deployment = deployment_api
+				.patch(
+					&green_name,
+					&PatchParams::apply(&green_name),
+					&Patch::Apply(KubeDeployment {
+						metadata: ObjectMeta {
+							name: Some(green_name.clone()),
+							namespace: Some(namespace.to_string()),
+							labels: Some(get_track_labels("green")),
+							annotations: hash_annotations.clone(),
+							owner_references: Some(vec![owner_reference.clone()]),
+							..ObjectMeta::default()
+						},
+						spec: Some(DeploymentSpec {
+							replicas: Some(green_replicas),
+							selector: LabelSelector {
+								match_expressions: None,
+								match_labels: Some(get_track_selector("green")),
+							},
+							template,
+							..DeploymentSpec::default()
+						}),
+						..KubeDeployment::default()
+					}),
+				)
+				.await?;
+
+			Some(get_kube_deployment_status(&green_deployment).0)
 		};
 
-		// Create the deployment defined above
-		trace!("creating deployment");
-		let deployment_api = Api::<KubeDeployment>::namespaced(ctx.client.clone(), namespace);
+		// Until the green deployment is ready, the deployment keeps serving all
+		// the traffic with the pods it already has
+		let kubernetes_deployment = match (phase, current_deployment) {
+			(BlueGreenPhase::Starting, Some(current_deployment)) => current_deployment,
+			_ => {
+				trace!("creating deployment");
+				deployment_api
+					.patch(
+						&deployment_name,
+						&PatchParams::apply(&deployment_name),
+						&Patch::Apply(KubeDeployment {
+							metadata: ObjectMeta {
+								annotations: hash_annotations,
+								..metadata
+							},
+							spec: Some(DeploymentSpec {
+								replicas: Some(stable_replicas.into()),
+								selector,
+								template,
+								strategy: Some(get_deployment_strategy(
+									spec.running_details.rollout_strategy,
+								)),
+								..DeploymentSpec::default()
+							}),
+							..KubeDeployment::default()
+						}),
+					)
+					.await?
+			}
+		};
 
-		deployment_api
-			.patch(
-				&format!("deployment-{}", spec.deployment.id),
-				&PatchParams::apply(&format!("deployment-{}", spec.deployment.id)),
-				&Patch::Apply(kubernetes_deployment),
-			)
-			.await?;
+		if let (Some(template), Some(canary_replicas)) = (canary_template, canary_replicas) {
+			trace!("creating canary deployment");
+			deployment_api
+				.patch(
+					&canary_name,
+					&PatchParams::apply(&canary_name),
+					&Patch::Apply(KubeDeployment {
+						metadata: ObjectMeta {
+							name: Some(canary_name.clone()),
+							namespace: Some(namespace.to_string()),
+							labels: Some(get_track_labels("canary")),
+							owner_references: Some(vec![owner_reference.clone()]),
+							..ObjectMeta::default()
+						},
+						spec: Some(DeploymentSpec {
+							replicas: Some(canary_replicas.into()),
+							selector: LabelSelector {
+								match_expressions: None,
+								match_labels: Some(get_track_selector("canary")),
+							},
+							template,
+							..DeploymentSpec::default()
+						}),
+						..KubeDeployment::default()
+					}),
+				)
+				.await?;
+		} else {
+			trace!("deleting the canary deployment if there are any");
+			deployment_api
+				.delete_opt(&canary_name, &DeleteParams::default())
+				.await?;
+		}
 
 		// This is because if a user wanted to delete the volume from there sts
 		// then a sts will be converted to deployment
@@ -687,9 +946,9 @@ async fn reconcile(
 				scale_target_ref: CrossVersionObjectReference {
 					api_version: Some("apps/v1".to_string()),
 					kind: "Deployment".to_string(),
-					name: format!("deployment-{}", spec.deployment.id),
+					name: deployment_name.clone(),
 				},
-				min_replicas: Some(spec.running_details.min_horizontal_scale.into()),
+				min_replicas: Some(stable_replicas.into()),
 				max_replicas: spec.running_details.max_horizontal_scale.into(),
 				target_cpu_utilization_percentage: Some(80),
 			}),
@@ -707,6 +966,30 @@ async fn reconcile(
 				&Patch::Apply(kubernetes_hpa),
 			)
 			.await?;
+
+		let (status, replicas) = get_kube_deployment_status(&kubernetes_deployment);
+		// The deployment is still being deployed until the traffic is back on
+		// its own pods
+		let status = match (phase, green_status) {
+			(BlueGreenPhase::Settled, _) => status,
+			(_, Some(DeploymentStatus::Errored)) => DeploymentStatus::Errored,
+			_ => DeploymentStatus::Deploying,
+		};
+		let service_selector = match phase {
+			// The service selects the pods of the canary as well, so that it
+			// gets a share of the traffic
+			BlueGreenPhase::Settled => {
+				get_selector(&[constants::DEPLOYMENT_ID, constants::WORKSPACE_ID])
+			}
+			BlueGreenPhase::Starting => get_selector(&[
+				constants::DEPLOYMENT_ID,
+				constants::WORKSPACE_ID,
+				constants::RUNNER,
+			]),
+			BlueGreenPhase::Switched => get_track_selector("green"),
+		};
+
+		(status, replicas, service_selector)
 	} else {
 		let kubernetes_sts = StatefulSet {
 			metadata,
@@ -729,7 +1012,7 @@ async fn reconcile(
 		trace!("creating stateful set");
 		let sts_api = Api::<StatefulSet>::namespaced(ctx.client.clone(), namespace);
 
-		sts_api
+		let kubernetes_sts = sts_api
 			.patch(
 				&format!("sts-{}", spec.deployment.id),
 				&PatchParams::apply(&format!("sts-{}", spec.deployment.id)),
@@ -758,7 +1041,61 @@ async fn reconcile(
 				&DeleteParams::default(),
 			)
 			.await?;
-	}
+
+		let status = kubernetes_sts.status.unwrap_or_default();
+		let (status, replicas) = get_runtime_status(
+			&kubernetes_sts.metadata,
+			status.observed_generation,
+			kubernetes_sts.spec.and_then(|spec| spec.replicas),
+			status.ready_replicas,
+			status.updated_replicas,
+			false,
+		);
+
+		(
+			status,
+			replicas,
+			get_selector(&[constants::DEPLOYMENT_ID, constants::WORKSPACE_ID]),
+		)
+	};
+
+	trace!("Creating deployment service");
+
+	Api::<Service>::namespaced(ctx.client.clone(), namespace)
+		.patch(
+			&format!("service-{}", spec.deployment.id),
+			&PatchParams::apply(&format!("service-{}", spec.deployment.id)),
+			&Patch::Apply(Service {
+				metadata: ObjectMeta {
+					name: Some(format!("service-{}", spec.deployment.id)),
+					owner_references: Some(vec![owner_reference.clone()]),
+					..ObjectMeta::default()
+				},
+				spec: Some(ServiceSpec {
+					ports: Some(
+						spec.running_details
+							.ports
+							.keys()
+							.map(|port| ServicePort {
+								port: port.value() as i32,
+								target_port: Some(IntOrString::Int(port.value() as i32)),
+								name: Some(format!("port-{}", port)),
+								..ServicePort::default()
+							})
+							.collect::<Vec<_>>(),
+					),
+					selector: Some(service_selector),
+					cluster_ip: if spec.running_details.volumes.is_empty() {
+						None
+					} else {
+						Some("None".to_string())
+					},
+					..ServiceSpec::default()
+				}),
+				..Service::default()
+			}),
+		)
+		.await?;
 
 	// For a deployment has more than one replica, then only we can use
 	// pod-disruption-budget to move pods between nodes without any down time.
@@ -863,5 +1200,348 @@ async fn reconcile(
 		)
 		.await?;
 
+	// The controller watches the objects it owns, so the deployment is
+	// reconciled (and reported on) again as its pods become ready, and the
+	// rollout is reported once every pod runs the latest version
+	let rolled_out = status == DeploymentStatus::Running && replicas.is_rolled_out();
+	_ = ctx.report_sender.send(
+		StreamRunnerDataForWorkspaceClientMsg::DeploymentStatusReported {
+			id: spec.deployment.id,
+			status,
+			replicas,
+		},
+	);
+	if rolled_out {
+		_ = ctx
+			.report_sender
+			.send(StreamRunnerDataForWorkspaceClientMsg::DeploymentRolledOut {
+				id: spec.deployment.id,
+				image: DeploymentRolloutImage {
+					image_tag: spec.deployment.data.image_tag.clone(),
+					digest: spec.deployment.data.current_live_digest.clone(),
+				},
+			});
+	}
+
 	Ok(Action::requeue(Duration::from_secs(3600)))
 }
+
+/// Get the status of a deployment and the number of its replicas from the
+/// Kubernetes object that runs it. Until Kubernetes has observed the latest
+/// version of the object, its replica counts are for the previous version, so
+/// the deployment is considered to be deploying until then
+fn get_runtime_status(
+	metadata: &ObjectMeta,
+	observed_generation: Option<i64>,
+	desired: Option<i32>,
+	ready: Option<i32>,
+	updated: Option<i32>,
+	progress_deadline_exceeded: bool,
+) -> (DeploymentStatus, DeploymentReplicaCounts) {
+	let to_count = |count: i32| u16::try_from(count.max(0)).unwrap_or(u16::MAX);
+	let replicas = DeploymentReplicaCounts {
+		desired: to_count(desired.unwrap_or(1)),
+		ready: to_count(ready.unwrap_or_default()),
+		updated: to_count(updated.unwrap_or_default()),
+	};
+
+	let status = if progress_deadline_exceeded {
+		DeploymentStatus::Errored
+	} else if observed_generation == metadata.generation && replicas.is_rolled_out() {
+		DeploymentStatus::Running
+	} else {
+		DeploymentStatus::Deploying
+	};
+
+	(status, replicas)
+}
+
+/// Get the status of a deployment and the number of its replicas from the
+/// Kubernetes deployment that runs it
+fn get_kube_deployment_status(
+	deployment: &KubeDeployment,
+) -> (DeploymentStatus, DeploymentReplicaCounts) {
+	let status = deployment.status.clone().unwrap_or_default();
+	get_runtime_status(
+		&deployment.metadata,
+		status.observed_generation,
+		deployment.spec.as_ref().and_then(|spec| spec.replicas),
+		status.ready_replicas,
+		status.updated_replicas,
+		status
+			.conditions
+			.unwrap_or_default()
+			.iter()
+			.any(|condition| condition.reason.as_deref() == Some("ProgressDeadlineExceeded")),
+	)
+}
+
+/// Splits the replicas of a deployment between its stable pods and its canary.
+/// The canary gets its percentage of the replicas, rounded up so that there's
+/// always at least one canary pod, while leaving at least one stable pod.
+/// Returns the number of stable pods and the number of canary pods
+fn get_canary_replicas(replicas: u16, percentage: u8) -> (u16, u16) {
+	let canary = u16::try_from((u32::from(replicas) * u32::from(percentage)).div_ceil(100))
+		.unwrap_or(replicas);
+	let canary = canary.clamp(1, replicas.saturating_sub(1).max(1));
+
+	(replicas.saturating_sub(canary).max(1), canary)
+}
+
+/// How far along a blue/green rollout of a deployment is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlueGreenPhase {
+	/// The deployment serves all the traffic with its latest pod template, and
+	/// there's no green deployment running next to it
+	Settled,
+	/// The green deployment is being brought up with the latest pod template,
+	/// while the deployment keeps serving all the traffic with its previous one
+	Starting,
+	/// All the traffic has been switched over to the green deployment, while
+	/// the deployment itself is being updated to the latest pod template
+	Switched,
+}
+
+/// Get the phase that the blue/green rollout of a deployment is in, given the
+/// hash of the pod template that the Kubernetes deployment currently runs (if
+/// it exists), the hash of the latest pod template, whether the green
+/// deployment is ready with the latest pod template (if it exists), and
+/// whether the Kubernetes deployment itself is ready
+fn get_blue_green_phase(
+	rollout_strategy: DeploymentRolloutStrategy,
+	current_template_hash: Option<&str>,
+	template_hash: &str,
+	green_ready: Option<bool>,
+	deployment_ready: bool,
+) -> BlueGreenPhase {
+	if rollout_strategy != DeploymentRolloutStrategy::BlueGreen {
+		return BlueGreenPhase::Settled;
+	}
+
+	match (current_template_hash, green_ready) {
+		// A new deployment has no traffic to switch over
+		(None, _) => BlueGreenPhase::Settled,
+		// The traffic stays on the green deployment until the deployment itself
+		// has rolled out the latest pod template
+		(Some(current), Some(_)) if current == template_hash && !deployment_ready => {
+			BlueGreenPhase::Switched
+		}
+		(Some(current), _) if current == template_hash => BlueGreenPhase::Settled,
+		(Some(_), Some(true)) => BlueGreenPhase::Switched,
+		(Some(_), _) => BlueGreenPhase::Starting,
+	}
+}
+
+/// Get the strategy that a Kubernetes deployment is updated with for the
+/// rollout strategy of a deployment
+fn get_deployment_strategy(rollout_strategy: DeploymentRolloutStrategy) -> DeploymentStrategy {
+	let rolling_update = |max_surge, max_unavailable| DeploymentStrategy {
+		type_: Some("RollingUpdate".to_owned()),
+		rolling_update: Some(RollingUpdateDeployment {
+			max_surge: Some(max_surge),
+			max_unavailable: Some(max_unavailable),
+		}),
+	};
+
+	match rollout_strategy {
+		DeploymentRolloutStrategy::Recreate => DeploymentStrategy {
+			type_: Some("Recreate".to_owned()),
+			rolling_update: None,
+		},
+		DeploymentRolloutStrategy::Rolling {
+			max_surge,
+			max_unavailable,
+		} => rolling_update(
+			IntOrString::Int(max_surge.into()),
+			IntOrString::Int(max_unavailable.into()),
+		),
+		// New versions are brought up in a green deployment, which takes all the
+		// traffic while the deployment itself is updated. So this only applies
+		// to restarts, which bring up all the new pods before taking any of the
+		// old ones down
+		DeploymentRolloutStrategy::BlueGreen => {
+			rolling_update(IntOrString::String("100%".to_owned()), IntOrString::Int(0))
+		}
+		// The new image has already run on the canary by the time it's rolled
+		// out to the rest of the pods, so they're replaced one at a time
+		DeploymentRolloutStrategy::Canary { .. } => {
+			rolling_update(IntOrString::Int(1), IntOrString::Int(0))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn assert_restart_patch_bumps_the_pod_template() {
+		let patch = get_restart_patch(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+
+		assert_eq!(
+			patch["spec"]["template"]["metadata"]["annotations"][constants::RESTARTED_AT],
+			"1700000000"
+		);
+		assert_ne!(
+			get_restart_patch(UNIX_EPOCH + Duration::from_secs(1_700_000_001)),
+			patch
+		);
+	}
+
+	#[test]
+	fn assert_deployment_strategy_matches_rollout_strategy() {
+		let strategy = get_deployment_strategy(DeploymentRolloutStrategy::Recreate);
+		assert_eq!(strategy.type_.as_deref(), Some("Recreate"));
+		assert!(strategy.rolling_update.is_none());
+
+		let strategy = get_deployment_strategy(DeploymentRolloutStrategy::Rolling {
+			max_surge: 2,
+			max_unavailable: 1,
+		});
+		assert_eq!(strategy.type_.as_deref(), Some("RollingUpdate"));
+		assert_eq!(
+			strategy.rolling_update,
+			Some(RollingUpdateDeployment {
+				max_surge: Some(IntOrString::Int(2)),
+				max_unavailable: Some(IntOrString::Int(1)),
+			})
+		);
+
+		let strategy = get_deployment_strategy(DeploymentRolloutStrategy::BlueGreen);
+		assert_eq!(
+			strategy.rolling_update,
+			Some(RollingUpdateDeployment {
+				max_surge: Some(IntOrString::String("100%".to_owned())),
+				max_unavailable: Some(IntOrString::Int(0)),
+			})
+		);
+
+		let strategy =
+			get_deployment_strategy(DeploymentRolloutStrategy::Canary { percentage: 10 });
+		assert_eq!(
+			strategy.rolling_update,
+			Some(RollingUpdateDeployment {
+				max_surge: Some(IntOrString::Int(1)),
+				max_unavailable: Some(IntOrString::Int(0)),
+			})
+		);
+	}
+
+	#[test]
+	fn assert_runtime_status_waits_for_the_latest_generation() {
+		let metadata = ObjectMeta {
+			generation: Some(2),
+			..ObjectMeta::default()
+		};
+
+		assert_eq!(
+			get_runtime_status(&metadata, Some(2), Some(3), Some(3), Some(3), false),
+			(
+				DeploymentStatus::Running,
+				DeploymentReplicaCounts {
+					desired: 3,
+					ready: 3,
+					updated: 3,
+				}
+			)
+		);
+		// The replica counts are still those of the previous generation
+		assert_eq!(
+			get_runtime_status(&metadata, Some(1), Some(3), Some(3), Some(3), false).0,
+			DeploymentStatus::Deploying
+		);
+		assert_eq!(
+			get_runtime_status(&metadata, Some(2), Some(3), Some(3), Some(1), false).0,
+			DeploymentStatus::Deploying
+		);
+		assert_eq!(
+			get_runtime_status(&metadata, Some(2), Some(3), Some(1), Some(3), true).0,
+			DeploymentStatus::Errored
+		);
+	}
+
+	#[test]
+	fn assert_runtime_status_defaults_to_one_replica() {
+		assert_eq!(
+			get_runtime_status(&ObjectMeta::default(), None, None, None, None, false),
+			(
+				DeploymentStatus::Deploying,
+				DeploymentReplicaCounts {
+					desired: 1,
+					ready: 0,
+					updated: 0,
+				}
+			)
+		);
+	}
+
+	#[test]
+	fn assert_canary_replicas_are_a_share_of_the_total() {
+		assert_eq!(get_canary_replicas(10, 10), (9, 1));
+		assert_eq!(get_canary_replicas(10, 25), (7, 3));
+		assert_eq!(get_canary_replicas(4, 50), (2, 2));
+		assert_eq!(get_canary_replicas(4, 25), (3, 1));
+	}
+
+	#[test]
+	fn assert_canary_replicas_keep_one_pod_on_each_track() {
+		assert_eq!(get_canary_replicas(1, 10), (1, 1));
+		assert_eq!(get_canary_replicas(2, 99), (1, 1));
+		assert_eq!(get_canary_replicas(5, 1), (4, 1));
+		assert_eq!(get_canary_replicas(5, 100), (1, 4));
+	}
+
+	#[test]
+	fn assert_blue_green_phase_is_settled_for_other_strategies() {
+		assert_eq!(
+			get_blue_green_phase(
+				DeploymentRolloutStrategy::Recreate,
+				Some("old"),
+				"new",
+				Some(true),
+				false,
+			),
+			BlueGreenPhase::Settled
+		);
+	}
+
+	#[test]
+	fn assert_blue_green_phase_follows_the_rollout() {
+		let phase = |current, green_ready, deployment_ready| {
+			get_blue_green_phase(
+				DeploymentRolloutStrategy::BlueGreen,
+				current,
+				"new",
+				green_ready,
+				deployment_ready,
+			)
+		};
+
+		// A new deployment is created directly
+		assert_eq!(phase(None, None, false), BlueGreenPhase::Settled);
+		// The deployment is up to date
+		assert_eq!(phase(Some("new"), None, true), BlueGreenPhase::Settled);
+		assert_eq!(phase(Some("new"), None, false), BlueGreenPhase::Settled);
+		// The deployment was updated, so the green deployment is brought up
+		assert_eq!(phase(Some("old"), None, true), BlueGreenPhase::Starting);
+		assert_eq!(
+			phase(Some("old"), Some(false), true),
+			BlueGreenPhase::Starting
+		);
+		// The green deployment is ready, so the traffic is switched over
+		assert_eq!(
+			phase(Some("old"), Some(true), true),
+			BlueGreenPhase::Switched
+		);
+		// The deployment is being updated while the green one takes the traffic
+		assert_eq!(
+			phase(Some("new"), Some(true), false),
+			BlueGreenPhase::Switched
+		);
+		// The deployment has rolled out, so the green deployment is removed
+		assert_eq!(
+			phase(Some("new"), Some(true), true),
+			BlueGreenPhase::Settled
+		);
+	}
+}
@@ -0,0 +1,206 @@
+use std::{pin::pin, sync::Arc};
+
+use futures::StreamExt;
+use k8s_openapi::api::core::v1::Event;
+use kube::{
+	runtime::{watcher, WatchStreamExt},
+	Api,
+};
+use models::{
+	api::workspace::{
+		deployment::runtime::{DeploymentContainerEvent, DeploymentContainerEventKind},
+		runner::StreamRunnerDataForWorkspaceClientMsg,
+	},
+	prelude::*,
+};
+use time::OffsetDateTime;
+
+use crate::prelude::*;
+
+/// Watches the events of the pods in the cluster, and reports the ones about
+/// the pods of deployments to the Patr API. Events that happened before the
+/// controller started are skipped, since Kubernetes keeps events around for a
+/// while, and they would otherwise be reported every time the controller
+/// restarts.
+pub(super) async fn watch_pod_events(state: Arc<AppState>) {
+	let started = OffsetDateTime::now_utc();
+
+	let mut events = pin!(watcher(
+		Api::<Event>::all(state.client.clone()),
+		watcher::Config::default().fields("involvedObject.kind=Pod"),
+	)
+	.applied_objects()
+	.default_backoff());
+
+	while let Some(event) = events.next().await {
+		let event = match event {
+			Ok(event) => event,
+			Err(err) => {
+				warn!("Error watching pod events: {}", err);
+				continue;
+			}
+		};
+
+		let Some((deployment_id, event)) = get_container_event(event) else {
+			continue;
+		};
+		if event.timestamp < started {
+			continue;
+		}
+
+		_ = state.report_sender.send(
+			StreamRunnerDataForWorkspaceClientMsg::DeploymentContainerEventOccurred {
+				id: deployment_id,
+				event,
+			},
+		);
+	}
+}
+
+/// Convert an event of a pod into an event of the deployment that the pod
+/// belongs to. Events of pods that don't belong to a deployment, and events
+/// that aren't relevant to the deployment (like a pod being scheduled), are
+/// ignored
+fn get_container_event(event: Event) -> Option<(Uuid, DeploymentContainerEvent)> {
+	// The pods of a deployment are named after the Kubernetes deployment (or
+	// stateful set) that runs them, which is named after the deployment
+	let pod = event.involved_object.name?;
+	let deployment_id = pod
+		.strip_prefix("deployment-")
+		.or_else(|| pod.strip_prefix("sts-"))
+		.and_then(|name| name.get(..32))
+		.and_then(|id| Uuid::parse_str(id).ok())?;
+
+	let message = event.message.unwrap_or_default();
+	let kind = match event.reason.as_deref()? {
+		// A pull is also reported when the image is already present, which
+		// isn't worth recording
+		"Pulled" if message.starts_with("Successfully pulled") => {
+			DeploymentContainerEventKind::ImagePulled
+		}
+		"Failed" if message.contains("pull") => DeploymentContainerEventKind::ImagePullFailed,
+		"BackOff" if message.contains("pulling image") => {
+			DeploymentContainerEventKind::ImagePullFailed
+		}
+		"BackOff" => DeploymentContainerEventKind::Crashed,
+		"Started" => DeploymentContainerEventKind::Started,
+		"Killing" => DeploymentContainerEventKind::Stopped,
+		"Unhealthy" => DeploymentContainerEventKind::Unhealthy,
+		_ => return None,
+	};
+
+	let timestamp = event
+		.last_timestamp
+		.map(|time| time.0)
+		.or(event.event_time.map(|time| time.0))
+		.and_then(|time| time.timestamp_nanos_opt())
+		.and_then(|nanos| OffsetDateTime::from_unix_timestamp_nanos(nanos.into()).ok())
+		.unwrap_or_else(OffsetDateTime::now_utc);
+
+	Some((
+		deployment_id,
+		DeploymentContainerEvent {
+			container: pod,
+			kind,
+			message: Some(message).filter(|message| !message.is_empty()),
+			timestamp,
+		},
+	))
+}
+
+#[cfg(test)]
+mod tests {
+	use k8s_openapi::{
+		api::core::v1::ObjectReference,
+		apimachinery::pkg::apis::meta::v1::Time,
+		chrono::DateTime,
+	};
+
+	use super::*;
+
+	/// Creates an event of the given pod, with the given reason and message
+	fn get_event(pod: &str, reason: &str, message: &str) -> Event {
+		Event {
+			involved_object: ObjectReference {
+				kind: Some("Pod".to_string()),
+				name: Some(pod.to_string()),
+				..Default::default()
+			},
+			reason: Some(reason.to_string()),
+			message: Some(message.to_string()),
+			last_timestamp: DateTime::from_timestamp(1_700_000_000, 0).map(Time),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn assert_pod_events_belong_to_their_deployment() {
+		let deployment_id = Uuid::new_v4();
+		let pod = format!("deployment-{}-5d8f7c9b4-x2x7q", deployment_id);
+
+		assert_eq!(
+			get_container_event(get_event(&pod, "Started", "Started container")),
+			Some((
+				deployment_id,
+				DeploymentContainerEvent {
+					container: pod,
+					kind: DeploymentContainerEventKind::Started,
+					message: Some("Started container".to_string()),
+					timestamp: OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap(),
+				}
+			))
+		);
+
+		let pod = format!("sts-{}-0", deployment_id);
+		assert_eq!(
+			get_container_event(get_event(&pod, "Killing", "")).map(|(id, event)| (
+				id,
+				event.kind,
+				event.message
+			)),
+			Some((deployment_id, DeploymentContainerEventKind::Stopped, None))
+		);
+	}
+
+	#[test]
+	fn assert_other_pods_are_ignored() {
+		assert_eq!(
+			get_container_event(get_event("coredns-7db6d8ff4d-2x5lq", "Started", "")),
+			None
+		);
+	}
+
+	#[test]
+	fn assert_event_kinds_are_read_from_reasons() {
+		let pod = format!("deployment-{}-5d8f7c9b4-x2x7q", Uuid::new_v4());
+		let get_kind = |reason, message| {
+			get_container_event(get_event(&pod, reason, message)).map(|(_, event)| event.kind)
+		};
+
+		assert_eq!(
+			get_kind("Pulled", "Successfully pulled image \"nginx\""),
+			Some(DeploymentContainerEventKind::ImagePulled)
+		);
+		assert_eq!(
+			get_kind("Pulled", "Container image \"nginx\" already present"),
+			None
+		);
+		assert_eq!(
+			get_kind("Failed", "Failed to pull image \"nginx\""),
+			Some(DeploymentContainerEventKind::ImagePullFailed)
+		);
+		assert_eq!(
+			get_kind("BackOff", "Back-off pulling image \"nginx\""),
+			Some(DeploymentContainerEventKind::ImagePullFailed)
+		);
+		assert_eq!(
+			get_kind("BackOff", "Back-off restarting failed container"),
+			Some(DeploymentContainerEventKind::Crashed)
+		);
+		assert_eq!(
+			get_kind("Unhealthy", "Readiness probe failed"),
+			Some(DeploymentContainerEventKind::Unhealthy)
+		);
+		assert_eq!(get_kind("Scheduled", "Successfully assigned"), None);
+	}
+}
@@ -0,0 +1,253 @@
+use std::sync::Arc;
+
+use futures::SinkExt;
+use k8s_openapi::{api::core::v1::Pod, apimachinery::pkg::apis::meta::v1::Status};
+use kube::{
+	api::{AttachParams, ListParams, TerminalSize},
+	Api,
+};
+use models::{api::workspace::runner::StreamRunnerDataForWorkspaceClientMsg, prelude::*};
+use tokio::{
+	io::{AsyncReadExt, AsyncWriteExt},
+	sync::mpsc::{self, UnboundedReceiver},
+	task,
+};
+
+use crate::{constants, prelude::*};
+
+/// Input to a terminal session, sent by the user that opened it
+#[derive(Debug, Clone)]
+pub enum ExecInput {
+	/// The user typed into the terminal
+	Data(Vec<u8>),
+	/// The user resized the terminal
+	Resize {
+		/// The number of rows in the terminal
+		rows: u16,
+		/// The number of columns in the terminal
+		cols: u16,
+	},
+}
+
+/// Starts a terminal session in one of the running pods of a deployment. The
+/// session runs in the background, and whatever the command writes is reported
+/// to the Patr API until the command exits or the session is closed.
+pub(super) fn start_session(
+	state: Arc<AppState>,
+	deployment_id: Uuid,
+	session_id: Uuid,
+	command: Vec<String>,
+) {
+	info!(
+		"Starting terminal session `{}` in deployment `{}`",
+		session_id, deployment_id
+	);
+
+	let (input_sender, input_receiver) = mpsc::unbounded_channel();
+	state
+		.exec_sessions
+		.lock()
+		.expect("exec sessions lock poisoned")
+		.insert(session_id, input_sender);
+
+	task::spawn(async move {
+		let message =
+			match run_session(&state, deployment_id, session_id, command, input_receiver).await {
+				Ok(exit_code) => StreamRunnerDataForWorkspaceClientMsg::DeploymentExecExited {
+					id: deployment_id,
+					session_id,
+					exit_code,
+				},
+				Err(err) => {
+					error!("Terminal session `{}` failed: {}", session_id, err);
+					StreamRunnerDataForWorkspaceClientMsg::DeploymentExecFailed {
+						id: deployment_id,
+						session_id,
+						error: err.to_string(),
+					}
+				}
+			};
+		_ = state.report_sender.send(message);
+	});
+}
+
+/// Sends the input of the user to a terminal session. Input to sessions that
+/// have already ended is dropped.
+pub(super) fn send_input(state: &AppState, session_id: Uuid, input: ExecInput) {
+	let sessions = state
+		.exec_sessions
+		.lock()
+		.expect("exec sessions lock poisoned");
+	if let Some(session) = sessions.get(&session_id) {
+		_ = session.send(input);
+	}
+}
+
+/// Closes a terminal session that the user has left. The command is stopped if
+/// it is still running.
+pub(super) fn close_session(state: &AppState, session_id: Uuid) {
+	info!("Closing terminal session `{}`", session_id);
+	state
+		.exec_sessions
+		.lock()
+		.expect("exec sessions lock poisoned")
+		.remove(&session_id);
+}
+
+/// Runs the command of a terminal session in a running pod of the deployment,
+/// and relays the session until the command exits or the session is closed.
+/// Returns the exit code of the command, if it could be found.
+async fn run_session(
+	state: &AppState,
+	deployment_id: Uuid,
+	session_id: Uuid,
+	command: Vec<String>,
+	mut input: UnboundedReceiver<ExecInput>,
+) -> Result<Option<i64>, AppError> {
+	let pods = Api::<Pod>::namespaced(state.client.clone(), &state.workspace_id.to_string());
+
+	let pod = pods
+		.list(&ListParams::default().labels(&format!(
+			"{}={}",
+			constants::DEPLOYMENT_ID,
+			deployment_id
+		)))
+		.await?
+		.items
+		.into_iter()
+		.find(|pod| {
+			pod.status
+				.as_ref()
+				.and_then(|status| status.phase.as_deref()) ==
+				Some("Running")
+		})
+		.and_then(|pod| pod.metadata.name)
+		.ok_or_else(|| {
+			AppError::InternalError("The deployment has no running instances".to_string())
+		})?;
+
+	trace!("Running terminal session `{}` in pod `{}`", session_id, pod);
+	let mut process = pods
+		.exec(&pod, command, &AttachParams::interactive_tty())
+		.await?;
+
+	let (Some(mut stdin), Some(mut stdout), Some(mut terminal_size), Some(status)) = (
+		process.stdin(),
+		process.stdout(),
+		process.terminal_size(),
+		process.take_status(),
+	) else {
+		return Err(AppError::InternalError(
+			"Failed to attach to the terminal of the command".to_string(),
+		));
+	};
+
+	let mut buffer = vec![0; 4096];
+	loop {
+		tokio::select! {
+			read = stdout.read(&mut buffer) => {
+				let read = read.map_err(|err| AppError::InternalError(err.to_string()))?;
+				// The output is closed once the command exits
+				if read == 0 {
+					break;
+				}
+				_ = state.report_sender.send(
+					StreamRunnerDataForWorkspaceClientMsg::DeploymentExecOutputSent {
+						id: deployment_id,
+						session_id,
+						data: Base64String::from(&buffer[..read]),
+					},
+				);
+			}
+			input = input.recv() => match input {
+				Some(ExecInput::Data(data)) => {
+					stdin
+						.write_all(&data)
+						.await
+						.map_err(|err| AppError::InternalError(err.to_string()))?;
+				}
+				Some(ExecInput::Resize { rows, cols }) => {
+					_ = terminal_size
+						.send(TerminalSize {
+							width: cols,
+							height: rows,
+						})
+						.await;
+				}
+				// The user has closed the session
+				None => {
+					process.abort();
+					return Ok(None);
+				}
+			}
+		}
+	}
+
+	Ok(get_exit_code(status.await))
+}
+
+/// Gets the exit code of a command from the status that Kubernetes reports once
+/// the command exits. A command that failed has its exit code as one of the
+/// causes of the failure.
+fn get_exit_code(status: Option<Status>) -> Option<i64> {
+	let status = status?;
+	if status.status.as_deref() == Some("Success") {
+		return Some(0);
+	}
+
+	status
+		.details?
+		.causes?
+		.into_iter()
+		.find(|cause| cause.reason.as_deref() == Some("ExitCode"))?
+		.message?
+		.parse()
+		.ok()
+}
+
+#[cfg(test)]
+mod tests {
+	use k8s_openapi::apimachinery::pkg::apis::meta::v1::{StatusCause, StatusDetails};
+
+	use super::*;
+
+	#[test]
+	fn assert_successful_command_exits_with_zero() {
+		let status = Status {
+			status: Some("Success".to_string()),
+			..Default::default()
+		};
+
+		assert_eq!(get_exit_code(Some(status)), Some(0));
+	}
+
+	#[test]
+	fn assert_failed_command_exits_with_its_code() {
+		let status = Status {
+			status: Some("Failure".to_string()),
+			details: Some(StatusDetails {
+				causes: Some(vec![StatusCause {
+					reason: Some("ExitCode".to_string()),
+					message: Some("127".to_string()),
+					..Default::default()
+				}]),
+				..Default::default()
+			}),
+			..Default::default()
+		};
+
+		assert_eq!(get_exit_code(Some(status)), Some(127));
+	}
+
+	#[test]
+	fn assert_unknown_exit_code_is_none() {
+		let status = Status {
+			status: Some("Failure".to_string()),
+			reason: Some("InternalError".to_string()),
+			..Default::default()
+		};
+
+		assert_eq!(get_exit_code(Some(status)), None);
+		assert_eq!(get_exit_code(None), None);
+	}
+}
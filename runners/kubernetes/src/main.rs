@@ -6,22 +6,26 @@
 //! respective cluster. The controller will periodically check with the API and
 //! make sure that the cluster's state is up to date with the API's state.
 
-use std::{marker::PhantomData, str::FromStr, sync::Arc};
+use std::{str::FromStr, sync::Arc};
 
 use ::models::{
 	api::workspace::runner::*,
-	prelude::UserAgent,
+	prelude::{ErrorType, UserAgent},
 	utils::{BearerToken, WebSocketUpgrade},
 	ApiRequest,
 };
 use app::AppState;
 use futures::StreamExt;
 use prelude::*;
-use tokio::{sync::broadcast, time::Duration};
+use tokio::{
+	sync::{broadcast, mpsc},
+	task,
+	time::Duration,
+};
+use tokio_stream::wrappers::UnboundedReceiverStream;
 
 /// A prelude that re-exports commonly used items.
 pub mod prelude {
-	use models::prelude::*;
 	pub use tracing::{debug, error, info, instrument, trace, warn};
 
 	pub use crate::{
@@ -34,8 +38,6 @@ pub mod prelude {
 /// All app state that is shared across the entire application. Used to share
 /// ApiTokens, backend connections, etc.
 mod app;
-/// The client used to communicate with the Patr API.
-mod client;
 /// All the constants used by the controller.
 mod constants;
 /// All functions and business logic to run a database controller and keep it
@@ -44,37 +46,141 @@ mod database;
 /// All functions and business login to run a deployment controller and keep it
 /// in sync with the Patr API data.
 mod deployment;
+/// Watching the events of the pods of deployments, and reporting them to the
+/// Patr API.
+mod event;
+/// Interactive terminal sessions in the pods of deployments, relayed between
+/// the user and the pod through the Patr API.
+mod exec;
 /// All models used by the controller, including CRDs, etc.
 mod models;
+/// Connections to the ports of the pods of deployments, relayed between the
+/// user and the pod through the Patr API.
+mod port_forward;
 /// Utility functions used by the controller.
 mod utils;
 
 #[tokio::main]
 async fn main() {
-	let state = Arc::new(AppState::try_default().await);
+	let (report_sender, report_receiver) = mpsc::unbounded_channel();
+	let state = Arc::new(AppState::try_default(report_sender).await);
 
 	let (patr_update_sender, patr_update_receiver) = broadcast::channel::<()>(100);
 
-	client::stream_request(
-		ApiRequest::<StreamRunnerDataForWorkspaceRequest>::builder()
-			.path(StreamRunnerDataForWorkspacePath {
-				workspace_id: state.workspace_id,
-				runner_id: state.region_id,
-			})
-			.query(())
-			.headers(StreamRunnerDataForWorkspaceRequestHeaders {
-				authorization: BearerToken::from_str(state.patr_token.as_str()).unwrap(),
-				user_agent: UserAgent::from_static("deployment-controller"),
-			})
-			.body(WebSocketUpgrade::new())
-			.build(),
-	)
-	.await
-	.unwrap()
-	.for_each(|deployment| async {
-		_ = patr_update_sender.send(());
-	})
-	.await;
+	let (reporter, messages) = state
+		.patr_client
+		.stream_request(
+			ApiRequest::<StreamRunnerDataForWorkspaceRequest>::builder()
+				.path(StreamRunnerDataForWorkspacePath {
+					workspace_id: state.workspace_id,
+					runner_id: state.region_id,
+				})
+				.query(())
+				.headers(StreamRunnerDataForWorkspaceRequestHeaders {
+					authorization: BearerToken::from_str(state.patr_token.as_str()).unwrap(),
+					user_agent: UserAgent::from_static("deployment-controller"),
+				})
+				.body(WebSocketUpgrade::default())
+				.build(),
+		)
+		.await
+		.unwrap()
+		.split();
+
+	// The reports are forwarded to the API and the messages from the API are
+	// handled in the background, so that the controller can run alongside them
+	task::spawn(async move {
+		if let Err(err) = UnboundedReceiverStream::new(report_receiver)
+			.map(Ok::<_, ErrorType>)
+			.forward(reporter)
+			.await
+		{
+			error!("Failed to report to the Patr API: {:?}", err);
+		}
+	});
+	task::spawn({
+		let state = state.clone();
+		messages.for_each(move |message| {
+			let state = state.clone();
+			let patr_update_sender = patr_update_sender.clone();
+			async move {
+				match message {
+					// A restart doesn't change the deployment, so there's
+					// nothing to reconcile
+					Ok(StreamRunnerDataForWorkspaceServerMsg::DeploymentRestarted { id }) => {
+						if let Err(err) = deployment::restart_deployment(&state, id).await {
+							error!("Failed to restart deployment `{}`: {}", id, err);
+						}
+					}
+					// Terminal sessions don't change the deployment either
+					Ok(StreamRunnerDataForWorkspaceServerMsg::DeploymentExecStarted {
+						id,
+						session_id,
+						command,
+					}) => {
+						exec::start_session(state, id, session_id, command);
+					}
+					Ok(StreamRunnerDataForWorkspaceServerMsg::DeploymentExecInputReceived {
+						id: _,
+						session_id,
+						data,
+					}) => {
+						exec::send_input(
+							&state,
+							session_id,
+							exec::ExecInput::Data(data.into_vec()),
+						);
+					}
+					Ok(StreamRunnerDataForWorkspaceServerMsg::DeploymentExecResized {
+						id: _,
+						session_id,
+						rows,
+						cols,
+					}) => {
+						exec::send_input(
+							&state,
+							session_id,
+							exec::ExecInput::Resize { rows, cols },
+						);
+					}
+					Ok(StreamRunnerDataForWorkspaceServerMsg::DeploymentExecClosed {
+						id: _,
+						session_id,
+					}) => {
+						exec::close_session(&state, session_id);
+					}
+					// Neither do forwarded ports
+					Ok(StreamRunnerDataForWorkspaceServerMsg::DeploymentPortForwardStarted {
+						id,
+						session_id,
+						port,
+					}) => {
+						port_forward::start_session(state, id, session_id, port);
+					}
+					Ok(
+						StreamRunnerDataForWorkspaceServerMsg::DeploymentPortForwardDataReceived {
+							id: _,
+							session_id,
+							data,
+						},
+					) => {
+						port_forward::send_data(&state, session_id, data.into_vec());
+					}
+					Ok(StreamRunnerDataForWorkspaceServerMsg::DeploymentPortForwardClosed {
+						id: _,
+						session_id,
+					}) => {
+						port_forward::close_session(&state, session_id);
+					}
+					_ => {
+						_ = patr_update_sender.send(());
+					}
+				}
+			}
+		})
+	});
+
+	task::spawn(event::watch_pod_events(state.clone()));
 
 	let (reconcile_all_deployments, deployment_controller_task) =
 		deployment::start_controller(state.client.clone(), state.clone(), patr_update_receiver);
@@ -99,6 +205,7 @@ async fn main() {
 	}
 }
 
+/// Waits for the controller to be asked to exit (using Ctrl+C)
 async fn exit_signal() {
 	tokio::signal::ctrl_c()
 		.await
@@ -1,6 +1,10 @@
 use kube::CustomResource;
 use models::{
-	api::workspace::deployment::{Deployment, DeploymentRunningDetails},
+	api::workspace::deployment::{
+		rollout::DeploymentRolloutImage,
+		Deployment,
+		DeploymentRunningDetails,
+	},
 	prelude::WithId,
 };
 use schemars::JsonSchema;
@@ -26,4 +30,9 @@ pub struct PatrDeploymentSpec {
 	/// documentation of [`DeploymentRunningDetails`].
 	#[schemars(flatten)]
 	pub running_details: DeploymentRunningDetails,
+	/// The image that the deployment was running before the current rollout,
+	/// if the new image is a canary that is awaiting promotion. The deployment
+	/// keeps running this image, and the new image only runs on the canary.
+	#[serde(default)]
+	pub stable_image: Option<DeploymentRolloutImage>,
 }
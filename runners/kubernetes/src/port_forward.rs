@@ -0,0 +1,152 @@
+use std::sync::Arc;
+
+use k8s_openapi::api::core::v1::Pod;
+use kube::{api::ListParams, Api};
+use models::{api::workspace::runner::StreamRunnerDataForWorkspaceClientMsg, prelude::*};
+use tokio::{
+	io::{AsyncReadExt, AsyncWriteExt},
+	sync::mpsc::{self, UnboundedReceiver},
+	task,
+};
+
+use crate::{constants, prelude::*};
+
+/// Connects to a port of one of the running pods of a deployment. The
+/// connection is relayed in the background, and whatever the pod sends is
+/// reported to the Patr API until either side closes the connection.
+pub(super) fn start_session(
+	state: Arc<AppState>,
+	deployment_id: Uuid,
+	session_id: Uuid,
+	port: u16,
+) {
+	info!(
+		"Forwarding port {} of deployment `{}` for session `{}`",
+		port, deployment_id, session_id
+	);
+
+	let (data_sender, data_receiver) = mpsc::unbounded_channel();
+	state
+		.port_forward_sessions
+		.lock()
+		.expect("port forward sessions lock poisoned")
+		.insert(session_id, data_sender);
+
+	task::spawn(async move {
+		let error = run_session(&state, deployment_id, session_id, port, data_receiver)
+			.await
+			.inspect_err(|err| error!("Forwarded port session `{}` failed: {}", session_id, err))
+			.err()
+			.map(|err| err.to_string());
+		_ = state.report_sender.send(
+			StreamRunnerDataForWorkspaceClientMsg::DeploymentPortForwardEnded {
+				id: deployment_id,
+				session_id,
+				error,
+			},
+		);
+	});
+}
+
+/// Sends the data of the user over a forwarded connection. Data for
+/// connections that have already been closed is dropped.
+pub(super) fn send_data(state: &AppState, session_id: Uuid, data: Vec<u8>) {
+	let sessions = state
+		.port_forward_sessions
+		.lock()
+		.expect("port forward sessions lock poisoned");
+	if let Some(session) = sessions.get(&session_id) {
+		_ = session.send(data);
+	}
+}
+
+/// Closes a forwarded connection that the user has closed. The connection to
+/// the pod is closed if it is still open.
+pub(super) fn close_session(state: &AppState, session_id: Uuid) {
+	info!("Closing forwarded port session `{}`", session_id);
+	state
+		.port_forward_sessions
+		.lock()
+		.expect("port forward sessions lock poisoned")
+		.remove(&session_id);
+}
+
+/// Connects to the port of a running pod of the deployment, and relays the
+/// connection until either side closes it.
+async fn run_session(
+	state: &AppState,
+	deployment_id: Uuid,
+	session_id: Uuid,
+	port: u16,
+	mut input: UnboundedReceiver<Vec<u8>>,
+) -> Result<(), AppError> {
+	let pods = Api::<Pod>::namespaced(state.client.clone(), &state.workspace_id.to_string());
+
+	let pod = pods
+		.list(&ListParams::default().labels(&format!(
+			"{}={}",
+			constants::DEPLOYMENT_ID,
+			deployment_id
+		)))
+		.await?
+		.items
+		.into_iter()
+		.find(|pod| {
+			pod.status
+				.as_ref()
+				.and_then(|status| status.phase.as_deref()) ==
+				Some("Running")
+		})
+		.and_then(|pod| pod.metadata.name)
+		.ok_or_else(|| {
+			AppError::InternalError("The deployment has no running instances".to_string())
+		})?;
+
+	trace!(
+		"Forwarding port {} of pod `{}` for session `{}`",
+		port,
+		pod,
+		session_id
+	);
+	let mut forwarder = pods.portforward(&pod, &[port]).await?;
+	let Some(stream) = forwarder.take_stream(port) else {
+		return Err(AppError::InternalError(format!(
+			"Failed to connect to port {}",
+			port
+		)));
+	};
+	let (mut reader, mut writer) = tokio::io::split(stream);
+
+	let mut buffer = vec![0; 4096];
+	loop {
+		tokio::select! {
+			read = reader.read(&mut buffer) => {
+				let read = read.map_err(|err| AppError::InternalError(err.to_string()))?;
+				// The pod has closed the connection
+				if read == 0 {
+					break;
+				}
+				_ = state.report_sender.send(
+					StreamRunnerDataForWorkspaceClientMsg::DeploymentPortForwardDataSent {
+						id: deployment_id,
+						session_id,
+						data: Base64String::from(&buffer[..read]),
+					},
+				);
+			}
+			data = input.recv() => match data {
+				Some(data) => {
+					writer
+						.write_all(&data)
+						.await
+						.map_err(|err| AppError::InternalError(err.to_string()))?;
+				}
+				// The user has closed their connection
+				None => break,
+			},
+		}
+	}
+
+	forwarder.abort();
+	Ok(())
+}
@@ -1,8 +1,14 @@
 use std::{fmt::Debug, future::Future};
 
 use either::Either;
-use kube::{api::DeleteParams, core::Status, error::ErrorResponse, Api, Error};
-use serde::de::DeserializeOwned;
+use kube::{
+	api::{DeleteParams, Patch, PatchParams},
+	core::Status,
+	error::ErrorResponse,
+	Api,
+	Error,
+};
+use serde::{de::DeserializeOwned, Serialize};
 
 /// Constants used in the Controller
 pub mod constants {
@@ -14,15 +20,28 @@ pub mod constants {
 	};
 }
 
+/// Extension methods for the Kubernetes [`Api`], that treat an object that
+/// doesn't exist as `None` instead of an error
 pub trait KubeApiExt<K>
 where
 	K: Clone + DeserializeOwned + Debug,
 {
+	/// Delete an object, returning `None` if it doesn't exist
 	fn delete_opt(
 		&self,
 		name: &str,
 		dp: &DeleteParams,
 	) -> impl Future<Output = Result<Option<Either<K, Status>>, Error>>;
+
+	/// Patch an object, returning `None` if it doesn't exist
+	fn patch_opt<P>(
+		&self,
+		name: &str,
+		pp: &PatchParams,
+		patch: &Patch<P>,
+	) -> impl Future<Output = Result<Option<K>, Error>>
+	where
+		P: Serialize + Debug;
 }
 
 impl<K> KubeApiExt<K> for Api<K>
@@ -40,4 +59,20 @@ where
 			Err(err) => Err(err),
 		}
 	}
+
+	async fn patch_opt<P>(
+		&self,
+		name: &str,
+		pp: &PatchParams,
+		patch: &Patch<P>,
+	) -> Result<Option<K>, Error>
+	where
+		P: Serialize + Debug,
+	{
+		match self.patch(name, pp, patch).await {
+			Ok(obj) => Ok(Some(obj)),
+			Err(Error::Api(ErrorResponse { code: 404, .. })) => Ok(None),
+			Err(err) => Err(err),
+		}
+	}
 }